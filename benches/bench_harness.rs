@@ -0,0 +1,12 @@
+use criterion::{criterion_group, criterion_main};
+
+use ribble_whisper::bench;
+
+criterion_group!(
+    benches,
+    bench::ring_buffer_throughput,
+    bench::dedup_blend,
+    bench::vad_per_second,
+    bench::snapshot_construction
+);
+criterion_main!(benches);
@@ -1,4 +1,6 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::thread::scope;
 
@@ -11,8 +13,8 @@ use ribble_whisper::transcriber::realtime_transcriber::{
     RealtimeTranscriber, RealtimeTranscriberBuilder, RealtimeTranscriberHandle,
 };
 use ribble_whisper::transcriber::vad::{
-    Silero, SileroBuilder, SileroSampleRate,
-    WebRtcBuilder, DEFAULT_VOICE_PROPORTION_THRESHOLD, REAL_TIME_VOICE_PROBABILITY_THRESHOLD, VAD,
+    Silero, SileroBuilder, SileroSampleRate, WebRtcBuilder, DEFAULT_VOICE_PROPORTION_THRESHOLD,
+    REAL_TIME_VOICE_PROBABILITY_THRESHOLD, VAD,
 };
 use ribble_whisper::transcriber::WhisperOutput;
 use ribble_whisper::utils;
@@ -32,6 +34,52 @@ use ribble_whisper::whisper::model::{DefaultModelBank, DefaultModelType, ModelBa
 // NOTE: There are some major problems with the Earshot implementation at the crate level and is
 // no longer able to build/prime for this test. It has been removed for the interim.
 
+// Allocations are counted per thread, so that only the transcriber's own allocations are measured.
+// Whisper.cpp allocates through malloc directly and is not counted.
+// The realtime loop keeps its buffers and parameters in a reusable scratch struct, so it should
+// only allocate for the text of new segments and the snapshots it sends: expect a small, constant
+// number of allocations per inference window. Each benchmark prints its measured average.
+struct CountingAllocator;
+
+thread_local! {
+    static THREAD_ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // Threads that are being torn down can no longer count.
+        let _ = THREAD_ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = THREAD_ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// Totals across benchmark iterations.
+static TRANSCRIBER_ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static INFERENCE_WINDOWS: AtomicU64 = AtomicU64::new(0);
+
+// Prints (and resets) the average number of allocations per inference window.
+fn report_allocations(name: &str) {
+    let allocations = TRANSCRIBER_ALLOCATIONS.swap(0, Ordering::Relaxed);
+    // Each inference window sends (at most) one snapshot.
+    let windows = INFERENCE_WINDOWS.swap(0, Ordering::Relaxed).max(1);
+    eprintln!(
+        "{name}: {:.1} allocations per inference window ({allocations} over {windows} windows)",
+        allocations as f64 / windows as f64
+    );
+}
+
 pub fn realtime_vad_benchmark(c: &mut Criterion) {
     // To prevent excess memory allocations from clouding the benchmark, pre-allocate as many
     // resources as feasible. Pass and share where appropriate.
@@ -125,6 +173,7 @@ pub fn realtime_vad_benchmark(c: &mut Criterion) {
             )
         });
     });
+    report_allocations("Silero realtime");
 
     c.bench_function("Webrtc_realtime", |b| {
         b.iter(|| {
@@ -137,6 +186,7 @@ pub fn realtime_vad_benchmark(c: &mut Criterion) {
             )
         });
     });
+    report_allocations("Webrtc_realtime");
 
     // c.bench_function("Earshot", |b| {
     //     b.iter(|| {
@@ -179,9 +229,12 @@ pub fn realtime_bencher<V: VAD<f32> + Send + Sync>(
             }
         });
         let _t_thread = s.spawn(move || {
+            let before = THREAD_ALLOCATIONS.with(Cell::get);
             transcriber
                 .run_stream(t_thread_run_transcription, Default::default())
                 .expect("Transcription should not not fail under these conditions.");
+            let allocations = THREAD_ALLOCATIONS.with(Cell::get) - before;
+            TRANSCRIBER_ALLOCATIONS.fetch_add(allocations, Ordering::Relaxed);
         });
         // Simple thread to just drain the audio - this will sleep until it receives output from
         // the transcriber
@@ -196,9 +249,14 @@ pub fn realtime_bencher<V: VAD<f32> + Send + Sync>(
             while d_thread_run_transcription.load(Ordering::Acquire) {
                 match receiver.recv() {
                     Ok(out) => {
+                        if !matches!(out, WhisperOutput::ControlPhrase(_)) {
+                            INFERENCE_WINDOWS.fetch_add(1, Ordering::Relaxed);
+                        }
                         let message = match out {
                             WhisperOutput::TranscriptionSnapshot(snapshot) => snapshot.to_string(),
-                            WhisperOutput::ControlPhrase(_) => "".to_string(),
+                            _ => {
+                                "".to_string()
+                            }
                         };
                         let current_len = message.len();
                         if current_len > offline_output_length - epsilon {
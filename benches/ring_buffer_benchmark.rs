@@ -0,0 +1,75 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use ribble_whisper::audio::audio_ring_buffer::AudioRingBuffer;
+
+// Counts allocations so that the benchmark can check the push path never touches the allocator.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// A typical audio callback: 10ms of 16 kHz audio. 160 does not divide the buffer capacity
+// evenly, so pushes regularly wrap around the end of the buffer.
+const CHUNK_LEN: usize = 160;
+const CHUNK_LEN_WRAPPING: usize = 173;
+
+fn assert_allocation_free(buffer: &AudioRingBuffer<f32>, chunk: &[f32]) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..10_000 {
+        buffer.push_audio(black_box(chunk));
+    }
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    assert_eq!(
+        allocations, 0,
+        "push_audio allocated {allocations} times over 10,000 pushes."
+    );
+}
+
+pub fn ring_buffer_benchmark(c: &mut Criterion) {
+    let buffer = AudioRingBuffer::<f32>::default();
+    let chunk = vec![0.5f32; CHUNK_LEN];
+    let wrapping_chunk = vec![0.5f32; CHUNK_LEN_WRAPPING];
+    // Larger than the buffer: only the tail is written.
+    let oversized_chunk = vec![0.5f32; buffer.get_capacity() * 2];
+
+    assert_allocation_free(&buffer, &chunk);
+    assert_allocation_free(&buffer, &wrapping_chunk);
+    assert_allocation_free(&buffer, &oversized_chunk);
+
+    let mut group = c.benchmark_group("AudioRingBuffer::push_audio");
+    group.bench_function("push: 10ms chunk", |b| {
+        b.iter(|| buffer.push_audio(black_box(&chunk)))
+    });
+    group.bench_function("push: wrapping chunk", |b| {
+        b.iter(|| buffer.push_audio(black_box(&wrapping_chunk)))
+    });
+    group.bench_function("push: chunk larger than the buffer", |b| {
+        b.iter(|| buffer.push_audio(black_box(&oversized_chunk)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, ring_buffer_benchmark);
+criterion_main!(benches);
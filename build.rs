@@ -0,0 +1,7 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    // Generates the gRPC service, (see: src/grpc). This requires protoc.
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/transcription.proto")
+        .expect("Failed to compile proto/transcription.proto");
+}
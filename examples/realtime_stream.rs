@@ -12,6 +12,8 @@ use ribble_whisper::audio::audio_backend::default_backend;
 use ribble_whisper::audio::audio_backend::AudioBackend;
 use ribble_whisper::audio::audio_backend::CaptureSpec;
 use ribble_whisper::audio::audio_ring_buffer::AudioRingBuffer;
+use ribble_whisper::audio::buffer_pool::DEFAULT_POOL_CAPACITY;
+use ribble_whisper::audio::dsp;
 use ribble_whisper::audio::microphone::MicCapture;
 use ribble_whisper::audio::recorder::ArcChannelSink;
 use ribble_whisper::audio::{AudioChannelConfiguration, WhisperAudioSample};
@@ -19,17 +21,19 @@ use ribble_whisper::audio::{AudioChannelConfiguration, WhisperAudioSample};
 use ribble_whisper::downloader::downloaders::sync_download_request;
 #[cfg(feature = "downloader")]
 use ribble_whisper::downloader::SyncDownload;
+use ribble_whisper::postprocess::diff::TranscriptDiffer;
 use ribble_whisper::transcriber::offline_transcriber::OfflineTranscriberBuilder;
 use ribble_whisper::transcriber::realtime_transcriber::RealtimeTranscriberBuilder;
 use ribble_whisper::transcriber::vad::Silero;
-use ribble_whisper::transcriber::{redirect_whisper_logging_to_hooks, TranscriptionSnapshot};
+use ribble_whisper::transcriber::{
+    redirect_whisper_logging_to_hooks, SnapshotAssembler, TranscriptionSnapshot,
+};
 use ribble_whisper::transcriber::{WhisperCallbacks, WhisperControlPhrase, WhisperOutput};
 use ribble_whisper::utils;
 use ribble_whisper::utils::callback::{Nop, RibbleWhisperCallback, StaticRibbleWhisperCallback};
-use ribble_whisper::whisper::configs::{RealtimeBufferingStrategy, WhisperRealtimeConfigs};
+use ribble_whisper::whisper::configs::{WhisperRealtimeConfigs, WindowLengths};
 use ribble_whisper::whisper::model;
 use ribble_whisper::whisper::model::{DefaultModelBank, ModelBank, ModelId};
-use strsim::jaro;
 
 fn main() {
     let mut args = env::args().skip(1);
@@ -68,11 +72,10 @@ fn main() {
         .with_use_flash_attention(true);
 
     if let Some(len_ms) = buffer_ms {
-        // 1000ms is effectively the same strategy as continuous.
-        let buffer_strategy_ms = len_ms.max(1000);
-        configs = configs.with_buffering_strategy(RealtimeBufferingStrategy::Buffered {
-            buffer_ms: buffer_strategy_ms,
-        });
+        // Derive the voice-detection and inference windows to match the buffer, so that long
+        // buffers still fit inside an inference window.
+        // 1000ms or less is effectively the same strategy as continuous.
+        configs = configs.with_window_lengths(WindowLengths::from_latency_target(len_ms));
     }
 
     let audio_ring_buffer = AudioRingBuffer::<f32>::default();
@@ -121,7 +124,7 @@ fn main() {
         }
     }
 
-    let run_jaro = if recording_audio {
+    let run_comparison = if recording_audio {
         stdin_buffer.clear();
         print!(
             "Would you like to compare transcription similarity between real-time and offline? y/n: "
@@ -156,7 +159,8 @@ fn main() {
 
     // Set up the Audio Backend.
     let spec = CaptureSpec::default();
-    let sink = ArcChannelSink::new(audio_sender);
+    // Audio chunks are dropped once they've been written to the ring buffer, so they can be pooled.
+    let sink = ArcChannelSink::new(audio_sender).with_buffer_pool(DEFAULT_POOL_CAPACITY);
     let (_ctx, backend) =
         default_backend().expect("Audio backend expected to build without issue.");
 
@@ -181,9 +185,9 @@ fn main() {
         let _audio_thread = s.spawn(move || {
             // Just hog the mutex because there's only one writer.
             let mut offline_buffer = t_offline_audio_buffer.lock();
+            let mut highest_peak = 1.0;
+            let mut gain_audio = Vec::with_capacity(gain_buffer_size);
             while a_thread_run_transcription.load(Ordering::Acquire) {
-                let mut highest_peak = 1.0;
-                let mut gain_audio = Vec::with_capacity(gain_buffer_size);
                 match audio_receiver.recv() {
                     Ok(audio_data) => {
                         // If the transcriber is not yet loaded, just consume the audio
@@ -196,16 +200,11 @@ fn main() {
                         //
                         // Add a small amount of gain and normalize to a rolling max peak
                         gain_audio.clear();
-                        gain_audio.extend(audio_data.iter().copied().map(|f| f * audio_gain));
-
-                        // Update the highest peak
-                        highest_peak = gain_audio
-                            .iter()
-                            .copied()
-                            .fold(highest_peak, |acc, f| f32::max(acc, f.abs()).max(1.0));
+                        gain_audio.extend_from_slice(&audio_data);
+                        dsp::apply_gain(&mut gain_audio, audio_gain);
 
-                        // Normalize the audio, then push to the ring-buffer
-                        gain_audio.iter_mut().for_each(|f| *f /= highest_peak);
+                        // Normalize the audio to the highest peak, then push to the ring-buffer
+                        highest_peak = dsp::normalize(&mut gain_audio, highest_peak);
 
                         audio_ring_buffer.push_audio(&gain_audio);
                         if let Some(buffer) = offline_buffer.as_mut() {
@@ -236,17 +235,21 @@ fn main() {
         let print_thread = s.spawn(move || {
             let mut latest_control_message = WhisperControlPhrase::GettingReady;
             let mut latest_snapshot = Arc::new(TranscriptionSnapshot::default());
+            let mut assembler = SnapshotAssembler::new();
             while p_thread_run_transcription.load(Ordering::Acquire) {
                 match text_receiver.recv() {
                     Ok(output) => match output {
                         // This is the most up-to-date full string transcription
-                        WhisperOutput::TranscriptionSnapshot(snapshot) => {
-                            latest_snapshot = Arc::clone(&snapshot);
-                        }
-
                         WhisperOutput::ControlPhrase(message) => {
                             latest_control_message = message;
                         }
+                        // Snapshots and deltas from the transcriber are assembled into the
+                        // most up-to-date full string transcription.
+                        output => {
+                            if let Some(snapshot) = assembler.push(&output) {
+                                latest_snapshot = snapshot;
+                            }
+                        }
                     },
                     Err(_) => {
                         eprintln!("PRINT CHANNEL CLOSED");
@@ -272,12 +275,14 @@ fn main() {
             // error out.
             while let Ok(last_msg) = text_receiver.recv() {
                 match last_msg {
-                    WhisperOutput::TranscriptionSnapshot(snapshot) => {
-                        latest_snapshot = Arc::clone(&snapshot);
-                    }
                     WhisperOutput::ControlPhrase(message) => {
                         latest_control_message = message;
                     }
+                    output => {
+                        if let Some(snapshot) = assembler.push(&output) {
+                            latest_snapshot = snapshot;
+                        }
+                    }
                 }
                 clear_stdout();
                 println!("Latest Control Message: {}\n", latest_control_message);
@@ -328,17 +333,11 @@ fn main() {
     // Offline audio (re) transcription:
     if let Some(mut buffer) = offline_audio_buffer.lock().take() {
         // Run a small amount of gain on the buffer and normalize to the max(highest_peak, 0dB)
-        let highest_peak = buffer
-            .iter()
-            .fold(1.0, |acc, f| f32::max(acc, f.abs()).max(1.0))
-            * audio_gain;
-
-        buffer
-            .iter_mut()
-            .for_each(|f| *f *= audio_gain / highest_peak);
+        dsp::apply_gain(&mut buffer, audio_gain);
+        dsp::normalize(&mut buffer, audio_gain);
 
         // Take the old (returned) transcription if the user wants to compare.
-        let old_transcription = if run_jaro { Some(transcription) } else { None };
+        let old_transcription = run_comparison.then_some(transcription);
 
         // Use silero to prune out the silence
         let vad = Silero::try_new_whisper_offline_default()
@@ -393,9 +392,19 @@ fn main() {
 
         if let Some(old_transcription) = old_transcription {
             println!("Running comparison...");
-            let jaro_similar = jaro(&old_transcription, &transcription);
-            // This may get expensive, so keep to a minimum I suppose?
-            println!("Similarity score (jaro): {jaro_similar}");
+            // Alignment is quadratic in the length of the differing text, so this may get
+            // expensive for long recordings.
+            let diff = TranscriptDiffer::new().diff_text(&old_transcription, &transcription);
+            println!(
+                "Matched: {}, Substituted: {}, Inserted: {}, Deleted: {}",
+                diff.matches(),
+                diff.substitutions(),
+                diff.insertions(),
+                diff.deletions()
+            );
+            for change in diff.changes() {
+                println!("{change}");
+            }
         }
     };
 }
@@ -0,0 +1,401 @@
+//! Archiving captured audio during long realtime sessions.
+//!
+//! An [AudioArchiver] writes audio to a sequence of WAV files in a directory, starting a new file
+//! whenever the current one reaches the [RotationPolicy]'s size or duration limit, so that a
+//! session running for hours never produces a single unbounded file. Alongside the audio,
+//! `manifest.json` lists each file with its offset into the session, (see: [ArchiveManifest]), so
+//! that transcript timestamps can be mapped back to the audio they came from.
+//!
+//! To archive microphone audio as it is captured, wrap the capture sink in an [ArchiveSink].
+//! Requires the serde feature.
+//!
+//! NOTE: archives are only written as uncompressed WAV, (see: [ArchiveSampleFormat]); there is no
+//! FLAC or other compressed output. At 16 kHz mono, Int16 audio takes roughly 115 MB per hour, so
+//! use a [RotationPolicy] and compress finished files externally if space is a concern.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::audio::recorder::SampleSink;
+use crate::utils::errors::RibbleWhisperError;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::utils::logging::log_warn_or_eprint;
+
+const MANIFEST_FILE: &str = "manifest.json";
+// RIFF header + fmt chunk + data chunk header, (with the fact chunk omitted).
+const WAV_HEADER_BYTES: u64 = 44;
+
+/// The sample encoding of archived WAV files.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum ArchiveSampleFormat {
+    /// 16-bit PCM: half the size, and playable almost everywhere.
+    #[default]
+    Int16,
+    /// 32-bit IEEE float: lossless for the f32 audio the transcribers use.
+    Float32,
+}
+
+impl ArchiveSampleFormat {
+    fn bytes_per_sample(&self) -> u16 {
+        match self {
+            ArchiveSampleFormat::Int16 => 2,
+            ArchiveSampleFormat::Float32 => 4,
+        }
+    }
+
+    fn wav_format_tag(&self) -> u16 {
+        match self {
+            ArchiveSampleFormat::Int16 => 1,
+            ArchiveSampleFormat::Float32 => 3,
+        }
+    }
+}
+
+/// When an [AudioArchiver] starts a new file. Either limit, (or both), can be set; a file is
+/// rotated as soon as it reaches either. With no limits, everything is written to a single file.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RotationPolicy {
+    max_duration: Option<Duration>,
+    max_bytes: Option<u64>,
+}
+
+impl RotationPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rotates files once they hold this much audio, (e.g. 15 minutes).
+    pub fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Rotates files before they grow past this many bytes, (including the WAV header).
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    pub fn max_duration(&self) -> Option<Duration> {
+        self.max_duration
+    }
+
+    pub fn max_bytes(&self) -> Option<u64> {
+        self.max_bytes
+    }
+
+    // The most frames a file can hold, if limited.
+    fn max_frames(&self, sample_rate: u32, frame_bytes: u64) -> Option<u64> {
+        let by_duration = self
+            .max_duration
+            .map(|duration| (duration.as_secs_f64() * sample_rate as f64) as u64);
+        let by_bytes = self
+            .max_bytes
+            .map(|bytes| bytes.saturating_sub(WAV_HEADER_BYTES) / frame_bytes);
+        match (by_duration, by_bytes) {
+            (Some(duration), Some(bytes)) => Some(duration.min(bytes)),
+            (limit, None) | (None, limit) => limit,
+        }
+        .map(|frames| frames.max(1))
+    }
+}
+
+/// An archived file, and where it falls in the session.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ArchiveFile {
+    /// The file name, relative to the archive directory.
+    pub file_name: String,
+    /// The offset of the file's first frame from the start of the session.
+    pub start_frame: u64,
+    /// The number of frames, (samples per channel), in the file.
+    pub frames: u64,
+}
+
+/// Lists the files of an archive, in order. Saved as `manifest.json` in the archive directory.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ArchiveManifest {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub format: ArchiveSampleFormat,
+    pub files: Vec<ArchiveFile>,
+}
+
+impl ArchiveManifest {
+    /// Loads the manifest of an archive directory.
+    pub fn load<P: AsRef<Path>>(directory: P) -> Result<Self, RibbleWhisperError> {
+        let file = File::open(directory.as_ref().join(MANIFEST_FILE))?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    /// The total number of frames archived.
+    pub fn frames(&self) -> u64 {
+        self.files.iter().map(|file| file.frames).sum()
+    }
+
+    /// The total duration of the archived audio.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.frames() as f64 / self.sample_rate as f64)
+    }
+
+    /// Finds the file holding the audio at a transcript timestamp, (in centiseconds from the start
+    /// of the session), and the offset of that timestamp within the file.
+    pub fn locate(&self, timestamp: i64) -> Option<(&ArchiveFile, Duration)> {
+        let frame = (timestamp.max(0) as f64 / 100f64 * self.sample_rate as f64) as u64;
+        let file = self
+            .files
+            .iter()
+            .find(|file| frame < file.start_frame + file.frames)?;
+        let offset = (frame - file.start_frame) as f64 / self.sample_rate as f64;
+        Some((file, Duration::from_secs_f64(offset)))
+    }
+}
+
+/// Writes audio to rotating WAV files with a manifest, (see: [crate::audio::archive]).
+///
+/// Files are named `{prefix}-{index}.wav`, (e.g. `session-0000.wav`). The manifest is rewritten
+/// each time a file is started or finished, so an archive interrupted by a crash is still usable
+/// up to the last rotation; call [AudioArchiver::finish] to finalize the last file.
+pub struct AudioArchiver {
+    directory: PathBuf,
+    prefix: String,
+    rotation: RotationPolicy,
+    manifest: ArchiveManifest,
+    writer: Option<BufWriter<File>>,
+    // Frames written to the current file.
+    file_frames: u64,
+}
+
+impl AudioArchiver {
+    /// Creates an archive of 16-bit WAV files in the given directory, creating the directory if
+    /// needed. Existing files with the same names are overwritten.
+    pub fn create<P: AsRef<Path>>(
+        directory: P,
+        sample_rate: u32,
+        channels: u16,
+    ) -> Result<Self, RibbleWhisperError> {
+        if sample_rate == 0 || channels == 0 {
+            return Err(RibbleWhisperError::ParameterError(
+                "Archived audio must have a sample rate and at least one channel.".to_string(),
+            ));
+        }
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory)?;
+        Ok(Self {
+            directory,
+            prefix: "session".to_string(),
+            rotation: RotationPolicy::default(),
+            manifest: ArchiveManifest {
+                sample_rate,
+                channels,
+                format: ArchiveSampleFormat::default(),
+                files: vec![],
+            },
+            writer: None,
+            file_frames: 0,
+        })
+    }
+
+    /// Sets when a new file is started. Defaults to never.
+    pub fn with_rotation(mut self, rotation: RotationPolicy) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Sets the sample encoding. Defaults to 16-bit PCM.
+    /// This should be set before any audio has been written.
+    pub fn with_format(mut self, format: ArchiveSampleFormat) -> Self {
+        self.manifest.format = format;
+        self
+    }
+
+    /// Sets the file name prefix. Defaults to "session".
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The manifest as of the last finished file, plus the file being written.
+    pub fn manifest(&self) -> &ArchiveManifest {
+        &self.manifest
+    }
+
+    fn frame_bytes(&self) -> u64 {
+        self.manifest.format.bytes_per_sample() as u64 * self.manifest.channels as u64
+    }
+
+    /// Writes interleaved samples, (in [-1.0, 1.0]), rotating files as needed.
+    /// Pushes should hold whole frames; a trailing partial frame is dropped.
+    pub fn push(&mut self, samples: &[f32]) -> Result<(), RibbleWhisperError> {
+        let channels = self.manifest.channels as usize;
+        let max_frames = self
+            .rotation
+            .max_frames(self.manifest.sample_rate, self.frame_bytes());
+        let mut remaining = &samples[..samples.len() - samples.len() % channels];
+        while !remaining.is_empty() {
+            if self.writer.is_none() {
+                self.start_file()?;
+            }
+            let capacity = match max_frames {
+                Some(max_frames) => max_frames.saturating_sub(self.file_frames),
+                None => u64::MAX,
+            };
+            if capacity == 0 {
+                self.finish_file()?;
+                continue;
+            }
+            let take = remaining
+                .len()
+                .min(capacity.saturating_mul(channels as u64) as usize);
+            let (chunk, rest) = remaining.split_at(take);
+            self.write_samples(chunk)?;
+            remaining = rest;
+        }
+        Ok(())
+    }
+
+    fn write_samples(&mut self, samples: &[f32]) -> Result<(), RibbleWhisperError> {
+        let format = self.manifest.format;
+        let writer = self.writer.as_mut().expect("A file should be open.");
+        for sample in samples {
+            match format {
+                ArchiveSampleFormat::Int16 => {
+                    let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+                    writer.write_all(&sample.to_le_bytes())?;
+                }
+                ArchiveSampleFormat::Float32 => writer.write_all(&sample.to_le_bytes())?,
+            }
+        }
+        let frames = samples.len() as u64 / self.manifest.channels as u64;
+        self.file_frames += frames;
+        if let Some(file) = self.manifest.files.last_mut() {
+            file.frames = self.file_frames;
+        }
+        Ok(())
+    }
+
+    fn start_file(&mut self) -> Result<(), RibbleWhisperError> {
+        let index = self.manifest.files.len();
+        let file_name = format!("{}-{index:04}.wav", self.prefix);
+        let mut writer = BufWriter::new(File::create(self.directory.join(&file_name))?);
+        // The sizes are patched once the file is finished.
+        write_wav_header(&mut writer, &self.manifest, 0)?;
+        self.manifest.files.push(ArchiveFile {
+            file_name,
+            start_frame: self.manifest.frames(),
+            frames: 0,
+        });
+        self.writer = Some(writer);
+        self.file_frames = 0;
+        self.write_manifest()
+    }
+
+    fn finish_file(&mut self) -> Result<(), RibbleWhisperError> {
+        let Some(writer) = self.writer.take() else {
+            return Ok(());
+        };
+        let mut file = writer.into_inner().map_err(|e| e.into_error())?;
+        let data_bytes = self.file_frames * self.frame_bytes();
+        file.seek(SeekFrom::Start(0))?;
+        write_wav_header(&mut file, &self.manifest, data_bytes)?;
+        file.flush()?;
+        self.file_frames = 0;
+        self.write_manifest()
+    }
+
+    fn write_manifest(&self) -> Result<(), RibbleWhisperError> {
+        // Written to a temporary file first, so that a crash never leaves a partial manifest.
+        let temp_path = self.directory.join(format!("{MANIFEST_FILE}.tmp"));
+        let file = File::create(&temp_path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &self.manifest)?;
+        fs::rename(temp_path, self.directory.join(MANIFEST_FILE))?;
+        Ok(())
+    }
+
+    /// Finalizes the current file and writes the manifest.
+    pub fn finish(mut self) -> Result<ArchiveManifest, RibbleWhisperError> {
+        self.finish_file()?;
+        self.write_manifest()?;
+        Ok(self.manifest)
+    }
+}
+
+fn write_wav_header(
+    writer: &mut impl Write,
+    manifest: &ArchiveManifest,
+    data_bytes: u64,
+) -> Result<(), RibbleWhisperError> {
+    let bytes_per_sample = manifest.format.bytes_per_sample();
+    let block_align = bytes_per_sample * manifest.channels;
+    let byte_rate = manifest.sample_rate * block_align as u32;
+    // WAV sizes are 32-bit; players read oversized files to the end regardless.
+    let data_bytes = data_bytes.min((u32::MAX as u64) - WAV_HEADER_BYTES) as u32;
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(data_bytes + WAV_HEADER_BYTES as u32 - 8).to_le_bytes())?;
+    writer.write_all(b"WAVEfmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&manifest.format.wav_format_tag().to_le_bytes())?;
+    writer.write_all(&manifest.channels.to_le_bytes())?;
+    writer.write_all(&manifest.sample_rate.to_le_bytes())?;
+    writer.write_all(&byte_rate.to_le_bytes())?;
+    writer.write_all(&block_align.to_le_bytes())?;
+    writer.write_all(&(bytes_per_sample * 8).to_le_bytes())?;
+    writer.write_all(b"data")?;
+    writer.write_all(&data_bytes.to_le_bytes())?;
+    Ok(())
+}
+
+/// A [SampleSink] that archives captured audio before passing it on to another sink, (e.g. a
+/// [crate::audio::recorder::RingBufSink] feeding a realtime transcriber).
+///
+/// If archiving fails, (e.g. the disk is full), a warning is logged and archiving stops; audio is
+/// still passed on. Call [ArchiveSink::finish] once capture has stopped.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct ArchiveSink<S: SampleSink<Sample = f32>> {
+    sink: S,
+    archiver: Option<AudioArchiver>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<S: SampleSink<Sample = f32>> ArchiveSink<S> {
+    pub fn new(sink: S, archiver: AudioArchiver) -> Self {
+        Self {
+            sink,
+            archiver: Some(archiver),
+        }
+    }
+
+    /// True until archiving fails.
+    pub fn archiving(&self) -> bool {
+        self.archiver.is_some()
+    }
+
+    /// Finalizes the archive, returning the inner sink and the manifest, (or None if archiving
+    /// failed).
+    pub fn finish(self) -> (S, Option<Result<ArchiveManifest, RibbleWhisperError>>) {
+        (self.sink, self.archiver.map(AudioArchiver::finish))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<S: SampleSink<Sample = f32>> SampleSink for ArchiveSink<S> {
+    type Sample = f32;
+    fn push(&mut self, data: &[f32]) {
+        if let Some(archiver) = self.archiver.as_mut()
+            && let Err(e) = archiver.push(data)
+        {
+            log_warn_or_eprint!(
+                "Archiving audio to {} failed, archiving stopped: {e}",
+                archiver.directory().display()
+            );
+            self.archiver = None;
+        }
+        self.sink.push(data);
+    }
+}
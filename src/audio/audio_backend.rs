@@ -1,8 +1,19 @@
 use crate::transcriber::WHISPER_SAMPLE_RATE;
 
-use crate::audio::microphone::{MicCapture, Sdl2Capture};
-use crate::audio::recorder::{Recorder, SampleSink};
+#[cfg(feature = "cpal")]
+use crate::audio::microphone::CpalCapture;
+#[cfg(feature = "sdl2")]
+use crate::audio::microphone::Sdl2Capture;
+use crate::audio::microphone::{MicCapture, RibbleAudioFormat};
+#[cfg(feature = "sdl2")]
+use crate::audio::recorder::Recorder;
+use crate::audio::recorder::SampleSink;
 use crate::utils::errors::RibbleWhisperError;
+#[cfg(feature = "cpal")]
+use crate::utils::logging::log_warn;
+
+#[cfg(feature = "cpal")]
+use cpal::traits::{DeviceTrait, HostTrait};
 
 #[cfg(feature = "sdl2")]
 use sdl2::AudioSubsystem;
@@ -19,6 +30,11 @@ pub struct CaptureSpec {
     /// The size-limit (in bytes) before triggering the audio callback to fire. Must be a
     /// power of 2
     period: Option<usize>,
+    /// The capture device, (see: the backend's list_input_devices, e.g.
+    /// [Sdl2Backend::list_input_devices]).
+    device: Option<DeviceId>,
+    /// Whether to capture system audio output instead of an input device.
+    loopback: bool,
 }
 
 impl CaptureSpec {
@@ -27,13 +43,23 @@ impl CaptureSpec {
             sample_rate: None,
             channels: None,
             period: None,
+            device: None,
+            loopback: false,
         }
     }
+
+    /// A spec for capturing system audio, (e.g. a meeting or video), instead of a microphone,
+    /// in whisper's format, (see: [CaptureSpec::with_loopback]).
+    pub fn loopback() -> Self {
+        Self::default().with_loopback(true)
+    }
     pub fn with_sample_rate(self, sample_rate: Option<usize>) -> Self {
         Self {
             sample_rate,
             channels: self.channels,
             period: self.period,
+            device: self.device,
+            loopback: self.loopback,
         }
     }
 
@@ -42,6 +68,8 @@ impl CaptureSpec {
             sample_rate: self.sample_rate,
             channels: num_channels,
             period: self.period,
+            device: self.device,
+            loopback: self.loopback,
         }
     }
 
@@ -50,6 +78,33 @@ impl CaptureSpec {
             sample_rate: self.sample_rate,
             channels: self.channels,
             period,
+            device: self.device,
+            loopback: self.loopback,
+        }
+    }
+
+    /// Set to None to use the system default capture device.
+    pub fn with_device(self, device: Option<DeviceId>) -> Self {
+        Self {
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            period: self.period,
+            device,
+            loopback: self.loopback,
+        }
+    }
+
+    /// Captures what a device plays instead of what it records: WASAPI loopback on Windows, (cpal
+    /// only), or monitor sources on PulseAudio and PipeWire. The device, if set, is an id from the
+    /// backend's list_loopback_devices, (e.g. [Sdl2Backend::list_loopback_devices]); otherwise
+    /// the default output is captured, (or the first monitor source, where there's no WASAPI).
+    pub fn with_loopback(self, loopback: bool) -> Self {
+        Self {
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            period: self.period,
+            device: self.device,
+            loopback,
         }
     }
 
@@ -62,6 +117,12 @@ impl CaptureSpec {
     pub fn period(&self) -> Option<usize> {
         self.period
     }
+    pub fn device(&self) -> Option<DeviceId> {
+        self.device
+    }
+    pub fn is_loopback(&self) -> bool {
+        self.loopback
+    }
 }
 
 #[cfg(feature = "sdl2")]
@@ -106,8 +167,146 @@ impl Default for CaptureSpec {
     }
 }
 
+/// Identifies a capture device: its position in the backend's device list, (e.g.
+/// [Sdl2Backend::list_input_devices]). Ids are only stable while the set of connected devices is;
+/// list the devices again after one is plugged in or removed.
+pub type DeviceId = u32;
+
+/// A capture device that can be selected with [CaptureSpec::with_device], (e.g. in a microphone
+/// picker).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputDevice {
+    id: DeviceId,
+    name: String,
+}
+
+impl InputDevice {
+    pub fn new(id: DeviceId, name: String) -> Self {
+        Self { id, name }
+    }
+
+    pub fn id(&self) -> DeviceId {
+        self.id
+    }
+
+    /// The name the backend reports for the device, (e.g. "USB Microphone").
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl std::fmt::Display for InputDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.id, self.name)
+    }
+}
+
+// Pairs device names with their ids.
+#[cfg(any(feature = "sdl2", feature = "cpal"))]
+fn input_devices_from_names(names: Vec<String>) -> Vec<InputDevice> {
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(id, name)| InputDevice::new(id as DeviceId, name))
+        .collect()
+}
+
+// PulseAudio and PipeWire name monitor sources "Monitor of <output>".
+#[cfg(any(feature = "sdl2", all(feature = "cpal", not(target_os = "windows"))))]
+fn is_monitor_device(name: &str) -> bool {
+    name.to_lowercase().contains("monitor")
+}
+
+/// The formats a capture device can be opened with, (see: [AudioBackend::device_capabilities]).
+/// Use this to check a [CaptureSpec] before opening it, and to decide up front whether audio needs
+/// resampling to whisper's 16 kHz.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeviceCapabilities {
+    sample_rates: Vec<usize>,
+    channels: Vec<u8>,
+    formats: Vec<RibbleAudioFormat>,
+    native_sample_rate: Option<usize>,
+    native_channels: Option<u8>,
+}
+
+impl DeviceCapabilities {
+    pub fn new(
+        sample_rates: Vec<usize>,
+        channels: Vec<u8>,
+        formats: Vec<RibbleAudioFormat>,
+    ) -> Self {
+        Self {
+            sample_rates,
+            channels,
+            formats,
+            native_sample_rate: None,
+            native_channels: None,
+        }
+    }
+
+    /// Sets the format the device captures in without conversion, if the backend reports it.
+    pub fn with_native_spec(mut self, sample_rate: Option<usize>, channels: Option<u8>) -> Self {
+        self.native_sample_rate = sample_rate;
+        self.native_channels = channels;
+        self
+    }
+
+    /// The sample rates, (in Hz), the device can be opened at.
+    pub fn sample_rates(&self) -> &[usize] {
+        &self.sample_rates
+    }
+    /// The channel counts the device can be opened with.
+    pub fn channels(&self) -> &[u8] {
+        &self.channels
+    }
+    pub fn formats(&self) -> &[RibbleAudioFormat] {
+        &self.formats
+    }
+    /// The sample rate the device captures at without conversion, if known.
+    pub fn native_sample_rate(&self) -> Option<usize> {
+        self.native_sample_rate
+    }
+    /// The channel count the device captures with without conversion, if known.
+    pub fn native_channels(&self) -> Option<u8> {
+        self.native_channels
+    }
+
+    /// Returns true if the spec can be opened. Fields left as None, (device defaults), are
+    /// always supported.
+    pub fn supports(&self, spec: &CaptureSpec) -> bool {
+        spec.sample_rate()
+            .is_none_or(|sample_rate| self.sample_rates.contains(&sample_rate))
+            && spec
+                .channels()
+                .is_none_or(|channels| self.channels.contains(&channels))
+    }
+
+    /// Returns true if the device can be opened in whisper's format, (16 kHz mono), e.g. with
+    /// [CaptureSpec::default].
+    pub fn supports_whisper(&self) -> bool {
+        self.sample_rates.contains(&(WHISPER_SAMPLE_RATE as usize)) && self.channels.contains(&1)
+    }
+
+    /// The sample rate to capture at and resample from when the device can't be opened at
+    /// 16 kHz: the native rate if known, otherwise the highest supported rate.
+    /// Returns None if no resampling is needed.
+    pub fn resample_from(&self) -> Option<usize> {
+        if self.sample_rates.contains(&(WHISPER_SAMPLE_RATE as usize)) {
+            return None;
+        }
+        self.native_sample_rate
+            .or_else(|| self.sample_rates.iter().max().copied())
+    }
+}
+
 pub trait AudioBackend<S: SampleSink>: Sized {
     type Capture: MicCapture;
+    /// Queries what a capture device supports, (None for the system default device), so that a
+    /// [CaptureSpec] can be validated before opening it.
+    fn device_capabilities(
+        &self,
+        device: Option<DeviceId>,
+    ) -> Result<DeviceCapabilities, RibbleWhisperError>;
     /// Opens an audio stream for capture
     fn open_capture(&self, spec: CaptureSpec, sink: S)
     -> Result<Self::Capture, RibbleWhisperError>;
@@ -137,12 +336,109 @@ impl Sdl2Backend {
     pub fn from_subsystem(audio_subsystem: AudioSubsystem) -> Self {
         Self { audio_subsystem }
     }
+
+    /// Lists the available capture devices, so that one can be chosen with
+    /// [CaptureSpec::with_device] instead of the system default.
+    pub fn list_input_devices(&self) -> Result<Vec<InputDevice>, RibbleWhisperError> {
+        Ok(input_devices_from_names(self.capture_device_names()?))
+    }
+
+    /// Lists the monitor sources, (PulseAudio or PipeWire), that capture another device's output,
+    /// for [CaptureSpec::with_loopback]. SDL2 has no WASAPI loopback, so this is empty on
+    /// Windows; use the cpal backend there instead.
+    pub fn list_loopback_devices(&self) -> Result<Vec<InputDevice>, RibbleWhisperError> {
+        Ok(self
+            .list_input_devices()?
+            .into_iter()
+            .filter(|device| is_monitor_device(device.name()))
+            .collect())
+    }
+
+    // The name of the capture device to open, or None for the default.
+    fn device_name(&self, spec: &CaptureSpec) -> Result<Option<String>, RibbleWhisperError> {
+        if spec.is_loopback() {
+            let devices = self.list_loopback_devices()?;
+            let device = match spec.device() {
+                Some(id) => devices.into_iter().find(|device| device.id() == id),
+                None => devices.into_iter().next(),
+            };
+            return device
+                .map(|device| Some(device.name().to_string()))
+                .ok_or_else(|| {
+                    RibbleWhisperError::DeviceError(format!(
+                        "No loopback capture device: {:?}",
+                        spec.device()
+                    ))
+                });
+        }
+        spec.device()
+            .map(|index| {
+                self.audio_subsystem
+                    .audio_capture_device_name(index)
+                    .map_err(|e| {
+                        RibbleWhisperError::DeviceError(format!(
+                            "Invalid capture device {index}: {e}"
+                        ))
+                    })
+            })
+            .transpose()
+    }
+
+    /// Lists the names of the available capture devices; a device's position in the list is its
+    /// [DeviceId].
+    pub fn capture_device_names(&self) -> Result<Vec<String>, RibbleWhisperError> {
+        let num_devices = self.audio_subsystem.num_audio_capture_devices().ok_or(
+            RibbleWhisperError::DeviceError("Failed to count capture devices".to_string()),
+        )?;
+        (0..num_devices)
+            .map(|index| {
+                self.audio_subsystem
+                    .audio_capture_device_name(index)
+                    .map_err(|e| {
+                        RibbleWhisperError::DeviceError(format!(
+                            "Failed to get capture device name: {e}"
+                        ))
+                    })
+            })
+            .collect()
+    }
 }
 
 #[cfg(feature = "sdl2")]
 impl<S: SampleSink> AudioBackend<S> for Sdl2Backend {
     type Capture = Sdl2Capture<S>;
 
+    /// SDL converts sample rates, channel counts and formats in software, so any of the listed
+    /// values can be opened on any device. The native spec, (SDL >= 2.0.16), is only reported for
+    /// devices chosen by index; capturing at the native rate and resampling with
+    /// the resampler feature usually sounds better than SDL's conversion.
+    fn device_capabilities(
+        &self,
+        device: Option<DeviceId>,
+    ) -> Result<DeviceCapabilities, RibbleWhisperError> {
+        let capabilities = DeviceCapabilities::new(
+            SDL_SAMPLE_RATES.to_vec(),
+            SDL_CHANNELS.to_vec(),
+            vec![RibbleAudioFormat::F32, RibbleAudioFormat::I16],
+        );
+        let Some(index) = device else {
+            return Ok(capabilities);
+        };
+        self.audio_subsystem
+            .audio_capture_device_name(index)
+            .map_err(|e| {
+                RibbleWhisperError::DeviceError(format!("Invalid capture device {index}: {e}"))
+            })?;
+
+        let mut spec: sdl2::sys::SDL_AudioSpec = unsafe { std::mem::zeroed() };
+        let found =
+            unsafe { sdl2::sys::SDL_GetAudioDeviceSpec(index as std::ffi::c_int, 1, &mut spec) };
+        if found != 0 || spec.freq <= 0 {
+            return Ok(capabilities);
+        }
+        Ok(capabilities.with_native_spec(Some(spec.freq as usize), Some(spec.channels)))
+    }
+
     fn open_capture(
         &self,
         spec: CaptureSpec,
@@ -157,10 +453,11 @@ impl<S: SampleSink> AudioBackend<S> for Sdl2Backend {
             )));
         }
 
+        let device_name = self.device_name(&spec)?;
         let audio_spec: AudioSpecDesired = spec.into();
         let device = self
             .audio_subsystem
-            .open_capture(None, &audio_spec, |_| Recorder::new(sink))
+            .open_capture(device_name.as_deref(), &audio_spec, |_| Recorder::new(sink))
             .map_err(|e| {
                 RibbleWhisperError::DeviceError(format!("Failed to build audio capture: {e}"))
             })?;
@@ -189,4 +486,319 @@ pub fn default_backend() -> Result<(sdl2::Sdl, Sdl2Backend), RibbleWhisperError>
     Ok((ctx, backend))
 }
 
+#[cfg(feature = "cpal")]
+/// An audio backend built on [cpal](https://github.com/RustAudio/cpal), for applications that
+/// don't link SDL, (e.g. egui or iced apps). Devices are opened in the requested format without
+/// conversion, so [CaptureSpec]s should be checked against [AudioBackend::device_capabilities]
+/// first; capture at the native rate and resample with the resampler feature if the device
+/// doesn't support 16 kHz.
+pub struct CpalBackend {
+    host: cpal::Host,
+}
+
+#[cfg(feature = "cpal")]
+impl CpalBackend {
+    /// Constructs a CpalBackend using the platform's default host.
+    pub fn new() -> Self {
+        Self::from_host(cpal::default_host())
+    }
+
+    /// Constructs a CpalBackend using a specific host, (e.g. JACK or ASIO).
+    pub fn from_host(host: cpal::Host) -> Self {
+        Self { host }
+    }
+
+    /// Lists the available capture devices, so that one can be chosen with
+    /// [CaptureSpec::with_device] instead of the system default.
+    pub fn list_input_devices(&self) -> Result<Vec<InputDevice>, RibbleWhisperError> {
+        Ok(input_devices_from_names(self.capture_device_names()?))
+    }
+
+    /// Lists the devices whose output can be captured with [CaptureSpec::with_loopback]: output
+    /// devices on Windows, (WASAPI loopback), and monitor sources elsewhere, (e.g. PulseAudio or
+    /// PipeWire). On Linux, cpal's ALSA host rarely lists monitor sources; prefer the SDL2
+    /// backend there.
+    pub fn list_loopback_devices(&self) -> Result<Vec<InputDevice>, RibbleWhisperError> {
+        self.loopback_devices()?
+            .into_iter()
+            .map(|(id, device)| {
+                device
+                    .name()
+                    .map(|name| InputDevice::new(id, name))
+                    .map_err(|e| {
+                        RibbleWhisperError::DeviceError(format!(
+                            "Failed to get loopback device name: {e}"
+                        ))
+                    })
+            })
+            .collect()
+    }
+
+    /// Lists the names of the available capture devices; a device's position in the list is its
+    /// [DeviceId].
+    pub fn capture_device_names(&self) -> Result<Vec<String>, RibbleWhisperError> {
+        self.input_devices()?
+            .iter()
+            .map(|device| {
+                device.name().map_err(|e| {
+                    RibbleWhisperError::DeviceError(format!(
+                        "Failed to get capture device name: {e}"
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    fn input_devices(&self) -> Result<Vec<cpal::Device>, RibbleWhisperError> {
+        Ok(self
+            .host
+            .input_devices()
+            .map_err(|e| {
+                RibbleWhisperError::DeviceError(format!("Failed to list capture devices: {e}"))
+            })?
+            .collect())
+    }
+
+    // The device at index, or the default input device.
+    fn device(&self, device: Option<DeviceId>) -> Result<cpal::Device, RibbleWhisperError> {
+        match device {
+            Some(index) => self
+                .input_devices()?
+                .into_iter()
+                .nth(index as usize)
+                .ok_or_else(|| {
+                    RibbleWhisperError::DeviceError(format!("Invalid capture device {index}"))
+                }),
+            None => self.host.default_input_device().ok_or_else(|| {
+                RibbleWhisperError::DeviceError("No default capture device".to_string())
+            }),
+        }
+    }
+
+    // WASAPI captures the output of any output device.
+    #[cfg(target_os = "windows")]
+    fn loopback_devices(&self) -> Result<Vec<(DeviceId, cpal::Device)>, RibbleWhisperError> {
+        Ok(self
+            .host
+            .output_devices()
+            .map_err(|e| {
+                RibbleWhisperError::DeviceError(format!("Failed to list output devices: {e}"))
+            })?
+            .enumerate()
+            .map(|(index, device)| (index as DeviceId, device))
+            .collect())
+    }
+
+    // Elsewhere, monitor sources are listed as input devices; they keep their input device ids.
+    #[cfg(not(target_os = "windows"))]
+    fn loopback_devices(&self) -> Result<Vec<(DeviceId, cpal::Device)>, RibbleWhisperError> {
+        Ok(self
+            .input_devices()?
+            .into_iter()
+            .enumerate()
+            .filter(|(_, device)| device.name().is_ok_and(|name| is_monitor_device(&name)))
+            .map(|(index, device)| (index as DeviceId, device))
+            .collect())
+    }
+
+    // The loopback device with the id, or the default output, (the first monitor source where
+    // there's no WASAPI).
+    fn loopback_device(
+        &self,
+        device: Option<DeviceId>,
+    ) -> Result<cpal::Device, RibbleWhisperError> {
+        #[cfg(target_os = "windows")]
+        if device.is_none() {
+            return self.host.default_output_device().ok_or_else(|| {
+                RibbleWhisperError::DeviceError("No default output device".to_string())
+            });
+        }
+        self.loopback_devices()?
+            .into_iter()
+            .find(|(id, _)| device.is_none_or(|device| device == *id))
+            .map(|(_, device)| device)
+            .ok_or_else(|| {
+                RibbleWhisperError::DeviceError(format!("No loopback capture device: {device:?}"))
+            })
+    }
+
+    // The default and supported configs to capture with. WASAPI loopback captures in the output
+    // device's formats.
+    fn capture_configs(
+        device: &cpal::Device,
+        loopback: bool,
+    ) -> Result<CpalCaptureConfigs, RibbleWhisperError> {
+        #[cfg(target_os = "windows")]
+        if loopback {
+            let native = device.default_output_config().map_err(|e| {
+                RibbleWhisperError::DeviceError(format!("Failed to get default output config: {e}"))
+            })?;
+            let configs = device
+                .supported_output_configs()
+                .map_err(|e| {
+                    RibbleWhisperError::DeviceError(format!(
+                        "Failed to query output device configurations: {e}"
+                    ))
+                })?
+                .collect();
+            return Ok((native, configs));
+        }
+        #[cfg(not(target_os = "windows"))]
+        let _ = loopback;
+        let native = device.default_input_config().map_err(|e| {
+            RibbleWhisperError::DeviceError(format!("Failed to get default capture config: {e}"))
+        })?;
+        Ok((native, Self::supported_configs(device)?))
+    }
+
+    fn supported_configs(
+        device: &cpal::Device,
+    ) -> Result<Vec<cpal::SupportedStreamConfigRange>, RibbleWhisperError> {
+        Ok(device
+            .supported_input_configs()
+            .map_err(|e| {
+                RibbleWhisperError::DeviceError(format!(
+                    "Failed to query capture device configurations: {e}"
+                ))
+            })?
+            .collect())
+    }
+}
+
+#[cfg(feature = "cpal")]
+impl Default for CpalBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "cpal")]
+impl<S: SampleSink> AudioBackend<S> for CpalBackend {
+    type Capture = CpalCapture<S>;
+
+    /// cpal reports rate ranges; the sample rates listed are the common rates within them.
+    fn device_capabilities(
+        &self,
+        device: Option<DeviceId>,
+    ) -> Result<DeviceCapabilities, RibbleWhisperError> {
+        let device = self.device(device)?;
+        let configs = Self::supported_configs(&device)?;
+
+        let sample_rates: Vec<usize> = COMMON_SAMPLE_RATES
+            .into_iter()
+            .filter(|&rate| {
+                configs.iter().any(|config| {
+                    (config.min_sample_rate().0..=config.max_sample_rate().0)
+                        .contains(&(rate as u32))
+                })
+            })
+            .collect();
+        let mut channels: Vec<u8> = configs
+            .iter()
+            .map(|config| config.channels() as u8)
+            .collect();
+        channels.sort_unstable();
+        channels.dedup();
+        let formats: Vec<RibbleAudioFormat> = [RibbleAudioFormat::F32, RibbleAudioFormat::I16]
+            .into_iter()
+            .filter(|&format| {
+                configs
+                    .iter()
+                    .any(|config| RibbleAudioFormat::from(config.sample_format()) == format)
+            })
+            .collect();
+
+        let native = device.default_input_config().ok();
+        Ok(
+            DeviceCapabilities::new(sample_rates, channels, formats).with_native_spec(
+                native
+                    .as_ref()
+                    .map(|config| config.sample_rate().0 as usize),
+                native.as_ref().map(|config| config.channels() as u8),
+            ),
+        )
+    }
+
+    fn open_capture(
+        &self,
+        spec: CaptureSpec,
+        mut sink: S,
+    ) -> Result<Self::Capture, RibbleWhisperError> {
+        let valid_period = spec.period().is_none_or(|period| period.is_power_of_two());
+        if !valid_period {
+            return Err(RibbleWhisperError::DeviceError(format!(
+                "Invalid Audio Device period size: {:?}",
+                spec.period()
+            )));
+        }
+
+        let device = if spec.is_loopback() {
+            self.loopback_device(spec.device())?
+        } else {
+            self.device(spec.device())?
+        };
+        let (native, configs) = Self::capture_configs(&device, spec.is_loopback())?;
+        let sample_rate = spec
+            .sample_rate()
+            .map_or(native.sample_rate(), |rate| cpal::SampleRate(rate as u32));
+        let channels = spec
+            .channels()
+            .map_or(native.channels(), |channels| channels as cpal::ChannelCount);
+        let sample_format = <S::Sample as cpal::SizedSample>::FORMAT;
+
+        let supported = configs.iter().any(|config| {
+            config.channels() == channels
+                && config.sample_format() == sample_format
+                && (config.min_sample_rate()..=config.max_sample_rate()).contains(&sample_rate)
+        });
+        if !supported {
+            return Err(RibbleWhisperError::DeviceError(format!(
+                "Capture device does not support {} Hz, {channels} channel(s), {sample_format}",
+                sample_rate.0
+            )));
+        }
+
+        let config = cpal::StreamConfig {
+            channels,
+            sample_rate,
+            buffer_size: spec.period().map_or(cpal::BufferSize::Default, |period| {
+                cpal::BufferSize::Fixed(period as u32)
+            }),
+        };
+        let stream = device
+            .build_input_stream(
+                &config,
+                move |data: &[S::Sample], _: &cpal::InputCallbackInfo| sink.push(data),
+                |_e| log_warn!("cpal capture error: {_e}"),
+                None,
+            )
+            .map_err(|e| {
+                RibbleWhisperError::DeviceError(format!("Failed to build audio capture: {e}"))
+            })?;
+        // Some hosts start streams as soon as they're built; captures start paused, as with SDL.
+        let capture = CpalCapture::new(stream, &config);
+        capture.pause();
+        Ok(capture)
+    }
+
+    /// Drops the stream, stopping capture.
+    fn close_capture(&self, _capture: CpalCapture<S>) {}
+}
+
 pub const AUDIO_BUFFER_SIZE: usize = 1024;
+// A device's default capture config and the configs it supports.
+#[cfg(feature = "cpal")]
+type CpalCaptureConfigs = (
+    cpal::SupportedStreamConfig,
+    Vec<cpal::SupportedStreamConfigRange>,
+);
+// Sample rates checked against cpal's supported ranges.
+#[cfg(feature = "cpal")]
+const COMMON_SAMPLE_RATES: [usize; 9] =
+    [8000, 11025, 16000, 22050, 32000, 44100, 48000, 88200, 96000];
+// The common sample rates SDL is asked to convert to; it accepts any rate.
+#[cfg(feature = "sdl2")]
+const SDL_SAMPLE_RATES: [usize; 9] = [8000, 11025, 16000, 22050, 32000, 44100, 48000, 88200, 96000];
+// SDL2 supports mono, stereo, quad, 5.1 and 7.1.
+#[cfg(feature = "sdl2")]
+const SDL_CHANNELS: [u8; 5] = [1, 2, 4, 6, 8];
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use crate::transcriber::WHISPER_SAMPLE_RATE;
 use crate::utils::errors::RibbleWhisperError;
+use crate::utils::telemetry::record_buffer_overrun;
 use parking_lot::Mutex;
 
 struct InnerAudioRingBuffer<T: Copy + Clone + Default> {
@@ -10,6 +11,8 @@ struct InnerAudioRingBuffer<T: Copy + Clone + Default> {
     head: AtomicUsize,
     // The amount of audio within the buffer, in units of sizeof(T)
     audio_len: AtomicUsize,
+    // The amount of audio ever pushed, in units of sizeof(T); clearing does not reset this.
+    samples_pushed: AtomicUsize,
     capacity_ms: AtomicUsize,
     buffer_capacity: AtomicUsize,
     sample_rate: AtomicUsize,
@@ -82,6 +85,7 @@ impl AudioRingBufferBuilder {
         let inner = Arc::new(InnerAudioRingBuffer {
             head,
             audio_len,
+            samples_pushed: AtomicUsize::new(0),
             capacity_ms,
             buffer_capacity: buffer_len,
             sample_rate,
@@ -119,65 +123,57 @@ impl<T: Copy + Clone + Default> AudioRingBuffer<T> {
     pub fn get_capacity(&self) -> usize {
         self.inner.buffer_capacity.load(Ordering::Acquire)
     }
+    /// Returns the sample rate of the audio in the buffer, in Hz
+    pub fn get_sample_rate(&self) -> usize {
+        self.inner.sample_rate.load(Ordering::Acquire)
+    }
     /// returns the current position of the write head
     pub fn get_head_position(&self) -> usize {
         self.inner.head.load(Ordering::Acquire)
     }
 
+    /// Returns the total amount of audio pushed to the buffer, measured in units of size_of(T).
+    /// Unlike the audio length, this is not reset by clearing, so it can be used as a timeline
+    /// of the stream, (e.g. the latest sample read is at samples_pushed - 1).
+    pub fn get_samples_pushed(&self) -> usize {
+        self.inner.samples_pushed.load(Ordering::Acquire)
+    }
+
     /// Writes the input samples to the buffer.
+    /// This does not allocate: samples are copied straight from the input.
     /// NOTE: if the input length exceeds the buffer capacity, only the last n samples are written
     /// to the buffer, where n = buffer capacity
     pub fn push_audio(&self, input: &[T]) {
-        let mut n_samples = input.len();
-        let mut stream = input.to_vec();
-
+        // Grab the buffer to hold the state before grabbing the capacity and head position; the
+        // capacity can change, (see: extend_capacity_and_keep).
+        let mut buffer = self.inner.buffer.lock();
         let buffer_len = self.inner.buffer_capacity.load(Ordering::Acquire);
-        if n_samples > buffer_len {
-            let len = n_samples;
-            n_samples = buffer_len;
-            let new_start = len - n_samples;
-            stream = stream[new_start..].to_vec();
-        }
+        let stream = &input[input.len().saturating_sub(buffer_len)..];
+        let n_samples = stream.len();
 
-        // Grab the buffer to hold the state before grabbing the head position
-        let mut buffer = self.inner.buffer.lock();
         let head_pos = self.inner.head.load(Ordering::Acquire);
-        if head_pos + n_samples > buffer_len {
-            let offset = buffer_len - head_pos;
-            // memcpy stuff
-            // First copy.
-            let copy_buffer = &mut buffer[head_pos..head_pos + offset];
-            let stream_buffer = &stream[0..offset];
-
-            copy_buffer.copy_from_slice(stream_buffer);
-
-            // Second copy
-            let diff_offset = n_samples - offset;
-            let copy_buffer = &mut buffer[0..diff_offset];
-            let stream_buffer = &stream[offset..offset + diff_offset];
 
-            copy_buffer.copy_from_slice(stream_buffer);
-
-            let new_head_pos = (head_pos + n_samples) % buffer_len;
-            self.inner.head.store(new_head_pos, Ordering::Release);
-
-            let old_audio_len = self.inner.audio_len.load(Ordering::Acquire);
-            let new_audio_len = (old_audio_len + n_samples).min(buffer_len);
-            self.inner.audio_len.store(new_audio_len, Ordering::Release);
-        } else {
-            let copy_buffer = &mut buffer[head_pos..head_pos + n_samples];
-            let stream_buffer = &stream[0..n_samples];
-
-            copy_buffer.copy_from_slice(stream_buffer);
-
-            let new_head_pos = (head_pos + n_samples) % buffer_len;
-            self.inner.head.store(new_head_pos, Ordering::Release);
-
-            let old_audio_len = self.inner.audio_len.load(Ordering::Acquire);
-            let new_audio_len = (old_audio_len + n_samples).min(buffer_len);
-
-            self.inner.audio_len.store(new_audio_len, Ordering::Release);
+        // Audio the transcriber has not yet cleared is about to be overwritten: it is falling behind.
+        if self.inner.audio_len.load(Ordering::Acquire) + input.len() > buffer_len {
+            record_buffer_overrun("ring_buffer");
         }
+
+        // Split the copy where the input wraps around the end of the buffer.
+        let to_endpoint = n_samples.min(buffer_len - head_pos);
+        let (first, second) = stream.split_at(to_endpoint);
+        buffer[head_pos..head_pos + to_endpoint].copy_from_slice(first);
+        buffer[..second.len()].copy_from_slice(second);
+
+        let new_head_pos = (head_pos + n_samples) % buffer_len;
+        self.inner.head.store(new_head_pos, Ordering::Release);
+
+        let old_audio_len = self.inner.audio_len.load(Ordering::Acquire);
+        let new_audio_len = (old_audio_len + n_samples).min(buffer_len);
+        self.inner.audio_len.store(new_audio_len, Ordering::Release);
+        // Samples skipped over, (when the input exceeds the capacity), still advance the stream.
+        self.inner
+            .samples_pushed
+            .fetch_add(input.len(), Ordering::AcqRel);
     }
 
     /// Reads min(len_ms, audio length) ms from the buffer and returns the output as `Vec<T>`
@@ -197,7 +193,6 @@ impl<T: Copy + Clone + Default> AudioRingBuffer<T> {
             ms = self.inner.capacity_ms.load(Ordering::Acquire);
         }
 
-        result.clear();
         let sample_rate = self.inner.sample_rate.load(Ordering::Acquire);
         let mut n_samples = (ms as f64 * sample_rate as f64 / 1000f64) as usize;
 
@@ -208,6 +203,57 @@ impl<T: Copy + Clone + Default> AudioRingBuffer<T> {
         if n_samples > audio_len {
             n_samples = audio_len;
         }
+        self.copy_latest(&buffer, n_samples, result);
+    }
+
+    /// Returns a copy of all the audio currently stored in the buffer, oldest first.
+    /// The copy is taken while holding the buffer's lock, so it is consistent with itself even
+    /// while other threads are writing: it contains exactly the audio a reader would have
+    /// received at that moment, (e.g. for dumping what the transcriber saw when a merge went
+    /// wrong, see: [crate::transcriber::black_box]).
+    pub fn snapshot(&self) -> Vec<T> {
+        let buffer = self.inner.buffer.lock();
+        let audio_len = self.inner.audio_len.load(Ordering::Acquire);
+        let mut result = vec![];
+        self.copy_latest(&buffer, audio_len, &mut result);
+        result
+    }
+
+    /// Grows the buffer to hold capacity_ms of audio, keeping the audio currently stored.
+    /// Does nothing if capacity_ms is not larger than the current capacity.
+    ///
+    /// This is safe to call while other threads are reading and writing: the buffer's lock is held
+    /// for the duration, so concurrent pushes and reads see either the old buffer or the new one,
+    /// never a mix. Audio pushed after this returns is appended after the kept audio.
+    /// Growing copies the stored audio, so avoid calling this on a hot path.
+    pub fn extend_capacity_and_keep(&self, capacity_ms: usize) {
+        let mut buffer = self.inner.buffer.lock();
+        if capacity_ms <= self.inner.capacity_ms.load(Ordering::Acquire) {
+            return;
+        }
+        let sample_rate = self.inner.sample_rate.load(Ordering::Acquire);
+        let new_capacity = ((capacity_ms as f64 / 1000.) * (sample_rate as f64)) as usize;
+        let audio_len = self.inner.audio_len.load(Ordering::Acquire);
+
+        // Unwrap the stored audio to the front of the new buffer.
+        let mut extended = Vec::with_capacity(new_capacity);
+        self.copy_latest(&buffer, audio_len, &mut extended);
+        extended.resize(new_capacity, T::default());
+        *buffer = extended;
+
+        self.inner
+            .head
+            .store(audio_len % new_capacity, Ordering::Release);
+        self.inner
+            .buffer_capacity
+            .store(new_capacity, Ordering::Release);
+        self.inner.capacity_ms.store(capacity_ms, Ordering::Release);
+    }
+
+    // Copies the latest n_samples, (n_samples <= audio length), into result, oldest first.
+    // The buffer's lock must be held.
+    fn copy_latest(&self, buffer: &[T], n_samples: usize, result: &mut Vec<T>) {
+        result.clear();
         result.resize(n_samples, T::default());
         // If n_samples == 0 (ie. the audio buffer has just been cleared).
         if result.is_empty() {
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+/// Buffers are pooled up to this many at a time by default: enough for a few seconds of queued
+/// audio callbacks.
+pub const DEFAULT_POOL_CAPACITY: usize = 64;
+
+/// Counters for how often an [ArcBufferPool] could reuse a buffer.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BufferPoolStats {
+    /// Buffers reused from the pool.
+    pub reused: u64,
+    /// Buffers allocated and added to the pool.
+    pub pooled: u64,
+    /// Buffers allocated outside of the pool, because every pooled buffer was still in use or the
+    /// audio was a different length.
+    pub unpooled: u64,
+}
+
+/// A slab of reusable `Arc<[T]>` audio buffers, for fanning audio out without allocating a new
+/// buffer per audio callback, (see: [crate::audio::recorder::ArcChannelSink::with_buffer_pool]).
+///
+/// The pool keeps a reference to each buffer it hands out. A buffer is recycled once every
+/// consumer has dropped its copy, so consumers receive plain `Arc<[T]>` and need no changes.
+/// Buffers that are held onto, (e.g. collected into a Vec), cannot be recycled; when every pooled
+/// buffer is in use, the pool falls back to allocating.
+///
+/// Pooled buffers all have the length of the first audio pushed, which matches audio callbacks
+/// that deliver a fixed number of samples.
+pub struct ArcBufferPool<T> {
+    buffers: Vec<Arc<[T]>>,
+    capacity: usize,
+    // Where to start looking for a free buffer; buffers tend to free up in the order they were
+    // handed out.
+    cursor: usize,
+    stats: BufferPoolStats,
+}
+
+impl<T: Copy> ArcBufferPool<T> {
+    /// Creates an empty pool that will hold at most capacity buffers.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffers: Vec::with_capacity(capacity),
+            capacity,
+            cursor: 0,
+            stats: BufferPoolStats::default(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of buffers currently held by the pool, whether or not they are in use.
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    pub fn stats(&self) -> BufferPoolStats {
+        self.stats
+    }
+
+    /// Copies the samples into a free pooled buffer, allocating one only if none are free.
+    pub fn copy_from_slice(&mut self, samples: &[T]) -> Arc<[T]> {
+        let n_buffers = self.buffers.len();
+        for i in 0..n_buffers {
+            let index = (self.cursor + i) % n_buffers;
+            let buffer = &mut self.buffers[index];
+            if buffer.len() != samples.len() {
+                continue;
+            }
+            // Only the pool holds the buffer once every consumer has dropped it.
+            if let Some(free) = Arc::get_mut(buffer) {
+                free.copy_from_slice(samples);
+                self.cursor = (index + 1) % n_buffers;
+                self.stats.reused += 1;
+                return Arc::clone(buffer);
+            }
+        }
+
+        let buffer: Arc<[T]> = Arc::from(samples);
+        let matches_pool = self
+            .buffers
+            .first()
+            .is_none_or(|first| first.len() == samples.len());
+        if n_buffers < self.capacity && matches_pool {
+            self.buffers.push(Arc::clone(&buffer));
+            self.stats.pooled += 1;
+        } else {
+            self.stats.unpooled += 1;
+        }
+        buffer
+    }
+
+    /// Drops every pooled buffer, (e.g. after the audio callback size changes).
+    /// Buffers still in use by consumers are freed when they are dropped.
+    pub fn clear(&mut self) {
+        self.buffers.clear();
+        self.cursor = 0;
+    }
+}
+
+impl<T: Copy> Default for ArcBufferPool<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_POOL_CAPACITY)
+    }
+}
@@ -0,0 +1,211 @@
+//! A short test recording for confirming that a capture device works, (e.g. a setup wizard's
+//! "we can hear you" step), before a transcription session starts.
+//!
+//! [verify_capture] records from a device for a fixed duration and returns a [CaptureCheck] with
+//! the audio, its levels, and how much of it a voice activity detector heard speech in.
+//! [CaptureCheck::analyze] runs the same checks over audio captured some other way.
+
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use crate::audio::audio_backend::{AudioBackend, CaptureSpec};
+use crate::audio::microphone::MicCapture;
+use crate::audio::recorder::SampleSink;
+use crate::transcriber::vad::VAD;
+use crate::transcriber::window_hooks::WindowLevels;
+use crate::utils::errors::RibbleWhisperError;
+
+/// The length of each frame the voice activity detector decides on, in milliseconds.
+pub const CHECK_VAD_FRAME_MS: usize = 100;
+
+/// Captures quieter than this, (RMS in dBFS), are treated as silent: most likely a muted or
+/// disconnected microphone.
+pub const DEFAULT_SILENCE_DBFS: f32 = -60.0;
+
+/// Samples at or above this absolute value are counted as clipped.
+const CLIP_LEVEL: f32 = 0.999;
+
+/// The results of a test recording, (see: [crate::audio::capture_check]).
+#[derive(Clone, Debug)]
+pub struct CaptureCheck {
+    audio: Arc<[f32]>,
+    sample_rate: usize,
+    channels: u8,
+    levels: WindowLevels,
+    clipped_samples: usize,
+    voice_probability: Option<f32>,
+}
+
+impl CaptureCheck {
+    /// Measures interleaved audio. The voice activity detector, if any, runs over the audio
+    /// downmixed to mono, in [CHECK_VAD_FRAME_MS] frames, and must support the sample rate,
+    /// (e.g. a [crate::transcriber::vad::Silero] built for 16 kHz only supports 16 kHz audio).
+    /// Its session is reset first.
+    ///
+    /// Returns Err if the sample rate or channel count is zero, or if the audio isn't a whole
+    /// number of frames.
+    pub fn analyze<V: VAD<f32> + ?Sized>(
+        audio: impl Into<Arc<[f32]>>,
+        sample_rate: usize,
+        channels: u8,
+        vad: Option<&mut V>,
+    ) -> Result<Self, RibbleWhisperError> {
+        let audio = audio.into();
+        if sample_rate == 0 || channels == 0 {
+            return Err(RibbleWhisperError::ParameterError(format!(
+                "Invalid capture format: {sample_rate} Hz, {channels} channel(s)."
+            )));
+        }
+        if !audio.len().is_multiple_of(channels as usize) {
+            return Err(RibbleWhisperError::AudioFormat {
+                reason: format!(
+                    "Audio with {channels} channels has a partial frame: {} samples",
+                    audio.len()
+                ),
+            });
+        }
+
+        let levels = WindowLevels::measure(&audio);
+        let clipped_samples = audio.iter().filter(|s| s.abs() >= CLIP_LEVEL).count();
+        let voice_probability = vad.and_then(|vad| {
+            let mono = downmix(&audio, channels as usize);
+            let frame_len = sample_rate * CHECK_VAD_FRAME_MS / 1000;
+            if frame_len == 0 || mono.len() < frame_len {
+                return None;
+            }
+            vad.reset_session();
+            let frames = mono.chunks_exact(frame_len);
+            let n_frames = frames.len();
+            let voiced = frames.filter(|frame| vad.voice_detected(frame)).count();
+            Some(voiced as f32 / n_frames as f32)
+        });
+
+        Ok(Self {
+            audio,
+            sample_rate,
+            channels,
+            levels,
+            clipped_samples,
+            voice_probability,
+        })
+    }
+
+    /// The recorded audio, interleaved, as the device captured it.
+    pub fn audio(&self) -> &[f32] {
+        &self.audio
+    }
+
+    /// A shared handle to the recorded audio, (e.g. to play it back to the user).
+    pub fn shared_audio(&self) -> Arc<[f32]> {
+        Arc::clone(&self.audio)
+    }
+
+    pub fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> u8 {
+        self.channels
+    }
+
+    /// The length of the recording.
+    pub fn duration(&self) -> Duration {
+        let frames = self.audio.len() / self.channels as usize;
+        Duration::from_secs_f64(frames as f64 / self.sample_rate as f64)
+    }
+
+    /// The peak and RMS levels of the whole recording.
+    pub fn levels(&self) -> WindowLevels {
+        self.levels
+    }
+
+    /// The number of samples at full scale; more than a handful means the input gain is too high.
+    pub fn clipped_samples(&self) -> usize {
+        self.clipped_samples
+    }
+
+    /// The fraction of frames the voice activity detector heard speech in, from 0 to 1.
+    /// None if no detector was run, or the recording was shorter than one frame.
+    pub fn voice_probability(&self) -> Option<f32> {
+        self.voice_probability
+    }
+
+    /// Whether the recording is quieter than [DEFAULT_SILENCE_DBFS].
+    pub fn is_silent(&self) -> bool {
+        self.is_below(DEFAULT_SILENCE_DBFS)
+    }
+
+    /// Whether the recording's RMS level is below the given level, in dBFS.
+    pub fn is_below(&self, dbfs: f32) -> bool {
+        self.levels.rms_dbfs() < dbfs
+    }
+
+    /// Whether the voice activity detector heard speech in any frame.
+    pub fn heard_voice(&self) -> bool {
+        self.voice_probability.is_some_and(|p| p > 0.0)
+    }
+}
+
+/// Collects f32 audio for [verify_capture].
+#[derive(Clone, Default)]
+pub struct CaptureCollector(Arc<Mutex<Vec<f32>>>);
+
+impl CaptureCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the audio collected so far.
+    pub fn take(&self) -> Vec<f32> {
+        std::mem::take(&mut *self.0.lock())
+    }
+}
+
+impl SampleSink for CaptureCollector {
+    type Sample = f32;
+    fn push(&mut self, data: &[Self::Sample]) {
+        self.0.lock().extend_from_slice(data);
+    }
+}
+
+/// Records from a capture device for the given duration, then analyzes the recording, (see:
+/// [CaptureCheck::analyze]). This blocks for the duration of the recording.
+///
+/// The capture is opened as f32 with the given spec; the check reports the sample rate and
+/// channels the backend actually obtained, (which the voice activity detector must support).
+pub fn verify_capture<B, V>(
+    backend: &B,
+    spec: CaptureSpec,
+    duration: Duration,
+    vad: Option<&mut V>,
+) -> Result<CaptureCheck, RibbleWhisperError>
+where
+    B: AudioBackend<CaptureCollector>,
+    V: VAD<f32> + ?Sized,
+{
+    let collector = CaptureCollector::new();
+    let capture = backend.open_capture(spec, collector.clone())?;
+    let sample_rate = capture.sample_rate();
+    let channels = capture.channels();
+
+    capture.play();
+    sleep(duration);
+    capture.pause();
+    backend.close_capture(capture);
+
+    CaptureCheck::analyze(collector.take(), sample_rate, channels, vad)
+}
+
+// Averages interleaved audio down to one channel.
+fn downmix(audio: &[f32], channels: usize) -> Vec<f32> {
+    if channels == 1 {
+        return audio.to_vec();
+    }
+    audio
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
@@ -0,0 +1,272 @@
+//! Per-sample gain, normalization, and PCM conversion loops. These run on every audio callback,
+//! so with the `simd` feature they are vectorized with SSE2 on x86_64 and NEON on aarch64.
+//! Other targets, and the samples left over after the last full vector, use the scalar loops.
+//!
+//! The vectorized and scalar paths produce identical results.
+
+/// Multiplies every sample by gain.
+pub fn apply_gain(samples: &mut [f32], gain: f32) {
+    #[cfg(feature = "simd")]
+    let samples = simd::apply_gain(samples, gain);
+    samples.iter_mut().for_each(|s| *s *= gain);
+}
+
+/// Returns the largest absolute sample value, or 0.0 for empty audio.
+/// NaN samples are ignored.
+pub fn peak(samples: &[f32]) -> f32 {
+    #[cfg(feature = "simd")]
+    let (peak, samples) = simd::peak(samples);
+    #[cfg(not(feature = "simd"))]
+    let peak = 0.0f32;
+    samples.iter().fold(peak, |acc, s| acc.max(s.abs()))
+}
+
+/// Scales the audio so that its peak is at most 1.0, treating quieter audio as though it peaked
+/// at floor, (e.g. a rolling peak from previous callbacks, so quiet audio isn't amplified).
+/// Returns the peak the audio was scaled by.
+pub fn normalize(samples: &mut [f32], floor: f32) -> f32 {
+    let peak = peak(samples).max(floor);
+    if peak > 0.0 && peak != 1.0 {
+        apply_gain(samples, peak.recip());
+    }
+    peak
+}
+
+/// Converts i16 PCM audio to f32 audio in [-1.0, 1.0), writing into output.
+/// Panics if input and output have different lengths.
+pub fn integer_to_float(input: &[i16], output: &mut [f32]) {
+    assert_eq!(
+        input.len(),
+        output.len(),
+        "Input and output audio must have the same length."
+    );
+    #[cfg(feature = "simd")]
+    let (input, output) = simd::integer_to_float(input, output);
+    input
+        .iter()
+        .zip(output.iter_mut())
+        .for_each(|(i, o)| *o = *i as f32 * INTEGER_TO_FLOAT);
+}
+
+/// Converts f32 audio to i16 PCM, writing into output. Samples are clamped to [-1.0, 1.0] and
+/// scaled by i16::MAX, (see: [crate::audio::pcm::IntoPcmS16]); NaN samples become 0.
+/// Panics if input and output have different lengths.
+pub fn float_to_integer(input: &[f32], output: &mut [i16]) {
+    assert_eq!(
+        input.len(),
+        output.len(),
+        "Input and output audio must have the same length."
+    );
+    #[cfg(feature = "simd")]
+    let (input, output) = simd::float_to_integer(input, output);
+    input
+        .iter()
+        .zip(output.iter_mut())
+        .for_each(|(i, o)| *o = (i.clamp(-1.0, 1.0) * FLOAT_TO_INTEGER) as i16);
+}
+
+// Dividing by a power of two is exact, so multiplying by its reciprocal gives the same result.
+const INTEGER_TO_FLOAT: f32 = 1.0 / 32768.0;
+const FLOAT_TO_INTEGER: f32 = i16::MAX as f32;
+
+// Each function processes as many full vectors as fit and returns the remaining samples.
+// SSE2 and NEON are part of the x86_64 and aarch64 baselines, so no runtime detection is needed.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd {
+    use std::arch::x86_64::*;
+
+    use super::{FLOAT_TO_INTEGER, INTEGER_TO_FLOAT};
+
+    pub(super) fn apply_gain(samples: &mut [f32], gain: f32) -> &mut [f32] {
+        let mut chunks = samples.chunks_exact_mut(4);
+        // SAFETY: SSE2 is always available on x86_64, and each chunk holds 4 f32s.
+        unsafe {
+            let gain = _mm_set1_ps(gain);
+            for chunk in &mut chunks {
+                let v = _mm_loadu_ps(chunk.as_ptr());
+                _mm_storeu_ps(chunk.as_mut_ptr(), _mm_mul_ps(v, gain));
+            }
+        }
+        chunks.into_remainder()
+    }
+
+    pub(super) fn peak(samples: &[f32]) -> (f32, &[f32]) {
+        let chunks = samples.chunks_exact(4);
+        let remainder = chunks.remainder();
+        let mut lanes = [0.0f32; 4];
+        // SAFETY: SSE2 is always available on x86_64, and each chunk holds 4 f32s.
+        unsafe {
+            let sign = _mm_set1_ps(-0.0);
+            let mut acc = _mm_setzero_ps();
+            for chunk in chunks {
+                let v = _mm_andnot_ps(sign, _mm_loadu_ps(chunk.as_ptr()));
+                // maxps returns its second operand when either is NaN, which keeps NaN out of
+                // the accumulator.
+                acc = _mm_max_ps(v, acc);
+            }
+            _mm_storeu_ps(lanes.as_mut_ptr(), acc);
+        }
+        (lanes.into_iter().fold(0.0, f32::max), remainder)
+    }
+
+    pub(super) fn integer_to_float<'a, 'b>(
+        input: &'a [i16],
+        output: &'b mut [f32],
+    ) -> (&'a [i16], &'b mut [f32]) {
+        let mut inputs = input.chunks_exact(8);
+        let mut outputs = output.chunks_exact_mut(8);
+        // SAFETY: SSE2 is always available on x86_64, and each chunk holds 8 samples.
+        unsafe {
+            let scale = _mm_set1_ps(INTEGER_TO_FLOAT);
+            for (i, o) in (&mut inputs).zip(&mut outputs) {
+                let v = _mm_loadu_si128(i.as_ptr().cast());
+                // SSE2 has no sign-extending widen: duplicate each i16 into both halves of an
+                // i32, then shift the copy in the high half back down.
+                let low = _mm_srai_epi32::<16>(_mm_unpacklo_epi16(v, v));
+                let high = _mm_srai_epi32::<16>(_mm_unpackhi_epi16(v, v));
+                _mm_storeu_ps(o.as_mut_ptr(), _mm_mul_ps(_mm_cvtepi32_ps(low), scale));
+                _mm_storeu_ps(
+                    o.as_mut_ptr().add(4),
+                    _mm_mul_ps(_mm_cvtepi32_ps(high), scale),
+                );
+            }
+        }
+        (inputs.remainder(), outputs.into_remainder())
+    }
+
+    pub(super) fn float_to_integer<'a, 'b>(
+        input: &'a [f32],
+        output: &'b mut [i16],
+    ) -> (&'a [f32], &'b mut [i16]) {
+        let mut inputs = input.chunks_exact(8);
+        let mut outputs = output.chunks_exact_mut(8);
+        // SAFETY: SSE2 is always available on x86_64, and each chunk holds 8 samples.
+        unsafe {
+            let min = _mm_set1_ps(-1.0);
+            let max = _mm_set1_ps(1.0);
+            let scale = _mm_set1_ps(FLOAT_TO_INTEGER);
+            let to_integer = |v: __m128| {
+                // Zero NaN lanes to match the scalar cast, then clamp and truncate.
+                let v = _mm_and_ps(v, _mm_cmpord_ps(v, v));
+                let v = _mm_min_ps(_mm_max_ps(v, min), max);
+                _mm_cvttps_epi32(_mm_mul_ps(v, scale))
+            };
+            for (i, o) in (&mut inputs).zip(&mut outputs) {
+                let low = to_integer(_mm_loadu_ps(i.as_ptr()));
+                let high = to_integer(_mm_loadu_ps(i.as_ptr().add(4)));
+                _mm_storeu_si128(o.as_mut_ptr().cast(), _mm_packs_epi32(low, high));
+            }
+        }
+        (inputs.remainder(), outputs.into_remainder())
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+mod simd {
+    use std::arch::aarch64::*;
+
+    use super::{FLOAT_TO_INTEGER, INTEGER_TO_FLOAT};
+
+    pub(super) fn apply_gain(samples: &mut [f32], gain: f32) -> &mut [f32] {
+        let mut chunks = samples.chunks_exact_mut(4);
+        // SAFETY: NEON is always available on aarch64, and each chunk holds 4 f32s.
+        unsafe {
+            for chunk in &mut chunks {
+                let v = vld1q_f32(chunk.as_ptr());
+                vst1q_f32(chunk.as_mut_ptr(), vmulq_n_f32(v, gain));
+            }
+        }
+        chunks.into_remainder()
+    }
+
+    pub(super) fn peak(samples: &[f32]) -> (f32, &[f32]) {
+        let chunks = samples.chunks_exact(4);
+        let remainder = chunks.remainder();
+        // SAFETY: NEON is always available on aarch64, and each chunk holds 4 f32s.
+        let peak = unsafe {
+            let mut acc = vdupq_n_f32(0.0);
+            for chunk in chunks {
+                // fmaxnm returns the number when one operand is NaN.
+                acc = vmaxnmq_f32(acc, vabsq_f32(vld1q_f32(chunk.as_ptr())));
+            }
+            vmaxnmvq_f32(acc)
+        };
+        (peak, remainder)
+    }
+
+    pub(super) fn integer_to_float<'a, 'b>(
+        input: &'a [i16],
+        output: &'b mut [f32],
+    ) -> (&'a [i16], &'b mut [f32]) {
+        let mut inputs = input.chunks_exact(8);
+        let mut outputs = output.chunks_exact_mut(8);
+        // SAFETY: NEON is always available on aarch64, and each chunk holds 8 samples.
+        unsafe {
+            for (i, o) in (&mut inputs).zip(&mut outputs) {
+                let v = vld1q_s16(i.as_ptr());
+                let low = vcvtq_f32_s32(vmovl_s16(vget_low_s16(v)));
+                let high = vcvtq_f32_s32(vmovl_high_s16(v));
+                vst1q_f32(o.as_mut_ptr(), vmulq_n_f32(low, INTEGER_TO_FLOAT));
+                vst1q_f32(o.as_mut_ptr().add(4), vmulq_n_f32(high, INTEGER_TO_FLOAT));
+            }
+        }
+        (inputs.remainder(), outputs.into_remainder())
+    }
+
+    pub(super) fn float_to_integer<'a, 'b>(
+        input: &'a [f32],
+        output: &'b mut [i16],
+    ) -> (&'a [f32], &'b mut [i16]) {
+        let mut inputs = input.chunks_exact(8);
+        let mut outputs = output.chunks_exact_mut(8);
+        // SAFETY: NEON is always available on aarch64, and each chunk holds 8 samples.
+        unsafe {
+            let min = vdupq_n_f32(-1.0);
+            let max = vdupq_n_f32(1.0);
+            // fmax/fmin propagate NaN, and fcvtzs truncates and maps NaN to 0, like the scalar
+            // cast.
+            let to_integer = |v: float32x4_t| {
+                let v = vminq_f32(vmaxq_f32(v, min), max);
+                vcvtq_s32_f32(vmulq_n_f32(v, FLOAT_TO_INTEGER))
+            };
+            for (i, o) in (&mut inputs).zip(&mut outputs) {
+                let low = to_integer(vld1q_f32(i.as_ptr()));
+                let high = to_integer(vld1q_f32(i.as_ptr().add(4)));
+                vst1q_s16(
+                    o.as_mut_ptr(),
+                    vcombine_s16(vqmovn_s32(low), vqmovn_s32(high)),
+                );
+            }
+        }
+        (inputs.remainder(), outputs.into_remainder())
+    }
+}
+
+// Without vector intrinsics, everything runs through the scalar loops.
+#[cfg(all(
+    feature = "simd",
+    not(any(target_arch = "x86_64", target_arch = "aarch64"))
+))]
+mod simd {
+    pub(super) fn apply_gain(samples: &mut [f32], _gain: f32) -> &mut [f32] {
+        samples
+    }
+
+    pub(super) fn peak(samples: &[f32]) -> (f32, &[f32]) {
+        (0.0, samples)
+    }
+
+    pub(super) fn integer_to_float<'a, 'b>(
+        input: &'a [i16],
+        output: &'b mut [f32],
+    ) -> (&'a [i16], &'b mut [f32]) {
+        (input, output)
+    }
+
+    pub(super) fn float_to_integer<'a, 'b>(
+        input: &'a [f32],
+        output: &'b mut [i16],
+    ) -> (&'a [f32], &'b mut [i16]) {
+        (input, output)
+    }
+}
@@ -0,0 +1,191 @@
+use std::fs::{create_dir_all, File};
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "resampler")]
+use crate::audio::loading::load_normalized_audio_file;
+use crate::audio::{AudioChannelConfiguration, WhisperAudioSample};
+use crate::transcriber::{RibbleWhisperSegment, WHISPER_SAMPLE_RATE};
+use crate::utils::errors::RibbleWhisperError;
+
+const PCM_FORMAT: u16 = 1;
+const IEEE_FLOAT_FORMAT: u16 = 3;
+
+/// Slices audio by segment timestamps, (e.g. to export per-utterance clips aligned with a
+/// transcript for correction tools or datasets).
+///
+/// Segment timestamps are relative to the audio that was transcribed: slice that audio, or the
+/// recording it was taken from if no voice activity detection was used to trim it.
+#[derive(Copy, Clone, Debug)]
+pub struct AudioExcerpter {
+    sample_rate: u32,
+    channels: u16,
+    padding: Duration,
+}
+
+impl AudioExcerpter {
+    /// For interleaved audio at the given sample rate.
+    pub fn new(sample_rate: u32, channels: AudioChannelConfiguration) -> Self {
+        let channels = match channels {
+            AudioChannelConfiguration::Mono => 1,
+            AudioChannelConfiguration::Stereo => 2,
+        };
+        Self {
+            sample_rate,
+            channels,
+            padding: Duration::ZERO,
+        }
+    }
+
+    /// Widens each excerpt by the padding on both sides, (e.g. to keep word onsets that fall
+    /// just outside of a segment's timestamps). Defaults to no padding.
+    pub fn with_padding(mut self, padding: Duration) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn padding(&self) -> Duration {
+        self.padding
+    }
+
+    /// Returns the range of (interleaved) samples covered by the segment, clamped to len.
+    pub fn sample_range(&self, segment: &RibbleWhisperSegment, len: usize) -> Range<usize> {
+        let padding = (self.padding.as_secs_f64() * self.sample_rate as f64) as u64;
+        // Segment timestamps are in centiseconds.
+        let to_frame = |timestamp: i64| timestamp.max(0) as u64 * self.sample_rate as u64 / 100;
+        let to_index = |frame: u64| (frame as usize * self.channels as usize).min(len);
+
+        let start = to_index(to_frame(segment.start_timestamp()).saturating_sub(padding));
+        let end = to_index(to_frame(segment.end_timestamp()) + padding).max(start);
+        start..end
+    }
+
+    /// Copies the audio covered by the segment.
+    pub fn extract(
+        &self,
+        audio: &WhisperAudioSample,
+        segment: &RibbleWhisperSegment,
+    ) -> WhisperAudioSample {
+        let range = self.sample_range(segment, audio.len());
+        match audio {
+            WhisperAudioSample::I16(samples) => WhisperAudioSample::I16(Arc::from(&samples[range])),
+            WhisperAudioSample::F32(samples) => WhisperAudioSample::F32(Arc::from(&samples[range])),
+        }
+    }
+
+    /// Writes the audio covered by the segment to a WAV file.
+    pub fn write_excerpt<P: AsRef<Path>>(
+        &self,
+        path: P,
+        audio: &WhisperAudioSample,
+        segment: &RibbleWhisperSegment,
+    ) -> Result<(), RibbleWhisperError> {
+        let excerpt = self.extract(audio, segment);
+        let mut writer = BufWriter::new(File::create(path)?);
+        write_wav(&mut writer, &excerpt, self.sample_rate, self.channels)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes a WAV excerpt for each segment into the directory, creating it if necessary.
+    /// Excerpts are named by segment index, (e.g. segment_0000.wav), so they sort in transcript order.
+    /// # Returns:
+    /// * Ok(paths), in segment order, on success.
+    pub fn write_excerpts<P: AsRef<Path>>(
+        &self,
+        directory: P,
+        audio: &WhisperAudioSample,
+        segments: &[RibbleWhisperSegment],
+    ) -> Result<Vec<PathBuf>, RibbleWhisperError> {
+        let directory = directory.as_ref();
+        create_dir_all(directory)?;
+        segments
+            .iter()
+            .enumerate()
+            .map(|(index, segment)| {
+                let path = directory.join(format!("segment_{index:04}.wav"));
+                self.write_excerpt(&path, audio, segment)?;
+                Ok(path)
+            })
+            .collect()
+    }
+
+    /// Loads an audio file and writes a WAV excerpt for each segment, see: [Self::write_excerpts].
+    /// The file is resampled to 16 kHz mono to line up with the segment timestamps, so the
+    /// excerpts are written as 16 kHz mono regardless of the excerpter's configuration.
+    /// NOTE: requires the resampler feature flag to be set
+    #[cfg(feature = "resampler")]
+    pub fn write_file_excerpts<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        audio_path: P,
+        directory: Q,
+        segments: &[RibbleWhisperSegment],
+    ) -> Result<Vec<PathBuf>, RibbleWhisperError> {
+        let audio = load_normalized_audio_file(audio_path, None::<fn(usize)>)?;
+        Self::default()
+            .with_padding(self.padding)
+            .write_excerpts(directory, &audio, segments)
+    }
+}
+
+impl Default for AudioExcerpter {
+    /// For whisper-ready audio: 16 kHz mono.
+    fn default() -> Self {
+        Self::new(WHISPER_SAMPLE_RATE as u32, AudioChannelConfiguration::Mono)
+    }
+}
+
+/// Writes interleaved audio as a WAV: 16-bit PCM for I16 audio and 32-bit float for F32 audio.
+pub fn write_wav<W: Write>(
+    writer: &mut W,
+    audio: &WhisperAudioSample,
+    sample_rate: u32,
+    channels: u16,
+) -> Result<(), RibbleWhisperError> {
+    let (format, bytes_per_sample) = match audio {
+        WhisperAudioSample::I16(_) => (PCM_FORMAT, 2u16),
+        WhisperAudioSample::F32(_) => (IEEE_FLOAT_FORMAT, 4u16),
+    };
+    let data_len = u32::try_from(audio.len() * bytes_per_sample as usize)
+        .ok()
+        .filter(|len| *len <= u32::MAX - 36)
+        .ok_or(RibbleWhisperError::AudioFormat {
+            reason: "Audio is too long for a WAV file".to_string(),
+        })?;
+    let block_align = channels * bytes_per_sample;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(36 + data_len).to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&format.to_le_bytes())?;
+    writer.write_all(&channels.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+    writer.write_all(&block_align.to_le_bytes())?;
+    writer.write_all(&(bytes_per_sample * 8).to_le_bytes())?;
+    writer.write_all(b"data")?;
+    writer.write_all(&data_len.to_le_bytes())?;
+
+    match audio {
+        WhisperAudioSample::I16(samples) => {
+            for sample in samples.iter() {
+                writer.write_all(&sample.to_le_bytes())?;
+            }
+        }
+        WhisperAudioSample::F32(samples) => {
+            for sample in samples.iter() {
+                writer.write_all(&sample.to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,11 +1,13 @@
 use std::fs::File;
+use std::io::Cursor;
 use std::path::Path;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use symphonia::core::audio::SampleBuffer;
 use symphonia::core::codecs::Decoder;
 use symphonia::core::errors::Error;
 use symphonia::core::formats::FormatReader;
-use symphonia::core::io::MediaSourceStream;
+use symphonia::core::io::{MediaSource, MediaSourceStream};
 use symphonia::core::probe::{Hint, ProbeResult};
 
 #[cfg(feature = "resampler")]
@@ -15,9 +17,14 @@ use crate::utils::callback::{Callback, Nop, RibbleWhisperCallback};
 use crate::utils::errors::RibbleWhisperError;
 
 fn get_audio_probe<P: AsRef<Path> + Sized>(path: P) -> Result<ProbeResult, RibbleWhisperError> {
-    let file = Box::new(File::open(path)?);
-    let mss = MediaSourceStream::new(file, Default::default());
-    let hint = Hint::new();
+    probe_media_source(Box::new(File::open(path)?), Hint::new())
+}
+
+fn probe_media_source(
+    source: Box<dyn MediaSource>,
+    hint: Hint,
+) -> Result<ProbeResult, RibbleWhisperError> {
+    let mss = MediaSourceStream::new(source, Default::default());
     let format_opts = Default::default();
     let metadata_opts = Default::default();
     let probe = symphonia::default::get_probe().format(&hint, mss, &format_opts, &metadata_opts)?;
@@ -34,15 +41,15 @@ pub fn audio_file_num_frames<P: AsRef<Path> + Sized>(path: P) -> Result<u64, Rib
     let format = probe.format;
     let track = format
         .default_track()
-        .ok_or(RibbleWhisperError::ParameterError(
-            "Failed to get default audio track".to_string(),
-        ))?;
+        .ok_or(RibbleWhisperError::AudioFormat {
+            reason: "Failed to get default audio track".to_string(),
+        })?;
     let codec_params = &track.codec_params;
     codec_params
         .n_frames
-        .ok_or(RibbleWhisperError::ParameterError(
-            "Failed to get the number of frames".to_string(),
-        ))
+        .ok_or(RibbleWhisperError::AudioFormat {
+            reason: "Failed to get the number of frames".to_string(),
+        })
 }
 
 /// Loads a RibbleWhisper-compatible (i.e. Stereo/mono, can be converted into whisper-compatible) audio file
@@ -53,56 +60,191 @@ pub fn load_audio_file<P: AsRef<Path>>(
     path: P,
     progress_callback: Option<impl FnMut(usize)>,
 ) -> Result<WhisperAudioSample, RibbleWhisperError> {
-    let decoder_opts = Default::default();
-    let probed = get_audio_probe(path)?;
+    load_audio_file_with_abort(path, progress_callback, &AtomicBool::new(true))
+}
 
+/// Loads an audio file like [load_audio_file], but stops decoding once run_transcription is set
+/// to false, returning Err([RibbleWhisperError::Cancelled]).
+/// The same flag can be passed on to an
+/// [OfflineTranscriber](crate::transcriber::offline_transcriber::OfflineTranscriber), which also
+/// returns Err([RibbleWhisperError::Cancelled]) once it's cleared, (with any partial transcription
+/// kept separately).
+pub fn load_audio_file_with_abort<P: AsRef<Path>>(
+    path: P,
+    progress_callback: Option<impl FnMut(usize)>,
+    run_transcription: &AtomicBool,
+) -> Result<WhisperAudioSample, RibbleWhisperError> {
+    decode_audio(get_audio_probe(path)?, progress_callback, run_transcription)
+}
+
+/// Loads a RibbleWhisper-compatible audio file from an in-memory buffer, (e.g. a file uploaded in a
+/// browser), see: [load_audio_file].
+/// NOTE: this expects the audio to be sampled at 16kHz. Either resample the audio beforehand, or use: [load_normalized_audio_bytes]
+pub fn load_audio_bytes(
+    bytes: Vec<u8>,
+    progress_callback: Option<impl FnMut(usize)>,
+) -> Result<WhisperAudioSample, RibbleWhisperError> {
+    load_audio_bytes_with_abort(bytes, progress_callback, &AtomicBool::new(true))
+}
+
+/// Loads audio from an in-memory buffer like [load_audio_bytes], but stops decoding once
+/// run_transcription is set to false, (see: [load_audio_file_with_abort]).
+pub fn load_audio_bytes_with_abort(
+    bytes: Vec<u8>,
+    progress_callback: Option<impl FnMut(usize)>,
+    run_transcription: &AtomicBool,
+) -> Result<WhisperAudioSample, RibbleWhisperError> {
+    decode_audio(
+        probe_media_source(Box::new(Cursor::new(bytes)), Hint::new())?,
+        progress_callback,
+        run_transcription,
+    )
+}
+
+fn decode_audio(
+    probed: ProbeResult,
+    progress_callback: Option<impl FnMut(usize)>,
+    run_transcription: &AtomicBool,
+) -> Result<WhisperAudioSample, RibbleWhisperError> {
+    let decoder_opts = Default::default();
     let format = probed.format;
     let track = format
         .default_track()
-        .ok_or(RibbleWhisperError::ParameterError(
-            "Failed to get default audio track".to_string(),
-        ))?;
+        .ok_or(RibbleWhisperError::AudioFormat {
+            reason: "Failed to get default audio track".to_string(),
+        })?;
 
     let decoder = symphonia::default::get_codecs().make(&track.codec_params, &decoder_opts)?;
     // Decode loop
     let samples = match progress_callback {
-        Some(p) => decode_loop(track.id, decoder, format, RibbleWhisperCallback::new(p)),
-        None => decode_loop(track.id, decoder, format, Nop::new()),
+        Some(p) => decode_loop(
+            track.id,
+            decoder,
+            format,
+            RibbleWhisperCallback::new(p),
+            run_transcription,
+        ),
+        None => decode_loop(track.id, decoder, format, Nop::new(), run_transcription),
     };
     Ok(WhisperAudioSample::F32(Arc::from(samples?)))
 }
 
 /// Loads a WhisperRealtime-compatible (i.e. Can be converted into whisper-compatible) audio file,
-/// and resamples to 16 kHz as necessary.
+/// and resamples/downmixes to 16 kHz mono as necessary.
 /// To receive the number of frames copied per each decode iteration, use the optional progress_callback.
 /// NOTE: requires the resampler feature flag to be set
 #[cfg(feature = "resampler")]
 pub fn load_normalized_audio_file<P: AsRef<Path> + Sized>(
     path: P,
     progress_callback: Option<impl FnMut(usize)>,
+) -> Result<WhisperAudioSample, RibbleWhisperError> {
+    load_normalized_audio_file_with_abort(path, progress_callback, &AtomicBool::new(true))
+}
+
+/// Loads an audio file like [load_normalized_audio_file], but stops decoding once
+/// run_transcription is set to false, returning Err([RibbleWhisperError::Cancelled]), so that
+/// cancelling a job stops promptly when loading long audio, (see: [load_audio_file_with_abort]).
+/// NOTE: requires the resampler feature flag to be set
+#[cfg(feature = "resampler")]
+pub fn load_normalized_audio_file_with_abort<P: AsRef<Path> + Sized>(
+    path: P,
+    progress_callback: Option<impl FnMut(usize)>,
+    run_transcription: &AtomicBool,
+) -> Result<WhisperAudioSample, RibbleWhisperError> {
+    decode_normalized_audio(get_audio_probe(path)?, progress_callback, run_transcription)
+}
+
+/// Loads a WhisperRealtime-compatible audio file from an in-memory buffer and resamples/downmixes
+/// to 16 kHz mono as necessary, see: [load_normalized_audio_file].
+/// NOTE: requires the resampler feature flag to be set
+#[cfg(feature = "resampler")]
+pub fn load_normalized_audio_bytes(
+    bytes: Vec<u8>,
+    progress_callback: Option<impl FnMut(usize)>,
+) -> Result<WhisperAudioSample, RibbleWhisperError> {
+    load_normalized_audio_bytes_with_abort(bytes, progress_callback, &AtomicBool::new(true))
+}
+
+/// Loads audio from an in-memory buffer like [load_normalized_audio_bytes], but stops decoding
+/// once run_transcription is set to false, (see: [load_normalized_audio_file_with_abort]).
+/// NOTE: requires the resampler feature flag to be set
+#[cfg(feature = "resampler")]
+pub fn load_normalized_audio_bytes_with_abort(
+    bytes: Vec<u8>,
+    progress_callback: Option<impl FnMut(usize)>,
+    run_transcription: &AtomicBool,
+) -> Result<WhisperAudioSample, RibbleWhisperError> {
+    decode_normalized_audio(
+        probe_media_source(Box::new(Cursor::new(bytes)), Hint::new())?,
+        progress_callback,
+        run_transcription,
+    )
+}
+
+/// Loads audio from an in-memory buffer like [load_normalized_audio_bytes], using its MIME type,
+/// (e.g. "audio/ogg" from an upload's Content-Type), to help identify the container format.
+/// NOTE: requires the resampler feature flag to be set
+#[cfg(feature = "resampler")]
+pub fn load_normalized_audio_bytes_with_mime_type(
+    bytes: Vec<u8>,
+    mime_type: &str,
+    progress_callback: Option<impl FnMut(usize)>,
+) -> Result<WhisperAudioSample, RibbleWhisperError> {
+    load_normalized_audio_bytes_with_mime_type_with_abort(
+        bytes,
+        mime_type,
+        progress_callback,
+        &AtomicBool::new(true),
+    )
+}
+
+/// Loads audio from an in-memory buffer like [load_normalized_audio_bytes_with_mime_type], but
+/// stops decoding once run_transcription is set to false, (see:
+/// [load_normalized_audio_file_with_abort]).
+/// NOTE: requires the resampler feature flag to be set
+#[cfg(feature = "resampler")]
+pub fn load_normalized_audio_bytes_with_mime_type_with_abort(
+    bytes: Vec<u8>,
+    mime_type: &str,
+    progress_callback: Option<impl FnMut(usize)>,
+    run_transcription: &AtomicBool,
+) -> Result<WhisperAudioSample, RibbleWhisperError> {
+    let mut hint = Hint::new();
+    hint.mime_type(mime_type);
+    decode_normalized_audio(
+        probe_media_source(Box::new(Cursor::new(bytes)), hint)?,
+        progress_callback,
+        run_transcription,
+    )
+}
+
+#[cfg(feature = "resampler")]
+fn decode_normalized_audio(
+    probed: ProbeResult,
+    progress_callback: Option<impl FnMut(usize)>,
+    run_transcription: &AtomicBool,
 ) -> Result<WhisperAudioSample, RibbleWhisperError> {
     let decoder_opts = Default::default();
-    let probed = get_audio_probe(path)?;
     let format = probed.format;
     let track = format
         .default_track()
-        .ok_or(RibbleWhisperError::ParameterError(
-            "Failed to get default track".to_string(),
-        ))?;
+        .ok_or(RibbleWhisperError::AudioFormat {
+            reason: "Failed to get default track".to_string(),
+        })?;
 
     // Get the codec parameters before passing ownership to the decode loop.
     let codec_params = &track.codec_params;
     let sample_rate = codec_params
         .sample_rate
-        .ok_or(RibbleWhisperError::ParameterError(
-            "Failed to grab sample rate".to_string(),
-        ))? as f64;
+        .ok_or(RibbleWhisperError::AudioFormat {
+            reason: "Failed to grab sample rate".to_string(),
+        })? as f64;
 
     let num_channels = codec_params
         .channels
-        .ok_or(RibbleWhisperError::ParameterError(
-            "Failed to grab number of channels".to_string(),
-        ))?
+        .ok_or(RibbleWhisperError::AudioFormat {
+            reason: "Failed to grab number of channels".to_string(),
+        })?
         .count();
 
     let needs_normalizing = needs_normalizing(track);
@@ -110,12 +252,18 @@ pub fn load_normalized_audio_file<P: AsRef<Path> + Sized>(
     // Decode loop
 
     let samples = match progress_callback {
-        Some(p) => decode_loop(track.id, decoder, format, RibbleWhisperCallback::new(p)),
-        None => decode_loop(track.id, decoder, format, Nop::new()),
+        Some(p) => decode_loop(
+            track.id,
+            decoder,
+            format,
+            RibbleWhisperCallback::new(p),
+            run_transcription,
+        ),
+        None => decode_loop(track.id, decoder, format, Nop::new(), run_transcription),
     }?;
 
-    // Normalize
-    if needs_normalizing? {
+    // Normalize; 16 kHz stereo audio still needs to be downmixed.
+    if needs_normalizing? || num_channels == 2 {
         let audio = ResampleableAudio::F32(&samples);
         normalize_audio(&audio, sample_rate, num_channels)
     } else {
@@ -125,16 +273,21 @@ pub fn load_normalized_audio_file<P: AsRef<Path> + Sized>(
 
 // Note: the progress_callback returns the total number of frames decoded per iteration in the
 // decode loop.
+// run_transcription is checked once per packet; decoding stops with Err(Cancelled) once it's false.
 fn decode_loop(
     track_id: u32,
     mut decoder: Box<dyn Decoder>,
     mut reader: Box<dyn FormatReader>,
     mut progress_callback: impl Callback<Argument = usize>,
+    run_transcription: &AtomicBool,
 ) -> Result<Vec<f32>, RibbleWhisperError> {
     let mut samples = vec![];
     let mut sample_buf = None;
 
     loop {
+        if !run_transcription.load(Ordering::Acquire) {
+            return Err(RibbleWhisperError::Cancelled);
+        }
         let next_packet = reader.next_packet();
         // This is the only recoverable error - and only applies to chained OGG
         // For all other containers, afaik, this can be "The end" of the stream
@@ -167,9 +320,11 @@ fn decode_loop(
                 }
                 let channels = audio_buffer.spec().channels.iter().count();
                 if channels > 2 {
-                    return Err(RibbleWhisperError::ParameterError(format!(
-                        "Only Stereo/Mono audio supported. Number of channels: {channels}"
-                    )));
+                    return Err(RibbleWhisperError::AudioFormat {
+                        reason: format!(
+                            "Only Stereo/Mono audio supported. Number of channels: {channels}"
+                        ),
+                    });
                 }
 
                 let in_mono = channels == 1;
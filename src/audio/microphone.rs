@@ -1,8 +1,19 @@
-use crate::audio::recorder::{Recorder, SampleSink};
+#[cfg(feature = "cpal")]
+use crate::audio::audio_backend::AUDIO_BUFFER_SIZE;
+#[cfg(feature = "sdl2")]
+use crate::audio::recorder::Recorder;
+#[cfg(any(feature = "sdl2", feature = "cpal"))]
+use crate::audio::recorder::SampleSink;
+#[cfg(feature = "cpal")]
+use crate::utils::logging::log_warn_or_eprint;
+#[cfg(feature = "cpal")]
+use cpal::traits::StreamTrait;
 #[cfg(feature = "sdl2")]
 use sdl2::audio::AudioDevice;
 #[cfg(feature = "sdl2")]
 use sdl2::audio::AudioFormat;
+#[cfg(feature = "cpal")]
+use std::marker::PhantomData;
 
 // TODO: document/rename
 // NOTE: this is just a quick little adapter for SDL's AudioFormat, filtering out all
@@ -11,7 +22,7 @@ use sdl2::audio::AudioFormat;
 // but afaik it's not a guarantee.
 
 // This is to be used mainly for getting information about whether to build a fallback device.
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum RibbleAudioFormat {
     F32,
     I16,
@@ -42,14 +53,24 @@ impl From<AudioFormat> for RibbleAudioFormat {
     }
 }
 
-/// Trait for starting/stopping audio capture.
+/// Trait for starting/stopping audio capture, and for querying the format the device was opened
+/// with. Backends may not grant the requested [CaptureSpec](crate::audio::audio_backend::CaptureSpec),
+/// so these report what was actually obtained.
 pub trait MicCapture {
     fn play(&self);
     fn pause(&self);
+    /// The sample rate of the captured audio, in Hz.
     fn sample_rate(&self) -> usize;
     fn format(&self) -> RibbleAudioFormat;
+    /// The number of interleaved channels in the captured audio.
     fn channels(&self) -> u8;
+    /// The number of sample frames delivered to the sink per callback, (i.e. per channel).
     fn buffer_size(&self) -> usize;
+    /// The number of samples, (across all channels), delivered to the sink per callback, e.g.
+    /// for preallocating per-chunk buffers.
+    fn buffer_samples(&self) -> usize {
+        self.buffer_size() * self.channels() as usize
+    }
 }
 
 #[cfg(feature = "sdl2")]
@@ -57,12 +78,14 @@ pub struct Sdl2Capture<S: SampleSink> {
     device: AudioDevice<Recorder<S>>,
 }
 
+#[cfg(feature = "sdl2")]
 impl<S: SampleSink> Sdl2Capture<S> {
     pub fn new(device: AudioDevice<Recorder<S>>) -> Self {
         Self { device }
     }
 }
 
+#[cfg(feature = "sdl2")]
 impl<S: SampleSink> MicCapture for Sdl2Capture<S> {
     fn play(&self) {
         self.device.resume()
@@ -84,5 +107,72 @@ impl<S: SampleSink> MicCapture for Sdl2Capture<S> {
     }
 }
 
-// Eventual TODO: other backends
-// e.g. CpalCapture...
+#[cfg(feature = "cpal")]
+impl From<cpal::SampleFormat> for RibbleAudioFormat {
+    fn from(value: cpal::SampleFormat) -> Self {
+        match value {
+            cpal::SampleFormat::F32 => RibbleAudioFormat::F32,
+            cpal::SampleFormat::I16 => RibbleAudioFormat::I16,
+            _ => RibbleAudioFormat::Invalid,
+        }
+    }
+}
+
+/// A capture stream opened by [CpalBackend](crate::audio::audio_backend::CpalBackend).
+/// ***Note: cpal streams cannot be sent across threads on every platform, (e.g. Android);
+/// keep the capture on the thread that opened it.***
+#[cfg(feature = "cpal")]
+pub struct CpalCapture<S: SampleSink> {
+    stream: cpal::Stream,
+    sample_rate: usize,
+    channels: u8,
+    format: RibbleAudioFormat,
+    buffer_size: usize,
+    _sink: PhantomData<S>,
+}
+
+#[cfg(feature = "cpal")]
+impl<S: SampleSink> CpalCapture<S> {
+    pub fn new(stream: cpal::Stream, config: &cpal::StreamConfig) -> Self {
+        let buffer_size = match config.buffer_size {
+            cpal::BufferSize::Fixed(frames) => frames as usize,
+            cpal::BufferSize::Default => AUDIO_BUFFER_SIZE,
+        };
+        Self {
+            stream,
+            sample_rate: config.sample_rate.0 as usize,
+            channels: config.channels as u8,
+            format: <S::Sample as cpal::SizedSample>::FORMAT.into(),
+            buffer_size,
+            _sink: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "cpal")]
+impl<S: SampleSink> MicCapture for CpalCapture<S> {
+    fn play(&self) {
+        if let Err(e) = self.stream.play() {
+            log_warn_or_eprint!("Failed to start cpal capture: {e}");
+        }
+    }
+    fn pause(&self) {
+        if let Err(e) = self.stream.pause() {
+            log_warn_or_eprint!("Failed to pause cpal capture: {e}");
+        }
+    }
+    fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+    fn format(&self) -> RibbleAudioFormat {
+        self.format
+    }
+    fn channels(&self) -> u8 {
+        self.channels
+    }
+    /// cpal doesn't report the buffer size it obtained, so this is the requested period, (or
+    /// [AUDIO_BUFFER_SIZE] if none was requested); callbacks may deliver fewer frames.
+    fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+}
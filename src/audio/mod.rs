@@ -1,13 +1,29 @@
 use std::sync::Arc;
 
+#[cfg(feature = "serde")]
+pub mod archive;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod audio_backend;
 pub mod audio_ring_buffer;
+pub mod buffer_pool;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod capture_check;
+pub mod dsp;
+pub mod excerpt;
 pub mod loading;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod microphone;
+#[cfg(all(feature = "opus", not(target_arch = "wasm32")))]
+pub mod opus_ingest;
 pub mod pcm;
+#[cfg(all(feature = "playback", not(target_arch = "wasm32")))]
+pub mod playback;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod recorder;
 #[cfg(feature = "resampler")]
 pub mod resampler;
+#[cfg(all(feature = "rtp", not(target_arch = "wasm32")))]
+pub mod rtp;
 
 /// Encapsulates a slice of (supported-format) audio for whisper transcription.
 #[derive(Clone)]
@@ -28,6 +44,7 @@ impl WhisperAudioSample {
 }
 
 /// Encapsulates supported channel configurations
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 #[derive(Copy, Clone, PartialEq)]
 pub enum AudioChannelConfiguration {
     Mono,
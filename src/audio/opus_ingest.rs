@@ -0,0 +1,305 @@
+//! Ingest for Opus-compressed audio, (e.g. forwarded from WebRTC or VoIP services), so that a
+//! captioning server can feed the realtime pipeline without transcoding to PCM first.
+//! Requires the opus feature, which links libopus.
+//!
+//! [OpusIngest] decodes raw Opus packets, (e.g. RTP payloads), and [OggOpusIngest] decodes an
+//! Ogg Opus stream as its bytes arrive, (e.g. from a socket or an HTTP body). Both push 16 kHz
+//! mono audio into an [AudioRingBuffer] shared with a
+//! [RealtimeTranscriber](crate::transcriber::realtime_transcriber::RealtimeTranscriber).
+//! libopus decodes directly at 16 kHz and downmixes stereo streams, so no resampler is needed.
+
+use std::collections::VecDeque;
+use std::io::Read;
+
+use opus::{Channels, Decoder};
+
+use crate::audio::audio_ring_buffer::AudioRingBuffer;
+use crate::transcriber::WHISPER_SAMPLE_RATE;
+use crate::utils::errors::RibbleWhisperError;
+
+// The longest Opus packet, (120 ms), in samples at 16 kHz.
+const MAX_PACKET_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize * 120 / 1000;
+// Opus timestamps, (e.g. the pre-skip), are always measured at 48 kHz.
+const OPUS_RATE_RATIO: usize = 48000 / WHISPER_SAMPLE_RATE as usize;
+const OGG_CAPTURE_PATTERN: &[u8; 4] = b"OggS";
+const OGG_HEADER_LEN: usize = 27;
+const OPUS_HEAD_MAGIC: &[u8; 8] = b"OpusHead";
+const OPUS_TAGS_MAGIC: &[u8; 8] = b"OpusTags";
+const READ_CHUNK_LEN: usize = 4096;
+
+/// Decodes Opus packets and pushes the audio into an [AudioRingBuffer], (see:
+/// [crate::audio::opus_ingest]).
+pub struct OpusIngest {
+    decoder: Decoder,
+    buffer: AudioRingBuffer<f32>,
+    scratch: Vec<f32>,
+    // Decoded samples still to be discarded from the start of the stream.
+    pre_skip: usize,
+    // The length of the last decoded packet, for concealing lost packets.
+    last_packet_len: usize,
+}
+
+impl OpusIngest {
+    /// Returns Err if the buffer's sample rate isn't 16 kHz, or libopus fails to initialize.
+    pub fn new(buffer: AudioRingBuffer<f32>) -> Result<Self, RibbleWhisperError> {
+        if buffer.get_sample_rate() != WHISPER_SAMPLE_RATE as usize {
+            return Err(RibbleWhisperError::ParameterError(format!(
+                "OpusIngest requires a {WHISPER_SAMPLE_RATE} Hz buffer, got: {} Hz.",
+                buffer.get_sample_rate()
+            )));
+        }
+        Ok(Self {
+            decoder: Decoder::new(WHISPER_SAMPLE_RATE as u32, Channels::Mono)?,
+            buffer,
+            scratch: vec![0.0; MAX_PACKET_SAMPLES],
+            pre_skip: 0,
+            last_packet_len: 0,
+        })
+    }
+
+    /// Discards the given number of samples, (at 48 kHz, as Opus headers measure them), from the
+    /// start of the stream; the encoder's priming audio is not meant to be heard.
+    pub fn with_pre_skip(mut self, pre_skip: usize) -> Self {
+        self.pre_skip = pre_skip / OPUS_RATE_RATIO;
+        self
+    }
+
+    /// Decodes a packet and pushes its audio. Returns the number of samples pushed.
+    pub fn push_packet(&mut self, packet: &[u8]) -> Result<usize, RibbleWhisperError> {
+        let decoded = self
+            .decoder
+            .decode_float(packet, &mut self.scratch, false)?;
+        self.last_packet_len = decoded;
+        Ok(self.push_decoded(decoded))
+    }
+
+    /// Conceals a lost packet, (e.g. a gap in RTP sequence numbers), by pushing audio
+    /// extrapolated from the previous packet. Returns the number of samples pushed; nothing is
+    /// pushed before the first packet.
+    pub fn push_lost_packet(&mut self) -> Result<usize, RibbleWhisperError> {
+        if self.last_packet_len == 0 {
+            return Ok(0);
+        }
+        let decoded =
+            self.decoder
+                .decode_float(&[], &mut self.scratch[..self.last_packet_len], false)?;
+        Ok(self.push_decoded(decoded))
+    }
+
+    /// Resets the decoder before decoding an unrelated stream.
+    pub fn reset(&mut self) -> Result<(), RibbleWhisperError> {
+        self.decoder.reset_state()?;
+        self.pre_skip = 0;
+        self.last_packet_len = 0;
+        Ok(())
+    }
+
+    fn push_decoded(&mut self, decoded: usize) -> usize {
+        let skipped = self.pre_skip.min(decoded);
+        self.pre_skip -= skipped;
+        self.buffer.push_audio(&self.scratch[skipped..decoded]);
+        decoded - skipped
+    }
+}
+
+/// A packet from an Ogg stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OggPacket {
+    /// The serial number of the logical stream the packet belongs to.
+    pub serial: u32,
+    pub data: Vec<u8>,
+}
+
+/// Splits Ogg pages into packets as their bytes arrive. Bytes that aren't part of a page are
+/// skipped until the next page starts, so a stream can be joined partway through.
+/// Page checksums are not verified; transports are expected to deliver bytes intact.
+#[derive(Clone, Debug, Default)]
+pub struct OggDemuxer {
+    pending: Vec<u8>,
+    // A packet continued on the next page, and the stream it belongs to.
+    partial: Option<OggPacket>,
+    packets: VecDeque<OggPacket>,
+}
+
+impl OggDemuxer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends bytes from the stream and splits every complete page in them into packets.
+    pub fn push_bytes(&mut self, bytes: &[u8]) {
+        let mut pending = std::mem::take(&mut self.pending);
+        pending.extend_from_slice(bytes);
+        let mut consumed = 0;
+        while let Some(page_len) = self.parse_page(&pending[consumed..]) {
+            consumed += page_len;
+        }
+        pending.drain(..consumed);
+        self.pending = pending;
+    }
+
+    /// Takes the next complete packet, if any.
+    pub fn next_packet(&mut self) -> Option<OggPacket> {
+        self.packets.pop_front()
+    }
+
+    // Parses the page at the start of bytes, (after any bytes that aren't a page), and returns the
+    // number of bytes consumed; None if the page is incomplete.
+    fn parse_page(&mut self, bytes: &[u8]) -> Option<usize> {
+        let Some(start) = bytes
+            .windows(OGG_CAPTURE_PATTERN.len())
+            .position(|window| window == OGG_CAPTURE_PATTERN)
+        else {
+            // Keep a possible partial capture pattern at the end.
+            let skipped = bytes.len().saturating_sub(OGG_CAPTURE_PATTERN.len() - 1);
+            return (skipped > 0).then_some(skipped);
+        };
+        if start > 0 {
+            return Some(start);
+        }
+        if bytes.len() < OGG_HEADER_LEN {
+            return None;
+        }
+        // Only version 0 exists; anything else is a false capture pattern.
+        if bytes[4] != 0 {
+            return Some(1);
+        }
+        let continued = bytes[5] & 0x01 != 0;
+        let serial = u32::from_le_bytes([bytes[14], bytes[15], bytes[16], bytes[17]]);
+        let n_segments = bytes[26] as usize;
+        let body_start = OGG_HEADER_LEN + n_segments;
+        let lacing = bytes.get(OGG_HEADER_LEN..body_start)?;
+        let body_len: usize = lacing.iter().map(|&len| len as usize).sum();
+        let body = bytes.get(body_start..body_start + body_len)?;
+
+        // Pages from other streams, (e.g. multiplexed video), don't interrupt a partial packet.
+        let mut packet = match self.partial.take_if(|partial| partial.serial == serial) {
+            Some(partial) if continued => partial,
+            _ => OggPacket {
+                serial,
+                data: vec![],
+            },
+        };
+        // A continued page without its first half, (e.g. after joining partway through), is
+        // dropped up to its first packet boundary.
+        let mut discard = continued && packet.data.is_empty();
+        let mut offset = 0;
+        for &len in lacing {
+            let len = len as usize;
+            if !discard {
+                packet.data.extend_from_slice(&body[offset..offset + len]);
+            }
+            offset += len;
+            if len < 255 {
+                if !discard {
+                    self.packets.push_back(std::mem::replace(
+                        &mut packet,
+                        OggPacket {
+                            serial,
+                            data: vec![],
+                        },
+                    ));
+                }
+                discard = false;
+            }
+        }
+        if !discard && !packet.data.is_empty() {
+            self.partial = Some(packet);
+        }
+        Some(body_start + body_len)
+    }
+}
+
+/// Decodes an Ogg Opus stream as its bytes arrive and pushes the audio into an
+/// [AudioRingBuffer], (see: [crate::audio::opus_ingest]).
+///
+/// Only the first Opus stream in the Ogg container is decoded; a chained stream, (i.e. a new
+/// OpusHead), replaces it. Multichannel streams, (channel mapping families other than 0), are
+/// not supported.
+pub struct OggOpusIngest {
+    ingest: OpusIngest,
+    demuxer: OggDemuxer,
+    // The serial number of the stream being decoded, once its OpusHead has been read.
+    serial: Option<u32>,
+    read_tags: bool,
+}
+
+impl OggOpusIngest {
+    /// Returns Err if the buffer's sample rate isn't 16 kHz, or libopus fails to initialize.
+    pub fn new(buffer: AudioRingBuffer<f32>) -> Result<Self, RibbleWhisperError> {
+        Ok(Self {
+            ingest: OpusIngest::new(buffer)?,
+            demuxer: OggDemuxer::new(),
+            serial: None,
+            read_tags: false,
+        })
+    }
+
+    /// Appends bytes from the stream and decodes every complete packet in them. Returns the
+    /// number of samples pushed.
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Result<usize, RibbleWhisperError> {
+        self.demuxer.push_bytes(bytes);
+        let mut pushed = 0;
+        while let Some(packet) = self.demuxer.next_packet() {
+            pushed += self.push_packet(packet)?;
+        }
+        Ok(pushed)
+    }
+
+    /// Reads the stream until it ends, decoding as bytes arrive. Returns the number of samples
+    /// pushed.
+    pub fn read_from<R: Read>(&mut self, mut reader: R) -> Result<usize, RibbleWhisperError> {
+        let mut chunk = [0u8; READ_CHUNK_LEN];
+        let mut pushed = 0;
+        loop {
+            match reader.read(&mut chunk) {
+                Ok(0) => return Ok(pushed),
+                Ok(n) => pushed += self.push_bytes(&chunk[..n])?,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn push_packet(&mut self, packet: OggPacket) -> Result<usize, RibbleWhisperError> {
+        if packet.data.starts_with(OPUS_HEAD_MAGIC) {
+            let pre_skip = parse_opus_head(&packet.data)?;
+            self.ingest.reset()?;
+            self.ingest.pre_skip = pre_skip / OPUS_RATE_RATIO;
+            self.serial = Some(packet.serial);
+            self.read_tags = false;
+            return Ok(0);
+        }
+        if self.serial != Some(packet.serial) {
+            return Ok(0);
+        }
+        if !self.read_tags {
+            self.read_tags = true;
+            if packet.data.starts_with(OPUS_TAGS_MAGIC) {
+                return Ok(0);
+            }
+        }
+        self.ingest.push_packet(&packet.data)
+    }
+}
+
+// Validates an OpusHead packet and returns its pre-skip, (see: RFC 7845, section 5.1).
+fn parse_opus_head(head: &[u8]) -> Result<usize, RibbleWhisperError> {
+    if head.len() < 19 {
+        return Err(RibbleWhisperError::AudioFormat {
+            reason: format!("OpusHead is too short: {} bytes", head.len()),
+        });
+    }
+    // The major version lives in the upper four bits; only version 0 is defined.
+    if head[8] >> 4 != 0 {
+        return Err(RibbleWhisperError::AudioFormat {
+            reason: format!("Unsupported Ogg Opus version: {}", head[8]),
+        });
+    }
+    if head[18] != 0 {
+        return Err(RibbleWhisperError::AudioFormat {
+            reason: format!("Unsupported Opus channel mapping family: {}", head[18]),
+        });
+    }
+    Ok(u16::from_le_bytes([head[10], head[11]]) as usize)
+}
@@ -1,3 +1,6 @@
+use crate::audio::dsp;
+use crate::utils::errors::RibbleWhisperError;
+
 /// A simple trait for round-trip conversion between i16 PCM audio and the original signal format
 pub trait PcmS16Convertible: IntoPcmS16 + FromPcmS16 {}
 
@@ -63,3 +66,37 @@ impl FromPcmS16 for f64 {
         sample as f64 / i16::MAX as f64
     }
 }
+
+/// Converts i16 PCM audio to f32 audio in [-1.0, 1.0).
+/// Equivalent to whisper_rs::convert_integer_to_float_audio, but also available on wasm32.
+pub fn convert_integer_to_float_audio(samples: &[i16]) -> Vec<f32> {
+    let mut float_samples = vec![0.0; samples.len()];
+    dsp::integer_to_float(samples, &mut float_samples);
+    float_samples
+}
+
+/// Converts f32 audio to i16 PCM, clamping samples to [-1.0, 1.0].
+/// Equivalent to mapping each sample through [IntoPcmS16].
+pub fn convert_float_to_integer_audio(samples: &[f32]) -> Vec<i16> {
+    let mut int_samples = vec![0; samples.len()];
+    dsp::float_to_integer(samples, &mut int_samples);
+    int_samples
+}
+
+/// Downmixes interleaved stereo f32 audio to mono by averaging each pair of samples.
+/// Equivalent to whisper_rs::convert_stereo_to_mono_audio, but also available on wasm32.
+/// Returns Err if the audio has an odd number of samples.
+pub fn convert_stereo_to_mono_audio(samples: &[f32]) -> Result<Vec<f32>, RibbleWhisperError> {
+    if !samples.len().is_multiple_of(2) {
+        return Err(RibbleWhisperError::AudioFormat {
+            reason: format!(
+                "Stereo audio has an odd number of samples: {}",
+                samples.len()
+            ),
+        });
+    }
+    Ok(samples
+        .chunks_exact(2)
+        .map(|pair| (pair[0] + pair[1]) / 2.0)
+        .collect())
+}
@@ -0,0 +1,185 @@
+use std::ops::Range;
+#[cfg(feature = "resampler")]
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use rodio::buffer::SamplesBuffer;
+use rodio::{OutputStream, OutputStreamHandle, Sink};
+
+#[cfg(feature = "resampler")]
+use crate::audio::loading::load_normalized_audio_file;
+use crate::audio::pcm::convert_integer_to_float_audio;
+use crate::audio::{AudioChannelConfiguration, WhisperAudioSample};
+use crate::transcriber::{RibbleWhisperSegment, WHISPER_SAMPLE_RATE};
+use crate::utils::errors::RibbleWhisperError;
+
+/// Plays back recorded audio, (e.g. a session recording), for reviewing transcripts.
+///
+/// Playback can start from any position, or be limited to the audio of a single segment with
+/// [Playback::play_segment], for "click a transcript line to hear it" features. Segment
+/// timestamps are relative to the audio that was transcribed, so segments from an offline
+/// transcription with voice activity detection will not line up with the original recording.
+///
+/// The output stream is not Send on all platforms, so keep the playback on the thread that
+/// created it. Requires the playback feature flag to be set.
+pub struct Playback {
+    // Dropping the stream stops playback.
+    _stream: OutputStream,
+    _handle: OutputStreamHandle,
+    sink: Sink,
+    samples: Arc<[f32]>,
+    sample_rate: u32,
+    channels: u16,
+    // The position of the current excerpt within the audio.
+    excerpt_start: Mutex<Duration>,
+}
+
+impl Playback {
+    /// Opens the default output device for interleaved audio at the given sample rate.
+    /// Playback starts paused.
+    /// # Returns:
+    /// * Ok(Self) on success, Err if there is no output device or the sample rate is 0.
+    pub fn new(
+        audio: WhisperAudioSample,
+        sample_rate: u32,
+        channels: AudioChannelConfiguration,
+    ) -> Result<Self, RibbleWhisperError> {
+        if sample_rate == 0 {
+            return Err(RibbleWhisperError::ParameterError(
+                "Playback sample rate must be greater than 0.".to_string(),
+            ));
+        }
+        let samples = match audio {
+            WhisperAudioSample::F32(samples) => samples,
+            WhisperAudioSample::I16(samples) => Arc::from(convert_integer_to_float_audio(&samples)),
+        };
+        let channels = match channels {
+            AudioChannelConfiguration::Mono => 1,
+            AudioChannelConfiguration::Stereo => 2,
+        };
+
+        let (stream, handle) = OutputStream::try_default()
+            .map_err(|e| RibbleWhisperError::DeviceError(format!("No output device: {e}")))?;
+        let sink = Sink::try_new(&handle).map_err(|e| {
+            RibbleWhisperError::DeviceError(format!("Failed to open audio playback: {e}"))
+        })?;
+        sink.pause();
+
+        let playback = Self {
+            _stream: stream,
+            _handle: handle,
+            sink,
+            samples,
+            sample_rate,
+            channels,
+            excerpt_start: Mutex::new(Duration::ZERO),
+        };
+        playback.queue(Duration::ZERO, None);
+        Ok(playback)
+    }
+
+    /// Plays back audio prepared for whisper: 16 kHz mono, (e.g. the audio passed to a transcriber).
+    pub fn from_whisper_audio(audio: WhisperAudioSample) -> Result<Self, RibbleWhisperError> {
+        Self::new(
+            audio,
+            WHISPER_SAMPLE_RATE as u32,
+            AudioChannelConfiguration::Mono,
+        )
+    }
+
+    /// Loads a recording, (e.g. a session WAV), resampled to 16 kHz mono so that it lines up
+    /// with the timestamps of its transcription. Requires the resampler feature flag to be set.
+    #[cfg(feature = "resampler")]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, RibbleWhisperError> {
+        Self::from_whisper_audio(load_normalized_audio_file(path, None::<fn(usize)>)?)
+    }
+
+    // Converts a time range to an (interleaved) sample range, clamped to the audio.
+    fn sample_range(&self, start: Duration, end: Option<Duration>) -> Range<usize> {
+        let channels = self.channels as usize;
+        let to_index = |time: Duration| {
+            let frame = (time.as_secs_f64() * self.sample_rate as f64) as usize;
+            (frame * channels).min(self.samples.len())
+        };
+        let start = to_index(start);
+        let end = end.map_or(self.samples.len(), to_index).max(start);
+        start..end
+    }
+
+    // Replaces whatever is queued with the audio between start and end, keeping the pause state.
+    fn queue(&self, start: Duration, end: Option<Duration>) {
+        let paused = self.sink.is_paused();
+        let range = self.sample_range(start, end);
+        let excerpt_start = Duration::from_secs_f64(
+            (range.start / self.channels as usize) as f64 / self.sample_rate as f64,
+        );
+        // Clearing also pauses the sink.
+        self.sink.clear();
+        self.sink.append(SamplesBuffer::new(
+            self.channels,
+            self.sample_rate,
+            self.samples[range].to_vec(),
+        ));
+        *self.excerpt_start.lock() = excerpt_start;
+        if !paused {
+            self.sink.play();
+        }
+    }
+
+    pub fn play(&self) {
+        self.sink.play();
+    }
+
+    pub fn pause(&self) {
+        self.sink.pause();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.sink.is_paused()
+    }
+
+    /// Returns true once the current excerpt has finished playing.
+    pub fn is_finished(&self) -> bool {
+        self.sink.empty()
+    }
+
+    /// Sets the volume, where 1.0 is the recorded volume.
+    pub fn set_volume(&self, volume: f32) {
+        self.sink.set_volume(volume.max(0.0));
+    }
+
+    /// The length of the audio.
+    pub fn duration(&self) -> Duration {
+        let frames = self.samples.len() / self.channels as usize;
+        Duration::from_secs_f64(frames as f64 / self.sample_rate as f64)
+    }
+
+    /// The playback position within the audio.
+    pub fn position(&self) -> Duration {
+        let position = *self.excerpt_start.lock() + self.sink.get_pos();
+        position.min(self.duration())
+    }
+
+    /// Continues playback, (or the paused state), from the given position to the end of the audio.
+    pub fn seek(&self, position: Duration) {
+        self.queue(position, None);
+    }
+
+    /// Plays the audio between start and end, then stops. None plays to the end of the audio.
+    pub fn play_range(&self, start: Duration, end: Option<Duration>) {
+        self.queue(start, end);
+        self.sink.play();
+    }
+
+    /// Plays the audio of a transcribed segment, then stops.
+    pub fn play_segment(&self, segment: &RibbleWhisperSegment) {
+        // Segment timestamps are in centiseconds.
+        let to_duration = |timestamp: i64| Duration::from_millis(timestamp.max(0) as u64 * 10);
+        self.play_range(
+            to_duration(segment.start_timestamp()),
+            Some(to_duration(segment.end_timestamp())),
+        );
+    }
+}
@@ -1,5 +1,9 @@
 use crate::audio::audio_ring_buffer::AudioRingBuffer;
+use crate::audio::buffer_pool::{ArcBufferPool, BufferPoolStats};
 use crate::utils::Sender;
+use crate::utils::logging::log_warn_or_eprint;
+use crate::utils::telemetry::record_buffer_overrun;
+#[cfg(feature = "sdl2")]
 use sdl2::audio::{AudioCallback, AudioFormatNum};
 use std::error::Error;
 use std::sync::Arc;
@@ -7,13 +11,41 @@ use std::thread::sleep;
 
 const SLEEP_MILLIS: u64 = 100;
 
+/// Trait alias for the sample formats SDL2 can capture; unbounded without the sdl2 feature.
+#[cfg(feature = "sdl2")]
+pub trait Sdl2Sample: AudioFormatNum {}
+#[cfg(feature = "sdl2")]
+impl<T: AudioFormatNum> Sdl2Sample for T {}
+#[cfg(not(feature = "sdl2"))]
+pub trait Sdl2Sample {}
+#[cfg(not(feature = "sdl2"))]
+impl<T> Sdl2Sample for T {}
+
+/// Trait alias for the sample formats cpal can capture; unbounded without the cpal feature.
+#[cfg(feature = "cpal")]
+pub trait CpalSample: cpal::SizedSample {}
+#[cfg(feature = "cpal")]
+impl<T: cpal::SizedSample> CpalSample for T {}
+#[cfg(not(feature = "cpal"))]
+pub trait CpalSample {}
+#[cfg(not(feature = "cpal"))]
+impl<T> CpalSample for T {}
+
 /// Trait alias to unify Audio formats to meet the bounds of Audio Backends.
 pub trait RecorderSample:
-    Default + Copy + AudioFormatNum + voice_activity_detector::Sample + Send + Sync + 'static
+    Default + Copy + Sdl2Sample + CpalSample + voice_activity_detector::Sample + Send + Sync + 'static
 {
 }
-impl<T: Default + Copy + AudioFormatNum + voice_activity_detector::Sample + Send + Sync + 'static>
-    RecorderSample for T
+impl<
+    T: Default
+        + Copy
+        + Sdl2Sample
+        + CpalSample
+        + voice_activity_detector::Sample
+        + Send
+        + Sync
+        + 'static,
+> RecorderSample for T
 {
 }
 
@@ -34,6 +66,7 @@ impl<S: SampleSink> Recorder<S> {
     }
 }
 
+#[cfg(feature = "sdl2")]
 impl<S: SampleSink> AudioCallback for Recorder<S> {
     type Channel = S::Sample;
 
@@ -57,18 +90,33 @@ impl<T: RecorderSample> RingBufSink<T> {
 pub struct ArcChannelSink<T> {
     channel: Sender<Arc<[T]>>,
     logged_disconnect: bool,
+    pool: Option<ArcBufferPool<T>>,
 }
 impl<T: RecorderSample> ArcChannelSink<T> {
     pub fn new(sender: Sender<Arc<[T]>>) -> Self {
         Self {
             channel: sender,
             logged_disconnect: false,
+            pool: None,
         }
     }
 
+    /// Reuses up to capacity buffers once their consumers drop them, instead of allocating a new
+    /// buffer per audio callback, to reduce allocator pressure during long capture sessions.
+    /// See: [ArcBufferPool]
+    pub fn with_buffer_pool(mut self, capacity: usize) -> Self {
+        self.pool = Some(ArcBufferPool::new(capacity));
+        self
+    }
+
     pub fn is_disconnected(&self) -> bool {
         self.logged_disconnect
     }
+
+    /// Returns how often the buffer pool was able to reuse a buffer, if the sink has one.
+    pub fn buffer_pool_stats(&self) -> Option<BufferPoolStats> {
+        self.pool.as_ref().map(ArcBufferPool::stats)
+    }
 }
 /// Pushes audio out using a message queue to fan out data as `Vec<T>`
 /// Use only if vectors are required in further processing, otherwise prefer the ArcChannelSink.
@@ -103,7 +151,11 @@ impl<T: RecorderSample> SampleSink for ArcChannelSink<T> {
     /// NOTE: Due to synchronization difficulties, this can log false positives if the sink is still
     /// in scope and has not yet been paused. This is most likely to occur when transcription finishes.
     fn push(&mut self, data: &[Self::Sample]) {
-        if let Err(e) = self.channel.try_send(Arc::from(data)) {
+        let samples = match self.pool.as_mut() {
+            Some(pool) => pool.copy_from_slice(data),
+            None => Arc::from(data),
+        };
+        if let Err(e) = self.channel.try_send(samples) {
             #[cfg(feature = "crossbeam")]
             let disconnected = e.is_disconnected();
             #[cfg(not(feature = "crossbeam"))]
@@ -112,38 +164,19 @@ impl<T: RecorderSample> SampleSink for ArcChannelSink<T> {
             if disconnected {
                 if !self.logged_disconnect {
                     self.logged_disconnect = true;
-                    #[cfg(feature = "ribble-logging")]
-                    {
-                        log::warn!("Arc Recorder channel disconnected!");
-                    }
-                    #[cfg(not(feature = "ribble-logging"))]
-                    {
-                        eprintln!("Arc Recorder channel disconnected!");
-                    }
+                    log_warn_or_eprint!("Arc Recorder channel disconnected!");
                 }
                 sleep(std::time::Duration::from_millis(SLEEP_MILLIS));
                 return;
             }
-            #[cfg(feature = "ribble-logging")]
-            {
-                log::warn!(
-                    "Failed to send audio data over recorder channel.\n\
-                    Error: {}\n\
-                    Error source:{:#?}",
-                    &e,
-                    e.source()
-                );
-            }
-            #[cfg(not(feature = "ribble-logging"))]
-            {
-                eprintln!(
-                    "Failed to send audio data over recorder channel.\n\
-                    Error: {}\n\
-                    Error source:{:#?}",
-                    &e,
-                    e.source()
-                );
-            }
+            record_buffer_overrun("recorder_channel");
+            log_warn_or_eprint!(
+                "Failed to send audio data over recorder channel.\n\
+                Error: {}\n\
+                Error source:{:#?}",
+                &e,
+                e.source()
+            );
         }
     }
 }
@@ -160,38 +193,19 @@ impl<T: RecorderSample> SampleSink for VecChannelSink<T> {
             if disconnected {
                 if !self.logged_disconnect {
                     self.logged_disconnect = true;
-                    #[cfg(feature = "ribble-logging")]
-                    {
-                        log::warn!("Vec Recorder channel disconnected!");
-                    }
-                    #[cfg(not(feature = "ribble-logging"))]
-                    {
-                        eprintln!("Vec Recorder channel disconnected!");
-                    }
+                    log_warn_or_eprint!("Vec Recorder channel disconnected!");
                 }
                 sleep(std::time::Duration::from_millis(SLEEP_MILLIS));
                 return;
             }
-            #[cfg(feature = "ribble-logging")]
-            {
-                log::warn!(
-                    "Failed to send audio data over recorder channel.\n\
-                    Error: {}\n\
-                    Error source:{:#?}",
-                    &e,
-                    e.source()
-                );
-            }
-            #[cfg(not(feature = "ribble-logging"))]
-            {
-                eprintln!(
-                    "Failed to send audio data over recorder channel.\n\
-                    Error: {}\n\
-                    Error source:{:#?}",
-                    &e,
-                    e.source()
-                );
-            }
+            record_buffer_overrun("recorder_channel");
+            log_warn_or_eprint!(
+                "Failed to send audio data over recorder channel.\n\
+                Error: {}\n\
+                Error source:{:#?}",
+                &e,
+                e.source()
+            );
         };
     }
 }
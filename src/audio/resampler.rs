@@ -8,6 +8,7 @@ use symphonia::core::formats::Track;
 use symphonia::core::io::MediaSourceStream;
 use symphonia::core::probe::Hint;
 
+use crate::audio::pcm::{convert_integer_to_float_audio, convert_stereo_to_mono_audio};
 use crate::audio::WhisperAudioSample;
 use crate::transcriber;
 use crate::utils::errors::RibbleWhisperError;
@@ -35,9 +36,9 @@ pub fn resample(
     num_channels: usize,
 ) -> Result<WhisperAudioSample, RibbleWhisperError> {
     if num_channels == 0 {
-        return Err(RibbleWhisperError::ParameterError(
-            "Zero channels.".to_owned(),
-        ));
+        return Err(RibbleWhisperError::AudioFormat {
+            reason: "Zero channels.".to_owned(),
+        });
     }
     let params = SincInterpolationParameters {
         sinc_len: 256,
@@ -48,11 +49,7 @@ pub fn resample(
     };
 
     let samples_to_process = match samples {
-        ResampleableAudio::I16(audio_in) => {
-            let mut output = vec![0.0f32; audio_in.len()];
-            whisper_rs::convert_integer_to_float_audio(audio_in, &mut output)?;
-            output
-        }
+        ResampleableAudio::I16(audio_in) => convert_integer_to_float_audio(audio_in),
         ResampleableAudio::F32(audio_in) => audio_in.to_vec(),
         ResampleableAudio::F64(audio_in) => audio_in.iter().map(|s| *s as f32).collect(),
     };
@@ -103,7 +100,7 @@ fn resample_stereo(
     Ok(WhisperAudioSample::F32(Arc::from(interleaved)))
 }
 
-/// Normalizes audio to sample at 16kHz. For use with whisper.
+/// Normalizes audio to 16kHz mono. For use with whisper.
 /// # Arguments:
 /// * samples: the audio to resample
 /// * in_sample_rate: the original sampling rate
@@ -117,14 +114,18 @@ pub fn normalize_audio(
     num_channels: usize,
 ) -> Result<WhisperAudioSample, RibbleWhisperError> {
     let resampled = resample(samples, 16000., in_sample_rate, num_channels)?;
-    if let WhisperAudioSample::F32(stereo) = resampled {
-        let mono = whisper_rs::convert_stereo_to_mono_audio(&stereo)?;
+    if let WhisperAudioSample::F32(audio) = resampled {
+        // Mono audio is already whisper-ready once resampled.
+        if num_channels == 1 {
+            return Ok(WhisperAudioSample::F32(audio));
+        }
+        let mono = convert_stereo_to_mono_audio(&audio)?;
         Ok(WhisperAudioSample::F32(Arc::from(mono)))
     } else {
         // This should never, ever happen
-        Err(RibbleWhisperError::ParameterError(
-            "Resampling returned invalid audio format".to_owned(),
-        ))
+        Err(RibbleWhisperError::AudioFormat {
+            reason: "Resampling returned invalid audio format".to_owned(),
+        })
     }
 }
 
@@ -141,9 +142,9 @@ pub fn file_needs_normalizing<P: AsRef<Path>>(path: P) -> Result<bool, RibbleWhi
     let format = probed.format;
     let track = format
         .default_track()
-        .ok_or(RibbleWhisperError::ParameterError(
-            "Failed to get default track".to_owned(),
-        ))?;
+        .ok_or(RibbleWhisperError::AudioFormat {
+            reason: "Failed to get default track".to_owned(),
+        })?;
     needs_normalizing(track)
 }
 
@@ -154,8 +155,8 @@ pub fn needs_normalizing(track: &Track) -> Result<bool, RibbleWhisperError> {
     let sample_rate = track
         .codec_params
         .sample_rate
-        .ok_or(RibbleWhisperError::ParameterError(
-            "Failed to get sample rate".to_owned(),
-        ))? as f64;
+        .ok_or(RibbleWhisperError::AudioFormat {
+            reason: "Failed to get sample rate".to_owned(),
+        })? as f64;
     Ok(sample_rate != transcriber::WHISPER_SAMPLE_RATE)
 }
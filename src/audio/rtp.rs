@@ -0,0 +1,544 @@
+//! A network audio source that receives RTP over UDP, (e.g. a SIP or intercom stream), and feeds
+//! an [AudioRingBuffer] shared with a
+//! [RealtimeTranscriber](crate::transcriber::realtime_transcriber::RealtimeTranscriber), without
+//! an external gateway process. Requires the rtp feature.
+//!
+//! Packets are reordered in a [JitterBuffer] before decoding; packets that never arrive are
+//! concealed, (silence for PCM, or libopus' packet loss concealment for Opus), so the timeline
+//! the transcriber sees stays continuous. Only unencrypted RTP is supported, (i.e. not SRTP).
+//!
+//! PCM is downmixed to mono and converted to 16 kHz with linear interpolation, which is adequate
+//! for speech; Opus, (with the opus feature), is decoded at 16 kHz directly.
+
+use std::collections::BTreeMap;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::audio::audio_ring_buffer::AudioRingBuffer;
+#[cfg(feature = "opus")]
+use crate::audio::opus_ingest::OpusIngest;
+use crate::transcriber::WHISPER_SAMPLE_RATE;
+use crate::utils::errors::RibbleWhisperError;
+use crate::utils::logging::log_warn;
+
+/// The default number of packets held for reordering, (about 100 ms of 20 ms packets).
+pub const DEFAULT_JITTER_DEPTH: usize = 5;
+const RTP_VERSION: u8 = 2;
+const RTP_HEADER_LEN: usize = 12;
+// The largest UDP payload.
+const MAX_DATAGRAM_LEN: usize = 65535;
+// How long the receive loop waits for a packet before checking whether to stop, and flushing
+// the jitter buffer.
+const RECV_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// The encoding of the audio in an RTP stream's payloads.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RtpPayloadFormat {
+    /// Uncompressed, big-endian 16-bit PCM, (RFC 3551 L16), at any sample rate.
+    L16 { sample_rate: usize, channels: u8 },
+    /// G.711 μ-law, 8 kHz mono, (the usual SIP telephony codec).
+    Pcmu,
+    /// G.711 A-law, 8 kHz mono.
+    Pcma,
+    /// Opus, (RFC 7587), decoded by [OpusIngest].
+    #[cfg(feature = "opus")]
+    Opus,
+}
+
+impl RtpPayloadFormat {
+    /// The static RTP payload type assigned to the format, (RFC 3551), if any. Other formats use
+    /// a dynamic payload type, (96-127), negotiated out of band, (e.g. by SDP).
+    pub fn static_payload_type(&self) -> Option<u8> {
+        match self {
+            RtpPayloadFormat::Pcmu => Some(0),
+            RtpPayloadFormat::Pcma => Some(8),
+            RtpPayloadFormat::L16 {
+                sample_rate: 44100,
+                channels: 2,
+            } => Some(10),
+            RtpPayloadFormat::L16 {
+                sample_rate: 44100,
+                channels: 1,
+            } => Some(11),
+            _ => None,
+        }
+    }
+
+    fn sample_rate(&self) -> usize {
+        match self {
+            RtpPayloadFormat::L16 { sample_rate, .. } => *sample_rate,
+            RtpPayloadFormat::Pcmu | RtpPayloadFormat::Pcma => 8000,
+            #[cfg(feature = "opus")]
+            RtpPayloadFormat::Opus => WHISPER_SAMPLE_RATE as usize,
+        }
+    }
+}
+
+/// A parsed RTP packet, (RFC 3550), borrowing its payload from the datagram.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RtpPacket<'a> {
+    pub marker: bool,
+    pub payload_type: u8,
+    pub sequence: u16,
+    pub timestamp: u32,
+    pub ssrc: u32,
+    pub payload: &'a [u8],
+}
+
+impl<'a> RtpPacket<'a> {
+    /// Parses a datagram, skipping any CSRCs, header extension and padding.
+    /// Returns Err if the datagram isn't a well-formed RTP version 2 packet.
+    pub fn parse(datagram: &'a [u8]) -> Result<Self, RibbleWhisperError> {
+        let malformed = |reason: &str| RibbleWhisperError::AudioFormat {
+            reason: format!("Malformed RTP packet: {reason}"),
+        };
+        if datagram.len() < RTP_HEADER_LEN {
+            return Err(malformed("too short"));
+        }
+        if datagram[0] >> 6 != RTP_VERSION {
+            return Err(malformed("not version 2"));
+        }
+        let padding = datagram[0] & 0x20 != 0;
+        let extension = datagram[0] & 0x10 != 0;
+        let csrc_count = (datagram[0] & 0x0F) as usize;
+
+        let mut start = RTP_HEADER_LEN + 4 * csrc_count;
+        if extension {
+            let header = datagram
+                .get(start..start + 4)
+                .ok_or_else(|| malformed("truncated header extension"))?;
+            start += 4 + 4 * u16::from_be_bytes([header[2], header[3]]) as usize;
+        }
+        let mut end = datagram.len();
+        if padding {
+            end = end.saturating_sub(datagram[end - 1] as usize);
+        }
+        let payload = datagram
+            .get(start..end)
+            .ok_or_else(|| malformed("header overruns the packet"))?;
+
+        Ok(Self {
+            marker: datagram[1] & 0x80 != 0,
+            payload_type: datagram[1] & 0x7F,
+            sequence: u16::from_be_bytes([datagram[2], datagram[3]]),
+            timestamp: u32::from_be_bytes([datagram[4], datagram[5], datagram[6], datagram[7]]),
+            ssrc: u32::from_be_bytes([datagram[8], datagram[9], datagram[10], datagram[11]]),
+            payload,
+        })
+    }
+}
+
+/// A payload released from a [JitterBuffer], in sequence order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JitterSlot {
+    Packet(Vec<u8>),
+    /// A packet that never arrived.
+    Lost,
+}
+
+/// Reorders packets by sequence number. A packet is released once every packet before it has
+/// been, or once more than depth packets are waiting, (at which point the missing ones are given
+/// up as lost). Duplicate packets and packets arriving after their turn are dropped.
+#[derive(Clone, Debug)]
+pub struct JitterBuffer {
+    depth: usize,
+    // Sequence numbers extended past 16 bits, so that ordering survives wraparound.
+    next: Option<u64>,
+    packets: BTreeMap<u64, Vec<u8>>,
+}
+
+impl JitterBuffer {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            next: None,
+            packets: BTreeMap::new(),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The number of packets waiting to be released.
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+
+    /// Adds a packet. Returns false if it was dropped as a duplicate or as too late.
+    pub fn push(&mut self, sequence: u16, payload: Vec<u8>) -> bool {
+        let next = *self.next.get_or_insert(u64::from(sequence));
+        let offset = sequence.wrapping_sub(next as u16) as i16;
+        let Some(extended) = next.checked_add_signed(offset.into()) else {
+            return false;
+        };
+        if extended < next || self.packets.contains_key(&extended) {
+            return false;
+        }
+        self.packets.insert(extended, payload);
+        true
+    }
+
+    /// Releases the next payload, if its turn has come.
+    pub fn pop(&mut self) -> Option<JitterSlot> {
+        let next = self.next?;
+        if let Some(payload) = self.packets.remove(&next) {
+            self.next = Some(next + 1);
+            return Some(JitterSlot::Packet(payload));
+        }
+        if self.packets.len() > self.depth {
+            self.next = Some(next + 1);
+            return Some(JitterSlot::Lost);
+        }
+        None
+    }
+
+    /// Releases every waiting payload, treating gaps between them as lost, (e.g. once the
+    /// stream goes quiet and the missing packets are clearly not coming).
+    pub fn flush(&mut self) -> Vec<JitterSlot> {
+        let Some(next) = self.next else {
+            return vec![];
+        };
+        let Some((&last, _)) = self.packets.last_key_value() else {
+            return vec![];
+        };
+        let slots = (next..=last)
+            .map(|sequence| {
+                self.packets
+                    .remove(&sequence)
+                    .map_or(JitterSlot::Lost, JitterSlot::Packet)
+            })
+            .collect();
+        self.next = Some(last + 1);
+        slots
+    }
+
+    /// Forgets every packet and the sequence, (e.g. when the stream's source changes).
+    pub fn reset(&mut self) {
+        self.next = None;
+        self.packets.clear();
+    }
+}
+
+// Converts a mono stream from one sample rate to another by linear interpolation, carrying its
+// position across chunks.
+#[derive(Clone, Debug)]
+struct LinearResampler {
+    // Input samples per output sample.
+    step: f64,
+    // The position of the next output sample, relative to the start of the next chunk; -1.0 is
+    // the last sample of the previous chunk.
+    position: f64,
+    previous: f32,
+}
+
+impl LinearResampler {
+    fn new(in_rate: usize, out_rate: usize) -> Self {
+        Self {
+            step: in_rate as f64 / out_rate as f64,
+            position: 0.0,
+            previous: 0.0,
+        }
+    }
+
+    fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        if self.step == 1.0 {
+            output.extend_from_slice(input);
+            return;
+        }
+        let Some(&last) = input.last() else {
+            return;
+        };
+        let sample = |index: isize| match index {
+            -1 => self.previous,
+            index => input[index as usize],
+        };
+        let end = (input.len() - 1) as f64;
+        let mut position = self.position;
+        while position < end {
+            let index = position.floor();
+            let frac = (position - index) as f32;
+            let index = index as isize;
+            output.push(sample(index) * (1.0 - frac) + sample(index + 1) * frac);
+            position += self.step;
+        }
+        self.position = position - input.len() as f64;
+        self.previous = last;
+    }
+}
+
+enum PayloadDecoder {
+    Pcm {
+        format: RtpPayloadFormat,
+        resampler: LinearResampler,
+        mono: Vec<f32>,
+        resampled: Vec<f32>,
+        // The duration of the last packet, in samples at 16 kHz, for concealing lost packets.
+        last_packet_len: usize,
+        buffer: AudioRingBuffer<f32>,
+    },
+    #[cfg(feature = "opus")]
+    Opus(OpusIngest),
+}
+
+impl PayloadDecoder {
+    fn new(
+        format: RtpPayloadFormat,
+        buffer: AudioRingBuffer<f32>,
+    ) -> Result<Self, RibbleWhisperError> {
+        if let RtpPayloadFormat::L16 {
+            sample_rate,
+            channels,
+        } = format
+            && (sample_rate == 0 || channels == 0)
+        {
+            return Err(RibbleWhisperError::ParameterError(format!(
+                "Invalid L16 format: {sample_rate} Hz, {channels} channel(s)."
+            )));
+        }
+        #[cfg(feature = "opus")]
+        if format == RtpPayloadFormat::Opus {
+            return Ok(Self::Opus(OpusIngest::new(buffer)?));
+        }
+        if buffer.get_sample_rate() != WHISPER_SAMPLE_RATE as usize {
+            return Err(RibbleWhisperError::ParameterError(format!(
+                "RtpSource requires a {WHISPER_SAMPLE_RATE} Hz buffer, got: {} Hz.",
+                buffer.get_sample_rate()
+            )));
+        }
+        Ok(Self::Pcm {
+            format,
+            resampler: LinearResampler::new(format.sample_rate(), WHISPER_SAMPLE_RATE as usize),
+            mono: vec![],
+            resampled: vec![],
+            last_packet_len: 0,
+            buffer,
+        })
+    }
+
+    // Decodes a slot into the buffer; returns the number of samples pushed.
+    fn push(&mut self, slot: JitterSlot) -> Result<usize, RibbleWhisperError> {
+        match self {
+            PayloadDecoder::Pcm {
+                format,
+                resampler,
+                mono,
+                resampled,
+                last_packet_len,
+                buffer,
+            } => {
+                resampled.clear();
+                match slot {
+                    JitterSlot::Packet(payload) => {
+                        decode_pcm(*format, &payload, mono);
+                        resampler.process(mono, resampled);
+                        *last_packet_len =
+                            mono.len() * WHISPER_SAMPLE_RATE as usize / format.sample_rate();
+                    }
+                    JitterSlot::Lost => resampled.resize(*last_packet_len, 0.0),
+                }
+                buffer.push_audio(resampled);
+                Ok(resampled.len())
+            }
+            #[cfg(feature = "opus")]
+            PayloadDecoder::Opus(ingest) => match slot {
+                JitterSlot::Packet(payload) => ingest.push_packet(&payload),
+                JitterSlot::Lost => ingest.push_lost_packet(),
+            },
+        }
+    }
+
+    fn reset(&mut self) -> Result<(), RibbleWhisperError> {
+        match self {
+            PayloadDecoder::Pcm {
+                format,
+                resampler,
+                last_packet_len,
+                ..
+            } => {
+                *resampler =
+                    LinearResampler::new(format.sample_rate(), WHISPER_SAMPLE_RATE as usize);
+                *last_packet_len = 0;
+                Ok(())
+            }
+            #[cfg(feature = "opus")]
+            PayloadDecoder::Opus(ingest) => ingest.reset(),
+        }
+    }
+}
+
+// Decodes a PCM payload to mono f32.
+fn decode_pcm(format: RtpPayloadFormat, payload: &[u8], mono: &mut Vec<f32>) {
+    mono.clear();
+    match format {
+        RtpPayloadFormat::L16 { channels, .. } => {
+            let channels = channels as usize;
+            mono.extend(payload.chunks_exact(2 * channels).map(|frame| {
+                frame
+                    .chunks_exact(2)
+                    .map(|pair| i16::from_be_bytes([pair[0], pair[1]]) as f32)
+                    .sum::<f32>()
+                    / (32768.0 * channels as f32)
+            }));
+        }
+        RtpPayloadFormat::Pcmu => {
+            mono.extend(payload.iter().map(|&u| ulaw_to_linear(u) as f32 / 32768.0));
+        }
+        RtpPayloadFormat::Pcma => {
+            mono.extend(payload.iter().map(|&a| alaw_to_linear(a) as f32 / 32768.0));
+        }
+        #[cfg(feature = "opus")]
+        RtpPayloadFormat::Opus => {}
+    }
+}
+
+/// Decodes a G.711 μ-law sample.
+pub fn ulaw_to_linear(ulaw: u8) -> i16 {
+    let ulaw = !ulaw;
+    let exponent = (ulaw >> 4) & 0x07;
+    let mantissa = (ulaw & 0x0F) as i16;
+    let magnitude = (((mantissa << 3) + 0x84) << exponent) - 0x84;
+    if ulaw & 0x80 != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Decodes a G.711 A-law sample.
+pub fn alaw_to_linear(alaw: u8) -> i16 {
+    let alaw = alaw ^ 0x55;
+    let exponent = (alaw >> 4) & 0x07;
+    let mantissa = (alaw & 0x0F) as i16;
+    let magnitude = match exponent {
+        0 => (mantissa << 4) + 8,
+        _ => ((mantissa << 4) + 0x108) << (exponent - 1),
+    };
+    if alaw & 0x80 != 0 {
+        magnitude
+    } else {
+        -magnitude
+    }
+}
+
+/// Receives an RTP stream over UDP and pushes its audio into an [AudioRingBuffer], (see:
+/// [crate::audio::rtp]).
+///
+/// Only one stream is decoded at a time: when packets from a new source, (SSRC), arrive, the
+/// jitter buffer and decoder are reset and the new source replaces the old one.
+pub struct RtpSource {
+    socket: UdpSocket,
+    decoder: PayloadDecoder,
+    jitter_buffer: JitterBuffer,
+    payload_type: Option<u8>,
+    ssrc: Option<u32>,
+}
+
+impl RtpSource {
+    /// Binds a UDP socket to receive the stream on. The buffer must be 16 kHz.
+    pub fn bind<A: ToSocketAddrs>(
+        addr: A,
+        format: RtpPayloadFormat,
+        buffer: AudioRingBuffer<f32>,
+    ) -> Result<Self, RibbleWhisperError> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_read_timeout(Some(RECV_TIMEOUT))?;
+        Ok(Self {
+            socket,
+            decoder: PayloadDecoder::new(format, buffer)?,
+            jitter_buffer: JitterBuffer::new(DEFAULT_JITTER_DEPTH),
+            payload_type: format.static_payload_type(),
+            ssrc: None,
+        })
+    }
+
+    /// Sets the number of packets held for reordering. Deeper buffers survive more network
+    /// jitter at the cost of latency.
+    pub fn with_jitter_depth(mut self, depth: usize) -> Self {
+        self.jitter_buffer = JitterBuffer::new(depth);
+        self
+    }
+
+    /// Sets the payload type to accept; packets of other types, (e.g. DTMF events or comfort
+    /// noise), are ignored. Defaults to the format's static payload type, if it has one;
+    /// otherwise, (i.e. None), every payload type is accepted.
+    pub fn with_payload_type(mut self, payload_type: Option<u8>) -> Self {
+        self.payload_type = payload_type;
+        self
+    }
+
+    /// The address the socket is bound to, (e.g. to find the port after binding to port 0).
+    pub fn local_addr(&self) -> Result<SocketAddr, RibbleWhisperError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Handles a datagram received some other way, (e.g. over a socket owned by a SIP stack).
+    /// Returns the number of samples pushed.
+    pub fn push_datagram(&mut self, datagram: &[u8]) -> Result<usize, RibbleWhisperError> {
+        let packet = RtpPacket::parse(datagram)?;
+        if self
+            .payload_type
+            .is_some_and(|payload_type| payload_type != packet.payload_type)
+        {
+            return Ok(0);
+        }
+        if self.ssrc != Some(packet.ssrc) {
+            self.ssrc = Some(packet.ssrc);
+            self.jitter_buffer.reset();
+            self.decoder.reset()?;
+        }
+        self.jitter_buffer
+            .push(packet.sequence, packet.payload.to_vec());
+        let mut pushed = 0;
+        while let Some(slot) = self.jitter_buffer.pop() {
+            pushed += self.decoder.push(slot)?;
+        }
+        Ok(pushed)
+    }
+
+    /// Releases every packet held for reordering. Returns the number of samples pushed.
+    pub fn flush(&mut self) -> Result<usize, RibbleWhisperError> {
+        let mut pushed = 0;
+        for slot in self.jitter_buffer.flush() {
+            pushed += self.decoder.push(slot)?;
+        }
+        Ok(pushed)
+    }
+
+    /// Receives the stream until run_transcription is set to false, (e.g. alongside
+    /// [RealtimeTranscriber::run_stream](crate::transcriber::realtime_transcriber::RealtimeTranscriber::run_stream)
+    /// on another thread). When the stream goes quiet, the jitter buffer is flushed, so the end
+    /// of an utterance isn't held back waiting for packets that aren't coming.
+    ///
+    /// Malformed packets are skipped; this only returns Err if the socket fails.
+    pub fn run(&mut self, run_transcription: Arc<AtomicBool>) -> Result<(), RibbleWhisperError> {
+        let mut datagram = vec![0u8; MAX_DATAGRAM_LEN];
+        while run_transcription.load(Ordering::Acquire) {
+            match self.socket.recv(&mut datagram) {
+                Ok(len) => {
+                    if let Err(_e) = self.push_datagram(&datagram[..len]) {
+                        log_warn!("Skipping RTP packet: {_e}");
+                    }
+                }
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    self.flush()?;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        self.flush()?;
+        Ok(())
+    }
+}
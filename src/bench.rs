@@ -0,0 +1,276 @@
+//! Criterion benchmark entry points for the realtime hot paths, for measuring the effect of tuning,
+//! (e.g. [crate::transcriber::dedup::DedupConfigs]), on your own hardware.
+//!
+//! Every benchmark runs on generated input, so results are reproducible without audio files or
+//! models. Register the entry points with criterion in a bench target:
+//! `criterion_group!(benches, ribble_whisper::bench::all);`
+//!
+//! The crate's own harness runs them with: `cargo bench --features bench --bench bench_harness`.
+
+use std::collections::VecDeque;
+use std::hint::black_box;
+use std::sync::Arc;
+
+use criterion::{BenchmarkId, Criterion, Throughput};
+
+use crate::audio::audio_ring_buffer::AudioRingBuffer;
+use crate::transcriber::dedup::{blend_segments, deduplicate_strings};
+use crate::transcriber::vad::{Earshot, Silero, WebRtc, VAD};
+use crate::transcriber::{
+    ConfirmedText, RibbleWhisperSegment, SnapshotEncoder, TranscriptionSnapshot,
+    WHISPER_SAMPLE_RATE,
+};
+use crate::whisper::configs::SnapshotEncoding;
+
+// Audio callback sizes, in samples at 16 kHz: 10ms, an odd size that wraps the buffer, and 100ms.
+const CHUNK_LENS: [usize; 3] = [160, 173, 1600];
+// Working segment counts for snapshot construction.
+const SEGMENT_COUNTS: [usize; 3] = [1, 8, 32];
+const SEED: u64 = 0x5EED;
+
+const WORDS: [&str; 16] = [
+    "the", "quick", "brown", "fox", "jumps", "over", "a", "lazy", "dog", "while", "whisper",
+    "listens", "to", "every", "spoken", "word",
+];
+
+/// Runs every benchmark in this module.
+pub fn all(c: &mut Criterion) {
+    ring_buffer_throughput(c);
+    dedup_blend(c);
+    vad_per_second(c);
+    snapshot_construction(c);
+}
+
+/// Pushing audio callbacks into, and reading inference windows out of, an [AudioRingBuffer].
+pub fn ring_buffer_throughput(c: &mut Criterion) {
+    let buffer = AudioRingBuffer::<f32>::default();
+    let mut group = c.benchmark_group("ring_buffer");
+    for len in CHUNK_LENS {
+        let chunk = noise(len, SEED);
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(BenchmarkId::new("push_audio", len), &chunk, |b, chunk| {
+            b.iter(|| buffer.push_audio(black_box(chunk)))
+        });
+    }
+
+    buffer.push_audio(&noise(buffer.get_capacity(), SEED));
+    let mut window = Vec::with_capacity(buffer.get_capacity());
+    for len_ms in [1000, buffer.get_capacity_in_ms()] {
+        let samples = len_ms as f64 * WHISPER_SAMPLE_RATE / 1000.0;
+        group.throughput(Throughput::Elements(samples as u64));
+        group.bench_with_input(
+            BenchmarkId::new("read_into", len_ms),
+            &len_ms,
+            |b, len_ms| b.iter(|| buffer.read_into(black_box(*len_ms), &mut window)),
+        );
+    }
+    group.finish();
+}
+
+/// Deduplicating and blending the overlapping text of consecutive segments, which runs for every
+/// segment of every realtime inference window.
+pub fn dedup_blend(c: &mut Criterion) {
+    let mut rng = Lcg(SEED);
+    // Pairs of segments where the right segment repeats the tail of the left, as consecutive
+    // windows do, and pairs with no overlap.
+    let overlapping: Vec<(String, String)> = (0..64)
+        .map(|_| {
+            let left = sentence(&mut rng, 12);
+            let mut tail: Vec<&str> = left.rsplitn(4, ' ').take(3).collect();
+            tail.reverse();
+            let right = format!("{} {}", tail.join(" "), sentence(&mut rng, 8));
+            (left, right)
+        })
+        .collect();
+    let disjoint: Vec<(String, String)> = (0..64)
+        .map(|_| (sentence(&mut rng, 12), sentence(&mut rng, 12)))
+        .collect();
+
+    let mut group = c.benchmark_group("dedup_blend");
+    for (name, pairs) in [("overlapping", &overlapping), ("disjoint", &disjoint)] {
+        group.throughput(Throughput::Elements(pairs.len() as u64));
+        group.bench_with_input(BenchmarkId::new("deduplicate", name), pairs, |b, pairs| {
+            b.iter(|| {
+                for (left, right) in pairs {
+                    black_box(deduplicate_strings(left, right));
+                }
+            })
+        });
+
+        let segments: Vec<(RibbleWhisperSegment, RibbleWhisperSegment)> = pairs
+            .iter()
+            .map(|(left, right)| (segment(left, 0, 300), segment(right, 200, 500)))
+            .collect();
+        group.bench_with_input(BenchmarkId::new("blend", name), &segments, |b, segments| {
+            b.iter(|| {
+                for (left, right) in segments {
+                    let mut left = left.clone();
+                    blend_segments(&mut left, right);
+                    black_box(left);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Voice detection over one second of 16 kHz audio, for each VAD backend.
+pub fn vad_per_second(c: &mut Criterion) {
+    let audio = speech_like(WHISPER_SAMPLE_RATE as usize, SEED);
+    let mut group = c.benchmark_group("vad_per_second");
+    group.throughput(Throughput::Elements(audio.len() as u64));
+
+    let mut earshot =
+        Earshot::try_new_whisper_realtime_default().expect("Earshot should build by default.");
+    group.bench_function("earshot", |b| {
+        b.iter(|| black_box(earshot.voice_detected(black_box(&audio))))
+    });
+    let mut webrtc =
+        WebRtc::try_new_whisper_realtime_default().expect("WebRtc should build by default.");
+    group.bench_function("webrtc", |b| {
+        b.iter(|| black_box(webrtc.voice_detected(black_box(&audio))))
+    });
+    let mut silero =
+        Silero::try_new_whisper_realtime_default().expect("Silero should build by default.");
+    group.bench_function("silero", |b| {
+        b.iter(|| black_box(silero.voice_detected(black_box(&audio))))
+    });
+    group.finish();
+}
+
+/// Building a [TranscriptionSnapshot] from the working segments and encoding it, as the realtime
+/// transcriber does after every inference window. `collect` is the cost of collecting every
+/// segment, for comparison with the transcriber's copy-on-write construction.
+pub fn snapshot_construction(c: &mut Criterion) {
+    let mut rng = Lcg(SEED);
+    let confirmed = ConfirmedText::from(Arc::<str>::from(sentence(&mut rng, 500)));
+    let mut group = c.benchmark_group("snapshot");
+    for n_segments in SEGMENT_COUNTS {
+        let segments: VecDeque<RibbleWhisperSegment> = (0..n_segments)
+            .map(|i| {
+                segment(
+                    &sentence(&mut rng, 12),
+                    i as i64 * 300,
+                    (i as i64 + 1) * 300,
+                )
+            })
+            .collect();
+        group.throughput(Throughput::Elements(n_segments as u64));
+
+        let build = |segments: &VecDeque<RibbleWhisperSegment>| {
+            let string_segments = segments.iter().map(|s| s.text.clone()).collect();
+            Arc::new(TranscriptionSnapshot::from_confirmed_text(
+                confirmed.clone(),
+                string_segments,
+            ))
+        };
+        group.bench_with_input(
+            BenchmarkId::new("collect", n_segments),
+            &segments,
+            |b, s| b.iter(|| black_box(build(s))),
+        );
+
+        // Alternating between two working sets that differ in their last segment, as when only
+        // the last segment is re-transcribed.
+        let mut edited = segments.clone();
+        if let Some(last) = edited.back_mut() {
+            last.replace_text(Arc::from(sentence(&mut rng, 12)));
+        }
+        let working_sets = [&segments, &edited];
+        for (name, changes) in [("build_unchanged", 0), ("build_tail_changed", 1)] {
+            let mut encoder = SnapshotEncoder::new(SnapshotEncoding::Full);
+            let mut i = 0;
+            group.bench_function(BenchmarkId::new(name, n_segments), |b| {
+                b.iter(|| {
+                    i += changes;
+                    let working_set = working_sets[i % 2].iter().map(|s| &s.text);
+                    black_box(encoder.build_snapshot(confirmed.clone(), working_set, None))
+                })
+            });
+        }
+
+        for (name, encoding) in [
+            ("encode_full", SnapshotEncoding::Full),
+            (
+                "encode_delta",
+                SnapshotEncoding::Delta {
+                    keyframe_interval: 0,
+                },
+            ),
+        ] {
+            // Alternate between two snapshots so that deltas always have something to encode.
+            let snapshots = [build(&segments), build(&edited)];
+            let mut encoder = SnapshotEncoder::new(encoding);
+            let mut i = 0;
+            group.bench_function(BenchmarkId::new(name, n_segments), |b| {
+                b.iter(|| {
+                    i += 1;
+                    black_box(encoder.encode(Arc::clone(&snapshots[i % 2])))
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+// A small linear congruential generator, so that generated input is identical across runs and
+// platforms.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+
+    // In [-1.0, 1.0).
+    fn next_f32(&mut self) -> f32 {
+        (self.next() as f32 / (1u64 << 31) as f32) * 2.0 - 1.0
+    }
+}
+
+fn noise(len: usize, seed: u64) -> Vec<f32> {
+    let mut rng = Lcg(seed);
+    (0..len).map(|_| rng.next_f32() * 0.1).collect()
+}
+
+// Alternating 250ms bursts of harmonics, (roughly voiced), and quiet noise.
+fn speech_like(len: usize, seed: u64) -> Vec<f32> {
+    let mut rng = Lcg(seed);
+    let burst = WHISPER_SAMPLE_RATE as usize / 4;
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / WHISPER_SAMPLE_RATE as f32;
+            let noise = rng.next_f32() * 0.01;
+            if (i / burst) % 2 == 0 {
+                let harmonics: f32 = (1..=4)
+                    .map(|h| (std::f32::consts::TAU * 150.0 * h as f32 * t).sin() / h as f32)
+                    .sum();
+                harmonics * 0.3 + noise
+            } else {
+                noise
+            }
+        })
+        .collect()
+}
+
+fn sentence(rng: &mut Lcg, n_words: usize) -> String {
+    (0..n_words)
+        .map(|_| WORDS[rng.next() as usize % WORDS.len()])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn segment(text: &str, start_time: i64, end_time: i64) -> RibbleWhisperSegment {
+    RibbleWhisperSegment {
+        text: Arc::from(text),
+        start_time,
+        end_time,
+        confidence: None,
+        word_confidences: None,
+        speaker: None,
+    }
+}
@@ -0,0 +1,659 @@
+use std::fs::File;
+use std::io::{stdout, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::Arc;
+use std::thread::scope;
+use std::time::Instant;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use ribble_whisper::audio::audio_backend::{default_backend, AudioBackend, CaptureSpec};
+use ribble_whisper::audio::audio_ring_buffer::AudioRingBuffer;
+use ribble_whisper::audio::loading::load_normalized_audio_file;
+use ribble_whisper::audio::microphone::MicCapture;
+use ribble_whisper::audio::recorder::RingBufSink;
+use ribble_whisper::audio::AudioChannelConfiguration;
+use ribble_whisper::downloader::downloaders::sync_download_request;
+use ribble_whisper::downloader::SyncDownload;
+use ribble_whisper::export::jsonl::{JsonlWriter, SegmentRecord};
+use ribble_whisper::export::subtitles::layout::CaptionLayout;
+use ribble_whisper::export::subtitles::srt::SrtExporter;
+use ribble_whisper::export::subtitles::vtt::VttExporter;
+use ribble_whisper::export::subtitles::SubtitleExporter;
+use ribble_whisper::transcriber::offline_transcriber::OfflineTranscriberBuilder;
+use ribble_whisper::transcriber::realtime_transcriber::RealtimeTranscriberBuilder;
+use ribble_whisper::transcriber::vad::{Silero, WebRtc, VAD};
+use ribble_whisper::transcriber::{
+    redirect_whisper_logging_to_hooks, ConfirmedText, RibbleWhisperSegment, SnapshotAssembler,
+    WhisperCallbacks, WhisperOutput, WHISPER_SAMPLE_RATE,
+};
+use ribble_whisper::utils::callback::{Nop, RibbleWhisperCallback, StaticRibbleWhisperCallback};
+use ribble_whisper::utils::errors::RibbleWhisperError;
+use ribble_whisper::utils::thread::apply_to_current_thread;
+use ribble_whisper::whisper::configs::{
+    Language, ThreadConfigs, ThreadPriority, WhisperConfigs, WhisperRealtimeConfigs,
+    WhisperSamplingStrategy,
+};
+use ribble_whisper::whisper::integrity_utils::verify_file_checksum;
+use ribble_whisper::whisper::model::{
+    CustomModelSource, DefaultModelBank, DefaultModelType, ModelBank, ModelId,
+};
+use strum::IntoEnumIterator;
+
+/// Transcribe audio files and microphone input with whisper, and manage whisper models.
+#[derive(Parser)]
+#[command(name = "ribble-whisper-cli", version)]
+struct Cli {
+    /// The directory models are stored in. Defaults to the platform data directory.
+    #[arg(long, global = true)]
+    model_dir: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Transcribe an audio file.
+    Transcribe(TranscribeArgs),
+    /// Transcribe microphone input in real time. Press Ctrl-C to stop.
+    Listen(ListenArgs),
+    /// Manage whisper models.
+    #[command(subcommand)]
+    Models(ModelsCommand),
+}
+
+#[derive(Subcommand)]
+enum ModelsCommand {
+    /// List the default models and whether they have been downloaded.
+    List,
+    /// Download a default model, (e.g. medium, small.en, large-v3-turbo).
+    Download { model: String },
+    /// Verify downloaded models against their expected checksums. Verifies every downloaded
+    /// default model when no model is given.
+    Verify { model: Option<String> },
+}
+
+#[derive(Args)]
+struct WhisperArgs {
+    /// A default model name, (e.g. medium, small.en), or a path to a ggml model file.
+    #[arg(short, long, default_value = "small")]
+    model: String,
+    /// Fail instead of downloading the model if it is missing.
+    #[arg(long)]
+    no_download: bool,
+    /// The spoken language, (e.g. en, fr), or auto to detect it.
+    #[arg(short, long)]
+    language: Option<String>,
+    /// Translate the transcription into English.
+    #[arg(long)]
+    translate: bool,
+    #[arg(short = 't', long, default_value_t = 8)]
+    threads: usize,
+    /// Run inference on the CPU only.
+    #[arg(long)]
+    no_gpu: bool,
+    #[arg(long)]
+    flash_attention: bool,
+    /// Decode with beam search instead of greedy sampling.
+    #[arg(long)]
+    beam_size: Option<usize>,
+}
+
+#[derive(Args)]
+struct TranscribeArgs {
+    /// The audio file to transcribe. Audio is resampled to 16 kHz mono as needed.
+    file: PathBuf,
+    #[command(flatten)]
+    whisper: WhisperArgs,
+    /// Skip silences with a voice activity detector. Timestamps will not line up with the
+    /// original audio, so this should not be used when exporting subtitles.
+    #[arg(long, value_enum)]
+    vad: Option<VadKind>,
+    /// Write SubRip subtitles to this path.
+    #[arg(long)]
+    srt: Option<PathBuf>,
+    /// Write WebVTT subtitles to this path.
+    #[arg(long)]
+    vtt: Option<PathBuf>,
+    /// Write the segments as JSON Lines to this path.
+    #[arg(long)]
+    jsonl: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct ListenArgs {
+    #[command(flatten)]
+    whisper: WhisperArgs,
+    /// The index of the capture device, (see: --list-devices). Defaults to the system default.
+    #[arg(short, long)]
+    device: Option<u32>,
+    /// List the capture devices and exit.
+    #[arg(long)]
+    list_devices: bool,
+    /// Transcribe system audio, (e.g. a meeting or video), from a monitor source instead of a
+    /// microphone. With --list-devices, lists the monitor sources.
+    #[arg(long)]
+    loopback: bool,
+    #[arg(long, value_enum, default_value_t = VadKind::Silero)]
+    vad: VadKind,
+    /// How confirmed text is written to stdout. JSON Lines timestamps are measured from when
+    /// listening starts.
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+    /// Append the output to this file instead of writing it to stdout.
+    #[arg(long)]
+    out: Option<PathBuf>,
+    /// Finish transcribing buffered audio after Ctrl-C.
+    #[arg(short, long)]
+    slow_stop: bool,
+    /// The scheduling priority of the transcription thread. Low keeps other applications
+    /// responsive when they compete with whisper for cores.
+    #[arg(long, value_enum, default_value_t = PriorityKind::Default)]
+    thread_priority: PriorityKind,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum VadKind {
+    Silero,
+    Webrtc,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum PriorityKind {
+    Default,
+    Low,
+    High,
+}
+
+impl From<PriorityKind> for ThreadPriority {
+    fn from(priority: PriorityKind) -> Self {
+        match priority {
+            PriorityKind::Default => ThreadPriority::Default,
+            PriorityKind::Low => ThreadPriority::Low,
+            PriorityKind::High => ThreadPriority::High,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Jsonl,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let run_transcription = Arc::new(AtomicBool::new(true));
+    let c_handler_run_transcription = Arc::clone(&run_transcription);
+    // Use CTRL-C to stop transcription/listening.
+    ctrlc::set_handler(move || {
+        c_handler_run_transcription.store(false, Ordering::SeqCst);
+    })
+    .expect("failed to set SIGINT handler");
+
+    // Block Whisper.cpp from logging to stdout/stderr.
+    redirect_whisper_logging_to_hooks();
+
+    let res = model_bank(cli.model_dir).and_then(|bank| match cli.command {
+        Command::Transcribe(args) => transcribe(bank, args, run_transcription),
+        Command::Listen(args) => listen(bank, args, run_transcription),
+        Command::Models(ModelsCommand::List) => list_models(&bank),
+        Command::Models(ModelsCommand::Download { model }) => {
+            let model_type = parse_model_type(&model)?;
+            download_model(&bank, model_type)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Models(ModelsCommand::Verify { model }) => verify_models(&bank, model),
+    });
+
+    match res {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn model_bank(model_dir: Option<PathBuf>) -> Result<DefaultModelBank, RibbleWhisperError> {
+    let bank = DefaultModelBank::new();
+    match model_dir {
+        Some(dir) => Ok(bank.with_model_directory(dir)),
+        None => bank.with_platform_data_dir("ribble-whisper"),
+    }
+}
+
+// Default models are named after their files, (e.g. "medium.en" for ggml-medium.en.bin).
+fn model_name(model_type: DefaultModelType) -> &'static str {
+    let file_name = model_type.to_file_name();
+    &file_name["ggml-".len()..file_name.len() - ".bin".len()]
+}
+
+fn parse_model_type(name: &str) -> Result<DefaultModelType, RibbleWhisperError> {
+    DefaultModelType::iter()
+        .find(|model_type| model_name(*model_type) == name)
+        .ok_or_else(|| {
+            let names: Vec<&str> = DefaultModelType::iter().map(model_name).collect();
+            RibbleWhisperError::ParameterError(format!(
+                "Unknown model: {name}. Expected a model file or one of: {}",
+                names.join(", ")
+            ))
+        })
+}
+
+// Resolves the model argument to a model in the bank, downloading it if needed.
+// Models that are not in the bank by default, (and model files), are registered as custom models.
+fn prepare_model(
+    bank: &mut DefaultModelBank,
+    args: &WhisperArgs,
+) -> Result<ModelId, RibbleWhisperError> {
+    let path = Path::new(&args.model);
+    if path.is_file() {
+        let name = path.file_stem().map_or(args.model.clone(), |stem| {
+            stem.to_string_lossy().into_owned()
+        });
+        return bank.register_custom_model(name, CustomModelSource::Path(path.to_path_buf()));
+    }
+
+    let model_type = parse_model_type(&args.model)?;
+    let model_path = bank.model_directory().join(model_type.to_file_name());
+    if !model_path.is_file() {
+        if args.no_download {
+            return Err(RibbleWhisperError::ParameterError(format!(
+                "Model {} has not been downloaded.",
+                args.model
+            )));
+        }
+        download_model(bank, model_type)?;
+    }
+
+    let model_id = bank.get_model_id(model_type);
+    if bank.get_model(model_id).is_some() {
+        Ok(model_id)
+    } else {
+        bank.register_custom_model(
+            model_name(model_type).to_string(),
+            CustomModelSource::Path(model_path),
+        )
+    }
+}
+
+fn download_model(
+    bank: &DefaultModelBank,
+    model_type: DefaultModelType,
+) -> Result<(), RibbleWhisperError> {
+    let name = model_name(model_type);
+    if bank
+        .model_directory()
+        .join(model_type.to_file_name())
+        .is_file()
+    {
+        eprintln!("{name} has already been downloaded.");
+        return Ok(());
+    }
+
+    let downloader = sync_download_request(&model_type.url(), model_type.to_file_name())?
+        .with_expected_checksum(Some(model_type.expected_checksum()));
+    let total_size = downloader.total_size().max(1);
+    let progress_callback = RibbleWhisperCallback::new(move |downloaded: usize| {
+        eprint!(
+            "\rDownloading {name}: {:>3}%",
+            downloaded * 100 / total_size
+        );
+    });
+    let mut downloader = downloader.with_progress_callback(progress_callback);
+    let res = downloader.download(bank.model_directory());
+    eprintln!();
+    res?;
+    Ok(())
+}
+
+fn list_models(bank: &DefaultModelBank) -> Result<ExitCode, RibbleWhisperError> {
+    println!("{:<16} {:>10}  FILE", "MODEL", "SIZE (MB)");
+    for model_type in DefaultModelType::iter() {
+        let file_name = model_type.to_file_name();
+        let size = match std::fs::metadata(bank.model_directory().join(file_name)) {
+            Ok(metadata) if metadata.is_file() => {
+                format!("{:.1}", metadata.len() as f64 / 1_000_000.0)
+            }
+            _ => "-".to_string(),
+        };
+        println!("{:<16} {:>10}  {}", model_name(model_type), size, file_name);
+    }
+    println!("\nModel directory: {}", bank.model_directory().display());
+    Ok(ExitCode::SUCCESS)
+}
+
+fn verify_models(
+    bank: &DefaultModelBank,
+    model: Option<String>,
+) -> Result<ExitCode, RibbleWhisperError> {
+    let model_types = match model {
+        Some(name) => vec![parse_model_type(&name)?],
+        None => DefaultModelType::iter()
+            .filter(|model_type| {
+                bank.model_directory()
+                    .join(model_type.to_file_name())
+                    .is_file()
+            })
+            .collect(),
+    };
+    if model_types.is_empty() {
+        eprintln!("No models have been downloaded.");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let mut all_valid = true;
+    for model_type in model_types {
+        let path = bank.model_directory().join(model_type.to_file_name());
+        let status = if !path.is_file() {
+            all_valid = false;
+            "missing"
+        } else if verify_file_checksum(&path, &model_type.expected_checksum().as_checksum())? {
+            "ok"
+        } else {
+            all_valid = false;
+            "checksum mismatch"
+        };
+        println!("{:<16} {status}", model_name(model_type));
+    }
+    Ok(if all_valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+impl WhisperArgs {
+    fn configs(&self, model_id: ModelId) -> Result<WhisperConfigs, RibbleWhisperError> {
+        let language = self
+            .language
+            .as_deref()
+            .map(|language| {
+                Language::from_str(&language.to_lowercase()).map_err(|_| {
+                    RibbleWhisperError::ParameterError(format!("Unknown language: {language}"))
+                })
+            })
+            .transpose()?;
+        let mut configs = WhisperConfigs::default()
+            .with_model_id(Some(model_id))
+            .with_n_threads(self.threads)
+            .with_language(language)
+            .with_translate(self.translate)
+            .with_use_gpu(!self.no_gpu)
+            .with_flash_attention(self.flash_attention);
+        if let Some(beam_size) = self.beam_size {
+            configs = configs.with_sampling_strategy(WhisperSamplingStrategy::BeamSearch {
+                beam_size,
+                patience: -1.0,
+            });
+        }
+        Ok(configs)
+    }
+}
+
+fn transcribe(
+    mut bank: DefaultModelBank,
+    args: TranscribeArgs,
+    run_transcription: Arc<AtomicBool>,
+) -> Result<ExitCode, RibbleWhisperError> {
+    let model_id = prepare_model(&mut bank, &args.whisper)?;
+    let configs = args.whisper.configs(model_id)?;
+    if args.vad.is_some() && (args.srt.is_some() || args.vtt.is_some()) {
+        eprintln!("warning: subtitle timestamps will not match the audio when using --vad.");
+    }
+
+    let start = Instant::now();
+    let audio = load_normalized_audio_file(&args.file, None::<fn(usize)>)?;
+    let audio_secs = audio.len() as f64 / WHISPER_SAMPLE_RATE;
+    let load_secs = start.elapsed().as_secs_f64();
+
+    let builder = OfflineTranscriberBuilder::<Silero, DefaultModelBank>::new()
+        .with_configs(configs)
+        .with_audio(audio)
+        .with_channel_configurations(AudioChannelConfiguration::Mono)
+        .with_model_retriever(bank);
+    let inference_start = Instant::now();
+    let segments = match args.vad {
+        None => run_offline(builder, run_transcription),
+        Some(VadKind::Silero) => run_offline(
+            builder.with_voice_activity_detector(Silero::try_new_whisper_offline_default()?),
+            run_transcription,
+        ),
+        Some(VadKind::Webrtc) => run_offline(
+            builder.with_voice_activity_detector(WebRtc::try_new_whisper_offline_default()?),
+            run_transcription,
+        ),
+    }?;
+    let inference_secs = inference_start.elapsed().as_secs_f64();
+
+    let transcription: String = segments.iter().map(|segment| segment.text()).collect();
+    println!("{}", transcription.trim());
+
+    let cues = CaptionLayout::new().layout_segments(&segments);
+    if let Some(path) = args.srt.as_ref() {
+        SrtExporter.write_to_file(&cues, path)?;
+    }
+    if let Some(path) = args.vtt.as_ref() {
+        VttExporter.write_to_file(&cues, path)?;
+    }
+    if let Some(path) = args.jsonl.as_ref() {
+        let mut writer = JsonlWriter::new(File::create(path)?);
+        for segment in segments.iter() {
+            writer.write_segment(segment)?;
+        }
+    }
+
+    // Timings double as a quick benchmark; model loading is included in the inference time.
+    eprintln!(
+        "Loaded {audio_secs:.1}s of audio in {load_secs:.2}s, transcribed in {inference_secs:.2}s (real-time factor: {:.2})",
+        inference_secs / audio_secs.max(f64::EPSILON)
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run_offline<V: VAD<f32>>(
+    builder: OfflineTranscriberBuilder<V, DefaultModelBank>,
+    run_transcription: Arc<AtomicBool>,
+) -> Result<Vec<RibbleWhisperSegment>, RibbleWhisperError> {
+    let transcriber = builder.build()?;
+    let callbacks = WhisperCallbacks {
+        progress: Some(StaticRibbleWhisperCallback::new(|percent: i32| {
+            eprint!("\rTranscribing: {percent:>3}%");
+        })),
+        new_segment: None::<Nop<String>>,
+    };
+    let segments = match transcriber.process_segments_with_callbacks(run_transcription, callbacks) {
+        // Stopping with Ctrl-C still prints what was transcribed.
+        Err(RibbleWhisperError::Cancelled) => Ok(transcriber.take_partial_segments()),
+        res => res,
+    };
+    eprintln!();
+    segments
+}
+
+fn listen(
+    mut bank: DefaultModelBank,
+    args: ListenArgs,
+    run_transcription: Arc<AtomicBool>,
+) -> Result<ExitCode, RibbleWhisperError> {
+    let (_ctx, backend) = default_backend()?;
+    if args.list_devices {
+        let devices = if args.loopback {
+            backend.list_loopback_devices()?
+        } else {
+            backend.list_input_devices()?
+        };
+        for device in devices {
+            println!("{device}");
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let model_id = prepare_model(&mut bank, &args.whisper)?;
+    let configs = args
+        .whisper
+        .configs(model_id)?
+        .into_realtime_v1()
+        .with_thread_configs(ThreadConfigs::new().with_priority(args.thread_priority.into()));
+
+    let out: Box<dyn Write + Send> = match args.out.as_ref() {
+        Some(path) => Box::new(File::options().create(true).append(true).open(path)?),
+        None => Box::new(stdout()),
+    };
+
+    let audio_buffer = AudioRingBuffer::<f32>::default();
+    let spec = CaptureSpec::default()
+        .with_device(args.device)
+        .with_loopback(args.loopback);
+    let mic = backend.open_capture(spec, RingBufSink::new(audio_buffer.clone()))?;
+
+    let session = RealtimeSession {
+        configs,
+        bank,
+        audio_buffer,
+        output: args.output,
+        run_transcription,
+        slow_stop: Arc::new(AtomicBool::new(args.slow_stop)),
+    };
+    mic.play();
+    let res = match args.vad {
+        VadKind::Silero => session.run(Silero::try_new_whisper_realtime_default()?, out),
+        VadKind::Webrtc => session.run(WebRtc::try_new_whisper_realtime_default()?, out),
+    };
+    mic.pause();
+    res.map(|_| ExitCode::SUCCESS)
+}
+
+struct RealtimeSession {
+    configs: WhisperRealtimeConfigs,
+    bank: DefaultModelBank,
+    audio_buffer: AudioRingBuffer<f32>,
+    output: OutputFormat,
+    run_transcription: Arc<AtomicBool>,
+    slow_stop: Arc<AtomicBool>,
+}
+
+impl RealtimeSession {
+    fn run<V: VAD<f32> + Send + Sync>(
+        self,
+        vad: V,
+        out: Box<dyn Write + Send>,
+    ) -> Result<(), RibbleWhisperError> {
+        let Self {
+            configs,
+            bank,
+            audio_buffer,
+            output,
+            run_transcription,
+            slow_stop,
+        } = self;
+        let thread_configs = configs.thread_configs();
+        let (sender, receiver) = sync_channel(32);
+        let (transcriber, _handle) = RealtimeTranscriberBuilder::<V, DefaultModelBank>::new()
+            .with_configs(configs)
+            .with_audio_buffer(&audio_buffer)
+            .with_output_sender(sender)
+            .with_voice_activity_detector(vad)
+            .with_model_retriever(bank)
+            .build()?;
+
+        scope(|s| {
+            // The transcriber is moved into the thread so that the output channel closes once it
+            // finishes, which ends the print loop.
+            let worker = s.spawn(move || {
+                if let Err(e) = apply_to_current_thread(&thread_configs) {
+                    eprintln!("Failed to set the transcription thread priority: {e}");
+                }
+                transcriber.run_stream(run_transcription, slow_stop)
+            });
+            let mut writer = ConfirmedWriter::new(output, out);
+            let printed = writer.write_outputs(receiver);
+            let transcription = worker.join().map_err(|_| {
+                RibbleWhisperError::Unknown("Transcription thread panicked.".to_string())
+            })??;
+            printed?;
+            // The final transcription may include audio that was never confirmed, (e.g. on a slow stop).
+            writer.write_confirmed(&transcription)
+        })
+    }
+}
+
+// Writes the confirmed transcription as it grows; working segments are not written, so the output
+// only ever gets appended to.
+enum ConfirmedOutput {
+    Text(Box<dyn Write + Send>),
+    Jsonl(JsonlWriter<Box<dyn Write + Send>>),
+}
+
+struct ConfirmedWriter {
+    out: ConfirmedOutput,
+    written_len: usize,
+    started: Instant,
+    last_confirmed_ms: i64,
+}
+
+impl ConfirmedWriter {
+    fn new(output: OutputFormat, out: Box<dyn Write + Send>) -> Self {
+        let out = match output {
+            OutputFormat::Text => ConfirmedOutput::Text(out),
+            OutputFormat::Jsonl => ConfirmedOutput::Jsonl(JsonlWriter::new(out)),
+        };
+        Self {
+            out,
+            written_len: 0,
+            started: Instant::now(),
+            last_confirmed_ms: 0,
+        }
+    }
+
+    fn write_outputs(
+        &mut self,
+        receiver: Receiver<WhisperOutput>,
+    ) -> Result<(), RibbleWhisperError> {
+        let mut assembler = SnapshotAssembler::new();
+        while let Ok(output) = receiver.recv() {
+            if let WhisperOutput::ControlPhrase(phrase) = &output {
+                eprintln!("{phrase}");
+                continue;
+            }
+            if let Some(snapshot) = assembler.push(&output) {
+                self.write_confirmed(snapshot.confirmed_text())?;
+            }
+        }
+        Ok(())
+    }
+
+    // Only the text appended since the last write is read out of the confirmed chunks.
+    fn write_confirmed(&mut self, confirmed: &ConfirmedText) -> Result<(), RibbleWhisperError> {
+        let Some(new_text) = confirmed.chunks_from(self.written_len) else {
+            return Ok(());
+        };
+        let new_text: String = new_text.collect();
+        if new_text.trim().is_empty() {
+            return Ok(());
+        }
+        self.written_len = confirmed.len();
+
+        let now_ms = self.started.elapsed().as_millis() as i64;
+        match &mut self.out {
+            ConfirmedOutput::Text(out) => {
+                out.write_all(new_text.as_bytes())?;
+                out.flush()?;
+            }
+            ConfirmedOutput::Jsonl(writer) => {
+                writer.write_record(SegmentRecord::new(
+                    new_text.trim(),
+                    self.last_confirmed_ms,
+                    now_ms,
+                ))?;
+            }
+        }
+        self.last_confirmed_ms = now_ms;
+        Ok(())
+    }
+}
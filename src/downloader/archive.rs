@@ -0,0 +1,293 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+use strum::{AsRefStr, Display, EnumIs, EnumIter, IntoStaticStr};
+
+use crate::utils::callback::{Callback, Nop};
+use crate::utils::errors::RibbleWhisperError;
+use crate::utils::logging::log_warn;
+
+/// Archive formats that can be extracted after a download.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Hash, AsRefStr, Display, EnumIs, EnumIter, IntoStaticStr,
+)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    /// A gzip-compressed tarball, (.tar.gz/.tgz)
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Guesses the format from a file's extension. Returns None if it is not a recognized archive.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?.to_lowercase();
+        if file_name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if file_name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// Progress updates sent while an archive is extracted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExtractionProgress {
+    /// The number of entries extracted so far.
+    pub entries_extracted: usize,
+    /// The number of entries in the archive, or None for (streamed) tarballs.
+    pub total_entries: Option<usize>,
+    /// The number of (uncompressed) bytes written so far.
+    pub bytes_extracted: u64,
+}
+
+/// A processing step that runs on a file once it has been downloaded,
+/// see: [crate::downloader::SyncDownload::download_and_process].
+/// This is implemented for closures of the form `FnMut(&Path) -> Result<PathBuf, RibbleWhisperError>`.
+pub trait PostDownload {
+    /// Processes the downloaded file and returns the path to the final artifact.
+    fn process(&mut self, downloaded_path: &Path) -> Result<PathBuf, RibbleWhisperError>;
+}
+
+impl<F> PostDownload for F
+where
+    F: FnMut(&Path) -> Result<PathBuf, RibbleWhisperError>,
+{
+    fn process(&mut self, downloaded_path: &Path) -> Result<PathBuf, RibbleWhisperError> {
+        self(downloaded_path)
+    }
+}
+
+/// A [PostDownload] step that extracts a downloaded archive.
+/// By default, the archive is extracted into its containing directory and then removed.
+pub struct ExtractArchive<CB>
+where
+    CB: Callback<Argument = ExtractionProgress>,
+{
+    format: Option<ArchiveFormat>,
+    destination: Option<PathBuf>,
+    remove_archive: bool,
+    progress_callback: CB,
+}
+
+impl ExtractArchive<Nop<ExtractionProgress>> {
+    pub fn new() -> Self {
+        Self {
+            format: None,
+            destination: None,
+            remove_archive: true,
+            progress_callback: Nop::new(),
+        }
+    }
+}
+
+impl Default for ExtractArchive<Nop<ExtractionProgress>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<CB> ExtractArchive<CB>
+where
+    CB: Callback<Argument = ExtractionProgress>,
+{
+    /// Sets the archive format. If unset, it is guessed from the downloaded file's extension.
+    pub fn with_format(mut self, format: ArchiveFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Sets the directory to extract into. If unset, the archive's directory is used.
+    pub fn with_destination(mut self, destination: PathBuf) -> Self {
+        self.destination = Some(destination);
+        self
+    }
+
+    /// Toggles removing the archive once it has been extracted. Defaults to true.
+    pub fn with_remove_archive(mut self, remove_archive: bool) -> Self {
+        self.remove_archive = remove_archive;
+        self
+    }
+
+    pub fn with_progress_callback<C>(self, progress_callback: C) -> ExtractArchive<C>
+    where
+        C: Callback<Argument = ExtractionProgress>,
+    {
+        ExtractArchive {
+            format: self.format,
+            destination: self.destination,
+            remove_archive: self.remove_archive,
+            progress_callback,
+        }
+    }
+}
+
+impl<CB> PostDownload for ExtractArchive<CB>
+where
+    CB: Callback<Argument = ExtractionProgress>,
+{
+    /// Extracts the archive and returns the extraction directory.
+    fn process(&mut self, downloaded_path: &Path) -> Result<PathBuf, RibbleWhisperError> {
+        let format = self
+            .format
+            .or_else(|| ArchiveFormat::from_path(downloaded_path))
+            .ok_or(RibbleWhisperError::ParameterError(format!(
+                "Unrecognized archive format: {downloaded_path:?}"
+            )))?;
+        let destination = match self.destination.as_ref() {
+            Some(destination) => destination.clone(),
+            None => downloaded_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+        };
+
+        let extracted = extract_archive(
+            downloaded_path,
+            &destination,
+            format,
+            &mut self.progress_callback,
+        );
+        if self.remove_archive {
+            let _ = fs::remove_file(downloaded_path);
+        }
+        extracted?;
+        Ok(destination)
+    }
+}
+
+/// Extracts an archive into the destination directory.
+/// Entries that would be written outside of the destination (absolute paths or `..` components),
+/// as well as symbolic and hard links in tarballs, are skipped.
+/// # Arguments:
+/// * archive_path: the archive to extract
+/// * destination: the directory to extract into. It will be created if it does not exist.
+/// * format: the archive format
+/// * progress_callback: receives an [ExtractionProgress] update after each entry
+/// # Returns:
+/// * Ok(`Vec<PathBuf>`) with the paths of the extracted files on success
+/// * Err(RibbleWhisperError) on I/O failure or if the archive is malformed.
+pub fn extract_archive<CB>(
+    archive_path: &Path,
+    destination: &Path,
+    format: ArchiveFormat,
+    progress_callback: &mut CB,
+) -> Result<Vec<PathBuf>, RibbleWhisperError>
+where
+    CB: Callback<Argument = ExtractionProgress>,
+{
+    fs::create_dir_all(destination)?;
+    let file = BufReader::new(File::open(archive_path)?);
+    match format {
+        ArchiveFormat::Zip => extract_zip(file, destination, progress_callback),
+        ArchiveFormat::Tar => extract_tar(file, destination, progress_callback),
+        ArchiveFormat::TarGz => extract_tar(
+            flate2::read::GzDecoder::new(file),
+            destination,
+            progress_callback,
+        ),
+    }
+}
+
+fn extract_zip<R, CB>(
+    reader: R,
+    destination: &Path,
+    progress_callback: &mut CB,
+) -> Result<Vec<PathBuf>, RibbleWhisperError>
+where
+    R: Read + io::Seek,
+    CB: Callback<Argument = ExtractionProgress>,
+{
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let total_entries = archive.len();
+    let mut progress = ExtractionProgress {
+        entries_extracted: 0,
+        total_entries: Some(total_entries),
+        bytes_extracted: 0,
+    };
+    let mut extracted = vec![];
+
+    for index in 0..total_entries {
+        let mut entry = archive.by_index(index)?;
+        // enclosed_name rejects absolute paths and paths that escape the archive root.
+        let Some(entry_path) = entry.enclosed_name().map(|name| destination.join(name)) else {
+            skip_entry(entry.name());
+            continue;
+        };
+
+        if entry.is_dir() {
+            fs::create_dir_all(&entry_path)?;
+        } else {
+            if let Some(parent) = entry_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut dest = File::create(&entry_path)?;
+            progress.bytes_extracted += io::copy(&mut entry, &mut dest)?;
+            extracted.push(entry_path);
+        }
+        progress.entries_extracted += 1;
+        progress_callback.call(progress);
+    }
+    Ok(extracted)
+}
+
+fn extract_tar<R, CB>(
+    reader: R,
+    destination: &Path,
+    progress_callback: &mut CB,
+) -> Result<Vec<PathBuf>, RibbleWhisperError>
+where
+    R: Read,
+    CB: Callback<Argument = ExtractionProgress>,
+{
+    let mut archive = tar::Archive::new(reader);
+    let mut progress = ExtractionProgress {
+        entries_extracted: 0,
+        total_entries: None,
+        bytes_extracted: 0,
+    };
+    let mut extracted = vec![];
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_type = entry.header().entry_type();
+        let relative_path = entry.path()?.into_owned();
+        if entry_type.is_symlink() || entry_type.is_hard_link() || !is_enclosed(&relative_path) {
+            skip_entry(&relative_path.to_string_lossy());
+            continue;
+        }
+
+        let entry_path = destination.join(&relative_path);
+        if entry_type.is_dir() {
+            fs::create_dir_all(&entry_path)?;
+        } else if entry_type.is_file() {
+            if let Some(parent) = entry_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut dest = File::create(&entry_path)?;
+            progress.bytes_extracted += io::copy(&mut entry, &mut dest)?;
+            extracted.push(entry_path);
+        } else {
+            // Other entry types, (e.g. pax headers, devices) are not meaningful for model files.
+            continue;
+        }
+        progress.entries_extracted += 1;
+        progress_callback.call(progress);
+    }
+    Ok(extracted)
+}
+
+// Only plain, relative components are allowed so that entries stay inside the destination.
+fn is_enclosed(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+fn skip_entry(_entry_name: &str) {
+    log_warn!("Skipping unsafe archive entry: {_entry_name}");
+}
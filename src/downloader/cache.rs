@@ -0,0 +1,115 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+
+use crate::utils::errors::RibbleWhisperError;
+
+// Appended to a downloaded file's name to locate its validators.
+const VALIDATORS_FILE_EXTENSION: &str = ".validators";
+const ETAG_PREFIX: &str = "etag: ";
+const LAST_MODIFIED_PREFIX: &str = "last-modified: ";
+
+/// The result of a cache-aware download request,
+/// see: [crate::downloader::request::DownloadRequest::send_sync_cached]
+pub enum CachedDownload<D> {
+    /// The file at this path is already up-to-date; no download is required.
+    UpToDate(PathBuf),
+    /// The file is missing or stale; the downloader is ready to fetch it.
+    Download(D),
+}
+
+impl<D> CachedDownload<D> {
+    pub fn is_up_to_date(&self) -> bool {
+        matches!(self, CachedDownload::UpToDate(_))
+    }
+}
+
+/// The HTTP cache validators (ETag and Last-Modified) a server returned with a download.
+/// These are stored in a small sidecar file next to the download so that later requests can ask
+/// the server whether the file has changed before downloading it again.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    /// Reads the validators from response headers.
+    /// Returns None if the server did not send either validator.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: HeaderName| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+        let validators = Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        };
+        (!validators.is_empty()).then_some(validators)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// Gets the path of the sidecar file that stores the validators for a downloaded file.
+    pub fn sidecar_path(file_path: &Path) -> PathBuf {
+        let mut file_name = file_path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(VALIDATORS_FILE_EXTENSION);
+        file_path.with_file_name(file_name)
+    }
+
+    /// Loads the stored validators for a downloaded file.
+    /// Returns None if there is no sidecar file, or if it cannot be read.
+    pub fn load(file_path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(Self::sidecar_path(file_path)).ok()?;
+        let mut validators = Self::default();
+        for line in contents.lines() {
+            if let Some(etag) = line.strip_prefix(ETAG_PREFIX) {
+                validators.etag = Some(etag.to_string());
+            } else if let Some(last_modified) = line.strip_prefix(LAST_MODIFIED_PREFIX) {
+                validators.last_modified = Some(last_modified.to_string());
+            }
+        }
+        (!validators.is_empty()).then_some(validators)
+    }
+
+    /// Stores the validators in the sidecar file for a downloaded file.
+    pub fn save(&self, file_path: &Path) -> Result<(), RibbleWhisperError> {
+        let mut contents = String::new();
+        if let Some(etag) = self.etag.as_ref() {
+            contents.push_str(&format!("{ETAG_PREFIX}{etag}\n"));
+        }
+        if let Some(last_modified) = self.last_modified.as_ref() {
+            contents.push_str(&format!("{LAST_MODIFIED_PREFIX}{last_modified}\n"));
+        }
+        fs::write(Self::sidecar_path(file_path), contents)?;
+        Ok(())
+    }
+
+    /// Removes the sidecar file for a downloaded file, if it exists.
+    pub fn remove(file_path: &Path) {
+        let sidecar = Self::sidecar_path(file_path);
+        if sidecar.is_file() {
+            let _ = fs::remove_file(sidecar);
+        }
+    }
+
+    /// Gets the conditional request headers (If-None-Match/If-Modified-Since) for these validators.
+    pub(crate) fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let mut insert = |name: HeaderName, value: Option<&String>| {
+            if let Some(value) = value.and_then(|v| HeaderValue::from_str(v).ok()) {
+                headers.insert(name, value);
+            }
+        };
+        insert(IF_NONE_MATCH, self.etag.as_ref());
+        insert(IF_MODIFIED_SINCE, self.last_modified.as_ref());
+        headers
+    }
+}
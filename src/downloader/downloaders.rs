@@ -1,6 +1,4 @@
-#[cfg(feature = "downloader-async")]
-use std::io::Write;
-use std::io::{copy, Read};
+use std::io::{copy, Read, Write};
 use std::path::{Path, PathBuf};
 
 #[cfg(feature = "downloader-async")]
@@ -11,11 +9,25 @@ use futures::StreamExt;
 use futures_core::stream::Stream;
 use url::Url;
 
+#[cfg(feature = "encoder-companions")]
+use crate::downloader::archive::{ArchiveFormat, ExtractArchive};
+use crate::downloader::cache::CacheValidators;
+use crate::downloader::manager::BandwidthLimiter;
+use crate::downloader::progress::{DownloadProgress, DownloadProgressTracker};
 #[cfg(feature = "downloader-async")]
 use crate::downloader::AsyncDownload;
 use crate::downloader::{SyncDownload, Writable};
 use crate::utils::callback::{AbortCallback, Callback, Nop};
 use crate::utils::errors::RibbleWhisperError;
+#[cfg(feature = "downloader-async")]
+use crate::utils::logging::InSpan;
+use crate::utils::logging::{log_warn, trace_event, trace_span};
+#[cfg(feature = "integrity")]
+use crate::whisper::integrity_utils::{compute_checksum, ChecksumWriter};
+#[cfg(feature = "integrity")]
+use crate::whisper::model::{DefaultModelType, ModelChecksum};
+#[cfg(feature = "encoder-companions")]
+use crate::whisper::model::{EncoderCompanion, Model};
 
 const TEMP_FILE_EXTENSION: &str = ".tmp";
 
@@ -45,6 +57,13 @@ where
     // Optional progress callback. Default is a Nop
     progress_callback: CB,
     abort_callback: A,
+    // Optional checksum to verify the download against before it is moved into place.
+    #[cfg(feature = "integrity")]
+    expected_checksum: Option<ModelChecksum>,
+    // Optional throughput cap, see: [BandwidthLimiter]
+    bandwidth_limiter: Option<BandwidthLimiter>,
+    // Cache validators from the response, to be stored alongside the download.
+    cache_validators: Option<CacheValidators>,
 }
 #[cfg(feature = "downloader-async")]
 impl<S> StreamDownloader<S, Nop<usize>, Nop<()>>
@@ -60,6 +79,10 @@ where
             total_size,
             progress_callback: Nop::new(),
             abort_callback: Nop::new(),
+            #[cfg(feature = "integrity")]
+            expected_checksum: None,
+            bandwidth_limiter: None,
+            cache_validators: None,
         }
     }
 }
@@ -86,6 +109,10 @@ where
             total_size,
             progress_callback,
             abort_callback,
+            #[cfg(feature = "integrity")]
+            expected_checksum: None,
+            bandwidth_limiter: None,
+            cache_validators: None,
         }
     }
 
@@ -103,6 +130,10 @@ where
             total_size,
             progress_callback,
             abort_callback: Nop::new(),
+            #[cfg(feature = "integrity")]
+            expected_checksum: None,
+            bandwidth_limiter: None,
+            cache_validators: None,
         }
     }
 
@@ -119,6 +150,10 @@ where
             total_size,
             progress_callback: Nop::new(),
             abort_callback,
+            #[cfg(feature = "integrity")]
+            expected_checksum: None,
+            bandwidth_limiter: None,
+            cache_validators: None,
         }
     }
 
@@ -127,13 +162,18 @@ where
     where
         S2: Stream<Item = Result<Bytes, reqwest::Error>> + Unpin,
     {
-        StreamDownloader::new_full(
+        StreamDownloader {
             file_stream,
-            self.content_name,
-            self.total_size,
-            self.progress_callback,
-            self.abort_callback,
-        )
+            content_name: self.content_name,
+            progress: self.progress,
+            total_size: self.total_size,
+            progress_callback: self.progress_callback,
+            abort_callback: self.abort_callback,
+            #[cfg(feature = "integrity")]
+            expected_checksum: self.expected_checksum,
+            bandwidth_limiter: self.bandwidth_limiter,
+            cache_validators: self.cache_validators,
+        }
     }
 
     /// Sets an optional progress callback.
@@ -142,26 +182,74 @@ where
     where
         C: Callback<Argument = usize>,
     {
-        StreamDownloader::new_full(
-            self.file_stream,
-            self.content_name,
-            self.total_size,
+        StreamDownloader {
+            file_stream: self.file_stream,
+            content_name: self.content_name,
+            progress: self.progress,
+            total_size: self.total_size,
             progress_callback,
-            self.abort_callback,
-        )
+            abort_callback: self.abort_callback,
+            #[cfg(feature = "integrity")]
+            expected_checksum: self.expected_checksum,
+            bandwidth_limiter: self.bandwidth_limiter,
+            cache_validators: self.cache_validators,
+        }
+    }
+
+    /// Sets a progress callback that receives structured [DownloadProgress] updates, (bytes, total,
+    /// speed and ETA) in place of the raw byte count.
+    /// To deliver the updates over a channel, use [crate::downloader::progress::DownloadProgressSender]
+    pub fn with_download_progress_callback<C>(
+        self,
+        progress_callback: C,
+    ) -> StreamDownloader<S, DownloadProgressTracker<C>, A>
+    where
+        C: Callback<Argument = DownloadProgress>,
+    {
+        let total_size = self.total_size;
+        self.with_progress_callback(DownloadProgressTracker::new(progress_callback, total_size))
     }
 
     pub fn with_abort_callback<A2>(self, abort_callback: A2) -> StreamDownloader<S, CB, A2>
     where
         A2: AbortCallback,
     {
-        StreamDownloader::new_full(
-            self.file_stream,
-            self.content_name,
-            self.total_size,
-            self.progress_callback,
+        StreamDownloader {
+            file_stream: self.file_stream,
+            content_name: self.content_name,
+            progress: self.progress,
+            total_size: self.total_size,
+            progress_callback: self.progress_callback,
             abort_callback,
-        )
+            #[cfg(feature = "integrity")]
+            expected_checksum: self.expected_checksum,
+            bandwidth_limiter: self.bandwidth_limiter,
+            cache_validators: self.cache_validators,
+        }
+    }
+
+    /// Sets an optional checksum to verify the download against.
+    /// If the downloaded file does not match, it is removed and the download returns
+    /// [RibbleWhisperError::ChecksumMismatch].
+    /// Requires the integrity feature flag to be set.
+    #[cfg(feature = "integrity")]
+    pub fn with_expected_checksum(mut self, checksum: Option<ModelChecksum>) -> Self {
+        self.expected_checksum = checksum;
+        self
+    }
+
+    /// Sets an optional bandwidth limiter to cap the download's throughput.
+    /// Supply the same (cloned) limiter to several downloaders to cap their combined throughput.
+    pub fn with_bandwidth_limiter(mut self, bandwidth_limiter: Option<BandwidthLimiter>) -> Self {
+        self.bandwidth_limiter = bandwidth_limiter;
+        self
+    }
+
+    /// Sets the cache validators to store alongside the download once it completes.
+    /// See: [crate::downloader::request::DownloadRequest::send_sync_cached]
+    pub fn with_cache_validators(mut self, cache_validators: Option<CacheValidators>) -> Self {
+        self.cache_validators = cache_validators;
+        self
     }
 
     /// Gets the download's total size
@@ -194,15 +282,23 @@ where
     /// This function must be awaited and should not be called on a UI thread.
     async fn download(&mut self, file_directory: &Path) -> Result<PathBuf, RibbleWhisperError> {
         Self::prepare_file_path(file_directory)?;
+        Self::ensure_disk_space(file_directory, self.total_size)?;
 
         let file_path = file_directory.join(&self.content_name);
         let tmp_path = file_directory.join([&self.content_name, TEMP_FILE_EXTENSION].concat());
 
         let mut dest = Self::open_write_file(tmp_path.as_path())?;
 
-        let stream = &mut self.file_stream;
+        let span = trace_span!(
+            INFO,
+            "download",
+            content_name = %self.content_name,
+            total_size = self.total_size,
+        );
+        let streamed = self.stream_into(&mut dest).in_span(span).await;
+        trace_event!(INFO, bytes = ?streamed.as_ref().ok(), "Download finished");
 
-        let cleanup = || {
+        if let Err(e) = streamed {
             // It's not particularly necessary to know that the file has been successfully removed.
             // If this fails, it most likely didn't begin to the first place and cleanup isn't
             // required.
@@ -211,23 +307,63 @@ where
             } else {
                 let _ = std::fs::remove_dir(tmp_path.as_path());
             }
-        };
+            return Err(e);
+        }
+
+        // Flush + close the file before computing its digest.
+        drop(dest);
+        #[cfg(feature = "integrity")]
+        verify_download(
+            tmp_path.as_path(),
+            &self.content_name,
+            self.expected_checksum.as_ref(),
+        )?;
+
+        std::fs::rename(tmp_path.as_path(), file_path.as_path())?;
+        store_cache_validators(file_path.as_path(), self.cache_validators.as_ref());
+        Ok(file_path)
+    }
+
+    /// Downloads into the writer asynchronously. Returns Err on I/O failure.
+    /// If the download has an expected checksum and the data does not match, this returns
+    /// [RibbleWhisperError::ChecksumMismatch]; the data already written should be discarded.
+    /// This function must be awaited and should not be called on a UI thread.
+    async fn download_to_writer<W: Write>(
+        &mut self,
+        writer: &mut W,
+    ) -> Result<usize, RibbleWhisperError> {
+        #[cfg(feature = "integrity")]
+        if let Some(expected) = self.expected_checksum.clone() {
+            let mut hashing_writer = ChecksumWriter::new(writer, &expected.as_checksum());
+            let downloaded = self.stream_into(&mut hashing_writer).await?;
+            verify_digest(&self.content_name, &expected, hashing_writer.finalize())?;
+            return Ok(downloaded);
+        }
+        self.stream_into(writer).await
+    }
+}
 
-        while let Some(next) = stream.next().await {
+#[cfg(feature = "downloader-async")]
+impl<S, CB, A> StreamDownloader<S, CB, A>
+where
+    S: Stream<Item = Result<Bytes, reqwest::Error>> + Unpin,
+    CB: Callback<Argument = usize>,
+    A: AbortCallback,
+{
+    // Streams the body into dest, returning the number of bytes written.
+    async fn stream_into<W: Write>(&mut self, dest: &mut W) -> Result<usize, RibbleWhisperError> {
+        let mut downloaded = 0;
+        while let Some(next) = self.file_stream.next().await {
             // Call the abort callback and escape if the download is cancelled
             if self.abort_callback.abort() {
-                cleanup();
                 return Err(RibbleWhisperError::DownloadAborted(
                     self.content_name.clone(),
                 ));
             }
 
-            let buf = next.inspect_err(|_e| {
-                cleanup();
-            })?;
-            dest.write_all(&buf).inspect_err(|_e| {
-                cleanup();
-            })?;
+            let buf = next?;
+            dest.write_all(&buf)?;
+            downloaded += buf.len();
 
             let mut cur_progress = self.progress;
 
@@ -236,10 +372,16 @@ where
 
             // Update the UI with the current progress
             self.progress_callback.call(self.progress);
-        }
 
-        std::fs::rename(tmp_path.as_path(), file_path.as_path())?;
-        Ok(file_path)
+            if let Some(limiter) = self.bandwidth_limiter.as_ref() {
+                let delay = limiter.reserve(buf.len());
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+        dest.flush()?;
+        Ok(downloaded)
     }
 }
 
@@ -263,6 +405,12 @@ where
     total_size: usize,
     progress_callback: CB,
     abort_callback: A,
+    #[cfg(feature = "integrity")]
+    expected_checksum: Option<ModelChecksum>,
+    // Optional throughput cap, see: [BandwidthLimiter]
+    bandwidth_limiter: Option<BandwidthLimiter>,
+    // Cache validators from the response, to be stored alongside the download.
+    cache_validators: Option<CacheValidators>,
 }
 
 impl<R: Read> SyncDownloader<R, Nop<usize>, Nop<()>> {
@@ -275,6 +423,10 @@ impl<R: Read> SyncDownloader<R, Nop<usize>, Nop<()>> {
             total_size,
             progress_callback: Nop::new(),
             abort_callback: Nop::new(),
+            #[cfg(feature = "integrity")]
+            expected_checksum: None,
+            bandwidth_limiter: None,
+            cache_validators: None,
         }
     }
 }
@@ -300,6 +452,10 @@ where
             total_size,
             progress_callback,
             abort_callback,
+            #[cfg(feature = "integrity")]
+            expected_checksum: None,
+            bandwidth_limiter: None,
+            cache_validators: None,
         }
     }
 
@@ -317,18 +473,27 @@ where
             total_size,
             progress_callback,
             abort_callback: Nop::new(),
+            #[cfg(feature = "integrity")]
+            expected_checksum: None,
+            bandwidth_limiter: None,
+            cache_validators: None,
         }
     }
 
     /// Sets the file stream.
     pub fn with_file_stream<R2: Read>(self, file_stream: R2) -> SyncDownloader<R2, CB, A> {
-        SyncDownloader::new_full(
+        SyncDownloader {
             file_stream,
-            self.content_name,
-            self.total_size,
-            self.progress_callback,
-            self.abort_callback,
-        )
+            content_name: self.content_name,
+            progress: self.progress,
+            total_size: self.total_size,
+            progress_callback: self.progress_callback,
+            abort_callback: self.abort_callback,
+            #[cfg(feature = "integrity")]
+            expected_checksum: self.expected_checksum,
+            bandwidth_limiter: self.bandwidth_limiter,
+            cache_validators: self.cache_validators,
+        }
     }
     /// Sets the (optional) progress callback.
     /// To un-set the callback, supply a [Nop]
@@ -336,26 +501,74 @@ where
     where
         C: Callback<Argument = usize>,
     {
-        SyncDownloader::new_full(
-            self.file_stream,
-            self.content_name,
-            self.total_size,
+        SyncDownloader {
+            file_stream: self.file_stream,
+            content_name: self.content_name,
+            progress: self.progress,
+            total_size: self.total_size,
             progress_callback,
-            self.abort_callback,
-        )
+            abort_callback: self.abort_callback,
+            #[cfg(feature = "integrity")]
+            expected_checksum: self.expected_checksum,
+            bandwidth_limiter: self.bandwidth_limiter,
+            cache_validators: self.cache_validators,
+        }
+    }
+
+    /// Sets a progress callback that receives structured [DownloadProgress] updates, (bytes, total,
+    /// speed and ETA) in place of the raw byte count.
+    /// To deliver the updates over a channel, use [crate::downloader::progress::DownloadProgressSender]
+    pub fn with_download_progress_callback<C>(
+        self,
+        progress_callback: C,
+    ) -> SyncDownloader<R, DownloadProgressTracker<C>, A>
+    where
+        C: Callback<Argument = DownloadProgress>,
+    {
+        let total_size = self.total_size;
+        self.with_progress_callback(DownloadProgressTracker::new(progress_callback, total_size))
     }
 
     pub fn with_abort_callback<A2>(self, abort_callback: A2) -> SyncDownloader<R, CB, A2>
     where
         A2: AbortCallback,
     {
-        SyncDownloader::new_full(
-            self.file_stream,
-            self.content_name,
-            self.total_size,
-            self.progress_callback,
+        SyncDownloader {
+            file_stream: self.file_stream,
+            content_name: self.content_name,
+            progress: self.progress,
+            total_size: self.total_size,
+            progress_callback: self.progress_callback,
             abort_callback,
-        )
+            #[cfg(feature = "integrity")]
+            expected_checksum: self.expected_checksum,
+            bandwidth_limiter: self.bandwidth_limiter,
+            cache_validators: self.cache_validators,
+        }
+    }
+
+    /// Sets an optional checksum to verify the download against.
+    /// If the downloaded file does not match, it is removed and the download returns
+    /// [RibbleWhisperError::ChecksumMismatch].
+    /// Requires the integrity feature flag to be set.
+    #[cfg(feature = "integrity")]
+    pub fn with_expected_checksum(mut self, checksum: Option<ModelChecksum>) -> Self {
+        self.expected_checksum = checksum;
+        self
+    }
+
+    /// Sets an optional bandwidth limiter to cap the download's throughput.
+    /// Supply the same (cloned) limiter to several downloaders to cap their combined throughput.
+    pub fn with_bandwidth_limiter(mut self, bandwidth_limiter: Option<BandwidthLimiter>) -> Self {
+        self.bandwidth_limiter = bandwidth_limiter;
+        self
+    }
+
+    /// Sets the cache validators to store alongside the download once it completes.
+    /// See: [crate::downloader::request::DownloadRequest::send_sync_cached]
+    pub fn with_cache_validators(mut self, cache_validators: Option<CacheValidators>) -> Self {
+        self.cache_validators = cache_validators;
+        self
     }
 
     /// Gets the download's total size
@@ -386,6 +599,13 @@ where
 
             // Update the UI with the current progress
             self.progress_callback.call(self.progress);
+
+            if let Some(limiter) = self.bandwidth_limiter.as_ref() {
+                let delay = limiter.reserve(num_bytes);
+                if !delay.is_zero() {
+                    std::thread::sleep(delay);
+                }
+            }
         };
         byte_read
     }
@@ -409,19 +629,25 @@ where
     /// This will block the calling thread.
     fn download(&mut self, file_directory: &Path) -> Result<PathBuf, RibbleWhisperError> {
         Self::prepare_file_path(file_directory)?;
+        Self::ensure_disk_space(file_directory, self.total_size)?;
 
         let file_path = file_directory.join(&self.content_name);
         let tmp_path = file_directory.join([&self.content_name, TEMP_FILE_EXTENSION].concat());
 
         let mut dest = Self::open_write_file(tmp_path.as_path())?;
 
-        let downloaded = copy(self, &mut dest).map_err(|e| {
-            if e.kind() == std::io::ErrorKind::ConnectionAborted {
-                RibbleWhisperError::DownloadAborted(self.content_name.clone())
-            } else {
-                e.into()
-            }
-        });
+        let downloaded = {
+            let _span = trace_span!(
+                INFO,
+                "download",
+                content_name = %self.content_name,
+                total_size = self.total_size,
+            )
+            .entered();
+            let downloaded = self.copy_into(&mut dest);
+            trace_event!(INFO, bytes = ?downloaded.as_ref().ok(), "Download finished");
+            downloaded
+        };
 
         if downloaded.is_err() {
             // It's not particularly necessary to know that the file has been successfully removed.
@@ -435,13 +661,59 @@ where
 
             return Err(downloaded.err().unwrap());
         } else {
+            drop(dest);
+            #[cfg(feature = "integrity")]
+            verify_download(
+                tmp_path.as_path(),
+                &self.content_name,
+                self.expected_checksum.as_ref(),
+            )?;
             // Otherwise, rename the temporary file to the file_path
             // Expect that this will never fail, but in case it does, the error will be returned.
             std::fs::rename(tmp_path.as_path(), file_path.as_path())?;
+            store_cache_validators(file_path.as_path(), self.cache_validators.as_ref());
         }
 
         Ok(file_path)
     }
+
+    /// Downloads into the writer synchronously. Returns Err on I/O failure.
+    /// If the download has an expected checksum and the data does not match, this returns
+    /// [RibbleWhisperError::ChecksumMismatch]; the data already written should be discarded.
+    /// This will block the calling thread.
+    fn download_to_writer<W: Write>(
+        &mut self,
+        writer: &mut W,
+    ) -> Result<usize, RibbleWhisperError> {
+        #[cfg(feature = "integrity")]
+        if let Some(expected) = self.expected_checksum.clone() {
+            let mut hashing_writer = ChecksumWriter::new(writer, &expected.as_checksum());
+            let downloaded = self.copy_into(&mut hashing_writer)?;
+            verify_digest(&self.content_name, &expected, hashing_writer.finalize())?;
+            return Ok(downloaded);
+        }
+        self.copy_into(writer)
+    }
+}
+
+impl<R, CB, A> SyncDownloader<R, CB, A>
+where
+    R: Read,
+    CB: Callback<Argument = usize>,
+    A: AbortCallback,
+{
+    // Copies the body into dest, returning the number of bytes written.
+    fn copy_into<W: Write>(&mut self, dest: &mut W) -> Result<usize, RibbleWhisperError> {
+        let downloaded = copy(self, dest).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::ConnectionAborted {
+                RibbleWhisperError::DownloadAborted(self.content_name.clone())
+            } else {
+                e.into()
+            }
+        })?;
+        dest.flush()?;
+        Ok(downloaded as usize)
+    }
 }
 
 /// Returns a StreamDownloader that encapsulates the request bytestream, progress,
@@ -539,7 +811,119 @@ pub fn sync_download_request(
     ))
 }
 
-enum BorrowedDownloadResponse<'a> {
+/// Returns a SyncDownloader for a [DefaultModelType] that verifies the downloaded file against the
+/// model's expected checksum before moving it into place.
+/// See: [sync_download_request]
+#[cfg(feature = "integrity")]
+pub fn sync_model_download_request(
+    model_type: DefaultModelType,
+) -> Result<SyncDownloader<impl Read, Nop<usize>, Nop<()>>, RibbleWhisperError> {
+    let url = model_type.url();
+    let downloader = sync_download_request(url.as_str(), model_type.to_file_name())?;
+    Ok(downloader.with_expected_checksum(Some(model_type.expected_checksum())))
+}
+
+/// Returns a StreamDownloader for a [DefaultModelType] that verifies the downloaded file against the
+/// model's expected checksum before moving it into place.
+/// See: [async_download_request]
+#[cfg(all(feature = "integrity", feature = "downloader-async"))]
+pub async fn async_model_download_request(
+    model_type: DefaultModelType,
+) -> Result<
+    StreamDownloader<impl Stream<Item = Result<Bytes, reqwest::Error>>, Nop<usize>, Nop<()>>,
+    RibbleWhisperError,
+> {
+    let url = model_type.url();
+    let downloader = async_download_request(url.as_str(), model_type.to_file_name()).await?;
+    Ok(downloader.with_expected_checksum(Some(model_type.expected_checksum())))
+}
+
+/// Downloads a zipped [EncoderCompanion] (e.g. a CoreML .mlmodelc) and extracts it next to its
+/// model in model_directory, where whisper.cpp expects to find it. The archive is removed once it
+/// has been extracted.
+/// Requires the encoder-companions feature flag to be set.
+/// # Arguments:
+/// * downloader: a SyncDownloader for the zipped encoder, see: [DefaultModelType::encoder_companion_url]
+/// * model: the model the encoder belongs to
+/// * companion: the kind of encoder being downloaded
+/// * model_directory: the directory containing the model
+/// # Returns:
+/// * Ok(PathBuf) to the extracted encoder on success
+/// * Err on download/extraction failure, or if the archive did not contain the expected encoder.
+#[cfg(feature = "encoder-companions")]
+pub fn download_encoder_companion<R, CB, A>(
+    mut downloader: SyncDownloader<R, CB, A>,
+    model: &Model,
+    companion: EncoderCompanion,
+    model_directory: &Path,
+) -> Result<PathBuf, RibbleWhisperError>
+where
+    R: Read,
+    CB: Callback<Argument = usize>,
+    A: AbortCallback,
+{
+    let mut extract = ExtractArchive::new().with_format(ArchiveFormat::Zip);
+    downloader.download_and_process(model_directory, &mut extract)?;
+
+    let companion_path = model_directory.join(model.encoder_companion_file_name(companion));
+    if !companion_path.exists() {
+        return Err(RibbleWhisperError::DownloadError(format!(
+            "Archive did not contain the expected encoder: {:?}",
+            companion_path
+        )));
+    }
+    Ok(companion_path)
+}
+
+// Checks a finished (temporary) download against its expected checksum.
+// On a mismatch, the file is removed so that a corrupted model never ends up in the model directory.
+#[cfg(feature = "integrity")]
+fn verify_download(
+    tmp_path: &Path,
+    content_name: &str,
+    expected_checksum: Option<&ModelChecksum>,
+) -> Result<(), RibbleWhisperError> {
+    let Some(expected) = expected_checksum else {
+        return Ok(());
+    };
+
+    let actual = compute_checksum(tmp_path, &expected.as_checksum()).inspect_err(|_e| {
+        let _ = std::fs::remove_file(tmp_path);
+    })?;
+
+    verify_digest(content_name, expected, actual).inspect_err(|_e| {
+        let _ = std::fs::remove_file(tmp_path);
+    })
+}
+
+#[cfg(feature = "integrity")]
+fn verify_digest(
+    content_name: &str,
+    expected: &ModelChecksum,
+    actual: String,
+) -> Result<(), RibbleWhisperError> {
+    if actual != expected.digest().to_lowercase() {
+        return Err(RibbleWhisperError::ChecksumMismatch {
+            file_name: content_name.to_string(),
+            expected: expected.digest().to_string(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+// Stale validators are removed so that a replaced file is never reported as up-to-date.
+// Failing to store validators only means the next cached request downloads the file again.
+fn store_cache_validators(file_path: &Path, cache_validators: Option<&CacheValidators>) {
+    CacheValidators::remove(file_path);
+    if let Some(validators) = cache_validators
+        && let Err(_e) = validators.save(file_path)
+    {
+        log_warn!("Failed to store cache validators for {file_path:?}. Error: {_e}");
+    }
+}
+
+pub(crate) enum BorrowedDownloadResponse<'a> {
     Async(&'a reqwest::Response),
     Blocking(&'a reqwest::blocking::Response),
 }
@@ -562,7 +946,7 @@ impl BorrowedDownloadResponse<'_> {
     }
 }
 
-fn get_content_name(response: BorrowedDownloadResponse) -> Option<String> {
+pub(crate) fn get_content_name(response: BorrowedDownloadResponse) -> Option<String> {
     let content_disp = response.response_headers_get_all(reqwest::header::CONTENT_DISPOSITION);
     let try_content_name = content_disp
         .iter()
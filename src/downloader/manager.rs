@@ -0,0 +1,236 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::{Condvar, Mutex};
+
+#[cfg(feature = "downloader-async")]
+use crate::downloader::downloaders::StreamDownloader;
+use crate::downloader::downloaders::SyncDownloader;
+#[cfg(feature = "downloader-async")]
+use crate::downloader::AsyncDownload;
+use crate::downloader::SyncDownload;
+use crate::utils::callback::{AbortCallback, Callback};
+use crate::utils::errors::RibbleWhisperError;
+#[cfg(feature = "downloader-async")]
+use bytes::Bytes;
+#[cfg(feature = "downloader-async")]
+use futures_core::stream::Stream;
+
+/// The default number of downloads a [DownloadManager] allows to run at once.
+pub const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 2;
+
+struct LimiterState {
+    // Bytes that can be consumed without waiting; negative when downloads are ahead of the limit.
+    available: f64,
+    last_update: Instant,
+}
+
+/// A token-bucket bandwidth limiter that caps download throughput at a maximum number of bytes per
+/// second. Cloned limiters share the same budget, so a single limiter can cap the combined
+/// throughput of several downloads.
+/// Bursts of up to one second's worth of bytes are allowed after a download has been idle.
+#[derive(Clone)]
+pub struct BandwidthLimiter {
+    max_bytes_per_sec: f64,
+    state: Arc<Mutex<LimiterState>>,
+}
+
+impl BandwidthLimiter {
+    /// # Arguments:
+    /// * max_bytes_per_sec: the throughput cap. This will always be at least 1.
+    pub fn new(max_bytes_per_sec: u64) -> Self {
+        let max_bytes_per_sec = max_bytes_per_sec.max(1) as f64;
+        Self {
+            max_bytes_per_sec,
+            state: Arc::new(Mutex::new(LimiterState {
+                available: max_bytes_per_sec,
+                last_update: Instant::now(),
+            })),
+        }
+    }
+
+    pub fn max_bytes_per_sec(&self) -> u64 {
+        self.max_bytes_per_sec as u64
+    }
+
+    /// Consumes `bytes` from the budget and returns how long the caller should wait before
+    /// reading more, (zero if it is within the limit).
+    pub fn reserve(&self, bytes: usize) -> Duration {
+        let mut state = self.state.lock();
+        let now = Instant::now();
+        let refill = now.duration_since(state.last_update).as_secs_f64() * self.max_bytes_per_sec;
+        state.available = (state.available + refill).min(self.max_bytes_per_sec) - bytes as f64;
+        state.last_update = now;
+
+        if state.available < 0.0 {
+            Duration::from_secs_f64(-state.available / self.max_bytes_per_sec)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+struct PermitState {
+    active: Mutex<usize>,
+    released: Condvar,
+    #[cfg(feature = "downloader-async")]
+    released_async: tokio::sync::Notify,
+}
+
+/// A slot in a [DownloadManager]'s concurrency limit. The slot is released when this is dropped.
+pub struct DownloadPermit {
+    state: Arc<PermitState>,
+}
+
+impl Drop for DownloadPermit {
+    fn drop(&mut self) {
+        *self.state.active.lock() -= 1;
+        self.state.released.notify_all();
+        #[cfg(feature = "downloader-async")]
+        self.state.released_async.notify_waiters();
+    }
+}
+
+/// Coordinates model downloads so that background fetching does not saturate the user's
+/// connection, (e.g. while captioning a live call). The manager limits the number of concurrent
+/// downloads and can optionally cap their combined bandwidth.
+///
+/// Clones share the same limits, so a single manager can be handed to multiple threads or
+/// [crate::whisper::auto_fetch::AutoFetchModelBank]s.
+#[derive(Clone)]
+pub struct DownloadManager {
+    max_concurrent: usize,
+    bandwidth_limiter: Option<BandwidthLimiter>,
+    permits: Arc<PermitState>,
+}
+
+impl DownloadManager {
+    /// Creates a manager allowing [DEFAULT_MAX_CONCURRENT_DOWNLOADS] with no bandwidth limit.
+    pub fn new() -> Self {
+        Self {
+            max_concurrent: DEFAULT_MAX_CONCURRENT_DOWNLOADS,
+            bandwidth_limiter: None,
+            permits: Arc::new(PermitState {
+                active: Mutex::new(0),
+                released: Condvar::new(),
+                #[cfg(feature = "downloader-async")]
+                released_async: tokio::sync::Notify::new(),
+            }),
+        }
+    }
+
+    /// Sets the maximum number of downloads that may run at once. This will always be at least 1.
+    pub fn with_max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent.max(1);
+        self
+    }
+
+    /// Caps the combined throughput of all downloads run through the manager.
+    /// Supply None to remove the limit.
+    pub fn with_max_bytes_per_sec(mut self, max_bytes_per_sec: Option<u64>) -> Self {
+        self.bandwidth_limiter = max_bytes_per_sec.map(BandwidthLimiter::new);
+        self
+    }
+
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    pub fn bandwidth_limiter(&self) -> Option<&BandwidthLimiter> {
+        self.bandwidth_limiter.as_ref()
+    }
+
+    /// Gets the number of downloads currently holding a permit.
+    pub fn active_downloads(&self) -> usize {
+        *self.permits.active.lock()
+    }
+
+    /// Returns a permit if a download slot is free, otherwise None.
+    pub fn try_acquire(&self) -> Option<DownloadPermit> {
+        let mut active = self.permits.active.lock();
+        (*active < self.max_concurrent).then(|| {
+            *active += 1;
+            self.permit()
+        })
+    }
+
+    /// Blocks the calling thread until a download slot is free.
+    pub fn acquire(&self) -> DownloadPermit {
+        let mut active = self.permits.active.lock();
+        while *active >= self.max_concurrent {
+            self.permits.released.wait(&mut active);
+        }
+        *active += 1;
+        self.permit()
+    }
+
+    /// Waits until a download slot is free.
+    #[cfg(feature = "downloader-async")]
+    pub async fn acquire_async(&self) -> DownloadPermit {
+        loop {
+            // Register for the notification before checking, so a release in between is not missed.
+            let released = self.permits.released_async.notified();
+            tokio::pin!(released);
+            released.as_mut().enable();
+            if let Some(permit) = self.try_acquire() {
+                return permit;
+            }
+            released.await;
+        }
+    }
+
+    fn permit(&self) -> DownloadPermit {
+        DownloadPermit {
+            state: Arc::clone(&self.permits),
+        }
+    }
+
+    /// Runs a blocking download once a slot is free, applying the manager's bandwidth limit.
+    /// This will block the calling thread until the download has finished.
+    /// # Returns:
+    /// * Ok(PathBuf) with the downloaded file path on success
+    /// * Err(RibbleWhisperError) on failure, see: [SyncDownload::download]
+    pub fn download_sync<R, CB, A>(
+        &self,
+        downloader: SyncDownloader<R, CB, A>,
+        file_directory: &Path,
+    ) -> Result<PathBuf, RibbleWhisperError>
+    where
+        R: Read,
+        CB: Callback<Argument = usize>,
+        A: AbortCallback,
+    {
+        let _permit = self.acquire();
+        let mut downloader = downloader.with_bandwidth_limiter(self.bandwidth_limiter.clone());
+        downloader.download(file_directory)
+    }
+
+    /// Runs a download once a slot is free, applying the manager's bandwidth limit.
+    /// NOTE: This function must be awaited and should not be called on a UI thread.
+    /// # Returns:
+    /// * Ok(PathBuf) with the downloaded file path on success
+    /// * Err(RibbleWhisperError) on failure, see: [AsyncDownload::download]
+    #[cfg(feature = "downloader-async")]
+    pub async fn download_async<S, CB, A>(
+        &self,
+        downloader: StreamDownloader<S, CB, A>,
+        file_directory: &Path,
+    ) -> Result<PathBuf, RibbleWhisperError>
+    where
+        S: Stream<Item = Result<Bytes, reqwest::Error>> + Unpin,
+        CB: Callback<Argument = usize>,
+        A: AbortCallback,
+    {
+        let _permit = self.acquire_async().await;
+        let mut downloader = downloader.with_bandwidth_limiter(self.bandwidth_limiter.clone());
+        downloader.download(file_directory).await
+    }
+}
+
+impl Default for DownloadManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
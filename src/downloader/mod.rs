@@ -1,14 +1,62 @@
 use std::fs::{self, File};
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+#[cfg(feature = "archive")]
+use crate::downloader::archive::PostDownload;
 use crate::utils::errors::RibbleWhisperError;
+use crate::utils::logging::log_warn;
 
+#[cfg(feature = "archive")]
+pub mod archive;
+pub mod cache;
 pub mod downloaders;
+pub mod manager;
+pub mod progress;
+pub mod request;
+
+/// Extra space (in bytes) required on top of a download's size before it is started: 64 MiB
+pub const DISK_SPACE_MARGIN: u64 = 64 * 1024 * 1024;
+
+/// Gets the free space (in bytes) available to the current user on the volume containing path.
+pub fn available_disk_space(path: &Path) -> Result<u64, RibbleWhisperError> {
+    Ok(fs4::available_space(path)?)
+}
 
 /// For downloading an object synchronously (blocking)
 pub trait SyncDownload: Writable {
     /// Downloads from the URL and returns the sanitized file stem
     fn download(&mut self, file_directory: &Path) -> Result<PathBuf, RibbleWhisperError>;
+
+    /// Downloads into any writer and returns the number of bytes written.
+    fn download_to_writer<W: Write>(&mut self, writer: &mut W)
+        -> Result<usize, RibbleWhisperError>;
+
+    /// Downloads into memory, e.g. to load a model as a
+    /// [crate::whisper::model::ModelLocation::DynamicBuffer] without touching the disk.
+    fn download_to_memory(&mut self) -> Result<Vec<u8>, RibbleWhisperError> {
+        let mut buffer = vec![];
+        self.download_to_writer(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Downloads into a shared buffer, see: [crate::whisper::model::ModelLocation::SharedBuffer]
+    fn download_to_shared_buffer(&mut self) -> Result<Arc<[u8]>, RibbleWhisperError> {
+        Ok(self.download_to_memory()?.into())
+    }
+
+    /// Downloads the file then runs a [PostDownload] step on it, (e.g. extracting an archive).
+    /// Returns the path produced by the post-download step.
+    #[cfg(feature = "archive")]
+    fn download_and_process<P: PostDownload>(
+        &mut self,
+        file_directory: &Path,
+        post_download: &mut P,
+    ) -> Result<PathBuf, RibbleWhisperError> {
+        let downloaded_path = self.download(file_directory)?;
+        post_download.process(&downloaded_path)
+    }
 }
 
 /// For downloading an object asynchronously (non-blocking, requires async runtime)
@@ -19,6 +67,43 @@ pub trait AsyncDownload: Writable {
         &mut self,
         file_directory: &Path,
     ) -> impl Future<Output = Result<PathBuf, RibbleWhisperError>>;
+
+    /// Downloads into any writer and returns the number of bytes written.
+    fn download_to_writer<W: Write>(
+        &mut self,
+        writer: &mut W,
+    ) -> impl Future<Output = Result<usize, RibbleWhisperError>>;
+
+    /// Downloads into memory, see: [SyncDownload::download_to_memory]
+    fn download_to_memory(&mut self) -> impl Future<Output = Result<Vec<u8>, RibbleWhisperError>> {
+        async move {
+            let mut buffer = vec![];
+            self.download_to_writer(&mut buffer).await?;
+            Ok(buffer)
+        }
+    }
+
+    /// Downloads into a shared buffer, see: [crate::whisper::model::ModelLocation::SharedBuffer]
+    fn download_to_shared_buffer(
+        &mut self,
+    ) -> impl Future<Output = Result<Arc<[u8]>, RibbleWhisperError>> {
+        async move { Ok(self.download_to_memory().await?.into()) }
+    }
+
+    /// Downloads the file then runs a [PostDownload] step on it,
+    /// see: [SyncDownload::download_and_process]
+    /// NOTE: the post-download step runs on the calling task; large archives will block it.
+    #[cfg(feature = "archive")]
+    fn download_and_process<P: PostDownload>(
+        &mut self,
+        file_directory: &Path,
+        post_download: &mut P,
+    ) -> impl Future<Output = Result<PathBuf, RibbleWhisperError>> {
+        async move {
+            let downloaded_path = self.download(file_directory).await?;
+            post_download.process(&downloaded_path)
+        }
+    }
 }
 
 /// To handle basic IO operations when downlading files
@@ -32,6 +117,30 @@ pub trait Writable {
         Ok(())
     }
 
+    /// Checks that file_directory has room for a download of download_size bytes, plus
+    /// [DISK_SPACE_MARGIN], so that a download fails early instead of running out of space midway.
+    /// Indeterminate sizes (0 or 1) are not checked. If the free space cannot be determined, the
+    /// check is skipped.
+    fn ensure_disk_space(
+        file_directory: &Path,
+        download_size: usize,
+    ) -> Result<(), RibbleWhisperError> {
+        if download_size <= 1 {
+            return Ok(());
+        }
+        let needed = download_size as u64 + DISK_SPACE_MARGIN;
+        match available_disk_space(file_directory) {
+            Ok(available) if available < needed => {
+                Err(RibbleWhisperError::InsufficientDiskSpace { needed, available })
+            }
+            Ok(_) => Ok(()),
+            Err(_e) => {
+                log_warn!("Failed to check disk space for {file_directory:?}. Error: {_e}");
+                Ok(())
+            }
+        }
+    }
+
     fn open_write_file(file_path: &Path) -> Result<File, RibbleWhisperError> {
         let dest = fs::OpenOptions::new()
             .create(true)
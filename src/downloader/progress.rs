@@ -0,0 +1,131 @@
+use std::time::{Duration, Instant};
+
+use crate::utils::callback::Callback;
+use crate::utils::Sender;
+
+// The minimum window over which download speed is sampled.
+const SPEED_SAMPLE_WINDOW: Duration = Duration::from_millis(250);
+// The weight given to the newest speed sample in the moving average.
+const SPEED_SMOOTHING: f64 = 0.3;
+
+/// A structured download progress update.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DownloadProgress {
+    /// The number of bytes downloaded so far.
+    pub bytes: usize,
+    /// The total download size, or None if it is indeterminate (e.g. no content-length).
+    pub total: Option<usize>,
+    /// A smoothed estimate of the current download speed.
+    pub bytes_per_sec: f64,
+    /// The estimated time remaining, or None if the total size or speed is not yet known.
+    pub eta: Option<Duration>,
+}
+
+impl DownloadProgress {
+    /// Gets the fraction of the download that has completed (0.0 - 1.0), if the total is known.
+    pub fn fraction(&self) -> Option<f64> {
+        self.total
+            .map(|total| (self.bytes as f64 / total.max(1) as f64).min(1.0))
+    }
+}
+
+/// Adapts the raw byte-count progress of a downloader into [DownloadProgress] updates, computing
+/// the download speed and ETA. Use [crate::downloader::downloaders::SyncDownloader::with_download_progress_callback]
+/// (or the StreamDownloader equivalent) to set this up.
+pub struct DownloadProgressTracker<CB>
+where
+    CB: Callback<Argument = DownloadProgress>,
+{
+    callback: CB,
+    total: Option<usize>,
+    started: Option<Instant>,
+    last_sample: Option<(Instant, usize)>,
+    bytes_per_sec: f64,
+}
+
+impl<CB> DownloadProgressTracker<CB>
+where
+    CB: Callback<Argument = DownloadProgress>,
+{
+    /// # Arguments:
+    /// * callback: receives the structured progress updates
+    /// * total_size: the downloader's total_size. A size of 0 or 1 is treated as indeterminate.
+    pub fn new(callback: CB, total_size: usize) -> Self {
+        Self {
+            callback,
+            total: (total_size > 1).then_some(total_size),
+            started: None,
+            last_sample: None,
+            bytes_per_sec: 0.0,
+        }
+    }
+
+    fn update_speed(&mut self, now: Instant, bytes: usize) {
+        let Some((sample_time, sample_bytes)) = self.last_sample else {
+            self.started = Some(now);
+            self.last_sample = Some((now, bytes));
+            return;
+        };
+
+        let elapsed = now.duration_since(sample_time);
+        if elapsed < SPEED_SAMPLE_WINDOW {
+            return;
+        }
+
+        let sample_speed = bytes.saturating_sub(sample_bytes) as f64 / elapsed.as_secs_f64();
+        self.bytes_per_sec = if self.bytes_per_sec == 0.0 {
+            sample_speed
+        } else {
+            SPEED_SMOOTHING * sample_speed + (1.0 - SPEED_SMOOTHING) * self.bytes_per_sec
+        };
+        self.last_sample = Some((now, bytes));
+    }
+
+    /// Gets the time elapsed since the first progress update.
+    pub fn elapsed(&self) -> Duration {
+        self.started
+            .map(|started| started.elapsed())
+            .unwrap_or_default()
+    }
+}
+
+impl<CB> Callback for DownloadProgressTracker<CB>
+where
+    CB: Callback<Argument = DownloadProgress>,
+{
+    type Argument = usize;
+    fn call(&mut self, bytes: usize) {
+        self.update_speed(Instant::now(), bytes);
+        let eta = match self.total {
+            Some(total) if self.bytes_per_sec > 0.0 => Some(Duration::from_secs_f64(
+                total.saturating_sub(bytes) as f64 / self.bytes_per_sec,
+            )),
+            _ => None,
+        };
+        self.callback.call(DownloadProgress {
+            bytes,
+            total: self.total,
+            bytes_per_sec: self.bytes_per_sec,
+            eta,
+        });
+    }
+}
+
+/// A [DownloadProgress] callback that delivers updates over a channel.
+/// Updates are dropped if the channel is full so that the download never blocks on the receiver.
+pub struct DownloadProgressSender {
+    sender: Sender<DownloadProgress>,
+}
+
+impl DownloadProgressSender {
+    pub fn new(sender: Sender<DownloadProgress>) -> Self {
+        Self { sender }
+    }
+}
+
+impl Callback for DownloadProgressSender {
+    type Argument = DownloadProgress;
+    fn call(&mut self, progress: DownloadProgress) {
+        let _ = self.sender.try_send(progress);
+    }
+}
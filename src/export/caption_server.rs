@@ -0,0 +1,372 @@
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{sleep, spawn, JoinHandle};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tungstenite::{Message, WebSocket};
+
+use crate::export::jsonl::SegmentRecord;
+use crate::transcriber::audio_events::AudioEvent;
+use crate::transcriber::corrections::Correction;
+use crate::transcriber::localization::{EnglishLocalizer, MessageLocalizer};
+use crate::transcriber::timestamps::TimestampedSnapshot;
+use crate::transcriber::{
+    ControlCode, ControlPayload, SnapshotAssembler, SnapshotDelta, TranscriptionSnapshot,
+    WhisperControlPhrase, WhisperOutput,
+};
+use crate::utils::errors::RibbleWhisperError;
+use crate::utils::logging::log_warn;
+use crate::utils::sink::{OutputSink, OutputSinkError};
+
+// How long to wait on a client before dropping it, so a stalled client can't stall transcription.
+const CLIENT_WRITE_TIMEOUT: Duration = Duration::from_millis(500);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The JSON messages broadcast by a [CaptionServer], tagged by "type":
+/// * `{"type":"snapshot","confirmed":"...","working":["..."]}`
+/// * `{"type":"delta","confirmed_append":"...","replaced":[[0,"..."]],"num_working":1}`, when the
+///   transcriber is configured for delta encoding, (see: [SnapshotDelta])
+/// * `{"type":"control","code":"start_speaking","phrase":"[START SPEAKING]"}`, with a
+///   `"payload"` for phrases that carry data, (e.g. `{"remaining_ms":30000}`). Match on the code;
+///   the phrase is for display, (see: [ControlCode])
+/// * `{"type":"event","kind":"applause","start_ms":0,"end_ms":0,"confidence":0.9}`, when the
+///   transcriber has an audio event classifier, (see: [crate::transcriber::audio_events])
+/// * `{"type":"correction","start":0,"end":0,"text":"..."}`, replacing bytes start..end of the
+///   confirmed text, when the transcriber sends corrections, (see: [Correction])
+/// * `{"type":"segment","sequence":0,"text":"...","start_ms":0,"end_ms":0,...}`,
+///   (see: [SegmentRecord])
+/// * `{"type":"timing","window_start_ms":0,"words":[{"text":"...","start_ms":0,"end_ms":0}]}`,
+///   replacing the timings of earlier words from window_start_ms onward, when the transcriber
+///   sends timestamped snapshots, (see: [TimestampedSnapshot])
+#[derive(serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum CaptionMessage<'a> {
+    Snapshot {
+        confirmed: &'a str,
+        working: Vec<&'a str>,
+    },
+    Delta {
+        confirmed_append: &'a str,
+        replaced: Vec<(usize, &'a str)>,
+        num_working: usize,
+    },
+    Control {
+        code: ControlCode,
+        phrase: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payload: Option<ControlPayload>,
+    },
+    Event {
+        kind: &'a str,
+        start_ms: i64,
+        end_ms: i64,
+        confidence: f32,
+    },
+    Correction {
+        start: usize,
+        end: usize,
+        text: &'a str,
+    },
+    Segment(&'a SegmentRecord),
+    Timing {
+        window_start_ms: i64,
+        words: Vec<TimedWord<'a>>,
+    },
+}
+
+#[derive(serde::Serialize)]
+struct TimedWord<'a> {
+    text: &'a str,
+    start_ms: i64,
+    end_ms: i64,
+}
+
+impl<'a> CaptionMessage<'a> {
+    fn snapshot(snapshot: &'a TranscriptionSnapshot) -> Self {
+        CaptionMessage::Snapshot {
+            confirmed: snapshot.confirmed(),
+            working: snapshot
+                .string_segments()
+                .iter()
+                .map(|segment| segment.as_ref())
+                .collect(),
+        }
+    }
+
+    fn delta(delta: &'a SnapshotDelta) -> Self {
+        CaptionMessage::Delta {
+            confirmed_append: delta.confirmed_append(),
+            replaced: delta
+                .replaced_segments()
+                .iter()
+                .map(|(i, segment)| (*i, segment.as_ref()))
+                .collect(),
+            num_working: delta.num_segments(),
+        }
+    }
+
+    fn control(phrase: &WhisperControlPhrase, localizer: &dyn MessageLocalizer) -> Self {
+        CaptionMessage::Control {
+            code: phrase.code(),
+            phrase: phrase.localized(localizer),
+            payload: phrase.payload(),
+        }
+    }
+
+    // Event timestamps are in centiseconds.
+    fn event(event: &'a AudioEvent) -> Self {
+        CaptionMessage::Event {
+            kind: event.kind().label(),
+            start_ms: event.start_timestamp() * 10,
+            end_ms: event.end_timestamp() * 10,
+            confidence: event.confidence(),
+        }
+    }
+
+    fn correction(correction: &'a Correction) -> Self {
+        let range = correction.range();
+        CaptionMessage::Correction {
+            start: range.start,
+            end: range.end,
+            text: correction.new_text(),
+        }
+    }
+
+    // Word timestamps are in centiseconds.
+    fn timing(snapshot: &'a TimestampedSnapshot) -> Self {
+        CaptionMessage::Timing {
+            window_start_ms: snapshot.window_start * 10,
+            words: snapshot
+                .words()
+                .map(|word| TimedWord {
+                    text: &word.text,
+                    start_ms: word.start_time * 10,
+                    end_ms: word.end_time * 10,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ServerState {
+    clients: Vec<WebSocket<TcpStream>>,
+    // Sent to clients when they connect, so that they don't start blank.
+    last_snapshot: Option<String>,
+    // Tracks the full snapshot when deltas are broadcast, so that new clients get a full snapshot.
+    assembler: SnapshotAssembler,
+}
+
+impl ServerState {
+    fn broadcast(&mut self, json: &str) {
+        self.clients.retain_mut(|client| {
+            let sent = client.send(Message::text(json.to_string()));
+            if let Err(_e) = &sent {
+                log_warn!("Dropping caption client: {_e}");
+            }
+            sent.is_ok()
+        });
+    }
+}
+
+fn accept_client(stream: TcpStream, state: &Mutex<ServerState>) -> Result<(), RibbleWhisperError> {
+    stream.set_nonblocking(false)?;
+    stream.set_nodelay(true)?;
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_WRITE_TIMEOUT))?;
+    let mut client = tungstenite::accept(stream).map_err(|e| {
+        RibbleWhisperError::IOError(std::io::Error::other(format!(
+            "WebSocket handshake failed: {e}"
+        )))
+    })?;
+
+    let mut state = state.lock();
+    if let Some(snapshot) = state.last_snapshot.as_ref()
+        && client.send(Message::text(snapshot.clone())).is_err()
+    {
+        return Ok(());
+    }
+    state.clients.push(client);
+    Ok(())
+}
+
+/// A WebSocket server that broadcasts transcriber outputs as JSON to every connected client,
+/// (e.g. an OBS browser source or remote viewers).
+///
+/// Clients connect to `ws://<address>/` and receive snapshots, control phrases and finalized
+/// segments, (see: [CaptionServer::broadcast_segment]). Clients that connect mid-session are sent
+/// the latest snapshot first. Any messages sent by clients are ignored.
+///
+/// This can be used directly as a transcriber's output sender, or fed from the loop that drains
+/// the output channel with [CaptionServer::broadcast]. Clients that stop reading are dropped so
+/// that they cannot stall the transcriber. Requires the caption-server feature.
+pub struct CaptionServer {
+    local_addr: SocketAddr,
+    state: Arc<Mutex<ServerState>>,
+    running: Arc<AtomicBool>,
+    accept_thread: Option<JoinHandle<()>>,
+    localizer: Box<dyn MessageLocalizer>,
+}
+
+impl CaptionServer {
+    /// Binds the server to the given address and starts accepting clients on a background thread.
+    /// Use port 0 to bind to any free port, (see: [CaptionServer::local_addr]).
+    pub fn bind<A: ToSocketAddrs>(address: A) -> Result<Self, RibbleWhisperError> {
+        let listener = TcpListener::bind(address)?;
+        // Non-blocking, so that the accept loop can observe shutdown.
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+
+        let state = Arc::new(Mutex::new(ServerState::default()));
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_state = Arc::clone(&state);
+        let thread_running = Arc::clone(&running);
+        let accept_thread = spawn(move || {
+            while thread_running.load(Ordering::Acquire) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        if let Err(_e) = accept_client(stream, &thread_state) {
+                            log_warn!("Failed to accept caption client: {_e}");
+                        }
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        sleep(ACCEPT_POLL_INTERVAL)
+                    }
+                    Err(_e) => {
+                        log_warn!("Caption server failed to accept a connection: {_e}");
+                        sleep(ACCEPT_POLL_INTERVAL)
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            local_addr,
+            state,
+            running,
+            accept_thread: Some(accept_thread),
+            localizer: Box::new(EnglishLocalizer),
+        })
+    }
+
+    /// Sets the localizer for the control phrase text sent to clients, (see:
+    /// [crate::transcriber::localization]). Codes are sent unchanged.
+    pub fn with_localizer<L: MessageLocalizer>(mut self, localizer: L) -> Self {
+        self.localizer = Box::new(localizer);
+        self
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn num_clients(&self) -> usize {
+        self.state.lock().clients.len()
+    }
+
+    /// Broadcasts a transcriber output to all connected clients.
+    pub fn broadcast(&self, output: &WhisperOutput) -> Result<(), RibbleWhisperError> {
+        match output {
+            WhisperOutput::TranscriptionSnapshot(snapshot) => self.broadcast_snapshot(snapshot),
+            WhisperOutput::SnapshotDelta(delta) => {
+                let json = serde_json::to_string(&CaptionMessage::delta(delta))?;
+                let mut state = self.state.lock();
+                state.broadcast(&json);
+                if let Some(snapshot) = state.assembler.push(output) {
+                    state.last_snapshot =
+                        Some(serde_json::to_string(&CaptionMessage::snapshot(&snapshot))?);
+                }
+                Ok(())
+            }
+            WhisperOutput::ControlPhrase(phrase) => {
+                let json = serde_json::to_string(&CaptionMessage::control(
+                    phrase,
+                    self.localizer.as_ref(),
+                ))?;
+                self.state.lock().broadcast(&json);
+                Ok(())
+            }
+            WhisperOutput::AudioEvent(event) => {
+                let json = serde_json::to_string(&CaptionMessage::event(event))?;
+                self.state.lock().broadcast(&json);
+                Ok(())
+            }
+            WhisperOutput::Correction(correction) => {
+                let json = serde_json::to_string(&CaptionMessage::correction(correction))?;
+                self.state.lock().broadcast(&json);
+                Ok(())
+            }
+            WhisperOutput::TimestampedSnapshot(snapshot) => {
+                let json = serde_json::to_string(&CaptionMessage::timing(snapshot))?;
+                self.state.lock().broadcast(&json);
+                Ok(())
+            }
+            // Diagnostics are for the application, not caption viewers.
+            WhisperOutput::DecodeDiagnostics(_) => Ok(()),
+        }
+    }
+
+    pub fn broadcast_snapshot(
+        &self,
+        snapshot: &TranscriptionSnapshot,
+    ) -> Result<(), RibbleWhisperError> {
+        let json = serde_json::to_string(&CaptionMessage::snapshot(snapshot))?;
+        let mut state = self.state.lock();
+        state.broadcast(&json);
+        state.last_snapshot = Some(json);
+        state
+            .assembler
+            .push(&WhisperOutput::TranscriptionSnapshot(Arc::new(
+                snapshot.clone(),
+            )));
+        Ok(())
+    }
+
+    /// Broadcasts a finalized segment, (e.g. one also written to a
+    /// [crate::export::jsonl::JsonlWriter]).
+    pub fn broadcast_segment(&self, segment: &SegmentRecord) -> Result<(), RibbleWhisperError> {
+        let json = serde_json::to_string(&CaptionMessage::Segment(segment))?;
+        self.state.lock().broadcast(&json);
+        Ok(())
+    }
+
+    /// Stops accepting clients and closes all connections.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Some(thread) = self.accept_thread.take() {
+            let _ = thread.join();
+        }
+        for mut client in self.state.lock().clients.drain(..) {
+            let _ = client.close(None);
+            let _ = client.flush();
+        }
+    }
+}
+
+impl Drop for CaptionServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Broadcasts outputs to the connected clients. Sending never fails; clients that cannot keep
+/// up are dropped instead.
+impl OutputSink<WhisperOutput> for CaptionServer {
+    fn try_send(&self, msg: WhisperOutput) -> Result<(), OutputSinkError<WhisperOutput>> {
+        if let Err(_e) = self.broadcast(&msg) {
+            log_warn!("Failed to broadcast caption output: {_e}");
+        }
+        Ok(())
+    }
+
+    fn send(&self, msg: WhisperOutput) -> Result<(), OutputSinkError<WhisperOutput>> {
+        self.try_send(msg)
+    }
+}
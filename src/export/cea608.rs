@@ -0,0 +1,314 @@
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::transcriber::{TranscriptionSnapshot, WhisperOutput};
+use crate::utils::logging::log_warn;
+use crate::utils::sink::{OutputSink, OutputSinkError};
+
+/// The width of a CEA-608 caption row, in characters.
+pub const ROW_WIDTH: usize = 32;
+/// Roll-up captions show this many rows by default.
+pub const DEFAULT_ROLL_UP_ROWS: u8 = 2;
+/// A CEA-608 field carries 2 characters per frame, (at 29.97 fps).
+pub const DEFAULT_CHARS_PER_SECOND: f32 = 59.94;
+/// Rows roll up at most this often by default, so that each line can be read.
+pub const DEFAULT_MIN_ROW_DURATION: Duration = Duration::from_millis(1500);
+
+// Control codes are sent twice for redundancy, taking two frames.
+const CONTROL_CODE_DURATION: Duration = Duration::from_millis(67);
+
+/// A roll-up caption command, (see: [RollUpChunker]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CaptionEvent {
+    /// Appends text to the bottom row. The text never overflows the row.
+    Text(String),
+    /// Rolls the display up one row, starting a new, empty bottom row.
+    CarriageReturn,
+    /// Erases the displayed captions, (e.g. after a long silence).
+    EraseDisplay,
+}
+
+/// A [CaptionEvent], with the time it should be sent to the encoder.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimedCaptionEvent {
+    /// When to send the event, measured from the start of the session.
+    pub at: Duration,
+    pub event: CaptionEvent,
+}
+
+// Keeps the characters of a word that a CEA-608 decoder can show, (the basic North American
+// character set and common special characters), replacing typographic punctuation.
+fn sanitize_word(word: &str) -> String {
+    let mut sanitized = String::with_capacity(word.len());
+    for c in word.chars() {
+        match c {
+            // These basic-set code points are replaced by accented letters and symbols in CEA-608.
+            '*' | '\\' | '^' | '_' | '`' | '{' | '|' | '}' | '~' => {}
+            '\u{2018}' | '\u{2019}' => sanitized.push('\''),
+            '\u{201C}' | '\u{201D}' => sanitized.push('"'),
+            '\u{2013}' | '\u{2014}' => sanitized.push('-'),
+            '\u{2026}' => sanitized.push_str("..."),
+            'á' | 'é' | 'í' | 'ó' | 'ú' | 'ç' | 'ñ' | 'Ñ' | '÷' | '♪' => {
+                sanitized.push(c)
+            }
+            _ if c.is_ascii_graphic() => sanitized.push(c),
+            _ => {}
+        }
+    }
+    sanitized
+}
+
+/// Re-flows finalized text into CEA-608 roll-up caption events: words wrapped onto 32-character
+/// rows, with a carriage return between rows.
+///
+/// Events are scheduled so that a hardware encoder can send them as-is: text is paced to the
+/// caption channel's bandwidth, and rows roll up no faster than the minimum row duration, even
+/// when text arrives in bursts. Characters that CEA-608 decoders cannot show are dropped, and
+/// typographic punctuation is replaced, (e.g. curly quotes).
+///
+/// Times are passed in by the caller, measured from the start of the session; to drive the
+/// chunker from the realtime transcriber, see: [RollUpSink].
+#[derive(Clone, Debug)]
+pub struct RollUpChunker {
+    rows: u8,
+    chars_per_second: f32,
+    min_row_duration: Duration,
+    row_len: usize,
+    next_available: Duration,
+    last_roll: Option<Duration>,
+    last_confirmed: String,
+}
+
+impl RollUpChunker {
+    pub fn new() -> Self {
+        Self {
+            rows: DEFAULT_ROLL_UP_ROWS,
+            chars_per_second: DEFAULT_CHARS_PER_SECOND,
+            min_row_duration: DEFAULT_MIN_ROW_DURATION,
+            row_len: 0,
+            next_available: Duration::ZERO,
+            last_roll: None,
+            last_confirmed: String::new(),
+        }
+    }
+
+    /// Sets the number of roll-up rows, (2-4), that the encoder should be configured for.
+    pub fn with_rows(mut self, rows: u8) -> Self {
+        self.rows = rows.clamp(2, 4);
+        self
+    }
+
+    /// Sets the rate text is paced to, (e.g. lower if the channel is shared).
+    pub fn with_chars_per_second(mut self, chars_per_second: f32) -> Self {
+        self.chars_per_second = chars_per_second.max(1.0);
+        self
+    }
+
+    pub fn with_min_row_duration(mut self, min_row_duration: Duration) -> Self {
+        self.min_row_duration = min_row_duration;
+        self
+    }
+
+    pub fn rows(&self) -> u8 {
+        self.rows
+    }
+    pub fn chars_per_second(&self) -> f32 {
+        self.chars_per_second
+    }
+    pub fn min_row_duration(&self) -> Duration {
+        self.min_row_duration
+    }
+
+    fn schedule(&mut self, now: Duration, event: CaptionEvent) -> TimedCaptionEvent {
+        let mut at = now.max(self.next_available);
+        match &event {
+            CaptionEvent::Text(text) => {
+                let chars = text.chars().count() as f32;
+                self.next_available = at + Duration::from_secs_f32(chars / self.chars_per_second);
+            }
+            CaptionEvent::CarriageReturn => {
+                if let Some(last_roll) = self.last_roll {
+                    at = at.max(last_roll + self.min_row_duration);
+                }
+                self.last_roll = Some(at);
+                self.next_available = at + CONTROL_CODE_DURATION;
+            }
+            CaptionEvent::EraseDisplay => {
+                self.next_available = at + CONTROL_CODE_DURATION;
+            }
+        }
+        TimedCaptionEvent { at, event }
+    }
+
+    /// Appends finalized text, returning the events to send.
+    pub fn push_text(&mut self, text: &str, now: Duration) -> Vec<TimedCaptionEvent> {
+        let mut events = vec![];
+        let mut pending = String::new();
+        for word in text.split_whitespace().map(sanitize_word) {
+            if word.is_empty() {
+                continue;
+            }
+            let mut chars: Vec<char> = word.chars().collect();
+            if self.row_len > 0 && self.row_len + 1 + chars.len() > ROW_WIDTH {
+                self.flush_pending(&mut pending, now, &mut events);
+                events.push(self.schedule(now, CaptionEvent::CarriageReturn));
+                self.row_len = 0;
+            }
+            if self.row_len > 0 {
+                pending.push(' ');
+                self.row_len += 1;
+            }
+            // Words longer than a row are broken across rows.
+            while self.row_len + chars.len() > ROW_WIDTH {
+                let rest = chars.split_off(ROW_WIDTH - self.row_len);
+                pending.extend(chars);
+                self.flush_pending(&mut pending, now, &mut events);
+                events.push(self.schedule(now, CaptionEvent::CarriageReturn));
+                self.row_len = 0;
+                chars = rest;
+            }
+            self.row_len += chars.len();
+            pending.extend(chars);
+        }
+        self.flush_pending(&mut pending, now, &mut events);
+        events
+    }
+
+    fn flush_pending(
+        &mut self,
+        pending: &mut String,
+        now: Duration,
+        events: &mut Vec<TimedCaptionEvent>,
+    ) {
+        if !pending.is_empty() {
+            let text = std::mem::take(pending);
+            events.push(self.schedule(now, CaptionEvent::Text(text)));
+        }
+    }
+
+    /// Appends the text confirmed since the previous snapshot.
+    /// Roll-up captions cannot be revised once sent, so if deduplication revised already-sent
+    /// text, only the text after the revision is sent.
+    pub fn push_snapshot(
+        &mut self,
+        snapshot: &TranscriptionSnapshot,
+        now: Duration,
+    ) -> Vec<TimedCaptionEvent> {
+        let confirmed = snapshot.confirmed();
+        let common_len: usize = confirmed
+            .char_indices()
+            .zip(self.last_confirmed.chars())
+            .take_while(|((_, a), b)| a == b)
+            .map(|((_, a), _)| a.len_utf8())
+            .sum();
+        let new_text = confirmed[common_len..].to_string();
+        confirmed.clone_into(&mut self.last_confirmed);
+        self.push_text(&new_text, now)
+    }
+
+    /// Appends the newly confirmed text of a snapshot or snapshot delta, or the marker of an audio
+    /// event, (e.g. "[applause]"); control phrases, diagnostics and timestamped snapshots produce
+    /// no events.
+    /// Corrections produce no events either: roll-up captions can't be revised, so the text after
+    /// the revision is sent with the next snapshot, (see: [RollUpChunker::push_snapshot]).
+    pub fn push_output(&mut self, output: &WhisperOutput, now: Duration) -> Vec<TimedCaptionEvent> {
+        match output {
+            WhisperOutput::TranscriptionSnapshot(snapshot) => self.push_snapshot(snapshot, now),
+            WhisperOutput::SnapshotDelta(delta) => {
+                self.last_confirmed.push_str(delta.confirmed_append());
+                self.push_text(delta.confirmed_append(), now)
+            }
+            WhisperOutput::ControlPhrase(_)
+            | WhisperOutput::DecodeDiagnostics(_)
+            | WhisperOutput::Correction(_)
+            | WhisperOutput::TimestampedSnapshot(_) => vec![],
+            WhisperOutput::AudioEvent(event) => self.push_text(&event.to_string(), now),
+        }
+    }
+
+    /// Erases the display, (e.g. at the end of a session), and starts again on a new row.
+    pub fn clear(&mut self, now: Duration) -> TimedCaptionEvent {
+        self.row_len = 0;
+        self.schedule(now, CaptionEvent::EraseDisplay)
+    }
+}
+
+impl Default for RollUpChunker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Chunks transcriber outputs into roll-up caption events, (see: [RollUpChunker]), and forwards
+/// them to another sink, (e.g. a channel read by an encoder integration).
+///
+/// This can be used directly as the realtime transcriber's output sender. Event times are
+/// measured from when the sink was created. If the inner sink is full, the remaining events for
+/// that output are dropped and a warning is logged.
+pub struct RollUpSink<S> {
+    chunker: Mutex<RollUpChunker>,
+    started: Instant,
+    sink: S,
+}
+
+impl<S: OutputSink<TimedCaptionEvent>> RollUpSink<S> {
+    pub fn new(chunker: RollUpChunker, sink: S) -> Self {
+        Self {
+            chunker: Mutex::new(chunker),
+            started: Instant::now(),
+            sink,
+        }
+    }
+
+    /// The time since the sink was created, (i.e. the clock that event times are measured on).
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Erases the display, (see: [RollUpChunker::clear]).
+    pub fn clear(&self) -> Result<(), OutputSinkError<TimedCaptionEvent>> {
+        let event = self.chunker.lock().clear(self.elapsed());
+        self.sink.send(event)
+    }
+
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+
+    fn forward(
+        &self,
+        msg: WhisperOutput,
+        blocking: bool,
+    ) -> Result<(), OutputSinkError<WhisperOutput>> {
+        let events = self.chunker.lock().push_output(&msg, self.elapsed());
+        for event in events {
+            let sent = if blocking {
+                self.sink.send(event)
+            } else {
+                self.sink.try_send(event)
+            };
+            match sent {
+                Ok(()) => {}
+                Err(OutputSinkError::Full(_)) => {
+                    log_warn!("Caption event sink is full; dropping caption events.");
+                    break;
+                }
+                Err(OutputSinkError::Disconnected(_)) => {
+                    return Err(OutputSinkError::Disconnected(msg));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S: OutputSink<TimedCaptionEvent>> OutputSink<WhisperOutput> for RollUpSink<S> {
+    fn try_send(&self, msg: WhisperOutput) -> Result<(), OutputSinkError<WhisperOutput>> {
+        self.forward(msg, false)
+    }
+
+    fn send(&self, msg: WhisperOutput) -> Result<(), OutputSinkError<WhisperOutput>> {
+        self.forward(msg, true)
+    }
+}
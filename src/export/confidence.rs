@@ -0,0 +1,106 @@
+//! Styling text by transcription confidence, so that shaky regions stand out in exported
+//! subtitles and documents, (e.g. for a correction pass).
+//!
+//! A [ConfidenceStyles] maps confidence bands to a [ConfidenceStyle]. Each format renders the
+//! parts of a style it supports; see [crate::export::subtitles::SubtitleExporter::export_with_styles]
+//! and [crate::export::document::TranscriptExporter::with_confidence_styles].
+
+/// How text in a confidence band is styled.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConfidenceStyle {
+    italic: bool,
+    colour: Option<[u8; 3]>,
+    class: Option<String>,
+}
+
+impl ConfidenceStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_italic(mut self, italic: bool) -> Self {
+        self.italic = italic;
+        self
+    }
+
+    /// Sets the colour of the text, as RGB.
+    pub fn with_colour(mut self, r: u8, g: u8, b: u8) -> Self {
+        self.colour = Some([r, g, b]);
+        self
+    }
+
+    /// Sets a class name for formats styled by a stylesheet, (HTML and WebVTT), e.g. to match a
+    /// correction tool's CSS.
+    pub fn with_class(mut self, class: &str) -> Self {
+        self.class = Some(class.to_string());
+        self
+    }
+
+    pub fn italic(&self) -> bool {
+        self.italic
+    }
+
+    pub fn colour(&self) -> Option<[u8; 3]> {
+        self.colour
+    }
+
+    pub fn class(&self) -> Option<&str> {
+        self.class.as_deref()
+    }
+
+    /// The colour as a CSS hex colour, (e.g. "#cc3333").
+    pub fn hex_colour(&self) -> Option<String> {
+        self.colour
+            .map(|[r, g, b]| format!("#{r:02x}{g:02x}{b:02x}"))
+    }
+}
+
+/// Maps confidence, (0.0 - 1.0), to styles. Text is styled by the band with the lowest threshold
+/// its confidence is below; text above every threshold, or without a confidence, is not styled.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConfidenceStyles {
+    // Sorted by threshold, lowest first.
+    bands: Vec<(f32, ConfidenceStyle)>,
+}
+
+impl ConfidenceStyles {
+    /// An empty mapping, which styles nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Italicizes text below the threshold.
+    pub fn uncertain_below(threshold: f32) -> Self {
+        Self::new().with_band(threshold, ConfidenceStyle::new().with_italic(true))
+    }
+
+    /// Styles text with a confidence below the threshold, unless a band with a lower threshold
+    /// applies. A band with the same threshold as an existing band replaces it.
+    pub fn with_band(mut self, threshold: f32, style: ConfidenceStyle) -> Self {
+        let threshold = threshold.clamp(0.0, 1.0);
+        self.bands.retain(|(existing, _)| *existing != threshold);
+        let index = self
+            .bands
+            .partition_point(|(existing, _)| *existing < threshold);
+        self.bands.insert(index, (threshold, style));
+        self
+    }
+
+    /// The bands, as (threshold, style), lowest threshold first.
+    pub fn bands(&self) -> &[(f32, ConfidenceStyle)] {
+        &self.bands
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bands.is_empty()
+    }
+
+    /// The style for text with the given confidence, if any.
+    pub fn style_for(&self, confidence: Option<f32>) -> Option<&ConfidenceStyle> {
+        let confidence = confidence?;
+        self.bands
+            .iter()
+            .find(|(threshold, _)| confidence < *threshold)
+            .map(|(_, style)| style)
+    }
+}
@@ -0,0 +1,252 @@
+use crate::export::confidence::{ConfidenceStyle, ConfidenceStyles};
+use crate::postprocess::document::{Paragraph, Sentence, TranscriptDocument};
+
+/// Sentences below this confidence are styled as uncertain by default.
+pub const DEFAULT_CONFIDENCE_THRESHOLD: f32 = 0.5;
+
+const HTML_STYLE: &str =
+    "body { font-family: sans-serif; max-width: 48em; margin: 2em auto; line-height: 1.5; }
+h2 { font-size: 1em; color: #555; }
+h2 a { color: inherit; text-decoration: none; }
+.speaker { font-weight: bold; color: #222; }
+.low-confidence { color: #933; text-decoration: underline dotted; }";
+
+// Formats a timestamp, (in centiseconds), as HH:MM:SS.
+fn format_timestamp(centiseconds: i64) -> String {
+    let seconds = centiseconds.max(0) / 100;
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60
+    )
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#' | '|'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders a [TranscriptDocument] as readable Markdown or HTML, (e.g. meeting notes).
+///
+/// Each paragraph is headed by its start time and speaker, when known, (see:
+/// [crate::postprocess::document::DocumentSegmenter::segment_records]). In HTML, timestamps are
+/// also anchors that can be linked to, (e.g. `notes.html#t-65` for 00:01:05). Sentences below
+/// the confidence threshold are styled as uncertain: italic in Markdown, and with the
+/// "low-confidence" class in HTML. For finer control, (e.g. several bands or colours), see:
+/// [TranscriptExporter::with_confidence_styles].
+#[derive(Clone, Debug)]
+pub struct TranscriptExporter {
+    title: Option<String>,
+    timestamps: bool,
+    speakers: bool,
+    confidence_threshold: f32,
+    confidence_styles: Option<ConfidenceStyles>,
+}
+
+impl TranscriptExporter {
+    pub fn new() -> Self {
+        Self {
+            title: None,
+            timestamps: true,
+            speakers: true,
+            confidence_threshold: DEFAULT_CONFIDENCE_THRESHOLD,
+            confidence_styles: None,
+        }
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Sets whether paragraphs are headed by their start time. This is enabled by default.
+    pub fn with_timestamps(mut self, timestamps: bool) -> Self {
+        self.timestamps = timestamps;
+        self
+    }
+
+    /// Sets whether paragraphs are headed by their speaker. This is enabled by default.
+    pub fn with_speakers(mut self, speakers: bool) -> Self {
+        self.speakers = speakers;
+        self
+    }
+
+    /// Sets the confidence, (0.0 - 1.0), below which sentences are styled as uncertain.
+    /// Use 0.0 to disable confidence styling.
+    pub fn with_confidence_threshold(mut self, threshold: f32) -> Self {
+        self.confidence_threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Styles sentences by their confidence, replacing the confidence threshold. Markdown only
+    /// supports italics; in HTML, styled sentences are wrapped in a span with the style's class,
+    /// and its italics and colour inlined.
+    pub fn with_confidence_styles(mut self, styles: ConfidenceStyles) -> Self {
+        self.confidence_styles = Some(styles);
+        self
+    }
+
+    fn timestamp(&self, paragraph: &Paragraph) -> Option<i64> {
+        self.timestamps
+            .then(|| paragraph.start_timestamp())
+            .flatten()
+    }
+
+    fn speaker<'a>(&self, paragraph: &'a Paragraph) -> Option<&'a str> {
+        self.speakers.then(|| paragraph.speaker()).flatten()
+    }
+
+    fn is_uncertain(&self, sentence: &Sentence) -> bool {
+        sentence
+            .confidence()
+            .is_some_and(|confidence| confidence < self.confidence_threshold)
+    }
+
+    fn style<'a>(&'a self, sentence: &Sentence) -> Option<&'a ConfidenceStyle> {
+        self.confidence_styles
+            .as_ref()
+            .and_then(|styles| styles.style_for(sentence.confidence()))
+    }
+
+    fn is_italic(&self, sentence: &Sentence) -> bool {
+        match self.confidence_styles.as_ref() {
+            Some(_) => self.style(sentence).is_some_and(ConfidenceStyle::italic),
+            None => self.is_uncertain(sentence),
+        }
+    }
+
+    fn to_html_sentence(&self, sentence: &Sentence) -> String {
+        let text = escape_html(sentence.text());
+        let Some(confidence) = sentence.confidence() else {
+            return text;
+        };
+        let title = format!("title=\"Confidence: {:.0}%\"", confidence * 100.0);
+        match self.confidence_styles.as_ref() {
+            Some(_) => {
+                let Some(style) = self.style(sentence) else {
+                    return text;
+                };
+                let mut attributes = vec![];
+                if let Some(class) = style.class() {
+                    attributes.push(format!("class=\"{}\"", escape_html(class)));
+                }
+                let inline: Vec<String> = style
+                    .italic()
+                    .then(|| "font-style: italic".to_string())
+                    .into_iter()
+                    .chain(style.hex_colour().map(|colour| format!("color: {colour}")))
+                    .collect();
+                if !inline.is_empty() {
+                    attributes.push(format!("style=\"{}\"", inline.join("; ")));
+                }
+                attributes.push(title);
+                format!("<span {}>{text}</span>", attributes.join(" "))
+            }
+            None if self.is_uncertain(sentence) => {
+                format!("<span class=\"low-confidence\" {title}>{text}</span>")
+            }
+            None => text,
+        }
+    }
+
+    pub fn to_markdown(&self, document: &TranscriptDocument) -> String {
+        let mut markdown = String::new();
+        if let Some(title) = self.title.as_ref() {
+            markdown.push_str(&format!("# {}\n\n", escape_markdown(title)));
+        }
+        for paragraph in document.paragraphs() {
+            let heading: Vec<String> = self
+                .timestamp(paragraph)
+                .map(format_timestamp)
+                .into_iter()
+                .chain(self.speaker(paragraph).map(escape_markdown))
+                .collect();
+            if !heading.is_empty() {
+                markdown.push_str(&format!("## {}\n\n", heading.join(" — ")));
+            }
+
+            let text: Vec<String> = paragraph
+                .sentences()
+                .iter()
+                .map(|sentence| {
+                    let text = escape_markdown(sentence.text());
+                    if self.is_italic(sentence) {
+                        format!("*{text}*")
+                    } else {
+                        text
+                    }
+                })
+                .collect();
+            markdown.push_str(&text.join(" "));
+            markdown.push_str("\n\n");
+        }
+        markdown
+    }
+
+    /// Renders the document as a complete, standalone HTML page.
+    pub fn to_html(&self, document: &TranscriptDocument) -> String {
+        let title = escape_html(self.title.as_deref().unwrap_or("Transcript"));
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n{HTML_STYLE}\n</style>\n</head>\n<body>\n"
+        );
+        if self.title.is_some() {
+            html.push_str(&format!("<h1>{title}</h1>\n"));
+        }
+        for paragraph in document.paragraphs() {
+            let timestamp = self.timestamp(paragraph);
+            let speaker = self.speaker(paragraph);
+            // Anchors are the start time in seconds.
+            let anchor = timestamp.map(|timestamp| format!("t-{}", timestamp.max(0) / 100));
+            match anchor.as_ref() {
+                Some(anchor) => html.push_str(&format!("<section id=\"{anchor}\">\n")),
+                None => html.push_str("<section>\n"),
+            }
+
+            let heading: Vec<String> = timestamp
+                .zip(anchor)
+                .map(|(timestamp, anchor)| {
+                    format!("<a href=\"#{anchor}\">{}</a>", format_timestamp(timestamp))
+                })
+                .into_iter()
+                .chain(speaker.map(|speaker| {
+                    format!("<span class=\"speaker\">{}</span>", escape_html(speaker))
+                }))
+                .collect();
+            if !heading.is_empty() {
+                html.push_str(&format!("<h2>{}</h2>\n", heading.join(" ")));
+            }
+
+            let text: Vec<String> = paragraph
+                .sentences()
+                .iter()
+                .map(|sentence| self.to_html_sentence(sentence))
+                .collect();
+            html.push_str(&format!("<p>{}</p>\n</section>\n", text.join(" ")));
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+impl Default for TranscriptExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
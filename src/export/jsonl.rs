@@ -0,0 +1,213 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::transcriber::RibbleWhisperSegment;
+use crate::utils::errors::RibbleWhisperError;
+
+/// A finalized segment, as written by [JsonlWriter]. Timestamps are in milliseconds.
+/// Optional fields are written as null when unknown.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SegmentRecord {
+    /// The position of the record within its log, starting at 0. This is assigned by the writer.
+    #[serde(default)]
+    pub sequence: u64,
+    pub text: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    /// Model confidence, (0.0 - 1.0), if known.
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    #[serde(default)]
+    pub speaker: Option<String>,
+    /// Defaults to the writer's session id, (see: [JsonlWriter::with_session_id]).
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+impl SegmentRecord {
+    pub fn new(text: impl Into<String>, start_ms: i64, end_ms: i64) -> Self {
+        Self {
+            sequence: 0,
+            text: text.into(),
+            start_ms,
+            end_ms,
+            confidence: None,
+            speaker: None,
+            session_id: None,
+        }
+    }
+
+    pub fn with_confidence(mut self, confidence: f32) -> Self {
+        self.confidence = Some(confidence);
+        self
+    }
+
+    pub fn with_speaker(mut self, speaker: impl Into<String>) -> Self {
+        self.speaker = Some(speaker.into());
+        self
+    }
+
+    pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+}
+
+impl From<&RibbleWhisperSegment> for SegmentRecord {
+    fn from(segment: &RibbleWhisperSegment) -> Self {
+        // Segment timestamps are in centiseconds.
+        let record = Self::new(
+            segment.text().trim(),
+            segment.start_timestamp() * 10,
+            segment.end_timestamp() * 10,
+        );
+        Self {
+            confidence: segment.confidence(),
+            speaker: segment.speaker().map(|speaker| speaker.to_string()),
+            ..record
+        }
+    }
+}
+
+// The records of a log, and the length (in bytes) of its complete lines.
+struct LogContents {
+    records: Vec<SegmentRecord>,
+    complete_len: u64,
+    ends_with_newline: bool,
+}
+
+fn read_log<R: BufRead>(mut reader: R) -> Result<LogContents, RibbleWhisperError> {
+    let mut contents = LogContents {
+        records: vec![],
+        complete_len: 0,
+        ends_with_newline: true,
+    };
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line)?;
+        if read == 0 {
+            break;
+        }
+        let terminated = line.ends_with('\n');
+        if line.trim().is_empty() {
+            contents.complete_len += read as u64;
+            continue;
+        }
+        match serde_json::from_str(line.trim()) {
+            Ok(record) => {
+                contents.records.push(record);
+                contents.complete_len += read as u64;
+                contents.ends_with_newline = terminated;
+            }
+            // An unterminated final line is a write that was interrupted, (e.g. by a crash).
+            Err(_) if !terminated => break,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(contents)
+}
+
+/// Reads the records of a JSON Lines log, (see: [JsonlWriter]).
+/// A truncated final line, (i.e. from a write interrupted by a crash), is skipped.
+/// Returns Err if any other line is not a valid [SegmentRecord].
+pub fn read_records<R: BufRead>(reader: R) -> Result<Vec<SegmentRecord>, RibbleWhisperError> {
+    Ok(read_log(reader)?.records)
+}
+
+/// Writes finalized segments as JSON Lines: one [SegmentRecord] per line.
+///
+/// Each record is written with a single write and flushed immediately, so the log can be tailed
+/// while a session is running and, at worst, loses the line being written if the application
+/// crashes. Requires the serde feature.
+pub struct JsonlWriter<W: Write> {
+    writer: W,
+    session_id: Option<String>,
+    next_sequence: u64,
+}
+
+impl<W: Write> JsonlWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            session_id: None,
+            next_sequence: 0,
+        }
+    }
+
+    /// Sets the session id written with records that do not have their own.
+    pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// The sequence number that will be assigned to the next record.
+    pub fn next_sequence(&self) -> u64 {
+        self.next_sequence
+    }
+
+    /// Appends a record to the log, assigning its sequence number.
+    pub fn write_record(&mut self, mut record: SegmentRecord) -> Result<(), RibbleWhisperError> {
+        record.sequence = self.next_sequence;
+        if record.session_id.is_none() {
+            record.session_id = self.session_id.clone();
+        }
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)?;
+        self.writer.flush()?;
+        self.next_sequence += 1;
+        Ok(())
+    }
+
+    /// Appends a segment to the log.
+    pub fn write_segment(
+        &mut self,
+        segment: &RibbleWhisperSegment,
+    ) -> Result<(), RibbleWhisperError> {
+        self.write_record(SegmentRecord::from(segment))
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl JsonlWriter<File> {
+    /// Opens a log file for appending, creating it if it does not exist.
+    /// Sequence numbers continue from the last record in the file, and a truncated final line
+    /// left by a crash is removed.
+    pub fn append<P: AsRef<Path>>(path: P) -> Result<Self, RibbleWhisperError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(path)?;
+
+        let contents = read_log(BufReader::new(&file))?;
+        file.set_len(contents.complete_len)?;
+        file.seek(SeekFrom::End(0))?;
+        if !contents.ends_with_newline {
+            file.write_all(b"\n")?;
+        }
+
+        let mut writer = Self::new(file);
+        writer.next_sequence = contents
+            .records
+            .last()
+            .map_or(0, |record| record.sequence + 1);
+        Ok(writer)
+    }
+}
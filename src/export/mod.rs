@@ -0,0 +1,16 @@
+//! Exporting transcripts to other formats and destinations.
+
+#[cfg(feature = "caption-server")]
+pub mod caption_server;
+pub mod cea608;
+pub mod confidence;
+pub mod document;
+#[cfg(feature = "serde")]
+pub mod jsonl;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+#[cfg(feature = "osc")]
+pub mod osc;
+#[cfg(feature = "serde")]
+pub mod store;
+pub mod subtitles;
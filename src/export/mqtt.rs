@@ -0,0 +1,422 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{sleep, spawn, JoinHandle};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use rumqttc::{Client, ConnectionError, Event, LastWill, MqttOptions, Outgoing};
+
+pub use rumqttc::QoS;
+
+use crate::export::jsonl::SegmentRecord;
+use crate::transcriber::{
+    SnapshotAssembler, TranscriptionSnapshot, WhisperControlPhrase, WhisperOutput,
+};
+use crate::utils::errors::RibbleWhisperError;
+use crate::utils::logging::log_warn;
+use crate::utils::sink::{OutputSink, OutputSinkError};
+
+/// Topics are published under this prefix unless set, (see: [MqttSinkBuilder::with_topic_prefix]).
+pub const DEFAULT_TOPIC_PREFIX: &str = "ribble/transcription";
+pub const DEFAULT_PORT: u16 = 1883;
+
+// Publishes are queued for the connection thread; once full, outputs are dropped rather than
+// stalling the transcriber.
+const REQUEST_CAPACITY: usize = 64;
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// The JSON payloads published to the event topic, tagged by "event":
+/// * `{"event":"session_started","session_id":"..."}`, when the transcriber starts listening
+/// * `{"event":"session_ended","session_id":"..."}`, when transcription ends
+/// * `{"event":"timeout","session_id":"..."}`, when the transcriber times out
+/// * `{"event":"control","phrase":"[GETTING_READY]","session_id":"..."}`, for other control phrases
+/// * `{"event":"disconnected","session_id":"..."}`, published by the broker as the client's last
+///   will if the connection is lost
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum MqttEvent {
+    SessionStarted {
+        session_id: Option<String>,
+    },
+    SessionEnded {
+        session_id: Option<String>,
+    },
+    Timeout {
+        session_id: Option<String>,
+    },
+    Control {
+        phrase: String,
+        session_id: Option<String>,
+    },
+    Disconnected {
+        session_id: Option<String>,
+    },
+}
+
+impl MqttEvent {
+    pub fn from_control_phrase(phrase: &WhisperControlPhrase, session_id: Option<String>) -> Self {
+        match phrase {
+            WhisperControlPhrase::StartSpeaking => MqttEvent::SessionStarted { session_id },
+            WhisperControlPhrase::EndTranscription => MqttEvent::SessionEnded { session_id },
+            WhisperControlPhrase::TranscriptionTimeout => MqttEvent::Timeout { session_id },
+            _ => MqttEvent::Control {
+                phrase: phrase.to_string(),
+                session_id,
+            },
+        }
+    }
+}
+
+// The JSON payload published to the text topic for newly finalized text.
+#[derive(serde::Serialize)]
+struct FinalizedText<'a> {
+    text: &'a str,
+    session_id: Option<&'a str>,
+}
+
+// Topics used for publishing must be non-empty and cannot contain wildcards.
+fn check_topic(topic: &str) -> Result<(), RibbleWhisperError> {
+    if topic.is_empty() || topic.contains(['+', '#', '\0']) {
+        Err(RibbleWhisperError::ParameterError(format!(
+            "Invalid MQTT topic: {topic}. Topics must be non-empty and cannot contain wildcards."
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Configures and connects an [MqttSink].
+pub struct MqttSinkBuilder {
+    host: String,
+    port: u16,
+    client_id: String,
+    credentials: Option<(String, String)>,
+    keep_alive: Duration,
+    text_topic: String,
+    event_topic: String,
+    working_topic: Option<String>,
+    qos: QoS,
+    retain: bool,
+    json_payloads: bool,
+    session_id: Option<String>,
+}
+
+impl MqttSinkBuilder {
+    /// Creates a builder for a broker, (e.g. "localhost", [DEFAULT_PORT]).
+    pub fn new(host: &str, port: u16) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            client_id: format!("ribble-whisper-{}", std::process::id()),
+            credentials: None,
+            keep_alive: Duration::from_secs(30),
+            text_topic: format!("{DEFAULT_TOPIC_PREFIX}/text"),
+            event_topic: format!("{DEFAULT_TOPIC_PREFIX}/event"),
+            working_topic: None,
+            qos: QoS::AtLeastOnce,
+            retain: false,
+            json_payloads: true,
+            session_id: None,
+        }
+    }
+
+    /// The client id must be unique per broker; it defaults to one derived from the process id.
+    pub fn with_client_id(mut self, client_id: &str) -> Self {
+        self.client_id = client_id.to_string();
+        self
+    }
+
+    pub fn with_credentials(mut self, username: &str, password: &str) -> Self {
+        self.credentials = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Brokers consider the client gone after 1.5x this long without a message. Minimum 5s.
+    pub fn with_keep_alive(mut self, keep_alive: Duration) -> Self {
+        self.keep_alive = keep_alive.max(Duration::from_secs(5));
+        self
+    }
+
+    /// Publishes finalized text to {prefix}/text and events to {prefix}/event.
+    pub fn with_topic_prefix(mut self, prefix: &str) -> Self {
+        let prefix = prefix.trim_end_matches('/');
+        self.text_topic = format!("{prefix}/text");
+        self.event_topic = format!("{prefix}/event");
+        self
+    }
+
+    pub fn with_text_topic(mut self, topic: &str) -> Self {
+        self.text_topic = topic.to_string();
+        self
+    }
+
+    pub fn with_event_topic(mut self, topic: &str) -> Self {
+        self.event_topic = topic.to_string();
+        self
+    }
+
+    /// Also publishes the working (unconfirmed) text of each snapshot to this topic.
+    /// Working text changes often, so it is always published at QoS 0.
+    pub fn with_working_topic(mut self, topic: &str) -> Self {
+        self.working_topic = Some(topic.to_string());
+        self
+    }
+
+    /// The QoS for finalized text and events. Defaults to [QoS::AtLeastOnce].
+    pub fn with_qos(mut self, qos: QoS) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Sets the retain flag on published messages, so that new subscribers receive the latest
+    /// text and event.
+    pub fn with_retain(mut self, retain: bool) -> Self {
+        self.retain = retain;
+        self
+    }
+
+    /// Publishes finalized text as plain UTF-8 instead of JSON, (e.g. for automations that match
+    /// on the payload). Events are always JSON.
+    pub fn with_json_payloads(mut self, json_payloads: bool) -> Self {
+        self.json_payloads = json_payloads;
+        self
+    }
+
+    /// Tags JSON payloads with a session id, (e.g. to tell multiple rooms/devices apart).
+    pub fn with_session_id(mut self, session_id: &str) -> Self {
+        self.session_id = Some(session_id.to_string());
+        self
+    }
+
+    /// Starts connecting to the broker on a background thread. The connection is retried until
+    /// the sink is shut down; messages published while disconnected are queued, (up to a limit).
+    /// Returns Err if a topic is invalid.
+    pub fn build(self) -> Result<MqttSink, RibbleWhisperError> {
+        check_topic(&self.text_topic)?;
+        check_topic(&self.event_topic)?;
+        if let Some(topic) = self.working_topic.as_ref() {
+            check_topic(topic)?;
+        }
+
+        let mut options = MqttOptions::new(self.client_id, self.host, self.port);
+        options.set_keep_alive(self.keep_alive);
+        if let Some((username, password)) = self.credentials {
+            options.set_credentials(username, password);
+        }
+        let last_will = serde_json::to_vec(&MqttEvent::Disconnected {
+            session_id: self.session_id.clone(),
+        })?;
+        options.set_last_will(LastWill::new(
+            self.event_topic.clone(),
+            last_will,
+            self.qos,
+            self.retain,
+        ));
+
+        let (client, mut connection) = Client::new(options, REQUEST_CAPACITY);
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = Arc::clone(&running);
+        // Driving the connection sends queued publishes, and reconnects after errors.
+        let connection_thread = spawn(move || {
+            for notification in connection.iter() {
+                match notification {
+                    // The disconnect is only sent once the publishes queued ahead of it have been.
+                    Ok(Event::Outgoing(Outgoing::Disconnect)) => return,
+                    Ok(_) => {}
+                    // Shutting down while the broker is unreachable.
+                    Err(_) if !thread_running.load(Ordering::Acquire) => return,
+                    Err(ConnectionError::RequestsDone) => return,
+                    Err(_e) => {
+                        log_warn!("MQTT connection error: {_e}");
+                        sleep(RECONNECT_DELAY);
+                    }
+                }
+            }
+        });
+
+        Ok(MqttSink {
+            client,
+            text_topic: self.text_topic,
+            event_topic: self.event_topic,
+            working_topic: self.working_topic,
+            qos: self.qos,
+            retain: self.retain,
+            json_payloads: self.json_payloads,
+            session_id: self.session_id,
+            last_confirmed: Mutex::new(String::new()),
+            assembler: Mutex::new(SnapshotAssembler::new()),
+            running,
+            connection_thread: Some(connection_thread),
+        })
+    }
+}
+
+/// Publishes finalized text and session events to an MQTT broker, (e.g. for home-automation
+/// systems that trigger actions from spoken phrases).
+///
+/// Snapshots are published as the text confirmed since the previous snapshot, so subscribers
+/// receive each finalized phrase once. Control phrases are published as [MqttEvent]s.
+///
+/// This can be used directly as a transcriber's output sender. Publishing never blocks or fails;
+/// if the broker cannot keep up, messages are dropped and logged. Requires the mqtt feature.
+pub struct MqttSink {
+    client: Client,
+    text_topic: String,
+    event_topic: String,
+    working_topic: Option<String>,
+    qos: QoS,
+    retain: bool,
+    json_payloads: bool,
+    session_id: Option<String>,
+    last_confirmed: Mutex<String>,
+    assembler: Mutex<SnapshotAssembler>,
+    running: Arc<AtomicBool>,
+    connection_thread: Option<JoinHandle<()>>,
+}
+
+impl MqttSink {
+    pub fn text_topic(&self) -> &str {
+        &self.text_topic
+    }
+
+    pub fn event_topic(&self) -> &str {
+        &self.event_topic
+    }
+
+    /// Queues a message for publishing at the sink's QoS.
+    /// Returns Err if the queue is full or the sink has been shut down.
+    pub fn publish(&self, topic: &str, payload: Vec<u8>) -> Result<(), RibbleWhisperError> {
+        self.publish_with_qos(topic, payload, self.qos)
+    }
+
+    fn publish_with_qos(
+        &self,
+        topic: &str,
+        payload: Vec<u8>,
+        qos: QoS,
+    ) -> Result<(), RibbleWhisperError> {
+        self.client
+            .try_publish(topic, qos, self.retain, payload)
+            .map_err(|e| {
+                RibbleWhisperError::TranscriptionSenderError(format!(
+                    "Failed to publish to {topic}: {e}"
+                ))
+            })
+    }
+
+    /// Publishes finalized text to the text topic.
+    pub fn publish_text(&self, text: &str) -> Result<(), RibbleWhisperError> {
+        let payload = if self.json_payloads {
+            serde_json::to_vec(&FinalizedText {
+                text,
+                session_id: self.session_id.as_deref(),
+            })?
+        } else {
+            text.as_bytes().to_vec()
+        };
+        self.publish(&self.text_topic, payload)
+    }
+
+    /// Publishes a finalized segment to the text topic, (as JSON, see: [SegmentRecord]).
+    /// Segments without a session id are tagged with the sink's.
+    pub fn publish_segment(&self, segment: &SegmentRecord) -> Result<(), RibbleWhisperError> {
+        if !self.json_payloads {
+            return self.publish(&self.text_topic, segment.text.as_bytes().to_vec());
+        }
+        let payload = match (segment.session_id.as_ref(), self.session_id.as_ref()) {
+            (None, Some(session_id)) => {
+                serde_json::to_vec(&segment.clone().with_session_id(session_id.as_str()))?
+            }
+            _ => serde_json::to_vec(segment)?,
+        };
+        self.publish(&self.text_topic, payload)
+    }
+
+    pub fn publish_event(&self, event: &MqttEvent) -> Result<(), RibbleWhisperError> {
+        self.publish(&self.event_topic, serde_json::to_vec(event)?)
+    }
+
+    /// Publishes the newly confirmed text of a snapshot, (and its working text, if enabled).
+    pub fn publish_snapshot(
+        &self,
+        snapshot: &TranscriptionSnapshot,
+    ) -> Result<(), RibbleWhisperError> {
+        let confirmed = snapshot.confirmed();
+        let new_text = {
+            let mut last_confirmed = self.last_confirmed.lock();
+            // Deduplication can revise the tail of the confirmed text; republish it all if so.
+            let new_text = confirmed
+                .strip_prefix(last_confirmed.as_str())
+                .unwrap_or(confirmed)
+                .trim()
+                .to_string();
+            confirmed.clone_into(&mut last_confirmed);
+            new_text
+        };
+
+        if !new_text.is_empty() {
+            self.publish_text(&new_text)?;
+        }
+        if let Some(working_topic) = self.working_topic.as_ref() {
+            let working = snapshot.string_segments().join(" ");
+            self.publish_with_qos(
+                working_topic,
+                working.trim().as_bytes().to_vec(),
+                QoS::AtMostOnce,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Publishes a transcriber output. Snapshot deltas are applied to the previous snapshot and
+    /// published as a snapshot would be.
+    pub fn publish_output(&self, output: &WhisperOutput) -> Result<(), RibbleWhisperError> {
+        if let WhisperOutput::ControlPhrase(control_phrase) = output {
+            return self.publish_event(&MqttEvent::from_control_phrase(
+                control_phrase,
+                self.session_id.clone(),
+            ));
+        }
+        let snapshot = self.assembler.lock().push(output);
+        match snapshot {
+            Some(snapshot) => self.publish_snapshot(&snapshot),
+            None => Ok(()),
+        }
+    }
+
+    /// Disconnects from the broker once queued messages have been sent, (or immediately, if the
+    /// broker is unreachable).
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        let Some(thread) = self.connection_thread.take() else {
+            return;
+        };
+        self.running.store(false, Ordering::Release);
+        // Disconnecting cleanly means the broker does not publish the last will. This waits for
+        // room in the queue, which is freed once the connection thread sends or gives up.
+        let _ = self.client.disconnect();
+        let _ = thread.join();
+    }
+}
+
+impl Drop for MqttSink {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl OutputSink<WhisperOutput> for MqttSink {
+    fn try_send(&self, msg: WhisperOutput) -> Result<(), OutputSinkError<WhisperOutput>> {
+        if let Err(_e) = self.publish_output(&msg) {
+            log_warn!("Failed to publish MQTT output: {_e}");
+        }
+        Ok(())
+    }
+
+    fn send(&self, msg: WhisperOutput) -> Result<(), OutputSinkError<WhisperOutput>> {
+        self.try_send(msg)
+    }
+}
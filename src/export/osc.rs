@@ -0,0 +1,237 @@
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use parking_lot::Mutex;
+
+use crate::export::jsonl::SegmentRecord;
+use crate::transcriber::localization::{EnglishLocalizer, MessageLocalizer};
+use crate::transcriber::{
+    SnapshotAssembler, TranscriptionSnapshot, WhisperControlPhrase, WhisperOutput,
+};
+use crate::utils::errors::RibbleWhisperError;
+use crate::utils::logging::log_warn;
+use crate::utils::sink::{OutputSink, OutputSinkError};
+
+/// Receives newly finalized caption text, (string), and finalized segments,
+/// (string, start ms, end ms).
+pub const DEFAULT_TEXT_PATH: &str = "/ribble/caption";
+/// Receives control phrases, (e.g. "[START SPEAKING]").
+pub const DEFAULT_CONTROL_PATH: &str = "/ribble/control";
+
+/// An OSC 1.0 argument.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscArg {
+    String(String),
+    Int(i32),
+    Float(f32),
+}
+
+impl OscArg {
+    fn type_tag(&self) -> char {
+        match self {
+            OscArg::String(_) => 's',
+            OscArg::Int(_) => 'i',
+            OscArg::Float(_) => 'f',
+        }
+    }
+}
+
+// OSC strings are null-terminated and padded to a multiple of 4 bytes.
+fn push_osc_string(buf: &mut Vec<u8>, string: &str) {
+    buf.extend_from_slice(string.as_bytes());
+    let padding = 4 - (string.len() % 4);
+    buf.extend(std::iter::repeat_n(0u8, padding));
+}
+
+/// Encodes an OSC message, (e.g. for sending over a transport other than UDP).
+pub fn encode_osc_message(address: &str, args: &[OscArg]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    push_osc_string(&mut buf, address);
+    let type_tags: String = std::iter::once(',')
+        .chain(args.iter().map(OscArg::type_tag))
+        .collect();
+    push_osc_string(&mut buf, &type_tags);
+    for arg in args {
+        match arg {
+            OscArg::String(string) => push_osc_string(&mut buf, string),
+            OscArg::Int(int) => buf.extend_from_slice(&int.to_be_bytes()),
+            OscArg::Float(float) => buf.extend_from_slice(&float.to_be_bytes()),
+        }
+    }
+    buf
+}
+
+fn check_path(path: &str) -> Result<(), RibbleWhisperError> {
+    if path.starts_with('/') && !path.contains(char::is_whitespace) {
+        Ok(())
+    } else {
+        Err(RibbleWhisperError::ParameterError(format!(
+            "Invalid OSC address: {path}. Addresses must start with '/' and contain no whitespace."
+        )))
+    }
+}
+
+/// Sends finalized caption text and control events over OSC (UDP), (e.g. to theater or AV
+/// caption display systems).
+///
+/// Snapshots are sent as the text confirmed since the previous snapshot, so displays receive
+/// each finalized phrase once. The working (unconfirmed) text can also be sent to a separate
+/// address, (see: [OscSink::with_working_path]).
+///
+/// This can be used directly as a transcriber's output sender. Sending never fails; delivery
+/// errors are logged. Requires the osc feature.
+pub struct OscSink {
+    socket: UdpSocket,
+    target: SocketAddr,
+    text_path: String,
+    control_path: String,
+    working_path: Option<String>,
+    last_confirmed: Mutex<String>,
+    assembler: Mutex<SnapshotAssembler>,
+    localizer: Box<dyn MessageLocalizer>,
+}
+
+impl OscSink {
+    /// Creates a sink that sends to the given address, (e.g. "127.0.0.1:9000").
+    pub fn new<A: ToSocketAddrs>(target: A) -> Result<Self, RibbleWhisperError> {
+        let target = target.to_socket_addrs()?.next().ok_or_else(|| {
+            RibbleWhisperError::ParameterError(
+                "OSC target did not resolve to an address".to_string(),
+            )
+        })?;
+        let bind_addr: SocketAddr = if target.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(bind_addr)?;
+        Ok(Self {
+            socket,
+            target,
+            text_path: DEFAULT_TEXT_PATH.to_string(),
+            control_path: DEFAULT_CONTROL_PATH.to_string(),
+            working_path: None,
+            last_confirmed: Mutex::new(String::new()),
+            assembler: Mutex::new(SnapshotAssembler::new()),
+            localizer: Box::new(EnglishLocalizer),
+        })
+    }
+
+    /// Sets the address for finalized text. Returns Err if the address is not a valid OSC address.
+    pub fn with_text_path(mut self, path: &str) -> Result<Self, RibbleWhisperError> {
+        check_path(path)?;
+        self.text_path = path.to_string();
+        Ok(self)
+    }
+
+    /// Sets the address for control phrases. Returns Err if the address is not a valid OSC
+    /// address.
+    pub fn with_control_path(mut self, path: &str) -> Result<Self, RibbleWhisperError> {
+        check_path(path)?;
+        self.control_path = path.to_string();
+        Ok(self)
+    }
+
+    /// Also sends the working (unconfirmed) text of each snapshot to this address.
+    /// Returns Err if the address is not a valid OSC address.
+    pub fn with_working_path(mut self, path: &str) -> Result<Self, RibbleWhisperError> {
+        check_path(path)?;
+        self.working_path = Some(path.to_string());
+        Ok(self)
+    }
+
+    /// Sets the localizer for the control phrase text, (see: [crate::transcriber::localization]).
+    pub fn with_localizer<L: MessageLocalizer>(mut self, localizer: L) -> Self {
+        self.localizer = Box::new(localizer);
+        self
+    }
+
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    /// Sends a single OSC message to the target.
+    pub fn send_message(&self, address: &str, args: &[OscArg]) -> Result<(), RibbleWhisperError> {
+        self.socket
+            .send_to(&encode_osc_message(address, args), self.target)?;
+        Ok(())
+    }
+
+    /// Sends the newly confirmed text of a snapshot, (and its working text, if enabled).
+    pub fn send_snapshot(
+        &self,
+        snapshot: &TranscriptionSnapshot,
+    ) -> Result<(), RibbleWhisperError> {
+        let confirmed = snapshot.confirmed();
+        let new_text = {
+            let mut last_confirmed = self.last_confirmed.lock();
+            // Deduplication can revise the tail of the confirmed text; resend it all if so.
+            let new_text = confirmed
+                .strip_prefix(last_confirmed.as_str())
+                .unwrap_or(confirmed)
+                .trim()
+                .to_string();
+            confirmed.clone_into(&mut last_confirmed);
+            new_text
+        };
+
+        if !new_text.is_empty() {
+            self.send_message(&self.text_path, &[OscArg::String(new_text)])?;
+        }
+        if let Some(working_path) = self.working_path.as_ref() {
+            let working = snapshot.string_segments().join(" ");
+            self.send_message(working_path, &[OscArg::String(working.trim().to_string())])?;
+        }
+        Ok(())
+    }
+
+    /// Sends a finalized segment to the text address, as (text, start ms, end ms).
+    pub fn send_segment(&self, segment: &SegmentRecord) -> Result<(), RibbleWhisperError> {
+        let clamp = |ms: i64| ms.clamp(0, i32::MAX as i64) as i32;
+        self.send_message(
+            &self.text_path,
+            &[
+                OscArg::String(segment.text.clone()),
+                OscArg::Int(clamp(segment.start_ms)),
+                OscArg::Int(clamp(segment.end_ms)),
+            ],
+        )
+    }
+
+    pub fn send_control_phrase(
+        &self,
+        control_phrase: &WhisperControlPhrase,
+    ) -> Result<(), RibbleWhisperError> {
+        self.send_message(
+            &self.control_path,
+            &[OscArg::String(
+                control_phrase.localized(self.localizer.as_ref()),
+            )],
+        )
+    }
+
+    /// Sends a transcriber output. Snapshot deltas are applied to the previous snapshot and
+    /// sent as a snapshot would be.
+    pub fn send_output(&self, output: &WhisperOutput) -> Result<(), RibbleWhisperError> {
+        if let WhisperOutput::ControlPhrase(control_phrase) = output {
+            return self.send_control_phrase(control_phrase);
+        }
+        let snapshot = self.assembler.lock().push(output);
+        match snapshot {
+            Some(snapshot) => self.send_snapshot(&snapshot),
+            None => Ok(()),
+        }
+    }
+}
+
+impl OutputSink<WhisperOutput> for OscSink {
+    fn try_send(&self, msg: WhisperOutput) -> Result<(), OutputSinkError<WhisperOutput>> {
+        if let Err(_e) = self.send_output(&msg) {
+            log_warn!("Failed to send OSC output: {_e}");
+        }
+        Ok(())
+    }
+
+    fn send(&self, msg: WhisperOutput) -> Result<(), OutputSinkError<WhisperOutput>> {
+        self.try_send(msg)
+    }
+}
@@ -0,0 +1,286 @@
+use std::fs::{self, File};
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::export::jsonl::{read_records, JsonlWriter, SegmentRecord};
+use crate::transcriber::{
+    RibbleWhisperSegment, SnapshotAssembler, TranscriptionSnapshot, WhisperOutput,
+};
+use crate::utils::errors::RibbleWhisperError;
+
+/// Snapshots are saved at most this often by default, (see: [TranscriptStore::save_snapshot]).
+pub const DEFAULT_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+
+const SNAPSHOT_EXTENSION: &str = "snapshot.json";
+const SEGMENTS_EXTENSION: &str = "segments.jsonl";
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct StoredSnapshot {
+    confirmed: String,
+    working: Vec<String>,
+}
+
+/// The state of a session, as last persisted by a [TranscriptStore].
+#[derive(Clone, Default)]
+pub struct RecoveredTranscript {
+    snapshot: Option<TranscriptionSnapshot>,
+    segments: Vec<SegmentRecord>,
+}
+
+impl RecoveredTranscript {
+    /// The last snapshot saved, if any.
+    pub fn snapshot(&self) -> Option<&TranscriptionSnapshot> {
+        self.snapshot.as_ref()
+    }
+
+    /// The finalized segments saved, in order.
+    pub fn segments(&self) -> &[SegmentRecord] {
+        &self.segments
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshot.is_none() && self.segments.is_empty()
+    }
+
+    /// The recovered transcript text: the last snapshot (including its working segments) if one
+    /// was saved, otherwise the finalized segments.
+    pub fn text(&self) -> String {
+        match &self.snapshot {
+            Some(snapshot) => snapshot.to_string().trim().to_string(),
+            None => self
+                .segments
+                .iter()
+                .map(|record| record.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+fn check_session_id(session_id: &str) -> Result<(), RibbleWhisperError> {
+    let valid = !session_id.is_empty()
+        && session_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(RibbleWhisperError::ParameterError(format!(
+            "Invalid session id: {session_id}. Session ids may only contain ASCII letters, digits, '-' and '_'."
+        )))
+    }
+}
+
+fn session_path(directory: &Path, session_id: &str, extension: &str) -> PathBuf {
+    directory.join(format!("{session_id}.{extension}"))
+}
+
+/// Persists the transcript of a realtime session to disk as it is transcribed, so that it can be
+/// recovered if the application crashes.
+///
+/// Each session is stored in the store's directory as:
+/// * `<session_id>.snapshot.json`: the latest [TranscriptionSnapshot], replaced atomically.
+/// * `<session_id>.segments.jsonl`: finalized segments, appended as JSON Lines, (see:
+///   [crate::export::jsonl]).
+///
+/// On restart, use [TranscriptStore::latest_session] and [TranscriptStore::load] to recover the
+/// last state. Requires the serde feature.
+pub struct TranscriptStore {
+    directory: PathBuf,
+    session_id: String,
+    segments: JsonlWriter<File>,
+    snapshot_interval: Duration,
+    last_snapshot: Option<Instant>,
+    assembler: SnapshotAssembler,
+}
+
+impl TranscriptStore {
+    /// Opens (or resumes) a session in the given directory, creating the directory if needed.
+    /// Returns Err if the session id is not a valid file name, (ASCII letters, digits, '-', '_').
+    pub fn open<P: AsRef<Path>>(
+        directory: P,
+        session_id: &str,
+    ) -> Result<Self, RibbleWhisperError> {
+        check_session_id(session_id)?;
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory)?;
+        let segments =
+            JsonlWriter::append(session_path(&directory, session_id, SEGMENTS_EXTENSION))?
+                .with_session_id(session_id);
+        Ok(Self {
+            directory,
+            session_id: session_id.to_string(),
+            segments,
+            snapshot_interval: DEFAULT_SNAPSHOT_INTERVAL,
+            last_snapshot: None,
+            assembler: SnapshotAssembler::new(),
+        })
+    }
+
+    /// Sets how often [TranscriptStore::save_snapshot] writes to disk.
+    pub fn with_snapshot_interval(mut self, interval: Duration) -> Self {
+        self.snapshot_interval = interval;
+        self
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Saves a snapshot if the snapshot interval has elapsed since the last save.
+    /// Realtime transcribers send snapshots far more often than they need to be persisted.
+    /// Returns Ok(true) if the snapshot was written.
+    pub fn save_snapshot(
+        &mut self,
+        snapshot: &TranscriptionSnapshot,
+    ) -> Result<bool, RibbleWhisperError> {
+        if self
+            .last_snapshot
+            .is_some_and(|last| last.elapsed() < self.snapshot_interval)
+        {
+            return Ok(false);
+        }
+        self.write_snapshot(snapshot)?;
+        Ok(true)
+    }
+
+    /// Saves a snapshot immediately, (e.g. the final snapshot of a session).
+    /// The snapshot is written to a temporary file and then renamed, so a crash mid-write leaves
+    /// the previous snapshot intact.
+    pub fn write_snapshot(
+        &mut self,
+        snapshot: &TranscriptionSnapshot,
+    ) -> Result<(), RibbleWhisperError> {
+        let stored = StoredSnapshot {
+            confirmed: snapshot.confirmed().to_string(),
+            working: snapshot
+                .string_segments()
+                .iter()
+                .map(|segment| segment.to_string())
+                .collect(),
+        };
+        let path = session_path(&self.directory, &self.session_id, SNAPSHOT_EXTENSION);
+        let temp_path = path.with_extension("json.tmp");
+        {
+            let mut file = File::create(&temp_path)?;
+            file.write_all(&serde_json::to_vec(&stored)?)?;
+            file.sync_all()?;
+        }
+        fs::rename(&temp_path, &path)?;
+        self.last_snapshot = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Appends a finalized segment to the session log.
+    pub fn save_segment(
+        &mut self,
+        segment: &RibbleWhisperSegment,
+    ) -> Result<(), RibbleWhisperError> {
+        self.segments.write_segment(segment)
+    }
+
+    /// Appends a finalized segment record to the session log.
+    pub fn save_record(&mut self, record: SegmentRecord) -> Result<(), RibbleWhisperError> {
+        self.segments.write_record(record)
+    }
+
+    /// Saves a transcriber output: snapshots are saved (rate-limited), control phrases are
+    /// ignored. Snapshot deltas are applied to the previous snapshot, so every output should be
+    /// passed here. This is intended to be called from the loop that drains the output channel.
+    pub fn save_output(&mut self, output: &WhisperOutput) -> Result<(), RibbleWhisperError> {
+        if let Some(snapshot) = self.assembler.push(output) {
+            self.save_snapshot(&snapshot)?;
+        }
+        Ok(())
+    }
+
+    /// Loads the last saved state of this session.
+    pub fn recover(&self) -> Result<RecoveredTranscript, RibbleWhisperError> {
+        Self::load(&self.directory, &self.session_id)
+    }
+
+    /// Deletes the session's files, (e.g. once the transcript has been saved elsewhere).
+    pub fn clear(self) -> Result<(), RibbleWhisperError> {
+        let Self {
+            directory,
+            session_id,
+            segments,
+            ..
+        } = self;
+        drop(segments);
+        for extension in [SNAPSHOT_EXTENSION, SEGMENTS_EXTENSION] {
+            let path = session_path(&directory, &session_id, extension);
+            if path.is_file() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the last saved state of a session. Returns an empty [RecoveredTranscript] if
+    /// nothing has been saved for the session.
+    pub fn load<P: AsRef<Path>>(
+        directory: P,
+        session_id: &str,
+    ) -> Result<RecoveredTranscript, RibbleWhisperError> {
+        check_session_id(session_id)?;
+        let directory = directory.as_ref();
+
+        let snapshot_path = session_path(directory, session_id, SNAPSHOT_EXTENSION);
+        let snapshot = if snapshot_path.is_file() {
+            let stored: StoredSnapshot =
+                serde_json::from_reader(BufReader::new(File::open(snapshot_path)?))?;
+            let working: Vec<Arc<str>> = stored.working.into_iter().map(Arc::from).collect();
+            Some(TranscriptionSnapshot::new(
+                Arc::from(stored.confirmed),
+                Arc::from(working),
+            ))
+        } else {
+            None
+        };
+
+        let segments_path = session_path(directory, session_id, SEGMENTS_EXTENSION);
+        let segments = if segments_path.is_file() {
+            read_records(BufReader::new(File::open(segments_path)?))?
+        } else {
+            vec![]
+        };
+        Ok(RecoveredTranscript { snapshot, segments })
+    }
+
+    /// Finds the most recently saved session in the directory, (e.g. to offer recovery on
+    /// startup). Returns None if the directory contains no sessions.
+    pub fn latest_session<P: AsRef<Path>>(
+        directory: P,
+    ) -> Result<Option<String>, RibbleWhisperError> {
+        let directory = directory.as_ref();
+        if !directory.is_dir() {
+            return Ok(None);
+        }
+        let mut latest: Option<(SystemTime, String)> = None;
+        for entry in fs::read_dir(directory)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let Some(session_id) = file_name.to_str().and_then(|name| {
+                name.strip_suffix(&format!(".{SNAPSHOT_EXTENSION}"))
+                    .or_else(|| name.strip_suffix(&format!(".{SEGMENTS_EXTENSION}")))
+            }) else {
+                continue;
+            };
+            if check_session_id(session_id).is_err() {
+                continue;
+            }
+            let modified = entry.metadata()?.modified()?;
+            if latest.as_ref().is_none_or(|(time, _)| modified > *time) {
+                latest = Some((modified, session_id.to_string()));
+            }
+        }
+        Ok(latest.map(|(_, session_id)| session_id))
+    }
+}
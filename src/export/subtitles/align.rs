@@ -0,0 +1,212 @@
+use crate::export::subtitles::Cue;
+use crate::transcriber::RibbleWhisperSegment;
+
+/// Words are matched at most this many words away from where they would be if the cues and the
+/// reference were spoken at an even pace, by default.
+pub const DEFAULT_SEARCH_WINDOW: usize = 200;
+
+// Alignment steps, for tracing back the cheapest path.
+const MATCH: u8 = 0;
+const CUE_ONLY: u8 = 1;
+const REFERENCE_ONLY: u8 = 2;
+const UNREACHABLE: u32 = u32::MAX / 2;
+
+struct ReferenceWord {
+    text: String,
+    start_ms: i64,
+    end_ms: i64,
+}
+
+/// Recovers timestamps for cues whose text has been edited, (e.g. a corrected transcript imported
+/// with [crate::export::subtitles::read_subtitle_file]), from a fresh transcription of the same
+/// audio.
+///
+/// The words of the cues are aligned with the words of the reference transcription by edit
+/// distance, so corrected words take the timings of the words they replaced. Each cue is then
+/// retimed to span its aligned words; cues with no aligned words keep their own timings.
+///
+/// Word timings within a reference segment are interpolated by their position in its text, so
+/// the most accurate reference has one segment per word, (see:
+/// [crate::transcriber::offline_transcriber::OfflineTranscriber::process_word_segments]).
+#[derive(Copy, Clone, Debug)]
+pub struct CueAligner {
+    search_window: usize,
+}
+
+impl CueAligner {
+    pub fn new() -> Self {
+        Self {
+            search_window: DEFAULT_SEARCH_WINDOW,
+        }
+    }
+
+    /// Sets how far, in words, the alignment can drift from an even pace. Larger windows handle
+    /// longer stretches of added or removed text, at the cost of memory and time proportional to
+    /// the number of words times the window.
+    pub fn with_search_window(mut self, words: usize) -> Self {
+        self.search_window = words.max(1);
+        self
+    }
+
+    pub fn search_window(&self) -> usize {
+        self.search_window
+    }
+
+    /// Retimes the cues to the reference segments, whose timestamps are in centiseconds.
+    pub fn align(&self, cues: &[Cue], reference: &[RibbleWhisperSegment]) -> Vec<Cue> {
+        let reference = reference_words(reference);
+        let cue_words: Vec<(usize, String)> = cues
+            .iter()
+            .enumerate()
+            .flat_map(|(i, cue)| {
+                cue.lines()
+                    .iter()
+                    .flat_map(|line| line.split_whitespace())
+                    .map(normalize)
+                    .filter(|word| !word.is_empty())
+                    .map(move |word| (i, word))
+            })
+            .collect();
+
+        let mut spans: Vec<Option<(i64, i64)>> = vec![None; cues.len()];
+        for (cue_word, reference_word) in self.align_words(&cue_words, &reference) {
+            let word = &reference[reference_word];
+            let span = &mut spans[cue_words[cue_word].0];
+            *span = Some(match *span {
+                Some((start, end)) => (start.min(word.start_ms), end.max(word.end_ms)),
+                None => (word.start_ms, word.end_ms),
+            });
+        }
+
+        let mut previous_end = 0;
+        cues.iter()
+            .zip(spans)
+            .map(|(cue, span)| {
+                let (start_ms, end_ms) = span.unwrap_or((cue.start_ms(), cue.end_ms()));
+                let start_ms = start_ms.max(previous_end);
+                let end_ms = end_ms.max(start_ms);
+                previous_end = end_ms;
+                Cue::from_lines(cue.lines().to_vec(), start_ms, end_ms)
+                    .with_confidence(cue.confidence())
+            })
+            .collect()
+    }
+
+    // Returns (cue word, reference word) index pairs for the words aligned by the cheapest edit,
+    // searching within a band around the diagonal.
+    fn align_words(
+        &self,
+        cue_words: &[(usize, String)],
+        reference: &[ReferenceWord],
+    ) -> Vec<(usize, usize)> {
+        let (n, m) = (cue_words.len(), reference.len());
+        if n == 0 || m == 0 {
+            return vec![];
+        }
+        // The band is widened by the pace, so that consecutive rows always overlap.
+        let window = self.search_window + m.div_ceil(n);
+        let band = |i: usize| {
+            let center = i * m / n;
+            (center.saturating_sub(window), (center + window).min(m))
+        };
+
+        let mut costs: Vec<Vec<u32>> = Vec::with_capacity(n + 1);
+        let mut steps: Vec<Vec<u8>> = Vec::with_capacity(n + 1);
+        for i in 0..=n {
+            let (lo, hi) = band(i);
+            let mut row_costs = vec![UNREACHABLE; hi - lo + 1];
+            let mut row_steps = vec![MATCH; hi - lo + 1];
+            for j in lo..=hi {
+                let (cost, step) = if i == 0 {
+                    (j as u32, REFERENCE_ONLY)
+                } else {
+                    let (previous_lo, previous_hi) = band(i - 1);
+                    let previous = |j: usize| {
+                        if (previous_lo..=previous_hi).contains(&j) {
+                            costs[i - 1][j - previous_lo]
+                        } else {
+                            UNREACHABLE
+                        }
+                    };
+                    let mut best = (previous(j) + 1, CUE_ONLY);
+                    if j > 0 {
+                        let substitution = u32::from(cue_words[i - 1].1 != reference[j - 1].text);
+                        let matched = previous(j - 1) + substitution;
+                        if matched <= best.0 {
+                            best = (matched, MATCH);
+                        }
+                        if j > lo && row_costs[j - 1 - lo] + 1 < best.0 {
+                            best = (row_costs[j - 1 - lo] + 1, REFERENCE_ONLY);
+                        }
+                    }
+                    best
+                };
+                row_costs[j - lo] = cost;
+                row_steps[j - lo] = step;
+            }
+            costs.push(row_costs);
+            steps.push(row_steps);
+        }
+
+        let mut aligned = vec![];
+        let (mut i, mut j) = (n, m);
+        while i > 0 && j > 0 {
+            match steps[i][j - band(i).0] {
+                MATCH => {
+                    aligned.push((i - 1, j - 1));
+                    i -= 1;
+                    j -= 1;
+                }
+                CUE_ONLY => i -= 1,
+                _ => j -= 1,
+            }
+        }
+        aligned.reverse();
+        aligned
+    }
+}
+
+impl Default for CueAligner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Words are compared by their lowercase letters and digits, so that punctuation and casing
+// edits still align.
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn reference_words(segments: &[RibbleWhisperSegment]) -> Vec<ReferenceWord> {
+    let mut words = vec![];
+    for segment in segments {
+        // Whisper separates segments with a leading space, which shouldn't take up any time.
+        let text = segment.text().trim();
+        // Segment timestamps are in centiseconds.
+        let start_ms = segment.start_timestamp() * 10;
+        let duration = (segment.end_timestamp() * 10 - start_ms).max(0);
+        let time_at = |offset: usize| {
+            let fraction = offset as f64 / text.len().max(1) as f64;
+            start_ms + (duration as f64 * fraction).round() as i64
+        };
+        let mut search_from = 0;
+        for word in text.split_whitespace() {
+            let start = search_from + text[search_from..].find(word).unwrap_or(0);
+            let end = start + word.len();
+            search_from = end;
+            let normalized = normalize(word);
+            if !normalized.is_empty() {
+                words.push(ReferenceWord {
+                    text: normalized,
+                    start_ms: time_at(start),
+                    end_ms: time_at(end),
+                });
+            }
+        }
+    }
+    words
+}
@@ -0,0 +1,313 @@
+use crate::export::confidence::ConfidenceStyles;
+use crate::export::subtitles::{timestamp_parts, Cue, SubtitleExporter};
+
+/// A colour in an ASS style. An alpha of 0 is opaque and 255 is fully transparent.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AssColour {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub alpha: u8,
+}
+
+impl AssColour {
+    pub const WHITE: Self = Self::rgb(255, 255, 255);
+    pub const BLACK: Self = Self::rgb(0, 0, 0);
+    pub const YELLOW: Self = Self::rgb(255, 255, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, alpha: 0 }
+    }
+
+    pub const fn with_alpha(mut self, alpha: u8) -> Self {
+        self.alpha = alpha;
+        self
+    }
+}
+
+// ASS colours are written as &HAABBGGRR.
+impl std::fmt::Display for AssColour {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "&H{:02X}{:02X}{:02X}{:02X}",
+            self.alpha, self.b, self.g, self.r
+        )
+    }
+}
+
+/// Where text is anchored on screen, (numbered like a numpad in ASS).
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AssAlignment {
+    BottomLeft = 1,
+    #[default]
+    BottomCenter = 2,
+    BottomRight = 3,
+    MiddleLeft = 4,
+    Middle = 5,
+    MiddleRight = 6,
+    TopLeft = 7,
+    TopCenter = 8,
+    TopRight = 9,
+}
+
+/// The style applied to every cue of an ASS export.
+/// Sizes and margins are in script pixels, (see: [AssExporter::with_resolution]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssStyle {
+    name: String,
+    font_name: String,
+    font_size: u32,
+    primary_colour: AssColour,
+    outline_colour: AssColour,
+    back_colour: AssColour,
+    bold: bool,
+    italic: bool,
+    opaque_box: bool,
+    outline: f32,
+    shadow: f32,
+    alignment: AssAlignment,
+    margin_l: u32,
+    margin_r: u32,
+    margin_v: u32,
+}
+
+impl AssStyle {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_font(mut self, font_name: &str, font_size: u32) -> Self {
+        self.font_name = font_name.to_string();
+        self.font_size = font_size;
+        self
+    }
+    pub fn with_primary_colour(mut self, colour: AssColour) -> Self {
+        self.primary_colour = colour;
+        self
+    }
+    pub fn with_outline_colour(mut self, colour: AssColour) -> Self {
+        self.outline_colour = colour;
+        self
+    }
+    /// Sets the colour of the shadow, (or the box, see: [AssStyle::with_opaque_box]).
+    pub fn with_back_colour(mut self, colour: AssColour) -> Self {
+        self.back_colour = colour;
+        self
+    }
+    pub fn with_bold(mut self, bold: bool) -> Self {
+        self.bold = bold;
+        self
+    }
+    pub fn with_italic(mut self, italic: bool) -> Self {
+        self.italic = italic;
+        self
+    }
+    /// Draws an opaque box, (in the back colour), behind the text instead of an outline,
+    /// (e.g. broadcast-style captions).
+    pub fn with_opaque_box(mut self, opaque_box: bool) -> Self {
+        self.opaque_box = opaque_box;
+        self
+    }
+    pub fn with_outline(mut self, outline: f32, shadow: f32) -> Self {
+        self.outline = outline.max(0.0);
+        self.shadow = shadow.max(0.0);
+        self
+    }
+    pub fn with_alignment(mut self, alignment: AssAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+    /// Sets the left, right and vertical margins.
+    pub fn with_margins(mut self, left: u32, right: u32, vertical: u32) -> Self {
+        self.margin_l = left;
+        self.margin_r = right;
+        self.margin_v = vertical;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn to_style_line(&self) -> String {
+        // ASS booleans are -1 for true and 0 for false.
+        let flag = |value: bool| if value { -1 } else { 0 };
+        let border_style = if self.opaque_box { 3 } else { 1 };
+        format!(
+            "Style: {},{},{},{},&H000000FF,{},{},{},{},0,0,100,100,0,0,{},{},{},{},{},{},{},1",
+            self.name,
+            self.font_name,
+            self.font_size,
+            self.primary_colour,
+            self.outline_colour,
+            self.back_colour,
+            flag(self.bold),
+            flag(self.italic),
+            border_style,
+            self.outline,
+            self.shadow,
+            self.alignment as u8,
+            self.margin_l,
+            self.margin_r,
+            self.margin_v
+        )
+    }
+}
+
+impl Default for AssStyle {
+    fn default() -> Self {
+        Self {
+            name: "Default".to_string(),
+            font_name: "Arial".to_string(),
+            font_size: 54,
+            primary_colour: AssColour::WHITE,
+            outline_colour: AssColour::BLACK,
+            back_colour: AssColour::BLACK.with_alpha(0x80),
+            bold: false,
+            italic: false,
+            opaque_box: false,
+            outline: 2.0,
+            shadow: 1.0,
+            alignment: AssAlignment::BottomCenter,
+            margin_l: 80,
+            margin_r: 80,
+            margin_v: 60,
+        }
+    }
+}
+
+/// Exports cues as Advanced SubStation Alpha (.ass), with a configurable style and position.
+///
+/// Lines are written as laid out, (see: [crate::export::subtitles::layout::CaptionLayout]);
+/// the renderer's own wrapping is disabled. Confidence styles are written as `\i1` and `\c`
+/// override tags; classes are ignored.
+#[derive(Clone, Debug)]
+pub struct AssExporter {
+    title: Option<String>,
+    resolution: (u32, u32),
+    style: AssStyle,
+    position: Option<(u32, u32)>,
+}
+
+// Braces start override blocks; a newline would end the event.
+fn escape_text(text: &str) -> String {
+    text.replace('{', "\\{")
+        .replace('}', "\\}")
+        .replace(['\n', '\r'], " ")
+}
+
+// Formats a timestamp as H:MM:SS.cc
+fn format_timestamp(ms: i64) -> String {
+    let (hours, minutes, seconds, millis) = timestamp_parts(ms);
+    format!("{hours}:{minutes:02}:{seconds:02}.{:02}", millis / 10)
+}
+
+impl AssExporter {
+    pub fn new() -> Self {
+        Self {
+            title: None,
+            resolution: (1920, 1080),
+            style: AssStyle::default(),
+            position: None,
+        }
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Sets the script resolution that style sizes, margins and positions are measured in.
+    /// This is 1920x1080 by default.
+    pub fn with_resolution(mut self, width: u32, height: u32) -> Self {
+        self.resolution = (width.max(1), height.max(1));
+        self
+    }
+
+    pub fn with_style(mut self, style: AssStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Places every cue at a fixed point, (in script pixels), instead of using the style's
+    /// alignment margins. The style's alignment sets which point of the text is placed there.
+    pub fn with_position(mut self, x: u32, y: u32) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    pub fn style(&self) -> &AssStyle {
+        &self.style
+    }
+}
+
+impl Default for AssExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SubtitleExporter for AssExporter {
+    fn extension(&self) -> &'static str {
+        "ass"
+    }
+
+    fn export(&self, cues: &[Cue]) -> String {
+        self.export_with_styles(cues, &ConfidenceStyles::new())
+    }
+
+    fn export_with_styles(&self, cues: &[Cue], styles: &ConfidenceStyles) -> String {
+        let mut ass = String::from("[Script Info]\n");
+        if let Some(title) = self.title.as_ref() {
+            ass.push_str(&format!("Title: {}\n", escape_text(title)));
+        }
+        ass.push_str(&format!(
+            "ScriptType: v4.00+\nPlayResX: {}\nPlayResY: {}\nWrapStyle: 2\nScaledBorderAndShadow: yes\n\n",
+            self.resolution.0, self.resolution.1
+        ));
+
+        ass.push_str("[V4+ Styles]\nFormat: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n");
+        ass.push_str(&self.style.to_style_line());
+        ass.push_str("\n\n");
+
+        ass.push_str("[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n");
+        let position = self
+            .position
+            .map(|(x, y)| format!("{{\\pos({x},{y})}}"))
+            .unwrap_or_default();
+        for cue in cues {
+            let text = cue
+                .lines()
+                .iter()
+                .map(|line| escape_text(line))
+                .collect::<Vec<_>>()
+                .join("\\N");
+            let overrides = styles
+                .style_for(cue.confidence())
+                .map(|style| {
+                    let italic = if style.italic() { "\\i1" } else { "" };
+                    // Override colours are written as &HBBGGRR&, without alpha.
+                    let colour = style
+                        .colour()
+                        .map(|[r, g, b]| format!("\\c&H{b:02X}{g:02X}{r:02X}&"))
+                        .unwrap_or_default();
+                    format!("{italic}{colour}")
+                })
+                .filter(|overrides| !overrides.is_empty())
+                .map(|overrides| format!("{{{overrides}}}"))
+                .unwrap_or_default();
+            ass.push_str(&format!(
+                "Dialogue: 0,{},{},{},,0,0,0,,{position}{overrides}{text}\n",
+                format_timestamp(cue.start_ms()),
+                format_timestamp(cue.end_ms()),
+                self.style.name
+            ));
+        }
+        ass
+    }
+}
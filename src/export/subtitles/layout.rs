@@ -0,0 +1,265 @@
+use std::time::Duration;
+
+use crate::export::subtitles::Cue;
+use crate::transcriber::RibbleWhisperSegment;
+
+/// Lines are wrapped to at most this many characters by default.
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 42;
+/// Cues have at most this many lines by default.
+pub const DEFAULT_MAX_LINES: usize = 2;
+/// Cues are timed for a reading speed of at most this many characters per second by default.
+pub const DEFAULT_MAX_CPS: f32 = 17.0;
+/// Cues are shown for at least this long by default.
+pub const DEFAULT_MIN_DURATION: Duration = Duration::from_millis(1000);
+/// Cues are split if they would be shown for longer than this by default.
+pub const DEFAULT_MAX_DURATION: Duration = Duration::from_secs(7);
+/// A pause in speech this long ends a cue by default.
+pub const DEFAULT_PAUSE_BREAK: Duration = Duration::from_millis(1000);
+/// Consecutive cues are kept at least this far apart by default, so that players show a
+/// change between them.
+pub const DEFAULT_MIN_GAP: Duration = Duration::from_millis(80);
+
+struct TimedWord<'a> {
+    text: &'a str,
+    start_ms: i64,
+    end_ms: i64,
+    // The confidence of the cue the word came from.
+    confidence: Option<f32>,
+}
+
+/// Lays out transcribed text as subtitle cues: splitting it into cues that fit within a line
+/// and duration limit, wrapping lines, and adjusting timings for reading speed.
+///
+/// Timings are adjusted by extending how long a cue is shown, to meet the minimum duration and
+/// the characters-per-second (CPS) limit. Cues are never extended into the next cue, so fast
+/// speech can still exceed the CPS limit; lower the maximum line length or number of lines to
+/// show less text at a time.
+#[derive(Copy, Clone, Debug)]
+pub struct CaptionLayout {
+    max_line_length: usize,
+    max_lines: usize,
+    max_cps: f32,
+    min_duration: Duration,
+    max_duration: Duration,
+    pause_break: Duration,
+    min_gap: Duration,
+}
+
+impl CaptionLayout {
+    pub fn new() -> Self {
+        Self {
+            max_line_length: DEFAULT_MAX_LINE_LENGTH,
+            max_lines: DEFAULT_MAX_LINES,
+            max_cps: DEFAULT_MAX_CPS,
+            min_duration: DEFAULT_MIN_DURATION,
+            max_duration: DEFAULT_MAX_DURATION,
+            pause_break: DEFAULT_PAUSE_BREAK,
+            min_gap: DEFAULT_MIN_GAP,
+        }
+    }
+
+    /// Sets the maximum line length, in characters. Words longer than this are given their own
+    /// line.
+    pub fn with_max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = max_line_length.max(1);
+        self
+    }
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = max_lines.max(1);
+        self
+    }
+    /// Sets the maximum reading speed, in characters per second.
+    pub fn with_max_cps(mut self, max_cps: f32) -> Self {
+        self.max_cps = max_cps.max(1.0);
+        self
+    }
+    pub fn with_min_duration(mut self, min_duration: Duration) -> Self {
+        self.min_duration = min_duration;
+        self
+    }
+    pub fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = max_duration;
+        self
+    }
+    /// Sets the pause in speech that ends a cue.
+    pub fn with_pause_break(mut self, pause_break: Duration) -> Self {
+        self.pause_break = pause_break;
+        self
+    }
+    pub fn with_min_gap(mut self, min_gap: Duration) -> Self {
+        self.min_gap = min_gap;
+        self
+    }
+
+    pub fn max_line_length(&self) -> usize {
+        self.max_line_length
+    }
+    pub fn max_lines(&self) -> usize {
+        self.max_lines
+    }
+    pub fn max_cps(&self) -> f32 {
+        self.max_cps
+    }
+    pub fn min_duration(&self) -> Duration {
+        self.min_duration
+    }
+    pub fn max_duration(&self) -> Duration {
+        self.max_duration
+    }
+    pub fn pause_break(&self) -> Duration {
+        self.pause_break
+    }
+    pub fn min_gap(&self) -> Duration {
+        self.min_gap
+    }
+
+    /// Lays out transcribed segments, (see: [CaptionLayout::layout]).
+    pub fn layout_segments(&self, segments: &[RibbleWhisperSegment]) -> Vec<Cue> {
+        let cues: Vec<Cue> = segments.iter().map(Cue::from).collect();
+        self.layout(&cues)
+    }
+
+    /// Re-flows cues, (e.g. one per transcribed segment), into cues that fit the layout.
+    /// Word timings within a cue are interpolated by their position in its text, and a laid out
+    /// cue's confidence is the mean confidence of its words.
+    pub fn layout(&self, cues: &[Cue]) -> Vec<Cue> {
+        let texts: Vec<String> = cues.iter().map(Cue::text).collect();
+        let mut words = vec![];
+        for (cue, text) in cues.iter().zip(texts.iter()) {
+            let duration = cue.duration_ms().max(0);
+            let time_at = |offset: usize| {
+                let fraction = offset as f64 / text.len().max(1) as f64;
+                cue.start_ms() + (duration as f64 * fraction).round() as i64
+            };
+            let mut search_from = 0;
+            for word in text.split_whitespace() {
+                let start = search_from + text[search_from..].find(word).unwrap_or(0);
+                let end = start + word.len();
+                search_from = end;
+                words.push(TimedWord {
+                    text: word,
+                    start_ms: time_at(start),
+                    end_ms: time_at(end),
+                    confidence: cue.confidence(),
+                });
+            }
+        }
+
+        let max_duration = self.max_duration.as_millis() as i64;
+        let pause_break = self.pause_break.as_millis() as i64;
+        let mut laid_out = vec![];
+        let mut current: Vec<&TimedWord> = vec![];
+        for word in words.iter() {
+            if let (Some(first), Some(last)) = (current.first(), current.last()) {
+                let paused = word.start_ms - last.end_ms >= pause_break;
+                let too_long = word.end_ms - first.start_ms > max_duration;
+                current.push(word);
+                let overflows = self.wrap(&current).is_none();
+                current.pop();
+                if paused || too_long || overflows {
+                    laid_out.push(self.make_cue(&current));
+                    current.clear();
+                }
+            }
+            current.push(word);
+        }
+        if !current.is_empty() {
+            laid_out.push(self.make_cue(&current));
+        }
+
+        self.adjust_timings(&mut laid_out);
+        laid_out
+    }
+
+    fn make_cue(&self, words: &[&TimedWord]) -> Cue {
+        let lines = self.wrap(words).unwrap_or_else(|| {
+            // Cues only grow while their words fit, so this is a fallback that should not be reached.
+            vec![words
+                .iter()
+                .map(|word| word.text)
+                .collect::<Vec<_>>()
+                .join(" ")]
+        });
+        let confidences: Vec<f32> = words.iter().filter_map(|word| word.confidence).collect();
+        let confidence = (!confidences.is_empty())
+            .then(|| confidences.iter().sum::<f32>() / confidences.len() as f32);
+        Cue::from_lines(
+            lines,
+            words.first().map_or(0, |word| word.start_ms),
+            words.last().map_or(0, |word| word.end_ms),
+        )
+        .with_confidence(confidence)
+    }
+
+    // Wraps words into lines, returning None if they need more than the maximum number of lines.
+    // Two-line cues are balanced, so that the lines are of similar length.
+    fn wrap(&self, words: &[&TimedWord]) -> Option<Vec<String>> {
+        let mut lines: Vec<String> = vec![];
+        for word in words {
+            match lines.last_mut() {
+                Some(line)
+                    if line.chars().count() + 1 + word.text.chars().count()
+                        <= self.max_line_length =>
+                {
+                    line.push(' ');
+                    line.push_str(word.text);
+                }
+                _ => lines.push(word.text.to_string()),
+            }
+        }
+        if lines.len() > self.max_lines {
+            return None;
+        }
+        if lines.len() == 2 {
+            return Some(self.balance(words).unwrap_or(lines));
+        }
+        Some(lines)
+    }
+
+    // Splits words into the two lines of the most similar length that both fit.
+    fn balance(&self, words: &[&TimedWord]) -> Option<Vec<String>> {
+        let join = |words: &[&TimedWord]| {
+            words
+                .iter()
+                .map(|word| word.text)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        (1..words.len())
+            .map(|split| (join(&words[..split]), join(&words[split..])))
+            .filter(|(top, bottom)| {
+                top.chars().count() <= self.max_line_length
+                    && bottom.chars().count() <= self.max_line_length
+            })
+            .min_by_key(|(top, bottom)| top.chars().count().abs_diff(bottom.chars().count()))
+            .map(|(top, bottom)| vec![top, bottom])
+    }
+
+    fn adjust_timings(&self, cues: &mut [Cue]) {
+        let min_duration = self.min_duration.as_millis() as i64;
+        let min_gap = self.min_gap.as_millis() as i64;
+        for i in 0..cues.len() {
+            let next_start = cues.get(i + 1).map(Cue::start_ms);
+            let cue = &mut cues[i];
+            let chars = cue.text().chars().count() as f32;
+            let reading_time = (chars / self.max_cps * 1000.0).ceil() as i64;
+            let mut end_ms = cue
+                .end_ms
+                .max(cue.start_ms + min_duration.max(reading_time));
+            if let Some(next_start) = next_start {
+                // Extend up to the gap before the next cue, but never shorten the spoken time below
+                // what it was, unless the cues overlap.
+                end_ms = end_ms
+                    .min(next_start - min_gap)
+                    .max(cue.end_ms.min(next_start));
+            }
+            cue.end_ms = end_ms;
+        }
+    }
+}
+
+impl Default for CaptionLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,263 @@
+pub mod align;
+pub mod ass;
+pub mod layout;
+pub mod srt;
+pub mod ttml;
+pub mod vtt;
+
+use std::path::Path;
+
+use crate::export::confidence::ConfidenceStyles;
+use crate::transcriber::RibbleWhisperSegment;
+use crate::utils::errors::RibbleWhisperError;
+
+/// A single subtitle cue: one or more lines of text shown between two timestamps.
+/// Timestamps are in milliseconds.
+///
+/// Cues made directly from segments are a single line each; use a
+/// [layout::CaptionLayout] to split and wrap them before exporting.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Cue {
+    start_ms: i64,
+    end_ms: i64,
+    lines: Vec<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    confidence: Option<f32>,
+}
+
+impl Cue {
+    /// Creates a cue, with a line for each line of the text. Blank lines are dropped.
+    pub fn new(text: &str, start_ms: i64, end_ms: i64) -> Self {
+        let lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect();
+        Self::from_lines(lines, start_ms, end_ms)
+    }
+
+    pub fn from_lines(lines: Vec<String>, start_ms: i64, end_ms: i64) -> Self {
+        Self {
+            start_ms,
+            end_ms,
+            lines,
+            confidence: None,
+        }
+    }
+
+    /// Sets the confidence, (0.0 - 1.0), of the cue's text, for styling uncertain cues, (see:
+    /// [SubtitleExporter::export_with_styles]).
+    pub fn with_confidence(mut self, confidence: Option<f32>) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    pub fn start_ms(&self) -> i64 {
+        self.start_ms
+    }
+    pub fn end_ms(&self) -> i64 {
+        self.end_ms
+    }
+    pub fn duration_ms(&self) -> i64 {
+        self.end_ms - self.start_ms
+    }
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+    pub fn confidence(&self) -> Option<f32> {
+        self.confidence
+    }
+
+    /// The lines of the cue, joined by spaces.
+    pub fn text(&self) -> String {
+        self.lines.join(" ")
+    }
+
+    /// The reading speed of the cue, in characters per second.
+    pub fn chars_per_second(&self) -> f32 {
+        let chars = self.text().chars().count() as f32;
+        chars / (self.duration_ms().max(1) as f32 / 1000.0)
+    }
+}
+
+impl From<&RibbleWhisperSegment> for Cue {
+    fn from(segment: &RibbleWhisperSegment) -> Self {
+        // Segment timestamps are in centiseconds.
+        Self::new(
+            segment.text(),
+            segment.start_timestamp() * 10,
+            segment.end_timestamp() * 10,
+        )
+        .with_confidence(segment.confidence())
+    }
+}
+
+/// Reads cues from a SubRip (.srt) or WebVTT (.vtt) file, (e.g. an edited transcript), choosing
+/// the format by the file's extension.
+pub fn read_subtitle_file<P: AsRef<Path>>(path: P) -> Result<Vec<Cue>, RibbleWhisperError> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    let parse = match extension.as_deref() {
+        Some("srt") => srt::parse_srt,
+        Some("vtt") => vtt::parse_vtt,
+        _ => {
+            return Err(RibbleWhisperError::ParameterError(format!(
+                "Unsupported subtitle format: {}",
+                path.display()
+            )));
+        }
+    };
+    parse(&std::fs::read_to_string(path)?)
+}
+
+/// A subtitle file format.
+pub trait SubtitleExporter {
+    /// The file extension for the format, without the period, (e.g. "srt").
+    fn extension(&self) -> &'static str;
+
+    /// Renders the cues, which are expected to be in order and non-overlapping.
+    fn export(&self, cues: &[Cue]) -> String;
+
+    /// Renders the cues like [SubtitleExporter::export], styling each cue by its confidence.
+    /// Formats apply what they support of a style; the default implementation ignores styles.
+    fn export_with_styles(&self, cues: &[Cue], styles: &ConfidenceStyles) -> String {
+        let _ = styles;
+        self.export(cues)
+    }
+
+    /// Renders the cues and writes them to a file.
+    fn write_to_file<P: AsRef<Path>>(&self, cues: &[Cue], path: P) -> Result<(), RibbleWhisperError>
+    where
+        Self: Sized,
+    {
+        std::fs::write(path, self.export(cues))?;
+        Ok(())
+    }
+}
+
+// Splits a timestamp into hours, minutes, seconds and milliseconds. Negative timestamps are
+// clamped to 0.
+fn timestamp_parts(ms: i64) -> (i64, i64, i64, i64) {
+    let ms = ms.max(0);
+    (
+        ms / 3_600_000,
+        (ms / 60_000) % 60,
+        (ms / 1000) % 60,
+        ms % 1000,
+    )
+}
+
+// Formats a timestamp as HH:MM:SS<separator>mmm, (e.g. SRT and WebVTT).
+fn format_timestamp(ms: i64, separator: char) -> String {
+    let (hours, minutes, seconds, millis) = timestamp_parts(ms);
+    format!("{hours:02}:{minutes:02}:{seconds:02}{separator}{millis:03}")
+}
+
+// Splits subtitle text into blocks of consecutive non-blank lines, with the (1-based) line number
+// each block starts on.
+fn blocks(text: &str) -> Vec<(usize, Vec<&str>)> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut blocks = vec![];
+    let mut current: Option<(usize, Vec<&str>)> = None;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            blocks.extend(current.take());
+        } else {
+            current.get_or_insert_with(|| (i + 1, vec![])).1.push(line);
+        }
+    }
+    blocks.extend(current);
+    blocks
+}
+
+// Parses a timestamp as [HH:]MM:SS(,|.)mmm, (e.g. SRT and WebVTT).
+fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    let (clock, fraction) = timestamp.split_once([',', '.'])?;
+    let parts = clock
+        .split(':')
+        .map(|part| part.parse::<i64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let (hours, minutes, seconds) = match parts[..] {
+        [hours, minutes, seconds] => (hours, minutes, seconds),
+        [minutes, seconds] => (0, minutes, seconds),
+        _ => return None,
+    };
+    if hours < 0 || !(0..60).contains(&minutes) || !(0..60).contains(&seconds) {
+        return None;
+    }
+    if fraction.is_empty() || fraction.len() > 3 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // Fractions shorter than milliseconds, (e.g. "1.5"), are scaled up.
+    let millis = fraction.parse::<i64>().ok()? * 10i64.pow(3 - fraction.len() as u32);
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1000 + millis)
+}
+
+// Parses a cue timing line, "start --> end", ignoring any trailing cue settings.
+fn parse_timing(line: &str, line_number: usize) -> Result<(i64, i64), RibbleWhisperError> {
+    let invalid = |reason: String| RibbleWhisperError::SubtitleParseError {
+        line: line_number,
+        reason,
+    };
+    let (start, rest) = line
+        .split_once("-->")
+        .ok_or_else(|| invalid(format!("Expected a cue timing, found: {line}")))?;
+    let end = rest.split_whitespace().next().unwrap_or_default();
+    let start_ms = parse_timestamp(start.trim())
+        .ok_or_else(|| invalid(format!("Invalid start timestamp: {}", start.trim())))?;
+    let end_ms =
+        parse_timestamp(end).ok_or_else(|| invalid(format!("Invalid end timestamp: {end}")))?;
+    if end_ms < start_ms {
+        return Err(invalid(format!("Cue ends before it starts: {line}")));
+    }
+    Ok((start_ms, end_ms))
+}
+
+// Parses a block of cue lines: an optional identifier, (e.g. an SRT cue number), the timing and
+// then the text.
+fn parse_cue(line_number: usize, lines: &[&str]) -> Result<Cue, RibbleWhisperError> {
+    let first = lines[0];
+    let skip = usize::from(!first.contains("-->"));
+    let Some(timing) = lines.get(skip) else {
+        return Err(RibbleWhisperError::SubtitleParseError {
+            line: line_number,
+            reason: format!("Cue {first} has no timing"),
+        });
+    };
+    let (start_ms, end_ms) = parse_timing(timing, line_number + skip)?;
+    let lines = lines[skip + 1..]
+        .iter()
+        .map(|line| strip_tags(line).trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    Ok(Cue::from_lines(lines, start_ms, end_ms))
+}
+
+// Removes formatting tags, (e.g. <i> or <font color="red">), and decodes the entities escaped
+// by WebVTT.
+fn strip_tags(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut in_tag = false;
+    for c in line.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => stripped.push(c),
+            _ => {}
+        }
+    }
+    stripped
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
@@ -0,0 +1,61 @@
+use crate::export::confidence::{ConfidenceStyle, ConfidenceStyles};
+use crate::export::subtitles::{blocks, format_timestamp, parse_cue, Cue, SubtitleExporter};
+use crate::utils::errors::RibbleWhisperError;
+
+/// Exports cues as SubRip (.srt).
+/// Confidence styles are written as `<i>` and `<font color>` tags; classes are ignored.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SrtExporter;
+
+impl SubtitleExporter for SrtExporter {
+    fn extension(&self) -> &'static str {
+        "srt"
+    }
+
+    fn export(&self, cues: &[Cue]) -> String {
+        self.export_with_styles(cues, &ConfidenceStyles::new())
+    }
+
+    fn export_with_styles(&self, cues: &[Cue], styles: &ConfidenceStyles) -> String {
+        let mut srt = String::new();
+        for (i, cue) in cues.iter().enumerate() {
+            srt.push_str(&format!(
+                "{}\n{} --> {}\n",
+                i + 1,
+                format_timestamp(cue.start_ms(), ','),
+                format_timestamp(cue.end_ms(), ',')
+            ));
+            let style = styles.style_for(cue.confidence());
+            for line in cue.lines() {
+                match style {
+                    Some(style) => srt.push_str(&style_line(line, style)),
+                    None => srt.push_str(line),
+                }
+                srt.push('\n');
+            }
+            srt.push('\n');
+        }
+        srt
+    }
+}
+
+// Tags are applied per line, since not every player carries them across line breaks.
+fn style_line(line: &str, style: &ConfidenceStyle) -> String {
+    let mut line = line.to_string();
+    if style.italic() {
+        line = format!("<i>{line}</i>");
+    }
+    if let Some(colour) = style.hex_colour() {
+        line = format!("<font color=\"{colour}\">{line}</font>");
+    }
+    line
+}
+
+/// Parses SubRip (.srt) text into cues, (e.g. to import an edited transcript).
+/// Cue numbers are optional and formatting tags are removed.
+pub fn parse_srt(srt: &str) -> Result<Vec<Cue>, RibbleWhisperError> {
+    blocks(srt)
+        .into_iter()
+        .map(|(line_number, lines)| parse_cue(line_number, &lines))
+        .collect()
+}
@@ -0,0 +1,145 @@
+use crate::export::confidence::ConfidenceStyles;
+use crate::export::subtitles::{format_timestamp, Cue, SubtitleExporter};
+
+/// Which part of the screen TTML captions are displayed in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TtmlRegion {
+    #[default]
+    Bottom,
+    Top,
+}
+
+impl TtmlRegion {
+    // The region's origin and extent, as percentages of the video.
+    fn origin_and_extent(&self) -> (&'static str, &'static str) {
+        match self {
+            TtmlRegion::Bottom => ("10% 70%", "80% 20%"),
+            TtmlRegion::Top => ("10% 10%", "80% 20%"),
+        }
+    }
+
+    fn display_align(&self) -> &'static str {
+        match self {
+            TtmlRegion::Bottom => "after",
+            TtmlRegion::Top => "before",
+        }
+    }
+}
+
+/// Exports cues as Timed Text Markup Language (.ttml), using only features of the IMSC 1.1 Text
+/// Profile, (e.g. for broadcast and streaming delivery).
+/// Confidence styles are written as inline tts:fontStyle and tts:color attributes; classes are
+/// ignored.
+#[derive(Clone, Debug)]
+pub struct TtmlExporter {
+    language: String,
+    region: TtmlRegion,
+    background: bool,
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl TtmlExporter {
+    pub fn new() -> Self {
+        Self {
+            language: "en".to_string(),
+            region: TtmlRegion::default(),
+            background: true,
+        }
+    }
+
+    /// Sets the language of the captions, as a BCP 47 tag, (e.g. "en-US"). This is "en" by
+    /// default.
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = language.to_string();
+        self
+    }
+
+    pub fn with_region(mut self, region: TtmlRegion) -> Self {
+        self.region = region;
+        self
+    }
+
+    /// Sets whether text is drawn on a black background. This is enabled by default.
+    pub fn with_background(mut self, background: bool) -> Self {
+        self.background = background;
+        self
+    }
+}
+
+impl Default for TtmlExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SubtitleExporter for TtmlExporter {
+    fn extension(&self) -> &'static str {
+        "ttml"
+    }
+
+    fn export(&self, cues: &[Cue]) -> String {
+        self.export_with_styles(cues, &ConfidenceStyles::new())
+    }
+
+    fn export_with_styles(&self, cues: &[Cue], styles: &ConfidenceStyles) -> String {
+        let (origin, extent) = self.region.origin_and_extent();
+        let background = if self.background {
+            "black"
+        } else {
+            "transparent"
+        };
+        let mut ttml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:ttp="http://www.w3.org/ns/ttml#parameter" xmlns:tts="http://www.w3.org/ns/ttml#styling" ttp:timeBase="media" xml:lang="{}">
+  <head>
+    <styling>
+      <style xml:id="caption" tts:color="white" tts:backgroundColor="{background}" tts:fontFamily="proportionalSansSerif" tts:fontSize="100%" tts:textAlign="center"/>
+    </styling>
+    <layout>
+      <region xml:id="captions" tts:origin="{origin}" tts:extent="{extent}" tts:displayAlign="{}"/>
+    </layout>
+  </head>
+  <body region="captions">
+    <div>
+"#,
+            escape_xml(&self.language),
+            self.region.display_align()
+        );
+        for cue in cues {
+            let text = cue
+                .lines()
+                .iter()
+                .map(|line| escape_xml(line))
+                .collect::<Vec<_>>()
+                .join("<br/>");
+            let attributes: String = styles
+                .style_for(cue.confidence())
+                .map(|style| {
+                    let italic = if style.italic() {
+                        " tts:fontStyle=\"italic\""
+                    } else {
+                        ""
+                    };
+                    let colour = style
+                        .hex_colour()
+                        .map(|colour| format!(" tts:color=\"{colour}\""))
+                        .unwrap_or_default();
+                    format!("{italic}{colour}")
+                })
+                .unwrap_or_default();
+            ttml.push_str(&format!(
+                "      <p begin=\"{}\" end=\"{}\"><span style=\"caption\"{attributes}>{text}</span></p>\n",
+                format_timestamp(cue.start_ms(), '.'),
+                format_timestamp(cue.end_ms(), '.')
+            ));
+        }
+        ttml.push_str("    </div>\n  </body>\n</tt>\n");
+        ttml
+    }
+}
@@ -0,0 +1,94 @@
+use crate::export::confidence::{ConfidenceStyle, ConfidenceStyles};
+use crate::export::subtitles::{blocks, format_timestamp, parse_cue, Cue, SubtitleExporter};
+use crate::utils::errors::RibbleWhisperError;
+
+/// Exports cues as WebVTT (.vtt).
+/// Confidence styles are written as `<i>` and `<c.class>` tags. WebVTT has no inline colours, so
+/// a style's colour is only applied when it has a class, through a STYLE block.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct VttExporter;
+
+// '<' and '&' start tags and entities, and "-->" separates cue timings.
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl SubtitleExporter for VttExporter {
+    fn extension(&self) -> &'static str {
+        "vtt"
+    }
+
+    fn export(&self, cues: &[Cue]) -> String {
+        self.export_with_styles(cues, &ConfidenceStyles::new())
+    }
+
+    fn export_with_styles(&self, cues: &[Cue], styles: &ConfidenceStyles) -> String {
+        let mut vtt = String::from("WEBVTT\n\n");
+        let rules: Vec<String> = styles
+            .bands()
+            .iter()
+            .filter_map(|(_, style)| Some((style.class()?, style.hex_colour()?)))
+            .map(|(class, colour)| format!("::cue(.{class}) {{ color: {colour}; }}"))
+            .collect();
+        if !rules.is_empty() {
+            vtt.push_str(&format!("STYLE\n{}\n\n", rules.join("\n")));
+        }
+        for cue in cues {
+            vtt.push_str(&format!(
+                "{} --> {}\n",
+                format_timestamp(cue.start_ms(), '.'),
+                format_timestamp(cue.end_ms(), '.')
+            ));
+            let style = styles.style_for(cue.confidence());
+            for line in cue.lines() {
+                let line = escape_text(line);
+                match style {
+                    Some(style) => vtt.push_str(&style_line(line, style)),
+                    None => vtt.push_str(&line),
+                }
+                vtt.push('\n');
+            }
+            vtt.push('\n');
+        }
+        vtt
+    }
+}
+
+fn style_line(mut line: String, style: &ConfidenceStyle) -> String {
+    if style.italic() {
+        line = format!("<i>{line}</i>");
+    }
+    if let Some(class) = style.class() {
+        line = format!("<c.{class}>{line}</c>");
+    }
+    line
+}
+
+/// Parses WebVTT (.vtt) text into cues, (e.g. to import an edited transcript).
+/// Cue identifiers, settings, comments and style blocks are skipped, and tags are removed.
+pub fn parse_vtt(vtt: &str) -> Result<Vec<Cue>, RibbleWhisperError> {
+    let mut blocks = blocks(vtt).into_iter();
+    match blocks.next() {
+        Some((_, header)) if header[0].starts_with("WEBVTT") => {}
+        _ => {
+            return Err(RibbleWhisperError::SubtitleParseError {
+                line: 1,
+                reason: "Missing WEBVTT header".to_string(),
+            });
+        }
+    }
+
+    let mut cues = vec![];
+    for (line_number, lines) in blocks {
+        if ["NOTE", "STYLE", "REGION"]
+            .iter()
+            .any(|keyword| lines[0].starts_with(keyword))
+        {
+            continue;
+        }
+        cues.push(parse_cue(line_number, &lines)?);
+    }
+    Ok(cues)
+}
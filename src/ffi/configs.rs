@@ -0,0 +1,149 @@
+use std::str::FromStr;
+
+use crate::utils::errors::RibbleWhisperError;
+use crate::whisper::configs::{
+    Language, RealtimeBufferingStrategy, WhisperConfigs, WhisperRealtimeConfigs,
+    WhisperSamplingStrategy,
+};
+use crate::whisper::model::ModelId;
+
+/// Whisper configurations for foreign callers, (see: [WhisperConfigs]).
+/// Use [default_transcription_configs] to start from the crate defaults.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct TranscriptionConfigs {
+    /// The model to transcribe with, (see: [crate::ffi::model::ModelBankHandle]).
+    pub model_id: ModelId,
+    pub n_threads: u32,
+    /// An ISO-639-1 language code, (e.g. "en"). None or "auto" detects the language.
+    pub language: Option<String>,
+    pub translate: bool,
+    pub use_gpu: bool,
+    pub flash_attention: bool,
+    pub use_no_context: bool,
+    /// Uses beam search with the given beam size; None uses greedy sampling.
+    pub beam_size: Option<u32>,
+}
+
+impl TranscriptionConfigs {
+    fn from_whisper_configs(configs: &WhisperConfigs, model_id: ModelId) -> Self {
+        let beam_size = match configs.sampling_strategy() {
+            WhisperSamplingStrategy::Greedy { .. } => None,
+            WhisperSamplingStrategy::BeamSearch { beam_size, .. } => Some(beam_size as u32),
+        };
+        Self {
+            model_id,
+            n_threads: configs.n_threads() as u32,
+            language: configs
+                .language()
+                .as_ref()
+                .map(|language| language.to_string()),
+            translate: configs.translate(),
+            use_gpu: configs.using_gpu(),
+            flash_attention: configs.using_flash_attention(),
+            use_no_context: configs.using_no_context(),
+            beam_size,
+        }
+    }
+
+    // Applies these configurations on top of the given defaults, (e.g. to keep the prompt size).
+    fn apply_to(&self, configs: WhisperConfigs) -> Result<WhisperConfigs, RibbleWhisperError> {
+        let language = self
+            .language
+            .as_deref()
+            .map(|code| {
+                Language::from_str(&code.to_lowercase()).map_err(|_| {
+                    RibbleWhisperError::ParameterError(format!("Unknown language code: {code}"))
+                })
+            })
+            .transpose()?;
+        let sampling_strategy = match self.beam_size {
+            Some(beam_size) => WhisperSamplingStrategy::BeamSearch {
+                beam_size: beam_size.max(1) as usize,
+                // Whisper's default patience.
+                patience: -1.0,
+            },
+            None => WhisperSamplingStrategy::Greedy { best_of: 1 },
+        };
+        Ok(configs
+            .with_model_id(Some(self.model_id))
+            .with_n_threads(self.n_threads as usize)
+            .with_language(language)
+            .with_translate(self.translate)
+            .with_use_gpu(self.use_gpu)
+            .with_flash_attention(self.flash_attention)
+            .with_use_no_context(self.use_no_context)
+            .with_sampling_strategy(sampling_strategy))
+    }
+}
+
+impl TryFrom<&TranscriptionConfigs> for WhisperConfigs {
+    type Error = RibbleWhisperError;
+    fn try_from(configs: &TranscriptionConfigs) -> Result<Self, Self::Error> {
+        configs.apply_to(WhisperConfigs::default())
+    }
+}
+
+/// Realtime transcription configurations for foreign callers, (see: [WhisperRealtimeConfigs]).
+/// Use [default_realtime_session_configs] to start from the crate defaults.
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct RealtimeSessionConfigs {
+    pub whisper: TranscriptionConfigs,
+    /// Stops the session after this long; 0 runs until stopped.
+    pub timeout_ms: u64,
+    /// The size of the audio sampling window.
+    pub audio_sample_len_ms: u64,
+    /// The size of the voice-detection sampling window.
+    pub vad_sample_len_ms: u64,
+    /// Buffers this much audio before running inference; None runs inference continuously, (see:
+    /// [RealtimeBufferingStrategy]).
+    pub buffer_ms: Option<u64>,
+}
+
+impl TryFrom<&RealtimeSessionConfigs> for WhisperRealtimeConfigs {
+    type Error = RibbleWhisperError;
+    fn try_from(configs: &RealtimeSessionConfigs) -> Result<Self, Self::Error> {
+        let defaults = WhisperRealtimeConfigs::default();
+        let whisper_configs = configs.whisper.apply_to(*defaults.as_whisper_configs())?;
+        let buffering_strategy = match configs.buffer_ms {
+            Some(buffer_ms) => RealtimeBufferingStrategy::Buffered {
+                buffer_ms: buffer_ms as usize,
+            },
+            None => RealtimeBufferingStrategy::Continuous,
+        };
+        let realtime_configs = defaults
+            .into_realtime_configs()
+            .with_realtime_timeout(configs.timeout_ms as usize)
+            .with_audio_sample_len(configs.audio_sample_len_ms as usize)
+            .with_vad_sample_len(configs.vad_sample_len_ms as usize)
+            .with_buffering_strategy(buffering_strategy);
+        Ok(WhisperRealtimeConfigs::new()
+            .with_whisper_configs(whisper_configs)
+            .with_realtime_configs(realtime_configs))
+    }
+}
+
+/// Gets the default offline transcription configurations for a model.
+#[uniffi::export]
+pub fn default_transcription_configs(model_id: ModelId) -> TranscriptionConfigs {
+    TranscriptionConfigs::from_whisper_configs(&WhisperConfigs::default(), model_id)
+}
+
+/// Gets the default realtime transcription configurations for a model.
+#[uniffi::export]
+pub fn default_realtime_session_configs(model_id: ModelId) -> RealtimeSessionConfigs {
+    let defaults = WhisperRealtimeConfigs::default();
+    let buffer_ms = match defaults.realtime_buffering_strategy() {
+        RealtimeBufferingStrategy::Continuous => None,
+        RealtimeBufferingStrategy::Buffered { buffer_ms } => Some(buffer_ms as u64),
+    };
+    RealtimeSessionConfigs {
+        whisper: TranscriptionConfigs::from_whisper_configs(
+            defaults.as_whisper_configs(),
+            model_id,
+        ),
+        timeout_ms: defaults.realtime_timeout() as u64,
+        audio_sample_len_ms: defaults.audio_sample_len_ms() as u64,
+        vad_sample_len_ms: defaults.vad_sample_len() as u64,
+        buffer_ms,
+    }
+}
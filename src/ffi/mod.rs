@@ -0,0 +1,14 @@
+//! UniFFI bindings for Swift/Kotlin consumers, (e.g. mobile apps transcribing on-device).
+//! Requires the uniffi feature flag to be set.
+//!
+//! The bindings cover configurations, the model bank, offline transcription and realtime
+//! sessions. Audio is captured by the app and passed in as 16 kHz f32 samples. Errors are
+//! surfaced as a flat RibbleWhisperError exception carrying the error's message.
+//!
+//! Build the crate as a cdylib/staticlib for the target platform, then generate the foreign
+//! bindings from the built library with the bundled uniffi-bindgen binary, (requires the
+//! uniffi-bindgen feature flag), e.g:
+//! `cargo run --features uniffi-bindgen --bin uniffi-bindgen generate --library <lib> --language swift --out-dir <dir>`
+pub mod configs;
+pub mod model;
+pub mod transcriber;
@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use crate::utils::errors::RibbleWhisperError;
+use crate::whisper::model::{
+    CustomModelSource, DefaultModelBank, DefaultModelType, ModelBank, ModelId, ModelLocation,
+    ModelMetadata, ModelRetriever,
+};
+
+/// A model in a [ModelBankHandle], (see: [crate::whisper::model::Model]).
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct ModelInfo {
+    pub id: ModelId,
+    pub name: String,
+    pub file_name: String,
+    /// The size of the model file in bytes, or None if the model has not been downloaded.
+    pub size_on_disk: Option<u64>,
+    /// False for English-only (.en) models.
+    pub multilingual: bool,
+    /// Where to download the model from, if known. Download the file into the bank's model
+    /// directory, then call [ModelBankHandle::refresh].
+    pub download_url: Option<String>,
+}
+
+// The bank is shared with running transcribers, so it is locked for retrieval.
+#[derive(Clone)]
+pub(crate) struct SharedModelBank(Arc<RwLock<DefaultModelBank>>);
+
+impl ModelRetriever for SharedModelBank {
+    fn retrieve_model(&self, model_id: ModelId) -> Option<ModelLocation> {
+        self.0.read().retrieve_model(model_id)
+    }
+}
+
+/// A [DefaultModelBank] for foreign callers. Models are stored in a directory owned by the app,
+/// (e.g. its Application Support or files directory).
+#[derive(uniffi::Object)]
+pub struct ModelBankHandle {
+    bank: SharedModelBank,
+}
+
+impl ModelBankHandle {
+    pub(crate) fn shared_bank(&self) -> SharedModelBank {
+        self.bank.clone()
+    }
+}
+
+#[uniffi::export]
+impl ModelBankHandle {
+    #[uniffi::constructor]
+    pub fn new(model_directory: String) -> Self {
+        let bank = DefaultModelBank::new().with_model_directory(PathBuf::from(model_directory));
+        Self {
+            bank: SharedModelBank(Arc::new(RwLock::new(bank))),
+        }
+    }
+
+    pub fn model_directory(&self) -> String {
+        self.bank
+            .0
+            .read()
+            .model_directory()
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Gets the id of one of the default whisper models.
+    pub fn default_model_id(&self, model_type: DefaultModelType) -> ModelId {
+        self.bank.0.read().get_model_id(model_type)
+    }
+
+    pub fn list_models(&self) -> Result<Vec<ModelInfo>, RibbleWhisperError> {
+        let bank = self.bank.0.read();
+        let mut models = bank
+            .list_models()
+            .into_iter()
+            .map(|(id, model)| {
+                let metadata = ModelMetadata::from_model(model, bank.model_directory())?;
+                Ok(ModelInfo {
+                    id,
+                    name: model.name().to_string(),
+                    file_name: model.file_name().to_string(),
+                    size_on_disk: metadata.size_on_disk,
+                    multilingual: metadata.multilingual,
+                    download_url: bank.download_url(id),
+                })
+            })
+            .collect::<Result<Vec<_>, RibbleWhisperError>>()?;
+        // The bank is unordered; sort so that lists are stable.
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(models)
+    }
+
+    pub fn model_in_storage(&self, model_id: ModelId) -> Result<bool, RibbleWhisperError> {
+        self.bank.0.read().model_exists_in_storage(model_id)
+    }
+
+    /// Registers a model file that already exists on disk, (e.g. a fine-tuned model), and returns
+    /// its id.
+    pub fn register_model_file(
+        &self,
+        name: String,
+        path: String,
+    ) -> Result<ModelId, RibbleWhisperError> {
+        self.bank
+            .0
+            .write()
+            .register_custom_model(name, CustomModelSource::Path(PathBuf::from(path)))
+    }
+
+    pub fn remove_model(&self, model_id: ModelId) -> Result<(), RibbleWhisperError> {
+        self.bank.0.write().remove_model(model_id)?;
+        Ok(())
+    }
+
+    /// Reloads the bank from its model directory, (e.g. after downloading a model).
+    pub fn refresh(&self) -> Result<(), RibbleWhisperError> {
+        self.bank.0.write().refresh_model_bank()
+    }
+}
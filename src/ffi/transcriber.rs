@@ -0,0 +1,326 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use crate::audio::audio_ring_buffer::AudioRingBuffer;
+use crate::audio::{AudioChannelConfiguration, WhisperAudioSample};
+use crate::ffi::configs::{RealtimeSessionConfigs, TranscriptionConfigs};
+use crate::ffi::model::{ModelBankHandle, SharedModelBank};
+use crate::transcriber::offline_transcriber::OfflineTranscriberBuilder;
+use crate::transcriber::realtime_transcriber::{
+    RealtimeTranscriberBuilder, RealtimeTranscriberHandle,
+};
+use crate::transcriber::vad::{Silero, WebRtc, VAD};
+use crate::transcriber::{SnapshotAssembler, WhisperCallbacks, WhisperOutput};
+use crate::utils::callback::StaticRibbleWhisperCallback;
+use crate::utils::errors::RibbleWhisperError;
+use crate::utils::sink::{OutputSink, OutputSinkError};
+use crate::whisper::configs::{WhisperConfigs, WhisperRealtimeConfigs};
+
+/// The voice activity detector used to find speech, (see: [crate::transcriber::vad]).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, uniffi::Enum)]
+pub enum VadKind {
+    /// Recommended for accuracy, but sensitive to background noise.
+    Silero,
+    WebRtc,
+}
+
+/// Receives progress from an [OfflineSession].
+#[uniffi::export(callback_interface)]
+pub trait OfflineTranscriptionListener: Send + Sync {
+    /// Transcription progress, as a percentage.
+    fn on_progress(&self, percent: i32);
+    /// The full transcription so far, sent as each new segment is decoded.
+    fn on_new_segment(&self, transcription: String);
+}
+
+/// Receives outputs from a [RealtimeSession]. These are called from the transcription thread and
+/// should return quickly, (e.g. by dispatching to the UI thread).
+#[uniffi::export(callback_interface)]
+pub trait RealtimeTranscriptionListener: Send + Sync {
+    /// The confirmed transcription, and the working segments that may still change.
+    fn on_snapshot(&self, confirmed: String, working: Vec<String>);
+    /// A change in the transcriber's state, (see: [crate::transcriber::WhisperControlPhrase]).
+    fn on_control_phrase(&self, phrase: String);
+}
+
+/// Transcribes recorded audio, (see: [crate::transcriber::offline_transcriber::OfflineTranscriber]).
+#[derive(uniffi::Object)]
+pub struct OfflineSession {
+    bank: SharedModelBank,
+    configs: WhisperConfigs,
+    vad: Option<VadKind>,
+    run_transcription: Arc<AtomicBool>,
+}
+
+impl OfflineSession {
+    fn run<V: VAD<f32>>(
+        &self,
+        builder: OfflineTranscriberBuilder<V, SharedModelBank>,
+        listener: Option<Arc<dyn OfflineTranscriptionListener>>,
+    ) -> Result<String, RibbleWhisperError> {
+        let transcriber = builder.build()?;
+        self.run_transcription.store(true, Ordering::Release);
+        let run_transcription = Arc::clone(&self.run_transcription);
+        let Some(listener) = listener else {
+            return transcriber.process_audio(run_transcription);
+        };
+
+        let progress_listener = Arc::clone(&listener);
+        let callbacks = WhisperCallbacks {
+            progress: Some(StaticRibbleWhisperCallback::new(move |percent| {
+                progress_listener.on_progress(percent)
+            })),
+            new_segment: Some(StaticRibbleWhisperCallback::new(move |transcription| {
+                listener.on_new_segment(transcription)
+            })),
+        };
+        transcriber.process_with_callbacks(run_transcription, callbacks)
+    }
+
+    fn transcribe_inner(
+        &self,
+        samples: Vec<f32>,
+        channels: AudioChannelConfiguration,
+        listener: Option<Arc<dyn OfflineTranscriptionListener>>,
+    ) -> Result<String, RibbleWhisperError> {
+        let builder = OfflineTranscriberBuilder::<Silero, SharedModelBank>::new()
+            .with_configs(self.configs)
+            .with_audio(WhisperAudioSample::F32(Arc::from(samples)))
+            .with_channel_configurations(channels)
+            .with_model_retriever(self.bank.clone());
+        match self.vad {
+            Some(VadKind::Silero) => self.run(
+                builder.with_voice_activity_detector(Silero::try_new_whisper_offline_default()?),
+                listener,
+            ),
+            Some(VadKind::WebRtc) => self.run(
+                builder.with_voice_activity_detector(WebRtc::try_new_whisper_offline_default()?),
+                listener,
+            ),
+            None => self.run(builder, listener),
+        }
+    }
+}
+
+#[uniffi::export]
+impl OfflineSession {
+    /// Set vad to only transcribe the parts of the audio that contain speech, which is faster
+    /// for recordings with long silences.
+    #[uniffi::constructor]
+    pub fn new(
+        model_bank: Arc<ModelBankHandle>,
+        configs: TranscriptionConfigs,
+        vad: Option<VadKind>,
+    ) -> Result<Self, RibbleWhisperError> {
+        Ok(Self {
+            bank: model_bank.shared_bank(),
+            configs: WhisperConfigs::try_from(&configs)?,
+            vad,
+            run_transcription: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Transcribes 16 kHz audio, returning the full transcription.
+    /// This blocks until transcription finishes or is cancelled, so call it off the main thread.
+    pub fn transcribe(
+        &self,
+        samples: Vec<f32>,
+        channels: AudioChannelConfiguration,
+    ) -> Result<String, RibbleWhisperError> {
+        self.transcribe_inner(samples, channels, None)
+    }
+
+    /// Transcribes 16 kHz audio, reporting progress to the listener, (see: [OfflineSession::transcribe]).
+    pub fn transcribe_with_listener(
+        &self,
+        samples: Vec<f32>,
+        channels: AudioChannelConfiguration,
+        listener: Box<dyn OfflineTranscriptionListener>,
+    ) -> Result<String, RibbleWhisperError> {
+        self.transcribe_inner(samples, channels, Some(Arc::from(listener)))
+    }
+
+    /// Stops a running transcription, (e.g. from another thread), which then returns
+    /// [RibbleWhisperError::Cancelled].
+    pub fn cancel(&self) {
+        self.run_transcription.store(false, Ordering::Release);
+    }
+}
+
+// Forwards the realtime transcriber's outputs to a foreign listener, assembling snapshot deltas.
+struct ListenerSink {
+    listener: Box<dyn RealtimeTranscriptionListener>,
+    assembler: Mutex<SnapshotAssembler>,
+}
+
+impl ListenerSink {
+    fn deliver(&self, msg: WhisperOutput) {
+        if let WhisperOutput::ControlPhrase(phrase) = &msg {
+            self.listener.on_control_phrase(phrase.to_string());
+            return;
+        }
+        let snapshot = self.assembler.lock().push(&msg);
+        if let Some(snapshot) = snapshot {
+            let working = snapshot
+                .string_segments()
+                .iter()
+                .map(|segment| segment.to_string())
+                .collect();
+            self.listener
+                .on_snapshot(snapshot.confirmed().to_string(), working);
+        }
+    }
+}
+
+impl OutputSink<WhisperOutput> for ListenerSink {
+    fn try_send(&self, msg: WhisperOutput) -> Result<(), OutputSinkError<WhisperOutput>> {
+        self.deliver(msg);
+        Ok(())
+    }
+
+    fn send(&self, msg: WhisperOutput) -> Result<(), OutputSinkError<WhisperOutput>> {
+        self.deliver(msg);
+        Ok(())
+    }
+}
+
+type RealtimeWorker = JoinHandle<Result<String, RibbleWhisperError>>;
+
+fn spawn_realtime<V: VAD<f32> + Send + Sync + 'static>(
+    vad: V,
+    configs: WhisperRealtimeConfigs,
+    audio_buffer: &AudioRingBuffer<f32>,
+    bank: SharedModelBank,
+    sink: ListenerSink,
+    run_transcription: &Arc<AtomicBool>,
+    slow_stop: &Arc<AtomicBool>,
+) -> Result<(RealtimeTranscriberHandle, RealtimeWorker), RibbleWhisperError> {
+    let (transcriber, handle) = RealtimeTranscriberBuilder::<V, SharedModelBank>::new()
+        .with_configs(configs)
+        .with_audio_buffer(audio_buffer)
+        .with_output_sender(sink)
+        .with_model_retriever(bank)
+        .with_voice_activity_detector(vad)
+        .build()?;
+    let run_transcription = Arc::clone(run_transcription);
+    let slow_stop = Arc::clone(slow_stop);
+    let worker = transcriber.spawn(run_transcription, slow_stop)?;
+    Ok((handle, worker))
+}
+
+/// Transcribes live audio on a background thread, (see:
+/// [crate::transcriber::realtime_transcriber::RealtimeTranscriber]).
+///
+/// The app captures audio and passes it in with [RealtimeSession::push_audio]; outputs are sent
+/// to the session's [RealtimeTranscriptionListener]. Dropping the session stops transcription.
+#[derive(uniffi::Object)]
+pub struct RealtimeSession {
+    audio_buffer: AudioRingBuffer<f32>,
+    handle: RealtimeTranscriberHandle,
+    run_transcription: Arc<AtomicBool>,
+    slow_stop: Arc<AtomicBool>,
+    worker: Mutex<Option<RealtimeWorker>>,
+}
+
+#[uniffi::export]
+impl RealtimeSession {
+    /// Loads the model and starts transcribing on a background thread.
+    #[uniffi::constructor]
+    pub fn start(
+        model_bank: Arc<ModelBankHandle>,
+        configs: RealtimeSessionConfigs,
+        vad: VadKind,
+        listener: Box<dyn RealtimeTranscriptionListener>,
+    ) -> Result<Self, RibbleWhisperError> {
+        let configs = WhisperRealtimeConfigs::try_from(&configs)?;
+        let audio_buffer = AudioRingBuffer::<f32>::default();
+        let run_transcription = Arc::new(AtomicBool::new(true));
+        let slow_stop = Arc::new(AtomicBool::new(false));
+        let sink = ListenerSink {
+            listener,
+            assembler: Mutex::new(SnapshotAssembler::new()),
+        };
+        let bank = model_bank.shared_bank();
+
+        let (handle, worker) = match vad {
+            VadKind::Silero => spawn_realtime(
+                Silero::try_new_whisper_realtime_default()?,
+                configs,
+                &audio_buffer,
+                bank,
+                sink,
+                &run_transcription,
+                &slow_stop,
+            ),
+            VadKind::WebRtc => spawn_realtime(
+                WebRtc::try_new_whisper_realtime_default()?,
+                configs,
+                &audio_buffer,
+                bank,
+                sink,
+                &run_transcription,
+                &slow_stop,
+            ),
+        }?;
+
+        Ok(Self {
+            audio_buffer,
+            handle,
+            run_transcription,
+            slow_stop,
+            worker: Mutex::new(Some(worker)),
+        })
+    }
+
+    /// Adds captured audio: 16 kHz, mono samples in [-1.0, 1.0].
+    pub fn push_audio(&self, samples: Vec<f32>) {
+        self.audio_buffer.push_audio(&samples);
+    }
+
+    /// Returns true once the model has loaded and the transcriber is listening.
+    pub fn is_ready(&self) -> bool {
+        self.handle.ready()
+    }
+
+    /// Extends the session's timeout, (e.g. after a "[TIMEOUT APPROACHING]" control phrase).
+    pub fn extend_timeout(&self, extension_ms: u64) {
+        self.handle
+            .extend_timeout(Duration::from_millis(extension_ms));
+    }
+
+    /// Returns false once the session has stopped, (e.g. on timeout or error).
+    pub fn is_running(&self) -> bool {
+        self.worker
+            .lock()
+            .as_ref()
+            .is_some_and(|worker| !worker.is_finished())
+    }
+
+    /// Stops transcription and returns the final transcription. Set slow_stop to finish
+    /// transcribing buffered audio first.
+    /// This blocks until the transcription thread exits.
+    pub fn stop(&self, slow_stop: bool) -> Result<String, RibbleWhisperError> {
+        let worker = self
+            .worker
+            .lock()
+            .take()
+            .ok_or(RibbleWhisperError::ParameterError(
+                "Realtime session has already been stopped.".to_string(),
+            ))?;
+        self.slow_stop.store(slow_stop, Ordering::Release);
+        self.run_transcription.store(false, Ordering::Release);
+        worker.join().map_err(|_| {
+            RibbleWhisperError::Unknown("Realtime transcription thread panicked.".to_string())
+        })?
+    }
+}
+
+impl Drop for RealtimeSession {
+    fn drop(&mut self) {
+        self.run_transcription.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::spawn;
+
+use parking_lot::Mutex;
+
+use crate::audio::loading::{
+    load_normalized_audio_bytes_with_abort, load_normalized_audio_file_with_abort,
+};
+use crate::audio::AudioChannelConfiguration;
+use crate::grpc::proto::submit_job_request::Source;
+use crate::grpc::proto::{Job, JobState, Segment, VadKind};
+use crate::transcriber::offline_transcriber::OfflineTranscriberBuilder;
+use crate::transcriber::vad::{Silero, WebRtc, VAD};
+use crate::transcriber::{RibbleWhisperSegment, WhisperCallbacks};
+use crate::utils::callback::{Nop, StaticRibbleWhisperCallback};
+use crate::utils::errors::RibbleWhisperError;
+use crate::utils::logging::log_warn;
+use crate::whisper::configs::WhisperConfigs;
+use crate::whisper::model::{ModelId, ModelLocation, ModelRetriever};
+
+// Shares the service's retriever with the job workers and realtime sessions.
+pub(crate) struct SharedRetriever<M: ModelRetriever>(pub(crate) Arc<M>);
+
+impl<M: ModelRetriever> Clone for SharedRetriever<M> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<M: ModelRetriever> ModelRetriever for SharedRetriever<M> {
+    fn retrieve_model(&self, model_id: ModelId) -> Option<ModelLocation> {
+        self.0.retrieve_model(model_id)
+    }
+}
+
+impl From<&RibbleWhisperSegment> for Segment {
+    fn from(segment: &RibbleWhisperSegment) -> Self {
+        // Whisper timestamps are in centiseconds.
+        Self {
+            text: segment.text().to_string(),
+            start_ms: segment.start_timestamp() * 10,
+            end_ms: segment.end_timestamp() * 10,
+        }
+    }
+}
+
+struct JobEntry {
+    // The submission order.
+    number: u64,
+    job: Mutex<Job>,
+    run_transcription: Arc<AtomicBool>,
+}
+
+impl JobEntry {
+    fn is_finished(&self) -> bool {
+        matches!(
+            self.job.lock().state(),
+            JobState::Completed | JobState::Failed | JobState::Cancelled
+        )
+    }
+}
+
+struct QueuedJob {
+    entry: Arc<JobEntry>,
+    configs: WhisperConfigs,
+    vad: VadKind,
+    source: Source,
+}
+
+impl QueuedJob {
+    fn run<M: ModelRetriever>(self, model_retriever: SharedRetriever<M>) {
+        {
+            let mut job = self.entry.job.lock();
+            // Cancelled while queued.
+            if job.state() != JobState::Queued {
+                return;
+            }
+            job.set_state(JobState::Running);
+        }
+
+        let res = self.transcribe(model_retriever);
+        let cancelled = !self.entry.run_transcription.load(Ordering::Acquire);
+        let mut job = self.entry.job.lock();
+        match res {
+            Ok(segments) => {
+                let transcription: String = segments.iter().map(|segment| segment.text()).collect();
+                job.transcription = transcription.trim().to_string();
+                job.segments = segments.iter().map(Segment::from).collect();
+                if cancelled {
+                    job.set_state(JobState::Cancelled);
+                } else {
+                    job.progress = 100;
+                    job.set_state(JobState::Completed);
+                }
+            }
+            Err(e) => {
+                log_warn!("Transcription job {} failed: {e}", job.id);
+                job.error = e.to_string();
+                job.set_state(if cancelled {
+                    JobState::Cancelled
+                } else {
+                    JobState::Failed
+                });
+            }
+        }
+    }
+
+    fn transcribe<M: ModelRetriever>(
+        &self,
+        model_retriever: SharedRetriever<M>,
+    ) -> Result<Vec<RibbleWhisperSegment>, RibbleWhisperError> {
+        // The audio is normalized to 16 kHz mono while loading.
+        let audio = match &self.source {
+            Source::Path(path) => load_normalized_audio_file_with_abort(
+                path,
+                None::<fn(usize)>,
+                &self.entry.run_transcription,
+            )?,
+            Source::AudioFile(bytes) => load_normalized_audio_bytes_with_abort(
+                bytes.clone(),
+                None::<fn(usize)>,
+                &self.entry.run_transcription,
+            )?,
+        };
+        let builder = OfflineTranscriberBuilder::<Silero, SharedRetriever<M>>::new()
+            .with_configs(self.configs)
+            .with_audio(audio)
+            .with_channel_configurations(AudioChannelConfiguration::Mono)
+            .with_model_retriever(model_retriever);
+        match self.vad {
+            VadKind::Default | VadKind::None => self.run_transcriber(builder),
+            VadKind::Silero => self.run_transcriber(
+                builder.with_voice_activity_detector(Silero::try_new_whisper_offline_default()?),
+            ),
+            VadKind::Webrtc => self.run_transcriber(
+                builder.with_voice_activity_detector(WebRtc::try_new_whisper_offline_default()?),
+            ),
+        }
+    }
+
+    fn run_transcriber<V: VAD<f32>, M: ModelRetriever>(
+        &self,
+        builder: OfflineTranscriberBuilder<V, SharedRetriever<M>>,
+    ) -> Result<Vec<RibbleWhisperSegment>, RibbleWhisperError> {
+        let transcriber = builder.build()?;
+        let progress_entry = Arc::clone(&self.entry);
+        let callbacks = WhisperCallbacks {
+            progress: Some(StaticRibbleWhisperCallback::new(move |percent| {
+                progress_entry.job.lock().progress = percent;
+            })),
+            new_segment: None::<Nop<String>>,
+        };
+        match transcriber
+            .process_segments_with_callbacks(Arc::clone(&self.entry.run_transcription), callbacks)
+        {
+            // Cancelled jobs keep whatever was transcribed before they stopped.
+            Err(RibbleWhisperError::Cancelled) => Ok(transcriber.take_partial_segments()),
+            res => res,
+        }
+    }
+}
+
+/// Queues offline transcription jobs and runs them on a fixed pool of worker threads.
+/// Jobs are kept, (with their results), until they are deleted.
+pub(crate) struct JobManager {
+    jobs: Mutex<HashMap<String, Arc<JobEntry>>>,
+    next_id: AtomicU64,
+    num_workers: usize,
+    // Workers are spawned on the first submission; they exit once the manager is dropped.
+    queue: Mutex<Option<Sender<QueuedJob>>>,
+}
+
+impl JobManager {
+    pub(crate) fn new(num_workers: usize) -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+            num_workers: num_workers.max(1),
+            queue: Mutex::new(None),
+        }
+    }
+
+    fn spawn_workers<M: ModelRetriever + Send + Sync + 'static>(
+        &self,
+        model_retriever: &SharedRetriever<M>,
+    ) -> Sender<QueuedJob> {
+        let (sender, receiver) = channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..self.num_workers {
+            let receiver: Arc<Mutex<Receiver<QueuedJob>>> = Arc::clone(&receiver);
+            let model_retriever = model_retriever.clone();
+            spawn(move || {
+                loop {
+                    // The lock is only held while waiting, so idle workers take turns.
+                    let next = receiver.lock().recv();
+                    let Ok(job) = next else {
+                        return;
+                    };
+                    job.run(model_retriever.clone());
+                }
+            });
+        }
+        sender
+    }
+
+    pub(crate) fn submit<M: ModelRetriever + Send + Sync + 'static>(
+        &self,
+        model_retriever: &SharedRetriever<M>,
+        configs: WhisperConfigs,
+        vad: VadKind,
+        source: Source,
+    ) -> Result<Job, RibbleWhisperError> {
+        let number = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let id = format!("job-{number}");
+        let job = Job {
+            id: id.clone(),
+            state: JobState::Queued.into(),
+            ..Default::default()
+        };
+        let entry = Arc::new(JobEntry {
+            number,
+            job: Mutex::new(job.clone()),
+            run_transcription: Arc::new(AtomicBool::new(true)),
+        });
+        self.jobs.lock().insert(id, Arc::clone(&entry));
+
+        let mut queue = self.queue.lock();
+        let sender = queue.get_or_insert_with(|| self.spawn_workers(model_retriever));
+        sender
+            .send(QueuedJob {
+                entry,
+                configs,
+                vad,
+                source,
+            })
+            .map_err(|_| {
+                RibbleWhisperError::Unknown("Transcription job workers have stopped.".to_string())
+            })?;
+        Ok(job)
+    }
+
+    pub(crate) fn get(&self, id: &str) -> Option<Job> {
+        self.jobs
+            .lock()
+            .get(id)
+            .map(|entry| entry.job.lock().clone())
+    }
+
+    /// Lists the jobs in submission order.
+    pub(crate) fn list(&self) -> Vec<Job> {
+        let mut entries: Vec<Arc<JobEntry>> = self.jobs.lock().values().cloned().collect();
+        entries.sort_by_key(|entry| entry.number);
+        entries
+            .iter()
+            .map(|entry| entry.job.lock().clone())
+            .collect()
+    }
+
+    pub(crate) fn cancel(&self, id: &str) -> Option<Job> {
+        let entry = self.jobs.lock().get(id).cloned()?;
+        entry.run_transcription.store(false, Ordering::Release);
+        let mut job = entry.job.lock();
+        // Running jobs are marked cancelled by their worker once transcription stops.
+        if job.state() == JobState::Queued {
+            job.set_state(JobState::Cancelled);
+        }
+        Some(job.clone())
+    }
+
+    /// Removes a finished job.
+    /// # Returns:
+    /// * Ok(Some(Job)) with the removed job, Ok(None) if there is no such job, or Err if the job
+    ///   has not yet finished.
+    pub(crate) fn delete(&self, id: &str) -> Result<Option<Job>, RibbleWhisperError> {
+        let mut jobs = self.jobs.lock();
+        match jobs.get(id) {
+            None => Ok(None),
+            Some(entry) if !entry.is_finished() => Err(RibbleWhisperError::ParameterError(
+                format!("Job {id} has not finished; cancel it first."),
+            )),
+            Some(_) => Ok(jobs.remove(id).map(|entry| entry.job.lock().clone())),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! A tonic gRPC service for running the crate as a transcription microservice.
+//! Requires the grpc feature flag to be set; the service is generated from
+//! proto/transcription.proto at build time, which requires protoc.
+//!
+//! The service exposes two kinds of transcription:
+//! * StreamTranscription: a bidirectional stream. Clients send a config followed by 16 kHz mono
+//!   audio, and receive snapshots and control phrases as the realtime transcriber produces them.
+//! * Jobs: audio files are queued for offline transcription on a pool of worker threads, and are
+//!   then polled, listed, cancelled and deleted by id.
+//!
+//! Serve it with tonic, e.g:
+//! `Server::builder().add_service(TranscriptionService::new(bank).into_server()).serve(addr).await`
+mod jobs;
+pub mod service;
+
+use std::str::FromStr;
+
+use tonic::Status;
+
+use crate::utils::errors::RibbleWhisperError;
+use crate::whisper::configs::{
+    Language, WhisperConfigs, WhisperRealtimeConfigs, WhisperSamplingStrategy,
+};
+
+/// The generated protobuf messages and tonic service definitions.
+pub mod proto {
+    tonic::include_proto!("ribble_whisper.v1");
+}
+
+impl proto::TranscriptionConfig {
+    // Applies the fields that are set on top of the given defaults.
+    fn apply_to(&self, mut configs: WhisperConfigs) -> Result<WhisperConfigs, RibbleWhisperError> {
+        if let Some(model_id) = self.model_id {
+            configs = configs.with_model_id(Some(model_id));
+        }
+        if let Some(n_threads) = self.n_threads {
+            configs = configs.with_n_threads(n_threads.max(1) as usize);
+        }
+        if let Some(code) = self.language.as_deref() {
+            let language = Language::from_str(&code.to_lowercase()).map_err(|_| {
+                RibbleWhisperError::ParameterError(format!("Unknown language code: {code}"))
+            })?;
+            configs = configs.with_language(Some(language));
+        }
+        if let Some(use_gpu) = self.use_gpu {
+            configs = configs.with_use_gpu(use_gpu);
+        }
+        if let Some(flash_attention) = self.flash_attention {
+            configs = configs.with_flash_attention(flash_attention);
+        }
+        if let Some(beam_size) = self.beam_size {
+            configs = configs.with_sampling_strategy(WhisperSamplingStrategy::BeamSearch {
+                beam_size: beam_size.max(1) as usize,
+                // Whisper's default patience.
+                patience: -1.0,
+            });
+        }
+        Ok(configs.with_translate(self.translate))
+    }
+}
+
+impl TryFrom<Option<&proto::TranscriptionConfig>> for WhisperConfigs {
+    type Error = RibbleWhisperError;
+    fn try_from(configs: Option<&proto::TranscriptionConfig>) -> Result<Self, Self::Error> {
+        let defaults = WhisperConfigs::default();
+        match configs {
+            Some(configs) => configs.apply_to(defaults),
+            None => Ok(defaults),
+        }
+    }
+}
+
+impl TryFrom<&proto::StreamConfig> for WhisperRealtimeConfigs {
+    type Error = RibbleWhisperError;
+    fn try_from(configs: &proto::StreamConfig) -> Result<Self, Self::Error> {
+        let defaults = WhisperRealtimeConfigs::default();
+        let whisper_configs = match configs.whisper.as_ref() {
+            Some(whisper) => whisper.apply_to(*defaults.as_whisper_configs())?,
+            None => *defaults.as_whisper_configs(),
+        };
+        let realtime_configs = match configs.timeout_ms {
+            Some(timeout_ms) => defaults
+                .into_realtime_configs()
+                .with_realtime_timeout(timeout_ms as usize),
+            None => defaults.into_realtime_configs(),
+        };
+        Ok(WhisperRealtimeConfigs::new()
+            .with_whisper_configs(whisper_configs)
+            .with_realtime_configs(realtime_configs))
+    }
+}
+
+impl From<RibbleWhisperError> for Status {
+    fn from(error: RibbleWhisperError) -> Self {
+        let message = error.to_string();
+        match error {
+            RibbleWhisperError::ParameterError(_)
+            | RibbleWhisperError::MissingField { .. }
+            | RibbleWhisperError::AudioFormat { .. }
+            | RibbleWhisperError::DecodeError(_) => Status::invalid_argument(message),
+            RibbleWhisperError::ModelNotFound { .. } => Status::not_found(message),
+            RibbleWhisperError::Cancelled => Status::cancelled(message),
+            RibbleWhisperError::DeviceError(_) | RibbleWhisperError::Gpu { .. } => {
+                Status::unavailable(message)
+            }
+            _ => Status::internal(message),
+        }
+    }
+}
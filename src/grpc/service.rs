@@ -0,0 +1,297 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::audio::audio_ring_buffer::AudioRingBuffer;
+use crate::grpc::jobs::{JobManager, SharedRetriever};
+use crate::grpc::proto::submit_job_request::Source;
+use crate::grpc::proto::transcription_server::{Transcription, TranscriptionServer};
+use crate::grpc::proto::{
+    stream_request, stream_response, Job, JobRequest, ListJobsRequest, ListJobsResponse, Snapshot,
+    StreamRequest, StreamResponse, SubmitJobRequest, VadKind,
+};
+use crate::transcriber::realtime_transcriber::RealtimeTranscriberBuilder;
+use crate::transcriber::vad::{Silero, WebRtc, VAD};
+use crate::transcriber::{SnapshotAssembler, WhisperOutput};
+use crate::utils::errors::RibbleWhisperError;
+use crate::utils::logging::log_warn;
+use crate::utils::thread::spawn_with_configs;
+use crate::whisper::configs::{WhisperConfigs, WhisperRealtimeConfigs};
+use crate::whisper::model::ModelRetriever;
+
+// Outputs are buffered so that a slow client doesn't immediately stall the transcriber.
+const OUTPUT_CHANNEL_SIZE: usize = 64;
+const RESPONSE_CHANNEL_SIZE: usize = 64;
+
+type RealtimeResult = Result<String, RibbleWhisperError>;
+
+/// Implements the generated Transcription service on top of the realtime and offline
+/// transcribers. Models are retrieved by id from the given [ModelRetriever], (e.g. a
+/// [crate::whisper::model::DefaultModelBank] or a [crate::whisper::model_cache::SharedModelCache]
+/// to keep hot models in memory across requests).
+///
+/// Each stream runs its own realtime transcriber on a dedicated thread; offline jobs are queued
+/// and run on a fixed number of worker threads, (see: [TranscriptionService::with_job_workers]).
+pub struct TranscriptionService<M: ModelRetriever> {
+    model_retriever: SharedRetriever<M>,
+    jobs: JobManager,
+    allow_file_paths: bool,
+}
+
+impl<M: ModelRetriever + Send + Sync + 'static> TranscriptionService<M> {
+    pub fn new(model_retriever: M) -> Self {
+        Self {
+            model_retriever: SharedRetriever(Arc::new(model_retriever)),
+            jobs: JobManager::new(1),
+            allow_file_paths: false,
+        }
+    }
+
+    /// Sets how many offline jobs are transcribed at once. Defaults to 1.
+    pub fn with_job_workers(mut self, num_workers: usize) -> Self {
+        self.jobs = JobManager::new(num_workers);
+        self
+    }
+
+    /// Allows jobs to transcribe files on the server's file system by path.
+    /// This is disabled by default, since it lets clients read any file the server can access.
+    pub fn with_allow_file_paths(mut self, allow_file_paths: bool) -> Self {
+        self.allow_file_paths = allow_file_paths;
+        self
+    }
+
+    /// Wraps the service in its tonic server, ready to be added to a [tonic::transport::Server].
+    pub fn into_server(self) -> TranscriptionServer<Self> {
+        TranscriptionServer::new(self)
+    }
+}
+
+// Owns everything a realtime stream needs to build its transcriber.
+struct RealtimeStream<M: ModelRetriever> {
+    configs: WhisperRealtimeConfigs,
+    audio_buffer: AudioRingBuffer<f32>,
+    model_retriever: SharedRetriever<M>,
+    output_sender: mpsc::Sender<WhisperOutput>,
+    run_transcription: Arc<AtomicBool>,
+    slow_stop: Arc<AtomicBool>,
+}
+
+impl<M: ModelRetriever + Send + Sync + 'static> RealtimeStream<M> {
+    // The output channel closes once the transcriber thread finishes; the final transcription is
+    // sent separately, so that it is always forwarded after the last snapshot.
+    fn spawn<V: VAD<f32> + Send + Sync + 'static>(
+        self,
+        vad: V,
+    ) -> Result<oneshot::Receiver<RealtimeResult>, RibbleWhisperError> {
+        let thread_configs = self.configs.thread_configs();
+        let (transcriber, _handle) = RealtimeTranscriberBuilder::<V, SharedRetriever<M>>::new()
+            .with_configs(self.configs)
+            .with_audio_buffer(&self.audio_buffer)
+            .with_output_sender(self.output_sender)
+            .with_model_retriever(self.model_retriever)
+            .with_voice_activity_detector(vad)
+            .build()?;
+        let (result_sender, result_receiver) = oneshot::channel();
+        let run_transcription = self.run_transcription;
+        let slow_stop = self.slow_stop;
+        spawn_with_configs("ribble-whisper-grpc-stream", thread_configs, move || {
+            let _ = result_sender.send(transcriber.run_stream(run_transcription, slow_stop));
+        })?;
+        Ok(result_receiver)
+    }
+}
+
+// Pushes incoming audio until the client closes the request stream, then stops transcription.
+async fn receive_audio(
+    mut requests: Streaming<StreamRequest>,
+    audio_buffer: AudioRingBuffer<f32>,
+    run_transcription: Arc<AtomicBool>,
+    slow_stop: Arc<AtomicBool>,
+) {
+    while run_transcription.load(Ordering::Acquire) {
+        match requests.message().await {
+            Ok(Some(StreamRequest {
+                request: Some(stream_request::Request::Audio(chunk)),
+            })) => audio_buffer.push_audio(&chunk.samples),
+            // Configs after the first request are ignored.
+            Ok(Some(_)) => {}
+            Ok(None) => break,
+            Err(_e) => {
+                log_warn!("Realtime stream closed with an error: {_e}");
+                // The client is gone; there's no one to send the remaining audio to.
+                slow_stop.store(false, Ordering::Release);
+                break;
+            }
+        }
+    }
+    run_transcription.store(false, Ordering::Release);
+}
+
+// Forwards the transcriber's outputs as snapshots, then sends the final transcription.
+async fn forward_outputs(
+    mut outputs: mpsc::Receiver<WhisperOutput>,
+    result: oneshot::Receiver<RealtimeResult>,
+    responses: mpsc::Sender<Result<StreamResponse, Status>>,
+    run_transcription: Arc<AtomicBool>,
+) {
+    let mut assembler = SnapshotAssembler::new();
+    while let Some(output) = outputs.recv().await {
+        let response = match &output {
+            WhisperOutput::ControlPhrase(phrase) => {
+                stream_response::Response::ControlPhrase(phrase.to_string())
+            }
+            _ => match assembler.push(&output) {
+                Some(snapshot) => stream_response::Response::Snapshot(Snapshot {
+                    confirmed: snapshot.confirmed().to_string(),
+                    working: snapshot
+                        .string_segments()
+                        .iter()
+                        .map(|segment| segment.to_string())
+                        .collect(),
+                }),
+                None => continue,
+            },
+        };
+        // Keep draining if the client has gone away, so that the transcriber isn't left blocked on
+        // a full channel while it stops.
+        let sent = responses
+            .send(Ok(StreamResponse {
+                response: Some(response),
+            }))
+            .await;
+        if sent.is_err() {
+            run_transcription.store(false, Ordering::Release);
+        }
+    }
+
+    let final_response = match result.await {
+        Ok(Ok(transcription)) => Ok(StreamResponse {
+            response: Some(stream_response::Response::FinalTranscription(transcription)),
+        }),
+        Ok(Err(e)) => Err(Status::from(e)),
+        Err(_) => Err(Status::internal("Realtime transcription thread panicked.")),
+    };
+    let _ = responses.send(final_response).await;
+}
+
+fn job_not_found(id: &str) -> Status {
+    Status::not_found(format!("No such job: {id}"))
+}
+
+#[tonic::async_trait]
+impl<M: ModelRetriever + Send + Sync + 'static> Transcription for TranscriptionService<M> {
+    type StreamTranscriptionStream = ReceiverStream<Result<StreamResponse, Status>>;
+
+    async fn stream_transcription(
+        &self,
+        request: Request<Streaming<StreamRequest>>,
+    ) -> Result<Response<Self::StreamTranscriptionStream>, Status> {
+        let mut requests = request.into_inner();
+        let config = match requests.message().await? {
+            Some(StreamRequest {
+                request: Some(stream_request::Request::Config(config)),
+            }) => config,
+            _ => {
+                return Err(Status::invalid_argument(
+                    "The first request must carry a StreamConfig.",
+                ));
+            }
+        };
+
+        let audio_buffer = AudioRingBuffer::<f32>::default();
+        let run_transcription = Arc::new(AtomicBool::new(true));
+        let slow_stop = Arc::new(AtomicBool::new(config.slow_stop));
+        let (output_sender, output_receiver) = mpsc::channel(OUTPUT_CHANNEL_SIZE);
+        let stream = RealtimeStream {
+            configs: WhisperRealtimeConfigs::try_from(&config)?,
+            audio_buffer: audio_buffer.clone(),
+            model_retriever: self.model_retriever.clone(),
+            output_sender,
+            run_transcription: Arc::clone(&run_transcription),
+            slow_stop: Arc::clone(&slow_stop),
+        };
+        let result = match config.vad() {
+            VadKind::Default | VadKind::Silero => {
+                stream.spawn(Silero::try_new_whisper_realtime_default()?)
+            }
+            VadKind::Webrtc => stream.spawn(WebRtc::try_new_whisper_realtime_default()?),
+            VadKind::None => {
+                return Err(Status::invalid_argument(
+                    "Realtime streams require a voice activity detector.",
+                ));
+            }
+        }?;
+
+        let (response_sender, response_receiver) = mpsc::channel(RESPONSE_CHANNEL_SIZE);
+        tokio::spawn(receive_audio(
+            requests,
+            audio_buffer,
+            Arc::clone(&run_transcription),
+            slow_stop,
+        ));
+        tokio::spawn(forward_outputs(
+            output_receiver,
+            result,
+            response_sender,
+            run_transcription,
+        ));
+        Ok(Response::new(ReceiverStream::new(response_receiver)))
+    }
+
+    async fn submit_job(
+        &self,
+        request: Request<SubmitJobRequest>,
+    ) -> Result<Response<Job>, Status> {
+        let request = request.into_inner();
+        let configs = WhisperConfigs::try_from(request.whisper.as_ref())?;
+        let vad = request.vad();
+        let source = request
+            .source
+            .ok_or_else(|| Status::invalid_argument("A job requires an audio source."))?;
+        if matches!(source, Source::Path(_)) && !self.allow_file_paths {
+            return Err(Status::permission_denied(
+                "Transcribing files by path is disabled on this server.",
+            ));
+        }
+        let job = self
+            .jobs
+            .submit(&self.model_retriever, configs, vad, source)?;
+        Ok(Response::new(job))
+    }
+
+    async fn get_job(&self, request: Request<JobRequest>) -> Result<Response<Job>, Status> {
+        let id = request.into_inner().id;
+        self.jobs
+            .get(&id)
+            .map(Response::new)
+            .ok_or_else(|| job_not_found(&id))
+    }
+
+    async fn list_jobs(
+        &self,
+        _request: Request<ListJobsRequest>,
+    ) -> Result<Response<ListJobsResponse>, Status> {
+        Ok(Response::new(ListJobsResponse {
+            jobs: self.jobs.list(),
+        }))
+    }
+
+    async fn cancel_job(&self, request: Request<JobRequest>) -> Result<Response<Job>, Status> {
+        let id = request.into_inner().id;
+        self.jobs
+            .cancel(&id)
+            .map(Response::new)
+            .ok_or_else(|| job_not_found(&id))
+    }
+
+    async fn delete_job(&self, request: Request<JobRequest>) -> Result<Response<Job>, Status> {
+        let id = request.into_inner().id;
+        self.jobs
+            .delete(&id)?
+            .map(Response::new)
+            .ok_or_else(|| job_not_found(&id))
+    }
+}
@@ -1,10 +1,23 @@
 #![doc = include_str!("../README.md")]
 pub mod audio;
+#[cfg(all(feature = "bench", not(target_arch = "wasm32")))]
+pub mod bench;
 #[cfg(feature = "downloader")]
 pub mod downloader;
+pub mod export;
+#[cfg(feature = "uniffi")]
+pub mod ffi;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod postprocess;
+#[cfg(all(feature = "testing", not(target_arch = "wasm32")))]
+pub mod testing;
 pub mod transcriber;
 pub mod utils;
 pub mod whisper;
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
 // Export sdl2 when using SDL as the audio backend.
 #[cfg(feature = "sdl2")]
 pub use sdl2;
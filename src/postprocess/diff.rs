@@ -0,0 +1,453 @@
+//! Word-level alignment of two transcripts of the same audio, (e.g. realtime against offline, or
+//! a small model against a larger one), for reconciling re-transcriptions.
+//!
+//! [TranscriptDiffer] aligns the words of a reference and a hypothesis transcript with the
+//! fewest edits, and returns each word as a [WordEdit]: a match, a substitution, an insertion,
+//! (only in the hypothesis), or a deletion, (only in the reference). Words keep their timestamps
+//! when the transcripts were timestamped, so changes can be located in the audio.
+
+use crate::transcriber::RibbleWhisperSegment;
+
+/// A word of a transcript.
+/// Timestamps are measured in centiseconds, (like [RibbleWhisperSegment]), and are only known
+/// for words taken from segments, (see: [words_from_segments]). Words partway through a segment
+/// have their timestamps interpolated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimedWord {
+    text: String,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+}
+
+impl TimedWord {
+    pub fn new(text: impl Into<String>, start_time: Option<i64>, end_time: Option<i64>) -> Self {
+        Self {
+            text: text.into(),
+            start_time,
+            end_time,
+        }
+    }
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    pub fn start_timestamp(&self) -> Option<i64> {
+        self.start_time
+    }
+    pub fn end_timestamp(&self) -> Option<i64> {
+        self.end_time
+    }
+}
+
+impl std::fmt::Display for TimedWord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Splits untimed text into words.
+pub fn words_from_text(text: &str) -> Vec<TimedWord> {
+    text.split_whitespace()
+        .map(|word| TimedWord::new(word, None, None))
+        .collect()
+}
+
+/// Splits segments into words, interpolating each word's timestamps by its position within its
+/// segment.
+pub fn words_from_segments(segments: &[RibbleWhisperSegment]) -> Vec<TimedWord> {
+    let mut words = vec![];
+    for segment in segments {
+        let text = segment.text().trim();
+        let duration = (segment.end_time - segment.start_time).max(0);
+        let time_at = |offset: usize| {
+            let fraction = offset as f64 / text.len().max(1) as f64;
+            segment.start_time + (duration as f64 * fraction).round() as i64
+        };
+        let mut search_from = 0;
+        for word in text.split_whitespace() {
+            let start = search_from + text[search_from..].find(word).unwrap_or(0);
+            let end = start + word.len();
+            search_from = end;
+            words.push(TimedWord::new(
+                word,
+                Some(time_at(start)),
+                Some(time_at(end)),
+            ));
+        }
+    }
+    words
+}
+
+/// A word of an aligned pair of transcripts, (see: [TranscriptDiff]).
+#[derive(Clone, Debug, PartialEq)]
+pub enum WordEdit {
+    /// The word is in both transcripts.
+    Equal {
+        reference: TimedWord,
+        hypothesis: TimedWord,
+    },
+    /// The reference word was transcribed as a different word.
+    Substitution {
+        reference: TimedWord,
+        hypothesis: TimedWord,
+    },
+    /// The word is only in the hypothesis.
+    Insertion(TimedWord),
+    /// The word is only in the reference.
+    Deletion(TimedWord),
+}
+
+impl WordEdit {
+    /// The reference word, if any.
+    pub fn reference(&self) -> Option<&TimedWord> {
+        match self {
+            Self::Equal { reference, .. } | Self::Substitution { reference, .. } => Some(reference),
+            Self::Deletion(reference) => Some(reference),
+            Self::Insertion(_) => None,
+        }
+    }
+
+    /// The hypothesis word, if any.
+    pub fn hypothesis(&self) -> Option<&TimedWord> {
+        match self {
+            Self::Equal { hypothesis, .. } | Self::Substitution { hypothesis, .. } => {
+                Some(hypothesis)
+            }
+            Self::Insertion(hypothesis) => Some(hypothesis),
+            Self::Deletion(_) => None,
+        }
+    }
+
+    /// Returns true for anything other than [WordEdit::Equal].
+    pub fn is_change(&self) -> bool {
+        !matches!(self, Self::Equal { .. })
+    }
+
+    /// The edit's start timestamp, from whichever transcript has one, (reference first).
+    pub fn start_timestamp(&self) -> Option<i64> {
+        self.reference()
+            .and_then(TimedWord::start_timestamp)
+            .or_else(|| self.hypothesis().and_then(TimedWord::start_timestamp))
+    }
+
+    /// The edit's end timestamp, from whichever transcript has one, (reference first).
+    pub fn end_timestamp(&self) -> Option<i64> {
+        self.reference()
+            .and_then(TimedWord::end_timestamp)
+            .or_else(|| self.hypothesis().and_then(TimedWord::end_timestamp))
+    }
+}
+
+impl std::fmt::Display for WordEdit {
+    /// Displays as "word", "-word", "+word", or "reference->hypothesis".
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Equal { reference, .. } => write!(f, "{reference}"),
+            Self::Substitution {
+                reference,
+                hypothesis,
+            } => write!(f, "{reference}->{hypothesis}"),
+            Self::Insertion(hypothesis) => write!(f, "+{hypothesis}"),
+            Self::Deletion(reference) => write!(f, "-{reference}"),
+        }
+    }
+}
+
+/// Which transcript to take changed words from when merging, (see: [TranscriptDiff::merge]).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum MergePreference {
+    #[default]
+    Reference,
+    Hypothesis,
+}
+
+/// The word-level alignment of two transcripts, (see: [TranscriptDiffer]).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TranscriptDiff {
+    edits: Vec<WordEdit>,
+}
+
+impl TranscriptDiff {
+    /// Every word of both transcripts, in order.
+    pub fn edits(&self) -> &[WordEdit] {
+        &self.edits
+    }
+
+    /// The edits that are not matches.
+    pub fn changes(&self) -> impl Iterator<Item = &WordEdit> {
+        self.edits.iter().filter(|edit| edit.is_change())
+    }
+
+    pub fn matches(&self) -> usize {
+        self.count(|edit| matches!(edit, WordEdit::Equal { .. }))
+    }
+    pub fn substitutions(&self) -> usize {
+        self.count(|edit| matches!(edit, WordEdit::Substitution { .. }))
+    }
+    pub fn insertions(&self) -> usize {
+        self.count(|edit| matches!(edit, WordEdit::Insertion(_)))
+    }
+    pub fn deletions(&self) -> usize {
+        self.count(|edit| matches!(edit, WordEdit::Deletion(_)))
+    }
+
+    /// Returns true if the transcripts have the same words, (as compared by the differ).
+    pub fn is_identical(&self) -> bool {
+        self.changes().next().is_none()
+    }
+
+    /// The number of words in the reference transcript.
+    pub fn reference_len(&self) -> usize {
+        self.edits.iter().filter_map(WordEdit::reference).count()
+    }
+
+    /// The number of words in the hypothesis transcript.
+    pub fn hypothesis_len(&self) -> usize {
+        self.edits.iter().filter_map(WordEdit::hypothesis).count()
+    }
+
+    /// Reconciles the transcripts into one, taking changed words from the preferred transcript.
+    /// Matched words are taken from the reference. Words keep their own timestamps, falling back
+    /// to those of the word they were aligned with.
+    pub fn merge(&self, preference: MergePreference) -> Vec<TimedWord> {
+        self.edits
+            .iter()
+            .filter_map(|edit| {
+                let word = match (edit, preference) {
+                    (WordEdit::Equal { reference, .. }, _) => reference,
+                    (WordEdit::Substitution { reference, .. }, MergePreference::Reference) => {
+                        reference
+                    }
+                    (WordEdit::Substitution { hypothesis, .. }, MergePreference::Hypothesis) => {
+                        hypothesis
+                    }
+                    (WordEdit::Deletion(reference), MergePreference::Reference) => reference,
+                    (WordEdit::Insertion(hypothesis), MergePreference::Hypothesis) => hypothesis,
+                    _ => return None,
+                };
+                Some(TimedWord::new(
+                    word.text(),
+                    word.start_time.or(edit.start_timestamp()),
+                    word.end_time.or(edit.end_timestamp()),
+                ))
+            })
+            .collect()
+    }
+
+    fn count(&self, predicate: impl Fn(&WordEdit) -> bool) -> usize {
+        self.edits.iter().filter(|edit| predicate(edit)).count()
+    }
+}
+
+impl std::fmt::Display for TranscriptDiff {
+    /// Displays the edits separated by spaces, (see: [WordEdit]'s Display).
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, edit) in self.edits.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{edit}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An alignment operation, (see: [align]).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum AlignOp {
+    Equal,
+    Substitution,
+    Insertion,
+    Deletion,
+}
+
+/// Aligns two token sequences with the fewest substitutions, insertions and deletions,
+/// (Levenshtein). A common prefix and suffix are matched before the quadratic alignment, so
+/// mostly-similar sequences stay cheap.
+pub(crate) fn align<T>(
+    reference: &[T],
+    hypothesis: &[T],
+    eq: impl Fn(&T, &T) -> bool,
+) -> Vec<AlignOp> {
+    let prefix = reference
+        .iter()
+        .zip(hypothesis)
+        .take_while(|(a, b)| eq(a, b))
+        .count();
+    let suffix = reference[prefix..]
+        .iter()
+        .rev()
+        .zip(hypothesis[prefix..].iter().rev())
+        .take_while(|(a, b)| eq(a, b))
+        .count();
+    let reference_middle = &reference[prefix..reference.len() - suffix];
+    let hypothesis_middle = &hypothesis[prefix..hypothesis.len() - suffix];
+
+    let rows = reference_middle.len() + 1;
+    let columns = hypothesis_middle.len() + 1;
+    let mut costs = vec![0u32; rows * columns];
+    for row in 0..rows {
+        costs[row * columns] = row as u32;
+    }
+    for (column, cost) in costs[..columns].iter_mut().enumerate() {
+        *cost = column as u32;
+    }
+    for row in 1..rows {
+        for column in 1..columns {
+            let substitution = u32::from(!eq(
+                &reference_middle[row - 1],
+                &hypothesis_middle[column - 1],
+            ));
+            costs[row * columns + column] = (costs[(row - 1) * columns + column - 1]
+                + substitution)
+                .min(costs[(row - 1) * columns + column] + 1)
+                .min(costs[row * columns + column - 1] + 1);
+        }
+    }
+
+    let mut middle = Vec::with_capacity(rows.max(columns));
+    let (mut row, mut column) = (rows - 1, columns - 1);
+    while row > 0 || column > 0 {
+        let cost = costs[row * columns + column];
+        if row > 0 && column > 0 {
+            let same = eq(&reference_middle[row - 1], &hypothesis_middle[column - 1]);
+            let diagonal = costs[(row - 1) * columns + column - 1] + u32::from(!same);
+            if cost == diagonal {
+                middle.push(if same {
+                    AlignOp::Equal
+                } else {
+                    AlignOp::Substitution
+                });
+                row -= 1;
+                column -= 1;
+                continue;
+            }
+        }
+        if row > 0 && cost == costs[(row - 1) * columns + column] + 1 {
+            middle.push(AlignOp::Deletion);
+            row -= 1;
+        } else {
+            middle.push(AlignOp::Insertion);
+            column -= 1;
+        }
+    }
+    middle.reverse();
+
+    let mut ops = vec![AlignOp::Equal; prefix];
+    ops.extend(middle);
+    ops.extend(std::iter::repeat_n(AlignOp::Equal, suffix));
+    ops
+}
+
+/// Aligns transcripts word by word, (see: [crate::postprocess::diff]).
+///
+/// By default words are compared ignoring case and surrounding punctuation, so "Hello," and
+/// "hello" match; the edits keep the words as they were written.
+///
+/// Alignment takes time and memory proportional to the product of the lengths of the differing
+/// stretch of the transcripts, (matching starts and ends are skipped). Diff long recordings in
+/// chunks, (e.g. by paragraph), rather than all at once.
+#[derive(Copy, Clone, Debug)]
+pub struct TranscriptDiffer {
+    case_sensitive: bool,
+    punctuation_sensitive: bool,
+}
+
+impl TranscriptDiffer {
+    pub fn new() -> Self {
+        Self {
+            case_sensitive: false,
+            punctuation_sensitive: false,
+        }
+    }
+
+    /// Toggles treating words that differ only in case as different.
+    pub fn with_case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Toggles treating words that differ only in leading or trailing punctuation as different.
+    pub fn with_punctuation_sensitive(mut self, punctuation_sensitive: bool) -> Self {
+        self.punctuation_sensitive = punctuation_sensitive;
+        self
+    }
+
+    pub fn case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    pub fn punctuation_sensitive(&self) -> bool {
+        self.punctuation_sensitive
+    }
+
+    /// Diffs two untimed transcripts.
+    pub fn diff_text(&self, reference: &str, hypothesis: &str) -> TranscriptDiff {
+        self.diff(words_from_text(reference), words_from_text(hypothesis))
+    }
+
+    /// Diffs two timestamped transcripts.
+    pub fn diff_segments(
+        &self,
+        reference: &[RibbleWhisperSegment],
+        hypothesis: &[RibbleWhisperSegment],
+    ) -> TranscriptDiff {
+        self.diff(
+            words_from_segments(reference),
+            words_from_segments(hypothesis),
+        )
+    }
+
+    /// Diffs two lists of words.
+    pub fn diff(&self, reference: Vec<TimedWord>, hypothesis: Vec<TimedWord>) -> TranscriptDiff {
+        let reference_keys: Vec<String> = reference.iter().map(|word| self.key(word)).collect();
+        let hypothesis_keys: Vec<String> = hypothesis.iter().map(|word| self.key(word)).collect();
+        let ops = align(&reference_keys, &hypothesis_keys, |a, b| a == b);
+
+        let mut reference = reference.into_iter();
+        let mut hypothesis = hypothesis.into_iter();
+        let edits = ops
+            .into_iter()
+            .filter_map(|op| {
+                Some(match op {
+                    AlignOp::Equal => WordEdit::Equal {
+                        reference: reference.next()?,
+                        hypothesis: hypothesis.next()?,
+                    },
+                    AlignOp::Substitution => WordEdit::Substitution {
+                        reference: reference.next()?,
+                        hypothesis: hypothesis.next()?,
+                    },
+                    AlignOp::Insertion => WordEdit::Insertion(hypothesis.next()?),
+                    AlignOp::Deletion => WordEdit::Deletion(reference.next()?),
+                })
+            })
+            .collect();
+        TranscriptDiff { edits }
+    }
+
+    // The form of a word that is compared.
+    fn key(&self, word: &TimedWord) -> String {
+        let text = if self.punctuation_sensitive {
+            word.text()
+        } else {
+            let trimmed = word.text().trim_matches(|c: char| !c.is_alphanumeric());
+            // Words that are only punctuation, (e.g. "-"), are compared as-is.
+            if trimmed.is_empty() {
+                word.text()
+            } else {
+                trimmed
+            }
+        };
+        if self.case_sensitive {
+            text.to_string()
+        } else {
+            text.to_lowercase()
+        }
+    }
+}
+
+impl Default for TranscriptDiffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
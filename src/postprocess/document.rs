@@ -0,0 +1,356 @@
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use crate::export::jsonl::SegmentRecord;
+use crate::transcriber::{RibbleWhisperSegment, TranscriptionSnapshot};
+
+/// A pause this long between segments ends a sentence, even without punctuation.
+pub const DEFAULT_SENTENCE_PAUSE: Duration = Duration::from_millis(1500);
+/// A pause this long between segments starts a new paragraph.
+pub const DEFAULT_PARAGRAPH_PAUSE: Duration = Duration::from_millis(3000);
+
+// Lower-cased abbreviations, (without their final period), that do not end a sentence.
+const ABBREVIATIONS: [&str; 12] = [
+    "mr", "mrs", "ms", "dr", "prof", "st", "vs", "etc", "e.g", "i.e", "jr", "sr",
+];
+
+/// A sentence of a [TranscriptDocument].
+/// Timestamps are measured in centiseconds, (like [RibbleWhisperSegment]), and are only known
+/// when the document was built from segments. Sentences that start or end partway through a
+/// segment have their timestamps interpolated.
+///
+/// Speakers and confidence are only known when the document was built from records that have
+/// them, (see: [DocumentSegmenter::segment_records]).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Sentence {
+    text: String,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    speaker: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    confidence: Option<f32>,
+}
+
+impl Sentence {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    pub fn start_timestamp(&self) -> Option<i64> {
+        self.start_time
+    }
+    pub fn end_timestamp(&self) -> Option<i64> {
+        self.end_time
+    }
+    pub fn speaker(&self) -> Option<&str> {
+        self.speaker.as_deref()
+    }
+    /// The mean confidence of the sentence's words, (0.0 - 1.0).
+    pub fn confidence(&self) -> Option<f32> {
+        self.confidence
+    }
+}
+
+/// A paragraph of a [TranscriptDocument]. A change of speaker always starts a new paragraph.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Paragraph {
+    sentences: Vec<Sentence>,
+}
+
+impl Paragraph {
+    pub fn sentences(&self) -> &[Sentence] {
+        &self.sentences
+    }
+    pub fn start_timestamp(&self) -> Option<i64> {
+        self.sentences.first().and_then(Sentence::start_timestamp)
+    }
+    pub fn end_timestamp(&self) -> Option<i64> {
+        self.sentences.last().and_then(Sentence::end_timestamp)
+    }
+    pub fn speaker(&self) -> Option<&str> {
+        self.sentences.first().and_then(Sentence::speaker)
+    }
+    /// The sentences of the paragraph, joined by spaces.
+    pub fn text(&self) -> String {
+        self.sentences
+            .iter()
+            .map(Sentence::text)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// A transcript split into paragraphs and sentences, (see: [DocumentSegmenter]).
+/// Displays as its paragraphs separated by blank lines.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct TranscriptDocument {
+    paragraphs: Vec<Paragraph>,
+}
+
+impl TranscriptDocument {
+    pub fn paragraphs(&self) -> &[Paragraph] {
+        &self.paragraphs
+    }
+
+    pub fn sentences(&self) -> impl Iterator<Item = &Sentence> {
+        self.paragraphs
+            .iter()
+            .flat_map(|paragraph| paragraph.sentences.iter())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paragraphs.is_empty()
+    }
+}
+
+impl std::fmt::Display for TranscriptDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, paragraph) in self.paragraphs.iter().enumerate() {
+            if i > 0 {
+                write!(f, "\n\n")?;
+            }
+            write!(f, "{}", paragraph.text())?;
+        }
+        Ok(())
+    }
+}
+
+// Returns true if the word ends a sentence, (e.g. "done." or "really?\"").
+fn ends_sentence(word: &str) -> bool {
+    let trimmed = word.trim_end_matches(['"', '\'', ')', ']']);
+    if trimmed.ends_with(['!', '?']) {
+        return true;
+    }
+    let Some(stem) = trimmed.strip_suffix('.') else {
+        return false;
+    };
+    !ABBREVIATIONS.contains(
+        &stem
+            .trim_start_matches(['"', '\'', '(', '['])
+            .to_lowercase()
+            .as_str(),
+    )
+}
+
+// A span of timed text to be split into sentences, (e.g. a segment or record).
+struct TimedText<'a> {
+    text: &'a str,
+    start_time: i64,
+    end_time: i64,
+    speaker: Option<&'a str>,
+    confidence: Option<f32>,
+}
+
+impl<'a> From<&'a RibbleWhisperSegment> for TimedText<'a> {
+    fn from(segment: &'a RibbleWhisperSegment) -> Self {
+        Self {
+            text: segment.text(),
+            start_time: segment.start_time,
+            end_time: segment.end_time,
+            speaker: None,
+            confidence: segment.confidence,
+        }
+    }
+}
+
+// Accumulates sentences and paragraphs while the document is being built.
+#[derive(Default)]
+struct DocumentState {
+    paragraphs: Vec<Paragraph>,
+    paragraph: Vec<Sentence>,
+    sentence: Vec<String>,
+    sentence_start: Option<i64>,
+    sentence_end: Option<i64>,
+    sentence_confidences: Vec<f32>,
+    speaker: Option<String>,
+}
+
+impl DocumentState {
+    fn push_word(&mut self, word: &str, start: Option<i64>, end: Option<i64>) {
+        if self.sentence.is_empty() {
+            self.sentence_start = start;
+        }
+        self.sentence.push(word.to_string());
+        self.sentence_end = end;
+    }
+
+    fn end_sentence(&mut self, max_sentences: Option<usize>) {
+        if self.sentence.is_empty() {
+            return;
+        }
+        let confidences = std::mem::take(&mut self.sentence_confidences);
+        let confidence = (!confidences.is_empty())
+            .then(|| confidences.iter().sum::<f32>() / confidences.len() as f32);
+        self.paragraph.push(Sentence {
+            text: self.sentence.join(" "),
+            start_time: self.sentence_start.take(),
+            end_time: self.sentence_end.take(),
+            speaker: self.speaker.clone(),
+            confidence,
+        });
+        self.sentence.clear();
+        if max_sentences.is_some_and(|max| self.paragraph.len() >= max) {
+            self.end_paragraph(max_sentences);
+        }
+    }
+
+    fn end_paragraph(&mut self, max_sentences: Option<usize>) {
+        self.end_sentence(max_sentences);
+        if !self.paragraph.is_empty() {
+            let sentences = std::mem::take(&mut self.paragraph);
+            self.paragraphs.push(Paragraph { sentences });
+        }
+    }
+}
+
+/// Splits transcribed text into a [TranscriptDocument] of sentences and paragraphs.
+///
+/// Sentences are split on sentence-ending punctuation, (skipping common abbreviations,
+/// e.g. "Dr."). When built from segments, pauses between segments also end sentences and
+/// paragraphs, (see: [DEFAULT_SENTENCE_PAUSE] and [DEFAULT_PARAGRAPH_PAUSE]).
+///
+/// Documents built from records with speakers start a new paragraph at each change of speaker.
+///
+/// NOTE: [TranscriptionSnapshot]s from the realtime transcriber do not carry timestamps, so
+/// documents built from them are split on punctuation and sentence count alone.
+#[derive(Copy, Clone, Debug)]
+pub struct DocumentSegmenter {
+    sentence_pause: Duration,
+    paragraph_pause: Duration,
+    max_sentences: Option<usize>,
+}
+
+impl DocumentSegmenter {
+    pub fn new() -> Self {
+        Self {
+            sentence_pause: DEFAULT_SENTENCE_PAUSE,
+            paragraph_pause: DEFAULT_PARAGRAPH_PAUSE,
+            max_sentences: None,
+        }
+    }
+
+    /// Sets the pause between segments that ends a sentence.
+    pub fn with_sentence_pause(mut self, pause: Duration) -> Self {
+        self.sentence_pause = pause;
+        self
+    }
+
+    /// Sets the pause between segments that starts a new paragraph.
+    pub fn with_paragraph_pause(mut self, pause: Duration) -> Self {
+        self.paragraph_pause = pause;
+        self
+    }
+
+    /// Sets the maximum number of sentences in a paragraph. This is unlimited by default, but
+    /// without timestamps, (e.g. realtime snapshots), it is the only way to break paragraphs.
+    pub fn with_max_sentences(mut self, max_sentences: usize) -> Self {
+        self.max_sentences = Some(max_sentences.max(1));
+        self
+    }
+
+    pub fn sentence_pause(&self) -> Duration {
+        self.sentence_pause
+    }
+    pub fn paragraph_pause(&self) -> Duration {
+        self.paragraph_pause
+    }
+    pub fn max_sentences(&self) -> Option<usize> {
+        self.max_sentences
+    }
+
+    /// Splits untimed text, using punctuation only.
+    pub fn segment_text(&self, text: &str) -> TranscriptDocument {
+        let mut state = DocumentState::default();
+        for word in text.split_whitespace() {
+            state.push_word(word, None, None);
+            if ends_sentence(word) {
+                state.end_sentence(self.max_sentences);
+            }
+        }
+        state.end_paragraph(self.max_sentences);
+        TranscriptDocument {
+            paragraphs: state.paragraphs,
+        }
+    }
+
+    /// Splits the confirmed text of a realtime snapshot, (see: [DocumentSegmenter::segment_text]).
+    /// The working segments are left out, since they may still change.
+    pub fn segment_snapshot(&self, snapshot: &TranscriptionSnapshot) -> TranscriptDocument {
+        self.segment_text(snapshot.confirmed())
+    }
+
+    /// Splits timestamped segments, using both punctuation and the pauses between segments.
+    pub fn segment(&self, segments: &[RibbleWhisperSegment]) -> TranscriptDocument {
+        self.segment_timed(segments.iter().map(TimedText::from))
+    }
+
+    /// Splits finalized segment records, (see: [crate::export::jsonl]), using punctuation, the
+    /// pauses between records and changes of speaker. Sentences keep the speaker and mean
+    /// confidence of their records. Requires the serde feature.
+    #[cfg(feature = "serde")]
+    pub fn segment_records(&self, records: &[SegmentRecord]) -> TranscriptDocument {
+        self.segment_timed(records.iter().map(|record| TimedText {
+            text: &record.text,
+            // Record timestamps are in milliseconds.
+            start_time: record.start_ms / 10,
+            end_time: record.end_ms / 10,
+            speaker: record.speaker.as_deref(),
+            confidence: record.confidence,
+        }))
+    }
+
+    fn segment_timed<'a>(&self, spans: impl Iterator<Item = TimedText<'a>>) -> TranscriptDocument {
+        // Segment timestamps are in centiseconds.
+        let sentence_pause = (self.sentence_pause.as_millis() / 10) as i64;
+        let paragraph_pause = (self.paragraph_pause.as_millis() / 10) as i64;
+
+        let mut state = DocumentState::default();
+        let mut previous_end: Option<i64> = None;
+        for segment in spans {
+            if state.speaker.as_deref() != segment.speaker {
+                state.end_paragraph(self.max_sentences);
+                state.speaker = segment.speaker.map(str::to_string);
+            } else if let Some(previous_end) = previous_end {
+                let pause = segment.start_time - previous_end;
+                if pause >= paragraph_pause {
+                    state.end_paragraph(self.max_sentences);
+                } else if pause >= sentence_pause {
+                    state.end_sentence(self.max_sentences);
+                }
+            }
+            previous_end = Some(segment.end_time);
+
+            let text = segment.text.trim();
+            // Interpolate word timestamps by their position within the segment.
+            let duration = (segment.end_time - segment.start_time).max(0);
+            let time_at = |offset: usize| {
+                let fraction = offset as f64 / text.len().max(1) as f64;
+                segment.start_time + (duration as f64 * fraction).round() as i64
+            };
+            let mut search_from = 0;
+            for word in text.split_whitespace() {
+                let start = search_from + text[search_from..].find(word).unwrap_or(0);
+                let end = start + word.len();
+                search_from = end;
+                state.push_word(word, Some(time_at(start)), Some(time_at(end)));
+                state.sentence_confidences.extend(segment.confidence);
+                if ends_sentence(word) {
+                    state.end_sentence(self.max_sentences);
+                }
+            }
+        }
+        state.end_paragraph(self.max_sentences);
+        TranscriptDocument {
+            paragraphs: state.paragraphs,
+        }
+    }
+}
+
+impl Default for DocumentSegmenter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,524 @@
+use crate::postprocess::TextProcessor;
+
+/// Standalone cardinals below this value are left as words by default, (e.g. "one idea").
+pub const DEFAULT_MIN_CARDINAL: u64 = 10;
+
+const ONES: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const TEENS: [&str; 10] = [
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const TENS: [&str; 8] = [
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const SCALES: [(&str, u64); 4] = [
+    ("thousand", 1_000),
+    ("million", 1_000_000),
+    ("billion", 1_000_000_000),
+    ("trillion", 1_000_000_000_000),
+];
+
+const ORDINAL_ONES: [&str; 9] = [
+    "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth",
+];
+const ORDINAL_TEENS: [&str; 10] = [
+    "tenth",
+    "eleventh",
+    "twelfth",
+    "thirteenth",
+    "fourteenth",
+    "fifteenth",
+    "sixteenth",
+    "seventeenth",
+    "eighteenth",
+    "nineteenth",
+];
+const ORDINAL_TENS: [(&str, u64); 2] = [("twentieth", 20), ("thirtieth", 30)];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+// "pounds" is left out, since it is as likely to be a weight.
+const CURRENCIES: [(&str, &str); 5] = [
+    ("dollar", "$"),
+    ("dollars", "$"),
+    ("euro", "€"),
+    ("euros", "€"),
+    ("yen", "¥"),
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum NumberWord {
+    Ones(u64),
+    Teen(u64),
+    Tens(u64),
+    Hundred,
+    Scale(u64),
+}
+
+fn number_word(word: &str) -> Option<NumberWord> {
+    let position = |words: &[&str]| words.iter().position(|w| *w == word).map(|i| i as u64);
+    if let Some(value) = position(&ONES) {
+        return Some(NumberWord::Ones(value));
+    }
+    if let Some(value) = position(&TEENS) {
+        return Some(NumberWord::Teen(value + 10));
+    }
+    if let Some(value) = position(&TENS) {
+        return Some(NumberWord::Tens((value + 2) * 10));
+    }
+    if word == "hundred" {
+        return Some(NumberWord::Hundred);
+    }
+    SCALES
+        .iter()
+        .find(|(scale, _)| *scale == word)
+        .map(|(_, value)| NumberWord::Scale(*value))
+}
+
+fn ordinal_word(word: &str) -> Option<u64> {
+    if let Some(i) = ORDINAL_ONES.iter().position(|w| *w == word) {
+        return Some(i as u64 + 1);
+    }
+    if let Some(i) = ORDINAL_TEENS.iter().position(|w| *w == word) {
+        return Some(i as u64 + 10);
+    }
+    ORDINAL_TENS
+        .iter()
+        .find(|(ordinal, _)| *ordinal == word)
+        .map(|(_, value)| *value)
+}
+
+// A single number word, with the punctuation that surrounded it.
+// Hyphenated numbers, (e.g. "twenty-five"), are split into one token per part; only the first
+// part carries the original word, so that it can be restored if the number is not converted.
+struct Token<'a> {
+    raw: &'a str,
+    prefix: &'a str,
+    word: String,
+    suffix: &'a str,
+}
+
+impl Token<'_> {
+    fn starts_word(&self) -> bool {
+        !self.raw.is_empty()
+    }
+
+    // Punctuation after a token ends any number that it is part of.
+    fn ends_phrase(&self) -> bool {
+        !self.suffix.is_empty()
+    }
+}
+
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    for raw in text.split_whitespace() {
+        let Some(start) = raw.find(char::is_alphanumeric) else {
+            tokens.push(Token {
+                raw,
+                prefix: raw,
+                word: String::new(),
+                suffix: "",
+            });
+            continue;
+        };
+        let end = raw
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_alphanumeric())
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(raw.len());
+        let (prefix, core, suffix) = (&raw[..start], &raw[start..end], &raw[end..]);
+        let word = core.to_lowercase();
+
+        let parts: Vec<&str> = word.split('-').collect();
+        let is_hyphenated_number = parts.len() > 1
+            && parts
+                .iter()
+                .all(|part| number_word(part).is_some() || ordinal_word(part).is_some());
+        if !is_hyphenated_number {
+            tokens.push(Token {
+                raw,
+                prefix,
+                word,
+                suffix,
+            });
+            continue;
+        }
+
+        let last = parts.len() - 1;
+        for (i, part) in parts.into_iter().enumerate() {
+            tokens.push(Token {
+                raw: if i == 0 { raw } else { "" },
+                prefix: if i == 0 { prefix } else { "" },
+                word: part.to_string(),
+                suffix: if i == last { suffix } else { "" },
+            });
+        }
+    }
+    tokens
+}
+
+// Digits are grouped for values of 5 or more digits, (e.g. "2500" but "25,000").
+fn format_integer(value: u64) -> String {
+    let digits = value.to_string();
+    if digits.len() < 5 {
+        return digits;
+    }
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+struct SpokenNumber {
+    integer: u64,
+    fraction: Option<String>,
+    // One past the last token of the number.
+    end: usize,
+}
+
+impl SpokenNumber {
+    fn format(&self) -> String {
+        match &self.fraction {
+            Some(fraction) => format!("{}.{fraction}", format_integer(self.integer)),
+            None => format_integer(self.integer),
+        }
+    }
+}
+
+// Parses a cardinal, (e.g. "one hundred and twenty five"). Returns the value and the index one
+// past its last token.
+fn parse_cardinal(tokens: &[Token], start: usize) -> Option<(u64, usize)> {
+    let mut total = 0;
+    let mut current = 0;
+    let mut last: Option<NumberWord> = None;
+    let mut last_scale = u64::MAX;
+    let mut index = start;
+
+    while index < tokens.len() {
+        let token = &tokens[index];
+        // Allow "and" between a hundred/scale and the rest of the number.
+        if token.word == "and"
+            && !token.ends_phrase()
+            && matches!(last, Some(NumberWord::Hundred | NumberWord::Scale(_)))
+            && tokens.get(index + 1).is_some_and(|next| {
+                matches!(
+                    number_word(&next.word),
+                    Some(NumberWord::Ones(1..) | NumberWord::Teen(_) | NumberWord::Tens(_))
+                )
+            })
+        {
+            index += 1;
+            continue;
+        }
+
+        let Some(word) = number_word(&token.word) else {
+            break;
+        };
+        let valid = match (last, word) {
+            (None, NumberWord::Hundred | NumberWord::Scale(_)) => false,
+            (None, _) => true,
+            (Some(_), NumberWord::Ones(0)) => false,
+            (Some(NumberWord::Tens(_)), NumberWord::Ones(_)) => true,
+            (
+                Some(NumberWord::Hundred | NumberWord::Scale(_)),
+                NumberWord::Ones(_) | NumberWord::Teen(_) | NumberWord::Tens(_),
+            ) => true,
+            (Some(NumberWord::Hundred | NumberWord::Scale(_)), _) => {
+                matches!(word, NumberWord::Scale(scale) if scale < last_scale && current > 0)
+            }
+            (Some(_), NumberWord::Hundred) => current > 0 && current < 100,
+            (Some(_), NumberWord::Scale(scale)) => scale < last_scale && current > 0,
+            (Some(_), _) => false,
+        };
+        if !valid {
+            break;
+        }
+
+        match word {
+            NumberWord::Ones(value) | NumberWord::Teen(value) | NumberWord::Tens(value) => {
+                current += value
+            }
+            NumberWord::Hundred => current *= 100,
+            NumberWord::Scale(scale) => {
+                total += current * scale;
+                current = 0;
+                last_scale = scale;
+            }
+        }
+        last = Some(word);
+        index += 1;
+        if token.ends_phrase() {
+            break;
+        }
+    }
+
+    (index > start).then_some((total + current, index))
+}
+
+// Parses a cardinal with an optional decimal part, (e.g. "two point five").
+fn parse_number(tokens: &[Token], start: usize) -> Option<SpokenNumber> {
+    let (integer, mut end) = parse_cardinal(tokens, start)?;
+    let mut number = SpokenNumber {
+        integer,
+        fraction: None,
+        end,
+    };
+    if tokens[end - 1].ends_phrase()
+        || !tokens
+            .get(end)
+            .is_some_and(|token| token.word == "point" && !token.ends_phrase())
+    {
+        return Some(number);
+    }
+
+    let mut fraction = String::new();
+    end += 1;
+    while let Some(token) = tokens.get(end) {
+        let Some(NumberWord::Ones(digit)) = number_word(&token.word) else {
+            break;
+        };
+        fraction.push_str(&digit.to_string());
+        end += 1;
+        if token.ends_phrase() {
+            break;
+        }
+    }
+    if !fraction.is_empty() {
+        number.fraction = Some(fraction);
+        number.end = end;
+    }
+    Some(number)
+}
+
+// Parses an ordinal day of the month, (e.g. "first", "twenty first", "thirtieth").
+fn parse_day(tokens: &[Token], start: usize) -> Option<(u64, usize)> {
+    let token = tokens.get(start)?;
+    if let Some(day) = ordinal_word(&token.word) {
+        return Some((day, start + 1));
+    }
+    let Some(NumberWord::Tens(tens @ (20 | 30))) = number_word(&token.word) else {
+        return None;
+    };
+    if token.ends_phrase() {
+        return None;
+    }
+    let ones = ordinal_word(&tokens.get(start + 1)?.word).filter(|ones| *ones < 10)?;
+    let day = tens + ones;
+    (day <= 31).then_some((day, start + 2))
+}
+
+// Parses 10-99, (e.g. "nineteen", "twenty four").
+fn parse_two_digits(tokens: &[Token], start: usize) -> Option<(u64, usize)> {
+    let token = tokens.get(start)?;
+    match number_word(&token.word)? {
+        NumberWord::Teen(value) => Some((value, start + 1)),
+        NumberWord::Tens(value) => {
+            if !token.ends_phrase()
+                && let Some(next) = tokens.get(start + 1)
+                && let Some(NumberWord::Ones(ones @ 1..)) = number_word(&next.word)
+            {
+                return Some((value + ones, start + 2));
+            }
+            Some((value, start + 1))
+        }
+        _ => None,
+    }
+}
+
+// Parses a year, (e.g. "twenty twenty four", "nineteen oh five", "two thousand and one").
+fn parse_year(tokens: &[Token], start: usize) -> Option<(u64, usize)> {
+    if let Some((year, end)) = parse_cardinal(tokens, start)
+        && (1000..3000).contains(&year)
+    {
+        return Some((year, end));
+    }
+
+    let (century, end) = parse_two_digits(tokens, start)?;
+    if tokens[end - 1].ends_phrase() {
+        return None;
+    }
+    let next = tokens.get(end)?;
+    if next.word == "hundred" {
+        return Some((century * 100, end + 1));
+    }
+    if next.word == "oh" && !next.ends_phrase() {
+        let Some(NumberWord::Ones(ones @ 1..)) = number_word(&tokens.get(end + 1)?.word) else {
+            return None;
+        };
+        return Some((century * 100 + ones, end + 2));
+    }
+    let (year, end) = parse_two_digits(tokens, end)?;
+    Some((century * 100 + year, end))
+}
+
+/// Inverse text normalization, (ITN), for English: converts spoken-form numbers into their
+/// written form.
+/// * cardinals: "two hundred and fifty" -> "250", "three point five" -> "3.5"
+/// * currencies: "twenty five dollars" -> "$25", "five dollars and fifty cents" -> "$5.50"
+/// * percentages: "ten percent" -> "10%"
+/// * dates: "january twenty first twenty twenty four" -> "January 21, 2024"
+///
+/// Standalone cardinals below the minimum, (see: [DEFAULT_MIN_CARDINAL]), are left as words.
+/// Dates are only recognized with ordinal days, (e.g. "may fifth", but not "may five").
+#[derive(Copy, Clone, Debug)]
+pub struct InverseTextNormalizer {
+    min_cardinal: u64,
+}
+
+impl InverseTextNormalizer {
+    pub fn new() -> Self {
+        Self {
+            min_cardinal: DEFAULT_MIN_CARDINAL,
+        }
+    }
+
+    /// Sets the smallest standalone cardinal to convert. Use 0 to convert all numbers.
+    /// Currencies, percentages and dates are always converted.
+    pub fn with_min_cardinal(mut self, min_cardinal: u64) -> Self {
+        self.min_cardinal = min_cardinal;
+        self
+    }
+
+    pub fn min_cardinal(&self) -> u64 {
+        self.min_cardinal
+    }
+
+    fn convert_date(&self, tokens: &[Token], start: usize) -> Option<(String, usize)> {
+        let token = &tokens[start];
+        let month = MONTHS
+            .iter()
+            .find(|month| month.eq_ignore_ascii_case(&token.word))?;
+        if token.ends_phrase() {
+            return None;
+        }
+        let (day, end) = parse_day(tokens, start + 1)?;
+
+        let day_suffix = tokens[end - 1].suffix;
+        if (day_suffix.is_empty() || day_suffix == ",")
+            && let Some((year, year_end)) = parse_year(tokens, end)
+        {
+            return Some((format!("{month} {day}, {year}"), year_end));
+        }
+        Some((format!("{month} {day}"), end))
+    }
+
+    fn convert_number(&self, tokens: &[Token], start: usize) -> Option<(String, usize)> {
+        let number = parse_number(tokens, start)?;
+        let end = number.end;
+        let unit = (!tokens[end - 1].ends_phrase())
+            .then(|| tokens.get(end))
+            .flatten();
+
+        if let Some(unit) = unit
+            && let Some((_, symbol)) = CURRENCIES.iter().find(|(name, _)| *name == unit.word)
+        {
+            if number.fraction.is_none()
+                && let Some((cents, cents_end)) = self.parse_cents(tokens, end)
+            {
+                return Some((
+                    format!("{symbol}{}.{cents:02}", format_integer(number.integer)),
+                    cents_end,
+                ));
+            }
+            return Some((format!("{symbol}{}", number.format()), end + 1));
+        }
+
+        if let Some(unit) = unit
+            && unit.word == "percent"
+        {
+            return Some((format!("{}%", number.format()), end + 1));
+        }
+
+        (number.fraction.is_some() || number.integer >= self.min_cardinal)
+            .then(|| (number.format(), end))
+    }
+
+    // Parses "and <n> cents" following the currency at the given index.
+    fn parse_cents(&self, tokens: &[Token], currency: usize) -> Option<(u64, usize)> {
+        if tokens[currency].ends_phrase() {
+            return None;
+        }
+        let and = tokens.get(currency + 1)?;
+        if and.word != "and" || and.ends_phrase() {
+            return None;
+        }
+        let (cents, end) = parse_cardinal(tokens, currency + 2)?;
+        if cents >= 100 || tokens[end - 1].ends_phrase() {
+            return None;
+        }
+        let unit = tokens.get(end)?;
+        matches!(unit.word.as_str(), "cent" | "cents").then_some((cents, end + 1))
+    }
+
+    fn convert_at(&self, tokens: &[Token], start: usize) -> Option<(String, usize)> {
+        let (converted, end) = self
+            .convert_date(tokens, start)
+            .or_else(|| self.convert_number(tokens, start))?;
+        // Only convert whole words, (e.g. not just the "twenty" of "twenty-first").
+        tokens
+            .get(end)
+            .is_none_or(Token::starts_word)
+            .then_some((converted, end))
+    }
+}
+
+impl Default for InverseTextNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextProcessor for InverseTextNormalizer {
+    fn process(&self, text: String) -> String {
+        let tokens = tokenize(&text);
+        let mut words = Vec::with_capacity(tokens.len());
+        let mut index = 0;
+        while index < tokens.len() {
+            let token = &tokens[index];
+            if !token.starts_word() {
+                index += 1;
+                continue;
+            }
+            if let Some((converted, end)) = self.convert_at(&tokens, index) {
+                words.push(format!(
+                    "{}{converted}{}",
+                    token.prefix,
+                    tokens[end - 1].suffix
+                ));
+                index = end;
+                continue;
+            }
+            words.push(token.raw.to_string());
+            index += 1;
+        }
+        words.join(" ")
+    }
+}
@@ -0,0 +1,254 @@
+use strum::{AsRefStr, Display, EnumIs, EnumIter, EnumString, IntoStaticStr};
+
+use crate::postprocess::TextProcessor;
+use crate::postprocess::stages::{CaseNormalizer, CaseStyle};
+use crate::whisper::configs::Language;
+
+// French typography puts a narrow no-break space before these, (and inside guillemets).
+const NARROW_NO_BREAK_SPACE: char = '\u{202F}';
+
+/// The punctuation conventions applied by [LocaleNormalizer].
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    AsRefStr,
+    Display,
+    EnumIs,
+    EnumIter,
+    EnumString,
+    IntoStaticStr,
+)]
+pub enum PunctuationStyle {
+    /// Punctuation is left as-is.
+    #[default]
+    Default,
+    /// A narrow no-break space before ";", ":", "!", "?" and "»", and after "«".
+    French,
+    /// Full-width punctuation, (e.g. "，", "。"), without surrounding spaces, as used in Chinese
+    /// and Japanese.
+    FullWidth,
+}
+
+impl PunctuationStyle {
+    /// The punctuation conventions of a language.
+    pub fn for_language(language: Language) -> Self {
+        match language {
+            Language::Fr => Self::French,
+            Language::Zh | Language::Ja | Language::Yue => Self::FullWidth,
+            _ => Self::Default,
+        }
+    }
+}
+
+/// Applies locale-aware finalization to text: sentence-case enforcement and the punctuation
+/// conventions of the output language, (see: [PunctuationStyle]).
+///
+/// The language is selected automatically by the transcribers, (see:
+/// [TextProcessor::process_for_language]), from the configured language, or the language whisper
+/// detected when the configured language is auto. A language set with
+/// [LocaleNormalizer::with_language] takes precedence. Text of an unknown language is treated as
+/// English.
+///
+/// This should run after [crate::postprocess::stages::PunctuationNormalizer], which assumes
+/// English spacing.
+#[derive(Copy, Clone, Debug)]
+pub struct LocaleNormalizer {
+    language: Option<Language>,
+    sentence_case: bool,
+}
+
+impl LocaleNormalizer {
+    /// Creates a normalizer that enforces sentence case and follows the output language.
+    pub fn new() -> Self {
+        Self {
+            language: None,
+            sentence_case: true,
+        }
+    }
+
+    /// Sets a fixed language, (ignoring the transcriber's). None or [Language::Auto] follows the
+    /// transcriber.
+    pub fn with_language(mut self, language: Option<Language>) -> Self {
+        self.language = language.filter(|language| *language != Language::Auto);
+        self
+    }
+
+    /// Toggles capitalizing the first letter of each sentence.
+    pub fn with_sentence_case(mut self, sentence_case: bool) -> Self {
+        self.sentence_case = sentence_case;
+        self
+    }
+
+    pub fn language(&self) -> Option<Language> {
+        self.language
+    }
+
+    pub fn sentence_case(&self) -> bool {
+        self.sentence_case
+    }
+
+    /// Normalizes text in the given language.
+    pub fn normalize(&self, text: String, language: Language) -> String {
+        let text = match (self.sentence_case, language) {
+            (false, _) => text,
+            // Also capitalizes the pronoun "I".
+            (true, Language::En | Language::Auto) => {
+                CaseNormalizer::new(CaseStyle::Sentence).process(text)
+            }
+            (true, language) => capitalize_sentences(&text, language),
+        };
+        match PunctuationStyle::for_language(language) {
+            PunctuationStyle::Default => text,
+            PunctuationStyle::French => french_spacing(&text),
+            PunctuationStyle::FullWidth => full_width(&text),
+        }
+    }
+}
+
+impl Default for LocaleNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextProcessor for LocaleNormalizer {
+    fn process(&self, text: String) -> String {
+        self.process_for_language(text, None)
+    }
+
+    fn process_for_language(&self, text: String, language: Option<Language>) -> String {
+        let language = self.language.or(language).unwrap_or(Language::Auto);
+        self.normalize(text, language)
+    }
+}
+
+fn is_sentence_end(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '。' | '！' | '？')
+}
+
+fn capitalize_sentences(text: &str, language: Language) -> String {
+    let mut cased = String::with_capacity(text.len());
+    let mut capitalize_next = true;
+    for c in text.chars() {
+        if capitalize_next && c.is_alphabetic() {
+            match (c, language) {
+                // Turkic languages keep the dot on a capital i.
+                ('i', Language::Tr | Language::Az) => cased.push('İ'),
+                _ => cased.extend(c.to_uppercase()),
+            }
+            capitalize_next = false;
+            continue;
+        }
+        if c.is_alphanumeric() {
+            capitalize_next = false;
+        } else if is_sentence_end(c) {
+            capitalize_next = true;
+        }
+        cased.push(c);
+    }
+    cased
+}
+
+fn french_spacing(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spaced = String::with_capacity(text.len() + 8);
+    for (index, &c) in chars.iter().enumerate() {
+        let between_digits = index > 0
+            && chars[index - 1].is_ascii_digit()
+            && chars.get(index + 1).is_some_and(char::is_ascii_digit);
+        match c {
+            // Times, (e.g. "10:30"), are left alone.
+            ':' if between_digits => spaced.push(c),
+            ';' | ':' | '!' | '?' | '»' => {
+                let trimmed = spaced.trim_end_matches([' ', '\u{00A0}', NARROW_NO_BREAK_SPACE]);
+                spaced.truncate(trimmed.len());
+                // Runs of punctuation, (e.g. "?!"), share one space.
+                if !spaced.is_empty() && !spaced.ends_with([';', ':', '!', '?']) {
+                    spaced.push(NARROW_NO_BREAK_SPACE);
+                }
+                spaced.push(c);
+            }
+            '«' => {
+                spaced.push(c);
+                spaced.push(NARROW_NO_BREAK_SPACE);
+            }
+            _ if c.is_whitespace() && spaced.ends_with('«') => {}
+            _ if c.is_whitespace() && spaced.ends_with(NARROW_NO_BREAK_SPACE) => {}
+            _ => spaced.push(c),
+        }
+    }
+    spaced
+}
+
+fn to_full_width(c: char) -> Option<char> {
+    match c {
+        ',' => Some('，'),
+        '.' => Some('。'),
+        '!' => Some('！'),
+        '?' => Some('？'),
+        ':' => Some('：'),
+        ';' => Some('；'),
+        _ => None,
+    }
+}
+
+fn is_full_width_punctuation(c: char) -> bool {
+    matches!(
+        c,
+        '，' | '。' | '！' | '？' | '：' | '；' | '、' | '「' | '」' | '『' | '』'
+    )
+}
+
+// Han, kana and full-width forms: scripts written without spaces between words.
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30FF}' | '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}'
+            | '\u{F900}'..='\u{FAFF}' | '\u{FF00}'..='\u{FFEF}'
+    ) || is_full_width_punctuation(c)
+}
+
+fn full_width(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    // Convert first, so that spacing sees the final punctuation.
+    let converted: Vec<char> = chars
+        .iter()
+        .enumerate()
+        .map(|(index, &c)| {
+            let after_cjk = chars[..index]
+                .iter()
+                .rev()
+                .find(|c| !c.is_whitespace())
+                .is_some_and(|c| is_cjk(*c));
+            let between_digits = index > 0
+                && chars[index - 1].is_ascii_digit()
+                && chars.get(index + 1).is_some_and(char::is_ascii_digit);
+            // Only punctuation ending CJK text is converted; embedded latin text, (e.g. names,
+            // numbers), keeps its punctuation.
+            match to_full_width(c) {
+                Some(full) if after_cjk && !between_digits => full,
+                _ => c,
+            }
+        })
+        .collect();
+
+    let mut spaced = String::with_capacity(text.len());
+    for (index, &c) in converted.iter().enumerate() {
+        if c.is_whitespace() {
+            let previous = spaced.chars().next_back();
+            let next = converted[index + 1..].iter().find(|c| !c.is_whitespace());
+            let joins_cjk = previous.is_some_and(is_cjk) && next.is_some_and(|c| is_cjk(*c));
+            let touches_punctuation = previous.is_some_and(is_full_width_punctuation)
+                || next.is_some_and(|c| is_full_width_punctuation(*c));
+            if joins_cjk || touches_punctuation || spaced.ends_with(char::is_whitespace) {
+                continue;
+            }
+        }
+        spaced.push(c);
+    }
+    spaced
+}
@@ -0,0 +1,85 @@
+pub mod diff;
+pub mod document;
+pub mod itn;
+pub mod locale;
+pub mod profanity;
+pub mod replacements;
+pub mod stages;
+
+use crate::whisper::configs::Language;
+
+/// A post-processing stage for finalized transcription text, (e.g. punctuation normalization,
+/// casing, filler-word removal).
+///
+/// Transcribers apply their processor to each segment as it is finalized: confirmed segments in
+/// [crate::transcriber::realtime_transcriber::RealtimeTranscriber], and every segment of the
+/// [crate::transcriber::offline_transcriber::OfflineTranscriber] output. Stages therefore see one
+/// segment at a time, (roughly a sentence or phrase), rather than the whole transcript.
+///
+/// This is implemented for closures of the form `Fn(String) -> String`.
+pub trait TextProcessor: Send + Sync {
+    fn process(&self, text: String) -> String;
+
+    /// Processes text in a known output language: the configured language, (or English when
+    /// translating), or the language whisper detected. Transcribers call this rather than
+    /// [TextProcessor::process]; override it for language-dependent stages, (see:
+    /// [locale::LocaleNormalizer]).
+    fn process_for_language(&self, text: String, _language: Option<Language>) -> String {
+        self.process(text)
+    }
+}
+
+impl<F> TextProcessor for F
+where
+    F: Fn(String) -> String + Send + Sync,
+{
+    fn process(&self, text: String) -> String {
+        self(text)
+    }
+}
+
+/// Runs a sequence of [TextProcessor] stages in the order they were added.
+/// Built-in stages can be found in [stages]; custom stages can be any [TextProcessor].
+#[derive(Default)]
+pub struct TextPipeline {
+    stages: Vec<Box<dyn TextProcessor>>,
+}
+
+impl TextPipeline {
+    pub fn new() -> Self {
+        Self { stages: vec![] }
+    }
+
+    /// Appends a stage to the end of the pipeline.
+    pub fn with_stage<P: TextProcessor + 'static>(mut self, stage: P) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Runs the text through each stage of the pipeline.
+    pub fn process(&self, text: String) -> String {
+        self.stages
+            .iter()
+            .fold(text, |text, stage| stage.process(text))
+    }
+}
+
+impl TextProcessor for TextPipeline {
+    fn process(&self, text: String) -> String {
+        TextPipeline::process(self, text)
+    }
+
+    fn process_for_language(&self, text: String, language: Option<Language>) -> String {
+        self.stages.iter().fold(text, |text, stage| {
+            stage.process_for_language(text, language)
+        })
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashSet;
+
+use crate::postprocess::TextProcessor;
+
+/// The words masked by [ProfanityFilter::new]. Inflections are listed separately, since words
+/// are matched whole. Add deployment-specific words, (e.g. slurs), with
+/// [ProfanityFilter::with_words].
+pub const DEFAULT_PROFANITY: &[&str] = &[
+    "arse",
+    "arsehole",
+    "ass",
+    "asshole",
+    "assholes",
+    "bastard",
+    "bastards",
+    "bitch",
+    "bitches",
+    "bitching",
+    "bollocks",
+    "bullshit",
+    "cock",
+    "cocks",
+    "crap",
+    "cunt",
+    "cunts",
+    "damn",
+    "damned",
+    "dick",
+    "dicks",
+    "fuck",
+    "fucked",
+    "fucker",
+    "fuckers",
+    "fucking",
+    "fuckin'",
+    "fucks",
+    "goddamn",
+    "motherfucker",
+    "motherfuckers",
+    "motherfucking",
+    "piss",
+    "pissed",
+    "prick",
+    "shit",
+    "shits",
+    "shitting",
+    "shitty",
+    "twat",
+    "wanker",
+];
+
+const GRAWLIX: [char; 4] = ['#', '$', '@', '!'];
+
+/// How [ProfanityFilter] masks a matched word. Punctuation around the word is kept.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum MaskStyle {
+    /// Replaces every character, (e.g. "****").
+    #[default]
+    Asterisks,
+    /// Keeps the first character, (e.g. "f***").
+    KeepFirst,
+    /// Replaces the word with symbols, (e.g. "#$@!").
+    Grawlix,
+    /// Replaces the word with fixed text, (e.g. "[bleep]").
+    Replace(String),
+    /// Removes the word entirely. Punctuation around the word is left behind, so this is best
+    /// followed by a [crate::postprocess::stages::PunctuationNormalizer].
+    Remove,
+}
+
+impl MaskStyle {
+    fn mask(&self, word: &str) -> String {
+        match self {
+            MaskStyle::Asterisks => "*".repeat(word.chars().count()),
+            MaskStyle::KeepFirst => word
+                .chars()
+                .enumerate()
+                .map(|(i, c)| if i == 0 { c } else { '*' })
+                .collect(),
+            MaskStyle::Grawlix => GRAWLIX.iter().cycle().take(word.chars().count()).collect(),
+            MaskStyle::Replace(replacement) => replacement.clone(),
+            MaskStyle::Remove => String::new(),
+        }
+    }
+}
+
+/// Masks profanity in finalized text, matched case-insensitively as whole words,
+/// (e.g. "shit" but not "shitake").
+#[derive(Clone, Debug)]
+pub struct ProfanityFilter {
+    words: HashSet<String>,
+    mask_style: MaskStyle,
+}
+
+impl ProfanityFilter {
+    /// Creates a filter for the [DEFAULT_PROFANITY], masked with [MaskStyle::Asterisks].
+    pub fn new() -> Self {
+        Self::empty().with_words(DEFAULT_PROFANITY.iter().copied())
+    }
+
+    /// Creates a filter with no words.
+    pub fn empty() -> Self {
+        Self {
+            words: HashSet::new(),
+            mask_style: MaskStyle::default(),
+        }
+    }
+
+    /// Adds words to mask.
+    pub fn with_words<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.words
+            .extend(words.into_iter().map(|word| word.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Stops masking words, (e.g. to allow "damn" and "crap" from the built-in list).
+    pub fn without_words<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for word in words {
+            self.words.remove(&word.as_ref().to_lowercase());
+        }
+        self
+    }
+
+    pub fn with_mask_style(mut self, mask_style: MaskStyle) -> Self {
+        self.mask_style = mask_style;
+        self
+    }
+
+    pub fn words(&self) -> &HashSet<String> {
+        &self.words
+    }
+
+    pub fn mask_style(&self) -> &MaskStyle {
+        &self.mask_style
+    }
+
+    fn filter_word(&self, word: &str) -> Option<String> {
+        let start = word.find(char::is_alphanumeric)?;
+        let last = word.rfind(char::is_alphanumeric)?;
+        let mut end = last + word[last..].chars().next()?.len_utf8();
+        // Allow a trailing apostrophe for dropped-g words, (e.g. "fuckin'").
+        if word[end..].starts_with('\'') && self.words.contains(&word[start..=end].to_lowercase()) {
+            end += 1;
+        }
+
+        let core = &word[start..end];
+        if !self.words.contains(&core.to_lowercase()) {
+            return None;
+        }
+        Some(format!(
+            "{}{}{}",
+            &word[..start],
+            self.mask_style.mask(core),
+            &word[end..]
+        ))
+    }
+}
+
+impl Default for ProfanityFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextProcessor for ProfanityFilter {
+    fn process(&self, text: String) -> String {
+        let filtered = text
+            .split_whitespace()
+            .filter_map(|word| match self.filter_word(word) {
+                Some(masked) => (!masked.is_empty()).then_some(masked),
+                None => Some(word.to_string()),
+            })
+            .collect::<Vec<_>>();
+        filtered.join(" ")
+    }
+}
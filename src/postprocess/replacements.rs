@@ -0,0 +1,257 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::postprocess::TextProcessor;
+use crate::utils::errors::RibbleWhisperError;
+
+/// Rules-file prefix for case-insensitive rules.
+pub const NOCASE_PREFIX: &str = "nocase:";
+/// Rules-file prefix for regex rules.
+pub const REGEX_PREFIX: &str = "regex:";
+/// Rules-file prefix for exact rules. Lines without a prefix are also exact rules.
+pub const EXACT_PREFIX: &str = "exact:";
+/// Separates the pattern from its replacement in a rules file.
+pub const RULE_SEPARATOR: &str = "=>";
+
+#[derive(Clone, Debug)]
+enum Matcher {
+    Exact(String),
+    CaseInsensitive(String),
+    #[cfg(feature = "regex-rules")]
+    Regex(regex::Regex),
+}
+
+/// A single find-and-replace rule, (e.g. "wisper" => "Whisper").
+///
+/// Exact and case-insensitive rules only match whole words: "wisper" matches "wisper's", but not
+/// "wispers". Regex rules match wherever the expression does, and their replacements can refer to
+/// capture groups, (e.g. "$1"). Regex rules require the regex-rules feature.
+#[derive(Clone, Debug)]
+pub struct ReplacementRule {
+    matcher: Matcher,
+    replacement: String,
+}
+
+impl ReplacementRule {
+    pub fn exact(pattern: &str, replacement: &str) -> Result<Self, RibbleWhisperError> {
+        Self::check_pattern(pattern)?;
+        Ok(Self {
+            matcher: Matcher::Exact(pattern.to_string()),
+            replacement: replacement.to_string(),
+        })
+    }
+
+    pub fn case_insensitive(pattern: &str, replacement: &str) -> Result<Self, RibbleWhisperError> {
+        Self::check_pattern(pattern)?;
+        Ok(Self {
+            matcher: Matcher::CaseInsensitive(pattern.to_string()),
+            replacement: replacement.to_string(),
+        })
+    }
+
+    /// Returns Err if the pattern is not a valid regular expression.
+    #[cfg(feature = "regex-rules")]
+    pub fn regex(pattern: &str, replacement: &str) -> Result<Self, RibbleWhisperError> {
+        Self::check_pattern(pattern)?;
+        let regex =
+            regex::Regex::new(pattern).map_err(|e| RibbleWhisperError::InvalidReplacementRule {
+                rule: pattern.to_string(),
+                reason: e.to_string(),
+            })?;
+        Ok(Self {
+            matcher: Matcher::Regex(regex),
+            replacement: replacement.to_string(),
+        })
+    }
+
+    fn check_pattern(pattern: &str) -> Result<(), RibbleWhisperError> {
+        if pattern.is_empty() {
+            return Err(RibbleWhisperError::InvalidReplacementRule {
+                rule: pattern.to_string(),
+                reason: "Empty pattern".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn pattern(&self) -> &str {
+        match &self.matcher {
+            Matcher::Exact(pattern) | Matcher::CaseInsensitive(pattern) => pattern,
+            #[cfg(feature = "regex-rules")]
+            Matcher::Regex(regex) => regex.as_str(),
+        }
+    }
+
+    pub fn replacement(&self) -> &str {
+        &self.replacement
+    }
+
+    /// Applies the rule to all matches within the text.
+    pub fn apply(&self, text: &str) -> String {
+        match &self.matcher {
+            Matcher::Exact(pattern) => replace_words(text, pattern, &self.replacement, false),
+            Matcher::CaseInsensitive(pattern) => {
+                replace_words(text, pattern, &self.replacement, true)
+            }
+            #[cfg(feature = "regex-rules")]
+            Matcher::Regex(regex) => regex
+                .replace_all(text, self.replacement.as_str())
+                .into_owned(),
+        }
+    }
+}
+
+// Returns the end of the match if the pattern matches the text at the given byte offset.
+fn match_at(text: &str, start: usize, pattern: &str, ignore_case: bool) -> Option<usize> {
+    let mut text_chars = text[start..].char_indices();
+    for p in pattern.chars() {
+        let (_, t) = text_chars.next()?;
+        let matches = t == p || (ignore_case && t.to_lowercase().eq(p.to_lowercase()));
+        if !matches {
+            return None;
+        }
+    }
+    Some(
+        text_chars
+            .next()
+            .map_or(text.len(), |(offset, _)| start + offset),
+    )
+}
+
+fn replace_words(text: &str, pattern: &str, replacement: &str, ignore_case: bool) -> String {
+    // Word boundaries are only required where the pattern itself starts/ends with a word character.
+    let check_start = pattern.chars().next().is_some_and(char::is_alphanumeric);
+    let check_end = pattern
+        .chars()
+        .next_back()
+        .is_some_and(char::is_alphanumeric);
+
+    let mut replaced = String::with_capacity(text.len());
+    let mut copied_to = 0;
+    for (start, _) in text.char_indices() {
+        if start < copied_to {
+            continue;
+        }
+        if check_start
+            && text[..start]
+                .chars()
+                .next_back()
+                .is_some_and(char::is_alphanumeric)
+        {
+            continue;
+        }
+        let Some(end) = match_at(text, start, pattern, ignore_case) else {
+            continue;
+        };
+        if check_end
+            && text[end..]
+                .chars()
+                .next()
+                .is_some_and(char::is_alphanumeric)
+        {
+            continue;
+        }
+        replaced.push_str(&text[copied_to..start]);
+        replaced.push_str(replacement);
+        copied_to = end;
+    }
+    replaced.push_str(&text[copied_to..]);
+    replaced
+}
+
+// Parses a single (non-empty, non-comment) line of a rules file.
+fn parse_rule(line: &str) -> Result<ReplacementRule, RibbleWhisperError> {
+    let invalid = |reason: &str| RibbleWhisperError::InvalidReplacementRule {
+        rule: line.to_string(),
+        reason: reason.to_string(),
+    };
+    let (rule, replacement) = line
+        .rsplit_once(RULE_SEPARATOR)
+        .ok_or_else(|| invalid("Missing '=>' between the pattern and the replacement"))?;
+    let replacement = replacement.trim();
+
+    if let Some(pattern) = rule.strip_prefix(NOCASE_PREFIX) {
+        ReplacementRule::case_insensitive(pattern.trim(), replacement)
+    } else if let Some(pattern) = rule.strip_prefix(REGEX_PREFIX) {
+        #[cfg(feature = "regex-rules")]
+        {
+            ReplacementRule::regex(pattern.trim(), replacement)
+        }
+        #[cfg(not(feature = "regex-rules"))]
+        {
+            let _ = pattern;
+            Err(invalid("Regex rules require the regex-rules feature"))
+        }
+    } else {
+        let pattern = rule.strip_prefix(EXACT_PREFIX).unwrap_or(rule);
+        ReplacementRule::exact(pattern.trim(), replacement)
+    }
+}
+
+/// An ordered set of [ReplacementRule]s, for correcting words that whisper consistently
+/// mis-hears, (e.g. product names and jargon). Rules are applied in the order they were added,
+/// so later rules see the output of earlier ones.
+///
+/// Rules can be loaded from a file with one rule per line:
+/// ```text
+/// # Lines starting with '#' are comments.
+/// wisper => Whisper
+/// exact: rust lang => Rust
+/// nocase: ribble => Ribble
+/// regex: (\d+) k\b => ${1}k
+/// ```
+/// An empty replacement removes the match.
+#[derive(Clone, Debug, Default)]
+pub struct ReplacementRules {
+    rules: Vec<ReplacementRule>,
+}
+
+impl ReplacementRules {
+    pub fn new() -> Self {
+        Self { rules: vec![] }
+    }
+
+    /// Appends a rule to the end of the rule set.
+    pub fn with_rule(mut self, rule: ReplacementRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Loads rules from a rules file, (see: [ReplacementRules]).
+    /// Returns Err if the file cannot be read or if any rule is invalid.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, RibbleWhisperError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    pub fn rules(&self) -> &[ReplacementRule] {
+        &self.rules
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+impl FromStr for ReplacementRules {
+    type Err = RibbleWhisperError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rules = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(parse_rule)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { rules })
+    }
+}
+
+impl TextProcessor for ReplacementRules {
+    fn process(&self, text: String) -> String {
+        self.rules.iter().fold(text, |text, rule| rule.apply(&text))
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashSet;
+
+use strum::{AsRefStr, Display, EnumIs, EnumIter, EnumString, IntoStaticStr};
+
+use crate::postprocess::TextProcessor;
+
+/// The filler words removed by [FillerWordRemover::new].
+pub const DEFAULT_FILLER_WORDS: &[&str] = &["um", "umm", "uh", "uhh", "uhm", "erm", "hmm", "mm"];
+
+// Punctuation that attaches to the preceding word.
+fn is_trailing_punctuation(c: char) -> bool {
+    matches!(c, ',' | '.' | '!' | '?' | ';' | ':')
+}
+
+/// Normalizes whitespace and punctuation spacing:
+/// * runs of whitespace are collapsed into a single space
+/// * whitespace before punctuation is removed, (e.g. "hello , world ." -> "hello, world.")
+/// * a space is inserted after punctuation that runs into the next word, (e.g. "hello,world")
+/// * repeated commas are collapsed
+///
+/// Numbers, (e.g. "3.5", "1,000"), are left alone.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PunctuationNormalizer;
+
+impl PunctuationNormalizer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl TextProcessor for PunctuationNormalizer {
+    fn process(&self, text: String) -> String {
+        let mut normalized = String::with_capacity(text.len());
+        let mut pending_space = false;
+        let mut chars = text.trim().chars().peekable();
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() {
+                pending_space = true;
+                continue;
+            }
+            if is_trailing_punctuation(c) {
+                // Drop the whitespace before punctuation, and any repeated commas.
+                pending_space = false;
+                if c == ',' && normalized.ends_with(',') {
+                    continue;
+                }
+                normalized.push(c);
+                if chars.peek().is_some_and(|next| next.is_alphabetic()) {
+                    pending_space = true;
+                }
+                continue;
+            }
+            if pending_space && !normalized.is_empty() {
+                normalized.push(' ');
+            }
+            pending_space = false;
+            normalized.push(c);
+        }
+        normalized
+    }
+}
+
+/// The casing styles applied by [CaseNormalizer].
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    AsRefStr,
+    Display,
+    EnumIs,
+    EnumIter,
+    EnumString,
+    IntoStaticStr,
+)]
+pub enum CaseStyle {
+    /// Capitalizes the first letter of each sentence and the pronoun "I".
+    #[default]
+    Sentence,
+    Lower,
+    Upper,
+}
+
+/// Normalizes the casing of text, see: [CaseStyle].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CaseNormalizer {
+    style: CaseStyle,
+}
+
+impl CaseNormalizer {
+    pub fn new(style: CaseStyle) -> Self {
+        Self { style }
+    }
+
+    pub fn style(&self) -> CaseStyle {
+        self.style
+    }
+}
+
+fn is_pronoun_i(word: &str) -> bool {
+    let core = word.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '\'');
+    core == "i" || core.strip_prefix("i'").is_some_and(|rest| !rest.is_empty())
+}
+
+fn sentence_case(text: &str) -> String {
+    let mut cased = String::with_capacity(text.len());
+    let mut capitalize_next = true;
+    for (index, word) in text.split(' ').enumerate() {
+        if index > 0 {
+            cased.push(' ');
+        }
+        if word.is_empty() {
+            continue;
+        }
+
+        if capitalize_next || is_pronoun_i(word) {
+            let mut chars = word.chars();
+            // Skip leading punctuation, (e.g. quotes), to find the first letter.
+            for c in chars.by_ref() {
+                if c.is_alphabetic() {
+                    cased.extend(c.to_uppercase());
+                    break;
+                }
+                cased.push(c);
+            }
+            cased.push_str(chars.as_str());
+        } else {
+            cased.push_str(word);
+        }
+
+        if word.chars().any(char::is_alphanumeric) {
+            capitalize_next = false;
+        }
+        if word.ends_with(['.', '!', '?']) {
+            capitalize_next = true;
+        }
+    }
+    cased
+}
+
+impl TextProcessor for CaseNormalizer {
+    fn process(&self, text: String) -> String {
+        match self.style {
+            CaseStyle::Sentence => sentence_case(&text),
+            CaseStyle::Lower => text.to_lowercase(),
+            CaseStyle::Upper => text.to_uppercase(),
+        }
+    }
+}
+
+/// Removes filler words, (e.g. "um", "uh"), matched case-insensitively.
+/// Commas attached to a filler word are removed along with it; sentence-ending punctuation
+/// is kept.
+#[derive(Clone, Debug)]
+pub struct FillerWordRemover {
+    filler_words: HashSet<String>,
+}
+
+impl FillerWordRemover {
+    /// Creates a remover for the [DEFAULT_FILLER_WORDS].
+    pub fn new() -> Self {
+        Self::empty().with_filler_words(DEFAULT_FILLER_WORDS.iter().copied())
+    }
+
+    /// Creates a remover with no filler words.
+    pub fn empty() -> Self {
+        Self {
+            filler_words: HashSet::new(),
+        }
+    }
+
+    /// Adds filler words to remove.
+    pub fn with_filler_words<I, S>(mut self, filler_words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.filler_words.extend(
+            filler_words
+                .into_iter()
+                .map(|word| word.as_ref().to_lowercase()),
+        );
+        self
+    }
+
+    pub fn filler_words(&self) -> &HashSet<String> {
+        &self.filler_words
+    }
+
+    fn is_filler(&self, word: &str) -> bool {
+        let core = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'');
+        !core.is_empty() && self.filler_words.contains(&core.to_lowercase())
+    }
+}
+
+impl Default for FillerWordRemover {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextProcessor for FillerWordRemover {
+    fn process(&self, text: String) -> String {
+        let mut kept: Vec<String> = Vec::new();
+        for word in text.split_whitespace() {
+            if !self.is_filler(word) {
+                kept.push(word.to_string());
+                continue;
+            }
+            // Keep sentence-ending punctuation by moving it onto the previous word.
+            let ending = word.trim_start_matches(|c: char| !matches!(c, '.' | '!' | '?'));
+            if let Some(previous) = kept.last_mut()
+                && !ending.is_empty()
+            {
+                let trimmed = previous.trim_end_matches(is_trailing_punctuation).len();
+                previous.truncate(trimmed);
+                previous.push_str(ending);
+            }
+        }
+        kept.join(" ")
+    }
+}
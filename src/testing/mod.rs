@@ -0,0 +1,12 @@
+//! Deterministic harnesses for testing the realtime pipeline without a microphone.
+//! Requires the testing feature.
+//!
+//! [replay::RealtimeReplay] drives a [crate::transcriber::realtime_transcriber::RealtimeTranscriber]
+//! from prerecorded audio, (faster than real time if desired), and collects everything it sends,
+//! so that regression tests can assert on the sequence of snapshots.
+//!
+//! [vad] provides stand-in voice activity detectors with fixed or scripted decisions, so that the
+//! pause, clear and merge branches of the realtime loop can be exercised deterministically.
+
+pub mod replay;
+pub mod vad;
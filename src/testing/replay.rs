@@ -0,0 +1,324 @@
+use std::iter::repeat;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::channel;
+use std::thread::{scope, sleep};
+use std::time::{Duration, Instant};
+
+use crate::audio::WhisperAudioSample;
+use crate::audio::audio_backend::AUDIO_BUFFER_SIZE;
+use crate::audio::audio_ring_buffer::AudioRingBuffer;
+#[cfg(not(feature = "resampler"))]
+use crate::audio::loading::load_audio_file;
+#[cfg(feature = "resampler")]
+use crate::audio::loading::load_normalized_audio_file;
+use crate::audio::pcm::convert_integer_to_float_audio;
+use crate::transcriber::audio_events::{AudioEventClassifier, AudioEventScore};
+use crate::transcriber::realtime_transcriber::RealtimeTranscriberBuilder;
+use crate::transcriber::vad::VAD;
+use crate::transcriber::{
+    TranscriptionSnapshot, WHISPER_SAMPLE_RATE, WhisperControlPhrase, WhisperOutput,
+};
+use crate::utils::errors::RibbleWhisperError;
+use crate::whisper::configs::WhisperRealtimeConfigs;
+use crate::whisper::model::{ModelId, ModelLocation, ModelRetriever};
+
+/// How long to keep feeding silence after the recording ends by default, so that the VAD detects
+/// the pause and the transcriber confirms the last of the speech.
+pub const DEFAULT_SETTLE_MS: u64 = 3000;
+
+/// A [ModelRetriever] that returns the same model file for every [ModelId], so that replays don't
+/// need a populated model bank.
+#[derive(Clone, Debug)]
+pub struct FixedModelRetriever {
+    path: PathBuf,
+}
+
+impl FixedModelRetriever {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl ModelRetriever for FixedModelRetriever {
+    fn retrieve_model(&self, _model_id: ModelId) -> Option<ModelLocation> {
+        Some(ModelLocation::DynamicFilePath(self.path.clone()))
+    }
+}
+
+/// Prerecorded 16 kHz mono audio, pushed into an [AudioRingBuffer] in capture-sized chunks as if
+/// it were coming from a microphone.
+#[derive(Clone)]
+pub struct AudioReplay {
+    audio: Arc<[f32]>,
+    chunk_len: usize,
+    speed: f64,
+}
+
+impl AudioReplay {
+    /// Replays the given samples, (16 kHz mono), at real time speed.
+    pub fn new(audio: impl Into<Arc<[f32]>>) -> Self {
+        Self {
+            audio: audio.into(),
+            chunk_len: AUDIO_BUFFER_SIZE,
+            speed: 1.0,
+        }
+    }
+
+    /// Loads a WAV, (or any other supported format), file to replay.
+    /// With the resampler feature, the audio is converted to 16 kHz mono as necessary;
+    /// otherwise it is expected to already be 16 kHz mono.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, RibbleWhisperError> {
+        #[cfg(feature = "resampler")]
+        let sample = load_normalized_audio_file(path, None::<fn(usize)>)?;
+        #[cfg(not(feature = "resampler"))]
+        let sample = load_audio_file(path, None::<fn(usize)>)?;
+
+        let audio: Arc<[f32]> = match sample {
+            WhisperAudioSample::I16(audio) => convert_integer_to_float_audio(&audio).into(),
+            WhisperAudioSample::F32(audio) => audio,
+        };
+        Ok(Self::new(audio))
+    }
+
+    /// Sets the number of samples pushed at a time, (defaults to the capture buffer size).
+    pub fn with_chunk_len(mut self, chunk_len: usize) -> Self {
+        self.chunk_len = chunk_len.max(1);
+        self
+    }
+
+    /// Sets the playback speed as a multiple of real time. Use f64::INFINITY to push the audio
+    /// as fast as possible.
+    /// NOTE: audio pushed faster than inference consumes it overwrites the ring buffer, so keep
+    /// this within what the model keeps up with on the test hardware.
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed.max(f64::MIN_POSITIVE);
+        self
+    }
+
+    pub fn audio(&self) -> &[f32] {
+        &self.audio
+    }
+
+    /// The length of the recording, (at real time speed).
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.audio.len() as f64 / WHISPER_SAMPLE_RATE)
+    }
+
+    /// Pushes the recording into the buffer, pacing the chunks according to the playback speed.
+    /// Returns early if running is cleared.
+    pub fn replay_into(&self, buffer: &AudioRingBuffer<f32>, running: &AtomicBool) {
+        push_paced(
+            self.audio.chunks(self.chunk_len),
+            self.chunk_len,
+            self.speed,
+            buffer,
+            running,
+        );
+    }
+
+    // Pushes silence for (at least) the given wall-clock time, paced like the recording.
+    fn settle_into(&self, buffer: &AudioRingBuffer<f32>, running: &AtomicBool, settle: Duration) {
+        let silence = vec![0.0; self.chunk_len];
+        let start = Instant::now();
+        let chunks = repeat(silence.as_slice()).take_while(|_| start.elapsed() < settle);
+        // Silence needs to keep arriving in real time for the VAD timeout to elapse.
+        push_paced(chunks, self.chunk_len, self.speed.min(1.0), buffer, running);
+    }
+}
+
+fn push_paced<'a>(
+    chunks: impl Iterator<Item = &'a [f32]>,
+    chunk_len: usize,
+    speed: f64,
+    buffer: &AudioRingBuffer<f32>,
+    running: &AtomicBool,
+) {
+    let chunk_duration = Duration::from_secs_f64(chunk_len as f64 / WHISPER_SAMPLE_RATE / speed);
+    let start = Instant::now();
+    for (idx, chunk) in chunks.enumerate() {
+        if !running.load(Ordering::Acquire) {
+            return;
+        }
+        buffer.push_audio(chunk);
+        // Pace against the start time so that sleep overshoot doesn't accumulate.
+        if let Some(remaining) = (chunk_duration * (idx as u32 + 1)).checked_sub(start.elapsed()) {
+            sleep(remaining);
+        }
+    }
+}
+
+/// Everything a [RealtimeReplay] observed.
+pub struct ReplayOutcome {
+    /// Every output the transcriber sent on its output sender, in order.
+    pub outputs: Vec<WhisperOutput>,
+    /// Every output the transcriber sent on its control sender, in order, (only when replayed
+    /// with [RealtimeReplay::with_control_channel]).
+    pub control_outputs: Vec<WhisperOutput>,
+    /// The final transcription returned by the transcriber.
+    pub transcription: String,
+}
+
+impl ReplayOutcome {
+    /// The full snapshots sent, in order. Configure [crate::whisper::configs::SnapshotEncoding::Full],
+    /// (the default), to receive a snapshot for every update.
+    pub fn snapshots(&self) -> impl Iterator<Item = &TranscriptionSnapshot> {
+        self.outputs.iter().filter_map(|output| match output {
+            WhisperOutput::TranscriptionSnapshot(snapshot) => Some(snapshot.as_ref()),
+            _ => None,
+        })
+    }
+
+    /// The confirmed text of each snapshot, with consecutive repeats removed.
+    pub fn confirmed_sequence(&self) -> Vec<String> {
+        let mut sequence: Vec<String> = vec![];
+        for snapshot in self.snapshots() {
+            if sequence.last().map(String::as_str) != Some(snapshot.confirmed()) {
+                sequence.push(snapshot.confirmed().to_string());
+            }
+        }
+        sequence
+    }
+
+    /// The control phrases sent, in order, on whichever sender carried them.
+    pub fn control_phrases(&self) -> impl Iterator<Item = &WhisperControlPhrase> {
+        let outputs = match self.control_outputs.is_empty() {
+            true => &self.outputs,
+            false => &self.control_outputs,
+        };
+        outputs.iter().filter_map(|output| match output {
+            WhisperOutput::ControlPhrase(phrase) => Some(phrase),
+            _ => None,
+        })
+    }
+}
+
+/// Drives a [crate::transcriber::realtime_transcriber::RealtimeTranscriber] from an
+/// [AudioReplay]: the recording is pushed once the transcriber is ready, followed by silence so
+/// that the last of the speech is confirmed, and then transcription is stopped.
+pub struct RealtimeReplay<V, M>
+where
+    V: VAD<f32> + Send + Sync,
+    M: ModelRetriever + Send + Sync,
+{
+    configs: WhisperRealtimeConfigs,
+    vad: V,
+    model_retriever: M,
+    replay: AudioReplay,
+    settle: Duration,
+    control_channel: bool,
+    audio_event_classifier: Option<Box<dyn AudioEventClassifier>>,
+}
+
+// Forwards to a boxed classifier, so that the replay doesn't need a type parameter for it.
+struct BoxedClassifier(Box<dyn AudioEventClassifier>);
+
+impl AudioEventClassifier for BoxedClassifier {
+    fn classify(&mut self, samples: &[f32]) -> Vec<AudioEventScore> {
+        self.0.classify(samples)
+    }
+    fn reset(&mut self) {
+        self.0.reset()
+    }
+}
+
+impl<V, M> RealtimeReplay<V, M>
+where
+    V: VAD<f32> + Send + Sync,
+    M: ModelRetriever + Send + Sync,
+{
+    pub fn new(
+        configs: WhisperRealtimeConfigs,
+        vad: V,
+        model_retriever: M,
+        replay: AudioReplay,
+    ) -> Self {
+        Self {
+            configs,
+            vad,
+            model_retriever,
+            replay,
+            settle: Duration::from_millis(DEFAULT_SETTLE_MS),
+            control_channel: false,
+            audio_event_classifier: None,
+        }
+    }
+
+    /// Sets how long to feed silence after the recording ends, before stopping transcription.
+    pub fn with_settle_time(mut self, settle: Duration) -> Self {
+        self.settle = settle;
+        self
+    }
+
+    /// Gives the transcriber a separate control sender, (see:
+    /// [RealtimeTranscriberBuilder::with_control_sender]), whose outputs are collected in
+    /// [ReplayOutcome::control_outputs].
+    pub fn with_control_channel(mut self, control_channel: bool) -> Self {
+        self.control_channel = control_channel;
+        self
+    }
+
+    /// Sets a classifier for annotating the replay with audio events, (see:
+    /// [RealtimeTranscriberBuilder::with_audio_event_classifier]).
+    pub fn with_audio_event_classifier<C: AudioEventClassifier>(mut self, classifier: C) -> Self {
+        self.audio_event_classifier = Some(Box::new(classifier));
+        self
+    }
+
+    /// Runs the replay to completion.
+    /// Returns Err if the transcriber fails to build, or if transcription fails.
+    pub fn run(self) -> Result<ReplayOutcome, RibbleWhisperError> {
+        // Unbounded, so that nothing is dropped however far the reader falls behind.
+        let (sender, receiver) = channel();
+        let (control_sender, control_receiver) = channel();
+        let audio_buffer = AudioRingBuffer::default();
+        let mut builder = RealtimeTranscriberBuilder::<V, M>::new()
+            .with_configs(self.configs)
+            .with_audio_buffer(&audio_buffer)
+            .with_output_sender(sender)
+            .with_voice_activity_detector(self.vad)
+            .with_model_retriever(self.model_retriever);
+        if self.control_channel {
+            builder = builder.with_control_sender(control_sender);
+        }
+        if let Some(classifier) = self.audio_event_classifier {
+            builder = builder.with_audio_event_classifier(BoxedClassifier(classifier));
+        }
+        let (transcriber, handle) = builder.build()?;
+
+        let run_transcription = Arc::new(AtomicBool::new(true));
+        let transcription = scope(|s| {
+            let t_run_transcription = Arc::clone(&run_transcription);
+            let transcriber_thread = s.spawn(move || {
+                let result = transcriber.run_stream(
+                    Arc::clone(&t_run_transcription),
+                    Arc::new(AtomicBool::new(true)),
+                );
+                // Stop the feed if the transcriber exits early, (e.g. on error).
+                t_run_transcription.store(false, Ordering::Release);
+                result
+            });
+
+            while !handle.ready() && run_transcription.load(Ordering::Acquire) {
+                sleep(Duration::from_millis(1));
+            }
+            self.replay.replay_into(&audio_buffer, &run_transcription);
+            self.replay
+                .settle_into(&audio_buffer, &run_transcription, self.settle);
+            run_transcription.store(false, Ordering::Release);
+
+            transcriber_thread.join().map_err(|_| {
+                RibbleWhisperError::Unknown("Realtime transcription thread panicked.".to_string())
+            })?
+        })?;
+
+        Ok(ReplayOutcome {
+            outputs: receiver.try_iter().collect(),
+            control_outputs: control_receiver.try_iter().collect(),
+            transcription,
+        })
+    }
+}
@@ -0,0 +1,118 @@
+use crate::transcriber::vad::{Resettable, VAD};
+
+/// A [VAD] that returns a predefined sequence of decisions, one per call, regardless of the
+/// audio. Once the script runs out, every call returns the fallback decision, (silence by default,
+/// so that a session settles).
+///
+/// This makes the realtime loop's pause, clear and merge branches reachable on demand: e.g.
+/// `ScriptedVad::new([true, true, false])` confirms after the second window of speech.
+#[derive(Clone, Debug, Default)]
+pub struct ScriptedVad {
+    decisions: Vec<bool>,
+    position: usize,
+    fallback: bool,
+}
+
+impl ScriptedVad {
+    pub fn new(decisions: impl IntoIterator<Item = bool>) -> Self {
+        Self {
+            decisions: decisions.into_iter().collect(),
+            position: 0,
+            fallback: false,
+        }
+    }
+
+    /// Sets the decision returned once the script has been exhausted.
+    pub fn with_fallback(mut self, fallback: bool) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    pub fn decisions(&self) -> &[bool] {
+        &self.decisions
+    }
+
+    pub fn fallback(&self) -> bool {
+        self.fallback
+    }
+
+    /// The number of decisions made since construction or the last reset.
+    pub fn calls(&self) -> usize {
+        self.position
+    }
+
+    /// True once every scripted decision has been returned.
+    pub fn exhausted(&self) -> bool {
+        self.position >= self.decisions.len()
+    }
+
+    fn next_decision(&mut self) -> bool {
+        let decision = self
+            .decisions
+            .get(self.position)
+            .copied()
+            .unwrap_or(self.fallback);
+        self.position += 1;
+        decision
+    }
+}
+
+impl Resettable for ScriptedVad {
+    /// Rewinds the script to the beginning.
+    fn reset_session(&mut self) {
+        self.position = 0;
+    }
+}
+
+impl VAD<f32> for ScriptedVad {
+    fn voice_detected(&mut self, _samples: &[f32]) -> bool {
+        self.next_decision()
+    }
+
+    /// Consumes one decision for the entire slice: all of the samples are kept if it is voiced,
+    /// otherwise none are.
+    fn extract_voiced_frames(&mut self, samples: &[f32]) -> Box<[f32]> {
+        if self.next_decision() {
+            Box::from(samples)
+        } else {
+            Box::default()
+        }
+    }
+}
+
+/// A [VAD] that detects voice in everything, (including empty slices), so that audio is never
+/// paused or trimmed.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AlwaysVoiced;
+
+impl Resettable for AlwaysVoiced {
+    fn reset_session(&mut self) {}
+}
+
+impl VAD<f32> for AlwaysVoiced {
+    fn voice_detected(&mut self, _samples: &[f32]) -> bool {
+        true
+    }
+
+    fn extract_voiced_frames(&mut self, samples: &[f32]) -> Box<[f32]> {
+        Box::from(samples)
+    }
+}
+
+/// A [VAD] that never detects voice, so that every window is treated as a pause.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NeverVoiced;
+
+impl Resettable for NeverVoiced {
+    fn reset_session(&mut self) {}
+}
+
+impl VAD<f32> for NeverVoiced {
+    fn voice_detected(&mut self, _samples: &[f32]) -> bool {
+        false
+    }
+
+    fn extract_voiced_frames(&mut self, _samples: &[f32]) -> Box<[f32]> {
+        Box::default()
+    }
+}
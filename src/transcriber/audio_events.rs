@@ -0,0 +1,179 @@
+//! Non-speech audio events, (e.g. music, applause, laughter), for annotating transcripts.
+//!
+//! Captions for deaf and hard-of-hearing audiences are expected to mark significant sounds,
+//! (e.g. "[applause]"), alongside the speech. An [AudioEventClassifier] attached with
+//! [RealtimeTranscriberBuilder::with_audio_event_classifier](crate::transcriber::realtime_transcriber::RealtimeTranscriberBuilder::with_audio_event_classifier)
+//! is run over the same audio as the voice activity detector, and each event it hears is sent as
+//! a [WhisperOutput::AudioEvent](crate::transcriber::WhisperOutput::AudioEvent) once it ends.
+//!
+//! Classifiers are typically small audio taggers, (e.g. a YAMNet or PANNs ONNX model run with
+//! `ort`); [AudioEventKind::from_label] maps their AudioSet labels onto event kinds.
+
+use std::sync::Arc;
+
+/// A kind of non-speech audio event.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AudioEventKind {
+    Music,
+    Applause,
+    Laughter,
+    /// Any other sound, by the classifier's label, (e.g. "door knock").
+    Other(Arc<str>),
+}
+
+impl AudioEventKind {
+    /// Maps an AudioSet class label, (as used by most audio taggers), to an event kind.
+    /// Labels without a dedicated kind are kept as [AudioEventKind::Other], in lowercase.
+    pub fn from_label(label: &str) -> Self {
+        match label.trim().to_lowercase().as_str() {
+            "music" | "musical instrument" | "singing" => Self::Music,
+            "applause" | "clapping" | "cheering" => Self::Applause,
+            "laughter" | "giggle" | "chuckle, chortle" | "belly laugh" | "snicker" => {
+                Self::Laughter
+            }
+            other => Self::Other(Arc::from(other)),
+        }
+    }
+
+    /// The label used in caption markers.
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Music => "music",
+            Self::Applause => "applause",
+            Self::Laughter => "laughter",
+            Self::Other(label) => label,
+        }
+    }
+}
+
+impl std::fmt::Display for AudioEventKind {
+    /// Displays as a caption marker, (e.g. "[applause]").
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}]", self.label())
+    }
+}
+
+/// A kind of event heard in a window of audio, with the classifier's confidence (0.0 - 1.0).
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioEventScore {
+    pub kind: AudioEventKind,
+    pub confidence: f32,
+}
+
+impl AudioEventScore {
+    pub fn new(kind: AudioEventKind, confidence: f32) -> Self {
+        Self { kind, confidence }
+    }
+}
+
+/// A non-speech event, spanning the consecutive windows it was heard in.
+/// Timestamps are measured in centiseconds, (like segments), from when the transcriber started
+/// listening.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioEvent {
+    kind: AudioEventKind,
+    start_time: i64,
+    end_time: i64,
+    confidence: f32,
+}
+
+impl AudioEvent {
+    pub fn new(kind: AudioEventKind, start_time: i64, end_time: i64, confidence: f32) -> Self {
+        Self {
+            kind,
+            start_time,
+            end_time,
+            confidence,
+        }
+    }
+
+    pub fn kind(&self) -> &AudioEventKind {
+        &self.kind
+    }
+
+    pub fn start_timestamp(&self) -> i64 {
+        self.start_time
+    }
+
+    pub fn end_timestamp(&self) -> i64 {
+        self.end_time
+    }
+
+    /// The highest confidence the event was heard with.
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+}
+
+impl std::fmt::Display for AudioEvent {
+    /// Displays as a caption marker, (e.g. "[applause]").
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+/// Detects non-speech events in 16 kHz mono audio, (see: [crate::transcriber::audio_events]).
+/// This is called from the transcription thread for every voice detection window, so it should
+/// run well within the window's duration.
+pub trait AudioEventClassifier: Send + 'static {
+    /// Returns the events heard in the samples. Apply any confidence threshold here; every
+    /// returned score counts as a detection.
+    fn classify(&mut self, samples: &[f32]) -> Vec<AudioEventScore>;
+    /// Clears any state carried between windows, (e.g. before a new session).
+    fn reset(&mut self) {}
+}
+
+/// Joins the detections of consecutive windows into [AudioEvent]s, which are finished by the
+/// first window that doesn't detect them.
+#[derive(Default)]
+pub struct AudioEventTracker {
+    active: Vec<AudioEvent>,
+}
+
+impl AudioEventTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the detections of a window, (spanning start_time to end_time), returning the events
+    /// that have finished.
+    pub fn update(
+        &mut self,
+        scores: Vec<AudioEventScore>,
+        start_time: i64,
+        end_time: i64,
+    ) -> Vec<AudioEvent> {
+        let (continued, finished): (Vec<_>, Vec<_>) = self
+            .active
+            .drain(..)
+            .partition(|event| scores.iter().any(|score| score.kind == event.kind));
+        self.active = continued;
+
+        for score in scores {
+            match self
+                .active
+                .iter_mut()
+                .find(|event| event.kind == score.kind)
+            {
+                Some(event) => {
+                    event.end_time = event.end_time.max(end_time);
+                    event.confidence = event.confidence.max(score.confidence);
+                }
+                None => self.active.push(AudioEvent::new(
+                    score.kind,
+                    start_time,
+                    end_time,
+                    score.confidence,
+                )),
+            }
+        }
+        finished
+    }
+
+    /// Finishes every event still being heard, (e.g. at the end of a session).
+    pub fn finish(&mut self) -> Vec<AudioEvent> {
+        std::mem::take(&mut self.active)
+    }
+}
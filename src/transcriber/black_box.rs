@@ -0,0 +1,377 @@
+//! Recording what a realtime session saw, for reproducing transcription bugs.
+//!
+//! The duplication and hallucination bugs the realtime loop works around depend on the exact
+//! audio each inference window received and the VAD decisions that led up to it, which makes
+//! them hard to reproduce from a description alone. A [SessionRecorder] attached with
+//! [RealtimeTranscriberBuilder::with_session_recorder](crate::transcriber::realtime_transcriber::RealtimeTranscriberBuilder::with_session_recorder)
+//! receives each of these as the session runs.
+//!
+//! With the serde feature, [BlackBoxRecorder] writes them to a bundle directory that can be
+//! attached to a bug report and loaded back with [SessionBundle]:
+//! * `configs.json`: the session's [WhisperRealtimeConfigs].
+//! * `events.jsonl`: one [BlackBoxEvent] per line, in order.
+//! * `audio.f32`: the audio of every inference window, as concatenated little-endian f32 samples.
+//!
+//! To reproduce a session, run whisper over [SessionBundle::window_audio] with the recorded
+//! configs, or feed [SessionBundle::vad_decisions] to a scripted VAD, (see: the testing feature).
+
+#[cfg(feature = "serde")]
+use std::fs::{self, File};
+#[cfg(feature = "serde")]
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+#[cfg(feature = "serde")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "serde")]
+use std::time::Instant;
+
+use crate::transcriber::RibbleWhisperSegment;
+#[cfg(feature = "serde")]
+use crate::utils::errors::RibbleWhisperError;
+#[cfg(feature = "serde")]
+use crate::utils::logging::log_warn_or_eprint;
+use crate::whisper::configs::WhisperRealtimeConfigs;
+
+#[cfg(feature = "serde")]
+const CONFIGS_FILE: &str = "configs.json";
+#[cfg(feature = "serde")]
+const EVENTS_FILE: &str = "events.jsonl";
+#[cfg(feature = "serde")]
+const AUDIO_FILE: &str = "audio.f32";
+#[cfg(feature = "serde")]
+const SAMPLE_BYTES: u64 = size_of::<f32>() as u64;
+
+/// Receives the inputs and whisper outputs of a realtime session as they happen.
+/// This is called from the transcription thread, so keep it cheap; buffer any I/O.
+pub trait SessionRecorder: Send + 'static {
+    /// Called once the model has loaded, before the first window.
+    fn start_session(&mut self, _configs: &WhisperRealtimeConfigs) {}
+    /// Called with every VAD decision and the audio it was made on.
+    fn record_vad(&mut self, voiced: bool, samples: &[f32]);
+    /// Called with the exact audio passed to whisper, before each inference.
+    fn record_window(&mut self, audio: &[f32], use_context: bool, segment_merge: bool);
+    /// Called with the segments whisper returned for the last recorded window.
+    fn record_segments(&mut self, segments: &[RibbleWhisperSegment]);
+    /// Called with the final transcription once the session has ended.
+    fn finish_session(&mut self, _transcription: &str) {}
+}
+
+/// A segment as whisper returned it, (timestamps in centiseconds, relative to the window).
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct RecordedSegment {
+    pub text: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+#[cfg(feature = "serde")]
+impl From<&RibbleWhisperSegment> for RecordedSegment {
+    fn from(segment: &RibbleWhisperSegment) -> Self {
+        Self {
+            text: segment.text().to_string(),
+            start_time: segment.start_time,
+            end_time: segment.end_time,
+            confidence: segment.confidence,
+        }
+    }
+}
+
+/// An entry in a session bundle's event log.
+/// elapsed_ms is measured from the start of the session.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BlackBoxEvent {
+    Vad {
+        elapsed_ms: u64,
+        voiced: bool,
+        samples: usize,
+    },
+    /// An inference window. Its audio is stored in the bundle's audio file, starting at
+    /// audio_offset, (in samples).
+    Window {
+        elapsed_ms: u64,
+        index: u64,
+        audio_offset: u64,
+        samples: usize,
+        use_context: bool,
+        segment_merge: bool,
+    },
+    Segments {
+        elapsed_ms: u64,
+        window: u64,
+        segments: Vec<RecordedSegment>,
+    },
+    Transcription {
+        elapsed_ms: u64,
+        text: String,
+    },
+}
+
+/// A [SessionRecorder] that writes a replayable bundle to a directory, (see: [SessionBundle]).
+///
+/// Every inference window is stored in full, (windows overlap and can hold up to 30s of audio), so
+/// expect bundles to grow by tens of megabytes for each minute of speech. If writing fails, (e.g. the disk is full), a warning is logged and
+/// recording stops; the transcription itself is unaffected. Requires the serde feature.
+#[cfg(feature = "serde")]
+pub struct BlackBoxRecorder {
+    directory: PathBuf,
+    events: BufWriter<File>,
+    audio: BufWriter<File>,
+    audio_offset: u64,
+    windows: u64,
+    started: Instant,
+    failed: bool,
+}
+
+#[cfg(feature = "serde")]
+impl BlackBoxRecorder {
+    /// Creates a bundle in the given directory, creating the directory if needed.
+    /// Any bundle previously recorded there is overwritten.
+    pub fn create<P: AsRef<Path>>(directory: P) -> Result<Self, RibbleWhisperError> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory)?;
+        let configs_path = directory.join(CONFIGS_FILE);
+        if configs_path.is_file() {
+            fs::remove_file(configs_path)?;
+        }
+        Ok(Self {
+            events: BufWriter::new(File::create(directory.join(EVENTS_FILE))?),
+            audio: BufWriter::new(File::create(directory.join(AUDIO_FILE))?),
+            directory,
+            audio_offset: 0,
+            windows: 0,
+            started: Instant::now(),
+            failed: false,
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The number of inference windows recorded so far.
+    pub fn windows(&self) -> u64 {
+        self.windows
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    fn write_event(&mut self, event: &BlackBoxEvent) -> Result<(), RibbleWhisperError> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        self.events.write_all(&line)?;
+        Ok(())
+    }
+
+    fn write_window(&mut self, audio: &[f32]) -> Result<(), RibbleWhisperError> {
+        for sample in audio {
+            self.audio.write_all(&sample.to_le_bytes())?;
+        }
+        // Flush at window boundaries, so that a crash leaves everything up to the last window.
+        self.audio.flush()?;
+        self.events.flush()?;
+        Ok(())
+    }
+
+    // Runs a write, disabling the recorder on the first failure.
+    fn try_record(&mut self, write: impl FnOnce(&mut Self) -> Result<(), RibbleWhisperError>) {
+        if self.failed {
+            return;
+        }
+        if let Err(e) = write(self) {
+            self.failed = true;
+            log_warn_or_eprint!(
+                "Black box recording to {} failed, recording stopped: {e}",
+                self.directory.display()
+            );
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl SessionRecorder for BlackBoxRecorder {
+    fn start_session(&mut self, configs: &WhisperRealtimeConfigs) {
+        self.started = Instant::now();
+        self.try_record(|recorder| {
+            let file = File::create(recorder.directory.join(CONFIGS_FILE))?;
+            serde_json::to_writer_pretty(file, configs)?;
+            Ok(())
+        });
+    }
+
+    fn record_vad(&mut self, voiced: bool, samples: &[f32]) {
+        let event = BlackBoxEvent::Vad {
+            elapsed_ms: self.elapsed_ms(),
+            voiced,
+            samples: samples.len(),
+        };
+        self.try_record(|recorder| recorder.write_event(&event));
+    }
+
+    fn record_window(&mut self, audio: &[f32], use_context: bool, segment_merge: bool) {
+        let event = BlackBoxEvent::Window {
+            elapsed_ms: self.elapsed_ms(),
+            index: self.windows,
+            audio_offset: self.audio_offset,
+            samples: audio.len(),
+            use_context,
+            segment_merge,
+        };
+        self.try_record(|recorder| {
+            recorder.write_event(&event)?;
+            recorder.write_window(audio)
+        });
+        self.audio_offset += audio.len() as u64;
+        self.windows += 1;
+    }
+
+    fn record_segments(&mut self, segments: &[RibbleWhisperSegment]) {
+        let event = BlackBoxEvent::Segments {
+            elapsed_ms: self.elapsed_ms(),
+            window: self.windows.saturating_sub(1),
+            segments: segments.iter().map(RecordedSegment::from).collect(),
+        };
+        self.try_record(|recorder| recorder.write_event(&event));
+    }
+
+    fn finish_session(&mut self, transcription: &str) {
+        let event = BlackBoxEvent::Transcription {
+            elapsed_ms: self.elapsed_ms(),
+            text: transcription.to_string(),
+        };
+        self.try_record(|recorder| {
+            recorder.write_event(&event)?;
+            recorder.events.flush()?;
+            recorder.audio.flush()?;
+            Ok(())
+        });
+    }
+}
+
+/// A session recorded by a [BlackBoxRecorder]. Requires the serde feature.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug)]
+pub struct SessionBundle {
+    directory: PathBuf,
+    configs: Option<WhisperRealtimeConfigs>,
+    events: Vec<BlackBoxEvent>,
+}
+
+#[cfg(feature = "serde")]
+impl SessionBundle {
+    /// Loads a bundle's configs and event log. Window audio is read on demand.
+    /// A truncated final event, (i.e. from a session that crashed mid-write), is skipped.
+    pub fn load<P: AsRef<Path>>(directory: P) -> Result<Self, RibbleWhisperError> {
+        let directory = directory.as_ref().to_path_buf();
+        let configs_path = directory.join(CONFIGS_FILE);
+        let configs = if configs_path.is_file() {
+            Some(serde_json::from_reader(BufReader::new(File::open(
+                configs_path,
+            )?))?)
+        } else {
+            None
+        };
+
+        let mut log = String::new();
+        File::open(directory.join(EVENTS_FILE))?.read_to_string(&mut log)?;
+        let terminated = log.ends_with('\n');
+        let lines: Vec<&str> = log.lines().filter(|line| !line.is_empty()).collect();
+        let mut events = Vec::with_capacity(lines.len());
+        for (idx, line) in lines.iter().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(event) => events.push(event),
+                Err(_) if !terminated && idx + 1 == lines.len() => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(Self {
+            directory,
+            configs,
+            events,
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The configs the session ran with, if it got as far as loading the model.
+    pub fn configs(&self) -> Option<&WhisperRealtimeConfigs> {
+        self.configs.as_ref()
+    }
+
+    pub fn events(&self) -> &[BlackBoxEvent] {
+        &self.events
+    }
+
+    /// Every VAD decision, in order.
+    pub fn vad_decisions(&self) -> Vec<bool> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                BlackBoxEvent::Vad { voiced, .. } => Some(*voiced),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The number of inference windows recorded.
+    pub fn n_windows(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|event| matches!(event, BlackBoxEvent::Window { .. }))
+            .count()
+    }
+
+    /// The segments whisper returned for a window, or None if none were recorded.
+    pub fn window_segments(&self, index: u64) -> Option<&[RecordedSegment]> {
+        self.events.iter().find_map(|event| match event {
+            BlackBoxEvent::Segments {
+                window, segments, ..
+            } if *window == index => Some(segments.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// Reads the exact audio passed to whisper for a window.
+    /// Returns Err if there is no such window, or its audio is missing from the bundle.
+    pub fn window_audio(&self, index: u64) -> Result<Vec<f32>, RibbleWhisperError> {
+        let Some((audio_offset, samples)) = self.events.iter().find_map(|event| match event {
+            BlackBoxEvent::Window {
+                index: window,
+                audio_offset,
+                samples,
+                ..
+            } if *window == index => Some((*audio_offset, *samples)),
+            _ => None,
+        }) else {
+            return Err(RibbleWhisperError::ParameterError(format!(
+                "No window {index} in session bundle: {}",
+                self.directory.display()
+            )));
+        };
+
+        let mut file = File::open(self.directory.join(AUDIO_FILE))?;
+        file.seek(SeekFrom::Start(audio_offset * SAMPLE_BYTES))?;
+        let mut bytes = vec![0u8; samples * SAMPLE_BYTES as usize];
+        file.read_exact(&mut bytes)?;
+        Ok(bytes
+            .chunks_exact(SAMPLE_BYTES as usize)
+            .map(|sample| f32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]]))
+            .collect())
+    }
+
+    /// The final transcription, if the session finished.
+    pub fn transcription(&self) -> Option<&str> {
+        self.events.iter().rev().find_map(|event| match event {
+            BlackBoxEvent::Transcription { text, .. } => Some(text.as_str()),
+            _ => None,
+        })
+    }
+}
@@ -0,0 +1,96 @@
+//! Corrections to already-confirmed text.
+//!
+//! Confirmed text is usually append-only, but deduplication occasionally revises the last few
+//! confirmed words when the next segment overlaps them. Snapshots always carry the revised text;
+//! consumers that only append, (e.g. captions, deltas over a socket), can instead fix the text
+//! they already showed with a [Correction], the way a human captioner backspaces over the last
+//! few words.
+//!
+//! The realtime transcriber sends corrections as
+//! [WhisperOutput::Correction](crate::transcriber::WhisperOutput::Correction) when enabled with
+//! [WhisperRealtimeConfigs::with_correction_horizon](crate::whisper::configs::WhisperRealtimeConfigs::with_correction_horizon).
+//! Post-processors that revise confirmed text can build their own with [Correction::between].
+
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Replaces a byte range of the previously confirmed text with new text.
+/// A correction is sent before the snapshot that contains the corrected text; applying it to the
+/// confirmed text of the previous snapshot, (see: [Correction::apply]), leaves a prefix of the
+/// next snapshot's confirmed text.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Correction {
+    range: Range<usize>,
+    new_text: Arc<str>,
+}
+
+impl Correction {
+    pub fn new(range: Range<usize>, new_text: &str) -> Self {
+        Self {
+            range,
+            new_text: Arc::from(new_text),
+        }
+    }
+
+    /// The smallest single replacement that turns previous into next, or None if the two are the
+    /// same.
+    pub fn between(previous: &str, next: &str) -> Option<Self> {
+        let prefix_len: usize = previous
+            .chars()
+            .zip(next.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        if prefix_len == previous.len() && prefix_len == next.len() {
+            return None;
+        }
+        // The common suffix can't overlap the common prefix.
+        let suffix_len: usize = previous[prefix_len..]
+            .chars()
+            .rev()
+            .zip(next[prefix_len..].chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        Some(Self::new(
+            prefix_len..previous.len() - suffix_len,
+            &next[prefix_len..next.len() - suffix_len],
+        ))
+    }
+
+    /// The range of the previous text, in bytes, that is replaced.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// The text that replaces the range.
+    pub fn new_text(&self) -> &str {
+        &self.new_text
+    }
+
+    /// How far back from the end of text of the given length the correction reaches, in bytes.
+    pub fn reach(&self, text_len: usize) -> usize {
+        text_len.saturating_sub(self.range.start)
+    }
+
+    /// Applies the correction to text. Returns false, (leaving the text unchanged), if the range
+    /// is out of bounds or does not fall on char boundaries.
+    pub fn apply(&self, text: &mut String) -> bool {
+        if text.get(self.range.clone()).is_none() {
+            return false;
+        }
+        text.replace_range(self.range.clone(), &self.new_text);
+        true
+    }
+}
+
+impl std::fmt::Display for Correction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[CORRECTION] {}..{}: {}",
+            self.range.start, self.range.end, self.new_text
+        )
+    }
+}
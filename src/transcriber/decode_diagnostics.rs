@@ -0,0 +1,256 @@
+//! Per-window decode diagnostics, for correlating poor output with whisper's decoder.
+//!
+//! When a decode looks unreliable, (a low average log probability, or repetitive tokens),
+//! whisper.cpp re-runs it at increasing temperatures, and returns its best attempt if every
+//! temperature fails. whisper.cpp does not report which temperature succeeded, so
+//! [DecodeDiagnostics] re-applies its checks, (with whisper.cpp's default thresholds), to the
+//! final output: [DecodeDiagnostics::fallback_exhausted] identifies windows where every
+//! temperature failed, which is where hallucinations and repetition loops usually come from.
+//!
+//! Diagnostics are sent as [WhisperOutput::DecodeDiagnostics](crate::transcriber::WhisperOutput::DecodeDiagnostics)
+//! after each realtime inference window when enabled with
+//! [WhisperRealtimeConfigs::with_decode_diagnostics](crate::whisper::configs::WhisperRealtimeConfigs::with_decode_diagnostics).
+//! Windows whose output looks broken can be re-run with a
+//! [RetryPolicy](crate::whisper::configs::RetryPolicy).
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// whisper.cpp falls back to a higher temperature when the average token log probability is
+/// below this.
+pub const LOGPROB_THRESHOLD: f32 = -1.0;
+/// whisper.cpp falls back to a higher temperature when the entropy of the last
+/// [ENTROPY_WINDOW] tokens is below this, (i.e. the output is repeating itself).
+pub const ENTROPY_THRESHOLD: f32 = 2.4;
+/// whisper.cpp treats a window as silence when the no-speech probability is above this, (and
+/// the average log probability is below [LOGPROB_THRESHOLD]).
+pub const NO_SPEECH_THRESHOLD: f32 = 0.6;
+/// Text that compresses by more than this, (see: [compression_ratio]), is likely a repetition
+/// loop.
+pub const COMPRESSION_RATIO_THRESHOLD: f32 = 2.4;
+/// The number of trailing tokens whisper.cpp measures entropy over. The entropy check only
+/// applies to outputs longer than this.
+pub const ENTROPY_WINDOW: usize = 32;
+
+// The shortest repeat worth encoding as a back-reference, (as in DEFLATE).
+const MIN_MATCH_LEN: usize = 3;
+// How far back to look for repeats.
+const MATCH_WINDOW: usize = 4096;
+
+/// The diagnostics of a single segment of an inference window.
+/// Timestamps are measured in centiseconds from the start of the window's audio.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SegmentDiagnostics {
+    pub start_time: i64,
+    pub end_time: i64,
+    /// The number of text tokens, (excluding special and timestamp tokens).
+    pub n_tokens: usize,
+    /// The mean log probability of the text tokens.
+    pub avg_logprob: f32,
+    /// The probability that the segment is silence, (0.0 - 1.0).
+    pub no_speech_probability: f32,
+    /// See: [compression_ratio].
+    pub compression_ratio: f32,
+}
+
+/// The diagnostics of an inference window, (see: [crate::transcriber::decode_diagnostics]).
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodeDiagnostics {
+    /// The duration of the window's audio.
+    pub audio_duration: Duration,
+    /// The time taken to run inference over the window, (including any fallbacks).
+    pub inference_time: Duration,
+    /// Whether previous text was used to prompt the decoder.
+    pub use_context: bool,
+    /// The number of times the window was re-run, (see:
+    /// [RetryPolicy](crate::whisper::configs::RetryPolicy)). These diagnostics describe the
+    /// last attempt.
+    pub retries: usize,
+    /// The number of text tokens decoded.
+    pub n_tokens: usize,
+    /// The mean log probability of the text tokens.
+    pub avg_logprob: f32,
+    /// The entropy of the last [ENTROPY_WINDOW] text tokens, (see: [token_entropy]).
+    pub token_entropy: f32,
+    /// See: [compression_ratio].
+    pub compression_ratio: f32,
+    pub segments: Vec<SegmentDiagnostics>,
+}
+
+impl DecodeDiagnostics {
+    /// Returns true if the window fails whisper.cpp's average log probability check.
+    pub fn low_logprob(&self) -> bool {
+        self.n_tokens > 0 && self.avg_logprob < LOGPROB_THRESHOLD
+    }
+
+    /// Returns true if the window fails whisper.cpp's entropy check, (i.e. it is repetitive).
+    pub fn repetitive(&self) -> bool {
+        self.n_tokens > ENTROPY_WINDOW && self.token_entropy < ENTROPY_THRESHOLD
+    }
+
+    /// Returns true if whisper.cpp would treat the window as silence.
+    pub fn no_speech(&self) -> bool {
+        self.low_logprob()
+            && self
+                .segments
+                .first()
+                .is_some_and(|segment| segment.no_speech_probability > NO_SPEECH_THRESHOLD)
+    }
+
+    /// Returns true if the output still fails whisper.cpp's checks, meaning every fallback
+    /// temperature failed and whisper returned its best attempt. Silence is not counted.
+    pub fn fallback_exhausted(&self) -> bool {
+        (self.low_logprob() || self.repetitive()) && !self.no_speech()
+    }
+
+    /// Returns true if the output's text compresses like a repetition loop.
+    pub fn highly_compressible(&self) -> bool {
+        self.compression_ratio > COMPRESSION_RATIO_THRESHOLD
+    }
+}
+
+impl std::fmt::Display for DecodeDiagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[DECODE] tokens: {}, avg logprob: {:.2}, entropy: {:.2}, compression ratio: {:.2}, \
+            inference: {} ms",
+            self.n_tokens,
+            self.avg_logprob,
+            self.token_entropy,
+            self.compression_ratio,
+            self.inference_time.as_millis()
+        )?;
+        if self.retries > 0 {
+            write!(f, ", retries: {}", self.retries)?;
+        }
+        if self.fallback_exhausted() {
+            f.write_str(", fallback exhausted")?;
+        }
+        Ok(())
+    }
+}
+
+/// The Shannon entropy, (in nats), of the last [ENTROPY_WINDOW] tokens, as whisper.cpp measures
+/// it. Repetitive output has low entropy.
+pub fn token_entropy(tokens: &[i32]) -> f32 {
+    let tail = &tokens[tokens.len().saturating_sub(ENTROPY_WINDOW)..];
+    let mut counts: HashMap<i32, usize> = HashMap::new();
+    for token in tail {
+        *counts.entry(*token).or_default() += 1;
+    }
+    counts
+        .values()
+        .map(|count| {
+            let p = *count as f64 / tail.len() as f64;
+            -p * p.ln()
+        })
+        .sum::<f64>() as f32
+}
+
+/// Approximates how well text compresses, (uncompressed / compressed length), with a greedy
+/// LZ77 parse: literals cost a byte and repeats cost three. Ordinary speech scores close to 1.0;
+/// repetition loops score well above [COMPRESSION_RATIO_THRESHOLD].
+///
+/// This stands in for the zlib compression ratio OpenAI's whisper uses, and reads lower than it
+/// for ordinary text, (zlib also entropy-codes literals).
+pub fn compression_ratio(text: &str) -> f32 {
+    let bytes = text.as_bytes();
+    if bytes.is_empty() {
+        return 1.0;
+    }
+    let mut cost = 0usize;
+    let mut position = 0;
+    while position < bytes.len() {
+        let longest = (position.saturating_sub(MATCH_WINDOW)..position)
+            .map(|start| {
+                // Matches may overlap the current position, (e.g. "abababab").
+                bytes[position..]
+                    .iter()
+                    .enumerate()
+                    .take_while(|(offset, byte)| bytes[start + offset] == **byte)
+                    .count()
+            })
+            .max()
+            .unwrap_or(0);
+        if longest >= MIN_MATCH_LEN {
+            cost += MIN_MATCH_LEN;
+            position += longest;
+        } else {
+            cost += 1;
+            position += 1;
+        }
+    }
+    bytes.len() as f32 / cost as f32
+}
+
+// The text tokens of a segment: (id, log probability).
+#[cfg(not(target_arch = "wasm32"))]
+fn text_tokens(
+    segment: &whisper_rs::WhisperSegment,
+    eot: whisper_rs::WhisperTokenId,
+) -> Vec<(i32, f32)> {
+    (0..segment.n_tokens())
+        .filter_map(|idx| segment.get_token(idx))
+        .map(|token| token.token_data())
+        // Special and timestamp tokens come after the end-of-text token.
+        .filter(|data| data.id < eot)
+        .map(|data| (data.id, data.plog))
+        .collect()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn mean_logprob(tokens: &[(i32, f32)]) -> f32 {
+    match tokens.len() {
+        0 => 0.0,
+        len => tokens.iter().map(|(_, plog)| plog).sum::<f32>() / len as f32,
+    }
+}
+
+// Collects the diagnostics of the last inference run on the state.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn collect_diagnostics(
+    whisper_state: &whisper_rs::WhisperState,
+    eot: whisper_rs::WhisperTokenId,
+    n_samples: usize,
+    inference_time: Duration,
+    use_context: bool,
+) -> DecodeDiagnostics {
+    let mut tokens = vec![];
+    let mut text = String::new();
+    let segments = whisper_state
+        .as_iter()
+        .map(|segment| {
+            let segment_tokens = text_tokens(&segment, eot);
+            let segment_text = segment.to_str_lossy().unwrap_or_default();
+            let diagnostics = SegmentDiagnostics {
+                start_time: segment.start_timestamp(),
+                end_time: segment.end_timestamp(),
+                n_tokens: segment_tokens.len(),
+                avg_logprob: mean_logprob(&segment_tokens),
+                no_speech_probability: segment.no_speech_probability(),
+                compression_ratio: compression_ratio(&segment_text),
+            };
+            tokens.extend(segment_tokens);
+            text.push_str(&segment_text);
+            diagnostics
+        })
+        .collect();
+
+    let ids: Vec<i32> = tokens.iter().map(|(id, _)| *id).collect();
+    DecodeDiagnostics {
+        audio_duration: Duration::from_secs_f64(
+            n_samples as f64 / crate::transcriber::WHISPER_SAMPLE_RATE,
+        ),
+        inference_time,
+        use_context,
+        retries: 0,
+        n_tokens: tokens.len(),
+        avg_logprob: mean_logprob(&tokens),
+        token_entropy: token_entropy(&ids),
+        compression_ratio: compression_ratio(&text),
+        segments,
+    }
+}
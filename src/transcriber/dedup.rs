@@ -0,0 +1,697 @@
+//! Deduplication of the text that consecutive, overlapping transcriptions share.
+//!
+//! Realtime inference windows overlap, so whisper transcribes the same words at the end of one
+//! segment and at the start of the next. These functions locate that overlap with a fuzzy
+//! (Jaro-Winkler) word match, which tolerates the small differences in spelling,
+//! capitalization and punctuation whisper produces when it sees more (or less) of a word:
+//! * [find_closest_match] finds a pair of similar words to anchor the overlap,
+//! * [run_stride] extends the anchor while the following words keep matching,
+//! * [DedupConfigs::deduplicate_strings] removes the overlap from two strings, preferring the
+//!   right-hand text,
+//! * [DedupConfigs::blend_segments] blends an overlapping segment into the previous one,
+//!   preferring the left-hand text.
+//!
+//! Only the last [DedupConfigs::n_tokens] words on the left and the first n_tokens words on the
+//! right are compared, so the cost does not grow with the length of the transcription.
+//! With [MergeStrategy::Timestamps], the segments' timestamps narrow this further to the words
+//! spoken during the time the two windows share, so a phrase the speaker repeats just before the
+//! overlap can't be mistaken for it.
+//!
+//! The library never prints its decisions. To collect tuning data, pass a [Callback] to the
+//! `_with_diagnostics` variants, (or set one on the realtime transcriber), to receive a
+//! [DedupEvent] for every boundary.
+
+use std::sync::Arc;
+
+use strsim::jaro_winkler;
+use strum::{Display, EnumString, IntoStaticStr};
+
+use crate::transcriber::{RibbleWhisperSegment, running_mean};
+use crate::utils::callback::{Callback, Nop};
+
+// Conservatively at 90% match
+// It does not do well with strings that don't share a prefix, so "has/as" will end up being an
+// artifact. Until I come up with some bulletproof, clever-er solution that runs with low latency,
+// these errors may just need to be tolerated.
+pub const DIFF_THRESHOLD_HIGH: f64 = 0.9;
+// This is not as high as the "best match" and is intended for stride heuristics.
+pub const DIFF_THRESHOLD_MED: f64 = 0.85;
+pub const DIFF_THRESHOLD_LOW: f64 = 0.75;
+/// The default number of words compared at each boundary.
+pub const N_TOKENS: usize = 5;
+/// The default number of matching words for an overlap to be trusted wherever it falls.
+pub const MIN_MATCH_WORDS: usize = 2;
+// Overlaps longer than this, (in centiseconds), are more likely to be bad timestamps than audio
+// the windows actually share.
+pub const TIMESTAMP_GAP: i64 = 1000;
+// Whisper's timestamps drift by about this much, (in centiseconds), at segment boundaries.
+pub const TIMESTAMP_EPSILON: i64 = 10;
+
+/// How the overlap between consecutive windows is located.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Display, EnumString, IntoStaticStr)]
+pub enum MergeStrategy {
+    /// Compare the words at each boundary by text similarity alone.
+    #[default]
+    TextSimilarity,
+    /// Align the windows by their segments' timestamps, and only compare the words that fall
+    /// within the time the windows share. Falls back to [MergeStrategy::TextSimilarity] when the
+    /// timestamps are missing or implausible.
+    Timestamps,
+}
+
+/// Which copy of the overlapping words is kept when two windows transcribe them differently.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Display, EnumString, IntoStaticStr)]
+pub enum ConflictPolicy {
+    /// Always keep the left-hand, (earlier), copy.
+    #[default]
+    Positional,
+    /// Keep the copy whose overlapping words whisper was more confident in, (see:
+    /// [RibbleWhisperSegment::word_confidences]), or whose whole segment it was, if the word
+    /// confidences weren't collected. Falls back to [ConflictPolicy::Positional] when either
+    /// confidence is unknown, or when only strings are being deduplicated.
+    Confidence,
+}
+
+/// The side of a boundary.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DedupSide {
+    Left,
+    Right,
+}
+
+/// Which operation produced a [DedupEvent].
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DedupStage {
+    /// [DedupConfigs::deduplicate_strings]
+    Deduplicate,
+    /// [DedupConfigs::blend_segments]
+    Blend,
+}
+
+/// The decision made at a boundary.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DedupOutcome {
+    /// No pair of compared words was similar enough to anchor an overlap.
+    NoMatch,
+    /// An overlap was found, but it was shorter than [DedupConfigs::min_match_words] and too far
+    /// from the boundary to be trusted; the text was left unchanged.
+    Rejected,
+    /// The overlap was removed, (or blended).
+    Applied,
+}
+
+/// Diagnostics for a single deduplication, for tuning [DedupConfigs] against real transcriptions.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DedupEvent {
+    pub stage: DedupStage,
+    pub outcome: DedupOutcome,
+    /// The number of overlapping words found, (0 for [DedupOutcome::NoMatch]).
+    pub match_len: usize,
+    /// The number of left-hand words after the overlap.
+    pub left_trailing: usize,
+    /// The number of right-hand words before the end of the overlap.
+    pub right_leading: usize,
+    /// The side whose copy of the overlap was kept.
+    pub kept: DedupSide,
+}
+
+/// Trait alias for callbacks that receive deduplication diagnostics.
+pub trait DedupDiagnosticsCallback: Callback<Argument = DedupEvent> + Send + 'static {}
+impl<T: Callback<Argument = DedupEvent> + Send + 'static> DedupDiagnosticsCallback for T {}
+
+/// Tuning for deduplication. The defaults are what the realtime transcriber uses.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DedupConfigs {
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_threshold"))]
+    match_threshold: f64,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_threshold"))]
+    stride_threshold: f64,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_n_tokens"))]
+    n_tokens: usize,
+    min_match_words: usize,
+    merge_strategy: MergeStrategy,
+    conflict_policy: ConflictPolicy,
+}
+
+impl DedupConfigs {
+    pub fn new() -> Self {
+        Self {
+            match_threshold: DIFF_THRESHOLD_HIGH,
+            stride_threshold: DIFF_THRESHOLD_MED,
+            n_tokens: N_TOKENS,
+            min_match_words: MIN_MATCH_WORDS,
+            merge_strategy: MergeStrategy::TextSimilarity,
+            conflict_policy: ConflictPolicy::Positional,
+        }
+    }
+
+    /// Sets the Jaro-Winkler similarity, in [0.0, 1.0], two words need to anchor an overlap.
+    /// Lower thresholds catch more of whisper's respellings, but also match unrelated words.
+    pub fn with_match_threshold(mut self, match_threshold: f64) -> Self {
+        self.match_threshold = match_threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the similarity, in [0.0, 1.0], the words after the anchor need to extend the overlap.
+    pub fn with_stride_threshold(mut self, stride_threshold: f64) -> Self {
+        self.stride_threshold = stride_threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the number of words compared at each boundary, (at least 1).
+    pub fn with_n_tokens(mut self, n_tokens: usize) -> Self {
+        self.n_tokens = n_tokens.max(1);
+        self
+    }
+
+    /// Sets the number of matching words for an overlap to be trusted wherever it falls.
+    /// Shorter overlaps are only accepted near the boundary, since a single repeated word far
+    /// from it is more likely to be a word the speaker actually repeated.
+    pub fn with_min_match_words(mut self, min_match_words: usize) -> Self {
+        self.min_match_words = min_match_words;
+        self
+    }
+
+    /// Sets how the realtime transcriber locates the overlap between consecutive windows.
+    pub fn with_merge_strategy(mut self, merge_strategy: MergeStrategy) -> Self {
+        self.merge_strategy = merge_strategy;
+        self
+    }
+
+    /// Sets which copy of the overlap is kept when segments from two windows disagree on it.
+    pub fn with_conflict_policy(mut self, conflict_policy: ConflictPolicy) -> Self {
+        self.conflict_policy = conflict_policy;
+        self
+    }
+
+    pub fn match_threshold(&self) -> f64 {
+        self.match_threshold
+    }
+
+    pub fn stride_threshold(&self) -> f64 {
+        self.stride_threshold
+    }
+
+    pub fn n_tokens(&self) -> usize {
+        self.n_tokens
+    }
+
+    pub fn min_match_words(&self) -> usize {
+        self.min_match_words
+    }
+
+    pub fn merge_strategy(&self) -> MergeStrategy {
+        self.merge_strategy
+    }
+
+    pub fn conflict_policy(&self) -> ConflictPolicy {
+        self.conflict_policy
+    }
+
+    /// Removes the overlap between the end of str1 and the start of str2, so that joining the
+    /// results with a space gives the text once. str1 is cut off just after the overlapping
+    /// words, and str2's words up to the end of the overlap are dropped: anything after the
+    /// overlap comes from str2, which usually has better punctuation since whisper saw more
+    /// audio when transcribing it. The overlap itself is kept from str1.
+    /// # Returns:
+    /// * Some((left, right)), the remaining words of str1 and str2
+    /// * None if no overlap was found, (or the only overlap is a single word far from the
+    ///   boundary)
+    pub fn deduplicate_strings(&self, str1: &str, str2: &str) -> Option<(String, String)> {
+        self.deduplicate_strings_with_diagnostics(str1, str2, &mut Nop::new())
+    }
+
+    /// Runs [Self::deduplicate_strings], reporting the decision to diagnostics.
+    pub fn deduplicate_strings_with_diagnostics(
+        &self,
+        str1: &str,
+        str2: &str,
+        diagnostics: &mut impl Callback<Argument = DedupEvent>,
+    ) -> Option<(String, String)> {
+        self.deduplicate_words(
+            str1,
+            str2,
+            (self.n_tokens, self.n_tokens),
+            DedupSide::Left,
+            diagnostics,
+        )
+    }
+
+    /// Runs [Self::deduplicate_strings] on two segments, keeping the copy of the overlap chosen
+    /// by the [ConflictPolicy].
+    pub fn deduplicate_segments(
+        &self,
+        l_segment: &RibbleWhisperSegment,
+        r_segment: &RibbleWhisperSegment,
+        diagnostics: &mut impl Callback<Argument = DedupEvent>,
+    ) -> Option<(String, String)> {
+        self.deduplicate_words(
+            l_segment.text(),
+            r_segment.text(),
+            (self.n_tokens, self.n_tokens),
+            self.resolve_conflict(l_segment, r_segment, (self.n_tokens, self.n_tokens)),
+            diagnostics,
+        )
+    }
+
+    /// Runs [Self::deduplicate_segments] on segments from consecutive windows, aligned by their
+    /// timestamps: only the words spoken during the time the windows share are compared.
+    /// r_offset is where r_segment's window starts on l_segment's timeline, in centiseconds.
+    /// Falls back to comparing the usual [Self::n_tokens] words if the timestamps are unusable.
+    pub fn deduplicate_segments_aligned(
+        &self,
+        l_segment: &RibbleWhisperSegment,
+        r_segment: &RibbleWhisperSegment,
+        r_offset: i64,
+        diagnostics: &mut impl Callback<Argument = DedupEvent>,
+    ) -> Option<(String, String)> {
+        let n_words = match self.align(l_segment, r_segment, r_offset) {
+            Alignment::Disjoint => {
+                diagnostics.call(DedupEvent::no_match(DedupStage::Deduplicate));
+                return None;
+            }
+            Alignment::Overlap(l_n, r_n) => (l_n, r_n),
+            Alignment::Unreliable => (self.n_tokens, self.n_tokens),
+        };
+        self.deduplicate_words(
+            l_segment.text(),
+            r_segment.text(),
+            n_words,
+            self.resolve_conflict(l_segment, r_segment, n_words),
+            diagnostics,
+        )
+    }
+
+    // Deduplicates the last n_words.0 words of str1 against the first n_words.1 words of str2,
+    // keeping the overlap from the given side.
+    fn deduplicate_words(
+        &self,
+        str1: &str,
+        str2: &str,
+        n_words: (usize, usize),
+        keep: DedupSide,
+        diagnostics: &mut impl Callback<Argument = DedupEvent>,
+    ) -> Option<(String, String)> {
+        let (mut l_buf, mut r_buf) = split_text(str1, str2, n_words);
+        let (l_start, r_end) = compared_ranges(&l_buf, &r_buf, n_words);
+        let mut event = DedupEvent::no_match(DedupStage::Deduplicate);
+
+        let Some((l_match, r_match)) =
+            find_closest_match(&l_buf[l_start..], &r_buf[..r_end], self.match_threshold)
+        else {
+            diagnostics.call(event);
+            return None;
+        };
+        let l_match_start = l_start + l_match;
+        let (l_match_end, r_match_end) = run_stride(
+            &l_buf,
+            l_match_start,
+            &r_buf,
+            r_match,
+            self.stride_threshold,
+        );
+
+        let num_words = l_match_end - l_match_start;
+        event.match_len = num_words;
+        event.left_trailing = l_buf.len() - l_match_end;
+        event.right_leading = r_match_end;
+        if num_words < self.min_match_words {
+            let l_midpoint = (l_start + (l_buf.len() - 1)) / 2;
+            let r_midpoint = (r_end - 1) / 2;
+            // If the match isn't close to where the segments would naturally fall,
+            // they are likely to be different strings that just happen to have repeated words.
+            // Err on the side of caution and accept a small amount of error to avoid accidentally
+            // clobbering the transcription on a false-positive.
+            if l_match_end < l_midpoint && r_match_end > r_midpoint {
+                event.outcome = DedupOutcome::Rejected;
+                diagnostics.call(event);
+                return None;
+            }
+        }
+
+        event.outcome = DedupOutcome::Applied;
+        event.kept = keep;
+        diagnostics.call(event);
+        match keep {
+            DedupSide::Left => {
+                // Confirm up to just before the end of the match on the left.
+                l_buf.truncate(l_match_end);
+                // Drop up to just before the end of the match on the right.
+                drop(r_buf.drain(..r_match_end.min(r_buf.len())));
+            }
+            DedupSide::Right => {
+                // Confirm up to the start of the match on the left, and take the rest from the
+                // right.
+                l_buf.truncate(l_match_start);
+                drop(r_buf.drain(..r_match));
+            }
+        }
+        Some((l_buf.join(" "), r_buf.join(" ")))
+    }
+
+    /// Blends the end of l_segment with the start of r_segment by cutting l_segment off one word
+    /// past their overlap. The left copy of the overlap is kept, in case whisper cut off
+    /// r_segment's first words, unless the [ConflictPolicy] prefers the right copy: that
+    /// replaces the overlap, (and the word after it), with r_segment's.
+    /// l_segment is unchanged if no overlap was found, (or the only overlap is a single word in
+    /// the first half of the compared words). r_segment is never modified.
+    pub fn blend_segments(
+        &self,
+        l_segment: &mut RibbleWhisperSegment,
+        r_segment: &RibbleWhisperSegment,
+    ) {
+        self.blend_segments_with_diagnostics(l_segment, r_segment, &mut Nop::new())
+    }
+
+    /// Runs [Self::blend_segments], reporting the decision to diagnostics.
+    pub fn blend_segments_with_diagnostics(
+        &self,
+        l_segment: &mut RibbleWhisperSegment,
+        r_segment: &RibbleWhisperSegment,
+        diagnostics: &mut impl Callback<Argument = DedupEvent>,
+    ) {
+        let n_words = (self.n_tokens, self.n_tokens);
+        let keep = self.resolve_conflict(l_segment, r_segment, n_words);
+        self.blend_words(l_segment, r_segment, n_words, keep, diagnostics)
+    }
+
+    /// Runs [Self::blend_segments] on segments from consecutive windows, aligned by their
+    /// timestamps: only the words spoken during the time the windows share are compared.
+    /// r_offset is where r_segment's window starts on l_segment's timeline, in centiseconds.
+    /// Falls back to comparing the usual [Self::n_tokens] words if the timestamps are unusable.
+    pub fn blend_segments_aligned(
+        &self,
+        l_segment: &mut RibbleWhisperSegment,
+        r_segment: &RibbleWhisperSegment,
+        r_offset: i64,
+        diagnostics: &mut impl Callback<Argument = DedupEvent>,
+    ) {
+        let n_words = match self.align(l_segment, r_segment, r_offset) {
+            Alignment::Disjoint => {
+                diagnostics.call(DedupEvent::no_match(DedupStage::Blend));
+                return;
+            }
+            Alignment::Overlap(l_n, r_n) => (l_n, r_n),
+            Alignment::Unreliable => (self.n_tokens, self.n_tokens),
+        };
+        let keep = self.resolve_conflict(l_segment, r_segment, n_words);
+        self.blend_words(l_segment, r_segment, n_words, keep, diagnostics)
+    }
+
+    // Blends the last n_words.0 words of l_segment with the first n_words.1 words of r_segment,
+    // keeping the overlap from the given side.
+    fn blend_words(
+        &self,
+        l_segment: &mut RibbleWhisperSegment,
+        r_segment: &RibbleWhisperSegment,
+        n_words: (usize, usize),
+        keep: DedupSide,
+        diagnostics: &mut impl Callback<Argument = DedupEvent>,
+    ) {
+        let (mut l_buf, r_buf) = split_text(l_segment.text(), r_segment.text(), n_words);
+        let (l_start, r_end) = compared_ranges(&l_buf, &r_buf, n_words);
+        let mut event = DedupEvent::no_match(DedupStage::Blend);
+
+        let Some((l_match, r_match)) =
+            find_closest_match(&l_buf[l_start..], &r_buf[..r_end], self.match_threshold)
+        else {
+            diagnostics.call(event);
+            return;
+        };
+        let l_match_start = l_start + l_match;
+        let (l_match_end, r_match_end) = run_stride(
+            &l_buf,
+            l_match_start,
+            &r_buf,
+            r_match,
+            self.stride_threshold,
+        );
+
+        let num_words = l_match_end - l_match_start;
+        event.match_len = num_words;
+        event.left_trailing = l_buf.len() - l_match_end;
+        event.right_leading = r_match_end;
+        // Unlike deduplication, a short match is rejected whenever it falls in the first half
+        // of the left words.
+        if num_words < self.min_match_words {
+            let l_midpoint = (l_start + (l_buf.len() - 1)) / 2;
+            if l_match_end < l_midpoint {
+                event.outcome = DedupOutcome::Rejected;
+                diagnostics.call(event);
+                return;
+            }
+        }
+
+        event.outcome = DedupOutcome::Applied;
+        event.kept = keep;
+        diagnostics.call(event);
+        let (l_kept, r_taken) = match keep {
+            // Confirm up to the end of the match on the left, and the word after it.
+            DedupSide::Left => ((l_match_end + 1).min(l_buf.len()), r_match..r_match),
+            // Swap in the right's copy of the match, and the word after it if it was compared,
+            // (past that, the right buffer holds the rest of the segment as one item).
+            DedupSide::Right => (l_match_start, r_match..(r_match_end + 1).min(r_end)),
+        };
+        let n_dropped = count_words(&l_buf[l_kept..]);
+        let r_words = count_words(&r_buf[..r_taken.start])..count_words(&r_buf[..r_taken.end]);
+        l_buf.truncate(l_kept);
+        l_buf.extend(&r_buf[r_taken]);
+        let text = Arc::from(l_buf.join(" ").trim());
+        l_segment.replace_text(text);
+        splice_word_confidences(l_segment, n_dropped, r_segment, r_words);
+    }
+
+    // The side whose copy of an overlap to keep: under the confidence policy, the side whose
+    // overlapping words, (the last n_words.0 words on the left, and the first n_words.1 words on
+    // the right), whisper was more confident in.
+    fn resolve_conflict(
+        &self,
+        l_segment: &RibbleWhisperSegment,
+        r_segment: &RibbleWhisperSegment,
+        n_words: (usize, usize),
+    ) -> DedupSide {
+        if self.conflict_policy != ConflictPolicy::Confidence {
+            return DedupSide::Left;
+        }
+        match (
+            trailing_confidence(l_segment, n_words.0),
+            leading_confidence(r_segment, n_words.1),
+        ) {
+            (Some(l_confidence), Some(r_confidence)) if r_confidence > l_confidence => {
+                DedupSide::Right
+            }
+            _ => DedupSide::Left,
+        }
+    }
+
+    // Estimates how many words of each segment were spoken during the time the two windows share,
+    // from the length of that time relative to each segment's duration. Each estimate is padded by
+    // a word for timestamp drift, and capped at n_tokens.
+    fn align(
+        &self,
+        l_segment: &RibbleWhisperSegment,
+        r_segment: &RibbleWhisperSegment,
+        r_offset: i64,
+    ) -> Alignment {
+        let l_duration = l_segment.end_time - l_segment.start_time;
+        let r_duration = r_segment.end_time - r_segment.start_time;
+        if l_duration <= 0 || r_duration <= 0 {
+            return Alignment::Unreliable;
+        }
+
+        let overlap = l_segment.end_time - (r_segment.start_time + r_offset);
+        if overlap < -TIMESTAMP_EPSILON {
+            return Alignment::Disjoint;
+        }
+        if overlap > TIMESTAMP_GAP {
+            return Alignment::Unreliable;
+        }
+
+        let overlap = overlap.clamp(0, l_duration.min(r_duration)) as usize;
+        let words_within = |text: &str, duration: i64| {
+            let n_words = text.split(' ').count();
+            (n_words * overlap)
+                .div_ceil(duration as usize)
+                .saturating_add(1)
+                .min(n_words)
+                .min(self.n_tokens)
+        };
+        Alignment::Overlap(
+            words_within(l_segment.text(), l_duration),
+            words_within(r_segment.text(), r_duration),
+        )
+    }
+}
+
+// Deserializing skips the setters, so values are clamped the same way they would be there.
+#[cfg(feature = "serde")]
+fn deserialize_threshold<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<f64, D::Error> {
+    <f64 as serde::Deserialize>::deserialize(deserializer)
+        .map(|threshold| threshold.clamp(0.0, 1.0))
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_n_tokens<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<usize, D::Error> {
+    <usize as serde::Deserialize>::deserialize(deserializer).map(|n_tokens| n_tokens.max(1))
+}
+
+impl Default for DedupConfigs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DedupEvent {
+    fn no_match(stage: DedupStage) -> Self {
+        Self {
+            stage,
+            outcome: DedupOutcome::NoMatch,
+            match_len: 0,
+            left_trailing: 0,
+            right_leading: 0,
+            kept: DedupSide::Left,
+        }
+    }
+}
+
+// The mean confidence of a segment's last n words, (or of the whole segment, if its word
+// confidences weren't collected).
+fn trailing_confidence(segment: &RibbleWhisperSegment, n: usize) -> Option<f32> {
+    match segment.word_confidences.as_deref() {
+        Some(words) => running_mean(words[words.len().saturating_sub(n)..].iter().copied()),
+        None => segment.confidence,
+    }
+}
+
+// The mean confidence of a segment's first n words, (or of the whole segment, if its word
+// confidences weren't collected).
+fn leading_confidence(segment: &RibbleWhisperSegment, n: usize) -> Option<f32> {
+    match segment.word_confidences.as_deref() {
+        Some(words) => running_mean(words.iter().take(n).copied()),
+        None => segment.confidence,
+    }
+}
+
+// The number of words in split text, (splitting on spaces leaves empty items around extra spaces).
+fn count_words(words: &[&str]) -> usize {
+    words.iter().filter(|word| !word.is_empty()).count()
+}
+
+// Keeps a blended segment's word confidences in step with its text: drops the confidences of
+// its last n_dropped words, and appends those of r_segment's words in r_words. The confidences
+// become unknown if r_segment's are.
+fn splice_word_confidences(
+    l_segment: &mut RibbleWhisperSegment,
+    n_dropped: usize,
+    r_segment: &RibbleWhisperSegment,
+    r_words: std::ops::Range<usize>,
+) {
+    let Some(l_words) = l_segment.word_confidences.as_deref() else {
+        return;
+    };
+    if n_dropped == 0 && r_words.is_empty() {
+        return;
+    }
+    let kept = &l_words[..l_words.len().saturating_sub(n_dropped)];
+    let taken = match r_segment.word_confidences.as_deref() {
+        _ if r_words.is_empty() => Some(&[][..]),
+        Some(r_confidences) => r_confidences.get(r_words),
+        None => None,
+    };
+    l_segment.word_confidences =
+        taken.map(|taken| kept.iter().chain(taken).copied().collect::<Arc<[f32]>>());
+}
+
+// Where two segments from consecutive windows overlap in time.
+enum Alignment {
+    // The timestamps are missing, (zero-length segments), or the overlap is implausibly long.
+    Unreliable,
+    // The right segment starts after the left segment ends.
+    Disjoint,
+    // The number of words of each segment within the overlap.
+    Overlap(usize, usize),
+}
+
+// The last n_words.0 words of str1, (preceded by the rest of str1 as one item), and the first
+// n_words.1 words of str2, (followed by the rest of str2).
+fn split_text<'a>(
+    str1: &'a str,
+    str2: &'a str,
+    n_words: (usize, usize),
+) -> (Vec<&'a str>, Vec<&'a str>) {
+    let mut l_buf = str1.rsplitn(n_words.0 + 1, ' ').collect::<Vec<_>>();
+    l_buf.reverse();
+    let r_buf = str2.splitn(n_words.1 + 1, ' ').collect::<Vec<_>>();
+    (l_buf, r_buf)
+}
+
+// The start of the compared words on the left, skipping the rest of str1 if it was split off, and
+// the end of the compared words on the right.
+fn compared_ranges(l_buf: &[&str], r_buf: &[&str], n_words: (usize, usize)) -> (usize, usize) {
+    let l_start = if l_buf.len() == n_words.0 + 1 { 1 } else { 0 };
+    (l_start, n_words.1.min(r_buf.len()))
+}
+
+/// Finds the pair of words, one from each buffer, that anchors their overlap: the last word in
+/// buf1 with a match in buf2 of at least threshold similarity, paired with its most similar word
+/// in buf2, (the later one on ties). Preferring later words matches repeated words in the speech
+/// as close to the boundary as possible.
+/// # Returns:
+/// * Some((buf1_index, buf2_index)), or None if no pair of words is similar enough
+pub fn find_closest_match(buf1: &[&str], buf2: &[&str], threshold: f64) -> Option<(usize, usize)> {
+    let mut best = None;
+    for (idx, l_token) in buf1.iter().enumerate() {
+        let mut max_score = 0.0;
+        for (jdx, r_token) in buf2.iter().enumerate() {
+            let similar = jaro_winkler(l_token, r_token);
+            if similar >= threshold && similar >= max_score {
+                best = Some((idx, jdx));
+                max_score = similar;
+            }
+        }
+    }
+    best
+}
+
+/// Walks both buffers forward from buf1_start and buf2_start while their words are at least
+/// threshold similar.
+/// # Returns:
+/// * The indices just past the last matching pair of words; the matching run covers
+///   buf1[buf1_start..end.0] and buf2[buf2_start..end.1], which are the same length
+pub fn run_stride(
+    buf1: &[&str],
+    buf1_start: usize,
+    buf2: &[&str],
+    buf2_start: usize,
+    threshold: f64,
+) -> (usize, usize) {
+    let run = buf1
+        .iter()
+        .skip(buf1_start)
+        .zip(buf2.iter().skip(buf2_start))
+        .take_while(|(l_token, r_token)| jaro_winkler(l_token, r_token) >= threshold)
+        .count();
+    (buf1_start + run, buf2_start + run)
+}
+
+/// Runs [DedupConfigs::deduplicate_strings] with the default configurations.
+pub fn deduplicate_strings(str1: &str, str2: &str) -> Option<(String, String)> {
+    DedupConfigs::default().deduplicate_strings(str1, str2)
+}
+
+/// Runs [DedupConfigs::blend_segments] with the default configurations.
+pub fn blend_segments(l_segment: &mut RibbleWhisperSegment, r_segment: &RibbleWhisperSegment) {
+    DedupConfigs::default().blend_segments(l_segment, r_segment)
+}
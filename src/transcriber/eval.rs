@@ -0,0 +1,251 @@
+//! Accuracy evaluation against reference transcripts: word error rate, (WER), character error
+//! rate, (CER), and the alignments behind them.
+//!
+//! Both rates are the number of substitutions, deletions and insertions needed to turn the
+//! reference into the hypothesis, divided by the length of the reference; lower is better, and
+//! a transcript with many insertions can score above 1.0. Text is normalized before it is
+//! compared, (see: [Evaluator]), so that formatting differences, (e.g. "Hello," against
+//! "hello"), are not counted as errors.
+//!
+//! To benchmark over a corpus, sum the [ErrorCounts] of each utterance rather than averaging
+//! their rates, so that longer utterances carry their proper weight.
+
+use crate::postprocess::diff::{AlignOp, TranscriptDiff, TranscriptDiffer, align};
+
+/// The edits that turn a reference into a hypothesis, (see: [crate::transcriber::eval]).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorCounts {
+    pub substitutions: usize,
+    pub deletions: usize,
+    pub insertions: usize,
+    /// The number of reference tokens, (words or characters).
+    pub reference_len: usize,
+}
+
+impl ErrorCounts {
+    /// The number of errors: substitutions, deletions and insertions.
+    pub fn errors(&self) -> usize {
+        self.substitutions + self.deletions + self.insertions
+    }
+
+    /// The number of reference tokens that were transcribed correctly.
+    pub fn hits(&self) -> usize {
+        self.reference_len
+            .saturating_sub(self.substitutions + self.deletions)
+    }
+
+    /// The error rate, (errors per reference token). An empty reference scores 0.0 against an
+    /// empty hypothesis and 1.0 otherwise.
+    pub fn rate(&self) -> f64 {
+        match (self.reference_len, self.errors()) {
+            (0, 0) => 0.0,
+            (0, _) => 1.0,
+            (len, errors) => errors as f64 / len as f64,
+        }
+    }
+
+    /// The accuracy, (1.0 - rate), clamped to 0.0.
+    pub fn accuracy(&self) -> f64 {
+        (1.0 - self.rate()).max(0.0)
+    }
+
+    fn from_ops(ops: &[AlignOp], reference_len: usize) -> Self {
+        let count = |kind: AlignOp| ops.iter().filter(|op| **op == kind).count();
+        Self {
+            substitutions: count(AlignOp::Substitution),
+            deletions: count(AlignOp::Deletion),
+            insertions: count(AlignOp::Insertion),
+            reference_len,
+        }
+    }
+}
+
+impl std::ops::Add for ErrorCounts {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            substitutions: self.substitutions + rhs.substitutions,
+            deletions: self.deletions + rhs.deletions,
+            insertions: self.insertions + rhs.insertions,
+            reference_len: self.reference_len + rhs.reference_len,
+        }
+    }
+}
+
+impl std::ops::AddAssign for ErrorCounts {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::iter::Sum for ErrorCounts {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |sum, counts| sum + counts)
+    }
+}
+
+/// The evaluation of a hypothesis against a reference, (see: [Evaluator::evaluate]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Evaluation {
+    words: ErrorCounts,
+    characters: ErrorCounts,
+    alignment: TranscriptDiff,
+}
+
+impl Evaluation {
+    /// The word-level error counts.
+    pub fn words(&self) -> ErrorCounts {
+        self.words
+    }
+    /// The character-level error counts.
+    pub fn characters(&self) -> ErrorCounts {
+        self.characters
+    }
+    pub fn word_error_rate(&self) -> f64 {
+        self.words.rate()
+    }
+    pub fn character_error_rate(&self) -> f64 {
+        self.characters.rate()
+    }
+    /// The word alignment of the normalized reference and hypothesis.
+    pub fn alignment(&self) -> &TranscriptDiff {
+        &self.alignment
+    }
+}
+
+/// Computes error rates of transcripts against reference text.
+///
+/// By default, text is lower-cased, punctuation is removed, (apostrophes within words are kept,
+/// e.g. "don't"), and whitespace is collapsed before comparison. Apply any further normalization,
+/// (e.g. [crate::postprocess::itn] to both sides), before evaluating.
+#[derive(Copy, Clone, Debug)]
+pub struct Evaluator {
+    case_sensitive: bool,
+    keep_punctuation: bool,
+}
+
+impl Evaluator {
+    pub fn new() -> Self {
+        Self {
+            case_sensitive: false,
+            keep_punctuation: false,
+        }
+    }
+
+    /// Toggles counting differences in case as errors.
+    pub fn with_case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Toggles keeping punctuation, (and so counting differences in it as errors).
+    pub fn with_keep_punctuation(mut self, keep_punctuation: bool) -> Self {
+        self.keep_punctuation = keep_punctuation;
+        self
+    }
+
+    pub fn case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    pub fn keep_punctuation(&self) -> bool {
+        self.keep_punctuation
+    }
+
+    /// Normalizes text as it is compared.
+    pub fn normalize(&self, text: &str) -> String {
+        let cased = if self.case_sensitive {
+            text.to_string()
+        } else {
+            text.to_lowercase()
+        };
+        if self.keep_punctuation {
+            return cased.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+
+        let chars: Vec<char> = cased.chars().collect();
+        let stripped: String = chars
+            .iter()
+            .enumerate()
+            .map(|(index, &c)| {
+                let within_word = index > 0
+                    && chars[index - 1].is_alphanumeric()
+                    && chars.get(index + 1).is_some_and(|c| c.is_alphanumeric());
+                match c {
+                    '\'' | '’' if within_word => c,
+                    _ if c.is_alphanumeric() || c.is_whitespace() => c,
+                    // Punctuation separates words, (e.g. "well-known"), like whitespace.
+                    _ => ' ',
+                }
+            })
+            .collect();
+        stripped.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    /// The word-level error counts of a hypothesis.
+    pub fn word_errors(&self, reference: &str, hypothesis: &str) -> ErrorCounts {
+        let reference = self.normalize(reference);
+        let hypothesis = self.normalize(hypothesis);
+        let reference: Vec<&str> = reference.split_whitespace().collect();
+        let hypothesis: Vec<&str> = hypothesis.split_whitespace().collect();
+        ErrorCounts::from_ops(
+            &align(&reference, &hypothesis, |a, b| a == b),
+            reference.len(),
+        )
+    }
+
+    /// The character-level error counts of a hypothesis. Spaces between words count as
+    /// characters.
+    pub fn character_errors(&self, reference: &str, hypothesis: &str) -> ErrorCounts {
+        let reference: Vec<char> = self.normalize(reference).chars().collect();
+        let hypothesis: Vec<char> = self.normalize(hypothesis).chars().collect();
+        ErrorCounts::from_ops(
+            &align(&reference, &hypothesis, |a, b| a == b),
+            reference.len(),
+        )
+    }
+
+    pub fn word_error_rate(&self, reference: &str, hypothesis: &str) -> f64 {
+        self.word_errors(reference, hypothesis).rate()
+    }
+
+    pub fn character_error_rate(&self, reference: &str, hypothesis: &str) -> f64 {
+        self.character_errors(reference, hypothesis).rate()
+    }
+
+    /// Computes both error rates and the word alignment, (to inspect what was mis-transcribed).
+    pub fn evaluate(&self, reference: &str, hypothesis: &str) -> Evaluation {
+        let alignment = TranscriptDiffer::new()
+            .with_case_sensitive(true)
+            .with_punctuation_sensitive(true)
+            .diff_text(&self.normalize(reference), &self.normalize(hypothesis));
+        let words = ErrorCounts {
+            substitutions: alignment.substitutions(),
+            deletions: alignment.deletions(),
+            insertions: alignment.insertions(),
+            reference_len: alignment.reference_len(),
+        };
+        Evaluation {
+            words,
+            characters: self.character_errors(reference, hypothesis),
+            alignment,
+        }
+    }
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The word error rate of a hypothesis, with the default normalization, (see: [Evaluator]).
+pub fn word_error_rate(reference: &str, hypothesis: &str) -> f64 {
+    Evaluator::new().word_error_rate(reference, hypothesis)
+}
+
+/// The character error rate of a hypothesis, with the default normalization, (see:
+/// [Evaluator]).
+pub fn character_error_rate(reference: &str, hypothesis: &str) -> f64 {
+    Evaluator::new().character_error_rate(reference, hypothesis)
+}
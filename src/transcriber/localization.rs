@@ -0,0 +1,64 @@
+//! Localization of the user-facing text the transcriber's outputs are displayed with.
+//!
+//! Control phrases and audio event markers display in English, (e.g. "[START SPEAKING]",
+//! "[applause]"). A [MessageLocalizer] supplies the text in the user's language instead; the
+//! sinks that render outputs as text for users consult one when it is attached, (e.g.
+//! [CaptionServer::with_localizer](crate::export::caption_server::CaptionServer::with_localizer)),
+//! and apps rendering outputs themselves can call [WhisperControlPhrase::localized] and
+//! [AudioEventKind::localized].
+//!
+//! Localizers should match on the variant or its [ControlCode](crate::transcriber::ControlCode),
+//! never on the English text.
+
+use std::sync::Arc;
+
+use crate::transcriber::WhisperControlPhrase;
+use crate::transcriber::audio_events::AudioEventKind;
+
+/// Supplies the text shown to users for transcriber outputs. Return None to fall back to the
+/// English text, so a localizer only needs to cover the messages it translates.
+pub trait MessageLocalizer: Send + Sync + 'static {
+    /// The text shown for a control phrase.
+    fn control_phrase(&self, _phrase: &WhisperControlPhrase) -> Option<String> {
+        None
+    }
+
+    /// The caption marker shown for an audio event, (e.g. "[applause]").
+    fn audio_event(&self, _kind: &AudioEventKind) -> Option<String> {
+        None
+    }
+}
+
+/// The default [MessageLocalizer]: English, (i.e. each output's Display).
+#[derive(Copy, Clone, Debug, Default)]
+pub struct EnglishLocalizer;
+
+impl MessageLocalizer for EnglishLocalizer {}
+
+impl<L: MessageLocalizer + ?Sized> MessageLocalizer for Arc<L> {
+    fn control_phrase(&self, phrase: &WhisperControlPhrase) -> Option<String> {
+        self.as_ref().control_phrase(phrase)
+    }
+
+    fn audio_event(&self, kind: &AudioEventKind) -> Option<String> {
+        self.as_ref().audio_event(kind)
+    }
+}
+
+impl WhisperControlPhrase {
+    /// The text shown for the phrase, from the localizer or in English.
+    pub fn localized(&self, localizer: &dyn MessageLocalizer) -> String {
+        localizer
+            .control_phrase(self)
+            .unwrap_or_else(|| self.to_string())
+    }
+}
+
+impl AudioEventKind {
+    /// The caption marker shown for the event, from the localizer or in English.
+    pub fn localized(&self, localizer: &dyn MessageLocalizer) -> String {
+        localizer
+            .audio_event(self)
+            .unwrap_or_else(|| self.to_string())
+    }
+}
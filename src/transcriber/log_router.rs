@@ -0,0 +1,282 @@
+//! Routing for whisper.cpp and GGML log output.
+//!
+//! By default whisper.cpp prints to stderr. [redirect_whisper_logging_to_hooks](crate::transcriber::redirect_whisper_logging_to_hooks)
+//! sends everything to `log`/`tracing`, (with the log_backend/tracing_backend features), or
+//! nowhere. A [WhisperLogRouter] instead decides per message: it filters by level, can remap
+//! levels, (e.g. demoting whisper's chatty model-loading info to debug), forwards to
+//! `log`/`tracing` when either is enabled, and can hand messages to a callback, (e.g. for an
+//! app's diagnostics panel).
+//!
+//! Messages are logged under the "whisper_cpp" and "ggml" targets.
+
+use std::ffi::{CStr, c_char, c_void};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use parking_lot::Mutex;
+use strum::{AsRefStr, Display, EnumIter, EnumString, IntoStaticStr};
+
+use crate::utils::callback::{Callback, panic_message};
+use crate::utils::logging::log_warn_or_eprint;
+
+// The installed router; None until the first call to install().
+static ROUTER: Mutex<Option<WhisperLogRouter>> = parking_lot::const_mutex(None);
+
+/// The severity of a whisper.cpp/GGML log message, (from least to most severe).
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    AsRefStr,
+    Display,
+    EnumIter,
+    EnumString,
+    IntoStaticStr,
+)]
+pub enum WhisperLogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// The library a log message came from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, AsRefStr, Display, EnumString, IntoStaticStr)]
+pub enum WhisperLogSource {
+    /// whisper.cpp, (e.g. model loading, decoding).
+    Whisper,
+    /// GGML, (e.g. backend selection, buffer allocation).
+    Ggml,
+}
+
+impl WhisperLogSource {
+    /// The log target messages are emitted under.
+    pub fn target(&self) -> &'static str {
+        match self {
+            Self::Whisper => "whisper_cpp",
+            Self::Ggml => "ggml",
+        }
+    }
+}
+
+/// A log message from whisper.cpp or GGML, after level remapping.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WhisperLogMessage {
+    pub level: WhisperLogLevel,
+    pub source: WhisperLogSource,
+    /// The message, without its trailing newline.
+    pub text: String,
+}
+
+/// Routes whisper.cpp and GGML log output, (see: [crate::transcriber::log_router]).
+///
+/// Only one router is active at a time; [WhisperLogRouter::install] replaces the previous one.
+/// Messages are delivered on whichever thread whisper.cpp logs from, (usually the transcription
+/// thread), so the callback should be quick and must not install a router itself.
+pub struct WhisperLogRouter {
+    min_level: Option<WhisperLogLevel>,
+    remaps: Vec<(WhisperLogLevel, Option<WhisperLogLevel>)>,
+    forward: bool,
+    callback: Option<Box<dyn Callback<Argument = WhisperLogMessage> + Send>>,
+    // The level of the previous message, for GGML continuation lines.
+    previous_level: Option<WhisperLogLevel>,
+}
+
+impl WhisperLogRouter {
+    /// Creates a router that forwards info and above to `log`/`tracing`, (when enabled).
+    pub fn new() -> Self {
+        Self {
+            min_level: Some(WhisperLogLevel::Info),
+            remaps: vec![],
+            forward: true,
+            callback: None,
+            previous_level: None,
+        }
+    }
+
+    /// Sets the least severe level that is routed, (after remapping). None silences whisper.cpp
+    /// entirely.
+    pub fn with_min_level(mut self, min_level: Option<WhisperLogLevel>) -> Self {
+        self.min_level = min_level;
+        self
+    }
+
+    /// Routes messages logged at one level as another, (e.g. Info to Debug). Remapping to None
+    /// drops them.
+    pub fn with_remap(mut self, from: WhisperLogLevel, to: Option<WhisperLogLevel>) -> Self {
+        self.remaps.retain(|(level, _)| *level != from);
+        self.remaps.push((from, to));
+        self
+    }
+
+    /// Toggles forwarding to `log`/`tracing`. Without the ribble-logging or tracing features,
+    /// nothing is forwarded regardless.
+    pub fn with_forwarding(mut self, forward: bool) -> Self {
+        self.forward = forward;
+        self
+    }
+
+    /// Sets a callback to receive every routed message.
+    pub fn with_callback<C>(mut self, callback: C) -> Self
+    where
+        C: Callback<Argument = WhisperLogMessage> + Send + 'static,
+    {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    pub fn min_level(&self) -> Option<WhisperLogLevel> {
+        self.min_level
+    }
+
+    pub fn forwarding(&self) -> bool {
+        self.forward
+    }
+
+    /// The level a message logged at the given level is routed at, or None if it is dropped.
+    pub fn route_level(&self, level: WhisperLogLevel) -> Option<WhisperLogLevel> {
+        let level = self
+            .remaps
+            .iter()
+            .find(|(from, _)| *from == level)
+            .map_or(Some(level), |(_, to)| *to)?;
+        self.min_level.filter(|min| level >= *min).map(|_| level)
+    }
+
+    /// Installs the router, replacing the previous one, and redirects whisper.cpp and GGML
+    /// logging to it. This can be called at any time, (e.g. when a user changes the log level).
+    pub fn install(self) {
+        *ROUTER.lock() = Some(self);
+        unsafe {
+            whisper_rs_sys::whisper_log_set(Some(whisper_log_trampoline), std::ptr::null_mut());
+            whisper_rs_sys::ggml_log_set(Some(ggml_log_trampoline), std::ptr::null_mut());
+        }
+    }
+
+    fn route(&mut self, level: Option<WhisperLogLevel>, source: WhisperLogSource, text: &str) {
+        // Continuation lines have no level of their own.
+        let Some(level) = level.or(self.previous_level) else {
+            return;
+        };
+        self.previous_level = Some(level);
+        let Some(level) = self.route_level(level) else {
+            return;
+        };
+        let text = text.trim_end_matches(['\n', '\r']);
+        if text.trim().is_empty() {
+            return;
+        }
+
+        if self.forward {
+            forward(level, source, text);
+        }
+        if let Some(callback) = self.callback.as_mut() {
+            let message = WhisperLogMessage {
+                level,
+                source,
+                text: text.to_string(),
+            };
+            if let Err(payload) = catch_unwind(AssertUnwindSafe(|| callback.call(message))) {
+                log_warn_or_eprint!(
+                    "Whisper log callback panicked: {}",
+                    panic_message(payload.as_ref())
+                );
+            }
+        }
+    }
+}
+
+impl Default for WhisperLogRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(unused_variables)]
+fn forward(level: WhisperLogLevel, source: WhisperLogSource, text: &str) {
+    #[cfg(feature = "tracing")]
+    {
+        // Tracing levels must be known at compile time.
+        match (level, source) {
+            (WhisperLogLevel::Debug, WhisperLogSource::Whisper) => {
+                tracing::debug!(target: "whisper_cpp", "{text}")
+            }
+            (WhisperLogLevel::Info, WhisperLogSource::Whisper) => {
+                tracing::info!(target: "whisper_cpp", "{text}")
+            }
+            (WhisperLogLevel::Warn, WhisperLogSource::Whisper) => {
+                tracing::warn!(target: "whisper_cpp", "{text}")
+            }
+            (WhisperLogLevel::Error, WhisperLogSource::Whisper) => {
+                tracing::error!(target: "whisper_cpp", "{text}")
+            }
+            (WhisperLogLevel::Debug, WhisperLogSource::Ggml) => {
+                tracing::debug!(target: "ggml", "{text}")
+            }
+            (WhisperLogLevel::Info, WhisperLogSource::Ggml) => {
+                tracing::info!(target: "ggml", "{text}")
+            }
+            (WhisperLogLevel::Warn, WhisperLogSource::Ggml) => {
+                tracing::warn!(target: "ggml", "{text}")
+            }
+            (WhisperLogLevel::Error, WhisperLogSource::Ggml) => {
+                tracing::error!(target: "ggml", "{text}")
+            }
+        }
+    }
+    #[cfg(all(feature = "ribble-logging", not(feature = "tracing")))]
+    {
+        let level = match level {
+            WhisperLogLevel::Debug => log::Level::Debug,
+            WhisperLogLevel::Info => log::Level::Info,
+            WhisperLogLevel::Warn => log::Level::Warn,
+            WhisperLogLevel::Error => log::Level::Error,
+        };
+        log::log!(target: source.target(), level, "{text}");
+    }
+}
+
+// Maps a GGML log level; None for continuation lines, (and unknown levels).
+fn log_level(level: whisper_rs_sys::ggml_log_level) -> Option<WhisperLogLevel> {
+    match level {
+        whisper_rs_sys::ggml_log_level_GGML_LOG_LEVEL_DEBUG => Some(WhisperLogLevel::Debug),
+        whisper_rs_sys::ggml_log_level_GGML_LOG_LEVEL_INFO => Some(WhisperLogLevel::Info),
+        whisper_rs_sys::ggml_log_level_GGML_LOG_LEVEL_WARN => Some(WhisperLogLevel::Warn),
+        whisper_rs_sys::ggml_log_level_GGML_LOG_LEVEL_ERROR => Some(WhisperLogLevel::Error),
+        _ => None,
+    }
+}
+
+fn route_from_c(
+    level: whisper_rs_sys::ggml_log_level,
+    source: WhisperLogSource,
+    text: *const c_char,
+) {
+    if text.is_null() {
+        return;
+    }
+    let text = unsafe { CStr::from_ptr(text) }.to_string_lossy();
+    if let Some(router) = ROUTER.lock().as_mut() {
+        router.route(log_level(level), source, &text);
+    }
+}
+
+unsafe extern "C" fn whisper_log_trampoline(
+    level: whisper_rs_sys::ggml_log_level,
+    text: *const c_char,
+    _: *mut c_void,
+) {
+    route_from_c(level, WhisperLogSource::Whisper, text);
+}
+
+unsafe extern "C" fn ggml_log_trampoline(
+    level: whisper_rs_sys::ggml_log_level,
+    text: *const c_char,
+    _: *mut c_void,
+) {
+    route_from_c(level, WhisperLogSource::Ggml, text);
+}
@@ -1,16 +1,50 @@
-use std::ops::Deref;
-use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, OnceLock};
 
+use crate::transcriber::audio_events::AudioEvent;
+use crate::transcriber::corrections::Correction;
+use crate::transcriber::decode_diagnostics::DecodeDiagnostics;
+use crate::transcriber::speaker::{SnapshotSpeakers, SpeakerId};
+use crate::transcriber::timestamps::TimestampedSnapshot;
 use crate::utils::callback::Callback;
 use crate::utils::errors::RibbleWhisperError;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::utils::logging::{trace_event, trace_span};
+use crate::whisper::configs::SnapshotEncoding;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::whisper::configs::{Language, WhisperConfigs};
+#[cfg(not(target_arch = "wasm32"))]
 use crate::whisper::model::ModelLocation;
 use strum::{Display, EnumString, IntoStaticStr};
+#[cfg(not(target_arch = "wasm32"))]
 use whisper_rs::WhisperSegment;
 
+pub mod audio_events;
+pub mod black_box;
+pub mod corrections;
+pub mod decode_diagnostics;
+pub mod dedup;
+pub mod eval;
+pub mod localization;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod log_router;
+// whisper.cpp, (and so both transcribers), can only be built for native targets; there is no
+// wasm32 transcriber.
+#[cfg(not(target_arch = "wasm32"))]
 pub mod offline_transcriber;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod realtime_transcriber;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod scheduler;
+pub mod speaker;
+pub mod timestamps;
+#[cfg(feature = "ui-bridge")]
+pub mod ui_bridge;
+#[cfg(all(feature = "upload", not(target_arch = "wasm32")))]
+pub mod upload;
 pub mod vad;
+pub mod wake_phrase;
+pub mod window_hooks;
 
 // Trait alias, used until the feature reaches stable
 pub trait OfflineWhisperProgressCallback: Callback<Argument = i32> + Send + Sync + 'static {}
@@ -26,6 +60,10 @@ impl<T: Callback<Argument = String> + Send + Sync + 'static> OfflineWhisperNewSe
 {
 }
 
+/// Redirects all whisper.cpp and GGML logging to `log`/`tracing`, (with the log_backend or
+/// tracing_backend features), or silences it. Use a [log_router::WhisperLogRouter] to filter
+/// messages by level or receive them in a callback.
+#[cfg(not(target_arch = "wasm32"))]
 #[inline]
 pub fn redirect_whisper_logging_to_hooks() {
     whisper_rs::install_logging_hooks()
@@ -61,12 +99,19 @@ where
     /// transcription whenever the new_segment callback fires. This is very expensive and should
     /// not be called frequently:
     /// * Implement [crate::utils::callback::ShortCircuitCallback] or use
-    /// [crate::utils::callback::ShortCircuitRibbleWhisperCallback] to provide a mechanism for controlling how often the
-    /// snapshotting happens.
+    /// [crate::utils::callback::ShortCircuitRibbleWhisperCallback] (with its built-in rate-limit
+    /// policies) to provide a mechanism for controlling how often the snapshotting happens.
+    /// Wrap it in a [crate::utils::callback::ShortCircuitAdapter] to pass it in here.
+    /// * Wrap the callback in a [crate::utils::callback::ThrottledCallback] or
+    /// [crate::utils::callback::DebouncedCallback] to rate-limit it.
+    ///
+    /// Wrap fallible callbacks in a [crate::utils::callback::TryCallbackReporter] to have their errors
+    /// reported over a channel; panics in plain callbacks are caught and logged.
     pub new_segment: Option<S>,
 }
 
 /// Encapsulates a whisper segment with start and end timestamps
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Clone)]
 pub struct RibbleWhisperSegment {
     /// Segment text
@@ -75,6 +120,14 @@ pub struct RibbleWhisperSegment {
     pub start_time: i64,
     /// Timestamp end time, measured in centiseconds
     pub end_time: i64,
+    /// The mean probability of the segment's text tokens, (0.0 - 1.0), if known.
+    pub confidence: Option<f32>,
+    /// The mean probability of each word's text tokens, in order, if known. Only collected when
+    /// resolving overlaps with [dedup::ConflictPolicy::Confidence].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub word_confidences: Option<Arc<[f32]>>,
+    /// The speaker of the segment, when diarization is enabled.
+    pub speaker: Option<SpeakerId>,
 }
 
 impl RibbleWhisperSegment {
@@ -96,8 +149,96 @@ impl RibbleWhisperSegment {
     pub fn end_timestamp(&self) -> i64 {
         self.end_time
     }
+
+    pub fn confidence(&self) -> Option<f32> {
+        self.confidence
+    }
+
+    pub fn speaker(&self) -> Option<SpeakerId> {
+        self.speaker
+    }
+}
+
+// The mean of the values, computed as it goes, (None if there are none).
+pub(crate) fn running_mean(values: impl IntoIterator<Item = f32>) -> Option<f32> {
+    let (sum, count) = values
+        .into_iter()
+        .fold((0.0, 0usize), |(sum, count), value| {
+            (sum + value, count + 1)
+        });
+    (count > 0).then(|| sum / count as f32)
+}
+
+// The mean probability of a whisper segment's text tokens.
+// Special and timestamp tokens come after the end-of-text token.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn mean_token_probability(
+    segment: &WhisperSegment,
+    eot: whisper_rs::WhisperTokenId,
+) -> Option<f32> {
+    running_mean(
+        (0..segment.n_tokens())
+            .filter_map(|idx| segment.get_token(idx))
+            .map(|token| token.token_data())
+            .filter(|data| data.id < eot)
+            .map(|data| data.p),
+    )
+}
+
+// The mean probability of each word's text tokens. Words are split like
+// [timestamps::words_from_tokens]: a token starting with whitespace starts a new word.
+#[cfg(not(target_arch = "wasm32"))]
+fn word_token_probabilities(
+    segment: &WhisperSegment,
+    eot: whisper_rs::WhisperTokenId,
+) -> Arc<[f32]> {
+    let mut words = vec![];
+    let (mut sum, mut count) = (0.0, 0usize);
+    for token in (0..segment.n_tokens()).filter_map(|idx| segment.get_token(idx)) {
+        let data = token.token_data();
+        let Ok(bytes) = token.to_bytes() else {
+            continue;
+        };
+        if data.id >= eot || bytes.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        if bytes[0].is_ascii_whitespace() && count > 0 {
+            words.push(sum / count as f32);
+            (sum, count) = (0.0, 0);
+        }
+        sum += data.p;
+        count += 1;
+    }
+    if count > 0 {
+        words.push(sum / count as f32);
+    }
+    Arc::from(words)
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+impl RibbleWhisperSegment {
+    // Converts a whisper segment, measuring its confidence over its text tokens, (and each word's,
+    // if word_confidences is set).
+    pub(crate) fn from_whisper_segment(
+        segment: &WhisperSegment,
+        eot: whisper_rs::WhisperTokenId,
+        word_confidences: bool,
+    ) -> Result<Self, RibbleWhisperError> {
+        let text = segment.to_str_lossy()?;
+        Ok(Self {
+            text: text.into(),
+            start_time: segment.start_timestamp(),
+            end_time: segment.end_timestamp(),
+            confidence: mean_token_probability(segment, eot),
+            word_confidences: word_confidences.then(|| word_token_probabilities(segment, eot)),
+            speaker: None,
+        })
+    }
+}
+
+// Telling text tokens from special tokens takes the context's end-of-text token, so segments
+// converted without it have an unknown confidence, (see: [RibbleWhisperSegment::confidence]).
+#[cfg(not(target_arch = "wasm32"))]
 impl<'a> TryFrom<WhisperSegment<'a>> for RibbleWhisperSegment {
     type Error = RibbleWhisperError;
     fn try_from(value: WhisperSegment) -> Result<Self, Self::Error> {
@@ -108,10 +249,14 @@ impl<'a> TryFrom<WhisperSegment<'a>> for RibbleWhisperSegment {
             text: text.into(),
             start_time,
             end_time,
+            confidence: None,
+            word_confidences: None,
+            speaker: None,
         })
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl<'a> TryFrom<&WhisperSegment<'a>> for RibbleWhisperSegment {
     type Error = RibbleWhisperError;
 
@@ -123,27 +268,252 @@ impl<'a> TryFrom<&WhisperSegment<'a>> for RibbleWhisperSegment {
             text: text.into(),
             start_time,
             end_time,
+            confidence: None,
+            word_confidences: None,
+            speaker: None,
+        })
+    }
+}
+
+/// Confirmed text is frozen into chunks of roughly this many bytes, (see: [ConfirmedText]).
+pub const CONFIRMED_CHUNK_LEN: usize = 4096;
+
+/// The confirmed text of a transcription, stored as a sequence of chunks so that long sessions do
+/// not copy the entire transcript each time it grows. The chunks concatenate, with no separator,
+/// to the full text.
+///
+/// Consumers that only need to write the text out, (e.g. exports), can stream the chunks; the full
+/// text is joined on first access and shared between clones.
+#[derive(Clone, Default)]
+pub struct ConfirmedText {
+    inner: Arc<ConfirmedChunks>,
+}
+
+#[derive(Default)]
+struct ConfirmedChunks {
+    chunks: Arc<[Arc<str>]>,
+    len: usize,
+    joined: OnceLock<Arc<str>>,
+}
+
+impl ConfirmedText {
+    pub fn from_chunks(chunks: Arc<[Arc<str>]>) -> Self {
+        let len = chunks.iter().map(|chunk| chunk.len()).sum();
+        Self {
+            inner: Arc::new(ConfirmedChunks {
+                chunks,
+                len,
+                joined: OnceLock::new(),
+            }),
+        }
+    }
+
+    pub fn chunks(&self) -> &[Arc<str>] {
+        &self.inner.chunks
+    }
+
+    /// The length of the text in bytes.
+    pub fn len(&self) -> usize {
+        self.inner.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.len == 0
+    }
+
+    /// The full text, joined from the chunks on first access.
+    pub fn as_str(&self) -> &str {
+        self.joined()
+    }
+
+    /// Streams the text from a byte offset, (e.g. the length already written out).
+    /// Returns None if the offset is past the end of the text or not on a char boundary.
+    pub fn chunks_from(&self, offset: usize) -> Option<impl Iterator<Item = &str>> {
+        if offset > self.len() {
+            return None;
+        }
+        let chunks = self.chunks();
+        let mut chunk_start = 0;
+        let mut index = chunks.len();
+        for (i, chunk) in chunks.iter().enumerate() {
+            if offset < chunk_start + chunk.len() {
+                index = i;
+                break;
+            }
+            chunk_start += chunk.len();
+        }
+        let first = match chunks.get(index) {
+            Some(chunk) => chunk.get(offset - chunk_start..)?,
+            None => "",
+        };
+        let rest = chunks.get(index + 1..).unwrap_or_default();
+        Some(std::iter::once(first).chain(rest.iter().map(|chunk| chunk.as_ref())))
+    }
+
+    fn joined(&self) -> &Arc<str> {
+        self.inner.joined.get_or_init(|| match self.chunks() {
+            [] => Arc::from(""),
+            [chunk] => Arc::clone(chunk),
+            chunks => Arc::from(chunks.concat()),
         })
     }
+
+    // Returns the text appended to previous, or None if this text does not extend it.
+    // Chunks shared with previous are skipped without comparing their text.
+    fn appended_to(&self, previous: &ConfirmedText) -> Option<String> {
+        let shared = self
+            .chunks()
+            .iter()
+            .zip(previous.chunks())
+            .take_while(|(chunk, previous)| Arc::ptr_eq(chunk, previous))
+            .count();
+        let rest = self.chunks()[shared..].concat();
+        let previous_rest = previous.chunks()[shared..].concat();
+        rest.strip_prefix(previous_rest.as_str())
+            .map(str::to_string)
+    }
+
+    // Returns the correction that turns previous into this text, or None if this text is the same
+    // as or extends previous. Chunks shared with previous are skipped without comparing their
+    // text.
+    pub(crate) fn correction_from(&self, previous: &ConfirmedText) -> Option<Correction> {
+        let shared = self
+            .chunks()
+            .iter()
+            .zip(previous.chunks())
+            .take_while(|(chunk, previous)| Arc::ptr_eq(chunk, previous))
+            .count();
+        let offset: usize = previous.chunks()[..shared]
+            .iter()
+            .map(|chunk| chunk.len())
+            .sum();
+        let rest = self.chunks()[shared..].concat();
+        let previous_rest = previous.chunks()[shared..].concat();
+        if rest.starts_with(previous_rest.as_str()) {
+            return None;
+        }
+        let correction = Correction::between(&previous_rest, &rest)?;
+        let range = correction.range();
+        Some(Correction::new(
+            offset + range.start..offset + range.end,
+            correction.new_text(),
+        ))
+    }
+
+    // Small appends are merged into the last chunk, so that applying many deltas doesn't leave
+    // many tiny chunks.
+    fn append(&self, text: &str) -> Self {
+        let mut chunks = self.chunks().to_vec();
+        match chunks.last_mut() {
+            Some(last) if last.len() + text.len() <= CONFIRMED_CHUNK_LEN => {
+                *last = Arc::from(format!("{last}{text}"));
+            }
+            _ => chunks.push(Arc::from(text)),
+        }
+        Self::from_chunks(Arc::from(chunks))
+    }
+}
+
+impl From<Arc<str>> for ConfirmedText {
+    fn from(text: Arc<str>) -> Self {
+        if text.is_empty() {
+            Self::default()
+        } else {
+            Self::from_chunks(Arc::from([text]))
+        }
+    }
+}
+
+impl std::fmt::Display for ConfirmedText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.chunks()
+            .iter()
+            .try_for_each(|chunk| f.write_str(chunk))
+    }
+}
+
+// Serialized as the full text; chunking is an in-memory detail.
+#[cfg(feature = "serde")]
+impl serde::Serialize for ConfirmedText {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ConfirmedText {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = <Arc<str> as serde::Deserialize>::deserialize(deserializer)?;
+        Ok(Self::from(text))
+    }
 }
 
 /// Encapsulates the state of whisper transcription (confirmed + working segments) at a given point in time
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Clone, Default)]
 pub struct TranscriptionSnapshot {
-    confirmed: Arc<str>,
+    confirmed: ConfirmedText,
     // This should probably be Arc<[Arc<str>]>
     // Otherwise this is going to involve a lot of string clones.
     string_segments: Arc<[Arc<str>]>,
+    // Only set when diarization is enabled.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    speakers: Option<Arc<SnapshotSpeakers>>,
 }
 impl TranscriptionSnapshot {
     pub fn new(confirmed: Arc<str>, string_segments: Arc<[Arc<str>]>) -> Self {
+        Self::from_confirmed_text(ConfirmedText::from(confirmed), string_segments)
+    }
+
+    pub fn from_confirmed_text(confirmed: ConfirmedText, string_segments: Arc<[Arc<str>]>) -> Self {
         Self {
             confirmed,
             string_segments,
+            speakers: None,
         }
     }
 
+    /// Sets the speakers of the confirmed text and working segments.
+    pub fn with_speakers(mut self, speakers: Option<Arc<SnapshotSpeakers>>) -> Self {
+        self.speakers = speakers;
+        self
+    }
+
+    /// The speakers of the snapshot, when diarization is enabled.
+    pub fn speakers(&self) -> Option<&SnapshotSpeakers> {
+        self.speakers.as_deref()
+    }
+
+    /// Splits the confirmed text at each change of speaker, (e.g. for color-coded captions).
+    /// Text before the first known speaker, (or all of it, without diarization), has no speaker.
+    pub fn confirmed_spans(&self) -> impl Iterator<Item = (Option<SpeakerId>, &str)> {
+        let text = self.confirmed();
+        let turns = self
+            .speakers()
+            .map(SnapshotSpeakers::confirmed_turns)
+            .unwrap_or_default();
+        let first = turns.first().map_or(text.len(), |turn| turn.offset);
+        let leading = (first > 0).then(|| (None, text.get(..first).unwrap_or_default()));
+        leading
+            .into_iter()
+            .chain(turns.iter().enumerate().map(move |(i, turn)| {
+                let end = turns.get(i + 1).map_or(text.len(), |next| next.offset);
+                (
+                    Some(turn.speaker),
+                    text.get(turn.offset..end).unwrap_or_default(),
+                )
+            }))
+    }
+
+    /// The confirmed text. For long transcripts, prefer streaming
+    /// [ConfirmedText::chunks] via [Self::confirmed_text] where a joined string isn't needed.
     pub fn confirmed(&self) -> &str {
+        self.confirmed.as_str()
+    }
+    pub fn confirmed_text(&self) -> &ConfirmedText {
         &self.confirmed
     }
     pub fn string_segments(&self) -> &[Arc<str>] {
@@ -151,44 +521,300 @@ impl TranscriptionSnapshot {
     }
 
     pub fn into_parts(self) -> (Arc<str>, Arc<[Arc<str>]>) {
-        (self.confirmed, self.string_segments)
+        (Arc::clone(self.confirmed.joined()), self.string_segments)
     }
     pub fn into_string(self) -> String {
-        let confirmed = self.confirmed.deref();
-        let segment_string = self.string_segments.join(" ");
-        format!("{confirmed} {segment_string}")
+        self.to_string()
     }
 }
 
 impl std::fmt::Display for TranscriptionSnapshot {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let confirmed = self.confirmed.deref();
         let segment_string = self.string_segments.join(" ");
-        write!(f, "{confirmed} {segment_string}")
+        write!(f, "{} {segment_string}", self.confirmed)
+    }
+}
+
+/// An update to the previous [TranscriptionSnapshot]: text appended to the confirmed
+/// transcription, and the working segments that have changed.
+/// Sent in place of full snapshots when configured, (see: [crate::whisper::configs::SnapshotEncoding]).
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SnapshotDelta {
+    confirmed_append: Arc<str>,
+    replaced_segments: Vec<(usize, Arc<str>)>,
+    num_segments: usize,
+    // Speakers are small and shared with the snapshot, so they are sent whole.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    speakers: Option<Arc<SnapshotSpeakers>>,
+}
+
+impl SnapshotDelta {
+    /// Computes the delta from one snapshot to the next.
+    /// Returns None if the next snapshot's confirmed text does not extend the previous
+    /// snapshot's, (e.g. deduplication revised it), in which case a full snapshot is needed.
+    pub fn between(previous: &TranscriptionSnapshot, next: &TranscriptionSnapshot) -> Option<Self> {
+        let confirmed_append = next.confirmed.appended_to(&previous.confirmed)?;
+        let replaced_segments = next
+            .string_segments
+            .iter()
+            .enumerate()
+            .filter(|(i, segment)| previous.string_segments.get(*i) != Some(*segment))
+            .map(|(i, segment)| (i, Arc::clone(segment)))
+            .collect();
+        Some(Self {
+            confirmed_append: Arc::from(confirmed_append),
+            replaced_segments,
+            num_segments: next.string_segments.len(),
+            speakers: next.speakers.clone(),
+        })
+    }
+
+    /// The text appended to the confirmed transcription.
+    pub fn confirmed_append(&self) -> &str {
+        &self.confirmed_append
+    }
+
+    /// The working segments that changed or were added, as (index, text).
+    pub fn replaced_segments(&self) -> &[(usize, Arc<str>)] {
+        &self.replaced_segments
+    }
+
+    /// The number of working segments after the update; segments past this were removed.
+    pub fn num_segments(&self) -> usize {
+        self.num_segments
+    }
+
+    /// The speakers after the update, when diarization is enabled.
+    pub fn speakers(&self) -> Option<&SnapshotSpeakers> {
+        self.speakers.as_deref()
+    }
+
+    /// Applies the delta to the snapshot it was computed from, returning the updated snapshot.
+    pub fn apply(&self, previous: &TranscriptionSnapshot) -> TranscriptionSnapshot {
+        let confirmed = if self.confirmed_append.is_empty() {
+            previous.confirmed.clone()
+        } else {
+            previous.confirmed.append(&self.confirmed_append)
+        };
+        let mut segments: Vec<Arc<str>> = previous
+            .string_segments
+            .iter()
+            .take(self.num_segments)
+            .cloned()
+            .collect();
+        segments.resize(self.num_segments, Arc::from(""));
+        for (i, text) in self.replaced_segments.iter() {
+            if let Some(segment) = segments.get_mut(*i) {
+                *segment = Arc::clone(text);
+            }
+        }
+        TranscriptionSnapshot::from_confirmed_text(confirmed, Arc::from(segments))
+            .with_speakers(self.speakers.clone())
+    }
+}
+
+/// Rebuilds full [TranscriptionSnapshot]s from transcriber outputs that may contain
+/// [SnapshotDelta]s, (e.g. on the receiving end of a channel or socket).
+#[derive(Clone, Default)]
+pub struct SnapshotAssembler {
+    current: Option<Arc<TranscriptionSnapshot>>,
+}
+
+impl SnapshotAssembler {
+    pub fn new() -> Self {
+        Self { current: None }
+    }
+
+    /// Applies an output, returning the updated snapshot. Returns None for control phrases, audio
+    /// events, diagnostics, corrections, (the next snapshot carries the corrected text), and
+    /// timestamped snapshots.
+    /// Deltas received before any full snapshot are applied to an empty snapshot.
+    pub fn push(&mut self, output: &WhisperOutput) -> Option<Arc<TranscriptionSnapshot>> {
+        let snapshot = match output {
+            WhisperOutput::TranscriptionSnapshot(snapshot) => Arc::clone(snapshot),
+            WhisperOutput::SnapshotDelta(delta) => {
+                let previous = self.current.take().unwrap_or_default();
+                Arc::new(delta.apply(&previous))
+            }
+            WhisperOutput::ControlPhrase(_)
+            | WhisperOutput::AudioEvent(_)
+            | WhisperOutput::DecodeDiagnostics(_)
+            | WhisperOutput::Correction(_)
+            | WhisperOutput::TimestampedSnapshot(_) => return None,
+        };
+        self.current = Some(Arc::clone(&snapshot));
+        Some(snapshot)
+    }
+
+    /// The latest snapshot, if any have been received.
+    pub fn current(&self) -> Option<&Arc<TranscriptionSnapshot>> {
+        self.current.as_ref()
+    }
+}
+
+// Encodes the realtime transcriber's snapshots according to the configured SnapshotEncoding.
+pub(crate) struct SnapshotEncoder {
+    encoding: SnapshotEncoding,
+    previous: Option<Arc<TranscriptionSnapshot>>,
+    since_keyframe: usize,
+    // The working segments of the last snapshot built, which are shared with it.
+    segments: Arc<[Arc<str>]>,
+}
+
+impl SnapshotEncoder {
+    pub(crate) fn new(encoding: SnapshotEncoding) -> Self {
+        Self {
+            encoding,
+            previous: None,
+            since_keyframe: 0,
+            segments: Arc::from([]),
+        }
+    }
+
+    /// Builds a snapshot of the working segments without re-collecting them when possible.
+    ///
+    /// Segments are only ever swapped for new text, never edited in place, so the segments
+    /// shared with the previous snapshot are found by pointer. If none have changed, the previous
+    /// segments are reused outright. If only the tail has changed and no one else still holds
+    /// the previous segments, the tail is overwritten in place. Otherwise the segments are
+    /// collected into a new slice.
+    pub(crate) fn build_snapshot<'a, I>(
+        &mut self,
+        confirmed: ConfirmedText,
+        segments: I,
+        speakers: Option<Arc<SnapshotSpeakers>>,
+    ) -> Arc<TranscriptionSnapshot>
+    where
+        I: IntoIterator<Item = &'a Arc<str>>,
+        I::IntoIter: ExactSizeIterator + Clone,
+    {
+        let segments = segments.into_iter();
+        let unchanged = self
+            .segments
+            .iter()
+            .zip(segments.clone())
+            .take_while(|(cached, segment)| Arc::ptr_eq(cached, segment))
+            .count();
+
+        if segments.len() == self.segments.len() {
+            if unchanged < segments.len() {
+                match Arc::get_mut(&mut self.segments) {
+                    Some(cached) => cached[unchanged..]
+                        .iter_mut()
+                        .zip(segments.skip(unchanged))
+                        .for_each(|(cached, segment)| *cached = Arc::clone(segment)),
+                    None => self.segments = segments.cloned().collect(),
+                }
+            }
+        } else {
+            self.segments = segments.cloned().collect();
+        }
+
+        Arc::new(
+            TranscriptionSnapshot::from_confirmed_text(confirmed, Arc::clone(&self.segments))
+                .with_speakers(speakers),
+        )
+    }
+
+    /// Returns the output to send for the snapshot, or None if nothing has changed since the
+    /// previous delta.
+    pub(crate) fn encode(&mut self, snapshot: Arc<TranscriptionSnapshot>) -> Option<WhisperOutput> {
+        let SnapshotEncoding::Delta { keyframe_interval } = self.encoding else {
+            return Some(WhisperOutput::TranscriptionSnapshot(snapshot));
+        };
+        let keyframe_due = keyframe_interval > 0 && self.since_keyframe >= keyframe_interval;
+        let delta = match self.previous.as_ref() {
+            Some(previous) if !keyframe_due => {
+                SnapshotDelta::between(previous, &snapshot).map(|delta| {
+                    let unchanged = delta.confirmed_append.is_empty()
+                        && delta.replaced_segments.is_empty()
+                        && delta.num_segments == previous.string_segments.len()
+                        && delta.speakers == previous.speakers;
+                    (delta, unchanged)
+                })
+            }
+            _ => None,
+        };
+
+        let output = match delta {
+            Some((_, true)) => return None,
+            Some((delta, false)) => {
+                self.since_keyframe += 1;
+                WhisperOutput::SnapshotDelta(Arc::new(delta))
+            }
+            None => {
+                self.since_keyframe = 0;
+                WhisperOutput::TranscriptionSnapshot(Arc::clone(&snapshot))
+            }
+        };
+        self.previous = Some(snapshot);
+        Some(output)
+    }
+
+    /// Forces the next update to be a full snapshot, (e.g. after an update failed to send).
+    pub(crate) fn reset(&mut self) {
+        self.previous = None;
     }
 }
 
 /// Encapsulates possible types of output sent through a Transcriber channel
-/// NOTE: Outputs with accompanying timestamps are not yet implemented.
+///
+/// With the serde feature, outputs (de)serialize for sending across a process boundary, (e.g. IPC,
+/// a WebSocket, or disk). Shared data is copied: a deserialized output doesn't share its text with
+/// earlier outputs, so rebuild [SnapshotDelta]s with a [SnapshotAssembler] on the receiving end.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Clone)]
 pub enum WhisperOutput {
     TranscriptionSnapshot(Arc<TranscriptionSnapshot>),
+    /// An update to the previous snapshot, (only sent when configured for delta encoding).
+    SnapshotDelta(Arc<SnapshotDelta>),
     /// For sending running state and control messages from the Transcriber
     ControlPhrase(WhisperControlPhrase),
+    /// A non-speech event, (e.g. applause), sent once it has ended.
+    /// Only sent when an [audio_events::AudioEventClassifier] is attached.
+    AudioEvent(AudioEvent),
+    /// The decoder's diagnostics for an inference window.
+    /// Only sent when enabled with
+    /// [crate::whisper::configs::WhisperRealtimeConfigs::with_decode_diagnostics].
+    DecodeDiagnostics(Arc<DecodeDiagnostics>),
+    /// A revision of already-confirmed text, sent before the snapshot that contains it.
+    /// Only sent when enabled with
+    /// [crate::whisper::configs::WhisperRealtimeConfigs::with_correction_horizon].
+    Correction(Correction),
+    /// The segments of an inference window, with word-level timestamps.
+    /// Only sent when enabled with
+    /// [crate::whisper::configs::WhisperRealtimeConfigs::with_timestamped_snapshots].
+    TimestampedSnapshot(Arc<TimestampedSnapshot>),
 }
 
 impl WhisperOutput {
     // Consumes and extracts the inner contents of a WhisperOutput into a string
+    // NOTE: for deltas, this is only the newly confirmed text.
     pub fn into_inner(self) -> String {
         match self {
             WhisperOutput::TranscriptionSnapshot(snapshot) => snapshot.to_string(),
+            WhisperOutput::SnapshotDelta(delta) => delta.confirmed_append().to_string(),
             WhisperOutput::ControlPhrase(control_phrase) => control_phrase.to_string(),
+            WhisperOutput::AudioEvent(event) => event.to_string(),
+            WhisperOutput::DecodeDiagnostics(diagnostics) => diagnostics.to_string(),
+            WhisperOutput::Correction(correction) => correction.to_string(),
+            WhisperOutput::TimestampedSnapshot(snapshot) => snapshot.to_string(),
         }
     }
 }
 
 /// A set of control phrases to pass information from the transcriber to a UI
-// These would benefit from some eventual localization
+///
+/// The Display strings, (e.g. "[START SPEAKING]"), are for humans and may change. Programs should
+/// match on the variant, or on its [ControlCode] and [ControlPayload] when the phrase has crossed
+/// a process boundary, (see: [WhisperControlPhrase::code]). To display phrases in the user's
+/// language, see: [localization].
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 #[derive(Default, Clone, EnumString, IntoStaticStr, Display)]
 pub enum WhisperControlPhrase {
     /// The default "ready" state
@@ -198,30 +824,213 @@ pub enum WhisperControlPhrase {
     /// Preparing whisper for transcription
     #[strum(serialize = "[GETTING_READY]")]
     GettingReady,
+    /// Whisper is set up and the transcriber is listening for its wake phrase, (see:
+    /// [crate::transcriber::wake_phrase]).
+    #[strum(serialize = "[AWAITING WAKE PHRASE]")]
+    AwaitingWakePhrase,
+    /// A dormant transcriber has started its session, (see: [crate::transcriber::wake_phrase]).
+    #[strum(serialize = "[SESSION STARTED]")]
+    SessionStarted(SessionStartReason),
     /// Whisper is set up and the transcriber loop is running to decode audio
     #[strum(serialize = "[START SPEAKING]")]
     StartSpeaking,
     /// The transcription time has exceeded its user-specified timeout boundary
+    /// The transcription will time out after the remaining time, (see:
+    /// [crate::whisper::configs::WhisperRealtimeConfigs::with_timeout_warning]).
+    /// The timeout can be extended with
+    /// [RealtimeTranscriberHandle::extend_timeout](crate::transcriber::realtime_transcriber::RealtimeTranscriberHandle::extend_timeout).
+    #[strum(serialize = "[TIMEOUT APPROACHING]")]
+    TimeoutApproaching { remaining: std::time::Duration },
     #[strum(serialize = "[TRANSCRIPTION TIMEOUT]")]
     TranscriptionTimeout,
+    /// The transcription was stopped after a prolonged silence, (see:
+    /// [crate::whisper::configs::IdlePolicy]).
+    #[strum(serialize = "[IDLE TIMEOUT]")]
+    IdleTimeout,
+    /// The transcriber is idling after a prolonged silence, and will resume with
+    /// [WhisperControlPhrase::StartSpeaking] once voice is detected.
+    #[strum(serialize = "[IDLE PAUSED]")]
+    IdlePaused,
     /// The transcription has fully ended and the final string will be returned
     #[strum(serialize = "[END TRANSCRIPTION]")]
     EndTranscription,
     #[strum(serialize = "[CLEANING UP]")]
     SlowStop,
+    /// The working set overflowed and its oldest n_segments were confirmed, (or dropped, if not
+    /// confirmed), (see: [crate::whisper::configs::OverflowPolicy]).
+    #[strum(serialize = "[WORKING SET OVERFLOW]")]
+    WorkingSetOverflow { n_segments: usize, confirmed: bool },
     /// For passing debugging messages across the channel
     #[strum(serialize = "Debug: {0}")]
     Debug(String),
 }
 
+/// Why a dormant transcriber started its session, (see: [crate::transcriber::wake_phrase]).
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum SessionStartReason {
+    /// The session was started with
+    /// [RealtimeTranscriberHandle::start_session](crate::transcriber::realtime_transcriber::RealtimeTranscriberHandle::start_session).
+    #[default]
+    Manual,
+    /// The wake phrase was heard, (as it is matched).
+    WakePhrase(String),
+}
+
+impl WhisperControlPhrase {
+    /// The phrase's stable code, (see: [ControlCode]).
+    pub fn code(&self) -> ControlCode {
+        match self {
+            Self::Idle => ControlCode::Idle,
+            Self::GettingReady => ControlCode::GettingReady,
+            Self::AwaitingWakePhrase => ControlCode::AwaitingWakePhrase,
+            Self::SessionStarted(_) => ControlCode::SessionStarted,
+            Self::StartSpeaking => ControlCode::StartSpeaking,
+            Self::TimeoutApproaching { .. } => ControlCode::TimeoutApproaching,
+            Self::TranscriptionTimeout => ControlCode::TranscriptionTimeout,
+            Self::IdleTimeout => ControlCode::IdleTimeout,
+            Self::IdlePaused => ControlCode::IdlePaused,
+            Self::EndTranscription => ControlCode::EndTranscription,
+            Self::SlowStop => ControlCode::SlowStop,
+            Self::Debug(_) => ControlCode::Debug,
+            Self::WorkingSetOverflow { .. } => ControlCode::WorkingSetOverflow,
+        }
+    }
+
+    /// The data the phrase carries, if any.
+    pub fn payload(&self) -> Option<ControlPayload> {
+        match self {
+            Self::SessionStarted(SessionStartReason::WakePhrase(phrase)) => {
+                Some(ControlPayload::WakePhrase(phrase.clone()))
+            }
+            Self::TimeoutApproaching { remaining } => Some(ControlPayload::RemainingMs(
+                remaining.as_millis().try_into().unwrap_or(u64::MAX),
+            )),
+            Self::Debug(message) => Some(ControlPayload::Message(message.clone())),
+            Self::WorkingSetOverflow {
+                n_segments,
+                confirmed,
+            } => Some(ControlPayload::Overflow {
+                n_segments: *n_segments,
+                confirmed: *confirmed,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// The stable, machine-readable identity of a [WhisperControlPhrase], for matching phrases that
+/// have crossed a process boundary, (e.g. over a socket or FFI), without parsing display strings.
+///
+/// Each code has a string form, (the snake_case variant name, e.g. "start_speaking"), and a
+/// numeric form, (see: [ControlCode::value]). Neither changes between releases: new codes are
+/// only ever added, and numbers are never reused.
+///
+/// | Code | Value | Payload |
+/// |---|---|---|
+/// | `idle` | 0 | |
+/// | `getting_ready` | 1 | |
+/// | `awaiting_wake_phrase` | 2 | |
+/// | `session_started` | 3 | [ControlPayload::WakePhrase], when started by the wake phrase |
+/// | `start_speaking` | 4 | |
+/// | `timeout_approaching` | 5 | [ControlPayload::RemainingMs] |
+/// | `transcription_timeout` | 6 | |
+/// | `idle_timeout` | 7 | |
+/// | `idle_paused` | 8 | |
+/// | `end_transcription` | 9 | |
+/// | `slow_stop` | 10 | |
+/// | `debug` | 11 | [ControlPayload::Message] |
+/// | `working_set_overflow` | 12 | [ControlPayload::Overflow] |
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, EnumString, IntoStaticStr, Display)]
+#[strum(serialize_all = "snake_case")]
+#[repr(u16)]
+pub enum ControlCode {
+    Idle = 0,
+    GettingReady = 1,
+    AwaitingWakePhrase = 2,
+    SessionStarted = 3,
+    StartSpeaking = 4,
+    TimeoutApproaching = 5,
+    TranscriptionTimeout = 6,
+    IdleTimeout = 7,
+    IdlePaused = 8,
+    EndTranscription = 9,
+    SlowStop = 10,
+    Debug = 11,
+    WorkingSetOverflow = 12,
+}
+
+impl ControlCode {
+    const ALL: [ControlCode; 13] = [
+        Self::Idle,
+        Self::GettingReady,
+        Self::AwaitingWakePhrase,
+        Self::SessionStarted,
+        Self::StartSpeaking,
+        Self::TimeoutApproaching,
+        Self::TranscriptionTimeout,
+        Self::IdleTimeout,
+        Self::IdlePaused,
+        Self::EndTranscription,
+        Self::SlowStop,
+        Self::Debug,
+        Self::WorkingSetOverflow,
+    ];
+
+    /// The code's string form, (e.g. "start_speaking").
+    pub fn as_str(&self) -> &'static str {
+        self.into()
+    }
+
+    /// The code's numeric form.
+    pub fn value(&self) -> u16 {
+        *self as u16
+    }
+
+    /// The code with the given numeric form, or None if it is unknown, (e.g. from a newer
+    /// release).
+    pub fn from_value(value: u16) -> Option<Self> {
+        Self::ALL.into_iter().find(|code| code.value() == value)
+    }
+}
+
+/// The data carried by a [WhisperControlPhrase], (see: [WhisperControlPhrase::payload]).
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ControlPayload {
+    /// The time left before the transcription times out, in milliseconds.
+    RemainingMs(u64),
+    /// The wake phrase that started the session, (as it is matched).
+    WakePhrase(String),
+    /// A debugging message.
+    Message(String),
+    /// The number of segments removed from an overflowing working set, and whether they were
+    /// confirmed or dropped.
+    Overflow { n_segments: usize, confirmed: bool },
+}
+
 pub const WHISPER_SAMPLE_RATE: f64 = 16000f64;
 
 // Quick and dirty utility function for both transcriber objects.
-fn build_whisper_context(
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn build_whisper_context(
     model_location: ModelLocation,
     params: whisper_rs::WhisperContextParameters,
 ) -> Result<whisper_rs::WhisperContext, RibbleWhisperError> {
-    Ok(match model_location {
+    let gpu_device = params.use_gpu.then_some(params.gpu_device);
+    let _span = trace_span!(
+        INFO,
+        "model_load",
+        source = match &model_location {
+            ModelLocation::StaticFilePath(_) | ModelLocation::DynamicFilePath(_) => "file",
+            _ => "buffer",
+        }
+    )
+    .entered();
+    let ctx = match model_location {
         ModelLocation::StaticFilePath(path) => {
             whisper_rs::WhisperContext::new_with_params(&path.to_string_lossy(), params)
         }
@@ -234,5 +1043,39 @@ fn build_whisper_context(
         ModelLocation::DynamicBuffer(buf) => {
             whisper_rs::WhisperContext::new_from_buffer_with_params(&buf, params)
         }
-    }?)
+        ModelLocation::SharedBuffer(buf) => {
+            whisper_rs::WhisperContext::new_from_buffer_with_params(&buf, params)
+        }
+    };
+    trace_event!(INFO, loaded = ctx.is_ok(), "Model load finished");
+    match (ctx, gpu_device) {
+        // whisper.cpp does not report why initialization failed; when a GPU was requested, the
+        // backend is the most likely culprit and the caller may be able to retry on the CPU.
+        (Err(whisper_rs::WhisperError::InitError), Some(device)) => Err(RibbleWhisperError::Gpu {
+            device,
+            reason: "Failed to initialize the whisper context".to_string(),
+        }),
+        (ctx, _) => Ok(ctx?),
+    }
+}
+
+// The language of whisper's output, for language-dependent post-processing: English when
+// translating, otherwise the configured language, or the language whisper detected for the most
+// recent inference.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn output_language(
+    configs: &WhisperConfigs,
+    state: &whisper_rs::WhisperState,
+) -> Option<Language> {
+    if configs.translate() {
+        return Some(Language::En);
+    }
+    match configs.language() {
+        Some(language) if *language != Language::Auto => Some(*language),
+        _ => state
+            .full_lang_id_from_state()
+            .ok()
+            .and_then(whisper_rs::get_lang_str)
+            .and_then(|language| language.parse().ok()),
+    }
 }
@@ -1,18 +1,29 @@
 use parking_lot::Mutex;
 use std::ffi::{c_int, c_void, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use whisper_rs::{WhisperNewSegmentCallback, WhisperProgressCallback};
 
+use crate::audio::pcm::convert_integer_to_float_audio;
 use crate::audio::{AudioChannelConfiguration, WhisperAudioSample};
+use crate::postprocess::TextProcessor;
+use crate::transcriber::scheduler::{InferencePermit, InferencePriority, InferenceScheduler};
+use crate::transcriber::speaker::{SpeakerId, TurnLabeler};
 use crate::transcriber::vad::VAD;
 use crate::transcriber::{
-    build_whisper_context, OfflineWhisperNewSegmentCallback, OfflineWhisperProgressCallback,
-    WhisperCallbacks,
+    build_whisper_context, mean_token_probability, output_language,
+    OfflineWhisperNewSegmentCallback, OfflineWhisperProgressCallback, RibbleWhisperSegment,
+    WhisperCallbacks, WHISPER_SAMPLE_RATE,
 };
+use crate::utils::callback::panic_message;
 use crate::utils::errors::RibbleWhisperError;
+use crate::utils::logging::{log_warn_or_eprint, trace_span};
+use crate::utils::telemetry::record_window;
 use crate::whisper::configs::WhisperConfigs;
+use crate::whisper::grammar::{apply_grammar, Grammar};
 use crate::whisper::model::ModelRetriever;
 
 /// Builder for [OfflineTranscriber]
@@ -28,6 +39,9 @@ where
     model_retriever: Option<Arc<M>>,
     /// (Optional) Used to extract voiced segments to reduce overall transcription time.
     voice_activity_detector: Option<Arc<Mutex<V>>>,
+    text_processor: Option<Arc<dyn TextProcessor>>,
+    scheduler: Option<InferenceScheduler>,
+    grammar: Option<Arc<Grammar>>,
 }
 
 impl<V, M> OfflineTranscriberBuilder<V, M>
@@ -42,6 +56,9 @@ where
             channels: None,
             model_retriever: None,
             voice_activity_detector: None,
+            text_processor: None,
+            scheduler: None,
+            grammar: None,
         }
     }
     /// Sets the whisper configurations
@@ -76,6 +93,9 @@ where
             channels: self.channels,
             model_retriever: self.model_retriever,
             voice_activity_detector: Some(v),
+            text_processor: self.text_processor,
+            scheduler: self.scheduler,
+            grammar: self.grammar,
         }
     }
     /// Sets an optional voice activity detector to optimize transcription by pruning out unvoiced audio frames.
@@ -92,6 +112,9 @@ where
             channels: self.channels,
             model_retriever: self.model_retriever,
             voice_activity_detector: Some(Arc::clone(&vad)),
+            text_processor: self.text_processor,
+            scheduler: self.scheduler,
+            grammar: self.grammar,
         }
     }
 
@@ -107,6 +130,9 @@ where
             channels: self.channels,
             model_retriever: Some(Arc::new(model_retriever)),
             voice_activity_detector: None,
+            text_processor: self.text_processor,
+            scheduler: self.scheduler,
+            grammar: self.grammar,
         }
     }
 
@@ -122,8 +148,35 @@ where
             channels: self.channels,
             model_retriever: Some(Arc::clone(&model_retriever)),
             voice_activity_detector: None,
+            text_processor: self.text_processor,
+            scheduler: self.scheduler,
+            grammar: self.grammar,
         }
     }
+
+    /// Sets an optional post-processor for the transcribed text, (e.g. a [TextPipeline](crate::postprocess::TextPipeline)).
+    /// This is applied to each segment of the final transcription.
+    /// NOTE: The new-segment callback receives the unprocessed segment text.
+    pub fn with_text_processor<P: TextProcessor + 'static>(mut self, text_processor: P) -> Self {
+        self.text_processor = Some(Arc::new(text_processor));
+        self
+    }
+
+    /// Sets an optional scheduler shared with other transcribers running on the same GPU.
+    /// Offline transcription runs at [InferencePriority::Background], and yields to waiting
+    /// realtime transcribers between its encoder windows.
+    pub fn with_inference_scheduler(mut self, scheduler: InferenceScheduler) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Sets an optional grammar to constrain the transcription to, (e.g. a fixed set of voice commands).
+    /// See: [crate::whisper::grammar]
+    pub fn with_grammar(mut self, grammar: Grammar) -> Self {
+        self.grammar = Some(Arc::new(grammar));
+        self
+    }
+
     /// Builds an `OfflineTranscriber<V>` according to the given parameters
     /// # Returns:
     /// * Ok(`OfflineTranscriber<V>`) on successful build
@@ -133,27 +186,32 @@ where
     ///   ** missing audio
     ///   ** Model ID is not set in configs.
     pub fn build(self) -> Result<OfflineTranscriber<V, M>, RibbleWhisperError> {
-        let configs = self.configs.ok_or(RibbleWhisperError::ParameterError(
-            "Configs missing in OfflineTranscriberBuilder..".to_string(),
-        ))?;
+        let configs = self.configs.ok_or(RibbleWhisperError::MissingField {
+            builder: "OfflineTranscriberBuilder",
+            field: "configs",
+        })?;
 
-        let _model_id = configs.model_id().ok_or(RibbleWhisperError::ParameterError(
-            "Model ID missing from configs in OfflineTranscriberBuilder".to_string(),
-        ));
+        let _model_id = configs.model_id().ok_or(RibbleWhisperError::MissingField {
+            builder: "OfflineTranscriberBuilder",
+            field: "model_id",
+        });
 
         let audio = self.audio.filter(|audio| !audio.is_empty()).ok_or(
-            RibbleWhisperError::ParameterError(
-                "Audio missing in OfflineTranscriberBuilder.".to_string(),
-            ),
+            RibbleWhisperError::MissingField {
+                builder: "OfflineTranscriberBuilder",
+                field: "audio",
+            },
         )?;
-        let channels = self.channels.ok_or(RibbleWhisperError::ParameterError(
-            "Channel configurations missing in OfflineTranscriberBuilder.".to_string(),
-        ))?;
+        let channels = self.channels.ok_or(RibbleWhisperError::MissingField {
+            builder: "OfflineTranscriberBuilder",
+            field: "channels",
+        })?;
         let model_retriever = self
             .model_retriever
-            .ok_or(RibbleWhisperError::ParameterError(
-                "Model retriever missing in OfflineTranscriberBuilder.".to_string(),
-            ))?;
+            .ok_or(RibbleWhisperError::MissingField {
+                builder: "OfflineTranscriberBuilder",
+                field: "model_retriever",
+            })?;
 
         // Vad can be None; if there is no VAD provided, the full speech will be processed.
         let vad = self.voice_activity_detector;
@@ -163,6 +221,10 @@ where
             channels,
             voice_activity_detector: vad,
             model_retriever,
+            text_processor: self.text_processor,
+            scheduler: self.scheduler,
+            grammar: self.grammar,
+            partial_segments: Mutex::new(Vec::new()),
         })
     }
 }
@@ -178,7 +240,14 @@ where
 }
 
 /// For running offline (non-realtime) transcription using whisper.
-/// NOTE: timestamps have not yet been implemented.
+/// NOTE: segment timestamps are relative to the audio whisper receives; when a voice activity
+/// detector is set, silences are removed first, so timestamps will not line up with the original
+/// audio.
+///
+/// Setting run_transcription to false cancels a transcription, which then returns
+/// Err([RibbleWhisperError::Cancelled]), whether it was cancelled while extracting voiced frames or
+/// during inference. Anything transcribed before it stopped can be retrieved with
+/// [OfflineTranscriber::take_partial_segments].
 pub struct OfflineTranscriber<V, M>
 where
     V: VAD<f32>,
@@ -193,6 +262,14 @@ where
     /// (Optional) Used to extract voiced segments to reduce overall transcription time.
     voice_activity_detector: Option<Arc<Mutex<V>>>,
     model_retriever: Arc<M>,
+    /// (Optional) For post-processing each transcribed segment.
+    text_processor: Option<Arc<dyn TextProcessor>>,
+    /// (Optional) For sharing the GPU with other transcribers.
+    scheduler: Option<InferenceScheduler>,
+    /// (Optional) For constraining the transcription to a grammar.
+    grammar: Option<Arc<Grammar>>,
+    /// The segments transcribed before the last cancelled transcription stopped.
+    partial_segments: Mutex<Vec<RibbleWhisperSegment>>,
 }
 
 impl<V, M> OfflineTranscriber<V, M>
@@ -202,37 +279,42 @@ where
 {
     fn run_transcription(
         &self,
-        full_params: whisper_rs::FullParams,
+        mut full_params: whisper_rs::FullParams,
         run_transcription: Arc<AtomicBool>,
-    ) -> Result<String, RibbleWhisperError> {
+    ) -> Result<Vec<RibbleWhisperSegment>, RibbleWhisperError> {
+        self.partial_segments.lock().clear();
+        if let Some(grammar) = self.grammar.as_deref() {
+            apply_grammar(&mut full_params, grammar, self.configs.grammar_penalty());
+        }
         let whisper_context_params = self.configs.as_whisper_context_params();
         // Since it's not possible to build an OfflineTranscriber without the ID set, this can be
         // safely unwrapped.
         let model_id = self.configs.model_id().unwrap();
 
-        let model_location = self.model_retriever.retrieve_model(model_id).ok_or(
-            RibbleWhisperError::ParameterError(format!("Failed to find model: {model_id}")),
-        )?;
+        let model_location = self
+            .model_retriever
+            .retrieve_model(model_id)
+            .ok_or(RibbleWhisperError::ModelNotFound { id: model_id })?;
 
         // Set up a whisper context
         let ctx = build_whisper_context(model_location, whisper_context_params)?;
 
         let mut whisper_state = ctx.create_state()?;
+        // For telling text tokens from special tokens when measuring confidence.
+        let eot = ctx.token_eot();
 
         // Prepare audio
         let mut audio_samples = match &self.audio {
-            WhisperAudioSample::I16(audio) => {
-                let len = audio.len();
-                let mut float_samples = vec![0.0; len];
-                whisper_rs::convert_integer_to_float_audio(audio, &mut float_samples)?;
-                Arc::from(float_samples)
-            }
+            WhisperAudioSample::I16(audio) => Arc::from(convert_integer_to_float_audio(audio)),
             WhisperAudioSample::F32(audio) => Arc::clone(audio),
         };
 
         // Extract speech frames if there's a VAD
         if let Some(vad) = self.voice_activity_detector.as_ref() {
-            audio_samples = Arc::from(vad.lock().extract_voiced_frames(&audio_samples))
+            audio_samples = Arc::from(
+                vad.lock()
+                    .extract_voiced_frames_with_abort(&audio_samples, &run_transcription)?,
+            )
         }
 
         let mono_audio = match self.channels {
@@ -242,30 +324,94 @@ where
             }
         };
 
-        if let Err(e) = whisper_state.full(full_params, &mono_audio) {
-            // Only escape early if the transcription is still supposed to be running;
-            // Otherwise, the abort callback fired true, and run_transcription is false - indicating
-            // the user has stopped the transcription.
-            if run_transcription.load(Ordering::Acquire) {
-                return Err(RibbleWhisperError::WhisperError(e));
+        let full_result = {
+            let _span = trace_span!(
+                DEBUG,
+                "inference_window",
+                samples = mono_audio.len(),
+                duration_ms = mono_audio.len() as f64 / WHISPER_SAMPLE_RATE * 1000f64,
+            )
+            .entered();
+            let mut permit = self
+                .scheduler
+                .as_ref()
+                .map(|scheduler| scheduler.acquire(InferencePriority::Background));
+            if let Some(permit) = permit.as_mut() {
+                // The permit outlives the call to full, which runs the callback on this thread.
+                unsafe {
+                    full_params.set_start_encoder_callback_user_data(
+                        permit as *mut InferencePermit as *mut c_void,
+                    );
+                    full_params.set_start_encoder_callback(Some(yield_callback));
+                }
             }
+            let inference_start = Instant::now();
+            let full_result = whisper_state.full(full_params, &mono_audio);
+            drop(permit);
+            record_window("offline", inference_start.elapsed(), mono_audio.len());
+            full_result
+        };
+
+        // If run_transcription is false, the user has stopped the transcription, (and the abort
+        // callback may have fired); whatever was transcribed is kept as the partial result.
+        let cancelled = !run_transcription.load(Ordering::Acquire);
+        if let Err(e) = full_result
+            && !cancelled
+        {
+            return Err(RibbleWhisperError::WhisperError(e));
         }
 
-        // Otherwise, expect the transcription to have been successful; subsequent errors
-        // will bubble up.
+        // Otherwise, expect the transcription to have been successful, (or cancelled);
+        // subsequent errors will bubble up.
         let num_segments = whisper_state.full_n_segments();
-        let mut text = Vec::with_capacity(num_segments as usize);
+        let mut segments = Vec::with_capacity(num_segments as usize);
+        let mut speakers = self
+            .configs
+            .using_tinydiarize()
+            .then(|| TurnLabeler::new(SpeakerId::default()));
+
+        let language = output_language(&self.configs, &whisper_state);
 
-        // Push the transcribed segments to the text buffer
+        // Collect the transcribed segments
         for segment in whisper_state.as_iter() {
-            text.push(segment.to_string())
+            let segment_text = segment.to_string();
+            let text = match self.text_processor.as_deref() {
+                // Processed text is trimmed, so restore the leading space whisper uses to
+                // separate segments.
+                Some(processor) => {
+                    let processed =
+                        processor.process_for_language(segment_text.trim().to_string(), language);
+                    format!(" {processed}")
+                }
+                None => segment_text,
+            };
+            segments.push(RibbleWhisperSegment {
+                text: Arc::from(text),
+                start_time: segment.start_timestamp(),
+                end_time: segment.end_timestamp(),
+                confidence: mean_token_probability(&segment, eot),
+                word_confidences: None,
+                speaker: speakers
+                    .as_mut()
+                    .map(|speakers| speakers.label(segment.next_segment_speaker_turn())),
+            });
         }
 
         // Clean up the whisper context
         drop(whisper_state);
         drop(ctx);
-        // Return the final transcription.
-        Ok(text.join("").trim().to_string())
+        if cancelled {
+            *self.partial_segments.lock() = segments;
+            return Err(RibbleWhisperError::Cancelled);
+        }
+        Ok(segments)
+    }
+
+    /// Takes the segments transcribed before the last transcription was cancelled, (see:
+    /// [OfflineTranscriber]), leaving none behind. This is empty if the last transcription
+    /// finished, or was cancelled before inference started, (e.g. while extracting voiced frames).
+    pub fn take_partial_segments(&self) -> Vec<RibbleWhisperSegment> {
+        std::mem::take(&mut *self.partial_segments.lock())
     }
 
     /// Loads a compatible whisper model, sets up the whisper state and runs the full model
@@ -273,12 +419,64 @@ where
     /// * run_transcription: `Arc<AtomicBool>`, a shared flag used to indicate when to stop transcribing
     /// # Returns
     /// * Ok(String) on success, Err(RibbleWhisperError) on failure
+    /// * Err(RibbleWhisperError::Cancelled) if run_transcription is set to false, (see:
+    ///   [OfflineTranscriber::take_partial_segments])
     pub fn process_audio(
         &self,
         run_transcription: Arc<AtomicBool>,
     ) -> Result<String, RibbleWhisperError> {
+        self.process_segments(run_transcription)
+            .map(|segments| join_segments(&segments))
+    }
+
+    /// Runs transcription like [OfflineTranscriber::process_audio], but returns the transcribed
+    /// segments with their timestamps, (e.g. for exporting subtitles).
+    /// # Arguments
+    /// * run_transcription: `Arc<AtomicBool>`, a shared flag used to indicate when to stop transcribing
+    /// # Returns
+    /// * Ok(`Vec<RibbleWhisperSegment>`) on success, Err(RibbleWhisperError) on failure
+    /// * Err(RibbleWhisperError::Cancelled) if run_transcription is set to false, (see:
+    ///   [OfflineTranscriber::take_partial_segments])
+    pub fn process_segments(
+        &self,
+        run_transcription: Arc<AtomicBool>,
+    ) -> Result<Vec<RibbleWhisperSegment>, RibbleWhisperError> {
+        let confs = Arc::clone(&self.configs);
+        self.run_with_abort(confs.as_whisper_full_params(), run_transcription)
+    }
+
+    /// Runs transcription like [OfflineTranscriber::process_segments], but with token-level
+    /// timestamps and one segment per word, (e.g. as the reference for re-aligning an edited
+    /// transcript with [crate::export::subtitles::align::CueAligner]).
+    /// # Arguments
+    /// * run_transcription: `Arc<AtomicBool>`, a shared flag used to indicate when to stop transcribing
+    /// * prompt: (Optional) text to prompt whisper with, (e.g. the edited transcript), to bias it
+    ///   towards the same vocabulary and spelling
+    /// # Returns
+    /// * Ok(`Vec<RibbleWhisperSegment>`) on success, Err(RibbleWhisperError) on failure
+    /// * Err(RibbleWhisperError::Cancelled) if run_transcription is set to false, (see:
+    ///   [OfflineTranscriber::take_partial_segments])
+    pub fn process_word_segments(
+        &self,
+        run_transcription: Arc<AtomicBool>,
+        prompt: Option<&str>,
+    ) -> Result<Vec<RibbleWhisperSegment>, RibbleWhisperError> {
         let confs = Arc::clone(&self.configs);
         let mut full_params = confs.as_whisper_full_params();
+        full_params.set_token_timestamps(true);
+        full_params.set_split_on_word(true);
+        full_params.set_max_len(1);
+        if let Some(prompt) = prompt {
+            full_params.set_initial_prompt(prompt);
+        }
+        self.run_with_abort(full_params, run_transcription)
+    }
+
+    fn run_with_abort(
+        &self,
+        mut full_params: whisper_rs::FullParams,
+        run_transcription: Arc<AtomicBool>,
+    ) -> Result<Vec<RibbleWhisperSegment>, RibbleWhisperError> {
         // Abort callback
         let r_transcription = Arc::clone(&run_transcription);
 
@@ -306,11 +504,34 @@ where
     /// * run_transcription: `Arc<AtomicBool>`, a shared flag used to indicate when to stop transcribing
     /// # Returns
     /// * Ok(String) on success, Err(RibbleWhisperError) on failure
+    /// * Err(RibbleWhisperError::Cancelled) if run_transcription is set to false, (see:
+    ///   [OfflineTranscriber::take_partial_segments])
     pub fn process_with_callbacks<P, S>(
         &self,
         run_transcription: Arc<AtomicBool>,
         callbacks: WhisperCallbacks<P, S>,
     ) -> Result<String, RibbleWhisperError>
+    where
+        P: OfflineWhisperProgressCallback,
+        S: OfflineWhisperNewSegmentCallback,
+    {
+        self.process_segments_with_callbacks(run_transcription, callbacks)
+            .map(|segments| join_segments(&segments))
+    }
+
+    /// Runs transcription like [OfflineTranscriber::process_with_callbacks], but returns the
+    /// transcribed segments with their timestamps.
+    /// # Arguments
+    /// * run_transcription: `Arc<AtomicBool>`, a shared flag used to indicate when to stop transcribing
+    /// # Returns
+    /// * Ok(`Vec<RibbleWhisperSegment>`) on success, Err(RibbleWhisperError) on failure
+    /// * Err(RibbleWhisperError::Cancelled) if run_transcription is set to false, (see:
+    ///   [OfflineTranscriber::take_partial_segments])
+    pub fn process_segments_with_callbacks<P, S>(
+        &self,
+        run_transcription: Arc<AtomicBool>,
+        callbacks: WhisperCallbacks<P, S>,
+    ) -> Result<Vec<RibbleWhisperSegment>, RibbleWhisperError>
     where
         P: OfflineWhisperProgressCallback,
         S: OfflineWhisperNewSegmentCallback,
@@ -379,6 +600,16 @@ where
     }
 }
 
+// Joins segments into the final transcription string.
+fn join_segments(segments: &[RibbleWhisperSegment]) -> String {
+    segments
+        .iter()
+        .map(|segment| segment.text())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
 // C-Callbacks (until "safe" handles are working in whisper-rs)
 // More callbacks will be implemented and exposed as necessary.
 // NOTE: As of the most current version of this library, all callbacks have been tested and should
@@ -388,6 +619,8 @@ where
 
 // Any panics/segfaults that are encountered imply a bug exists within the closure/function
 // passed to a StaticRibbleWhisperCallback object rather than the trampolines.
+// Panics cannot unwind across the C boundary, so the trampolines catch and log them; use a
+// TryCallbackReporter to have callback failures delivered to the caller.
 
 // This function gets called at the beginning of each run of the decoder to determine whether to
 // abort transcription. The function aborts on true.
@@ -405,6 +638,18 @@ unsafe extern "C" fn abort_callback(user_data: *mut c_void) -> bool {
     !run_transcription
 }
 
+// This callback gets called before each run of the encoder, (i.e. each ~30s window), to hand the
+// scheduler's turn to any waiting transcriber. Returning false would abort the transcription.
+unsafe extern "C" fn yield_callback(
+    _: *mut whisper_rs_sys::whisper_context,
+    _: *mut whisper_rs_sys::whisper_state,
+    user_data: *mut c_void,
+) -> bool {
+    let permit = unsafe { &mut *(user_data as *mut InferencePermit) };
+    permit.yield_turn();
+    true
+}
+
 // This callback gets called in order to forward progress updates from Whisper to a UI.
 // To guarantee the safety of the C library, whisper_context and whisper_states should
 // not be mutated.
@@ -416,7 +661,12 @@ unsafe extern "C" fn progress_callback<PC: OfflineWhisperProgressCallback>(
     user_data: *mut c_void,
 ) {
     let callback = unsafe { &mut *(user_data as *mut PC) };
-    callback.call(progress);
+    if let Err(payload) = catch_unwind(AssertUnwindSafe(|| callback.call(progress))) {
+        log_warn_or_eprint!(
+            "Progress callback panicked: {}",
+            panic_message(payload.as_ref())
+        );
+    }
 }
 
 // This callback fires once new segments have been confirmed to push the last n segments
@@ -440,5 +690,10 @@ unsafe extern "C" fn new_segment_callback<S: OfflineWhisperNewSegmentCallback>(
         segments.push(segment.to_string_lossy())
     }
     let new_segments = segments.join(" ");
-    callback.call(new_segments);
+    if let Err(payload) = catch_unwind(AssertUnwindSafe(|| callback.call(new_segments))) {
+        log_warn_or_eprint!(
+            "New segment callback panicked: {}",
+            panic_message(payload.as_ref())
+        );
+    }
 }
@@ -1,19 +1,40 @@
 use parking_lot::Mutex;
 use std::collections::VecDeque;
-use std::sync::{Arc, atomic::AtomicBool, atomic::Ordering};
-use std::thread::sleep;
+use std::sync::{Arc, atomic::AtomicBool, atomic::AtomicU64, atomic::Ordering};
+use std::thread::{JoinHandle, sleep};
 use std::time::{Duration, Instant};
-use strsim::jaro_winkler;
 
 use crate::audio::audio_ring_buffer::AudioRingBuffer;
+use crate::postprocess::TextProcessor;
+use crate::transcriber::audio_events::{AudioEvent, AudioEventClassifier, AudioEventTracker};
+use crate::transcriber::black_box::SessionRecorder;
+use crate::transcriber::corrections::Correction;
+use crate::transcriber::decode_diagnostics::{DecodeDiagnostics, collect_diagnostics};
+use crate::transcriber::dedup::{
+    ConflictPolicy, DedupConfigs, DedupDiagnosticsCallback, DedupEvent, MergeStrategy,
+};
+use crate::transcriber::scheduler::{InferencePermit, InferencePriority, InferenceScheduler};
+use crate::transcriber::speaker::{SnapshotSpeakers, SpeakerId, SpeakerTurn, TurnLabeler};
+use crate::transcriber::timestamps::{TimestampedSegment, TimestampedSnapshot, word_timings};
 use crate::transcriber::vad::VAD;
+use crate::transcriber::wake_phrase::WakePhrase;
+use crate::transcriber::window_hooks::WindowHook;
 use crate::transcriber::{
-    RibbleWhisperSegment, TranscriptionSnapshot, WHISPER_SAMPLE_RATE, WhisperControlPhrase,
-    WhisperOutput, build_whisper_context,
+    CONFIRMED_CHUNK_LEN, ConfirmedText, RibbleWhisperSegment, SessionStartReason, SnapshotEncoder,
+    WHISPER_SAMPLE_RATE, WhisperControlPhrase, WhisperOutput, build_whisper_context,
+    output_language,
 };
 use crate::utils::Sender;
+use crate::utils::callback::Callback;
 use crate::utils::errors::RibbleWhisperError;
-use crate::whisper::configs::WhisperRealtimeConfigs;
+use crate::utils::logging::{log_warn_or_eprint, trace_event, trace_span};
+use crate::utils::sink::OutputSink;
+use crate::utils::telemetry::{record_dropped_output, record_segments_confirmed, record_window};
+use crate::utils::thread::spawn_with_configs;
+use crate::whisper::configs::{
+    CommitPolicy, IdlePolicy, Language, OverflowPolicy, WhisperRealtimeConfigs,
+};
+use crate::whisper::grammar::{Grammar, apply_grammar};
 use crate::whisper::model::ModelRetriever;
 use std::error::Error;
 
@@ -21,22 +42,12 @@ use std::error::Error;
 // latency.
 // ATM, I've yet to solve this particularly well.
 
-// Conservatively at 90% match
-// It does not do well with strings that don't share a prefix, so "has/as" will end up being an
-// artifact. Until I come up with some bulletproof, clever-er solution that runs with low latency,
-// these errors may just need to be tolerated.
-pub const DIFF_THRESHOLD_HIGH: f64 = 0.9;
-// This is not as high as the "best match" and is intended for stride heuristics.
-pub const DIFF_THRESHOLD_MED: f64 = 0.85;
-pub const DIFF_THRESHOLD_LOW: f64 = 0.75;
-pub const TIMESTAMP_GAP: i64 = 1000;
-pub const TIMESTAMP_EPSILON: i64 = 10;
-
-pub const N_TOKENS: usize = 5;
-// This is a little on the "safe" side, roughly around the amount of time it takes to say a word.
-// The string deduplication does a relatively decent job with catching these, so they will get
-// stamped out.
-pub const RETAIN_MS: usize = 500;
+pub use crate::transcriber::dedup::{
+    DIFF_THRESHOLD_HIGH, DIFF_THRESHOLD_LOW, DIFF_THRESHOLD_MED, N_TOKENS, TIMESTAMP_EPSILON,
+    TIMESTAMP_GAP,
+};
+
+pub use crate::whisper::configs::RETAIN_MS;
 
 // This is an artifact from the old implementation--but I believe whisper resolves across ~3
 // segments before it "confirms", so this is just going to main here until it really needs to
@@ -49,6 +60,8 @@ pub const PAUSE_DURATION: u64 = 100;
 pub const N_SAMPLES_30S: usize = ((1e-3 * 30000.0) * WHISPER_SAMPLE_RATE) as usize;
 // This could probably be a little shorter
 const VAD_TIMEOUT_MS: u128 = 1500;
+// How often to check for voice while paused by the idle policy.
+const IDLE_POLL_DURATION: u64 = 1000;
 
 const MIN_SIZE_FOR_WHISPER: usize = WHISPER_SAMPLE_RATE as usize;
 
@@ -57,16 +70,27 @@ const MIN_SIZE_FOR_WHISPER: usize = WHISPER_SAMPLE_RATE as usize;
 /// Multiple VAD implementations have been provided, see: [crate::transcriber::vad]
 /// Silero: [crate::transcriber::vad::Silero] is recommended for accuracy, but is very sensitive to background noise.
 /// See: examples/realtime_transcriber.rs for example usage.
-pub struct RealtimeTranscriberBuilder<V, M>
+/// Outputs can be sent over any [OutputSink]; this defaults to [crate::utils::Sender].
+pub struct RealtimeTranscriberBuilder<V, M, S = Sender<WhisperOutput>>
 where
     V: VAD<f32>,
     M: ModelRetriever,
+    S: OutputSink<WhisperOutput>,
 {
     configs: Option<Arc<WhisperRealtimeConfigs>>,
     audio_buffer: Option<AudioRingBuffer<f32>>,
-    output_sender: Option<Sender<WhisperOutput>>,
+    output_sender: Option<S>,
+    control_sender: Option<Box<dyn OutputSink<WhisperOutput> + Send>>,
     model_retriever: Option<Arc<M>>,
     voice_activity_detector: Option<Arc<Mutex<V>>>,
+    text_processor: Option<Arc<dyn TextProcessor>>,
+    scheduler: Option<InferenceScheduler>,
+    dedup_diagnostics: Option<Arc<Mutex<dyn DedupDiagnosticsCallback>>>,
+    session_recorder: Option<Arc<Mutex<dyn SessionRecorder>>>,
+    audio_event_classifier: Option<Arc<Mutex<dyn AudioEventClassifier>>>,
+    grammar: Option<Arc<Grammar>>,
+    window_hooks: Vec<Arc<Mutex<dyn WindowHook>>>,
+    wake_phrase: Option<Arc<WakePhrase>>,
 }
 
 impl<V, M> RealtimeTranscriberBuilder<V, M>
@@ -79,11 +103,27 @@ where
             configs: None,
             audio_buffer: None,
             output_sender: None,
+            control_sender: None,
             model_retriever: None,
             voice_activity_detector: None,
+            text_processor: None,
+            scheduler: None,
+            dedup_diagnostics: None,
+            session_recorder: None,
+            audio_event_classifier: None,
+            grammar: None,
+            window_hooks: vec![],
+            wake_phrase: None,
         }
     }
+}
 
+impl<V, M, S> RealtimeTranscriberBuilder<V, M, S>
+where
+    V: VAD<f32>,
+    M: ModelRetriever,
+    S: OutputSink<WhisperOutput>,
+{
     /// Set configurations.
     pub fn with_configs(mut self, configs: WhisperRealtimeConfigs) -> Self {
         self.configs = Some(Arc::new(configs));
@@ -95,9 +135,39 @@ where
         self
     }
 
-    /// Set the output sender.
-    pub fn with_output_sender(mut self, sender: Sender<WhisperOutput>) -> Self {
-        self.output_sender = Some(sender);
+    /// Set the output sender. Any [OutputSink] can be used, (e.g. a tokio sender for async
+    /// applications).
+    pub fn with_output_sender<S2: OutputSink<WhisperOutput>>(
+        self,
+        sender: S2,
+    ) -> RealtimeTranscriberBuilder<V, M, S2> {
+        RealtimeTranscriberBuilder {
+            configs: self.configs,
+            audio_buffer: self.audio_buffer,
+            output_sender: Some(sender),
+            control_sender: self.control_sender,
+            model_retriever: self.model_retriever,
+            voice_activity_detector: self.voice_activity_detector,
+            text_processor: self.text_processor,
+            scheduler: self.scheduler,
+            dedup_diagnostics: self.dedup_diagnostics,
+            session_recorder: self.session_recorder,
+            audio_event_classifier: self.audio_event_classifier,
+            grammar: self.grammar,
+            window_hooks: self.window_hooks,
+            wake_phrase: self.wake_phrase,
+        }
+    }
+
+    /// Set a separate sender for control phrases and decode diagnostics, so that a UI's render
+    /// loop only receives transcript updates, (snapshots, deltas, corrections, audio events and
+    /// timestamped snapshots), on the output sender. By default, everything is sent on the output
+    /// sender.
+    pub fn with_control_sender<C: OutputSink<WhisperOutput> + Send + 'static>(
+        mut self,
+        sender: C,
+    ) -> Self {
+        self.control_sender = Some(Box::new(sender));
         self
     }
 
@@ -106,13 +176,22 @@ where
     pub fn with_model_retriever<M2: ModelRetriever>(
         self,
         model_retriever: M2,
-    ) -> RealtimeTranscriberBuilder<V, M2> {
+    ) -> RealtimeTranscriberBuilder<V, M2, S> {
         RealtimeTranscriberBuilder {
             configs: self.configs,
             audio_buffer: self.audio_buffer,
             output_sender: self.output_sender,
+            control_sender: self.control_sender,
             model_retriever: Some(Arc::new(model_retriever)),
             voice_activity_detector: self.voice_activity_detector,
+            text_processor: self.text_processor,
+            scheduler: self.scheduler,
+            dedup_diagnostics: self.dedup_diagnostics,
+            session_recorder: self.session_recorder,
+            audio_event_classifier: self.audio_event_classifier,
+            grammar: self.grammar,
+            window_hooks: self.window_hooks,
+            wake_phrase: self.wake_phrase,
         }
     }
 
@@ -121,13 +200,22 @@ where
     pub fn with_shared_model_retriever<M2: ModelRetriever>(
         self,
         model_retriever: Arc<M2>,
-    ) -> RealtimeTranscriberBuilder<V, M2> {
+    ) -> RealtimeTranscriberBuilder<V, M2, S> {
         RealtimeTranscriberBuilder {
             configs: self.configs,
             audio_buffer: self.audio_buffer,
             output_sender: self.output_sender,
+            control_sender: self.control_sender,
             model_retriever: Some(Arc::clone(&model_retriever)),
             voice_activity_detector: self.voice_activity_detector,
+            text_processor: self.text_processor,
+            scheduler: self.scheduler,
+            dedup_diagnostics: self.dedup_diagnostics,
+            session_recorder: self.session_recorder,
+            audio_event_classifier: self.audio_event_classifier,
+            grammar: self.grammar,
+            window_hooks: self.window_hooks,
+            wake_phrase: self.wake_phrase,
         }
     }
 
@@ -135,14 +223,23 @@ where
     pub fn with_voice_activity_detector<V2: VAD<f32> + Sync + Send>(
         self,
         vad: V2,
-    ) -> RealtimeTranscriberBuilder<V2, M> {
+    ) -> RealtimeTranscriberBuilder<V2, M, S> {
         let voice_activity_detector = Some(Arc::new(Mutex::new(vad)));
         RealtimeTranscriberBuilder {
             configs: self.configs,
             audio_buffer: self.audio_buffer,
             output_sender: self.output_sender,
+            control_sender: self.control_sender,
             model_retriever: self.model_retriever,
             voice_activity_detector,
+            text_processor: self.text_processor,
+            scheduler: self.scheduler,
+            dedup_diagnostics: self.dedup_diagnostics,
+            session_recorder: self.session_recorder,
+            audio_event_classifier: self.audio_event_classifier,
+            grammar: self.grammar,
+            window_hooks: self.window_hooks,
+            wake_phrase: self.wake_phrase,
         }
     }
     /// Set the voice activity detector to a shared VAD, (e.g. pre-allocated).
@@ -151,63 +248,156 @@ where
     pub fn with_shared_voice_activity_detector<V2: VAD<f32> + Sync + Send>(
         self,
         vad: Arc<Mutex<V2>>,
-    ) -> RealtimeTranscriberBuilder<V2, M> {
+    ) -> RealtimeTranscriberBuilder<V2, M, S> {
         RealtimeTranscriberBuilder {
             configs: self.configs,
             audio_buffer: self.audio_buffer,
             output_sender: self.output_sender,
+            control_sender: self.control_sender,
             model_retriever: self.model_retriever,
             voice_activity_detector: Some(Arc::clone(&vad)),
+            text_processor: self.text_processor,
+            scheduler: self.scheduler,
+            dedup_diagnostics: self.dedup_diagnostics,
+            session_recorder: self.session_recorder,
+            audio_event_classifier: self.audio_event_classifier,
+            grammar: self.grammar,
+            window_hooks: self.window_hooks,
+            wake_phrase: self.wake_phrase,
         }
     }
 
+    /// Set a post-processor for finalized text, (e.g. a [TextPipeline](crate::postprocess::TextPipeline)).
+    /// This is applied to each segment as it is confirmed; the working set in each
+    /// [TranscriptionSnapshot](crate::transcriber::TranscriptionSnapshot) is sent unprocessed.
+    pub fn with_text_processor<P: TextProcessor + 'static>(mut self, text_processor: P) -> Self {
+        self.text_processor = Some(Arc::new(text_processor));
+        self
+    }
+
+    /// Set a scheduler shared with other transcribers running on the same GPU.
+    /// Each inference window runs at [InferencePriority::Realtime], ahead of any waiting offline
+    /// transcription.
+    pub fn with_inference_scheduler(mut self, scheduler: InferenceScheduler) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Set a callback to receive a [DedupEvent] for every overlap the transcriber resolves, for
+    /// tuning [DedupConfigs]. This is called from the transcription thread, so keep it cheap.
+    pub fn with_dedup_diagnostics<D: DedupDiagnosticsCallback>(mut self, diagnostics: D) -> Self {
+        self.dedup_diagnostics = Some(Arc::new(Mutex::new(diagnostics)));
+        self
+    }
+
+    /// Set a recorder to receive every VAD decision, inference window and whisper output of the
+    /// session, (e.g. a [BlackBoxRecorder](crate::transcriber::black_box::BlackBoxRecorder) for
+    /// capturing a reproducible bug report). See: [crate::transcriber::black_box]
+    pub fn with_session_recorder<R: SessionRecorder>(mut self, recorder: R) -> Self {
+        self.session_recorder = Some(Arc::new(Mutex::new(recorder)));
+        self
+    }
+
+    /// Set a classifier to detect non-speech events, (e.g. applause), in the audio. Events are
+    /// sent as [WhisperOutput::AudioEvent]s so that captions can include markers like "[music]".
+    /// This is called from the transcription thread for every VAD window, so keep it cheap.
+    /// See: [crate::transcriber::audio_events]
+    pub fn with_audio_event_classifier<C: AudioEventClassifier>(mut self, classifier: C) -> Self {
+        self.audio_event_classifier = Some(Arc::new(Mutex::new(classifier)));
+        self
+    }
+
+    /// (Optional) Set a grammar to constrain the transcription to, (e.g. a fixed set of voice
+    /// commands). See: [crate::whisper::grammar]
+    pub fn with_grammar(mut self, grammar: Grammar) -> Self {
+        self.grammar = Some(Arc::new(grammar));
+        self
+    }
+
+    /// Add a hook to process the audio of each inference window right before whisper runs, (e.g.
+    /// a denoiser). Hooks run in the order they were added. This is called from the transcription
+    /// thread for every inference window, so keep it cheap.
+    /// See: [crate::transcriber::window_hooks]
+    pub fn with_window_hook<H: WindowHook>(mut self, hook: H) -> Self {
+        self.window_hooks.push(Arc::new(Mutex::new(hook)));
+        self
+    }
+
+    /// Set a wake phrase to start each session with. The transcriber stays dormant, (only running
+    /// whisper over short windows with voice in them), until the phrase is heard or the session is
+    /// started with [RealtimeTranscriberHandle::start_session].
+    /// See: [crate::transcriber::wake_phrase]
+    pub fn with_wake_phrase(mut self, wake_phrase: WakePhrase) -> Self {
+        self.wake_phrase = Some(Arc::new(wake_phrase));
+        self
+    }
+
     /// This returns a tuple struct containing both the transcriber object and a handle to check the
     /// transcriber's ready state from another location.
-    /// Returns Err when a parameter is missing.
+    /// Returns Err when a parameter is missing, or the configured window lengths are inconsistent,
+    /// (see: [crate::whisper::configs::WindowLengths::validate]).
     pub fn build(
         self,
-    ) -> Result<(RealtimeTranscriber<V, M>, RealtimeTranscriberHandle), RibbleWhisperError> {
-        let configs = self.configs.ok_or(RibbleWhisperError::ParameterError(
-            "Configs missing in RealtimeTranscriberBuilder.".to_string(),
-        ))?;
+    ) -> Result<(RealtimeTranscriber<V, M, S>, RealtimeTranscriberHandle), RibbleWhisperError> {
+        let configs = self.configs.ok_or(RibbleWhisperError::MissingField {
+            builder: "RealtimeTranscriberBuilder",
+            field: "configs",
+        })?;
 
         let model_retriever = self
             .model_retriever
-            .ok_or(RibbleWhisperError::ParameterError(
-                "Model retriever missing in RealtimeTranscriberBuilder.".to_string(),
-            ))?;
-
-        let _model_id = configs
-            .model_id()
-            .ok_or(RibbleWhisperError::ParameterError(
-                "Configs are missing model ID in RealtimeTranscriberBuilder.".to_string(),
-            ))?;
-
-        let audio_feed = self.audio_buffer.ok_or(RibbleWhisperError::ParameterError(
-            "Audio feed missing in RealtimeTranscriberBuilder".to_string(),
-        ))?;
-        let output_sender = self
-            .output_sender
-            .ok_or(RibbleWhisperError::ParameterError(
-                "Output sender missing in RealtimeTranscriberBuilder".to_string(),
-            ))?;
+            .ok_or(RibbleWhisperError::MissingField {
+                builder: "RealtimeTranscriberBuilder",
+                field: "model_retriever",
+            })?;
+
+        let _model_id = configs.model_id().ok_or(RibbleWhisperError::MissingField {
+            builder: "RealtimeTranscriberBuilder",
+            field: "model_id",
+        })?;
+        configs.window_lengths().validate()?;
+
+        let audio_feed = self.audio_buffer.ok_or(RibbleWhisperError::MissingField {
+            builder: "RealtimeTranscriberBuilder",
+            field: "audio_buffer",
+        })?;
+        let output_sender = self.output_sender.ok_or(RibbleWhisperError::MissingField {
+            builder: "RealtimeTranscriberBuilder",
+            field: "output_sender",
+        })?;
         let vad = self
             .voice_activity_detector
-            .ok_or(RibbleWhisperError::ParameterError(
-                "Voice activity detector missing in RealtimeTranscriberBuilder.".to_string(),
-            ))?;
+            .ok_or(RibbleWhisperError::MissingField {
+                builder: "RealtimeTranscriberBuilder",
+                field: "voice_activity_detector",
+            })?;
         let ready = Arc::new(AtomicBool::new(false));
+        let timeout_extension = Arc::new(AtomicU64::new(0));
+        let start_requested = Arc::new(AtomicBool::new(false));
 
         let handle = RealtimeTranscriberHandle {
             ready: Arc::clone(&ready),
+            timeout_extension: Arc::clone(&timeout_extension),
+            start_requested: Arc::clone(&start_requested),
         };
         let transcriber = RealtimeTranscriber {
             configs,
             audio_feed,
             output_sender,
+            control_sender: self.control_sender,
             ready,
+            timeout_extension,
+            start_requested,
             model_retriever,
             vad,
+            text_processor: self.text_processor,
+            scheduler: self.scheduler,
+            dedup_diagnostics: self.dedup_diagnostics,
+            session_recorder: self.session_recorder,
+            audio_event_classifier: self.audio_event_classifier,
+            grammar: self.grammar,
+            window_hooks: self.window_hooks,
+            wake_phrase: self.wake_phrase,
         };
         Ok((transcriber, handle))
     }
@@ -217,59 +407,421 @@ where
 /// RealtimeTranscriber cannot be shared across threads because it has a singular ready state.
 /// It is also infeasible to call [Transcriber::process_audio] in parallel due
 /// to the cost of running whisper.
-pub struct RealtimeTranscriber<V, M>
+pub struct RealtimeTranscriber<V, M, S = Sender<WhisperOutput>>
 where
     V: VAD<f32>,
     M: ModelRetriever,
+    S: OutputSink<WhisperOutput>,
 {
     configs: Arc<WhisperRealtimeConfigs>,
     /// The shared input buffer from which samples are pulled for transcription
     audio_feed: AudioRingBuffer<f32>,
     /// For sending output to a UI
-    output_sender: Sender<WhisperOutput>,
+    output_sender: S,
+    /// (Optional) For sending control phrases and diagnostics apart from the transcript.
+    control_sender: Option<Box<dyn OutputSink<WhisperOutput> + Send>>,
     /// Ready flag.
     /// A RealtimeTranscriber is considered to be ready when all of its whisper initialization has completed,
     /// and it is about to enter its transcription loop.
     /// NOTE: This cannot be accessed directly, because RealtimeTranscriber is not Sync.
     /// Use a [RealtimeTranscriberHandle] to check the ready state.
     ready: Arc<AtomicBool>,
+    /// Time added to the timeout, (in ms), with [RealtimeTranscriberHandle::extend_timeout], not
+    /// yet applied to the running session.
+    timeout_extension: Arc<AtomicU64>,
+    /// Set with [RealtimeTranscriberHandle::start_session] to start a dormant session.
+    start_requested: Arc<AtomicBool>,
     /// For obtaining a model's file path based on an ID stored in [WhisperRealtimeConfigs].
     model_retriever: Arc<M>,
     /// For voice detection
     vad: Arc<Mutex<V>>,
+    /// (Optional) For post-processing confirmed segments.
+    text_processor: Option<Arc<dyn TextProcessor>>,
+    /// (Optional) For sharing the GPU with other transcribers.
+    scheduler: Option<InferenceScheduler>,
+    /// (Optional) For collecting deduplication tuning data.
+    dedup_diagnostics: Option<Arc<Mutex<dyn DedupDiagnosticsCallback>>>,
+    /// (Optional) For recording sessions to reproduce bugs.
+    session_recorder: Option<Arc<Mutex<dyn SessionRecorder>>>,
+    /// (Optional) For annotating the transcript with non-speech events.
+    audio_event_classifier: Option<Arc<Mutex<dyn AudioEventClassifier>>>,
+    /// (Optional) For constraining the transcription to a grammar.
+    grammar: Option<Arc<Grammar>>,
+    /// (Optional) For processing each inference window before whisper runs.
+    window_hooks: Vec<Arc<Mutex<dyn WindowHook>>>,
+    /// (Optional) For starting sessions hands-free.
+    wake_phrase: Option<Arc<WakePhrase>>,
+}
+
+// State reused across inference windows, so that the realtime loop only allocates for the text of
+// new segments rather than re-growing buffers and rebuilding parameters every window.
+struct InferenceScratch<'a, 'b> {
+    // Audio read from the ring buffer, preallocated for the longest window whisper accepts.
+    audio: Vec<f32>,
+    // Segments from the most recent inference, drained into the working set.
+    segments: Vec<RibbleWhisperSegment>,
+    // The same segments with their word timings, when timestamped snapshots are enabled.
+    timestamped: Vec<TimestampedSegment>,
+    // Segments drained from an overflowing working set, waiting to be confirmed.
+    confirm_from: VecDeque<RibbleWhisperSegment>,
+    // The configured parameters; each window only changes whether context is used.
+    params: whisper_rs::FullParams<'a, 'b>,
+}
+
+impl<'a, 'b> InferenceScratch<'a, 'b> {
+    fn new(params: whisper_rs::FullParams<'a, 'b>) -> Self {
+        Self {
+            audio: Vec::with_capacity(N_SAMPLES_30S),
+            segments: Vec::with_capacity(WORKING_SET_SIZE),
+            timestamped: vec![],
+            confirm_from: VecDeque::with_capacity(WORKING_SET_SIZE),
+            params,
+        }
+    }
+
+    // Whisper consumes its parameters, so each window runs with a copy of the template.
+    fn window_params(&self, use_context: bool) -> whisper_rs::FullParams<'a, 'b> {
+        let mut params = self.params.clone();
+        params.set_no_context(!use_context);
+        params
+    }
+
+    // Replaces the scratch segments with the output of the last inference.
+    // If there's a null pointer, the segment is skipped; expect that to happen extremely
+    // rarely-to-never.
+    // When diarizing, segments are labelled from first_speaker onward by the turns whisper detects.
+    // Word confidences are only collected when word_confidences is set, (see:
+    // [ConflictPolicy::Confidence]), and word timings when timestamped is set.
+    fn collect_segments(
+        &mut self,
+        whisper_state: &whisper_rs::WhisperState,
+        first_speaker: Option<SpeakerId>,
+        eot: whisper_rs::WhisperTokenId,
+        word_confidences: bool,
+        timestamped: bool,
+    ) {
+        self.segments.clear();
+        self.timestamped.clear();
+        let mut speakers = first_speaker.map(TurnLabeler::new);
+        for ws in whisper_state.as_iter() {
+            let speaker = speakers
+                .as_mut()
+                .map(|speakers| speakers.label(ws.next_segment_speaker_turn()));
+            let Ok(segment) =
+                RibbleWhisperSegment::from_whisper_segment(&ws, eot, word_confidences)
+            else {
+                continue;
+            };
+            let segment = RibbleWhisperSegment { speaker, ..segment };
+            // Word timings come from the same whisper segment, so they can't be paired with the
+            // wrong text.
+            if timestamped {
+                self.timestamped.push(TimestampedSegment {
+                    segment: segment.clone(),
+                    words: word_timings(&ws, eot),
+                });
+            }
+            self.segments.push(segment);
+        }
+    }
+
+    // The timestamped segments of the last inference window, which starts at window_start,
+    // skipping those already committed.
+    fn timestamped_snapshot(&self, window_start: i64, committed_until: i64) -> TimestampedSnapshot {
+        let segments = self
+            .timestamped
+            .iter()
+            .filter(|timestamped| window_start + timestamped.segment.end_time > committed_until)
+            .map(|timestamped| timestamped.clone().offset(window_start))
+            .collect();
+        TimestampedSnapshot {
+            window_start,
+            segments,
+        }
+    }
 }
 
-impl<V, M> RealtimeTranscriber<V, M>
+impl<V, M, S> RealtimeTranscriber<V, M, S>
 where
     V: VAD<f32>,
     M: ModelRetriever,
+    S: OutputSink<WhisperOutput>,
 {
-    fn send_snapshot(&self, confirmed: Arc<str>, segments: &VecDeque<RibbleWhisperSegment>) {
-        let string_segments = segments
-            .iter()
-            .map(|segment| segment.text.clone())
-            .collect();
-        let snapshot = Arc::new(TranscriptionSnapshot::new(confirmed, string_segments));
+    fn send_snapshot(
+        &self,
+        encoder: &mut SnapshotEncoder,
+        confirmed: &mut ConfirmedTranscript,
+        segments: &VecDeque<RibbleWhisperSegment>,
+    ) {
+        if let Some(correction) = confirmed.correction(self.configs.correction_horizon()) {
+            trace_event!(
+                DEBUG,
+                start = correction.range().start,
+                end = correction.range().end,
+                "Correction"
+            );
+            if let Err(e) = self
+                .output_sender
+                .try_send(WhisperOutput::Correction(correction))
+            {
+                record_dropped_output("correction");
+                log_warn_or_eprint!("Error sending correction: {:#?}", e.source());
+            }
+        }
+        let snapshot = encoder.build_snapshot(
+            confirmed.text(),
+            segments.iter().map(|segment| &segment.text),
+            confirmed.speakers(segments),
+        );
+        let Some(output) = encoder.encode(snapshot) else {
+            return;
+        };
+
+        if let Err(e) = self.output_sender.try_send(output) {
+            // Deltas sent after a dropped update would not apply; resync with a full snapshot.
+            encoder.reset();
+            record_dropped_output("snapshot");
+            log_warn_or_eprint!(
+                "Error sending transcription-snapshot mid loop: {:#?}",
+                e.source()
+            );
+        }
+    }
 
+    // The speaker a window starts with, when diarizing: a segment merge window continues the
+    // working set; otherwise the window starts after the confirmed text. tinydiarize can't detect
+    // a turn across windows, so a new window is assumed to continue with the last speaker.
+    fn window_speaker(
+        &self,
+        segment_merge: bool,
+        working_set: &VecDeque<RibbleWhisperSegment>,
+        confirmed: &ConfirmedTranscript,
+    ) -> Option<SpeakerId> {
+        if !self.configs.using_tinydiarize() {
+            return None;
+        }
+        let previous = match segment_merge {
+            true => working_set.back().and_then(RibbleWhisperSegment::speaker),
+            false => None,
+        };
+        Some(
+            previous
+                .or_else(|| confirmed.last_speaker())
+                .unwrap_or_default(),
+        )
+    }
+
+    fn record(&self, record: impl FnOnce(&mut dyn SessionRecorder)) {
+        if let Some(recorder) = self.session_recorder.as_deref() {
+            record(&mut *recorder.lock());
+        }
+    }
+
+    // Runs the window hooks over an inference window, in order.
+    fn apply_window_hooks(&self, samples: &mut [f32]) {
+        for hook in &self.window_hooks {
+            hook.lock().process(samples);
+        }
+    }
+
+    // Classifies a VAD window, (ending elapsed since the session started), and sends the events
+    // that have ended.
+    fn classify_audio_events(
+        &self,
+        tracker: &mut AudioEventTracker,
+        samples: &[f32],
+        elapsed: Duration,
+    ) {
+        let Some(classifier) = self.audio_event_classifier.as_deref() else {
+            return;
+        };
+        let scores = classifier.lock().classify(samples);
+        // Timestamps are in centiseconds, (like segments).
+        let end_time = (elapsed.as_millis() / 10) as i64;
+        let start_time =
+            (end_time - (samples.len() as f64 / WHISPER_SAMPLE_RATE * 100f64) as i64).max(0);
+        let finished = tracker.update(scores, start_time, end_time);
+        self.send_audio_events(finished);
+    }
+
+    fn send_audio_events(&self, events: Vec<AudioEvent>) {
+        for event in events {
+            trace_event!(DEBUG, kind = event.kind().label(), "Audio event");
+            if let Err(e) = self
+                .output_sender
+                .try_send(WhisperOutput::AudioEvent(event))
+            {
+                record_dropped_output("audio_event");
+                log_warn_or_eprint!("Error sending audio event: {:#?}", e.source());
+            }
+        }
+    }
+
+    // Control phrases and diagnostics go to the control sender, if one was set.
+    fn control_sink(&self) -> &dyn OutputSink<WhisperOutput> {
+        match self.control_sender.as_deref() {
+            Some(sender) => sender,
+            None => &self.output_sender,
+        }
+    }
+
+    // Sends the decoder's diagnostics for the last inference window, if enabled.
+    fn send_decode_diagnostics(&self, diagnostics: impl FnOnce() -> DecodeDiagnostics) {
+        if !self.configs.decode_diagnostics() {
+            return;
+        }
+        let diagnostics = diagnostics();
+        trace_event!(
+            DEBUG,
+            n_tokens = diagnostics.n_tokens,
+            avg_logprob = diagnostics.avg_logprob,
+            fallback_exhausted = diagnostics.fallback_exhausted(),
+            "Decode diagnostics"
+        );
+        if let Err(e) = self
+            .control_sink()
+            .try_send(WhisperOutput::DecodeDiagnostics(Arc::new(diagnostics)))
+        {
+            record_dropped_output("decode_diagnostics");
+            log_warn_or_eprint!("Error sending decode diagnostics: {:#?}", e.source());
+        }
+    }
+
+    // Sends the segments of the last inference window with word-level timestamps, if enabled.
+    fn send_timestamped_snapshot(&self, snapshot: impl FnOnce() -> TimestampedSnapshot) {
+        if !self.configs.timestamped_snapshots() {
+            return;
+        }
+        let snapshot = snapshot();
+        trace_event!(
+            DEBUG,
+            window_start = snapshot.window_start,
+            n_segments = snapshot.segments.len(),
+            "Timestamped snapshot"
+        );
         if let Err(e) = self
             .output_sender
-            .try_send(WhisperOutput::TranscriptionSnapshot(snapshot))
+            .try_send(WhisperOutput::TimestampedSnapshot(Arc::new(snapshot)))
         {
-            #[cfg(feature = "ribble-logging")]
-            {
-                log::warn!(
-                    "Error sending transcription-snapshot mid loop: {:#?}",
-                    e.source()
-                )
+            record_dropped_output("timestamped_snapshot");
+            log_warn_or_eprint!("Error sending timestamped snapshot: {:#?}", e.source());
+        }
+    }
+
+    // Runs inference over the window in the scratch audio, re-running it while the retry policy
+    // finds the output broken, then sends the decode diagnostics of the attempt that was kept.
+    fn run_window(
+        &self,
+        whisper_state: &mut whisper_rs::WhisperState,
+        scratch: &InferenceScratch,
+        eot: whisper_rs::WhisperTokenId,
+        use_context: bool,
+    ) -> Result<(), RibbleWhisperError> {
+        let retry_policy = self.configs.retry_policy();
+        let permit = self.acquire_inference();
+        let inference_start = Instant::now();
+        let _ = whisper_state.full(scratch.window_params(use_context), &scratch.audio)?;
+        let mut retries = 0;
+        while retries < retry_policy.max_retries() {
+            let diagnostics = collect_diagnostics(
+                whisper_state,
+                eot,
+                scratch.audio.len(),
+                inference_start.elapsed(),
+                retry_policy.use_context(use_context, retries),
+            );
+            if !retry_policy.should_retry(&diagnostics) {
+                break;
             }
-            #[cfg(not(feature = "ribble-logging"))]
-            {
-                eprintln!(
-                    "Error sending transcription-snapshot mid loop: {:#?}",
-                    e.source()
-                )
+            retries += 1;
+            trace_event!(
+                DEBUG,
+                retries,
+                compression_ratio = diagnostics.compression_ratio,
+                repetitive = diagnostics.repetitive(),
+                "Retrying inference window"
+            );
+            let mut params = scratch.window_params(retry_policy.use_context(use_context, retries));
+            params.set_temperature(retry_policy.temperature(retries));
+            let _ = whisper_state.full(params, &scratch.audio)?;
+        }
+        drop(permit);
+        let inference_time = inference_start.elapsed();
+        record_window("realtime", inference_time, scratch.audio.len());
+        self.send_decode_diagnostics(|| DecodeDiagnostics {
+            retries,
+            ..collect_diagnostics(
+                whisper_state,
+                eot,
+                scratch.audio.len(),
+                inference_time,
+                retry_policy.use_context(use_context, retries),
+            )
+        });
+        Ok(())
+    }
+
+    // Listens for the wake phrase until it's heard, (or the session is started by hand), then
+    // clears the audio buffer so that nothing heard while dormant is transcribed. Whisper only runs
+    // over windows the VAD hears voice in. Returns None if the transcriber is stopped first.
+    fn await_wake_phrase(
+        &self,
+        wake_phrase: &WakePhrase,
+        whisper_state: &mut whisper_rs::WhisperState,
+        audio: &mut Vec<f32>,
+        run_transcription: &AtomicBool,
+    ) -> Result<Option<SessionStartReason>, RibbleWhisperError> {
+        self.send_control_phrase(WhisperControlPhrase::AwaitingWakePhrase);
+        // Listen without the session's grammar or context.
+        let mut params = self.configs.as_whisper_full_params();
+        params.set_no_context(true);
+        let vad_size =
+            (self.configs.vad_sample_len() as f64 / 1000f64 * WHISPER_SAMPLE_RATE) as usize;
+
+        while run_transcription.load(Ordering::Acquire) {
+            if self.start_requested.swap(false, Ordering::AcqRel) {
+                return Ok(Some(self.start_session(SessionStartReason::Manual)));
+            }
+            sleep(Duration::from_millis(self.configs.vad_sample_len() as u64));
+
+            self.audio_feed
+                .read_into(self.configs.vad_sample_len(), audio);
+            if audio.len() < vad_size || !self.vad.lock().voice_detected(audio) {
+                continue;
+            }
+            self.audio_feed.read_into(wake_phrase.window_ms(), audio);
+            if audio.len() < MIN_SIZE_FOR_WHISPER {
+                continue;
+            }
+
+            let permit = self.acquire_inference();
+            let _ = whisper_state.full(params.clone(), audio)?;
+            drop(permit);
+            let heard: String = whisper_state
+                .as_iter()
+                .filter_map(|segment| segment.to_str_lossy().ok().map(|text| text.into_owned()))
+                .collect();
+            trace_event!(DEBUG, heard = heard.as_str(), "Listening for wake phrase");
+            if let Some(phrase) = wake_phrase.detect(&heard) {
+                let reason = SessionStartReason::WakePhrase(phrase.to_string());
+                return Ok(Some(self.start_session(reason)));
             }
         }
+        Ok(None)
+    }
+
+    // Clears what was heard while dormant.
+    fn start_session(&self, reason: SessionStartReason) -> SessionStartReason {
+        self.audio_feed.clear();
+        self.vad.lock().reset_session();
+        reason
+    }
+
+    // Waits for the scheduler's turn, if the GPU is shared with other transcribers.
+    fn acquire_inference(&self) -> Option<InferencePermit> {
+        self.scheduler
+            .as_ref()
+            .map(|scheduler| scheduler.acquire(InferencePriority::Realtime))
     }
 
     fn send_control_phrase(&self, control_phrase: WhisperControlPhrase) {
@@ -280,29 +832,17 @@ where
         };
 
         if let Err(e) = self
-            .output_sender
+            .control_sink()
             .try_send(WhisperOutput::ControlPhrase(control_phrase))
         {
-            #[cfg(feature = "ribble-logging")]
-            {
-                log::warn!(
-                    "Error sending control phrase: {control_phrase_type} \n\
-                    Error: {}
-                    Error source: {:#?}",
-                    &e,
-                    e.source()
-                );
-            }
-            #[cfg(not(feature = "ribble-logging"))]
-            {
-                eprintln!(
-                    "Error sending control phrase: {control_phrase_type} \n\
-                    Error: {}
-                    Error source: {:#?}",
-                    &e,
-                    e.source()
-                )
-            }
+            record_dropped_output("control_phrase");
+            log_warn_or_eprint!(
+                "Error sending control phrase: {control_phrase_type} \n\
+                Error: {}
+                Error source: {:#?}",
+                &e,
+                e.source()
+            );
         }
     }
 
@@ -328,22 +868,55 @@ where
         self.send_control_phrase(WhisperControlPhrase::GettingReady);
 
         // Set up whisper
-        let full_params = self.configs.as_whisper_full_params();
+        let mut scratch = InferenceScratch::new(self.configs.as_whisper_full_params());
+        if let Some(grammar) = self.grammar.as_deref() {
+            apply_grammar(&mut scratch.params, grammar, self.configs.grammar_penalty());
+        }
 
         let whisper_context_params = self.configs.as_whisper_context_params();
 
         // Since it's not possible to build a realtime transcriber, there must be an ID; it's fine to unwrap.
         let model_id = self.configs.model_id().unwrap();
 
-        let model_location = self.model_retriever.retrieve_model(model_id).ok_or(
-            RibbleWhisperError::ParameterError(format!("Failed to find model: {model_id}")),
-        )?;
+        let model_location = self
+            .model_retriever
+            .retrieve_model(model_id)
+            .ok_or(RibbleWhisperError::ModelNotFound { id: model_id })?;
 
         // Set up a whisper context
         let ctx = build_whisper_context(model_location, whisper_context_params)?;
 
         let mut whisper_state = ctx.create_state()?;
+        // For telling text tokens from special tokens in decode diagnostics.
+        let eot = ctx.token_eot();
+        // Extensions only apply to the session they were requested during.
+        self.timeout_extension.store(0, Ordering::Release);
+        self.start_requested.store(false, Ordering::Release);
         self.ready.store(true, Ordering::Release);
+        self.record(|recorder| recorder.start_session(&self.configs));
+        if let Some(classifier) = self.audio_event_classifier.as_deref() {
+            classifier.lock().reset();
+        }
+        for hook in &self.window_hooks {
+            hook.lock().reset();
+        }
+        if let Some(wake_phrase) = self.wake_phrase.as_deref() {
+            let started = self.await_wake_phrase(
+                wake_phrase,
+                &mut whisper_state,
+                &mut scratch.audio,
+                &run_transcription,
+            )?;
+            let Some(reason) = started else {
+                // Stopped while dormant: there's nothing to transcribe.
+                self.send_control_phrase(WhisperControlPhrase::EndTranscription);
+                self.ready.store(false, Ordering::Release);
+                self.record(|recorder| recorder.finish_session(""));
+                return Ok(String::new());
+            };
+            trace_event!(INFO, reason = ?reason, "Session started");
+            self.send_control_phrase(WhisperControlPhrase::SessionStarted(reason));
+        }
         self.send_control_phrase(WhisperControlPhrase::StartSpeaking);
 
         // Set up remaining loop data.
@@ -353,30 +926,57 @@ where
         // look into using total time/time offsets to make smarter, dynamic decisions about trimming the word buffer
         // to cut down on word-cutoffs.
         let mut t_last = Instant::now();
+        // For timestamping audio events.
+        let session_start = t_last;
+        let mut audio_events = AudioEventTracker::new();
         // For timing the transcription (and timeout)
         let mut total_time = 0u128;
         let timeout_limit_usize = self.configs.realtime_timeout();
 
-        let timeout_limit = if timeout_limit_usize == usize::MAX {
+        let mut timeout_limit = if timeout_limit_usize == usize::MAX {
             usize::MAX as u128
         } else {
             timeout_limit_usize.try_into().unwrap()
         };
-
-        // To collect audio from the ring buffer.
-        let mut audio_samples: Vec<f32> = vec![0f32; N_SAMPLES_30S];
+        let timeout_warning = self.configs.timeout_warning() as u128;
+        let mut timeout_warned = false;
 
         // For collecting the transcribed segments to return a full transcription at the end
-        // NOTE: since this implementation is read-heavy, Arc<str> is used over a preallocated string
-        // to reduce the cost of cloning.
-        let mut output_string: Arc<str> = Default::default();
+        let dedup = self.configs.dedup_configs();
+        // Overlaps are only resolved by their words' confidences under the confidence policy.
+        let word_confidences = dedup.conflict_policy() == ConflictPolicy::Confidence;
+        let mut confirmed = ConfirmedTranscript::new(dedup);
+        let mut dedup_diagnostics = DedupReporter(self.dedup_diagnostics.as_deref());
         let mut working_set: VecDeque<RibbleWhisperSegment> =
             VecDeque::with_capacity(WORKING_SET_SIZE);
+        let mut snapshot_encoder = SnapshotEncoder::new(self.configs.snapshot_encoding());
 
         // If voice is detected early but there's not enough data to run whisper, this flag should
         // be set to guarantee inference happens after a pause.
         let mut skip_vad_run_inference = false;
         let mut run_segment_merge = false;
+        // Where the window after a segment merge clear starts on the previous window's timeline,
+        // (in centiseconds), for aligning the two by timestamp.
+        let mut merge_offset = 0;
+        let commit_policy = self.configs.commit_policy();
+        let overflow_policy = self.configs.overflow_policy();
+        let working_set_limit = match overflow_policy {
+            OverflowPolicy::Grow { max_segments } => max_segments.max(WORKING_SET_SIZE),
+            OverflowPolicy::ConfirmOldest | OverflowPolicy::DropOldest => WORKING_SET_SIZE,
+        };
+        // Where the confirmed segments end, (in centiseconds from the start of the stream, see:
+        // AudioRingBuffer::get_samples_pushed). Windows re-transcribe the buffer until it's
+        // cleared, (or until it slides past them), so segments ending before this have already
+        // been confirmed.
+        let mut committed_until = 0;
+        // Where the window the working set was transcribed from starts, (its segments' timestamps
+        // are relative to it).
+        let mut working_set_start = 0;
+        let buffer_clear_policy = self.configs.buffer_clear_policy();
+        // Confirms segments before they slide out of a full window.
+        let slide_policy = CommitPolicy::Time {
+            after_ms: self.configs.audio_sample_len_ms() / 2,
+        };
 
         let mut previous_pause_clear_buffer = false;
 
@@ -386,16 +986,28 @@ where
         // It seems to be triggering before 1 second has passed.
         let mut vad_timeout_start_instant = None;
 
+        // For the idle policy: when voice was last detected, and whether the session is paused.
+        let idle_policy = self.configs.idle_policy();
+        let mut last_voice = Instant::now();
+        let mut idle_paused = false;
+
         let audio_buffer_capacity = self.audio_feed.get_capacity();
 
         // This is from the buffering strategy--higher buffer sample sizes
         let min_sample_len = self.configs.min_sample_len().min(audio_buffer_capacity);
+        // The length of a full inference window, in samples.
+        let window_len = ((self.configs.audio_sample_len_ms() as f64 / 1000f64
+            * WHISPER_SAMPLE_RATE) as usize)
+            .min(audio_buffer_capacity);
 
         while run_transcription.load(Ordering::Acquire) {
             let t_now = Instant::now();
             let diff = t_now - t_last;
             let millis = diff.as_millis();
-            total_time += millis;
+            // Time spent idle-paused doesn't count toward the timeout.
+            if !idle_paused {
+                total_time += millis;
+            }
 
             // To prevent accidental audio clearing, hold off to ensure at least
             // vad_sample_len() ms have passed before trying to detect voice.
@@ -403,7 +1015,8 @@ where
             // alerted to start speaking.
             if millis < self.configs.vad_sample_len() as u128 {
                 // Get half the time difference difference in ms.
-                let diff = (((self.configs.vad_sample_len() as u128 - millis) >> 1) as u64).max(PAUSE_DURATION);
+                let diff = (((self.configs.vad_sample_len() as u128 - millis) >> 1) as u64)
+                    .max(PAUSE_DURATION);
                 // Sleep for the halved time.
                 sleep(Duration::from_millis(diff));
                 continue;
@@ -414,7 +1027,7 @@ where
             // read_into will return min(requested_len, audio_len)
             // It will also escape early if the buffer is length 0
             self.audio_feed
-                .read_into(self.configs.vad_sample_len(), &mut audio_samples);
+                .read_into(self.configs.vad_sample_len(), &mut scratch.audio);
 
             let vad_size =
                 (self.configs.vad_sample_len() as f64 / 1000f64 * WHISPER_SAMPLE_RATE) as usize;
@@ -422,12 +1035,48 @@ where
             // If there's not enough samples yet to perform VAD, just skip the loop.
             // Sleeping may or may not be required/beneficial; this has not been tested
             // The spinlock might produce better results.
-            if audio_samples.len() < vad_size {
+            if scratch.audio.len() < vad_size {
                 continue;
             }
 
+            self.classify_audio_events(&mut audio_events, &scratch.audio, session_start.elapsed());
+
             let pause_detected = if !skip_vad_run_inference {
-                let voice_detected = self.vad.lock().voice_detected(&audio_samples);
+                let voice_detected = self.vad.lock().voice_detected(&scratch.audio);
+                trace_event!(
+                    TRACE,
+                    voice_detected,
+                    samples = scratch.audio.len(),
+                    "VAD decision"
+                );
+                self.record(|recorder| recorder.record_vad(voice_detected, &scratch.audio));
+                if voice_detected {
+                    last_voice = Instant::now();
+                    if idle_paused {
+                        idle_paused = false;
+                        self.send_control_phrase(WhisperControlPhrase::StartSpeaking);
+                    }
+                } else if idle_policy
+                    .idle_timeout()
+                    .is_some_and(|idle_timeout| last_voice.elapsed() >= idle_timeout)
+                {
+                    match idle_policy {
+                        IdlePolicy::Pause { .. } => {
+                            if !idle_paused {
+                                idle_paused = true;
+                                trace_event!(INFO, "Idle: pausing");
+                                self.send_control_phrase(WhisperControlPhrase::IdlePaused);
+                            }
+                            sleep(Duration::from_millis(IDLE_POLL_DURATION));
+                        }
+                        _ => {
+                            trace_event!(INFO, "Idle: stopping");
+                            self.send_control_phrase(WhisperControlPhrase::IdleTimeout);
+                            run_transcription.store(false, Ordering::Release);
+                        }
+                    }
+                    continue;
+                }
                 if !voice_detected {
                     let vad_t_now = Instant::now();
 
@@ -439,16 +1088,15 @@ where
                     }
 
                     let timeout_start_instant = vad_timeout_start_instant.unwrap();
-                    
+
                     let p_diff = vad_t_now.duration_since(timeout_start_instant).as_millis();
 
-                    if p_diff < VAD_TIMEOUT_MS
-                    {
+                    if p_diff < VAD_TIMEOUT_MS {
                         #[cfg(debug_assertions)]
                         self.send_control_phrase(WhisperControlPhrase::Debug(
                             "PAUSE TIMEOUT TICKING".to_string(),
                         ));
-                       
+
                         let diff = ((VAD_TIMEOUT_MS - p_diff) as u64 >> 1).max(PAUSE_DURATION);
 
                         // Sleep for a small amount of time to cut down on spinning
@@ -465,20 +1113,42 @@ where
                     // This means inference has been run at least 1 last time and the dedup has run
                     // I think I might be baking this incorrectly.
                     if previous_pause_clear_buffer {
-                        #[cfg(debug_assertions)]
-                        self.send_control_phrase(WhisperControlPhrase::Debug(
-                            "PAUSE TIMEOUT: CLEARING BUFFER".to_string(),
-                        ));
-
-                        self.audio_feed.clear();
+                        // Everything heard before the pause is confirmed below, (or already was),
+                        // so windows after it only measure new audio against this point.
+                        committed_until =
+                            samples_to_centiseconds(self.audio_feed.get_samples_pushed());
+                        if buffer_clear_policy.clear_on_pause() {
+                            #[cfg(debug_assertions)]
+                            self.send_control_phrase(WhisperControlPhrase::Debug(
+                                "PAUSE TIMEOUT: CLEARING BUFFER".to_string(),
+                            ));
+
+                            trace_event!(DEBUG, reason = "pause", "Clearing audio buffer");
+                            self.audio_feed.clear();
+                            // The next window starts fresh, rather than after a segment merge.
+                            merge_offset = 0;
+                        } else {
+                            // The audio stays buffered, so it's marked confirmed, (above), rather
+                            // than re-transcribed.
+                            if working_set.is_empty() {
+                                run_segment_merge = false;
+                                vad_timeout_start_instant = None;
+                                sleep(Duration::from_millis(PAUSE_DURATION));
+                                continue;
+                            }
+                        }
 
                         #[cfg(debug_assertions)]
                         self.send_control_phrase(WhisperControlPhrase::Debug(
                             "RUNNING OUTPUT DEDUP".to_string(),
                         ));
 
-                        output_string = confirm_transcription(output_string, &mut working_set);
-                        self.send_snapshot(Arc::clone(&output_string), &working_set);
+                        confirmed.confirm(
+                            &mut working_set,
+                            self.text_processor.as_deref(),
+                            &mut dedup_diagnostics,
+                        );
+                        self.send_snapshot(&mut snapshot_encoder, &mut confirmed, &working_set);
 
                         run_segment_merge = false;
                         // RESET the VAD timeout so it doesn't get stuck in a clearing loop.
@@ -499,17 +1169,24 @@ where
                 false
             };
 
-            if !pause_detected{
+            if !pause_detected {
                 vad_timeout_start_instant = None;
             }
 
             // Read the audio buffer in chunks of audio_sample_len
             self.audio_feed
-                .read_into(self.configs.audio_sample_len_ms(), &mut audio_samples);
+                .read_into(self.configs.audio_sample_len_ms(), &mut scratch.audio);
+            let read_head = self.audio_feed.get_head_position();
+            // Where the window starts in the stream.
+            let window_start = samples_to_centiseconds(
+                self.audio_feed
+                    .get_samples_pushed()
+                    .saturating_sub(scratch.audio.len()),
+            );
 
             // Depending on the buffering strategy, this will hold off on running the decode loop
             // excessively at the cost of some latency.
-            if audio_samples.len() < min_sample_len {
+            if scratch.audio.len() < min_sample_len {
                 #[cfg(debug_assertions)]
                 {
                     // Whisper requires at least 1000ms of audio to run correctly, so even
@@ -522,7 +1199,7 @@ where
                 // Skip over the next VAD
                 // This will also skip over the clearing.
                 skip_vad_run_inference = true;
-                let a_diff = min_sample_len - audio_samples.len();
+                let a_diff = min_sample_len - scratch.audio.len();
                 let f_a_diff = (a_diff as f64) * 1000f64 / WHISPER_SAMPLE_RATE;
                 // This -should- be halving the difference.
                 let diff = ((f_a_diff as u64) >> 1).min(PAUSE_DURATION);
@@ -542,11 +1219,25 @@ where
                 self.send_control_phrase(WhisperControlPhrase::Debug(inference_msg.to_string()));
             }
 
-            let mut params = full_params.clone();
-            params.set_no_context(!use_context);
-
-            let _ = whisper_state.full(params, &audio_samples)?;
-            let num_segments = whisper_state.full_n_segments();
+            let num_segments = {
+                let _span = trace_span!(
+                    DEBUG,
+                    "inference_window",
+                    samples = scratch.audio.len(),
+                    duration_ms = scratch.audio.len() as f64 / WHISPER_SAMPLE_RATE * 1000f64,
+                    use_context,
+                    segment_merge = run_segment_merge,
+                )
+                .entered();
+                self.apply_window_hooks(&mut scratch.audio);
+                self.record(|recorder| {
+                    recorder.record_window(&scratch.audio, use_context, run_segment_merge)
+                });
+                self.run_window(&mut whisper_state, &scratch, eot, use_context)?;
+                let num_segments = whisper_state.full_n_segments();
+                trace_event!(DEBUG, num_segments, "Inference finished");
+                num_segments
+            };
 
             if num_segments == 0 {
                 #[cfg(debug_assertions)]
@@ -557,14 +1248,26 @@ where
 
             skip_vad_run_inference = false;
 
-            // If there's a null pointer, just skip over the segment
-            // Expect that to happen extremely rarely-to-never.
-            let mut segments = whisper_state.as_iter().flat_map(|ws| ws.try_into());
+            let first_speaker = self.window_speaker(run_segment_merge, &working_set, &confirmed);
+            scratch.collect_segments(
+                &whisper_state,
+                first_speaker,
+                eot,
+                word_confidences,
+                self.configs.timestamped_snapshots(),
+            );
+            confirmed.language = output_language(self.configs.as_whisper_configs(), &whisper_state);
+            self.record(|recorder| recorder.record_segments(&scratch.segments));
+            self.send_timestamped_snapshot(|| {
+                scratch.timestamped_snapshot(window_start, committed_until)
+            });
+            let mut segments = scratch.segments.drain(..);
 
             if !run_segment_merge {
                 use_context = false;
                 let audio_len = self.audio_feed.get_audio_length();
-                run_segment_merge = audio_len >= audio_buffer_capacity;
+                let retain_ms = buffer_clear_policy.rollover_retain_ms();
+                run_segment_merge = retain_ms.is_some() && audio_len >= audio_buffer_capacity;
 
                 // If the "differ" should be run on the next pass, clear the audio, push the entire audio buffer to the working set,
                 // And expect the differ to run on the next pass.
@@ -580,13 +1283,58 @@ where
                     // Alternatively, word-level timestamps + offset is feasible to improve buffer
                     // trimming and reduce word boundary errors.
                     // TODO: look into word-level timestamp + offset approach to improve buffer trimming
-                    self.audio_feed.clear_from_back_retain_ms(RETAIN_MS);
+                    let retain_ms = retain_ms.unwrap_or(RETAIN_MS);
+                    trace_event!(
+                        DEBUG,
+                        reason = "segment_merge",
+                        retain_ms,
+                        "Clearing audio buffer"
+                    );
+                    // The retained audio ends with whatever arrived during inference.
+                    let pushed = (self.audio_feed.get_head_position() + audio_buffer_capacity
+                        - read_head)
+                        % audio_buffer_capacity;
+                    // The next window reads at most a window's worth of the retained audio.
+                    let retained = ((retain_ms as f64 / 1000f64 * WHISPER_SAMPLE_RATE) as usize)
+                        .min(audio_len)
+                        .min(window_len);
+                    merge_offset = samples_to_centiseconds(
+                        (scratch.audio.len() + pushed).saturating_sub(retained),
+                    );
+                    self.audio_feed.clear_from_back_retain_ms(retain_ms);
                     working_set.clear();
-                    working_set.extend(segments);
+                    working_set_start = window_start;
+                    working_set.extend(
+                        segments
+                            .filter(|segment| window_start + segment.end_time > committed_until),
+                    );
                     use_context = true;
                 } else {
                     working_set.clear();
-                    working_set.extend(segments);
+                    working_set_start = window_start;
+                    working_set.extend(
+                        segments
+                            .filter(|segment| window_start + segment.end_time > committed_until),
+                    );
+                    // Confirm the segments the commit policy considers settled, and when sliding,
+                    // those about to leave the window.
+                    let window_end = samples_to_centiseconds(scratch.audio.len());
+                    let mut n_ready =
+                        commit_policy.n_ready(working_set.make_contiguous(), window_end);
+                    if retain_ms.is_none() && scratch.audio.len() >= window_len {
+                        n_ready = n_ready
+                            .max(slide_policy.n_ready(working_set.make_contiguous(), window_end));
+                    }
+                    if n_ready > 0 {
+                        committed_until = window_start + working_set[n_ready - 1].end_time;
+                        scratch.confirm_from.clear();
+                        scratch.confirm_from.extend(working_set.drain(..n_ready));
+                        confirmed.confirm(
+                            &mut scratch.confirm_from,
+                            self.text_processor.as_deref(),
+                            &mut dedup_diagnostics,
+                        );
+                    }
                 }
             } else {
                 #[cfg(debug_assertions)]
@@ -598,15 +1346,26 @@ where
                 let first_new_segment = segments.next();
 
                 match (last_segment, first_new_segment) {
-                    (Some(last_seg), Some(new_seg)) => {
-                        blend_segments(last_seg, &new_seg);
-                    }
+                    (Some(last_seg), Some(new_seg)) => match dedup.merge_strategy() {
+                        MergeStrategy::TextSimilarity => dedup.blend_segments_with_diagnostics(
+                            last_seg,
+                            &new_seg,
+                            &mut dedup_diagnostics,
+                        ),
+                        MergeStrategy::Timestamps => dedup.blend_segments_aligned(
+                            last_seg,
+                            &new_seg,
+                            merge_offset,
+                            &mut dedup_diagnostics,
+                        ),
+                    },
 
                     // If the working set has just been cleared (pauses, etc.)
                     // Push the data to the working set and skip onto the next iteration.
                     // In the case where this is being run as a last-pass before deduplication.
                     // These segments shouldn't be trusted unless there is a genuine pause afterward.
                     (None, Some(new_seg)) => {
+                        working_set_start = window_start;
                         working_set.push_back(new_seg);
                         working_set.extend(segments);
                         run_segment_merge = false;
@@ -619,13 +1378,21 @@ where
                     (_, _) => {}
                 }
 
-                if !working_set.is_empty() {
+                if let Some(last_segment) = working_set.back() {
                     #[cfg(debug_assertions)]
                     self.send_control_phrase(WhisperControlPhrase::Debug(
                         "RUNNING DEDUP AFTER BLEND".to_string(),
                     ));
 
-                    output_string = confirm_transcription(output_string, &mut working_set);
+                    // The retained audio overlaps the blended segments, so later windows shouldn't
+                    // bring them back.
+                    committed_until =
+                        committed_until.max(working_set_start + last_segment.end_time);
+                    confirmed.confirm(
+                        &mut working_set,
+                        self.text_processor.as_deref(),
+                        &mut dedup_diagnostics,
+                    );
                 }
 
                 run_segment_merge = false;
@@ -635,31 +1402,50 @@ where
                 use_context = false;
             }
 
-            // Drain the working set when it exceeds its bounded size. It is most likely that the
-            // n segments drained are actually part of the transcription.
+            // Drain the working set when it exceeds its bounded size, (see: OverflowPolicy).
+            // It is most likely that the n segments drained are actually part of the
+            // transcription.
             // It is highly, highly unlikely for this condition to ever trigger, given that
             // the VAD implementations are generally pretty good at detecting pauses.
             // It is most likely that the working set will get drained beforehand, but this is a
-            // fallback to ensure the working_set bounded to WORKING_SET_SIZE
-            if working_set.len() > WORKING_SET_SIZE {
-                #[cfg(debug_assertions)]
-                self.send_control_phrase(WhisperControlPhrase::Debug(
-                    "DRAINING WORKING SET".to_string(),
-                ));
-                let up_to = working_set.len().saturating_sub(WORKING_SET_SIZE);
-                let mut confirm_from = working_set.drain(..up_to).collect();
-
-                output_string = confirm_transcription(output_string, &mut confirm_from);
+            // fallback to keep the working set bounded.
+            if working_set.len() > working_set_limit {
+                let up_to = working_set.len().saturating_sub(working_set_limit);
+                let confirm = overflow_policy != OverflowPolicy::DropOldest;
+                // Either way, the segments shouldn't come back with the next window.
+                committed_until =
+                    committed_until.max(window_start + working_set[up_to - 1].end_time);
+                trace_event!(
+                    WARN,
+                    n_segments = up_to,
+                    confirmed = confirm,
+                    "Working set overflowed"
+                );
+                if confirm {
+                    scratch.confirm_from.clear();
+                    scratch.confirm_from.extend(working_set.drain(..up_to));
+                    confirmed.confirm(
+                        &mut scratch.confirm_from,
+                        self.text_processor.as_deref(),
+                        &mut dedup_diagnostics,
+                    );
+                } else {
+                    working_set.drain(..up_to);
+                }
+                self.send_control_phrase(WhisperControlPhrase::WorkingSetOverflow {
+                    n_segments: up_to,
+                    confirmed: confirm,
+                });
             }
 
             // Send the current transcription as it exists, so that the UI can update.
             // Since the working set is updated after every run of the inference/differ/buffer
             // clear, and there are earlier skips to avoid running inference, it can generally be
             // assumed that each inference = needs snapshot.
-            let push_snapshot = !(output_string.trim().is_empty() && working_set.is_empty());
+            let push_snapshot = !(confirmed.is_empty() && working_set.is_empty());
 
             if push_snapshot {
-                self.send_snapshot(Arc::clone(&output_string), &working_set);
+                self.send_snapshot(&mut snapshot_encoder, &mut confirmed, &working_set);
             }
 
             // If the timeout is set to 0, this loop runs infinitely.
@@ -667,6 +1453,21 @@ where
                 continue;
             }
 
+            let extension = self.timeout_extension.swap(0, Ordering::AcqRel);
+            if extension > 0 {
+                timeout_limit = timeout_limit.saturating_add(extension as u128);
+                timeout_warned = false;
+            }
+
+            // Warn ahead of the timeout, so that the user can be prompted to extend it.
+            let remaining = timeout_limit.saturating_sub(total_time);
+            if !timeout_warned && remaining > 0 && remaining <= timeout_warning {
+                timeout_warned = true;
+                self.send_control_phrase(WhisperControlPhrase::TimeoutApproaching {
+                    remaining: Duration::from_millis(remaining as u64),
+                });
+            }
+
             // Otherwise check for timeout.
             if total_time > timeout_limit {
                 self.send_control_phrase(WhisperControlPhrase::TranscriptionTimeout);
@@ -677,21 +1478,56 @@ where
 
         if slow_stop.load(Ordering::Acquire) {
             self.send_control_phrase(WhisperControlPhrase::SlowStop);
-            // This can just consume full params
-            let mut final_full_params = full_params;
-            final_full_params.set_no_context(!use_context);
 
             // Read the audio buffer in chunks of audio_sample_len
             self.audio_feed
-                .read_into(self.configs.audio_sample_len_ms(), &mut audio_samples);
-
-            let enough_audio = audio_samples.len() >= MIN_SIZE_FOR_WHISPER;
-            if enough_audio
-                && whisper_state
-                    .full(final_full_params, &audio_samples)
-                    .is_ok()
-            {
-                let mut segments = whisper_state.as_iter().flat_map(|ws| ws.try_into());
+                .read_into(self.configs.audio_sample_len_ms(), &mut scratch.audio);
+            let window_start = samples_to_centiseconds(
+                self.audio_feed
+                    .get_samples_pushed()
+                    .saturating_sub(scratch.audio.len()),
+            );
+
+            let enough_audio = scratch.audio.len() >= MIN_SIZE_FOR_WHISPER;
+            let _final_span = trace_span!(
+                DEBUG,
+                "final_inference_window",
+                samples = scratch.audio.len(),
+                enough_audio,
+            )
+            .entered();
+            if enough_audio {
+                self.apply_window_hooks(&mut scratch.audio);
+                self.record(|recorder| {
+                    recorder.record_window(&scratch.audio, use_context, run_segment_merge)
+                });
+            }
+            let transcribed = enough_audio
+                && self
+                    .run_window(&mut whisper_state, &scratch, eot, use_context)
+                    .is_ok();
+            if transcribed {
+                let first_speaker =
+                    self.window_speaker(run_segment_merge, &working_set, &confirmed);
+                scratch.collect_segments(
+                    &whisper_state,
+                    first_speaker,
+                    eot,
+                    word_confidences,
+                    self.configs.timestamped_snapshots(),
+                );
+                confirmed.language =
+                    output_language(self.configs.as_whisper_configs(), &whisper_state);
+                self.record(|recorder| recorder.record_segments(&scratch.segments));
+                self.send_timestamped_snapshot(|| {
+                    scratch.timestamped_snapshot(window_start, committed_until)
+                });
+                // The final window re-transcribes the buffered audio, which can include segments
+                // already confirmed by the commit policy.
+                let mut segments = scratch
+                    .segments
+                    .drain(..)
+                    .filter(|segment| window_start + segment.end_time > committed_until);
                 if run_segment_merge {
                     let last_segment = working_set.iter_mut().last();
                     let first_new_segment: Option<RibbleWhisperSegment> = segments.next();
@@ -702,13 +1538,25 @@ where
                             // This does skip to the string deduplication and modifies the segments in place.
                             // It does use the right-priority strategy, but if there are any artifacts,
                             // they are expected to be caught in the final deduplication.
-                            let (l_str, r_str) =
-                                match deduplicate_strings(l_seg.text(), r_seg.text()) {
-                                    None => (Arc::clone(&l_seg.text), Arc::clone(&r_seg.text)),
-                                    Some((new_l_str, new_r_str)) => {
-                                        (Arc::from(new_l_str.trim()), Arc::from(new_r_str.trim()))
-                                    }
-                                };
+                            let deduped = match dedup.merge_strategy() {
+                                MergeStrategy::TextSimilarity => dedup.deduplicate_segments(
+                                    l_seg,
+                                    &r_seg,
+                                    &mut dedup_diagnostics,
+                                ),
+                                MergeStrategy::Timestamps => dedup.deduplicate_segments_aligned(
+                                    l_seg,
+                                    &r_seg,
+                                    merge_offset,
+                                    &mut dedup_diagnostics,
+                                ),
+                            };
+                            let (l_str, r_str) = match deduped {
+                                None => (Arc::clone(&l_seg.text), Arc::clone(&r_seg.text)),
+                                Some((new_l_str, new_r_str)) => {
+                                    (Arc::from(new_l_str.trim()), Arc::from(new_r_str.trim()))
+                                }
+                            };
 
                             l_seg.replace_text(l_str);
                             r_seg.replace_text(r_str);
@@ -734,6 +1582,7 @@ where
                 }
             }
         }
+        self.send_audio_events(audio_events.finish());
         self.send_control_phrase(WhisperControlPhrase::EndTranscription);
 
         // Clean up the whisper context
@@ -748,12 +1597,39 @@ where
             "RUNNING FINAL OUTPUT DEDUP".to_string(),
         ));
 
-        output_string = confirm_transcription(output_string, &mut working_set);
+        confirmed.confirm(
+            &mut working_set,
+            self.text_processor.as_deref(),
+            &mut dedup_diagnostics,
+        );
         // Set internal state to non-ready in case the transcriber is going to be reused
         self.ready.store(false, Ordering::Release);
 
-        // Strip remaining whitespace and return
-        Ok(output_string.trim().to_string())
+        // The confirmed text is already trimmed.
+        let transcription = confirmed.text().to_string();
+        self.record(|recorder| recorder.finish_session(&transcription));
+        Ok(transcription)
+    }
+}
+
+impl<V, M, S> RealtimeTranscriber<V, M, S>
+where
+    V: VAD<f32> + Send + Sync + 'static,
+    M: ModelRetriever + Send + Sync + 'static,
+    S: OutputSink<WhisperOutput> + Send + 'static,
+{
+    /// Runs [Self::run_stream] on a new thread, scheduled according to the configured
+    /// [ThreadConfigs](crate::whisper::configs::ThreadConfigs), (e.g. a lower priority so that a
+    /// UI on the same cores doesn't stutter). Returns a handle to join for the final transcription.
+    pub fn spawn(
+        self,
+        run_transcription: Arc<AtomicBool>,
+        slow_stop: Arc<AtomicBool>,
+    ) -> Result<JoinHandle<Result<String, RibbleWhisperError>>, RibbleWhisperError> {
+        let thread_configs = self.configs.thread_configs();
+        spawn_with_configs("ribble-whisper-realtime", thread_configs, move || {
+            self.run_stream(run_transcription, slow_stop)
+        })
     }
 }
 
@@ -772,204 +1648,256 @@ where
 #[derive(Clone)]
 pub struct RealtimeTranscriberHandle {
     ready: Arc<AtomicBool>,
+    timeout_extension: Arc<AtomicU64>,
+    start_requested: Arc<AtomicBool>,
 }
 
 impl RealtimeTranscriberHandle {
     pub fn ready(&self) -> bool {
         self.ready.load(Ordering::Acquire)
     }
-}
 
-fn find_closest_match(buf1: &[&str], buf2: &[&str]) -> Option<(usize, usize)> {
-    let mut l_match = None;
-    let mut r_match = None;
-    for (idx, l_token) in buf1.iter().enumerate() {
-        let mut max_score = 0.0;
-        for (jdx, r_token) in buf2.iter().enumerate() {
-            let similar = jaro_winkler(l_token, r_token);
-            // Take greater-equal the greatest score in-case there's a lot of repeating going on in the actual speech.
-            if similar >= DIFF_THRESHOLD_HIGH && similar >= max_score {
-                l_match = Some(idx);
-                r_match = Some(jdx);
-                max_score = similar;
-            }
+    /// Extends the running session's timeout, (e.g. when the user responds to a
+    /// [WhisperControlPhrase::TimeoutApproaching] prompt). The warning is sent again if the
+    /// extended timeout is approached. This has no effect if the timeout is disabled.
+    pub fn extend_timeout(&self, extension: Duration) {
+        let extension = u64::try_from(extension.as_millis()).unwrap_or(u64::MAX);
+        let _ =
+            self.timeout_extension
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |pending| {
+                    Some(pending.saturating_add(extension))
+                });
+    }
+    /// Starts a session that is waiting for its wake phrase, (e.g. from a push-to-talk button),
+    /// as though the phrase was heard. This has no effect if the transcriber has no wake phrase,
+    /// or its session has already started.
+    pub fn start_session(&self) {
+        if self.ready() {
+            self.start_requested.store(true, Ordering::Release);
         }
     }
-    Some((l_match?, r_match?))
 }
 
-// This could be done with slices and just return the offsets, but it's easier to just write this
-// imperatively.
-fn run_stride(
-    buf1: &[&str],
-    buf1_start: usize,
-    buf2: &[&str],
-    buf2_start: usize,
-) -> (usize, usize) {
-    let mut l_start = buf1_start;
-    let mut r_start = buf2_start;
-
-    loop {
-        let l_token = buf1.get(l_start);
-        let r_token = buf2.get(r_start);
-        if l_token.is_none() || r_token.is_none() {
-            break;
-        }
-        let similar = jaro_winkler(l_token.unwrap(), r_token.unwrap());
-        // PERHAPS this should be ~0.85-0.9, 0.8 is a little low I think.
-        // TODO: possibly swap to high.
-        if similar < DIFF_THRESHOLD_MED {
-            break;
-        }
-        l_start += 1;
-        r_start += 1;
-    }
-    (l_start, r_start)
+fn samples_to_centiseconds(n_samples: usize) -> i64 {
+    (n_samples as f64 * 100f64 / WHISPER_SAMPLE_RATE) as i64
 }
 
-// This runs right-side priority--since this is to catch words that are potentially duplicated, they're
-// most likely going to have better punctuation. Sometimes whisper will insert punctuation on the
-// left hand side when it doesn't have enough audio--this helps to mitigate that.
-fn deduplicate_strings(str1: &str, str2: &str) -> Option<(String, String)> {
-    let (mut l_buf, mut r_buf) = split_text(str1, str2);
-    let l_start = if l_buf.len() == N_TOKENS + 1 { 1 } else { 0 };
-    let r_end = N_TOKENS.min(r_buf.len());
-    find_closest_match(&l_buf[l_start..], &r_buf[..r_end]).and_then(|(l_match, r_match)| {
-        // If there are more than 5 tokens, the l_buf is compared from 1 instead of 0;
-        // The index needs to be decremented by one.
-        let l_match_start = if l_start == 1 {
-            l_match.saturating_add(1).min(l_buf.len() - 1)
-        } else {
-            l_match
-        };
-
-        let (l_match_end, r_match_end) = run_stride(&l_buf, l_match_start, &r_buf, r_match);
+// Forwards deduplication events to the user's diagnostics callback, if one was set.
+struct DedupReporter<'a>(Option<&'a Mutex<dyn DedupDiagnosticsCallback>>);
 
-        let num_words = l_match_end.saturating_sub(l_match_start);
+impl Callback for DedupReporter<'_> {
+    type Argument = DedupEvent;
+    fn call(&mut self, event: DedupEvent) {
+        if let Some(diagnostics) = self.0 {
+            diagnostics.lock().call(event);
+        }
+    }
+}
 
-        if num_words < 2 {
-            let l_midpoint = (l_start + (l_buf.len() - 1)) / 2;
-            let r_midpoint = (r_end - 1) / 2;
+// The confirmed transcription. Deduplication only compares against the last few words, so only
+// the tail is rewritten as segments are confirmed; once the tail grows past CONFIRMED_CHUNK_LEN,
+// all but its last words are frozen into a chunk. This keeps each confirmation from copying the
+// entire transcript, which adds up over multi-hour sessions.
+struct ConfirmedTranscript {
+    dedup: DedupConfigs,
+    frozen: Vec<Arc<str>>,
+    tail: String,
+    // The text sent with snapshots, until the next confirmation.
+    text: Option<ConfirmedText>,
+    // The text sent with the last snapshot, for corrections.
+    sent: ConfirmedText,
+    // Changes of speaker, (when diarizing), by byte offset into the full text.
+    turns: Vec<SpeakerTurn>,
+    // The turns sent with snapshots, until the next confirmation.
+    shared_turns: Option<Arc<[SpeakerTurn]>>,
+    // The language of the most recent inference, for the text processor.
+    language: Option<Language>,
+}
 
-            // If the match isn't close to where the segments would naturally fall,
-            // they are likely to be different strings that just happen to have repeated words.
-            // Err on the side of caution and accept a small amount of error to avoid accidentally
-            // clobbering the transcription on a false-positive.
-            if l_match_end < l_midpoint && r_match_end > r_midpoint {
-                return None;
-            }
+impl ConfirmedTranscript {
+    fn new(dedup: DedupConfigs) -> Self {
+        Self {
+            dedup,
+            frozen: vec![],
+            tail: String::new(),
+            text: None,
+            sent: ConfirmedText::default(),
+            turns: vec![],
+            shared_turns: None,
+            language: None,
         }
+    }
 
-        // Confirm up to just before the end of the match on the left.
-        l_buf.truncate(l_match_end);
-        // Drop up to just before the end of the match on the right.
-        let up_to = r_match_end.min(r_buf.len());
-        drop(r_buf.drain(..up_to));
-
-        Some((l_buf.join(" "), r_buf.join(" ")))
-    })
-}
+    fn last_speaker(&self) -> Option<SpeakerId> {
+        self.turns.last().map(|turn| turn.speaker)
+    }
 
-fn split_text<'a>(str1: &'a str, str2: &'a str) -> (Vec<&'a str>, Vec<&'a str>) {
-    let mut l_buf = str1.rsplitn(N_TOKENS + 1, " ").collect::<Vec<_>>();
-    l_buf.reverse();
-    let r_buf = str2.splitn(N_TOKENS + 1, " ").collect::<Vec<_>>();
-    (l_buf, r_buf)
-}
+    // None unless something has been attributed to a speaker.
+    fn speakers(
+        &mut self,
+        working_set: &VecDeque<RibbleWhisperSegment>,
+    ) -> Option<Arc<SnapshotSpeakers>> {
+        if self.turns.is_empty() && working_set.iter().all(|segment| segment.speaker.is_none()) {
+            return None;
+        }
+        let turns = self
+            .shared_turns
+            .get_or_insert_with(|| Arc::from(self.turns.as_slice()));
+        Some(Arc::new(SnapshotSpeakers::new(
+            Arc::clone(turns),
+            working_set
+                .iter()
+                .map(RibbleWhisperSegment::speaker)
+                .collect(),
+        )))
+    }
 
-// NOTE: this is doing left priority in-case words end up cut off.
-fn blend_segments(l_segment: &mut RibbleWhisperSegment, r_segment: &RibbleWhisperSegment) {
-    let (mut l_buf, mut r_buf) = split_text(l_segment.text.as_ref(), r_segment.text.as_ref());
-    let l_start = if l_buf.len() == N_TOKENS + 1 { 1 } else { 0 };
-    let r_end = N_TOKENS.min(r_buf.len());
-    let last_is_word = r_buf.len() <= N_TOKENS;
-
-    if let Some((l_match, r_match)) = find_closest_match(&l_buf[l_start..], &r_buf[..r_end]) {
-        // If there are more than 5 tokens, the l_buf is compared from 1 instead of 0;
-        // The index needs to be decremented by one.
-        let l_match_start = if l_start == 1 {
-            l_match.saturating_add(1).min(l_buf.len() - 1)
-        } else {
-            l_match
+    // Adds a turn where a segment's text starts, if its speaker differs from the last.
+    fn push_turn(&mut self, offset: usize, text: &str, speaker: Option<SpeakerId>) {
+        let Some(speaker) = speaker else {
+            return;
         };
+        if text.trim().is_empty() || self.last_speaker() == Some(speaker) {
+            return;
+        }
+        self.turns.push(SpeakerTurn {
+            offset: offset + text.len() - text.trim_start().len(),
+            speaker,
+        });
+    }
 
-        let (l_match_end, _r_match_end) = run_stride(&l_buf, l_match_start, &r_buf, r_match);
+    fn is_empty(&self) -> bool {
+        self.frozen.is_empty() && self.tail.trim().is_empty()
+    }
 
-        let num_words = l_match_end.saturating_sub(l_match_start);
+    fn text(&mut self) -> ConfirmedText {
+        self.text
+            .get_or_insert_with(|| {
+                let tail = (!self.tail.is_empty()).then(|| Arc::from(self.tail.as_str()));
+                ConfirmedText::from_chunks(self.frozen.iter().cloned().chain(tail).collect())
+            })
+            .clone()
+    }
 
-        if num_words < 2 {
-            let l_midpoint = (l_start + (l_buf.len() - 1)) / 2;
-            let r_midpoint = (r_end - 1) / 2;
+    // The revision of the confirmed text since it was last sent, if there was one and it reaches
+    // back no further than the horizon, (in bytes). A horizon of 0 disables corrections.
+    fn correction(&mut self, horizon: usize) -> Option<Correction> {
+        if horizon == 0 {
+            return None;
+        }
+        let text = self.text();
+        let previous = std::mem::replace(&mut self.sent, text.clone());
+        let correction = text.correction_from(&previous)?;
+        if correction.reach(previous.len()) > horizon {
+            trace_event!(
+                DEBUG,
+                reach = correction.reach(previous.len()),
+                horizon,
+                "Revision beyond the correction horizon"
+            );
+            return None;
+        }
+        Some(correction)
+    }
 
-            // If the match isn't close to where the segments would naturally fall,
-            // they are likely to be different strings that just happen to have repeated words.
-            // Err on the side of caution and accept a small amount of error to avoid accidentally
-            // clobbering the transcription on a false-positive.
-            if l_match_end < l_midpoint && r_end > r_midpoint {
-                return;
+    fn confirm(
+        &mut self,
+        working_set: &mut VecDeque<RibbleWhisperSegment>,
+        text_processor: Option<&dyn TextProcessor>,
+        diagnostics: &mut impl Callback<Argument = DedupEvent>,
+    ) {
+        record_segments_confirmed(working_set.len());
+        // Both sides of the deduplication are processed text, so the two stay consistent.
+        if let Some(processor) = text_processor {
+            for segment in working_set.iter_mut() {
+                let processed =
+                    processor.process_for_language(segment.text().to_string(), self.language);
+                segment.replace_text(Arc::from(processed));
             }
         }
-        // Confirm up to the end of the match on the left.
-        l_buf.truncate(l_match_end + 1);
 
-        // Drop up to the end of the match on the right.
-        let up_to = (r_end + 1).min(r_buf.len());
-        drop(r_buf.drain(..up_to));
+        // Turns are tracked by offset into the full text, which is the frozen chunks followed by
+        // the tail; rewriting the tail leaves the frozen chunks untouched.
+        let starts_transcript = self.is_empty();
+        let new_turns = self.turns.len();
+        let mut frozen_len = 0;
+        let mut separate = !starts_transcript;
+        let mut tail = if starts_transcript {
+            String::new()
+        } else {
+            let Some(segment) = working_set.pop_front() else {
+                return;
+            };
+            self.ensure_tail_words();
+            frozen_len = self.frozen.iter().map(|chunk| chunk.len()).sum();
+            let (mut deduped, rest) = match self.dedup.deduplicate_strings_with_diagnostics(
+                &self.tail,
+                segment.text(),
+                diagnostics,
+            ) {
+                None => (self.tail.clone(), segment.text().to_string()),
+                Some(deduped) => deduped,
+            };
+            // Deduplication may have removed the end of the tail, along with any turns in it.
+            self.turns
+                .retain(|turn| turn.offset < frozen_len + deduped.len());
+            deduped.push(' ');
+            self.push_turn(frozen_len + deduped.len(), &rest, segment.speaker);
+            deduped.push_str(&rest);
+            deduped
+        };
+
+        for segment in working_set.drain(..) {
+            if separate {
+                tail.push(' ');
+            }
+            separate = true;
+            self.push_turn(frozen_len + tail.len(), segment.text(), segment.speaker);
+            tail.push_str(segment.text());
+        }
 
-        // If the buffer is still full, it either has the rest of the segment, or it has a word
-        // If it has a word, it's either the boundary word, or a duplicate that will get deduplicated.
-        // If it has the rest of the segment,
-        if !r_buf.is_empty() && last_is_word {
-            // Since the last element is just the rest of the string
-            // (and it's going to be dropped anyway), just swap-remove.
-            l_buf.push(r_buf.swap_remove(0));
+        // The start of the transcript is trimmed, (see: set_tail).
+        if starts_transcript {
+            let trimmed = tail.len() - tail.trim_start().len();
+            for turn in self.turns[new_turns..].iter_mut() {
+                turn.offset = turn.offset.saturating_sub(trimmed);
+            }
         }
+        self.set_tail(tail);
+    }
 
-        l_segment.replace_text(Arc::from(l_buf.join(" ").trim()))
+    // Deduplication compares against the last n_tokens words, so they all need to be in the tail.
+    fn ensure_tail_words(&mut self) {
+        while self.tail.matches(' ').count() < self.dedup.n_tokens()
+            && let Some(chunk) = self.frozen.pop()
+        {
+            self.tail.insert_str(0, &chunk);
+        }
     }
-}
 
-fn confirm_transcription(
-    output_string: Arc<str>,
-    working_set: &mut VecDeque<RibbleWhisperSegment>,
-) -> Arc<str> {
-    if output_string.trim().is_empty() {
-        Arc::from(
-            working_set
-                .drain(..)
-                .map(|seg| seg.into_text())
-                .collect::<Vec<_>>()
-                .join(" ")
-                .trim(),
-        )
-    } else {
-        match working_set.pop_front() {
-            None => output_string,
-            Some(segment) => match deduplicate_strings(output_string.as_ref(), segment.text()) {
-                None => {
-                    let mut deduped = format!("{output_string} {}", segment.text());
-                    let remaining = working_set
-                        .drain(..)
-                        .map(|seg| seg.into_text())
-                        .collect::<Vec<_>>()
-                        .join(" ");
-                    deduped.push(' ');
-                    deduped.push_str(&remaining);
-                    Arc::from(deduped.trim())
-                }
-                Some((mut deduped, rest)) => {
-                    deduped.push(' ');
-                    deduped.push_str(&rest);
-                    let remaining = working_set
-                        .drain(..)
-                        .map(|seg| seg.into_text())
-                        .collect::<Vec<_>>()
-                        .join(" ");
-                    deduped.push(' ');
-                    deduped.push_str(&remaining);
-                    Arc::from(deduped.trim())
-                }
-            },
+    fn set_tail(&mut self, tail: String) {
+        // The transcript is trimmed at both ends; its start is in the tail until the first chunk
+        // is frozen.
+        self.tail = if self.frozen.is_empty() {
+            tail.trim()
+        } else {
+            tail.trim_end()
+        }
+        .to_string();
+        self.text = None;
+        self.shared_turns = None;
+
+        // Frozen chunks end just before a space, which stays with the tail. Twice the compared
+        // words are kept in the tail, so that deduplication rarely has to reach back into a
+        // frozen chunk.
+        if self.tail.len() > CONFIRMED_CHUNK_LEN
+            && let Some((split, _)) = self
+                .tail
+                .rmatch_indices(' ')
+                .nth((self.dedup.n_tokens() * 2).saturating_sub(1))
+        {
+            self.frozen.push(Arc::from(&self.tail[..split]));
+            self.tail.replace_range(..split, "");
         }
     }
 }
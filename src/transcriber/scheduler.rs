@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use parking_lot::{Condvar, Mutex, MutexGuard};
+use strum::{Display, EnumIter, IntoStaticStr};
+
+/// The priority of a whisper inference call waiting on an [InferenceScheduler].
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Display, EnumIter, IntoStaticStr)]
+pub enum InferencePriority {
+    /// Realtime inference windows, which always run before waiting background calls.
+    Realtime,
+    /// Offline (batch) transcription.
+    Background,
+}
+
+impl InferencePriority {
+    fn index(self) -> usize {
+        match self {
+            InferencePriority::Realtime => 0,
+            InferencePriority::Background => 1,
+        }
+    }
+}
+
+/// Serializes whisper inference across transcribers that share one GPU, so that a batch job
+/// cannot starve a realtime stream by saturating the device.
+///
+/// Only one call runs at a time. Waiting realtime calls run before waiting background calls, and
+/// calls of the same priority run in the order they arrived. A running call cannot be pre-empted,
+/// but offline transcribers yield between their encoder windows, (roughly every 30 seconds of
+/// audio), to any realtime call that is waiting and to other background jobs in turn.
+///
+/// The scheduler is cheap to clone; clones share the same queue. Pass a clone to each transcriber
+/// that should be scheduled with
+/// [RealtimeTranscriberBuilder::with_inference_scheduler](crate::transcriber::realtime_transcriber::RealtimeTranscriberBuilder::with_inference_scheduler)
+/// and
+/// [OfflineTranscriberBuilder::with_inference_scheduler](crate::transcriber::offline_transcriber::OfflineTranscriberBuilder::with_inference_scheduler).
+#[derive(Clone, Default)]
+pub struct InferenceScheduler {
+    inner: Arc<SchedulerInner>,
+}
+
+#[derive(Default)]
+struct SchedulerInner {
+    queue: Mutex<Queue>,
+    turn: Condvar,
+}
+
+// Each priority hands out tickets in arrival order and serves them in that order.
+#[derive(Default)]
+struct Queue {
+    running: bool,
+    next_ticket: [u64; 2],
+    serving: [u64; 2],
+}
+
+impl Queue {
+    fn waiting(&self, priority: InferencePriority) -> usize {
+        let i = priority.index();
+        (self.next_ticket[i] - self.serving[i]) as usize
+    }
+
+    fn take_ticket(&mut self, priority: InferencePriority) -> u64 {
+        let ticket = self.next_ticket[priority.index()];
+        self.next_ticket[priority.index()] += 1;
+        ticket
+    }
+
+    fn is_turn(&self, priority: InferencePriority, ticket: u64) -> bool {
+        !self.running
+            && self.serving[priority.index()] == ticket
+            && (priority == InferencePriority::Realtime
+                || self.waiting(InferencePriority::Realtime) == 0)
+    }
+
+    fn start(&mut self, priority: InferencePriority) {
+        self.running = true;
+        self.serving[priority.index()] += 1;
+    }
+}
+
+impl InferenceScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Blocks until it is priority's turn to run inference.
+    /// The turn is held until the returned permit is dropped.
+    pub fn acquire(&self, priority: InferencePriority) -> InferencePermit {
+        let mut queue = self.inner.queue.lock();
+        self.wait_for_turn(&mut queue, priority);
+        InferencePermit {
+            scheduler: self.clone(),
+            priority,
+        }
+    }
+
+    /// Returns a permit only if inference is idle and nothing is waiting ahead of priority.
+    pub fn try_acquire(&self, priority: InferencePriority) -> Option<InferencePermit> {
+        let mut queue = self.inner.queue.lock();
+        let ticket = queue.next_ticket[priority.index()];
+        if !queue.is_turn(priority, ticket) {
+            return None;
+        }
+        queue.take_ticket(priority);
+        queue.start(priority);
+        Some(InferencePermit {
+            scheduler: self.clone(),
+            priority,
+        })
+    }
+
+    /// The number of calls of the given priority waiting for their turn.
+    pub fn waiting(&self, priority: InferencePriority) -> usize {
+        self.inner.queue.lock().waiting(priority)
+    }
+
+    /// Returns true while a call holds the turn.
+    pub fn is_running(&self) -> bool {
+        self.inner.queue.lock().running
+    }
+
+    fn wait_for_turn(&self, queue: &mut MutexGuard<Queue>, priority: InferencePriority) {
+        let ticket = queue.take_ticket(priority);
+        while !queue.is_turn(priority, ticket) {
+            self.inner.turn.wait(queue);
+        }
+        queue.start(priority);
+    }
+
+    fn release(&self, queue: &mut Queue) {
+        queue.running = false;
+        // Waiters check different conditions, so all of them need to re-check.
+        self.inner.turn.notify_all();
+    }
+}
+
+/// A turn to run inference on an [InferenceScheduler], released when dropped.
+pub struct InferencePermit {
+    scheduler: InferenceScheduler,
+    priority: InferencePriority,
+}
+
+impl InferencePermit {
+    pub fn priority(&self) -> InferencePriority {
+        self.priority
+    }
+
+    /// Hands the turn over if a call that would be scheduled next is waiting: a realtime call,
+    /// or a call of the same priority. Blocks until this permit's turn comes around again.
+    /// Returns true if the turn was handed over.
+    pub fn yield_turn(&mut self) -> bool {
+        let mut queue = self.scheduler.inner.queue.lock();
+        let waiting = queue.waiting(InferencePriority::Realtime) + queue.waiting(self.priority);
+        if waiting == 0 {
+            return false;
+        }
+        self.scheduler.release(&mut queue);
+        self.scheduler.wait_for_turn(&mut queue, self.priority);
+        true
+    }
+}
+
+impl Drop for InferencePermit {
+    fn drop(&mut self) {
+        let mut queue = self.scheduler.inner.queue.lock();
+        self.scheduler.release(&mut queue);
+    }
+}
@@ -0,0 +1,212 @@
+//! Speaker labels for diarized transcription.
+//!
+//! When diarization is enabled, (see: [crate::whisper::configs::WhisperConfigs::with_tinydiarize]),
+//! segments carry the [SpeakerId] whisper attributes them to, and realtime
+//! [TranscriptionSnapshot](crate::transcriber::TranscriptionSnapshot)s carry
+//! [SnapshotSpeakers] so that live captions can be rendered per speaker.
+//!
+//! tinydiarize only detects speaker turns, not who is speaking, so each turn starts a new
+//! [SpeakerId]. A [SpeakerRegistry] lets users name speakers and merge ids that belong to the same
+//! person as the conversation goes on.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Identifies a speaker within a transcription session. Ids are assigned in order of appearance,
+/// starting at 0.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SpeakerId(pub u32);
+
+impl SpeakerId {
+    /// The id assigned to the speaker after this one.
+    pub fn next(self) -> Self {
+        Self(self.0.saturating_add(1))
+    }
+}
+
+impl std::fmt::Display for SpeakerId {
+    /// Speakers are displayed counting from 1, (e.g. "Speaker 1").
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Speaker {}", self.0 as u64 + 1)
+    }
+}
+
+/// A change of speaker within confirmed text: the speaker of the text from offset, (in bytes),
+/// until the next turn.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SpeakerTurn {
+    pub offset: usize,
+    pub speaker: SpeakerId,
+}
+
+/// The speakers of a [TranscriptionSnapshot](crate::transcriber::TranscriptionSnapshot): the
+/// turns within its confirmed text, and the speaker of each working segment.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SnapshotSpeakers {
+    confirmed_turns: Arc<[SpeakerTurn]>,
+    segment_speakers: Arc<[Option<SpeakerId>]>,
+}
+
+impl SnapshotSpeakers {
+    pub fn new(
+        confirmed_turns: Arc<[SpeakerTurn]>,
+        segment_speakers: Arc<[Option<SpeakerId>]>,
+    ) -> Self {
+        Self {
+            confirmed_turns,
+            segment_speakers,
+        }
+    }
+
+    /// The speaker turns within the confirmed text, in order of offset.
+    pub fn confirmed_turns(&self) -> &[SpeakerTurn] {
+        &self.confirmed_turns
+    }
+
+    /// The speaker of each working segment, (parallel to the snapshot's string segments).
+    pub fn segment_speakers(&self) -> &[Option<SpeakerId>] {
+        &self.segment_speakers
+    }
+
+    /// The speaker of the working segment at index, if known.
+    pub fn segment_speaker(&self, index: usize) -> Option<SpeakerId> {
+        self.segment_speakers.get(index).copied().flatten()
+    }
+
+    /// The speaker of the end of the confirmed text, if known.
+    pub fn last_confirmed_speaker(&self) -> Option<SpeakerId> {
+        self.confirmed_turns.last().map(|turn| turn.speaker)
+    }
+}
+
+/// Assigns speakers to consecutive segments from the turns whisper detects: a segment flagged
+/// with a turn is the last segment of its speaker.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct TurnLabeler {
+    current: SpeakerId,
+    turn_pending: bool,
+}
+
+impl TurnLabeler {
+    pub(crate) fn new(first: SpeakerId) -> Self {
+        Self {
+            current: first,
+            turn_pending: false,
+        }
+    }
+
+    pub(crate) fn label(&mut self, speaker_turn_next: bool) -> SpeakerId {
+        if self.turn_pending {
+            self.current = self.current.next();
+        }
+        self.turn_pending = speaker_turn_next;
+        self.current
+    }
+}
+
+/// Names and merges the speakers of a session, (e.g. in response to a user renaming or combining
+/// speakers in a UI). Ids that have not been named are labelled by their [SpeakerId]'s Display.
+///
+/// Merging is not applied to transcriber output; resolve ids through the registry when rendering.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, Debug, Default)]
+pub struct SpeakerRegistry {
+    names: HashMap<SpeakerId, String>,
+    merged: HashMap<SpeakerId, SpeakerId>,
+    seen: Vec<SpeakerId>,
+}
+
+impl SpeakerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that a speaker has appeared, so that it is listed by [SpeakerRegistry::speakers].
+    pub fn observe(&mut self, speaker: SpeakerId) {
+        if !self.seen.contains(&speaker) {
+            self.seen.push(speaker);
+        }
+    }
+
+    /// Observes every speaker in a snapshot.
+    pub fn observe_snapshot(&mut self, speakers: &SnapshotSpeakers) {
+        for turn in speakers.confirmed_turns() {
+            self.observe(turn.speaker);
+        }
+        for speaker in speakers.segment_speakers().iter().flatten() {
+            self.observe(*speaker);
+        }
+    }
+
+    /// The id a speaker has been merged into, (or the speaker itself if it has not been merged).
+    pub fn resolve(&self, speaker: SpeakerId) -> SpeakerId {
+        let mut resolved = speaker;
+        // Merges always point at an unmerged id, so this is at most one step; the bound is just
+        // a guard against a hand-edited registry with a cycle.
+        for _ in 0..=self.merged.len() {
+            match self.merged.get(&resolved) {
+                Some(into) => resolved = *into,
+                None => break,
+            }
+        }
+        resolved
+    }
+
+    /// Names a speaker, (or the speaker it has been merged into).
+    pub fn rename(&mut self, speaker: SpeakerId, name: impl Into<String>) {
+        let speaker = self.resolve(speaker);
+        self.observe(speaker);
+        self.names.insert(speaker, name.into());
+    }
+
+    /// The name given to a speaker, if any.
+    pub fn name(&self, speaker: SpeakerId) -> Option<&str> {
+        self.names.get(&self.resolve(speaker)).map(String::as_str)
+    }
+
+    /// The display label for a speaker: its name if it has been named, otherwise its id.
+    pub fn label(&self, speaker: SpeakerId) -> String {
+        let speaker = self.resolve(speaker);
+        self.names
+            .get(&speaker)
+            .cloned()
+            .unwrap_or_else(|| speaker.to_string())
+    }
+
+    /// Merges a speaker into another, (e.g. when diarization split one person into two ids).
+    /// The merged speaker's name is kept if the speaker it is merged into has not been named.
+    /// Merging a speaker into itself does nothing.
+    pub fn merge(&mut self, speaker: SpeakerId, into: SpeakerId) {
+        let speaker = self.resolve(speaker);
+        let into = self.resolve(into);
+        if speaker == into {
+            return;
+        }
+        if let Some(name) = self.names.remove(&speaker) {
+            self.names.entry(into).or_insert(name);
+        }
+        // Repoint anything already merged into the speaker, so that merges stay one step.
+        for target in self.merged.values_mut() {
+            if *target == speaker {
+                *target = into;
+            }
+        }
+        self.merged.insert(speaker, into);
+        self.observe(into);
+    }
+
+    /// The distinct speakers observed, after merging, in order of appearance.
+    pub fn speakers(&self) -> Vec<SpeakerId> {
+        let mut speakers = Vec::with_capacity(self.seen.len());
+        for speaker in self.seen.iter().map(|speaker| self.resolve(*speaker)) {
+            if !speakers.contains(&speaker) {
+                speakers.push(speaker);
+            }
+        }
+        speakers
+    }
+}
@@ -0,0 +1,175 @@
+//! Word-level timestamps for realtime output, so that UIs can render live captions with timing,
+//! (e.g. highlighting each word as it is spoken).
+//!
+//! When enabled with
+//! [WhisperRealtimeConfigs::with_timestamped_snapshots](crate::whisper::configs::WhisperRealtimeConfigs::with_timestamped_snapshots),
+//! a [TimestampedSnapshot] of each inference window's segments is sent as
+//! [WhisperOutput::TimestampedSnapshot](crate::transcriber::WhisperOutput::TimestampedSnapshot).
+//! Word timings are grouped from whisper's token-level timestamps, (see: [words_from_tokens]).
+//!
+//! All timestamps are measured in centiseconds from the start of the stream, (see:
+//! [AudioRingBuffer::get_samples_pushed](crate::audio::audio_ring_buffer::AudioRingBuffer::get_samples_pushed)),
+//! so timings stay comparable across windows and buffer clears.
+
+use std::sync::Arc;
+
+use crate::transcriber::RibbleWhisperSegment;
+
+/// A word and when it was spoken.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct WordTiming {
+    pub text: Arc<str>,
+    /// Measured in centiseconds.
+    pub start_time: i64,
+    /// Measured in centiseconds.
+    pub end_time: i64,
+    /// The mean probability of the word's tokens, (0.0 - 1.0).
+    pub probability: f32,
+}
+
+/// A text token of a decoded segment, for grouping into words, (see: [words_from_tokens]).
+#[derive(Copy, Clone, Debug)]
+pub struct TokenTiming<'a> {
+    /// The token's bytes. A multi-byte character may be split across tokens.
+    pub bytes: &'a [u8],
+    /// Measured in centiseconds.
+    pub start_time: i64,
+    /// Measured in centiseconds.
+    pub end_time: i64,
+    pub probability: f32,
+}
+
+/// Groups a segment's text tokens into words. A token starting with whitespace starts a new word;
+/// any other token, (e.g. the rest of a split word, or punctuation), continues the current word.
+/// Special and timestamp tokens are expected to be filtered out beforehand.
+pub fn words_from_tokens<'a>(tokens: impl IntoIterator<Item = TokenTiming<'a>>) -> Vec<WordTiming> {
+    let mut words = vec![];
+    let mut bytes = vec![];
+    let mut probabilities = vec![];
+    let mut start_time = 0;
+    let mut end_time = 0;
+    for token in tokens {
+        let starts_word = token
+            .bytes
+            .first()
+            .is_some_and(|byte| byte.is_ascii_whitespace());
+        if starts_word || bytes.is_empty() {
+            push_word(&mut words, &bytes, &probabilities, start_time, end_time);
+            bytes.clear();
+            probabilities.clear();
+            start_time = token.start_time;
+        }
+        bytes.extend_from_slice(token.bytes);
+        probabilities.push(token.probability);
+        end_time = token.end_time.max(start_time);
+    }
+    push_word(&mut words, &bytes, &probabilities, start_time, end_time);
+    words
+}
+
+// Pushes a word from its tokens, unless it's only whitespace.
+fn push_word(
+    words: &mut Vec<WordTiming>,
+    bytes: &[u8],
+    probabilities: &[f32],
+    start_time: i64,
+    end_time: i64,
+) {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim();
+    if text.is_empty() {
+        return;
+    }
+    words.push(WordTiming {
+        text: Arc::from(text),
+        start_time,
+        end_time,
+        probability: probabilities.iter().sum::<f32>() / probabilities.len() as f32,
+    });
+}
+
+/// A segment and the timings of its words.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone)]
+pub struct TimestampedSegment {
+    pub segment: RibbleWhisperSegment,
+    pub words: Vec<WordTiming>,
+}
+
+impl TimestampedSegment {
+    /// Moves the segment and its words later by offset, (in centiseconds), e.g. from a window's
+    /// timeline onto the stream's.
+    pub fn offset(mut self, offset: i64) -> Self {
+        self.segment.start_time += offset;
+        self.segment.end_time += offset;
+        for word in self.words.iter_mut() {
+            word.start_time += offset;
+            word.end_time += offset;
+        }
+        self
+    }
+}
+
+/// The segments of an inference window, with word-level timestamps, (see:
+/// [crate::transcriber::timestamps]).
+///
+/// Each window re-transcribes audio that hasn't been confirmed yet, so a snapshot replaces the
+/// timings of any earlier snapshot that overlaps it, from window_start onward. Segments that were
+/// already committed are left out, so the first segment may start after window_start.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Default)]
+pub struct TimestampedSnapshot {
+    /// Where the window starts, measured in centiseconds.
+    pub window_start: i64,
+    pub segments: Vec<TimestampedSegment>,
+}
+
+impl TimestampedSnapshot {
+    /// The words of every segment, in order.
+    pub fn words(&self) -> impl Iterator<Item = &WordTiming> {
+        self.segments
+            .iter()
+            .flat_map(|segment| segment.words.iter())
+    }
+
+    /// Where the last segment ends, (or the window start, if there are no segments).
+    pub fn end_time(&self) -> i64 {
+        self.segments
+            .last()
+            .map_or(self.window_start, |segment| segment.segment.end_time)
+    }
+}
+
+impl std::fmt::Display for TimestampedSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = self
+            .segments
+            .iter()
+            .map(|segment| segment.segment.text().trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+        write!(f, "{text}")
+    }
+}
+
+// Groups a whisper segment's text tokens into words, timed relative to its inference window.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn word_timings(
+    segment: &whisper_rs::WhisperSegment,
+    eot: whisper_rs::WhisperTokenId,
+) -> Vec<WordTiming> {
+    let tokens = (0..segment.n_tokens())
+        .filter_map(|idx| segment.get_token(idx))
+        .map(|token| (token.token_data(), token.to_bytes().map(<[u8]>::to_vec)))
+        // Special and timestamp tokens come after the end-of-text token.
+        .filter(|(data, _)| data.id < eot)
+        .filter_map(|(data, bytes)| Some((data, bytes.ok()?)))
+        .collect::<Vec<_>>();
+    words_from_tokens(tokens.iter().map(|(data, bytes)| TokenTiming {
+        bytes,
+        start_time: data.t0,
+        end_time: data.t1,
+        probability: data.p,
+    }))
+}
@@ -0,0 +1,164 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{spawn, JoinHandle};
+use std::time::{Duration, Instant};
+
+use arc_swap::ArcSwap;
+
+use crate::transcriber::{
+    SnapshotAssembler, TranscriptionSnapshot, WhisperControlPhrase, WhisperOutput,
+};
+
+/// Counters collected from the outputs consumed by a [UiBridge].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct UiMetrics {
+    /// Snapshots received, (including snapshot deltas).
+    pub snapshots: u64,
+    pub control_phrases: u64,
+    /// The time between the bridge starting and the most recent output, if any.
+    pub last_output: Option<Duration>,
+    /// The length of the confirmed transcription, in characters.
+    pub confirmed_chars: usize,
+    /// The number of working (unconfirmed) segments in the latest snapshot.
+    pub working_segments: usize,
+}
+
+impl UiMetrics {
+    /// Snapshots received per second, averaged over the given time since the bridge started.
+    pub fn snapshots_per_second(&self, elapsed: Duration) -> f64 {
+        let secs = elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.snapshots as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+struct UiState {
+    snapshot: ArcSwap<TranscriptionSnapshot>,
+    control_phrase: ArcSwap<WhisperControlPhrase>,
+    metrics: ArcSwap<UiMetrics>,
+    generation: AtomicU64,
+    finished: AtomicBool,
+}
+
+/// Consumes a transcriber's outputs on a background thread and exposes the latest state through
+/// lock-free reads, for immediate-mode GUIs, (e.g. egui), that poll every frame.
+///
+/// Reading never blocks the transcriber, and the transcriber never blocks the UI: each read
+/// returns the most recent snapshot, control phrase and metrics. Use [UiBridge::generation] to
+/// skip work on frames where nothing has changed, or [UiBridge::spawn_with_notify] to wake the UI
+/// when an output arrives, (e.g. with egui's request_repaint, or by emitting a Tauri event).
+///
+/// The background thread exits once the output channel disconnects, (i.e. when the transcriber
+/// finishes and drops its sender). Requires the ui-bridge feature.
+pub struct UiBridge {
+    state: Arc<UiState>,
+    started: Instant,
+    thread: JoinHandle<()>,
+}
+
+impl UiBridge {
+    /// Starts consuming outputs, (e.g. from the receiving end of [crate::utils::get_channel]).
+    pub fn spawn<I>(outputs: I) -> Self
+    where
+        I: IntoIterator<Item = WhisperOutput> + Send + 'static,
+    {
+        Self::spawn_with_notify(outputs, || {})
+    }
+
+    /// Starts consuming outputs, calling notify after each output has been applied.
+    /// Notify is called from the background thread and should return quickly.
+    pub fn spawn_with_notify<I, F>(outputs: I, notify: F) -> Self
+    where
+        I: IntoIterator<Item = WhisperOutput> + Send + 'static,
+        F: Fn() + Send + 'static,
+    {
+        let state = Arc::new(UiState {
+            snapshot: ArcSwap::from_pointee(TranscriptionSnapshot::default()),
+            control_phrase: ArcSwap::from_pointee(WhisperControlPhrase::default()),
+            metrics: ArcSwap::from_pointee(UiMetrics::default()),
+            generation: AtomicU64::new(0),
+            finished: AtomicBool::new(false),
+        });
+        let started = Instant::now();
+        let thread_state = Arc::clone(&state);
+        let thread = spawn(move || {
+            let mut assembler = SnapshotAssembler::new();
+            let mut metrics = UiMetrics::default();
+            for output in outputs {
+                metrics.last_output = Some(started.elapsed());
+                match &output {
+                    WhisperOutput::ControlPhrase(phrase) => {
+                        metrics.control_phrases += 1;
+                        thread_state.control_phrase.store(Arc::new(phrase.clone()));
+                    }
+                    WhisperOutput::AudioEvent(_)
+                    | WhisperOutput::DecodeDiagnostics(_)
+                    | WhisperOutput::Correction(_)
+                    | WhisperOutput::TimestampedSnapshot(_) => {}
+                    _ => {
+                        metrics.snapshots += 1;
+                        if let Some(snapshot) = assembler.push(&output) {
+                            metrics.confirmed_chars = snapshot
+                                .confirmed_text()
+                                .chunks()
+                                .iter()
+                                .map(|chunk| chunk.chars().count())
+                                .sum();
+                            metrics.working_segments = snapshot.string_segments().len();
+                            thread_state.snapshot.store(snapshot);
+                        }
+                    }
+                }
+                thread_state.metrics.store(Arc::new(metrics));
+                thread_state.generation.fetch_add(1, Ordering::Release);
+                notify();
+            }
+            thread_state.generation.fetch_add(1, Ordering::Release);
+            notify();
+            thread_state.finished.store(true, Ordering::Release);
+        });
+        Self {
+            state,
+            started,
+            thread,
+        }
+    }
+
+    /// The latest snapshot; empty until the first snapshot arrives.
+    pub fn snapshot(&self) -> Arc<TranscriptionSnapshot> {
+        self.state.snapshot.load_full()
+    }
+
+    /// The latest control phrase; [WhisperControlPhrase::Idle] until the first arrives.
+    pub fn control_phrase(&self) -> Arc<WhisperControlPhrase> {
+        self.state.control_phrase.load_full()
+    }
+
+    pub fn metrics(&self) -> UiMetrics {
+        **self.state.metrics.load()
+    }
+
+    /// The time since the bridge started consuming outputs.
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Increments whenever the state changes, so that the UI can compare it with the value from
+    /// its last frame to tell whether anything needs redrawing.
+    pub fn generation(&self) -> u64 {
+        self.state.generation.load(Ordering::Acquire)
+    }
+
+    /// Returns true once the output channel has disconnected and every output has been applied.
+    pub fn is_finished(&self) -> bool {
+        self.state.finished.load(Ordering::Acquire)
+    }
+
+    /// Blocks until the output channel disconnects, (e.g. after stopping the transcriber).
+    pub fn join(self) {
+        let _ = self.thread.join();
+    }
+}
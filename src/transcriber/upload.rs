@@ -0,0 +1,203 @@
+//! Upload-and-transcribe for web services. Requires the upload feature.
+//!
+//! [UploadTranscriber] takes an uploaded file as it arrives, (e.g. an HTTP request body and its
+//! Content-Type), decodes and resamples it, runs an
+//! [OfflineTranscriber](crate::transcriber::offline_transcriber::OfflineTranscriber) over it, and
+//! returns a [TranscriptDocument], so an endpoint only needs to pass the body through and
+//! serialize the result.
+//!
+//! Decoding and transcription run on tokio's blocking thread pool, so awaiting a transcription
+//! does not stall the runtime's worker threads.
+
+use std::error::Error;
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use futures_core::Stream;
+use futures_util::StreamExt;
+
+use crate::audio::AudioChannelConfiguration;
+use crate::audio::loading::load_normalized_audio_bytes_with_mime_type_with_abort;
+use crate::postprocess::document::{DocumentSegmenter, TranscriptDocument};
+use crate::transcriber::offline_transcriber::OfflineTranscriberBuilder;
+use crate::transcriber::vad::Silero;
+use crate::utils::errors::RibbleWhisperError;
+use crate::whisper::configs::WhisperConfigs;
+use crate::whisper::model::ModelRetriever;
+
+/// The default limit on the size of an upload, (500 MiB).
+pub const DEFAULT_MAX_UPLOAD_BYTES: usize = 500 * 1024 * 1024;
+
+// MIME types that aren't audio/* or video/*, but may hold audio.
+const AUDIO_CONTAINER_MIME_TYPES: [&str; 2] = ["application/ogg", "application/octet-stream"];
+
+/// Transcribes uploaded audio files into [TranscriptDocument]s, (see:
+/// [crate::transcriber::upload]). This can be shared across requests, (e.g. in an `Arc`).
+pub struct UploadTranscriber<M: ModelRetriever + Send + Sync + 'static> {
+    configs: WhisperConfigs,
+    model_retriever: Arc<M>,
+    segmenter: DocumentSegmenter,
+    max_upload_bytes: usize,
+}
+
+impl<M: ModelRetriever + Send + Sync + 'static> UploadTranscriber<M> {
+    pub fn new(configs: WhisperConfigs, model_retriever: M) -> Self {
+        Self::with_shared_model_retriever(configs, Arc::new(model_retriever))
+    }
+
+    /// Shares a model retriever with the rest of the service, (e.g. a realtime endpoint).
+    pub fn with_shared_model_retriever(configs: WhisperConfigs, model_retriever: Arc<M>) -> Self {
+        Self {
+            configs,
+            model_retriever,
+            segmenter: DocumentSegmenter::new(),
+            max_upload_bytes: DEFAULT_MAX_UPLOAD_BYTES,
+        }
+    }
+
+    /// Sets how transcriptions are split into paragraphs and sentences.
+    pub fn with_segmenter(mut self, segmenter: DocumentSegmenter) -> Self {
+        self.segmenter = segmenter;
+        self
+    }
+
+    /// Sets the largest upload accepted, in bytes. Larger uploads are rejected as soon as they
+    /// pass the limit, without buffering the rest.
+    pub fn with_max_upload_bytes(mut self, max_upload_bytes: usize) -> Self {
+        self.max_upload_bytes = max_upload_bytes;
+        self
+    }
+
+    pub fn configs(&self) -> &WhisperConfigs {
+        &self.configs
+    }
+
+    pub fn max_upload_bytes(&self) -> usize {
+        self.max_upload_bytes
+    }
+
+    /// Reads an upload's body to the end, then transcribes it, (see: [UploadTranscriber::transcribe]).
+    /// Returns Err before reading the body if the MIME type can't hold audio, (see:
+    /// [is_audio_mime_type]), or as soon as the body passes the size limit.
+    pub async fn transcribe_stream<S, B, E>(
+        &self,
+        mime_type: &str,
+        body: S,
+        run_transcription: Arc<AtomicBool>,
+    ) -> Result<TranscriptDocument, RibbleWhisperError>
+    where
+        S: Stream<Item = Result<B, E>>,
+        B: AsRef<[u8]>,
+        E: Error + Send + Sync + 'static,
+    {
+        check_mime_type(mime_type)?;
+        let mut body = pin!(body);
+        let mut bytes = Vec::new();
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk.map_err(std::io::Error::other)?;
+            let chunk = chunk.as_ref();
+            if bytes.len() + chunk.len() > self.max_upload_bytes {
+                return Err(self.too_large());
+            }
+            bytes.extend_from_slice(chunk);
+        }
+        self.transcribe(mime_type, bytes, run_transcription).await
+    }
+
+    /// Decodes an upload, resamples it to 16 kHz mono and transcribes it.
+    ///
+    /// Setting run_transcription to false cancels the transcription, (returning Err); so does
+    /// dropping the future, (e.g. when the client disconnects).
+    pub async fn transcribe(
+        &self,
+        mime_type: &str,
+        bytes: Vec<u8>,
+        run_transcription: Arc<AtomicBool>,
+    ) -> Result<TranscriptDocument, RibbleWhisperError> {
+        check_mime_type(mime_type)?;
+        if bytes.len() > self.max_upload_bytes {
+            return Err(self.too_large());
+        }
+
+        let mime_type = mime_essence(mime_type);
+        let configs = self.configs;
+        let model_retriever = Arc::clone(&self.model_retriever);
+        let cancel_on_drop = CancelOnDrop(Some(Arc::clone(&run_transcription)));
+        let segments = tokio::task::spawn_blocking(move || {
+            let audio = load_normalized_audio_bytes_with_mime_type_with_abort(
+                bytes,
+                &mime_type,
+                None::<fn(usize)>,
+                &run_transcription,
+            )?;
+            OfflineTranscriberBuilder::<Silero, M>::new()
+                .with_configs(configs)
+                .with_audio(audio)
+                .with_channel_configurations(AudioChannelConfiguration::Mono)
+                .with_shared_model_retriever(model_retriever)
+                .build()?
+                .process_segments(run_transcription)
+        })
+        .await;
+        cancel_on_drop.disarm();
+        let segments = segments.map_err(|e| {
+            RibbleWhisperError::Unknown(format!("Upload transcription failed: {e}"))
+        })??;
+
+        Ok(self.segmenter.segment(&segments))
+    }
+
+    fn too_large(&self) -> RibbleWhisperError {
+        RibbleWhisperError::ParameterError(format!(
+            "Upload exceeds the limit of {} bytes.",
+            self.max_upload_bytes
+        ))
+    }
+}
+
+/// Whether an upload's MIME type can hold audio: audio/*, video/*, (for the audio track), Ogg,
+/// or unspecified binary data. Parameters, (e.g. "; codecs=opus"), are ignored.
+pub fn is_audio_mime_type(mime_type: &str) -> bool {
+    let essence = mime_essence(mime_type);
+    essence.starts_with("audio/")
+        || essence.starts_with("video/")
+        || AUDIO_CONTAINER_MIME_TYPES.contains(&essence.as_str())
+}
+
+// The MIME type without parameters, lowercased, (e.g. "audio/ogg; codecs=opus" -> "audio/ogg").
+fn mime_essence(mime_type: &str) -> String {
+    mime_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn check_mime_type(mime_type: &str) -> Result<(), RibbleWhisperError> {
+    if is_audio_mime_type(mime_type) {
+        Ok(())
+    } else {
+        Err(RibbleWhisperError::AudioFormat {
+            reason: format!("Unsupported upload type: {mime_type}"),
+        })
+    }
+}
+
+// Stops the transcription if the future awaiting it is dropped; disarmed once it finishes.
+struct CancelOnDrop(Option<Arc<AtomicBool>>);
+
+impl CancelOnDrop {
+    fn disarm(mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if let Some(run_transcription) = self.0.take() {
+            run_transcription.store(false, Ordering::Release);
+        }
+    }
+}
@@ -1,4 +1,8 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(not(target_arch = "wasm32"))]
 use parking_lot::Mutex;
+#[cfg(not(target_arch = "wasm32"))]
 use voice_activity_detector::{IteratorExt, LabeledAudio};
 
 use crate::audio::pcm::PcmS16Convertible;
@@ -12,6 +16,29 @@ pub trait VAD<T>: Resettable {
     fn voice_detected(&mut self, samples: &[T]) -> bool;
     // For optimizing offline transcription by reducing the amount of audio that whisper needs to process
     fn extract_voiced_frames(&mut self, samples: &[T]) -> Box<[T]>;
+
+    /// Like [VAD::extract_voiced_frames], but stops scanning once run_transcription is set to false,
+    /// returning Err([RibbleWhisperError::Cancelled]), so that long audio can be cancelled promptly.
+    /// The default implementation only checks the flag before and after extracting; backends that
+    /// scan frame-by-frame should override this to check it between frames.
+    fn extract_voiced_frames_with_abort(
+        &mut self,
+        samples: &[T],
+        run_transcription: &AtomicBool,
+    ) -> Result<Box<[T]>, RibbleWhisperError> {
+        check_running(run_transcription)?;
+        let voiced = self.extract_voiced_frames(samples);
+        check_running(run_transcription)?;
+        Ok(voiced)
+    }
+}
+
+fn check_running(run_transcription: &AtomicBool) -> Result<(), RibbleWhisperError> {
+    if run_transcription.load(Ordering::Acquire) {
+        Ok(())
+    } else {
+        Err(RibbleWhisperError::Cancelled)
+    }
 }
 
 /// For resetting the state of a voice activity detector backend so that it can be reused
@@ -20,6 +47,9 @@ pub trait Resettable {
     fn reset_session(&mut self);
 }
 
+// Silero and WebRtc wrap native libraries, (onnxruntime and libfvad), and are unavailable on
+// wasm32, (there is no ONNX-wasm backend); Earshot is pure Rust and can be used there instead.
+
 /// Builder for [Silero] that adapts voice_activity_detector's builder
 /// and also includes a starting detection probability.
 /// The probability threshold can be swapped after building if needed.
@@ -33,6 +63,7 @@ pub trait Resettable {
 /// Self-hosted ONNX runtime binaries have not yet been implemented and may not be.
 /// In the meantime, use [WebRtc] or [Earshot]
 /// if telemetry is a concern.
+#[cfg(not(target_arch = "wasm32"))]
 #[derive(Copy, Clone)]
 pub struct SileroBuilder {
     sample_rate: SileroSampleRate,
@@ -44,6 +75,7 @@ pub struct SileroBuilder {
 }
 
 // TODO: docstring
+#[cfg(not(target_arch = "wasm32"))]
 #[derive(Default, Copy, Clone)]
 pub enum SileroSampleRate {
     R8kHz,
@@ -51,6 +83,7 @@ pub enum SileroSampleRate {
     R16kHz,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl SileroSampleRate {
     fn vad_sample_rate(&self) -> i64 {
         (*self).into()
@@ -64,6 +97,7 @@ impl SileroSampleRate {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl From<SileroSampleRate> for i64 {
     fn from(value: SileroSampleRate) -> Self {
         match value {
@@ -73,6 +107,7 @@ impl From<SileroSampleRate> for i64 {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl SileroBuilder {
     pub fn new() -> Self {
         Self {
@@ -121,6 +156,7 @@ impl SileroBuilder {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl Default for SileroBuilder {
     fn default() -> Self {
         Self::new()
@@ -133,6 +169,7 @@ impl Default for SileroBuilder {
 /// Self-hosted ONNX runtime binaries have not yet been implemented and may not be.
 /// In the meantime, use [WebRtc] or [Earshot]
 /// if telemetry is a concern.
+#[cfg(not(target_arch = "wasm32"))]
 pub struct Silero {
     vad: voice_activity_detector::VoiceActivityDetector,
     /// Samples with probabilities higher than this threshold are considered to have voice activity.
@@ -142,6 +179,7 @@ pub struct Silero {
     voiced_proportion_threshold: f32,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl Silero {
     const PADDING_CHUNKS: usize = 3;
 
@@ -169,6 +207,7 @@ impl Silero {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl Resettable for Silero {
     /// Clears the state of the VAD backend. For VAD reuse.
     fn reset_session(&mut self) {
@@ -178,6 +217,7 @@ impl Resettable for Silero {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl<T: voice_activity_detector::Sample> VAD<T> for Silero {
     /// Detects whether the given samples contain voiced audio.
     /// NOTE: This implementation assumes that the samples are at the same sample rate as the configured VAD.
@@ -217,11 +257,20 @@ impl<T: voice_activity_detector::Sample> VAD<T> for Silero {
         voiced_proportion >= self.voiced_proportion_threshold
     }
     fn extract_voiced_frames(&mut self, samples: &[T]) -> Box<[T]> {
+        self.extract_voiced_frames_with_abort(samples, &AtomicBool::new(true))
+            .unwrap_or_default()
+    }
+
+    fn extract_voiced_frames_with_abort(
+        &mut self,
+        samples: &[T],
+        run_transcription: &AtomicBool,
+    ) -> Result<Box<[T]>, RibbleWhisperError> {
         if samples.is_empty() {
-            return vec![].into_boxed_slice();
+            return Ok(vec![].into_boxed_slice());
         }
 
-        samples
+        let voiced = samples
             .iter()
             .copied()
             .label(
@@ -229,10 +278,14 @@ impl<T: voice_activity_detector::Sample> VAD<T> for Silero {
                 self.detection_probability_threshold,
                 Self::PADDING_CHUNKS,
             )
+            // Each chunk runs the model, so stop pulling chunks as soon as the job is cancelled.
+            .take_while(|_| run_transcription.load(Ordering::Acquire))
             .filter(|frame| frame.is_speech())
             // Extract the chunks which contain speech
             .flat_map(|frame| frame.iter().copied().collect::<Vec<T>>())
-            .collect()
+            .collect();
+        check_running(run_transcription)?;
+        Ok(voiced)
     }
 }
 
@@ -246,6 +299,7 @@ pub enum WebRtcSampleRate {
 }
 
 impl WebRtcSampleRate {
+    #[cfg(not(target_arch = "wasm32"))]
     fn to_webrtc_sample_rate(self) -> webrtc_vad::SampleRate {
         match self {
             Self::R8kHz => webrtc_vad::SampleRate::Rate8kHz,
@@ -280,6 +334,7 @@ pub enum WebRtcFilterAggressiveness {
 }
 
 impl WebRtcFilterAggressiveness {
+    #[cfg(not(target_arch = "wasm32"))]
     fn to_webrtc_vad_mode(self) -> webrtc_vad::VadMode {
         match self {
             Self::Quality => webrtc_vad::VadMode::Quality,
@@ -373,6 +428,7 @@ impl WebRtcBuilder {
 
     /// Builds a [WebRtc] VAD backend.
     /// Returns Err if there's an internal panic due to a memory allocation error.
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn build_webrtc(self) -> Result<WebRtc, RibbleWhisperError> {
         std::panic::catch_unwind(|| {
             webrtc_vad::Vad::new_with_rate_and_mode(
@@ -422,6 +478,7 @@ impl Default for WebRtcBuilder {
 
 /// A thread-safe WebRtc VAD backend for use in transcription.
 /// Adapts [webrtc_vad::Vad] to predict voice activity using WebRtc
+#[cfg(not(target_arch = "wasm32"))]
 pub struct WebRtc {
     vad: Mutex<webrtc_vad::Vad>,
     sample_rate: WebRtcSampleRate,
@@ -434,6 +491,7 @@ pub struct WebRtc {
     voiced_proportion_threshold: f32,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl WebRtc {
     /// Sets the realtime detection probability threshold.
     pub fn with_voiced_proportion_threshold(mut self, proportion: f32) -> Self {
@@ -461,9 +519,12 @@ impl WebRtc {
 
 /// WebRtc is Mutex-protected to adhere to the following thread-safety guarantees made by WebRtc Vad:
 /// <https://chromium.googlesource.com/external/webrtc/+/0332c2db39d6f5c780ce9e92b850bcb57e24e7f8/webrtc/modules/audio_processing/include/audio_processing.h#197>
+#[cfg(not(target_arch = "wasm32"))]
 unsafe impl Send for WebRtc {}
+#[cfg(not(target_arch = "wasm32"))]
 unsafe impl Sync for WebRtc {}
 
+#[cfg(not(target_arch = "wasm32"))]
 impl Resettable for WebRtc {
     /// Clears the state of the VAD backend. For VAD reuse.
     fn reset_session(&mut self) {
@@ -476,6 +537,7 @@ impl Resettable for WebRtc {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl<T: PcmS16Convertible + Copy> VAD<T> for WebRtc {
     /// Detects whether the given samples contain voiced audio.
     /// NOTE: This implementation assumes that the samples are at the same sample rate as the configured VAD
@@ -511,24 +573,35 @@ impl<T: PcmS16Convertible + Copy> VAD<T> for WebRtc {
         voiced_proportion >= self.voiced_proportion_threshold
     }
     fn extract_voiced_frames(&mut self, samples: &[T]) -> Box<[T]> {
+        self.extract_voiced_frames_with_abort(samples, &AtomicBool::new(true))
+            .unwrap_or_default()
+    }
+
+    fn extract_voiced_frames_with_abort(
+        &mut self,
+        samples: &[T],
+        run_transcription: &AtomicBool,
+    ) -> Result<Box<[T]>, RibbleWhisperError> {
         if samples.is_empty() {
-            return vec![].into_boxed_slice();
+            return Ok(vec![].into_boxed_slice());
         }
         let (int_audio, frame_size) = prepare_webrtc_frames(
             samples,
             self.frame_length_in_ms,
             self.sample_rate.to_sample_rate_hz(),
         );
-        let frames = int_audio.chunks_exact(frame_size);
         let mut vad = self.vad.lock();
-        frames
-            .filter(|&frame| {
-                vad.is_voice_segment(frame)
-                    .expect("The Frame size should be valid")
-            })
-            .flatten()
-            .map(|&s| T::from_pcm_s16(s))
-            .collect()
+        let mut voiced = vec![];
+        for frame in int_audio.chunks_exact(frame_size) {
+            check_running(run_transcription)?;
+            if vad
+                .is_voice_segment(frame)
+                .expect("The Frame size should be valid")
+            {
+                voiced.extend(frame.iter().map(|&s| T::from_pcm_s16(s)));
+            }
+        }
+        Ok(voiced.into_boxed_slice())
     }
 }
 
@@ -622,21 +695,31 @@ impl<T: PcmS16Convertible + Copy> VAD<T> for Earshot {
     }
 
     fn extract_voiced_frames(&mut self, samples: &[T]) -> Box<[T]> {
+        self.extract_voiced_frames_with_abort(samples, &AtomicBool::new(true))
+            .unwrap_or_default()
+    }
+
+    fn extract_voiced_frames_with_abort(
+        &mut self,
+        samples: &[T],
+        run_transcription: &AtomicBool,
+    ) -> Result<Box<[T]>, RibbleWhisperError> {
         if samples.is_empty() {
-            return vec![].into_boxed_slice();
+            return Ok(vec![].into_boxed_slice());
         }
 
         let (int_audio, frame_size) =
             prepare_webrtc_frames(samples, self.frame_length_in_ms, self.sample_rate);
-        let frames = int_audio.chunks_exact(frame_size);
-        let vad = &mut self.vad;
-        frames
-            .filter(|&frame| {
-                (self.prediction_predicate)(vad, frame).expect("Frame size should be valid.")
-            })
-            .flatten()
-            .map(|&s| T::from_pcm_s16(s))
-            .collect()
+        let mut voiced = vec![];
+        for frame in int_audio.chunks_exact(frame_size) {
+            check_running(run_transcription)?;
+            if (self.prediction_predicate)(&mut self.vad, frame)
+                .expect("Frame size should be valid.")
+            {
+                voiced.extend(frame.iter().map(|&s| T::from_pcm_s16(s)));
+            }
+        }
+        Ok(voiced.into_boxed_slice())
     }
 }
 
@@ -0,0 +1,82 @@
+//! Hands-free session start with a wake phrase, (e.g. "hey ribble").
+//!
+//! A realtime transcriber built with
+//! [RealtimeTranscriberBuilder::with_wake_phrase](crate::transcriber::realtime_transcriber::RealtimeTranscriberBuilder::with_wake_phrase)
+//! starts out dormant: it only runs whisper over short windows that the voice activity detector
+//! hears speech in, and spots the wake phrase in their text. Once it's heard, (or the session is
+//! started with
+//! [RealtimeTranscriberHandle::start_session](crate::transcriber::realtime_transcriber::RealtimeTranscriberHandle::start_session)),
+//! the audio buffer is cleared, [WhisperControlPhrase::SessionStarted](crate::transcriber::WhisperControlPhrase::SessionStarted)
+//! is sent, and transcription runs as usual.
+//!
+//! Nothing heard while dormant is sent, recorded or kept, including the wake phrase itself.
+
+use crate::utils::errors::RibbleWhisperError;
+
+/// The default length of audio searched for the wake phrase, in milliseconds.
+pub const DEFAULT_WAKE_WINDOW_MS: usize = 3000;
+
+/// One or more phrases that start a session, (see: [crate::transcriber::wake_phrase]).
+/// Phrases are matched on whole words, ignoring case and punctuation, so "Hey, Ribble!" matches
+/// "hey ribble" but "hey ribbles" does not.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WakePhrase {
+    // Normalized, (see: normalize).
+    phrases: Vec<String>,
+    window_ms: usize,
+}
+
+impl WakePhrase {
+    /// Returns Err if there are no phrases with any words in them.
+    pub fn new<S: AsRef<str>>(phrases: &[S]) -> Result<Self, RibbleWhisperError> {
+        let phrases: Vec<String> = phrases
+            .iter()
+            .map(|phrase| normalize(phrase.as_ref()))
+            .filter(|phrase| !phrase.is_empty())
+            .collect();
+        if phrases.is_empty() {
+            return Err(RibbleWhisperError::ParameterError(
+                "WakePhrase requires at least one non-empty phrase.".to_string(),
+            ));
+        }
+        Ok(Self {
+            phrases,
+            window_ms: DEFAULT_WAKE_WINDOW_MS,
+        })
+    }
+
+    /// Sets the length of audio searched for the wake phrase, in milliseconds. This should be
+    /// long enough to fit the longest phrase; longer windows cost more to transcribe.
+    pub fn with_window_ms(mut self, window_ms: usize) -> Self {
+        self.window_ms = window_ms;
+        self
+    }
+
+    /// The phrases, as they are matched, (lowercase, without punctuation).
+    pub fn phrases(&self) -> &[String] {
+        &self.phrases
+    }
+
+    pub fn window_ms(&self) -> usize {
+        self.window_ms
+    }
+
+    /// Returns the first wake phrase heard in text, if any.
+    pub fn detect(&self, text: &str) -> Option<&str> {
+        let heard = format!(" {} ", normalize(text));
+        self.phrases
+            .iter()
+            .find(|phrase| heard.contains(&format!(" {phrase} ")))
+            .map(String::as_str)
+    }
+}
+
+// Lowercases text and replaces punctuation with single spaces between words.
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.trim_matches('\''))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,78 @@
+//! Hooks into the audio of each realtime inference window.
+//!
+//! A [WindowHook] attached with
+//! [RealtimeTranscriberBuilder::with_window_hook](crate::transcriber::realtime_transcriber::RealtimeTranscriberBuilder::with_window_hook)
+//! receives each window's samples right before whisper runs over them, and may modify them in
+//! place, (e.g. to apply a denoiser or a gain stage), or just inspect them, (e.g. [LevelMeter]).
+//! Hooks run in the order they were added, and a session recorder receives the window after every
+//! hook has run, (i.e. exactly what whisper saw).
+//!
+//! Each window is a fresh copy of the buffered audio, and consecutive windows overlap, so changes
+//! never carry over into the next window: a stateful hook, (e.g. a denoiser with a noise
+//! estimate), sees most of the same audio again.
+
+use crate::audio::dsp::peak;
+use crate::utils::callback::Callback;
+
+/// Processes the 16 kHz mono audio of each inference window, (see:
+/// [crate::transcriber::window_hooks]).
+/// This is called from the transcription thread for every inference window, so it adds directly
+/// to the transcriber's latency.
+pub trait WindowHook: Send + 'static {
+    /// Processes the window's samples in place.
+    fn process(&mut self, samples: &mut [f32]);
+    /// Clears any state carried between windows, (e.g. before a new session).
+    fn reset(&mut self) {}
+}
+
+/// The levels of an inference window.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WindowLevels {
+    /// The largest absolute sample value.
+    pub peak: f32,
+    /// The root mean square of the samples.
+    pub rms: f32,
+}
+
+impl WindowLevels {
+    /// Measures the levels of the samples; empty audio is silent.
+    pub fn measure(samples: &[f32]) -> Self {
+        let rms = match samples.len() {
+            0 => 0.0,
+            len => (samples.iter().map(|s| s * s).sum::<f32>() / len as f32).sqrt(),
+        };
+        Self {
+            peak: peak(samples),
+            rms,
+        }
+    }
+
+    /// The peak level in dBFS, (negative infinity for silence).
+    pub fn peak_dbfs(&self) -> f32 {
+        20.0 * self.peak.log10()
+    }
+
+    /// The RMS level in dBFS, (negative infinity for silence).
+    pub fn rms_dbfs(&self) -> f32 {
+        20.0 * self.rms.log10()
+    }
+}
+
+/// A [WindowHook] that measures each window's levels and passes them to a callback, (e.g. for an
+/// input meter, or for flagging windows that are too quiet to transcribe). The audio is left
+/// unchanged.
+pub struct LevelMeter<C: Callback<Argument = WindowLevels> + Send + 'static> {
+    callback: C,
+}
+
+impl<C: Callback<Argument = WindowLevels> + Send + 'static> LevelMeter<C> {
+    pub fn new(callback: C) -> Self {
+        Self { callback }
+    }
+}
+
+impl<C: Callback<Argument = WindowLevels> + Send + 'static> WindowHook for LevelMeter<C> {
+    fn process(&mut self, samples: &mut [f32]) {
+        self.callback.call(WindowLevels::measure(samples));
+    }
+}
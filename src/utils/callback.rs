@@ -1,4 +1,12 @@
+use std::any::Any;
+use std::fmt::{Debug, Display, Formatter};
 use std::marker::PhantomData;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::utils::sink::OutputSink;
 
 /// Trait representing optional callbacks
 pub trait Callback {
@@ -6,6 +14,15 @@ pub trait Callback {
     fn call(&mut self, arg: Self::Argument);
 }
 
+/// Trait representing fallible callbacks.
+/// Whisper's callbacks are called from C and cannot return errors, so wrap these in a
+/// [TryCallbackReporter] to have failures reported back to the caller.
+pub trait TryCallback {
+    type Argument;
+    type Error;
+    fn try_call(&mut self, arg: Self::Argument) -> Result<(), Self::Error>;
+}
+
 /// Trait representing optional short-circuiting callbacks.
 /// For use with expensive callbacks, (e.g. OfflineWhisperNewSegmentCallback) that may not
 /// always be required to run. Allows controlling over the frequency at which these callbacks get
@@ -46,6 +63,29 @@ impl<T, CB: FnMut(T)> Callback for RibbleWhisperCallback<T, CB> {
     }
 }
 
+/// Encapsulates a fallible FnMut(T) -> Result<(), E> callback.
+pub struct RibbleWhisperTryCallback<T, E, CB: FnMut(T) -> Result<(), E>> {
+    callback: CB,
+    _marker: PhantomData<(T, E)>,
+}
+
+impl<T, E, CB: FnMut(T) -> Result<(), E>> RibbleWhisperTryCallback<T, E, CB> {
+    pub fn new(callback: CB) -> Self {
+        Self {
+            callback,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, E, CB: FnMut(T) -> Result<(), E>> TryCallback for RibbleWhisperTryCallback<T, E, CB> {
+    type Argument = T;
+    type Error = E;
+    fn try_call(&mut self, arg: T) -> Result<(), E> {
+        (self.callback)(arg)
+    }
+}
+
 /// This is the static equivalent of  [RibbleWhisperCallback]
 /// Encouraged for use when `'static` lifetimes are required, (e.g. OfflineWhisperProgressCallback).
 /// It is not strictly necessary to use this over ProgressCallback, but it may help with
@@ -74,6 +114,16 @@ impl<T, CB: FnMut(T) + 'static> Callback for StaticRibbleWhisperCallback<T, CB>
 
 /// Encapsulates both a basic FnMut(T) callback and an FnMut() -> bool short-circuiting callback
 /// to early-escape potentially expensive callbacks.
+///
+/// Built-in rate-limiting policies can be set at construction; the callback only runs when the
+/// should_run_callback closure and every policy allow it:
+/// * [ShortCircuitRibbleWhisperCallback::with_min_interval]: at most once per interval
+/// * [ShortCircuitRibbleWhisperCallback::with_every_nth]: on the first, and then every nth call
+/// * [ShortCircuitRibbleWhisperCallback::with_only_when_changed]: only when the argument differs
+///   from the last one the callback ran with
+///
+/// To use this where a [Callback] is expected, (e.g. the offline new-segment callback), wrap it
+/// in a [ShortCircuitAdapter].
 #[repr(C)]
 pub struct ShortCircuitRibbleWhisperCallback<T, B, CB>
 where
@@ -82,7 +132,13 @@ where
 {
     should_run_callback: B,
     callback: CB,
-    _marker: PhantomData<T>,
+    min_interval: Option<Duration>,
+    every_nth: Option<usize>,
+    // (T::eq, T::clone): for comparing against the last argument the callback ran with.
+    change_detection: Option<(fn(&T, &T) -> bool, fn(&T) -> T)>,
+    last_run: Option<Instant>,
+    num_checks: usize,
+    last_arg: Option<T>,
 }
 
 impl<T, B, CB> ShortCircuitRibbleWhisperCallback<T, B, CB>
@@ -94,9 +150,58 @@ where
         Self {
             should_run_callback,
             callback,
-            _marker: PhantomData,
+            min_interval: None,
+            every_nth: None,
+            change_detection: None,
+            last_run: None,
+            num_checks: 0,
+            last_arg: None,
         }
     }
+
+    /// Only runs the callback if at least min_interval has passed since it last ran.
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = Some(min_interval);
+        self
+    }
+
+    /// Only runs the callback on the first and then every nth call. This will always be at least 1.
+    pub fn with_every_nth(mut self, n: usize) -> Self {
+        self.every_nth = Some(n.max(1));
+        self
+    }
+
+    pub fn min_interval(&self) -> Option<Duration> {
+        self.min_interval
+    }
+
+    pub fn every_nth(&self) -> Option<usize> {
+        self.every_nth
+    }
+}
+
+impl<T, CB> ShortCircuitRibbleWhisperCallback<T, fn() -> bool, CB>
+where
+    CB: FnMut(T) + 'static,
+{
+    /// Creates a short-circuiting callback that is controlled only by its built-in policies.
+    pub fn from_callback(callback: CB) -> Self {
+        Self::new(|| true, callback)
+    }
+}
+
+impl<T, B, CB> ShortCircuitRibbleWhisperCallback<T, B, CB>
+where
+    T: PartialEq + Clone,
+    B: FnMut() -> bool + 'static,
+    CB: FnMut(T) + 'static,
+{
+    /// Skips the callback if the argument is unchanged since the callback last ran.
+    pub fn with_only_when_changed(mut self) -> Self {
+        self.change_detection = Some((T::eq, T::clone));
+        self.last_arg = None;
+        self
+    }
 }
 
 impl<T, B, CB> ShortCircuitCallback for ShortCircuitRibbleWhisperCallback<T, B, CB>
@@ -107,14 +212,65 @@ where
     type Argument = T;
 
     fn should_run_callback(&mut self) -> bool {
-        (self.should_run_callback)()
+        if !(self.should_run_callback)() {
+            return false;
+        }
+
+        if let Some(n) = self.every_nth {
+            let nth_call = self.num_checks % n == 0;
+            self.num_checks = self.num_checks.wrapping_add(1);
+            if !nth_call {
+                return false;
+            }
+        }
+
+        match (self.min_interval, self.last_run) {
+            (Some(min_interval), Some(last_run)) => last_run.elapsed() >= min_interval,
+            _ => true,
+        }
     }
 
     fn call(&mut self, arg: Self::Argument) {
+        if let Some((is_unchanged, clone)) = self.change_detection {
+            if self
+                .last_arg
+                .as_ref()
+                .is_some_and(|last_arg| is_unchanged(last_arg, &arg))
+            {
+                return;
+            }
+            self.last_arg = Some(clone(&arg));
+        }
+        self.last_run = Some(Instant::now());
         (self.callback)(arg)
     }
 }
 
+/// Adapts a [ShortCircuitCallback] into a [Callback] that checks
+/// [ShortCircuitCallback::should_run_callback] before each call.
+pub struct ShortCircuitAdapter<SC: ShortCircuitCallback> {
+    callback: SC,
+}
+
+impl<SC: ShortCircuitCallback> ShortCircuitAdapter<SC> {
+    pub fn new(callback: SC) -> Self {
+        Self { callback }
+    }
+
+    pub fn into_inner(self) -> SC {
+        self.callback
+    }
+}
+
+impl<SC: ShortCircuitCallback> Callback for ShortCircuitAdapter<SC> {
+    type Argument = SC::Argument;
+    fn call(&mut self, arg: Self::Argument) {
+        if self.callback.should_run_callback() {
+            self.callback.call(arg);
+        }
+    }
+}
+
 /// Encapsulates an abort callback. Return true in the close to indicate "should abort"
 #[repr(C)]
 pub struct RibbleAbortCallback<B>
@@ -183,3 +339,211 @@ impl AbortCallback for Nop<()> {
         false
     }
 }
+
+/// Rate-limits a [Callback] so that it is called at most once per interval. Calls made within the
+/// interval are held back and only the latest value is kept; it is delivered by the next call
+/// after the interval has elapsed, by [ThrottledCallback::flush], or when this is dropped.
+///
+/// Use this to rate-limit expensive consumers, (e.g. UI updates from download progress or the
+/// offline new-segment snapshotting) without writing the timing logic by hand.
+pub struct ThrottledCallback<CB: Callback> {
+    callback: CB,
+    interval: Duration,
+    last_call: Option<Instant>,
+    pending: Option<CB::Argument>,
+}
+
+impl<CB: Callback> ThrottledCallback<CB> {
+    pub fn new(callback: CB, interval: Duration) -> Self {
+        Self {
+            callback,
+            interval,
+            last_call: None,
+            pending: None,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Delivers the held-back value, if there is one.
+    pub fn flush(&mut self) {
+        if let Some(arg) = self.pending.take() {
+            self.last_call = Some(Instant::now());
+            self.callback.call(arg);
+        }
+    }
+}
+
+impl<CB: Callback> Callback for ThrottledCallback<CB> {
+    type Argument = CB::Argument;
+    fn call(&mut self, arg: Self::Argument) {
+        self.pending = Some(arg);
+        let ready = self
+            .last_call
+            .is_none_or(|last_call| last_call.elapsed() >= self.interval);
+        if ready {
+            self.flush();
+        }
+    }
+}
+
+impl<CB: Callback> Drop for ThrottledCallback<CB> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Debounces a [Callback] so that a value is only delivered once calls have settled, (no newer
+/// value arrived for at least the delay). Since there is no timer, a settled value is delivered
+/// by the next call after the delay, by [DebouncedCallback::flush], or when this is dropped.
+pub struct DebouncedCallback<CB: Callback> {
+    callback: CB,
+    delay: Duration,
+    last_call: Option<Instant>,
+    pending: Option<CB::Argument>,
+}
+
+impl<CB: Callback> DebouncedCallback<CB> {
+    pub fn new(callback: CB, delay: Duration) -> Self {
+        Self {
+            callback,
+            delay,
+            last_call: None,
+            pending: None,
+        }
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Delivers the pending value, if there is one, regardless of whether it has settled.
+    pub fn flush(&mut self) {
+        if let Some(arg) = self.pending.take() {
+            self.callback.call(arg);
+        }
+    }
+}
+
+impl<CB: Callback> Callback for DebouncedCallback<CB> {
+    type Argument = CB::Argument;
+    fn call(&mut self, arg: Self::Argument) {
+        let settled = self
+            .last_call
+            .is_some_and(|last_call| last_call.elapsed() >= self.delay);
+        if settled {
+            self.flush();
+        }
+        self.pending = Some(arg);
+        self.last_call = Some(Instant::now());
+    }
+}
+
+impl<CB: Callback> Drop for DebouncedCallback<CB> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// A failure raised inside a user callback, see: [TryCallbackReporter].
+pub enum CallbackError<E> {
+    /// The callback returned an error.
+    Failed(E),
+    /// The callback panicked. Contains the panic message, if it was a string.
+    Panicked(String),
+}
+
+impl<E> CallbackError<E> {
+    pub fn is_panic(&self) -> bool {
+        matches!(self, CallbackError::Panicked(_))
+    }
+}
+
+impl<E: Debug> Debug for CallbackError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallbackError::Failed(e) => f.debug_tuple("Failed").field(e).finish(),
+            CallbackError::Panicked(msg) => f.debug_tuple("Panicked").field(msg).finish(),
+        }
+    }
+}
+
+impl<E: Display> Display for CallbackError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallbackError::Failed(e) => write!(f, "Callback failed: {e}"),
+            CallbackError::Panicked(msg) => write!(f, "Callback panicked: {msg}"),
+        }
+    }
+}
+
+impl<E: Debug + Display> std::error::Error for CallbackError<E> {}
+
+/// Gets the message from a panic payload, if it was a string.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|msg| msg.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Unknown panic".to_string())
+}
+
+/// Adapts a [TryCallback] into a [Callback] that reports errors, (and panics), through an
+/// [OutputSink], e.g. the sending half of a channel polled by the UI.
+/// This allows fallible callbacks to be used wherever a [Callback] is expected, including whisper's
+/// progress and new-segment callbacks, where errors would otherwise be swallowed.
+///
+/// Errors are sent without blocking; if the sink is full, the error is dropped.
+/// Supply a stop flag with [TryCallbackReporter::with_stop_flag] to also stop the transcription
+/// (or download) when the callback fails.
+pub struct TryCallbackReporter<CB, S>
+where
+    CB: TryCallback,
+    S: OutputSink<CallbackError<CB::Error>>,
+{
+    callback: CB,
+    error_sink: S,
+    stop_flag: Option<Arc<AtomicBool>>,
+}
+
+impl<CB, S> TryCallbackReporter<CB, S>
+where
+    CB: TryCallback,
+    S: OutputSink<CallbackError<CB::Error>>,
+{
+    pub fn new(callback: CB, error_sink: S) -> Self {
+        Self {
+            callback,
+            error_sink,
+            stop_flag: None,
+        }
+    }
+
+    /// Sets a running flag, (e.g. the run_transcription flag), that is set to false when the
+    /// callback fails.
+    pub fn with_stop_flag(mut self, stop_flag: Arc<AtomicBool>) -> Self {
+        self.stop_flag = Some(stop_flag);
+        self
+    }
+}
+
+impl<CB, S> Callback for TryCallbackReporter<CB, S>
+where
+    CB: TryCallback,
+    S: OutputSink<CallbackError<CB::Error>>,
+{
+    type Argument = CB::Argument;
+    fn call(&mut self, arg: Self::Argument) {
+        let error = match catch_unwind(AssertUnwindSafe(|| self.callback.try_call(arg))) {
+            Ok(Ok(())) => return,
+            Ok(Err(e)) => CallbackError::Failed(e),
+            Err(payload) => CallbackError::Panicked(panic_message(payload.as_ref())),
+        };
+        if let Some(stop_flag) = self.stop_flag.as_ref() {
+            stop_flag.store(false, Ordering::Release);
+        }
+        let _ = self.error_sink.try_send(error);
+    }
+}
@@ -1,14 +1,54 @@
+use strum::IntoStaticStr;
 use thiserror::Error;
 
-#[derive(Debug, Error)]
+use crate::whisper::model::ModelId;
+
+/// Errors returned by Ribble-Whisper.
+/// Use [RibbleWhisperError::error_code] for a stable, machine-readable kind and
+/// [RibbleWhisperError::is_recoverable] to decide whether the operation is worth retrying.
+#[derive(Debug, Error, IntoStaticStr)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error), uniffi(flat_error))]
+#[strum(serialize_all = "snake_case")]
 pub enum RibbleWhisperError {
     /// Called on a data-channel failure
     /// Contains the output message, so it can be recovered.
     #[error("{0}")]
     TranscriptionSenderError(String),
-    /// User either supplied an incorrect parameter, or failed to supply a parameter at all
+    /// User supplied an incorrect parameter
     #[error("Parameter Error {0}")]
     ParameterError(String),
+    /// A required field was not supplied to a builder, (or to the configs it was given).
+    #[error("Missing field: {field} in {builder}")]
+    MissingField {
+        builder: &'static str,
+        field: &'static str,
+    },
+    /// The model could not be found by the model bank/retriever.
+    #[error("Failed to find model: {id}")]
+    ModelNotFound { id: ModelId },
+    /// The audio is missing a default track, or a property (e.g. sample rate, channels) required
+    /// to decode and resample it.
+    #[error("Audio format error: {reason}")]
+    AudioFormat { reason: String },
+    /// The model failed to initialize while GPU inference was requested.
+    /// It may be possible to recover by retrying on the CPU, (or on a different device).
+    #[error("GPU Error on device {device}: {reason}")]
+    Gpu {
+        device: std::ffi::c_int,
+        reason: String,
+    },
+    /// The operation was cancelled by the caller before it completed.
+    #[error("Cancelled")]
+    Cancelled,
+    /// A text replacement rule could not be parsed or compiled.
+    #[error("Invalid replacement rule: {rule}. Reason: {reason}")]
+    InvalidReplacementRule { rule: String, reason: String },
+    /// A subtitle file could not be parsed.
+    #[error("Failed to parse subtitles at line {line}. Reason: {reason}")]
+    SubtitleParseError { line: usize, reason: String },
+    /// A decoding grammar could not be parsed.
+    #[error("Failed to parse grammar at offset {offset}. Reason: {reason}")]
+    GrammarParseError { offset: usize, reason: String },
     /// [std::io::Error]
     #[error("I/O error: {0}")]
     IOError(#[from] std::io::Error),
@@ -25,8 +65,12 @@ pub enum RibbleWhisperError {
     #[error("UrlParse Error {0}")]
     UrlParseError(#[from] url::ParseError),
     /// [whisper_rs::WhisperError]
+    #[cfg(not(target_arch = "wasm32"))]
     #[error("Whisper Error {0}")]
     WhisperError(#[from] whisper_rs::WhisperError),
+    #[cfg(all(feature = "opus", not(target_arch = "wasm32")))]
+    #[error("Opus Error {0}")]
+    OpusError(#[from] opus::Error),
     #[error("Device Error {0}")]
     DeviceError(String),
     /// [rubato::ResampleError]
@@ -50,10 +94,57 @@ pub enum RibbleWhisperError {
     #[cfg(feature = "downloader")]
     #[error("Download Aborted: {0}")]
     DownloadAborted(String),
+    /// There is not enough free space at the download destination.
+    /// Sizes are in bytes; needed includes a safety margin for temporary files.
+    #[cfg(feature = "downloader")]
+    #[error("Insufficient disk space. Needed: {needed} bytes, Available: {available} bytes")]
+    InsufficientDiskSpace { needed: u64, available: u64 },
+    /// [zip::result::ZipError]
+    #[cfg(feature = "archive")]
+    #[error("Archive Error {0}")]
+    ArchiveError(#[from] zip::result::ZipError),
+    /// A file's digest does not match its expected checksum.
+    /// Most likely caused by a truncated or corrupted download.
+    #[cfg(feature = "integrity")]
+    #[error("Checksum mismatch for {file_name}. Expected: {expected}, Actual: {actual}")]
+    ChecksumMismatch {
+        file_name: String,
+        expected: String,
+        actual: String,
+    },
     /// [serde_json::Error]
     #[error("JSON Parse Error {0}")]
-    #[cfg(feature = "integrity")]
+    #[cfg(feature = "serde")]
     JsonParseError(#[from] serde_json::Error),
     #[error("ModelError")]
     ModelError(String),
 }
+
+impl RibbleWhisperError {
+    /// Returns a stable, snake_case identifier for the kind of error, (e.g. "model_not_found"),
+    /// suitable for branching on in applications and FFI consumers.
+    pub fn error_code(&self) -> &'static str {
+        self.into()
+    }
+
+    /// Returns true if the failure is transient or environmental, such that retrying the operation
+    /// (possibly after the user has intervened, e.g. reconnecting a device or freeing disk space)
+    /// may succeed. Errors caused by invalid input or configuration are not recoverable.
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            RibbleWhisperError::TranscriptionSenderError(_)
+            | RibbleWhisperError::IOError(_)
+            | RibbleWhisperError::DeviceError(_)
+            | RibbleWhisperError::Gpu { .. }
+            | RibbleWhisperError::Cancelled => true,
+            #[cfg(feature = "downloader")]
+            RibbleWhisperError::ReqwestError(_)
+            | RibbleWhisperError::DownloadError(_)
+            | RibbleWhisperError::DownloadAborted(_)
+            | RibbleWhisperError::InsufficientDiskSpace { .. } => true,
+            #[cfg(feature = "integrity")]
+            RibbleWhisperError::ChecksumMismatch { .. } => true,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Crate-internal diagnostics macros.
+//!
+//! Non-fatal errors are reported through `tracing` when the tracing feature is enabled, otherwise
+//! through `log` with ribble-logging. Spans and structured events are only emitted with tracing;
+//! without it, they compile down to nothing.
+
+/// A stand-in for [tracing::Span] when the tracing feature is disabled.
+#[cfg(not(feature = "tracing"))]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Span;
+
+#[cfg(not(feature = "tracing"))]
+impl Span {
+    pub(crate) fn entered(self) -> Self {
+        self
+    }
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) type Span = tracing::Span;
+
+/// Runs a future within a span, (entered spans cannot be held across an await).
+#[cfg(feature = "downloader-async")]
+pub(crate) trait InSpan: std::future::Future + Sized {
+    fn in_span(self, span: Span) -> impl std::future::Future<Output = Self::Output> {
+        #[cfg(feature = "tracing")]
+        {
+            tracing::Instrument::instrument(self, span)
+        }
+        #[cfg(not(feature = "tracing"))]
+        {
+            let _ = span;
+            self
+        }
+    }
+}
+
+#[cfg(feature = "downloader-async")]
+impl<F: std::future::Future> InSpan for F {}
+
+/// Reports a non-fatal error. This is silent if neither tracing nor ribble-logging is enabled.
+macro_rules! log_warn {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        {
+            tracing::warn!($($arg)+);
+        }
+        #[cfg(all(feature = "ribble-logging", not(feature = "tracing")))]
+        {
+            log::warn!($($arg)+);
+        }
+    }};
+}
+
+/// Reports a non-fatal error, falling back to stderr if neither tracing nor ribble-logging is
+/// enabled.
+macro_rules! log_warn_or_eprint {
+    ($($arg:tt)+) => {{
+        #[cfg(any(feature = "tracing", feature = "ribble-logging"))]
+        {
+            $crate::utils::logging::log_warn!($($arg)+);
+        }
+        #[cfg(not(any(feature = "tracing", feature = "ribble-logging")))]
+        {
+            eprintln!($($arg)+);
+        }
+    }};
+}
+
+/// Creates a span at the given level, (e.g. `trace_span!(DEBUG, "inference", samples = n)`).
+/// The span must be entered (or used to instrument a future) to take effect.
+/// NOTE: Fields are not evaluated when tracing is disabled.
+macro_rules! trace_span {
+    ($level:ident, $($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        {
+            tracing::span!(tracing::Level::$level, $($arg)+)
+        }
+        #[cfg(not(feature = "tracing"))]
+        {
+            $crate::utils::logging::Span
+        }
+    }};
+}
+
+/// Emits a structured event at the given level, (e.g. `trace_event!(TRACE, voice_detected, "vad")`).
+/// NOTE: Fields are not evaluated when tracing is disabled.
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        {
+            tracing::event!(tracing::Level::$level, $($arg)+);
+        }
+    }};
+}
+
+pub(crate) use {log_warn, log_warn_or_eprint, trace_event, trace_span};
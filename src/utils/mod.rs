@@ -1,5 +1,11 @@
 pub mod callback;
 pub mod errors;
+pub(crate) mod logging;
+pub mod progress;
+pub mod sink;
+pub mod telemetry;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod thread;
 
 /// Type alias to handle channel configurations
 #[cfg(not(feature = "crossbeam"))]
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::utils::callback::Callback;
+
+struct CompositeState<CB>
+where
+    CB: Callback<Argument = f64>,
+{
+    callback: CB,
+    weights: Vec<f64>,
+    fractions: Vec<f64>,
+    last_reported: Option<f64>,
+}
+
+impl<CB> CompositeState<CB>
+where
+    CB: Callback<Argument = f64>,
+{
+    fn overall(&self) -> f64 {
+        let total_weight: f64 = self.weights.iter().sum();
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+        let completed: f64 = self
+            .weights
+            .iter()
+            .zip(self.fractions.iter())
+            .map(|(weight, fraction)| weight * fraction)
+            .sum();
+        (completed / total_weight).clamp(0.0, 1.0)
+    }
+
+    fn update(&mut self, stage: usize, fraction: f64) {
+        self.fractions[stage] = if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        };
+        let overall = self.overall();
+        if self.last_reported != Some(overall) {
+            self.last_reported = Some(overall);
+            self.callback.call(overall);
+        }
+    }
+}
+
+/// Combines the progress of the stages of a multi-stage job, (e.g. download, load, transcribe),
+/// into a single normalized (0.0 - 1.0) progress stream.
+///
+/// Each stage is given a weight: its share of the overall progress. Weights are relative, so
+/// `with_stage(20.0).with_stage(10.0).with_stage(70.0)` reports download 0-20%, load 20-30% and
+/// transcribe 30-100%. Stages report through the [StageProgress] handles from
+/// [CompositeProgress::stage], which can be moved into the callbacks of each stage.
+///
+/// The callback is only called when the overall progress changes.
+pub struct CompositeProgress<CB>
+where
+    CB: Callback<Argument = f64>,
+{
+    state: Arc<Mutex<CompositeState<CB>>>,
+}
+
+impl<CB> CompositeProgress<CB>
+where
+    CB: Callback<Argument = f64>,
+{
+    /// # Arguments:
+    /// * callback: receives the overall progress, (0.0 - 1.0)
+    pub fn new(callback: CB) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CompositeState {
+                callback,
+                weights: vec![],
+                fractions: vec![],
+                last_reported: None,
+            })),
+        }
+    }
+
+    /// Adds a stage with the given relative weight. Negative weights are treated as 0.
+    /// Stages are indexed in the order they are added.
+    pub fn with_stage(self, weight: f64) -> Self {
+        {
+            let mut state = self.state.lock();
+            state.weights.push(weight.max(0.0));
+            state.fractions.push(0.0);
+        }
+        self
+    }
+
+    pub fn num_stages(&self) -> usize {
+        self.state.lock().weights.len()
+    }
+
+    /// Gets a handle for reporting a stage's progress. Returns None if there is no such stage.
+    pub fn stage(&self, index: usize) -> Option<StageProgress<CB>> {
+        (index < self.num_stages()).then(|| StageProgress {
+            index,
+            state: Arc::clone(&self.state),
+        })
+    }
+
+    /// Gets the overall progress, (0.0 - 1.0).
+    pub fn fraction(&self) -> f64 {
+        self.state.lock().overall()
+    }
+}
+
+/// Reports the progress of a single stage of a [CompositeProgress].
+/// As a [Callback], this accepts the fraction of the stage that has completed, (0.0 - 1.0).
+/// Use the helper methods to report from counts or percentages, e.g. from within a
+/// [crate::utils::callback::RibbleWhisperCallback] closure.
+pub struct StageProgress<CB>
+where
+    CB: Callback<Argument = f64>,
+{
+    index: usize,
+    state: Arc<Mutex<CompositeState<CB>>>,
+}
+
+impl<CB> StageProgress<CB>
+where
+    CB: Callback<Argument = f64>,
+{
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Sets the fraction of this stage that has completed, (clamped to 0.0 - 1.0).
+    pub fn set_fraction(&self, fraction: f64) {
+        self.state.lock().update(self.index, fraction);
+    }
+
+    /// Sets this stage's progress from a count, (e.g. bytes downloaded out of the total size).
+    /// A total of 0 is treated as indeterminate and does not update the progress.
+    pub fn set_count(&self, completed: usize, total: usize) {
+        if total > 0 {
+            self.set_fraction(completed as f64 / total as f64);
+        }
+    }
+
+    /// Sets this stage's progress from a percentage, (e.g. whisper's 0-100 progress callback).
+    pub fn set_percent(&self, percent: i32) {
+        self.set_fraction(percent as f64 / 100.0);
+    }
+
+    /// Marks this stage as complete.
+    pub fn finish(&self) {
+        self.set_fraction(1.0);
+    }
+}
+
+impl<CB> Clone for StageProgress<CB>
+where
+    CB: Callback<Argument = f64>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            index: self.index,
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl<CB> Callback for StageProgress<CB>
+where
+    CB: Callback<Argument = f64>,
+{
+    type Argument = f64;
+    fn call(&mut self, fraction: f64) {
+        self.set_fraction(fraction);
+    }
+}
@@ -0,0 +1,151 @@
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Returned when an [OutputSink] cannot accept a message. Contains the message so it can be
+/// recovered.
+pub enum OutputSinkError<T> {
+    /// The sink is at capacity (try_send only).
+    Full(T),
+    /// The receiving end has been dropped.
+    Disconnected(T),
+}
+
+impl<T> OutputSinkError<T> {
+    pub fn is_full(&self) -> bool {
+        matches!(self, OutputSinkError::Full(_))
+    }
+
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self, OutputSinkError::Disconnected(_))
+    }
+
+    /// Returns the message that could not be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            OutputSinkError::Full(msg) | OutputSinkError::Disconnected(msg) => msg,
+        }
+    }
+}
+
+impl<T> Debug for OutputSinkError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputSinkError::Full(_) => write!(f, "Full(..)"),
+            OutputSinkError::Disconnected(_) => write!(f, "Disconnected(..)"),
+        }
+    }
+}
+
+impl<T> Display for OutputSinkError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputSinkError::Full(_) => write!(f, "sending on a full channel"),
+            OutputSinkError::Disconnected(_) => write!(f, "sending on a disconnected channel"),
+        }
+    }
+}
+
+impl<T> Error for OutputSinkError<T> {}
+
+/// The sending half of a channel that transcribers deliver their outputs to.
+/// This is implemented for [crate::utils::Sender] (std or crossbeam), and for tokio and flume
+/// senders behind the tokio-channels/flume feature flags, so applications can receive outputs on
+/// whichever channel they already use.
+pub trait OutputSink<T> {
+    /// Sends a message without blocking.
+    fn try_send(&self, msg: T) -> Result<(), OutputSinkError<T>>;
+    /// Sends a message, blocking until there is capacity.
+    /// NOTE: For tokio senders, this must not be called from within an async runtime.
+    fn send(&self, msg: T) -> Result<(), OutputSinkError<T>>;
+    /// Closes this end of the channel. The receiver is disconnected once all senders are closed.
+    fn close(self)
+    where
+        Self: Sized,
+    {
+        drop(self)
+    }
+}
+
+impl<T> OutputSink<T> for std::sync::mpsc::SyncSender<T> {
+    fn try_send(&self, msg: T) -> Result<(), OutputSinkError<T>> {
+        std::sync::mpsc::SyncSender::try_send(self, msg).map_err(|e| match e {
+            std::sync::mpsc::TrySendError::Full(msg) => OutputSinkError::Full(msg),
+            std::sync::mpsc::TrySendError::Disconnected(msg) => OutputSinkError::Disconnected(msg),
+        })
+    }
+
+    fn send(&self, msg: T) -> Result<(), OutputSinkError<T>> {
+        std::sync::mpsc::SyncSender::send(self, msg).map_err(|e| OutputSinkError::Disconnected(e.0))
+    }
+}
+
+// Unbounded: try_send never reports Full.
+impl<T> OutputSink<T> for std::sync::mpsc::Sender<T> {
+    fn try_send(&self, msg: T) -> Result<(), OutputSinkError<T>> {
+        OutputSink::send(self, msg)
+    }
+
+    fn send(&self, msg: T) -> Result<(), OutputSinkError<T>> {
+        std::sync::mpsc::Sender::send(self, msg).map_err(|e| OutputSinkError::Disconnected(e.0))
+    }
+}
+
+#[cfg(feature = "crossbeam")]
+impl<T> OutputSink<T> for crossbeam::channel::Sender<T> {
+    fn try_send(&self, msg: T) -> Result<(), OutputSinkError<T>> {
+        crossbeam::channel::Sender::try_send(self, msg).map_err(|e| match e {
+            crossbeam::channel::TrySendError::Full(msg) => OutputSinkError::Full(msg),
+            crossbeam::channel::TrySendError::Disconnected(msg) => {
+                OutputSinkError::Disconnected(msg)
+            }
+        })
+    }
+
+    fn send(&self, msg: T) -> Result<(), OutputSinkError<T>> {
+        crossbeam::channel::Sender::send(self, msg).map_err(|e| OutputSinkError::Disconnected(e.0))
+    }
+}
+
+#[cfg(feature = "tokio-channels")]
+impl<T> OutputSink<T> for tokio::sync::mpsc::Sender<T> {
+    fn try_send(&self, msg: T) -> Result<(), OutputSinkError<T>> {
+        tokio::sync::mpsc::Sender::try_send(self, msg).map_err(|e| match e {
+            tokio::sync::mpsc::error::TrySendError::Full(msg) => OutputSinkError::Full(msg),
+            tokio::sync::mpsc::error::TrySendError::Closed(msg) => {
+                OutputSinkError::Disconnected(msg)
+            }
+        })
+    }
+
+    fn send(&self, msg: T) -> Result<(), OutputSinkError<T>> {
+        self.blocking_send(msg)
+            .map_err(|e| OutputSinkError::Disconnected(e.0))
+    }
+}
+
+// Unbounded: try_send never reports Full.
+#[cfg(feature = "tokio-channels")]
+impl<T> OutputSink<T> for tokio::sync::mpsc::UnboundedSender<T> {
+    fn try_send(&self, msg: T) -> Result<(), OutputSinkError<T>> {
+        OutputSink::send(self, msg)
+    }
+
+    fn send(&self, msg: T) -> Result<(), OutputSinkError<T>> {
+        tokio::sync::mpsc::UnboundedSender::send(self, msg)
+            .map_err(|e| OutputSinkError::Disconnected(e.0))
+    }
+}
+
+#[cfg(feature = "flume")]
+impl<T> OutputSink<T> for flume::Sender<T> {
+    fn try_send(&self, msg: T) -> Result<(), OutputSinkError<T>> {
+        flume::Sender::try_send(self, msg).map_err(|e| match e {
+            flume::TrySendError::Full(msg) => OutputSinkError::Full(msg),
+            flume::TrySendError::Disconnected(msg) => OutputSinkError::Disconnected(msg),
+        })
+    }
+
+    fn send(&self, msg: T) -> Result<(), OutputSinkError<T>> {
+        flume::Sender::send(self, msg).map_err(|e| OutputSinkError::Disconnected(e.0))
+    }
+}
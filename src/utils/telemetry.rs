@@ -0,0 +1,101 @@
+//! Transcription metrics, emitted through the [metrics](https://docs.rs/metrics) facade when the
+//! metrics feature is enabled. Install a recorder in the application, (e.g. metrics-exporter-prometheus),
+//! to collect them; without one, recording is a no-op.
+
+use std::time::Duration;
+
+#[cfg(feature = "metrics")]
+use crate::transcriber::WHISPER_SAMPLE_RATE;
+
+/// Counter: inference windows run, labelled by transcriber ("realtime" or "offline").
+pub const WINDOWS_PROCESSED: &str = "ribble_whisper_windows_processed_total";
+/// Counter: segments moved into the confirmed transcription.
+pub const SEGMENTS_CONFIRMED: &str = "ribble_whisper_segments_confirmed_total";
+/// Counter: outputs that could not be delivered to the output sink, labelled by kind
+/// ("snapshot", "control_phrase", "audio_event", "decode_diagnostics", "correction" or
+/// "timestamped_snapshot").
+pub const DROPPED_OUTPUTS: &str = "ribble_whisper_dropped_outputs_total";
+/// Counter: audio overwritten or dropped before it could be transcribed, labelled by source
+/// ("ring_buffer" or "recorder_channel").
+pub const BUFFER_OVERRUNS: &str = "ribble_whisper_buffer_overruns_total";
+/// Histogram: the time taken to run inference over a window, in seconds.
+pub const INFERENCE_LATENCY: &str = "ribble_whisper_inference_latency_seconds";
+/// Histogram: the real-time factor of each window, (inference time / audio duration).
+/// Values below 1.0 mean inference is keeping up with the audio.
+pub const REAL_TIME_FACTOR: &str = "ribble_whisper_real_time_factor";
+
+/// Registers units and descriptions for Ribble-Whisper's metrics with the installed recorder.
+/// Call this once after installing the recorder.
+#[cfg(feature = "metrics")]
+pub fn describe_metrics() {
+    use metrics::{describe_counter, describe_histogram, Unit};
+    describe_counter!(WINDOWS_PROCESSED, Unit::Count, "Inference windows run");
+    describe_counter!(SEGMENTS_CONFIRMED, Unit::Count, "Segments confirmed");
+    describe_counter!(
+        DROPPED_OUTPUTS,
+        Unit::Count,
+        "Outputs dropped by the output sink"
+    );
+    describe_counter!(
+        BUFFER_OVERRUNS,
+        Unit::Count,
+        "Audio overwritten or dropped before transcription"
+    );
+    describe_histogram!(
+        INFERENCE_LATENCY,
+        Unit::Seconds,
+        "Inference latency per window"
+    );
+    describe_histogram!(REAL_TIME_FACTOR, "Inference time divided by audio duration");
+}
+
+pub(crate) fn record_window(transcriber: &'static str, latency: Duration, n_samples: usize) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!(WINDOWS_PROCESSED, "transcriber" => transcriber).increment(1);
+        metrics::histogram!(INFERENCE_LATENCY, "transcriber" => transcriber)
+            .record(latency.as_secs_f64());
+        let audio_secs = n_samples as f64 / WHISPER_SAMPLE_RATE;
+        if audio_secs > 0.0 {
+            metrics::histogram!(REAL_TIME_FACTOR, "transcriber" => transcriber)
+                .record(latency.as_secs_f64() / audio_secs);
+        }
+    }
+    #[cfg(not(feature = "metrics"))]
+    {
+        let _ = (transcriber, latency, n_samples);
+    }
+}
+
+pub(crate) fn record_segments_confirmed(n_segments: usize) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!(SEGMENTS_CONFIRMED).increment(n_segments as u64);
+    }
+    #[cfg(not(feature = "metrics"))]
+    {
+        let _ = n_segments;
+    }
+}
+
+pub(crate) fn record_dropped_output(kind: &'static str) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!(DROPPED_OUTPUTS, "kind" => kind).increment(1);
+    }
+    #[cfg(not(feature = "metrics"))]
+    {
+        let _ = kind;
+    }
+}
+
+pub(crate) fn record_buffer_overrun(source: &'static str) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!(BUFFER_OVERRUNS, "source" => source).increment(1);
+    }
+    #[cfg(not(feature = "metrics"))]
+    {
+        let _ = source;
+    }
+}
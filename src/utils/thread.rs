@@ -0,0 +1,201 @@
+use std::io;
+use std::thread::JoinHandle;
+
+use crate::utils::errors::RibbleWhisperError;
+use crate::utils::logging::log_warn_or_eprint;
+use crate::whisper::configs::{QosClass, ThreadConfigs, ThreadPriority};
+
+/// Applies the scheduling hints to the calling thread.
+/// Returns Err with the first setting that could not be applied; the priority is applied first.
+pub fn apply_to_current_thread(configs: &ThreadConfigs) -> Result<(), RibbleWhisperError> {
+    let priority = match (configs.priority(), configs.qos_class()) {
+        (ThreadPriority::Default, Some(qos_class)) => qos_priority(qos_class),
+        (priority, _) => priority,
+    };
+    if priority != ThreadPriority::Default || configs.qos_class().is_some() {
+        platform::set_priority(priority, configs.qos_class())?;
+    }
+    let cores = configs.cpu_affinity();
+    if !cores.is_empty() {
+        platform::set_affinity(&cores)?;
+    }
+    Ok(())
+}
+
+/// Spawns a named thread that applies the scheduling hints before running f.
+/// The hints are best-effort: if they cannot be applied, a warning is logged and f still runs.
+pub fn spawn_with_configs<F, T>(
+    name: impl Into<String>,
+    configs: ThreadConfigs,
+    f: F,
+) -> Result<JoinHandle<T>, RibbleWhisperError>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let handle = std::thread::Builder::new()
+        .name(name.into())
+        .spawn(move || {
+            if let Err(e) = apply_to_current_thread(&configs) {
+                log_warn_or_eprint!("Failed to apply thread configs {configs:?}: {e}");
+            }
+            f()
+        })?;
+    Ok(handle)
+}
+
+fn qos_priority(qos_class: QosClass) -> ThreadPriority {
+    match qos_class {
+        QosClass::UserInteractive | QosClass::UserInitiated => ThreadPriority::High,
+        QosClass::Default => ThreadPriority::Default,
+        QosClass::Utility | QosClass::Background => ThreadPriority::Low,
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+fn unsupported(setting: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("Setting the thread {setting} is not supported on this platform."),
+    )
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod platform {
+    use std::io;
+
+    use super::{QosClass, ThreadPriority};
+
+    // Niceness is per-thread on Linux, so this only affects the calling thread.
+    pub(super) fn set_priority(priority: ThreadPriority, _: Option<QosClass>) -> io::Result<()> {
+        let nice = match priority {
+            ThreadPriority::Default => 0,
+            ThreadPriority::Low => 10,
+            ThreadPriority::High => -10,
+        };
+        // SAFETY: gettid has no preconditions, and setpriority only reads its arguments.
+        let result = unsafe {
+            let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+            libc::setpriority(libc::PRIO_PROCESS, tid, nice)
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    pub(super) fn set_affinity(cores: &[usize]) -> io::Result<()> {
+        // SAFETY: cpu_set_t is plain data, and the cores are all below 64, which it can hold.
+        let result = unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            for core in cores {
+                libc::CPU_SET(*core, &mut set);
+            }
+            libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set)
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+mod platform {
+    use std::io;
+
+    use super::{unsupported, QosClass, ThreadPriority};
+
+    // Apple platforms schedule by QoS class rather than priority.
+    pub(super) fn set_priority(
+        priority: ThreadPriority,
+        qos_class: Option<QosClass>,
+    ) -> io::Result<()> {
+        let qos_class = qos_class.unwrap_or(match priority {
+            ThreadPriority::Default => QosClass::Default,
+            ThreadPriority::Low => QosClass::Utility,
+            ThreadPriority::High => QosClass::UserInitiated,
+        });
+        let qos_class = match qos_class {
+            QosClass::UserInteractive => libc::qos_class_t::QOS_CLASS_USER_INTERACTIVE,
+            QosClass::UserInitiated => libc::qos_class_t::QOS_CLASS_USER_INITIATED,
+            QosClass::Default => libc::qos_class_t::QOS_CLASS_DEFAULT,
+            QosClass::Utility => libc::qos_class_t::QOS_CLASS_UTILITY,
+            QosClass::Background => libc::qos_class_t::QOS_CLASS_BACKGROUND,
+        };
+        // SAFETY: This only changes the calling thread's QoS class.
+        let result = unsafe { libc::pthread_set_qos_class_self_np(qos_class, 0) };
+        if result != 0 {
+            return Err(io::Error::from_raw_os_error(result));
+        }
+        Ok(())
+    }
+
+    pub(super) fn set_affinity(_: &[usize]) -> io::Result<()> {
+        Err(unsupported("CPU affinity"))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::c_void;
+    use std::io;
+
+    use super::{QosClass, ThreadPriority};
+
+    const THREAD_PRIORITY_BELOW_NORMAL: i32 = -1;
+    const THREAD_PRIORITY_NORMAL: i32 = 0;
+    const THREAD_PRIORITY_ABOVE_NORMAL: i32 = 1;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetCurrentThread() -> *mut c_void;
+        fn SetThreadPriority(thread: *mut c_void, priority: i32) -> i32;
+        fn SetThreadAffinityMask(thread: *mut c_void, mask: usize) -> usize;
+    }
+
+    pub(super) fn set_priority(priority: ThreadPriority, _: Option<QosClass>) -> io::Result<()> {
+        let priority = match priority {
+            ThreadPriority::Default => THREAD_PRIORITY_NORMAL,
+            ThreadPriority::Low => THREAD_PRIORITY_BELOW_NORMAL,
+            ThreadPriority::High => THREAD_PRIORITY_ABOVE_NORMAL,
+        };
+        // SAFETY: GetCurrentThread returns a pseudo-handle that is always valid for the caller.
+        if unsafe { SetThreadPriority(GetCurrentThread(), priority) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    pub(super) fn set_affinity(cores: &[usize]) -> io::Result<()> {
+        let mask = cores
+            .iter()
+            .filter(|core| **core < usize::BITS as usize)
+            .fold(0usize, |mask, core| mask | (1 << core));
+        // SAFETY: GetCurrentThread returns a pseudo-handle that is always valid for the caller.
+        if unsafe { SetThreadAffinityMask(GetCurrentThread(), mask) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    windows
+)))]
+mod platform {
+    use std::io;
+
+    use super::{unsupported, QosClass, ThreadPriority};
+
+    pub(super) fn set_priority(_: ThreadPriority, _: Option<QosClass>) -> io::Result<()> {
+        Err(unsupported("priority"))
+    }
+
+    pub(super) fn set_affinity(_: &[usize]) -> io::Result<()> {
+        Err(unsupported("CPU affinity"))
+    }
+}
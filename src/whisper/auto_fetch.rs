@@ -0,0 +1,186 @@
+use parking_lot::Mutex;
+
+use crate::downloader::downloaders::sync_download_request;
+use crate::downloader::manager::DownloadManager;
+use crate::downloader::SyncDownload;
+use crate::utils::callback::RibbleWhisperCallback;
+use crate::utils::errors::RibbleWhisperError;
+use crate::utils::logging::log_warn;
+use crate::utils::Sender;
+use crate::whisper::model::{ModelBank, ModelId, ModelLocation, ModelRetriever};
+
+/// Lifecycle events sent while an [AutoFetchModelBank] downloads a missing model.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModelFetchEvent {
+    /// A missing model has started downloading.
+    /// total_size is taken from the content-length header and is 1 if it is indeterminate.
+    Started {
+        model_id: ModelId,
+        total_size: usize,
+    },
+    /// The number of bytes downloaded so far.
+    Progress {
+        model_id: ModelId,
+        downloaded: usize,
+        total_size: usize,
+    },
+    /// The model has been downloaded (and verified, if it has an expected checksum).
+    Finished { model_id: ModelId },
+    /// The model could not be fetched. Contains the error message.
+    Failed { model_id: ModelId, error: String },
+}
+
+/// A [ModelBank] wrapper that transparently downloads missing models on retrieval, so that
+/// "first run" flows do not need to check for, download, and verify a model before transcribing.
+/// Download urls are resolved with [ModelBank::download_url].
+///
+/// NOTE: downloads are blocking; [ModelRetriever::retrieve_model] will block the calling thread
+/// until the model has been fetched. Transcribers retrieve their model on their own thread.
+pub struct AutoFetchModelBank<M: ModelBank> {
+    inner: M,
+    event_sender: Option<Sender<ModelFetchEvent>>,
+    download_manager: Option<DownloadManager>,
+    // Prevents concurrent retrievals from downloading the same model twice.
+    fetch_lock: Mutex<()>,
+}
+
+impl<M: ModelBank> AutoFetchModelBank<M> {
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            event_sender: None,
+            download_manager: None,
+            fetch_lock: Mutex::new(()),
+        }
+    }
+
+    /// Sets a channel to receive [ModelFetchEvent]s on.
+    /// Progress events are dropped if the channel is full, but Started/Finished/Failed events block
+    /// until there is space, so the receiver must be drained while fetching.
+    pub fn with_event_sender(mut self, sender: Sender<ModelFetchEvent>) -> Self {
+        self.event_sender = Some(sender);
+        self
+    }
+
+    /// Sets a [DownloadManager] to limit the number of concurrent downloads and their bandwidth.
+    /// Share the manager with other downloaders to keep background fetching from saturating the
+    /// connection.
+    pub fn with_download_manager(mut self, download_manager: DownloadManager) -> Self {
+        self.download_manager = Some(download_manager);
+        self
+    }
+
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut M {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> M {
+        self.inner
+    }
+
+    /// Downloads a model into the model directory if it is not already in storage.
+    /// # Returns:
+    /// * Ok(()) if the model is in storage
+    /// * Err(RibbleWhisperError) if the model is not in the bank, has no known download url, or
+    ///   the download fails.
+    pub fn fetch_model(&self, model_id: ModelId) -> Result<(), RibbleWhisperError> {
+        let _guard = self.fetch_lock.lock();
+        if self.inner.model_exists_in_storage(model_id)? {
+            return Ok(());
+        }
+
+        let result = self.download_model(model_id);
+        if let Err(e) = &result {
+            self.send_event(ModelFetchEvent::Failed {
+                model_id,
+                error: e.to_string(),
+            });
+        }
+        result
+    }
+
+    fn download_model(&self, model_id: ModelId) -> Result<(), RibbleWhisperError> {
+        let model = self
+            .inner
+            .get_model(model_id)
+            .ok_or(RibbleWhisperError::ModelNotFound { id: model_id })?;
+        let url = self
+            .inner
+            .download_url(model_id)
+            .ok_or(RibbleWhisperError::ParameterError(format!(
+                "No download url for model: {}",
+                model.name()
+            )))?;
+
+        // Wait for a free download slot before sending the request.
+        let _permit = self
+            .download_manager
+            .as_ref()
+            .map(|manager| manager.acquire());
+        let downloader = sync_download_request(url.as_str(), model.file_name())?;
+        let total_size = downloader.total_size();
+        self.send_event(ModelFetchEvent::Started {
+            model_id,
+            total_size,
+        });
+
+        let progress_sender = self.event_sender.clone();
+        let progress_callback = RibbleWhisperCallback::new(move |downloaded: usize| {
+            if let Some(sender) = progress_sender.as_ref() {
+                let _ = sender.try_send(ModelFetchEvent::Progress {
+                    model_id,
+                    downloaded,
+                    total_size,
+                });
+            }
+        });
+
+        #[cfg(feature = "integrity")]
+        let downloader = downloader.with_expected_checksum(model.expected_checksum().cloned());
+        let bandwidth_limiter = self
+            .download_manager
+            .as_ref()
+            .and_then(|manager| manager.bandwidth_limiter().cloned());
+        let mut downloader = downloader
+            .with_bandwidth_limiter(bandwidth_limiter)
+            .with_progress_callback(progress_callback);
+
+        let model_directory = self.inner.model_directory();
+        let downloaded_path = downloader.download(model_directory)?;
+
+        // The downloaded file is named after the response; make sure it lands where the bank
+        // expects to find it.
+        let expected_path = model_directory.join(model.file_name());
+        if downloaded_path != expected_path {
+            std::fs::rename(&downloaded_path, &expected_path)?;
+        }
+
+        self.send_event(ModelFetchEvent::Finished { model_id });
+        Ok(())
+    }
+
+    fn send_event(&self, event: ModelFetchEvent) {
+        if let Some(sender) = self.event_sender.as_ref() {
+            let _ = sender.send(event);
+        }
+    }
+}
+
+impl<M: ModelBank + ModelRetriever> ModelRetriever for AutoFetchModelBank<M> {
+    /// Retrieves the model, downloading it first if it is missing from storage.
+    /// Returns None if the model cannot be fetched; the reason is sent as a
+    /// [ModelFetchEvent::Failed] event.
+    fn retrieve_model(&self, model_id: ModelId) -> Option<ModelLocation> {
+        match self.fetch_model(model_id) {
+            Ok(()) => self.inner.retrieve_model(model_id),
+            Err(_e) => {
+                log_warn!("Failed to fetch model: {model_id}. Error: {_e}");
+                None
+            }
+        }
+    }
+}
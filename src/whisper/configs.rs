@@ -2,17 +2,35 @@ use std::hash::{DefaultHasher, Hash, Hasher};
 use std::num::NonZeroUsize;
 use std::str::FromStr;
 
-use crate::transcriber::WHISPER_SAMPLE_RATE;
+use crate::transcriber::decode_diagnostics::{COMPRESSION_RATIO_THRESHOLD, DecodeDiagnostics};
+use crate::transcriber::dedup::DedupConfigs;
+use crate::transcriber::{RibbleWhisperSegment, WHISPER_SAMPLE_RATE};
+use crate::utils::errors::RibbleWhisperError;
 use crate::whisper::model::{DefaultModelType, Model, ModelId};
 use strum::{AsRefStr, Display, EnumCount, EnumIter, EnumString, FromRepr, IntoStaticStr};
+#[cfg(not(target_arch = "wasm32"))]
 use whisper_rs;
 
 pub const MAX_PROMPT_TOKENS: usize = 16384;
 // Recommended 1Hr.
 pub const REALTIME_AUDIO_TIMEOUT: usize = std::time::Duration::new(3600, 0).as_millis() as usize;
+// 1 minute before the timeout, in ms.
+pub const TIMEOUT_WARNING_MS: usize = 60000;
 pub const VAD_SAMPLE_MS: usize = 300;
 // in ms
 pub const AUDIO_SAMPLE_MS: usize = 10000;
+// The shortest voice-detection window derived from a latency target, in ms.
+pub const MIN_VAD_SAMPLE_MS: usize = 100;
+// Whisper decodes at most 30 seconds of audio at once, in ms.
+pub const MAX_AUDIO_SAMPLE_MS: usize = 30000;
+// This is a little on the "safe" side, roughly around the amount of time it takes to say a word.
+// The string deduplication does a relatively decent job with catching these, so they will get
+// stamped out.
+pub const RETAIN_MS: usize = 500;
+// whisper.cpp's default.
+pub const DEFAULT_GRAMMAR_PENALTY: f32 = 100.0;
+// whisper.cpp's default temperature fallback step.
+pub const RETRY_TEMPERATURE_INCREMENT: f32 = 0.2;
 
 /// Versioned wrapper for supported whisper-realtime configuration types.
 /// Can be serialized (using serde or otherwise) to persist settings.
@@ -95,6 +113,12 @@ pub struct WhisperConfigs {
     use_gpu: bool,
     /// Use flash attention
     flash_attention: bool,
+    /// The index of the gpu device to run on when using the gpu.
+    gpu_device: std::ffi::c_int,
+    /// Detect speaker turns with tinydiarize. Requires a tdrz model, (e.g. small.en-tdrz).
+    tinydiarize: bool,
+    /// How strongly a decoding grammar is enforced, (see: [crate::whisper::grammar]).
+    grammar_penalty: f32,
 }
 
 impl WhisperConfigs {
@@ -107,8 +131,11 @@ impl WhisperConfigs {
             language: None,
             use_gpu: false,
             flash_attention: false,
+            gpu_device: 0,
             use_no_context: false,
             model_id: None,
+            tinydiarize: false,
+            grammar_penalty: DEFAULT_GRAMMAR_PENALTY,
         }
     }
 
@@ -157,6 +184,14 @@ impl WhisperConfigs {
         self
     }
 
+    /// Sets the gpu device to run on in multi-gpu systems. Defaults to 0.
+    /// NOTE: CoreML and OpenVINO encoders are picked up automatically by whisper.cpp when their
+    /// companion files are placed next to the model. See: [crate::whisper::model::EncoderCompanion]
+    pub fn with_gpu_device(mut self, gpu_device: std::ffi::c_int) -> Self {
+        self.gpu_device = gpu_device.max(0);
+        self
+    }
+
     /// Sets the sampling strategy.
     pub fn with_sampling_strategy(mut self, sampling_strategy: WhisperSamplingStrategy) -> Self {
         self.sampling_strategy = sampling_strategy;
@@ -169,6 +204,22 @@ impl WhisperConfigs {
         self
     }
 
+    /// Toggles detecting speaker turns with tinydiarize, which labels each segment with a
+    /// [SpeakerId](crate::transcriber::speaker::SpeakerId). This requires a tinydiarize model,
+    /// (e.g. small.en-tdrz); other models ignore it.
+    pub fn with_tinydiarize(mut self, tinydiarize: bool) -> Self {
+        self.tinydiarize = tinydiarize;
+        self
+    }
+
+    /// Sets how strongly a decoding grammar is enforced: the amount subtracted from the logits of
+    /// tokens that do not match it. Higher values constrain the output more strictly. This has no
+    /// effect unless a [Grammar](crate::whisper::grammar::Grammar) is set on the transcriber.
+    pub fn with_grammar_penalty(mut self, grammar_penalty: f32) -> Self {
+        self.grammar_penalty = grammar_penalty.max(0.0);
+        self
+    }
+
     /// Gets the number of threads used in transcription.
     pub fn n_threads(&self) -> usize {
         self.n_threads as usize
@@ -188,6 +239,11 @@ impl WhisperConfigs {
         &self.language
     }
 
+    /// Gets the whisper sampling strategy.
+    pub fn sampling_strategy(&self) -> WhisperSamplingStrategy {
+        self.sampling_strategy
+    }
+
     /// Indicates whether the gpu should be used.
     pub fn using_gpu(&self) -> bool {
         self.use_gpu
@@ -201,6 +257,18 @@ impl WhisperConfigs {
     pub fn using_flash_attention(&self) -> bool {
         self.flash_attention
     }
+    /// Gets the gpu device index
+    pub fn gpu_device(&self) -> std::ffi::c_int {
+        self.gpu_device
+    }
+    /// Indicates whether speaker turns are detected with tinydiarize.
+    pub fn using_tinydiarize(&self) -> bool {
+        self.tinydiarize
+    }
+    /// Gets how strongly a decoding grammar is enforced.
+    pub fn grammar_penalty(&self) -> f32 {
+        self.grammar_penalty
+    }
 
     /// Borrows the handle to a retrievable model.
     pub fn model_id(&self) -> &Option<ModelId> {
@@ -216,6 +284,7 @@ impl WhisperConfigs {
     /// Note: these configurations do not cover FullParams in entirety
     /// Features are exposed on an as-needed bases.
     /// See: [whisper_rs::FullParams] for documentation.
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn as_whisper_full_params(&'_ self) -> whisper_rs::FullParams<'_, '_> {
         let mut params = match self.sampling_strategy {
             WhisperSamplingStrategy::Greedy { best_of } => {
@@ -239,6 +308,7 @@ impl WhisperConfigs {
             params.set_language(Some(lang.into()))
         }
         params.set_no_context(self.use_no_context);
+        params.set_tdrz_enable(self.tinydiarize);
         // Explicitly disable printing to stdout
         // These are considered to be unnecessary features and are unlikely to be exposed.
         // Progress can be obtained via the callback API
@@ -251,10 +321,12 @@ impl WhisperConfigs {
     }
 
     /// Constructs a WhisperContextParameters object used to build [whisper_rs::WhisperContext]
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn as_whisper_context_params(&'_ self) -> whisper_rs::WhisperContextParameters<'_> {
         let mut params = whisper_rs::WhisperContextParameters::default();
         params.use_gpu(self.use_gpu);
         params.flash_attn(self.flash_attention);
+        params.gpu_device(self.gpu_device);
         params
     }
 }
@@ -290,7 +362,7 @@ pub enum WhisperSamplingStrategy {
 /// performance cost, but it will increase streaming latency. Lower-end hardware is the most likely
 /// to benefit from using buffered input.
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum RealtimeBufferingStrategy {
     #[default]
     Continuous,
@@ -313,6 +385,484 @@ impl RealtimeBufferingStrategy {
             }
         }
     }
+
+    /// The minimum amount of audio required to run the decode pass, in milliseconds.
+    pub fn min_sample_ms(&self) -> usize {
+        match self {
+            RealtimeBufferingStrategy::Continuous => 1000,
+            RealtimeBufferingStrategy::Buffered { buffer_ms } => (*buffer_ms).max(1000),
+        }
+    }
+}
+
+/// A coherent set of realtime window lengths: how much audio the voice activity detector checks
+/// at a time, (vad_sample_len), how much audio each inference window reads, (audio_sample_len),
+/// and how much audio must be buffered before inference runs, (the buffering strategy, see:
+/// [RealtimeBufferingStrategy::min_sample_len]).
+///
+/// The lengths depend on each other: a decode pass never runs if its window is shorter than the
+/// minimum it waits for, and voice detection over more audio than an inference window reads
+/// lets stale speech keep the transcriber running. Deriving them together from a latency target
+/// keeps them consistent; [WindowLengths::validate] checks any set of lengths.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WindowLengths {
+    vad_sample_len: usize,
+    audio_sample_len: usize,
+    buffering_strategy: RealtimeBufferingStrategy,
+}
+
+impl WindowLengths {
+    /// Derives window lengths from a latency target: roughly how long, (in milliseconds), after
+    /// speech starts the first text should arrive, (not counting inference time).
+    /// * Inference waits for the target, (at least 1 second, the least whisper can decode, and at
+    ///   most 30 seconds).
+    /// * Voice is checked every quarter of the target, (100 - 1000 ms).
+    /// * Inference windows read 8 times the target, (at least twice the buffered audio, and at
+    ///   most 30 seconds); longer windows give whisper more context but take longer to decode.
+    pub fn from_latency_target(latency_ms: usize) -> Self {
+        let buffering_strategy = match latency_ms {
+            0..=1000 => RealtimeBufferingStrategy::Continuous,
+            buffer_ms => RealtimeBufferingStrategy::Buffered {
+                buffer_ms: buffer_ms.min(MAX_AUDIO_SAMPLE_MS),
+            },
+        };
+        let min_sample_ms = buffering_strategy.min_sample_ms();
+        Self {
+            vad_sample_len: (latency_ms / 4).clamp(MIN_VAD_SAMPLE_MS, 1000),
+            audio_sample_len: latency_ms
+                .saturating_mul(8)
+                .max(min_sample_ms.saturating_mul(2))
+                .min(MAX_AUDIO_SAMPLE_MS),
+            buffering_strategy,
+        }
+    }
+
+    /// Overrides the voice-detection window length, (in ms).
+    pub fn with_vad_sample_len(mut self, len_ms: usize) -> Self {
+        self.vad_sample_len = len_ms;
+        self
+    }
+
+    /// Overrides the inference window length, (in ms).
+    pub fn with_audio_sample_len(mut self, len_ms: usize) -> Self {
+        self.audio_sample_len = len_ms;
+        self
+    }
+
+    /// Overrides the buffering strategy.
+    pub fn with_buffering_strategy(
+        mut self,
+        buffering_strategy: RealtimeBufferingStrategy,
+    ) -> Self {
+        self.buffering_strategy = buffering_strategy;
+        self
+    }
+
+    pub fn vad_sample_len(&self) -> usize {
+        self.vad_sample_len
+    }
+
+    pub fn audio_sample_len(&self) -> usize {
+        self.audio_sample_len
+    }
+
+    pub fn buffering_strategy(&self) -> RealtimeBufferingStrategy {
+        self.buffering_strategy
+    }
+
+    /// Returns Err, describing the problem, if the lengths are inconsistent:
+    /// * the voice-detection window is empty or longer than the inference window,
+    /// * the inference window is shorter than the minimum buffered audio, (so inference would
+    ///   never run), or
+    /// * the inference window is longer than the 30 seconds whisper can decode at once.
+    pub fn validate(&self) -> Result<(), RibbleWhisperError> {
+        let min_sample_ms = self.buffering_strategy.min_sample_ms();
+        if self.vad_sample_len == 0 {
+            return Err(RibbleWhisperError::ParameterError(
+                "The voice-detection window (vad_sample_len) cannot be empty.".to_string(),
+            ));
+        }
+        if self.vad_sample_len > self.audio_sample_len {
+            return Err(RibbleWhisperError::ParameterError(format!(
+                "The voice-detection window ({} ms) is longer than the inference window ({} ms).",
+                self.vad_sample_len, self.audio_sample_len
+            )));
+        }
+        if self.audio_sample_len < min_sample_ms {
+            return Err(RibbleWhisperError::ParameterError(format!(
+                "The inference window ({} ms) is shorter than the minimum buffered audio ({} ms), \
+                so inference would never run.",
+                self.audio_sample_len, min_sample_ms
+            )));
+        }
+        if self.audio_sample_len > MAX_AUDIO_SAMPLE_MS {
+            return Err(RibbleWhisperError::ParameterError(format!(
+                "The inference window ({} ms) is longer than whisper can decode at once ({} ms).",
+                self.audio_sample_len, MAX_AUDIO_SAMPLE_MS
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Represents how the realtime transcriber sends transcription updates. Set to full to send every
+/// update as a complete [crate::transcriber::TranscriptionSnapshot]. Set to delta to send
+/// [crate::transcriber::SnapshotDelta]s against the previous update instead, which reduces the
+/// bandwidth of high-frequency updates over IPC/WebSocket.
+///
+/// In delta mode, a full snapshot is still sent first, whenever an update cannot be expressed
+/// as a delta, (i.e. deduplication revised the confirmed text), after any update fails to send,
+/// and every keyframe_interval updates, (set to 0 to disable periodic keyframes). Use a
+/// [crate::transcriber::SnapshotAssembler] to rebuild full snapshots on the receiving end.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SnapshotEncoding {
+    #[default]
+    Full,
+    Delta {
+        keyframe_interval: usize,
+    },
+}
+
+/// What the realtime transcriber does after a prolonged silence, (measured in milliseconds since
+/// voice was last detected), so that an unattended session doesn't keep running indefinitely.
+/// Set to stop to end the session, (as if stopped by the user), with a
+/// [crate::transcriber::WhisperControlPhrase::IdleTimeout]. Set to pause to poll for voice at a
+/// reduced rate until speech resumes; this is signalled with a
+/// [crate::transcriber::WhisperControlPhrase::IdlePaused] and a
+/// [crate::transcriber::WhisperControlPhrase::StartSpeaking] on resuming. Time spent paused is
+/// not counted toward the realtime timeout.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum IdlePolicy {
+    #[default]
+    Disabled,
+    Stop {
+        after_ms: usize,
+    },
+    Pause {
+        after_ms: usize,
+    },
+}
+
+impl IdlePolicy {
+    /// The length of silence that triggers the policy, or None if disabled.
+    pub fn idle_timeout(&self) -> Option<std::time::Duration> {
+        match self {
+            IdlePolicy::Disabled => None,
+            IdlePolicy::Stop { after_ms } | IdlePolicy::Pause { after_ms } => {
+                Some(std::time::Duration::from_millis(*after_ms as u64))
+            }
+        }
+    }
+}
+
+/// When the realtime transcriber promotes segments from the working set, (text that may still be
+/// revised by later inference windows), to confirmed text.
+///
+/// By default, segments are confirmed when the speaker pauses or the audio buffer fills up, which
+/// rarely commits words that later get revised, but can leave long stretches of speech
+/// unconfirmed. The other policies confirm settled segments mid-utterance, trading a higher risk
+/// of committing a mis-transcription for lower latency of final text. Segments are always
+/// confirmed in order: a segment is only promoted once every segment before it has been.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum CommitPolicy {
+    /// Confirm on pauses and buffer rollovers only.
+    #[default]
+    OnPause,
+    /// Confirm a segment once at least n_segments, (minimum 1), follow it in the window,
+    /// (e.g. [N_SEGMENTS_DIFF](crate::transcriber::realtime_transcriber::N_SEGMENTS_DIFF)).
+    SegmentCount { n_segments: usize },
+    /// Confirm a segment once it ended at least after_ms before the end of the window's audio.
+    Time { after_ms: usize },
+    /// Confirm a segment once it is followed by another, if whisper's mean token probability for
+    /// it is at least min_confidence, (0.0 - 1.0).
+    Confidence { min_confidence: f32 },
+}
+
+impl CommitPolicy {
+    /// The number of leading segments of an inference window that are ready to be confirmed.
+    /// window_end is the length of the window's audio, in centiseconds, (like segment
+    /// timestamps).
+    pub fn n_ready(&self, segments: &[RibbleWhisperSegment], window_end: i64) -> usize {
+        match *self {
+            CommitPolicy::OnPause => 0,
+            CommitPolicy::SegmentCount { n_segments } => {
+                segments.len().saturating_sub(n_segments.max(1))
+            }
+            CommitPolicy::Time { after_ms } => segments
+                .iter()
+                .take_while(|segment| segment.end_time + (after_ms / 10) as i64 <= window_end)
+                .count(),
+            CommitPolicy::Confidence { min_confidence } => segments
+                .iter()
+                .take(segments.len().saturating_sub(1))
+                .take_while(|segment| {
+                    segment
+                        .confidence
+                        .is_some_and(|confidence| confidence >= min_confidence)
+                })
+                .count(),
+        }
+    }
+}
+
+/// What the realtime transcriber does when the working set, (text that may still be revised),
+/// grows past its bound of
+/// [WORKING_SET_SIZE](crate::transcriber::realtime_transcriber::WORKING_SET_SIZE) segments.
+/// This only happens during long stretches of speech without a pause or buffer rollover, and is
+/// signalled with a [crate::transcriber::WhisperControlPhrase::WorkingSetOverflow].
+///
+/// Overflowing segments are usually settled, but confirming them early is a known source of
+/// committed mistakes; choose the policy by which mistake is worse for the application.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Confirm the oldest segments.
+    #[default]
+    ConfirmOldest,
+    /// Drop the oldest segments without confirming them. They are lost from the transcription.
+    DropOldest,
+    /// Let the working set grow to max_segments, (at least
+    /// [WORKING_SET_SIZE](crate::transcriber::realtime_transcriber::WORKING_SET_SIZE)), then
+    /// confirm the oldest segments.
+    Grow { max_segments: usize },
+}
+
+/// When the realtime transcriber clears its audio buffer.
+///
+/// By default, the buffer is cleared when it fills up, (a rollover), keeping the last
+/// rollover_retain_ms of audio so that the next window can be merged with the last, and on
+/// pauses, once the working set has been confirmed. Clearing keeps inference windows short, but
+/// words cut by a rollover are sometimes chopped: retaining more audio gives the merge more
+/// overlap to work with, at the cost of re-transcribing it.
+///
+/// Set to slide only to never clear the buffer: each window is the latest audio_sample_len of
+/// audio, (see: [WindowLengths]), sliding over the stream, and segments are confirmed once they
+/// reach the older half of a full window, (as well as by the [CommitPolicy] and on pauses).
+/// This avoids cutting words at all, but keeps inference windows at their full length, (and the
+/// ring buffer counts the audio it slides past as overruns).
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BufferClearPolicy {
+    Clear {
+        rollover_retain_ms: usize,
+        clear_on_pause: bool,
+    },
+    SlideOnly,
+}
+
+impl Default for BufferClearPolicy {
+    fn default() -> Self {
+        BufferClearPolicy::Clear {
+            rollover_retain_ms: RETAIN_MS,
+            clear_on_pause: true,
+        }
+    }
+}
+
+impl BufferClearPolicy {
+    /// The audio kept when the buffer fills up, (in ms), or None if it is never cleared.
+    pub fn rollover_retain_ms(&self) -> Option<usize> {
+        match self {
+            BufferClearPolicy::Clear {
+                rollover_retain_ms, ..
+            } => Some(*rollover_retain_ms),
+            BufferClearPolicy::SlideOnly => None,
+        }
+    }
+
+    /// Whether the buffer is cleared on pauses.
+    pub fn clear_on_pause(&self) -> bool {
+        match self {
+            BufferClearPolicy::Clear { clear_on_pause, .. } => *clear_on_pause,
+            BufferClearPolicy::SlideOnly => false,
+        }
+    }
+}
+
+/// When the realtime transcriber re-runs an inference window whose output looks broken, (see:
+/// [RetryPolicy::should_retry]), rather than accepting whatever the first decode produced.
+/// Each retry runs at a higher temperature, (and optionally without the previous text as a
+/// prompt, which is where repetition loops tend to be seeded from). Retries stop at the first
+/// attempt that passes; if none do, the last attempt is kept.
+///
+/// Retries run on the inference thread and add their full cost to the window's latency.
+/// Disabled, (0 retries), by default.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    max_retries: usize,
+    temperature_increment: f32,
+    disable_context: bool,
+    compression_ratio_threshold: f32,
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self {
+            max_retries: 0,
+            temperature_increment: RETRY_TEMPERATURE_INCREMENT,
+            disable_context: true,
+            compression_ratio_threshold: COMPRESSION_RATIO_THRESHOLD,
+        }
+    }
+
+    /// Sets the maximum number of times a window is re-run. Set to 0 to disable retries.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets how much the temperature rises with each retry, (clamped to 0.0 - 1.0).
+    /// Defaults to 0.2.
+    pub fn with_temperature_increment(mut self, temperature_increment: f32) -> Self {
+        self.temperature_increment = temperature_increment.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Toggles re-running windows without the previous text as a prompt. Defaults to true.
+    pub fn with_disable_context(mut self, disable_context: bool) -> Self {
+        self.disable_context = disable_context;
+        self
+    }
+
+    /// Sets the compression ratio above which a window is retried, (see:
+    /// [crate::transcriber::decode_diagnostics::compression_ratio]).
+    pub fn with_compression_ratio_threshold(mut self, compression_ratio_threshold: f32) -> Self {
+        self.compression_ratio_threshold = compression_ratio_threshold;
+        self
+    }
+
+    pub fn max_retries(&self) -> usize {
+        self.max_retries
+    }
+
+    pub fn temperature_increment(&self) -> f32 {
+        self.temperature_increment
+    }
+
+    pub fn disable_context(&self) -> bool {
+        self.disable_context
+    }
+
+    pub fn compression_ratio_threshold(&self) -> f32 {
+        self.compression_ratio_threshold
+    }
+
+    /// Returns true if retries are enabled.
+    pub fn enabled(&self) -> bool {
+        self.max_retries > 0
+    }
+
+    /// Returns true if a window's output looks broken: its text compresses above the threshold,
+    /// or its tokens fail whisper.cpp's entropy check, (see: [DecodeDiagnostics::repetitive]).
+    pub fn should_retry(&self, diagnostics: &DecodeDiagnostics) -> bool {
+        diagnostics.compression_ratio > self.compression_ratio_threshold || diagnostics.repetitive()
+    }
+
+    /// The sampling temperature of a retry, (counted from 1).
+    pub fn temperature(&self, retry: usize) -> f32 {
+        (self.temperature_increment * retry as f32).min(1.0)
+    }
+
+    /// Whether a retry, (counted from 1), prompts the decoder with the previous text, given
+    /// whether the original attempt did.
+    pub fn use_context(&self, use_context: bool, retry: usize) -> bool {
+        use_context && (retry == 0 || !self.disable_context)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How the realtime transcriber's inference thread should be scheduled relative to other threads,
+/// (see: [ThreadConfigs]).
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ThreadPriority {
+    /// Leave the priority as inherited from the spawning thread.
+    #[default]
+    Default,
+    /// Yield to other threads, (e.g. a UI), when cores are contended.
+    Low,
+    /// Prefer the inference thread when cores are contended.
+    /// On Linux this usually requires CAP_SYS_NICE or a raised RLIMIT_NICE.
+    High,
+}
+
+/// macOS/iOS quality-of-service classes, from most to least urgent. On other platforms, these map
+/// to the closest [ThreadPriority] when no priority is set.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum QosClass {
+    UserInteractive,
+    UserInitiated,
+    Default,
+    Utility,
+    Background,
+}
+
+/// Scheduling hints for the realtime inference thread, applied when the transcriber is started
+/// with [crate::transcriber::realtime_transcriber::RealtimeTranscriber::spawn].
+/// These are best-effort: settings that the platform does not support, or that the process lacks
+/// permission for, are logged and skipped. whisper.cpp's worker threads are spawned from the
+/// inference thread and inherit its priority and affinity on Linux.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ThreadConfigs {
+    priority: ThreadPriority,
+    qos_class: Option<QosClass>,
+    // Bit i is set if the thread may run on core i; 0 leaves the affinity unchanged.
+    cpu_affinity: u64,
+}
+
+impl ThreadConfigs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_priority(mut self, priority: ThreadPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets the QoS class on macOS/iOS, which takes precedence over the priority there.
+    pub fn with_qos_class(mut self, qos_class: QosClass) -> Self {
+        self.qos_class = Some(qos_class);
+        self
+    }
+
+    /// Restricts the thread to the given cores, (by index, up to 63). Unsupported on Apple
+    /// platforms, which do not expose CPU affinity.
+    pub fn with_cpu_affinity(mut self, cores: &[usize]) -> Self {
+        self.cpu_affinity = cores
+            .iter()
+            .filter(|core| **core < u64::BITS as usize)
+            .fold(0, |mask, core| mask | (1 << core));
+        self
+    }
+
+    pub fn priority(&self) -> ThreadPriority {
+        self.priority
+    }
+
+    pub fn qos_class(&self) -> Option<QosClass> {
+        self.qos_class
+    }
+
+    /// The cores the thread is restricted to, or an empty Vec if unrestricted.
+    pub fn cpu_affinity(&self) -> Vec<usize> {
+        (0..u64::BITS as usize)
+            .filter(|core| self.cpu_affinity & (1 << core) != 0)
+            .collect()
+    }
 }
 
 /// Encapsulates relevant configurations for tweaking realtime transcription.
@@ -322,25 +872,106 @@ impl RealtimeBufferingStrategy {
 #[derive(Copy, Clone, Debug)]
 pub struct RealtimeConfigs {
     realtime_timeout: usize,
+    timeout_warning: usize,
+    idle_policy: IdlePolicy,
+    decode_diagnostics: bool,
+    timestamped_snapshots: bool,
+    retry_policy: RetryPolicy,
+    commit_policy: CommitPolicy,
+    overflow_policy: OverflowPolicy,
+    buffer_clear_policy: BufferClearPolicy,
+    correction_horizon: usize,
     audio_sample_len: usize,
     vad_sample_len: usize,
     buffering_strategy: RealtimeBufferingStrategy,
+    snapshot_encoding: SnapshotEncoding,
+    thread_configs: ThreadConfigs,
+    dedup_configs: DedupConfigs,
 }
 
 impl RealtimeConfigs {
     pub fn new() -> Self {
         Self {
             realtime_timeout: 0,
+            timeout_warning: 0,
+            idle_policy: IdlePolicy::Disabled,
+            decode_diagnostics: false,
+            timestamped_snapshots: false,
+            retry_policy: RetryPolicy::new(),
+            commit_policy: CommitPolicy::OnPause,
+            overflow_policy: OverflowPolicy::ConfirmOldest,
+            buffer_clear_policy: BufferClearPolicy::default(),
+            correction_horizon: 0,
             audio_sample_len: 0,
             vad_sample_len: 0,
             buffering_strategy: RealtimeBufferingStrategy::Continuous,
+            snapshot_encoding: SnapshotEncoding::Full,
+            thread_configs: ThreadConfigs::new(),
+            dedup_configs: DedupConfigs::new(),
         }
     }
     /// Sets the realtime timeout. Set to 0 for "Infinite"
+    /// Time spent paused by [IdlePolicy::Pause] is not counted toward it.
     pub fn with_realtime_timeout(mut self, realtime_timeout: usize) -> Self {
         self.realtime_timeout = realtime_timeout;
         self
     }
+    /// Sets how long before the realtime timeout a
+    /// [crate::transcriber::WhisperControlPhrase::TimeoutApproaching] warning is sent.
+    /// Set to 0 to disable the warning. Defaults to 1 minute (60 000 ms).
+    pub fn with_timeout_warning(mut self, lead_ms: usize) -> Self {
+        self.timeout_warning = lead_ms;
+        self
+    }
+    /// Sets what happens after a prolonged silence, (see: [IdlePolicy]). Defaults to disabled.
+    pub fn with_idle_policy(mut self, idle_policy: IdlePolicy) -> Self {
+        self.idle_policy = idle_policy;
+        self
+    }
+    /// Toggles sending [crate::transcriber::WhisperOutput::DecodeDiagnostics] after each
+    /// inference window. Defaults to false.
+    pub fn with_decode_diagnostics(mut self, decode_diagnostics: bool) -> Self {
+        self.decode_diagnostics = decode_diagnostics;
+        self
+    }
+    /// Toggles sending [crate::transcriber::WhisperOutput::TimestampedSnapshot] after each
+    /// inference window. Defaults to false.
+    pub fn with_timestamped_snapshots(mut self, timestamped_snapshots: bool) -> Self {
+        self.timestamped_snapshots = timestamped_snapshots;
+        self
+    }
+    /// Sets when inference windows with broken-looking output are re-run, (see: [RetryPolicy]).
+    /// Defaults to disabled.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+    /// Sets when working-set segments are confirmed, (see: [CommitPolicy]). Defaults to on
+    /// pauses.
+    pub fn with_commit_policy(mut self, commit_policy: CommitPolicy) -> Self {
+        self.commit_policy = commit_policy;
+        self
+    }
+    /// Sets what happens when the working set overflows, (see: [OverflowPolicy]). Defaults to
+    /// confirming the oldest segments.
+    pub fn with_overflow_policy(mut self, overflow_policy: OverflowPolicy) -> Self {
+        self.overflow_policy = overflow_policy;
+        self
+    }
+    /// Sets when the audio buffer is cleared, (see: [BufferClearPolicy]). Defaults to clearing
+    /// on rollovers, (retaining [RETAIN_MS]), and pauses.
+    pub fn with_buffer_clear_policy(mut self, buffer_clear_policy: BufferClearPolicy) -> Self {
+        self.buffer_clear_policy = buffer_clear_policy;
+        self
+    }
+    /// Sets how far back, (in bytes from the end of the confirmed text), a revision of confirmed
+    /// text can reach and still be sent as a [crate::transcriber::WhisperOutput::Correction].
+    /// Revisions reaching further back still appear in snapshots. Set to 0 to disable corrections.
+    /// Defaults to 0.
+    pub fn with_correction_horizon(mut self, horizon: usize) -> Self {
+        self.correction_horizon = horizon;
+        self
+    }
     /// Sets the size of the audio sampling window. Defaults to 10 seconds (10 000 ms).
     pub fn with_audio_sample_len(mut self, len_ms: usize) -> Self {
         self.audio_sample_len = len_ms;
@@ -360,10 +991,73 @@ impl RealtimeConfigs {
         self
     }
 
+    /// Sets how transcription updates are sent. Defaults to full snapshots.
+    pub fn with_snapshot_encoding(mut self, snapshot_encoding: SnapshotEncoding) -> Self {
+        self.snapshot_encoding = snapshot_encoding;
+        self
+    }
+
+    /// Sets how the inference thread is scheduled. Defaults to inheriting the spawning thread's.
+    pub fn with_thread_configs(mut self, thread_configs: ThreadConfigs) -> Self {
+        self.thread_configs = thread_configs;
+        self
+    }
+
+    /// Sets how overlapping text is deduplicated between inference windows.
+    pub fn with_dedup_configs(mut self, dedup_configs: DedupConfigs) -> Self {
+        self.dedup_configs = dedup_configs;
+        self
+    }
+
+    /// Sets the voice-detection window, inference window and buffering strategy together, (see:
+    /// [WindowLengths]).
+    pub fn with_window_lengths(mut self, window_lengths: WindowLengths) -> Self {
+        self.vad_sample_len = window_lengths.vad_sample_len;
+        self.audio_sample_len = window_lengths.audio_sample_len;
+        self.buffering_strategy = window_lengths.buffering_strategy;
+        self
+    }
+
     /// Gets the realtime timeout.
     pub fn realtime_timeout(&self) -> usize {
         self.realtime_timeout
     }
+    /// Gets how long before the realtime timeout the warning is sent.
+    pub fn timeout_warning(&self) -> usize {
+        self.timeout_warning
+    }
+    /// Gets what happens after a prolonged silence.
+    pub fn idle_policy(&self) -> IdlePolicy {
+        self.idle_policy
+    }
+    /// Indicates whether decode diagnostics are sent.
+    pub fn decode_diagnostics(&self) -> bool {
+        self.decode_diagnostics
+    }
+    /// Indicates whether timestamped snapshots are sent.
+    pub fn timestamped_snapshots(&self) -> bool {
+        self.timestamped_snapshots
+    }
+    /// Gets when inference windows are re-run.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+    /// Gets when working-set segments are confirmed.
+    pub fn commit_policy(&self) -> CommitPolicy {
+        self.commit_policy
+    }
+    /// Gets what happens when the working set overflows.
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
+    }
+    /// Gets when the audio buffer is cleared.
+    pub fn buffer_clear_policy(&self) -> BufferClearPolicy {
+        self.buffer_clear_policy
+    }
+    /// Gets how far back corrections can reach, (0 if disabled).
+    pub fn correction_horizon(&self) -> usize {
+        self.correction_horizon
+    }
 
     /// Gets the audio sampling window size.
     pub fn audio_sample_len(&self) -> usize {
@@ -378,9 +1072,30 @@ impl RealtimeConfigs {
         self.buffering_strategy
     }
 
+    pub fn snapshot_encoding(&self) -> SnapshotEncoding {
+        self.snapshot_encoding
+    }
+
+    pub fn thread_configs(&self) -> ThreadConfigs {
+        self.thread_configs
+    }
+
+    pub fn dedup_configs(&self) -> DedupConfigs {
+        self.dedup_configs
+    }
+
     pub fn min_sample_len(&self) -> usize {
         self.buffering_strategy.min_sample_len()
     }
+
+    /// Gets the voice-detection window, inference window and buffering strategy.
+    pub fn window_lengths(&self) -> WindowLengths {
+        WindowLengths {
+            vad_sample_len: self.vad_sample_len,
+            audio_sample_len: self.audio_sample_len,
+            buffering_strategy: self.buffering_strategy,
+        }
+    }
 }
 
 impl Default for RealtimeConfigs {
@@ -388,6 +1103,8 @@ impl Default for RealtimeConfigs {
         Self::new()
             // 1 hour
             .with_realtime_timeout(REALTIME_AUDIO_TIMEOUT)
+            // 1 minute
+            .with_timeout_warning(TIMEOUT_WARNING_MS)
             // 10 seconds / 10 000 ms
             .with_audio_sample_len(AUDIO_SAMPLE_MS)
             // .3 seconds / 300 ms
@@ -592,6 +1309,25 @@ impl WhisperRealtimeConfigs {
         self
     }
 
+    /// Sets the gpu device to run on in multi-gpu systems.
+    pub fn with_gpu_device(mut self, gpu_device: std::ffi::c_int) -> Self {
+        self.whisper = self.whisper.with_gpu_device(gpu_device);
+        self
+    }
+
+    /// Toggles detecting speaker turns with tinydiarize, (see: [WhisperConfigs::with_tinydiarize]).
+    /// Snapshots then carry the speakers of the confirmed text and working segments.
+    pub fn with_tinydiarize(mut self, tinydiarize: bool) -> Self {
+        self.whisper.tinydiarize = tinydiarize;
+        self
+    }
+
+    /// Sets how strongly a decoding grammar is enforced, (see: [WhisperConfigs::with_grammar_penalty]).
+    pub fn with_grammar_penalty(mut self, grammar_penalty: f32) -> Self {
+        self.whisper = self.whisper.with_grammar_penalty(grammar_penalty);
+        self
+    }
+
     /// Sets the sampling strategy.
     pub fn with_sampling_strategy(mut self, sampling_strategy: WhisperSamplingStrategy) -> Self {
         self.whisper.sampling_strategy = sampling_strategy;
@@ -604,12 +1340,72 @@ impl WhisperRealtimeConfigs {
         self
     }
 
-    /// Sets the model.
+    /// Sets the realtime timeout (in ms). Set to 0 for "Infinite"
+    /// Time spent paused by [IdlePolicy::Pause] is not counted toward it.
     pub fn with_realtime_timeout(mut self, realtime_timeout: usize) -> Self {
         self.realtime.realtime_timeout = realtime_timeout;
         self
     }
 
+    /// Sets how long before the realtime timeout (in ms) a warning is sent, (see:
+    /// [RealtimeConfigs::with_timeout_warning]).
+    pub fn with_timeout_warning(mut self, lead_ms: usize) -> Self {
+        self.realtime.timeout_warning = lead_ms;
+        self
+    }
+
+    /// Sets what happens after a prolonged silence, (see: [IdlePolicy]).
+    pub fn with_idle_policy(mut self, idle_policy: IdlePolicy) -> Self {
+        self.realtime.idle_policy = idle_policy;
+        self
+    }
+
+    /// Toggles sending decode diagnostics after each inference window, (see:
+    /// [crate::transcriber::decode_diagnostics]).
+    pub fn with_decode_diagnostics(mut self, decode_diagnostics: bool) -> Self {
+        self.realtime.decode_diagnostics = decode_diagnostics;
+        self
+    }
+
+    /// Toggles sending segments with word-level timestamps after each inference window, (see:
+    /// [crate::transcriber::timestamps]). This turns on whisper's token-level timestamps, which
+    /// adds a little to the cost of each window.
+    pub fn with_timestamped_snapshots(mut self, timestamped_snapshots: bool) -> Self {
+        self.realtime.timestamped_snapshots = timestamped_snapshots;
+        self
+    }
+
+    /// Sets when inference windows with broken-looking output are re-run, (see: [RetryPolicy]).
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.realtime.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets when working-set segments are confirmed, (see: [CommitPolicy]).
+    pub fn with_commit_policy(mut self, commit_policy: CommitPolicy) -> Self {
+        self.realtime.commit_policy = commit_policy;
+        self
+    }
+
+    /// Sets what happens when the working set overflows, (see: [OverflowPolicy]).
+    pub fn with_overflow_policy(mut self, overflow_policy: OverflowPolicy) -> Self {
+        self.realtime.overflow_policy = overflow_policy;
+        self
+    }
+
+    /// Sets when the audio buffer is cleared, (see: [BufferClearPolicy]).
+    pub fn with_buffer_clear_policy(mut self, buffer_clear_policy: BufferClearPolicy) -> Self {
+        self.realtime.buffer_clear_policy = buffer_clear_policy;
+        self
+    }
+
+    /// Sets how far back, (in bytes), revisions of confirmed text are sent as corrections, (see:
+    /// [crate::transcriber::corrections]). Set to 0 to disable corrections.
+    pub fn with_correction_horizon(mut self, horizon: usize) -> Self {
+        self.realtime.correction_horizon = horizon;
+        self
+    }
+
     /// Sets the size of the audio sampling window (in ms). Defaults to 10 seconds (10 000 ms).
     pub fn with_audio_sample_len(mut self, len_ms: usize) -> Self {
         self.realtime.audio_sample_len = len_ms;
@@ -629,6 +1425,31 @@ impl WhisperRealtimeConfigs {
         self
     }
 
+    /// Sets the voice-detection window, inference window and buffering strategy together, (see:
+    /// [WindowLengths]).
+    pub fn with_window_lengths(mut self, window_lengths: WindowLengths) -> Self {
+        self.realtime = self.realtime.with_window_lengths(window_lengths);
+        self
+    }
+
+    /// Sets how transcription updates are sent. Defaults to full snapshots.
+    pub fn with_snapshot_encoding(mut self, snapshot_encoding: SnapshotEncoding) -> Self {
+        self.realtime.snapshot_encoding = snapshot_encoding;
+        self
+    }
+
+    /// Sets how the inference thread is scheduled, (see: [ThreadConfigs]).
+    pub fn with_thread_configs(mut self, thread_configs: ThreadConfigs) -> Self {
+        self.realtime.thread_configs = thread_configs;
+        self
+    }
+
+    /// Sets how overlapping text is deduplicated, (see: [DedupConfigs]).
+    pub fn with_dedup_configs(mut self, dedup_configs: DedupConfigs) -> Self {
+        self.realtime.dedup_configs = dedup_configs;
+        self
+    }
+
     // Whisper accessors
     /// Gets the number of threads used in transcription.
     pub fn n_threads(&self) -> usize {
@@ -662,6 +1483,18 @@ impl WhisperRealtimeConfigs {
     pub fn using_flash_attention(&self) -> bool {
         self.whisper.flash_attention
     }
+    /// Gets the gpu device index
+    pub fn gpu_device(&self) -> std::ffi::c_int {
+        self.whisper.gpu_device
+    }
+    /// Indicates whether speaker turns are detected with tinydiarize.
+    pub fn using_tinydiarize(&self) -> bool {
+        self.whisper.tinydiarize
+    }
+    /// Gets how strongly a decoding grammar is enforced.
+    pub fn grammar_penalty(&self) -> f32 {
+        self.whisper.grammar_penalty
+    }
 
     /// Gets a reference to the model being used for transcription
     pub fn model_id(&self) -> &Option<ModelId> {
@@ -674,6 +1507,51 @@ impl WhisperRealtimeConfigs {
         self.realtime.realtime_timeout
     }
 
+    /// Gets how long before the realtime timeout (in ms) the warning is sent.
+    pub fn timeout_warning(&self) -> usize {
+        self.realtime.timeout_warning
+    }
+
+    /// Gets what happens after a prolonged silence.
+    pub fn idle_policy(&self) -> IdlePolicy {
+        self.realtime.idle_policy
+    }
+
+    /// Indicates whether decode diagnostics are sent after each inference window.
+    pub fn decode_diagnostics(&self) -> bool {
+        self.realtime.decode_diagnostics
+    }
+
+    /// Indicates whether timestamped snapshots are sent after each inference window.
+    pub fn timestamped_snapshots(&self) -> bool {
+        self.realtime.timestamped_snapshots
+    }
+
+    /// Gets when inference windows are re-run.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.realtime.retry_policy
+    }
+
+    /// Gets when working-set segments are confirmed.
+    pub fn commit_policy(&self) -> CommitPolicy {
+        self.realtime.commit_policy
+    }
+
+    /// Gets what happens when the working set overflows.
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.realtime.overflow_policy
+    }
+
+    /// Gets when the audio buffer is cleared.
+    pub fn buffer_clear_policy(&self) -> BufferClearPolicy {
+        self.realtime.buffer_clear_policy
+    }
+
+    /// Gets how far back corrections can reach, (0 if disabled).
+    pub fn correction_horizon(&self) -> usize {
+        self.realtime.correction_horizon
+    }
+
     /// Gets the audio sampling window size (in ms).
     pub fn audio_sample_len_ms(&self) -> usize {
         self.realtime.audio_sample_len
@@ -688,10 +1566,27 @@ impl WhisperRealtimeConfigs {
     pub fn realtime_buffering_strategy(&self) -> RealtimeBufferingStrategy {
         self.realtime.buffering_strategy
     }
+    /// Gets how transcription updates are sent.
+    pub fn snapshot_encoding(&self) -> SnapshotEncoding {
+        self.realtime.snapshot_encoding
+    }
+    /// Gets how the inference thread is scheduled.
+    pub fn thread_configs(&self) -> ThreadConfigs {
+        self.realtime.thread_configs
+    }
+    /// Gets how overlapping text is deduplicated.
+    pub fn dedup_configs(&self) -> DedupConfigs {
+        self.realtime.dedup_configs
+    }
     pub fn min_sample_len(&self) -> usize {
         self.realtime.min_sample_len()
     }
 
+    /// Gets the voice-detection window, inference window and buffering strategy.
+    pub fn window_lengths(&self) -> WindowLengths {
+        self.realtime.window_lengths()
+    }
+
     /// Gets the inner Whisper_configs
     pub fn as_whisper_configs(&self) -> &WhisperConfigs {
         &self.whisper
@@ -716,15 +1611,20 @@ impl WhisperRealtimeConfigs {
     /// Note: these configurations do not cover FullParams in entirety
     /// Features are exposed on an as-needed bases.
     /// See: [whisper_rs::FullParams] for documentation.
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn as_whisper_full_params(&'_ self) -> whisper_rs::FullParams<'_, '_> {
         let mut params = self.whisper.as_whisper_full_params();
         // Forcing single segment transcription helps alleviate transcription artifacts when
         // running realtime to reduce the amount of false negatives in the
-        // word-boundary resolution algorithm
-        params.set_single_segment(true);
+        // word-boundary resolution algorithm.
+        // Speaker turns split segments, so diarization needs whisper to segment freely.
+        params.set_single_segment(!self.whisper.tinydiarize);
+        // Word timings come from whisper's token-level timestamps.
+        params.set_token_timestamps(self.realtime.timestamped_snapshots);
         params
     }
     /// Constructs a WhisperContextParameters object used to build [whisper_rs::WhisperContext]
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn as_whisper_context_params(&'_ self) -> whisper_rs::WhisperContextParameters<'_> {
         self.whisper.as_whisper_context_params()
     }
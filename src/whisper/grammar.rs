@@ -0,0 +1,455 @@
+//! Grammar-constrained decoding: restricts whisper's output to text matching a GBNF grammar,
+//! (the format used by whisper.cpp and llama.cpp), for command-and-control use cases where the
+//! output must come from a fixed vocabulary, (e.g. "open settings", "next slide").
+//!
+//! Grammars are parsed with [Grammar::parse], or built from a list of phrases with
+//! [Grammar::from_phrases], and attached to a transcriber with
+//! [RealtimeTranscriberBuilder::with_grammar](crate::transcriber::realtime_transcriber::RealtimeTranscriberBuilder::with_grammar)
+//! or [OfflineTranscriberBuilder::with_grammar](crate::transcriber::offline_transcriber::OfflineTranscriberBuilder::with_grammar).
+//! How strongly the grammar is enforced is set with
+//! [WhisperConfigs::with_grammar_penalty](crate::whisper::configs::WhisperConfigs::with_grammar_penalty).
+//!
+//! e.g.
+//! ```text
+//! root   ::= " "? command "."?
+//! command ::= "open " ("settings" | "files") | "next slide" | "previous slide"
+//! ```
+//!
+//! Whisper's tokens usually begin with a space, so grammars should allow leading whitespace.
+
+use std::collections::HashMap;
+
+use crate::utils::errors::RibbleWhisperError;
+
+/// The rule decoding starts from when none is specified.
+pub const DEFAULT_START_RULE: &str = "root";
+
+/// The kind of a [GrammarElement], (mirroring whisper.cpp's whisper_gretype).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GrammarElementKind {
+    /// Ends a rule definition.
+    End,
+    /// Starts an alternate definition of a rule.
+    Alternate,
+    /// A reference to the rule with the id in the value.
+    RuleReference,
+    /// Matches the character in the value.
+    Character,
+    /// Matches any character except the value, (and any following alternates).
+    NotCharacter,
+    /// Modifies the preceding character to match the inclusive range up to the value.
+    CharacterRangeUpper,
+    /// Adds an alternate character to match to the preceding character or range.
+    CharacterAlternate,
+}
+
+/// An element of a grammar rule.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GrammarElement {
+    pub kind: GrammarElementKind,
+    /// A rule id or a Unicode code point, depending on the kind.
+    pub value: u32,
+}
+
+impl GrammarElement {
+    pub fn new(kind: GrammarElementKind, value: u32) -> Self {
+        Self { kind, value }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn to_whisper(self) -> whisper_rs::WhisperGrammarElement {
+        use whisper_rs::WhisperGrammarElementType as Type;
+        let kind = match self.kind {
+            GrammarElementKind::End => Type::End,
+            GrammarElementKind::Alternate => Type::Alternate,
+            GrammarElementKind::RuleReference => Type::RuleReference,
+            GrammarElementKind::Character => Type::Character,
+            GrammarElementKind::NotCharacter => Type::NotCharacter,
+            GrammarElementKind::CharacterRangeUpper => Type::CharacterRangeUpper,
+            GrammarElementKind::CharacterAlternate => Type::CharacterAlternates,
+        };
+        whisper_rs::WhisperGrammarElement::new(kind, self.value)
+    }
+}
+
+/// A parsed GBNF grammar, (see: [crate::whisper::grammar]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grammar {
+    rules: Vec<Vec<GrammarElement>>,
+    symbols: HashMap<String, u32>,
+    start_rule: u32,
+}
+
+impl Grammar {
+    /// Parses a GBNF grammar, starting from the "root" rule.
+    pub fn parse(gbnf: &str) -> Result<Self, RibbleWhisperError> {
+        Self::parse_with_start_rule(gbnf, DEFAULT_START_RULE)
+    }
+
+    /// Parses a GBNF grammar, starting from the named rule.
+    pub fn parse_with_start_rule(gbnf: &str, start_rule: &str) -> Result<Self, RibbleWhisperError> {
+        let mut parser = Parser {
+            src: gbnf.as_bytes(),
+            text: gbnf,
+            symbols: HashMap::new(),
+            rules: vec![],
+        };
+        parser.parse()?;
+
+        // Every referenced rule must be defined.
+        for rule in &parser.rules {
+            for element in rule {
+                if element.kind == GrammarElementKind::RuleReference
+                    && parser
+                        .rules
+                        .get(element.value as usize)
+                        .is_none_or(Vec::is_empty)
+                {
+                    let name = parser
+                        .symbols
+                        .iter()
+                        .find(|(_, id)| **id == element.value)
+                        .map_or("", |(name, _)| name.as_str());
+                    return Err(RibbleWhisperError::GrammarParseError {
+                        offset: gbnf.len(),
+                        reason: format!("Undefined rule: {name}"),
+                    });
+                }
+            }
+        }
+        let Some(start_rule) = parser.symbols.get(start_rule).copied() else {
+            return Err(RibbleWhisperError::GrammarParseError {
+                offset: 0,
+                reason: format!("Missing start rule: {start_rule}"),
+            });
+        };
+        Ok(Self {
+            rules: parser.rules,
+            symbols: parser.symbols,
+            start_rule,
+        })
+    }
+
+    /// Builds a grammar matching exactly one of the phrases, (with optional leading whitespace
+    /// and trailing punctuation).
+    pub fn from_phrases<S: AsRef<str>>(phrases: &[S]) -> Result<Self, RibbleWhisperError> {
+        if phrases.is_empty() {
+            return Err(RibbleWhisperError::ParameterError(
+                "A phrase grammar requires at least one phrase.".to_string(),
+            ));
+        }
+        let alternates = phrases
+            .iter()
+            .map(|phrase| {
+                let escaped = phrase
+                    .as_ref()
+                    .trim()
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"");
+                format!("\"{escaped}\"")
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+        Self::parse(&format!("root ::= [ ]? ({alternates}) [.!?]?\n"))
+    }
+
+    /// The rules, indexed by id. Each rule ends with [GrammarElementKind::End].
+    pub fn rules(&self) -> &[Vec<GrammarElement>] {
+        &self.rules
+    }
+
+    /// The id of the rule decoding starts from.
+    pub fn start_rule(&self) -> usize {
+        self.start_rule as usize
+    }
+
+    /// The id of a named rule.
+    pub fn rule_id(&self, name: &str) -> Option<usize> {
+        self.symbols.get(name).map(|id| *id as usize)
+    }
+}
+
+// A recursive-descent port of the GBNF parser in whisper.cpp's examples, (grammar-parser.cpp).
+struct Parser<'a> {
+    src: &'a [u8],
+    text: &'a str,
+    symbols: HashMap<String, u32>,
+    rules: Vec<Vec<GrammarElement>>,
+}
+
+impl Parser<'_> {
+    fn error<T>(&self, pos: usize, reason: impl Into<String>) -> Result<T, RibbleWhisperError> {
+        Err(RibbleWhisperError::GrammarParseError {
+            offset: pos,
+            reason: reason.into(),
+        })
+    }
+
+    fn peek(&self, pos: usize) -> Option<u8> {
+        self.src.get(pos).copied()
+    }
+
+    fn symbol_id(&mut self, name: &str) -> u32 {
+        let next_id = self.symbols.len() as u32;
+        *self.symbols.entry(name.to_string()).or_insert(next_id)
+    }
+
+    // Generated rules, (for groups and repetitions), are named after the rule they occur in.
+    fn generate_symbol_id(&mut self, base_name: &str) -> u32 {
+        let next_id = self.symbols.len() as u32;
+        self.symbols
+            .insert(format!("{base_name}_{next_id}"), next_id);
+        next_id
+    }
+
+    fn add_rule(&mut self, rule_id: u32, rule: Vec<GrammarElement>) {
+        let index = rule_id as usize;
+        if self.rules.len() <= index {
+            self.rules.resize(index + 1, vec![]);
+        }
+        self.rules[index] = rule;
+    }
+
+    fn is_word_char(c: u8) -> bool {
+        c.is_ascii_alphanumeric() || c == b'-'
+    }
+
+    fn parse_space(&self, mut pos: usize, newline_ok: bool) -> usize {
+        while let Some(c) = self.peek(pos) {
+            match c {
+                b' ' | b'\t' => pos += 1,
+                b'\r' | b'\n' if newline_ok => pos += 1,
+                b'#' => {
+                    while self.peek(pos).is_some_and(|c| c != b'\r' && c != b'\n') {
+                        pos += 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+        pos
+    }
+
+    fn parse_name(&self, pos: usize) -> Result<usize, RibbleWhisperError> {
+        let mut end = pos;
+        while self.peek(end).is_some_and(Self::is_word_char) {
+            end += 1;
+        }
+        if end == pos {
+            return self.error(pos, "Expected a rule name");
+        }
+        Ok(end)
+    }
+
+    // Parses a (possibly escaped) character, returning its code point and the position after it.
+    fn parse_char(&self, pos: usize) -> Result<(u32, usize), RibbleWhisperError> {
+        if self.peek(pos) == Some(b'\\') {
+            let hex = |len: usize| {
+                let digits = self
+                    .text
+                    .get(pos + 2..pos + 2 + len)
+                    .filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()));
+                match digits.and_then(|digits| u32::from_str_radix(digits, 16).ok()) {
+                    Some(value) => Ok((value, pos + 2 + len)),
+                    None => self.error(pos, "Expected hex digits in escape"),
+                }
+            };
+            return match self.peek(pos + 1) {
+                Some(b'x') => hex(2),
+                Some(b'u') => hex(4),
+                Some(b'U') => hex(8),
+                Some(b't') => Ok(('\t' as u32, pos + 2)),
+                Some(b'r') => Ok(('\r' as u32, pos + 2)),
+                Some(b'n') => Ok(('\n' as u32, pos + 2)),
+                Some(c @ (b'\\' | b'"' | b'[' | b']')) => Ok((c as u32, pos + 2)),
+                _ => self.error(pos, "Unknown escape"),
+            };
+        }
+        match self.text.get(pos..).and_then(|rest| rest.chars().next()) {
+            Some(c) => Ok((c as u32, pos + c.len_utf8())),
+            None => self.error(pos, "Unexpected end of input"),
+        }
+    }
+
+    fn parse(&mut self) -> Result<(), RibbleWhisperError> {
+        let mut pos = self.parse_space(0, true);
+        while pos < self.src.len() {
+            pos = self.parse_rule(pos)?;
+        }
+        if self.rules.is_empty() {
+            return self.error(0, "The grammar has no rules");
+        }
+        Ok(())
+    }
+
+    fn parse_rule(&mut self, pos: usize) -> Result<usize, RibbleWhisperError> {
+        let name_end = self.parse_name(pos)?;
+        let name = self.text[pos..name_end].to_string();
+        let rule_id = self.symbol_id(&name);
+        let pos = self.parse_space(name_end, false);
+        if !self.src[pos..].starts_with(b"::=") {
+            return self.error(pos, "Expected ::=");
+        }
+        let pos = self.parse_space(pos + 3, true);
+        let mut pos = self.parse_alternates(pos, &name, rule_id, false)?;
+        match self.peek(pos) {
+            Some(b'\r') if self.peek(pos + 1) == Some(b'\n') => pos += 2,
+            Some(b'\n' | b'\r') => pos += 1,
+            Some(_) => return self.error(pos, "Expected a newline or end of input"),
+            None => {}
+        }
+        Ok(self.parse_space(pos, true))
+    }
+
+    fn parse_alternates(
+        &mut self,
+        pos: usize,
+        rule_name: &str,
+        rule_id: u32,
+        is_nested: bool,
+    ) -> Result<usize, RibbleWhisperError> {
+        let mut rule = vec![];
+        let mut pos = self.parse_sequence(pos, rule_name, &mut rule, is_nested)?;
+        while self.peek(pos) == Some(b'|') {
+            rule.push(GrammarElement::new(GrammarElementKind::Alternate, 0));
+            pos = self.parse_space(pos + 1, true);
+            pos = self.parse_sequence(pos, rule_name, &mut rule, is_nested)?;
+        }
+        rule.push(GrammarElement::new(GrammarElementKind::End, 0));
+        self.add_rule(rule_id, rule);
+        Ok(pos)
+    }
+
+    fn parse_sequence(
+        &mut self,
+        mut pos: usize,
+        rule_name: &str,
+        elements: &mut Vec<GrammarElement>,
+        is_nested: bool,
+    ) -> Result<usize, RibbleWhisperError> {
+        let mut last_sym_start = elements.len();
+        while let Some(c) = self.peek(pos) {
+            match c {
+                b'"' => {
+                    pos += 1;
+                    last_sym_start = elements.len();
+                    while self.peek(pos) != Some(b'"') {
+                        if self.peek(pos).is_none() {
+                            return self.error(pos, "Unexpected end of input");
+                        }
+                        let (value, next) = self.parse_char(pos)?;
+                        elements.push(GrammarElement::new(GrammarElementKind::Character, value));
+                        pos = next;
+                    }
+                    pos = self.parse_space(pos + 1, is_nested);
+                }
+                b'[' => {
+                    pos += 1;
+                    let mut start_kind = GrammarElementKind::Character;
+                    if self.peek(pos) == Some(b'^') {
+                        pos += 1;
+                        start_kind = GrammarElementKind::NotCharacter;
+                    }
+                    last_sym_start = elements.len();
+                    while self.peek(pos) != Some(b']') {
+                        if self.peek(pos).is_none() {
+                            return self.error(pos, "Unexpected end of input");
+                        }
+                        let (value, next) = self.parse_char(pos)?;
+                        let kind = if last_sym_start < elements.len() {
+                            GrammarElementKind::CharacterAlternate
+                        } else {
+                            start_kind
+                        };
+                        elements.push(GrammarElement::new(kind, value));
+                        pos = next;
+                        if self.peek(pos) == Some(b'-')
+                            && self.peek(pos + 1).is_some_and(|c| c != b']')
+                        {
+                            let (upper, next) = self.parse_char(pos + 1)?;
+                            elements.push(GrammarElement::new(
+                                GrammarElementKind::CharacterRangeUpper,
+                                upper,
+                            ));
+                            pos = next;
+                        }
+                    }
+                    pos = self.parse_space(pos + 1, is_nested);
+                }
+                c if Self::is_word_char(c) => {
+                    let name_end = self.parse_name(pos)?;
+                    let name = self.text[pos..name_end].to_string();
+                    let reference = self.symbol_id(&name);
+                    pos = self.parse_space(name_end, is_nested);
+                    last_sym_start = elements.len();
+                    elements.push(GrammarElement::new(
+                        GrammarElementKind::RuleReference,
+                        reference,
+                    ));
+                }
+                b'(' => {
+                    pos = self.parse_space(pos + 1, true);
+                    let sub_rule_id = self.generate_symbol_id(rule_name);
+                    pos = self.parse_alternates(pos, rule_name, sub_rule_id, true)?;
+                    last_sym_start = elements.len();
+                    elements.push(GrammarElement::new(
+                        GrammarElementKind::RuleReference,
+                        sub_rule_id,
+                    ));
+                    if self.peek(pos) != Some(b')') {
+                        return self.error(pos, "Expected )");
+                    }
+                    pos = self.parse_space(pos + 1, is_nested);
+                }
+                b'*' | b'+' | b'?' => {
+                    if last_sym_start == elements.len() {
+                        return self.error(pos, "Expected an item before a repetition operator");
+                    }
+                    // Rewrite the preceding symbol, (S), as a generated rule:
+                    // S* --> S' ::= S S' |
+                    // S+ --> S' ::= S S' | S
+                    // S? --> S' ::= S |
+                    let sub_rule_id = self.generate_symbol_id(rule_name);
+                    let symbol = elements[last_sym_start..].to_vec();
+                    let mut sub_rule = symbol.clone();
+                    if c != b'?' {
+                        sub_rule.push(GrammarElement::new(
+                            GrammarElementKind::RuleReference,
+                            sub_rule_id,
+                        ));
+                    }
+                    sub_rule.push(GrammarElement::new(GrammarElementKind::Alternate, 0));
+                    if c == b'+' {
+                        sub_rule.extend(symbol);
+                    }
+                    sub_rule.push(GrammarElement::new(GrammarElementKind::End, 0));
+                    self.add_rule(sub_rule_id, sub_rule);
+
+                    elements.truncate(last_sym_start);
+                    elements.push(GrammarElement::new(
+                        GrammarElementKind::RuleReference,
+                        sub_rule_id,
+                    ));
+                    pos = self.parse_space(pos + 1, is_nested);
+                }
+                _ => break,
+            }
+        }
+        Ok(pos)
+    }
+}
+
+/// Constrains decoding to the grammar. The parameters keep their own copy of the rules.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn apply_grammar(params: &mut whisper_rs::FullParams, grammar: &Grammar, penalty: f32) {
+    // Whisper takes the rules concatenated, each ending with End.
+    let elements: Vec<_> = grammar
+        .rules
+        .iter()
+        .flatten()
+        .map(|element| element.to_whisper())
+        .collect();
+    params.set_grammar(Some(&elements));
+    params.set_start_rule(grammar.start_rule());
+    params.set_grammar_penalty(penalty);
+}
@@ -5,8 +5,11 @@ use std::{fs, io};
 
 use regex::Regex;
 use reqwest::blocking;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
 
 use crate::utils::errors::RibbleWhisperError;
+use crate::whisper::model::Checksum;
 
 // Filename for the recordfile that stores a literal of the most up-to-date commit hash of whisper.cpp's huggingface repository
 const LATEST_CHECKSUM: &str = "latest_checksum";
@@ -16,6 +19,8 @@ const CHECKSUM_FILE: &str = "checksum.json";
 const REPO_URL: &str = "https://huggingface.co/api/models/ggerganov/whisper.cpp";
 // As of this implementation, model sha1 checksums are made available in README.md
 const README_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/raw/main/README.md";
+// Url for the repository file tree; LFS entries carry their sha256 as the object id.
+const TREE_URL: &str = "https://huggingface.co/api/models/ggerganov/whisper.cpp/tree/main";
 
 // (Very specific) Regex: Used to extract the required fields from README.md
 // |whitespace*(non-whitespace+)whitespace*|, First field: Model name, first capture group
@@ -157,3 +162,113 @@ fn get_latest_repo_checksum(
         ))?;
     Ok(latest_checksum.to_owned())
 }
+
+/// Computes the lowercase hex sha1 digest of a file.
+pub fn compute_sha1(file_path: &Path) -> Result<String, RibbleWhisperError> {
+    let mut file = fs::File::open(file_path)?;
+    let mut hasher = Sha1::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Computes the lowercase hex sha256 digest of a file.
+pub fn compute_sha256(file_path: &Path) -> Result<String, RibbleWhisperError> {
+    let mut file = fs::File::open(file_path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+enum ChecksumHasher {
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+/// A writer that computes the digest of everything written through it, for verifying downloads
+/// that are not written to a file. The digest uses the same algorithm as the provided checksum.
+pub struct ChecksumWriter<W: io::Write> {
+    writer: W,
+    hasher: ChecksumHasher,
+}
+
+impl<W: io::Write> ChecksumWriter<W> {
+    pub fn new(writer: W, checksum: &Checksum) -> Self {
+        let hasher = match checksum {
+            Checksum::Sha1(_) => ChecksumHasher::Sha1(Sha1::new()),
+            Checksum::Sha256(_) => ChecksumHasher::Sha256(Sha256::new()),
+        };
+        Self { writer, hasher }
+    }
+
+    /// Consumes the writer and returns the lowercase hex digest of the bytes written.
+    pub fn finalize(self) -> String {
+        match self.hasher {
+            ChecksumHasher::Sha1(hasher) => format!("{:x}", hasher.finalize()),
+            ChecksumHasher::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+        }
+    }
+}
+
+impl<W: io::Write> io::Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        match &mut self.hasher {
+            ChecksumHasher::Sha1(hasher) => hasher.update(&buf[..written]),
+            ChecksumHasher::Sha256(hasher) => hasher.update(&buf[..written]),
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Computes the digest of a file using the same algorithm as the provided checksum.
+pub fn compute_checksum(
+    file_path: &Path,
+    checksum: &Checksum,
+) -> Result<String, RibbleWhisperError> {
+    match checksum {
+        Checksum::Sha1(_) => compute_sha1(file_path),
+        Checksum::Sha256(_) => compute_sha256(file_path),
+    }
+}
+
+/// Verifies a file against the expected checksum. Comparison is case-insensitive.
+/// # Returns:
+/// * Ok(true) if the file digest matches, Ok(false) if not, Err on an I/O failure.
+pub fn verify_file_checksum(
+    file_path: &Path,
+    checksum: &Checksum,
+) -> Result<bool, RibbleWhisperError> {
+    let digest = compute_checksum(file_path, checksum)?;
+    Ok(digest == checksum.digest().to_lowercase())
+}
+
+/// Requests the repository file tree and returns the sha256 of the named (LFS-tracked) file, if it
+/// exists. Huggingface only exposes sha256 digests through LFS metadata, so this makes a blocking
+/// network request.
+pub fn get_sha256_checksum(
+    file_name: &str,
+    client: Option<&blocking::Client>,
+) -> Result<Option<String>, RibbleWhisperError> {
+    let json: serde_json::Value = match client {
+        None => blocking::get(TREE_URL),
+        Some(r_client) => r_client.get(TREE_URL).send(),
+    }?
+    .json()?;
+
+    let entries = json
+        .as_array()
+        .ok_or(RibbleWhisperError::DownloadError(
+            "Response JSON is not a file tree".to_owned(),
+        ))?;
+
+    let checksum = entries
+        .iter()
+        .find(|entry| entry["path"].as_str() == Some(file_name))
+        .and_then(|entry| entry["lfs"]["oid"].as_str())
+        .map(|oid| oid.to_lowercase());
+    Ok(checksum)
+}
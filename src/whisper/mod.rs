@@ -1,5 +1,13 @@
+/// Optional model bank wrapper that downloads missing models on retrieval
+#[cfg(feature = "downloader")]
+pub mod auto_fetch;
 pub mod configs;
+pub mod grammar;
 /// Optional integrity utilities for verifying compatible whisper model
 #[cfg(feature = "integrity")]
 pub mod integrity_utils;
 pub mod model;
+/// Caches loaded whisper contexts; unavailable on wasm32.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod model_cache;
+pub mod model_info;
@@ -1,22 +1,26 @@
+#[cfg(feature = "encoder-companions")]
+use crate::downloader::downloaders::{download_encoder_companion, sync_download_request};
 use crate::utils::errors::RibbleWhisperError;
+#[cfg(feature = "serde")]
+use crate::utils::logging::log_warn_or_eprint;
+use crate::whisper::configs::WhisperConfigs;
 #[cfg(feature = "integrity")]
 use crate::whisper::integrity_utils::{
-    checksums_need_updating, get_model_checksum, get_new_checksums, serialize_new_checksums,
-    write_latest_repo_checksum_to_disk, ChecksumStatus,
+    checksums_need_updating, get_model_checksum, get_new_checksums, get_sha256_checksum,
+    serialize_new_checksums, verify_file_checksum, write_latest_repo_checksum_to_disk,
+    ChecksumStatus,
 };
 #[cfg(feature = "integrity")]
 use reqwest::blocking;
-#[cfg(feature = "integrity")]
-use sha1::Sha1;
-#[cfg(feature = "integrity")]
-use sha2::{Digest, Sha256};
-use std::collections::{hash_map, HashMap};
+use std::cell::RefCell;
+use std::collections::{hash_map, BTreeMap, HashMap};
 use std::fs;
 use std::hash::{DefaultHasher, Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use strum::{
-    AsRefStr, Display, EnumCount, EnumIs, EnumIter, EnumString, FromRepr, IntoStaticStr,
-    VariantArray, VariantNames,
+    AsRefStr, Display, EnumCount, EnumIs, EnumIter, EnumString, FromRepr, IntoEnumIterator,
+    IntoStaticStr, VariantArray, VariantNames,
 };
 
 /// A Type alias representing a model's ID, (e.g. based on a hash)
@@ -81,6 +85,140 @@ pub trait ModelBank {
         model_id: ModelId,
         checksum: &Checksum,
     ) -> Result<bool, RibbleWhisperError>;
+
+    /// Verifies a model in storage against the expected checksum stored with the [Model].
+    /// Models constructed from [DefaultModelType::to_model] carry their expected (sha256) checksum,
+    /// so this does not require a network connection.
+    /// # Returns:
+    /// * Ok(true) if the file matches.
+    /// * Ok(false) if the file does not match, or the model's file is not in storage.
+    /// * Err(RibbleWhisperError) if the model is not in the bank, has no expected checksum, or on I/O failure.
+    #[cfg(feature = "integrity")]
+    fn verify_model(&mut self, model_id: ModelId) -> Result<bool, RibbleWhisperError> {
+        let expected = self
+            .get_model(model_id)
+            .ok_or(RibbleWhisperError::ModelNotFound { id: model_id })?
+            .expected_checksum()
+            .cloned()
+            .ok_or(RibbleWhisperError::ParameterError(format!(
+                "Model: {model_id} has no expected checksum"
+            )))?;
+        self.verify_checksum(model_id, &expected.as_checksum())
+    }
+
+    /// Returns the [ModelId] and [Model] of every entry in the bank, e.g. to populate a
+    /// model-manager screen.
+    fn list_models(&self) -> Vec<(ModelId, &Model)> {
+        self.iter().map(|(id, model)| (*id, model)).collect()
+    }
+
+    /// Returns the [ModelMetadata] for a model in the bank.
+    /// # Returns:
+    /// * Ok(Some(ModelMetadata)) if the model was found.
+    /// * Ok(None) if the model is not in the bank.
+    /// * Err(RibbleWhisperError) on an I/O failure while inspecting the model file.
+    fn model_metadata(
+        &self,
+        model_id: ModelId,
+    ) -> Result<Option<ModelMetadata>, RibbleWhisperError> {
+        self.get_model(model_id)
+            .map(|model| ModelMetadata::from_model(model, self.model_directory()))
+            .transpose()
+    }
+
+    /// Deletes a model's file from storage, but keeps its entry in the bank so that it can be
+    /// downloaded again. Use [ModelBank::remove_model] to also remove the entry.
+    /// # Returns:
+    /// * Ok(Some(ModelId)) if the model file was found and deleted.
+    /// * Ok(None) if the model (or its file) does not exist.
+    /// * Err(RibbleWhisperError) on an I/O failure.
+    fn delete_model(&mut self, model_id: ModelId) -> Result<Option<ModelId>, RibbleWhisperError> {
+        let Some(model) = self.get_model(model_id) else {
+            return Ok(None);
+        };
+        if !self.model_exists_in_storage(model_id)? {
+            return Ok(None);
+        }
+        fs::remove_file(self.model_directory().join(model.file_name()))?;
+        Ok(Some(model_id))
+    }
+
+    /// Returns true if the model's [EncoderCompanion] has been placed next to it in storage.
+    fn encoder_companion_exists(
+        &self,
+        model_id: ModelId,
+        companion: EncoderCompanion,
+    ) -> Result<bool, RibbleWhisperError> {
+        let Some(model) = self.get_model(model_id) else {
+            return Ok(false);
+        };
+        let path = self
+            .model_directory()
+            .join(model.encoder_companion_file_name(companion));
+        match fs::metadata(path) {
+            Ok(_) => Ok(true),
+            Err(e) => match e.kind() {
+                std::io::ErrorKind::NotFound => Ok(false),
+                _ => Err(e.into()),
+            },
+        }
+    }
+
+    /// Downloads and extracts the model's [EncoderCompanion] into the model directory.
+    /// This is a blocking call; for progress/abort callbacks, construct the downloader manually
+    /// and use [crate::downloader::downloaders::download_encoder_companion].
+    /// Requires the encoder-companions feature flag to be set.
+    /// # Arguments:
+    /// * model_id: the model the encoder belongs to
+    /// * companion: the kind of encoder to fetch
+    /// * url: the url of the zipped encoder. Supply None to use the hosted encoder for default models.
+    /// # Returns:
+    /// * Ok(PathBuf) to the extracted encoder on success
+    /// * Err if the model is not in the bank, no url is known, or on download/extraction failure.
+    #[cfg(feature = "encoder-companions")]
+    fn fetch_encoder_companion(
+        &self,
+        model_id: ModelId,
+        companion: EncoderCompanion,
+        url: Option<&str>,
+    ) -> Result<PathBuf, RibbleWhisperError> {
+        let model = self
+            .get_model(model_id)
+            .ok_or(RibbleWhisperError::ModelNotFound { id: model_id })?;
+        let url = url
+            .map(|url| url.to_string())
+            .or_else(|| {
+                DefaultModelType::from_file_name(model.file_name())
+                    .and_then(|model_type| model_type.encoder_companion_url(companion))
+            })
+            .ok_or(RibbleWhisperError::ParameterError(format!(
+                "No {companion} encoder url for model: {}",
+                model.name()
+            )))?;
+        let fallback_name = [
+            model.encoder_companion_file_name(companion).as_str(),
+            ".zip",
+        ]
+        .concat();
+        let downloader = sync_download_request(&url, &fallback_name)?;
+        download_encoder_companion(downloader, model, companion, self.model_directory())
+    }
+
+    /// Returns the url a missing model can be downloaded from, if one is known.
+    /// By default, this resolves models whose file names match a [DefaultModelType].
+    fn download_url(&self, model_id: ModelId) -> Option<String> {
+        let model = self.get_model(model_id)?;
+        DefaultModelType::from_file_name(model.file_name()).map(|model_type| model_type.url())
+    }
+
+    /// Returns the total size (in bytes) of every model file in storage.
+    fn disk_usage(&self) -> Result<u64, RibbleWhisperError> {
+        let mut total = 0;
+        for (_, model) in self.iter() {
+            total += model_size_on_disk(self.model_directory(), model)?.unwrap_or(0);
+        }
+        Ok(total)
+    }
 }
 
 /// A thread-safe equivalent trait to [ModelBank]. It does not need to be strictly implemented, but
@@ -143,9 +281,96 @@ pub trait ConcurrentModelBank: Send + Sync {
         checksum: &Checksum,
     ) -> Result<bool, RibbleWhisperError>;
 
+    /// Verifies a model in storage against the expected checksum stored with the [Model].
+    /// See: [ModelBank::verify_model]
+    #[cfg(feature = "integrity")]
+    fn verify_model(&self, model_id: ModelId) -> Result<bool, RibbleWhisperError> {
+        let expected = self
+            .get_model(model_id)
+            .ok_or(RibbleWhisperError::ModelNotFound { id: model_id })?
+            .expected_checksum()
+            .cloned()
+            .ok_or(RibbleWhisperError::ParameterError(format!(
+                "Model: {model_id} has no expected checksum"
+            )))?;
+        self.verify_checksum(model_id, &expected.as_checksum())
+    }
+
     fn remove_model(&self, model_id: ModelId) -> Result<Option<ModelId>, RibbleWhisperError>;
 
     fn refresh_model_bank(&self) -> Result<(), RibbleWhisperError>;
+
+    /// Returns the [ModelId] and a clone of every [Model] in the bank.
+    /// See: [ModelBank::list_models]
+    fn list_models(&self) -> Vec<(ModelId, Model)> {
+        let models = RefCell::new(vec![]);
+        self.for_each(|(id, model)| models.borrow_mut().push((*id, model.clone())));
+        models.into_inner()
+    }
+
+    /// Returns the [ModelMetadata] for a model in the bank.
+    /// See: [ModelBank::model_metadata]
+    fn model_metadata(
+        &self,
+        model_id: ModelId,
+    ) -> Result<Option<ModelMetadata>, RibbleWhisperError> {
+        self.get_model(model_id)
+            .map(|model| ModelMetadata::from_model(&model, self.model_directory()))
+            .transpose()
+    }
+
+    /// Deletes a model's file from storage, but keeps its entry in the bank.
+    /// See: [ModelBank::delete_model]
+    fn delete_model(&self, model_id: ModelId) -> Result<Option<ModelId>, RibbleWhisperError> {
+        let Some(model) = self.get_model(model_id) else {
+            return Ok(None);
+        };
+        if !self.model_exists_in_storage(model_id)? {
+            return Ok(None);
+        }
+        fs::remove_file(self.model_directory().join(model.file_name()))?;
+        Ok(Some(model_id))
+    }
+
+    /// Returns the total size (in bytes) of every model file in storage.
+    fn disk_usage(&self) -> Result<u64, RibbleWhisperError> {
+        let mut total = 0;
+        for (_, model) in self.list_models() {
+            total += model_size_on_disk(self.model_directory(), &model)?.unwrap_or(0);
+        }
+        Ok(total)
+    }
+}
+
+/// Gets the platform-standard data directory for an application:
+/// * Linux/BSD: $XDG_DATA_HOME/app_name, falling back to ~/.local/share/app_name
+/// * MacOS: ~/Library/Application Support/app_name
+/// * Windows: %APPDATA%\\app_name
+///
+/// The directory is not created; this happens when the first model is downloaded into it.
+/// # Returns:
+/// * Ok(PathBuf) on success, Err if the required environment variables are not set.
+pub fn platform_data_directory(app_name: &str) -> Result<PathBuf, RibbleWhisperError> {
+    let env_path = |key: &str| {
+        std::env::var_os(key)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+
+    let base = if cfg!(target_os = "windows") {
+        env_path("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_path("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env_path("XDG_DATA_HOME")
+            .filter(|path| path.is_absolute())
+            .or_else(|| env_path("HOME").map(|home| home.join(".local").join("share")))
+    };
+
+    base.map(|base| base.join(app_name))
+        .ok_or(RibbleWhisperError::ParameterError(
+            "Failed to determine the platform data directory.".to_string(),
+        ))
 }
 
 pub enum ModelLocation {
@@ -153,6 +378,8 @@ pub enum ModelLocation {
     DynamicFilePath(PathBuf),
     StaticBuffer(&'static [u8]),
     DynamicBuffer(Vec<u8>),
+    /// A reference-counted buffer, e.g. handed out by a [crate::whisper::model_cache::SharedModelCache]
+    SharedBuffer(Arc<[u8]>),
 }
 
 /// A simple trait that allows Transcriber objects to retrieve models from storage.
@@ -160,10 +387,94 @@ pub trait ModelRetriever {
     fn retrieve_model(&self, model_id: ModelId) -> Option<ModelLocation>;
 }
 
+/// Where a user-registered model (e.g. a fine-tuned or third-party ggml model) comes from.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CustomModelSource {
+    /// A model file that already exists on disk. It is used in place and never deleted by the bank.
+    /// Relative paths are resolved against the current directory when the model is registered.
+    Path(PathBuf),
+    /// A model to be downloaded into the bank's model directory.
+    Url(String),
+}
+
+impl CustomModelSource {
+    /// Gets the file name the model is stored under.
+    /// Path sources keep their full path so that they resolve outside the model directory.
+    fn file_name(&self) -> Result<String, RibbleWhisperError> {
+        match self {
+            CustomModelSource::Path(path) => Ok(path.to_string_lossy().into_owned()),
+            CustomModelSource::Url(url) => {
+                let parsed = url::Url::parse(url)?;
+                parsed
+                    .path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    .filter(|segment| !segment.is_empty())
+                    .map(|segment| segment.to_string())
+                    .ok_or(RibbleWhisperError::ParameterError(format!(
+                        "Failed to determine a file name from url: {url}"
+                    )))
+            }
+        }
+    }
+}
+
+// A single user-registered model entry, as stored in the manifest.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize, serde::Serialize)]
+struct CustomModelEntry {
+    name: String,
+    source: CustomModelSource,
+    // Set if a url model has been stored under a different file name than its url's, (see:
+    // ModelBank::change_model_file_name).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file_name: Option<String>,
+}
+
+// A single named profile, as stored in the manifest.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize, serde::Serialize)]
+struct ProfileEntry {
+    name: String,
+    // The file name of the profile's model. ModelIds are hashes, which aren't stable across
+    // builds, so the id is looked up by file name on loading, (see: DefaultModelBank::profile).
+    // Profiles saved before this was added fall back to the id in configs.
+    #[serde(default)]
+    model: Option<String>,
+    configs: WhisperConfigs,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+struct Manifest {
+    models: Vec<CustomModelEntry>,
+    #[serde(default)]
+    profiles: Vec<ProfileEntry>,
+}
+
+// Manifests written before profiles were added are a bare list of models.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum StoredManifest {
+    Current(Manifest),
+    Legacy(Vec<CustomModelEntry>),
+}
+
 /// A default [ModelBank] implementation that stores information for a small subset of [DefaultModelType] members.
+/// User models can be registered at runtime with [DefaultModelBank::register_custom_model], and
+/// persisted to a manifest file (requires the serde feature flag).
+/// Named profiles, (e.g. "dictation" -> small.en with greedy sampling), pair a model with the
+/// configurations to run it with, (see: [DefaultModelBank::set_profile]), and are persisted to
+/// the same manifest.
 pub struct DefaultModelBank {
     model_directory: PathBuf,
     models: HashMap<ModelId, Model>,
+    custom_models: HashMap<ModelId, CustomModelSource>,
+    // Profiles are stored with their model's file name, (rather than its id), which is resolved
+    // to the model's current id when the profile is looked up.
+    profiles: BTreeMap<String, (String, WhisperConfigs)>,
+    manifest_path: Option<PathBuf>,
 }
 
 impl DefaultModelBank {
@@ -190,13 +501,314 @@ impl DefaultModelBank {
         Self {
             model_directory: path,
             models,
+            custom_models: HashMap::new(),
+            profiles: BTreeMap::new(),
+            manifest_path: None,
         }
     }
+    /// Sets the directory in which the bank's models are stored.
+    pub fn with_model_directory(mut self, model_directory: PathBuf) -> Self {
+        self.model_directory = model_directory;
+        self
+    }
+
+    /// Stores the bank's models under the platform-standard data directory, namespaced by app_name,
+    /// instead of CWD/data/models. e.g. on Linux: $XDG_DATA_HOME/app_name/models.
+    /// See: [platform_data_directory]
+    /// # Returns:
+    /// * Ok(Self) on success, Err if the platform data directory cannot be determined.
+    pub fn with_platform_data_dir(self, app_name: &str) -> Result<Self, RibbleWhisperError> {
+        let model_directory = platform_data_directory(app_name)?.join("models");
+        Ok(self.with_model_directory(model_directory))
+    }
     pub fn get_model_id(&self, model_type: DefaultModelType) -> ModelId {
         let mut hasher = DefaultHasher::new();
         model_type.hash(&mut hasher);
         hasher.finish()
     }
+
+    /// Sets the manifest file used to persist user-registered models and loads any models it
+    /// already contains. The manifest is rewritten whenever a custom model is registered or removed.
+    /// Requires the serde feature flag to be set.
+    /// # Returns:
+    /// * Ok(Self) on success, Err on an I/O failure or a malformed manifest.
+    #[cfg(feature = "serde")]
+    pub fn with_manifest(mut self, manifest_path: PathBuf) -> Result<Self, RibbleWhisperError> {
+        self.manifest_path = Some(manifest_path);
+        self.load_manifest()?;
+        Ok(self)
+    }
+
+    /// Registers a user model (e.g. a fine-tuned or third-party ggml model) into the bank.
+    /// Registered models work with the same [ModelRetriever] plumbing as the default models.
+    /// # Arguments:
+    /// * name: The user-facing name of the model
+    /// * source: A path to an existing model file, or a url to download into the model directory.
+    /// # Returns:
+    /// * Ok(ModelId) on success
+    /// * Err(RibbleWhisperError) if the path is not a file, the url has no file name, or the
+    ///   manifest fails to save.
+    ///
+    /// Relative paths are made absolute, (against the current directory), so that the model keeps
+    /// resolving to the same file.
+    pub fn register_custom_model(
+        &mut self,
+        name: String,
+        source: CustomModelSource,
+    ) -> Result<ModelId, RibbleWhisperError> {
+        let model_id = self.insert_custom_model(name, source)?;
+        #[cfg(feature = "serde")]
+        self.save_manifest()?;
+        Ok(model_id)
+    }
+
+    /// Gets the source of a user-registered model, or None if the model is not a custom model.
+    pub fn custom_model_source(&self, model_id: ModelId) -> Option<&CustomModelSource> {
+        self.custom_models.get(&model_id)
+    }
+
+    /// Returns true if the model was registered with [DefaultModelBank::register_custom_model].
+    pub fn is_custom_model(&self, model_id: ModelId) -> bool {
+        self.custom_models.contains_key(&model_id)
+    }
+
+    // Returns true if the model was registered by path, (i.e. its file belongs to the user).
+    fn is_user_owned(&self, model_id: ModelId) -> bool {
+        matches!(
+            self.custom_models.get(&model_id),
+            Some(CustomModelSource::Path(_))
+        )
+    }
+
+    fn insert_custom_model(
+        &mut self,
+        name: String,
+        source: CustomModelSource,
+    ) -> Result<ModelId, RibbleWhisperError> {
+        // Path models are stored by their full path, which is joined onto the model directory
+        // when resolving them; a relative path would resolve inside it.
+        let source = match source {
+            CustomModelSource::Path(path) => CustomModelSource::Path(std::path::absolute(path)?),
+            url => url,
+        };
+        if let CustomModelSource::Path(path) = &source
+            && !path.is_file()
+        {
+            return Err(RibbleWhisperError::ParameterError(format!(
+                "Custom model path is not a file: {path:?}"
+            )));
+        }
+        let model = Model::new_with_parameters(name, source.file_name()?);
+        let model_id = self.insert_model(model)?;
+        self.custom_models.insert(model_id, source);
+        Ok(model_id)
+    }
+
+    // Changes a custom model's file name and moves it to the id the new file name hashes to, (see:
+    // ModelBank::insert_model), so that it keeps the same id when reloaded from the manifest.
+    fn change_custom_model_file_name(
+        &mut self,
+        model_id: ModelId,
+        new_file_name: String,
+    ) -> Result<Option<ModelId>, RibbleWhisperError> {
+        let Some(source) = self.custom_models.get(&model_id) else {
+            return Ok(None);
+        };
+        // Path models are stored by their full path, (see: insert_custom_model).
+        let source = match source {
+            CustomModelSource::Path(_) => {
+                let path = std::path::absolute(&new_file_name)?;
+                if !path.is_file() {
+                    return Err(RibbleWhisperError::ParameterError(format!(
+                        "Custom model path is not a file: {path:?}"
+                    )));
+                }
+                CustomModelSource::Path(path)
+            }
+            url => url.clone(),
+        };
+        let new_file_name = match &source {
+            CustomModelSource::Path(_) => source.file_name()?,
+            CustomModelSource::Url(_) => new_file_name,
+        };
+        let Some(mut model) = self.models.remove(&model_id) else {
+            return Ok(None);
+        };
+        self.custom_models.remove(&model_id);
+        model.change_file_name(new_file_name);
+        let new_id = self.insert_model(model)?;
+        self.custom_models.insert(new_id, source);
+        Ok(Some(new_id))
+    }
+
+    /// Stores a named profile, replacing any profile with the same name. The profile runs the
+    /// model set in configs, (see: [WhisperConfigs::with_model_id]), with the rest of configs.
+    /// # Returns:
+    /// * Ok(()) on success
+    /// * Err(RibbleWhisperError) if configs has no model id, the model is not in the bank, or the
+    ///   manifest fails to save.
+    pub fn set_profile(
+        &mut self,
+        name: String,
+        configs: WhisperConfigs,
+    ) -> Result<(), RibbleWhisperError> {
+        let model_id = configs
+            .model_id()
+            .ok_or(RibbleWhisperError::ParameterError(format!(
+                "Profile has no model: {name}"
+            )))?;
+        let file_name = self
+            .models
+            .get(&model_id)
+            .ok_or(RibbleWhisperError::ModelNotFound { id: model_id })?
+            .file_name()
+            .to_string();
+        self.profiles.insert(name, (file_name, configs));
+        #[cfg(feature = "serde")]
+        self.save_manifest()?;
+        Ok(())
+    }
+
+    /// Resolves a named profile to its model and configurations, or None if there is no such
+    /// profile or its model has since been removed from the bank.
+    /// The model is looked up by its file name, so the returned configs carry the model's current
+    /// id, (e.g. after [ModelBank::change_model_file_name], or reloading the manifest).
+    pub fn profile(&self, name: &str) -> Option<(ModelId, WhisperConfigs)> {
+        let (file_name, configs) = self.profiles.get(name)?;
+        self.find_model_id(file_name)
+            .map(|model_id| (model_id, configs.with_model_id(Some(model_id))))
+    }
+
+    // Finds the model stored under file_name.
+    fn find_model_id(&self, file_name: &str) -> Option<ModelId> {
+        self.models
+            .iter()
+            .find(|(_, model)| model.file_name() == file_name)
+            .map(|(model_id, _)| *model_id)
+    }
+
+    // Keeps profiles pointing at a model whose file name has changed.
+    fn update_profile_models(&mut self, old_file_name: &str, new_file_name: &str) {
+        for (file_name, _) in self.profiles.values_mut() {
+            if file_name == old_file_name {
+                *file_name = new_file_name.to_string();
+            }
+        }
+    }
+
+    /// Gets the names of all stored profiles, in order, (e.g. for a mode switch).
+    pub fn profile_names(&self) -> Vec<&str> {
+        self.profiles.keys().map(String::as_str).collect()
+    }
+
+    /// Removes a named profile, returning its configurations if it existed.
+    /// # Returns:
+    /// * Ok(Some(WhisperConfigs)) if the profile was removed, Ok(None) if there was no such profile.
+    /// * Err(RibbleWhisperError) if the manifest fails to save.
+    pub fn remove_profile(
+        &mut self,
+        name: &str,
+    ) -> Result<Option<WhisperConfigs>, RibbleWhisperError> {
+        let removed = self.profiles.remove(name).map(|(_, configs)| configs);
+        #[cfg(feature = "serde")]
+        if removed.is_some() {
+            self.save_manifest()?;
+        }
+        Ok(removed)
+    }
+
+    /// Writes all user-registered models and profiles to the manifest file, if one has been set.
+    /// Requires the serde feature flag to be set.
+    #[cfg(feature = "serde")]
+    pub fn save_manifest(&self) -> Result<(), RibbleWhisperError> {
+        let Some(manifest_path) = self.manifest_path.as_ref() else {
+            return Ok(());
+        };
+        let models = self
+            .custom_models
+            .iter()
+            .filter_map(|(id, source)| {
+                self.models.get(id).map(|model| CustomModelEntry {
+                    name: model.name().to_string(),
+                    source: source.clone(),
+                    file_name: source
+                        .file_name()
+                        .ok()
+                        .filter(|file_name| file_name != model.file_name())
+                        .map(|_| model.file_name().to_string()),
+                })
+            })
+            .collect::<Vec<_>>();
+        let profiles = self
+            .profiles
+            .iter()
+            .map(|(name, (file_name, configs))| ProfileEntry {
+                name: name.clone(),
+                model: Some(file_name.clone()),
+                configs: configs.with_model_id(None),
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(parent) = manifest_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = fs::File::create(manifest_path)?;
+        serde_json::to_writer_pretty(file, &Manifest { models, profiles })?;
+        Ok(())
+    }
+
+    /// Registers all models and profiles stored in the manifest file, if one has been set and it
+    /// exists.
+    /// Requires the serde feature flag to be set.
+    #[cfg(feature = "serde")]
+    pub fn load_manifest(&mut self) -> Result<(), RibbleWhisperError> {
+        let Some(manifest_path) = self.manifest_path.as_ref() else {
+            return Ok(());
+        };
+        if !manifest_path.is_file() {
+            return Ok(());
+        }
+        let file = fs::File::open(manifest_path)?;
+        let manifest = match serde_json::from_reader(file)? {
+            StoredManifest::Current(manifest) => manifest,
+            StoredManifest::Legacy(models) => Manifest {
+                models,
+                ..Default::default()
+            },
+        };
+        for entry in manifest.models {
+            // Path-registered models may have since been moved or deleted; skip them rather than
+            // failing to load the rest of the manifest.
+            let loaded = self
+                .insert_custom_model(entry.name, entry.source)
+                .and_then(|model_id| match entry.file_name {
+                    Some(file_name) => self.change_custom_model_file_name(model_id, file_name),
+                    None => Ok(Some(model_id)),
+                });
+            if let Err(e) = loaded {
+                log_warn_or_eprint!("Failed to load custom model from manifest: {e}");
+            }
+        }
+        // Profiles are kept even if their model failed to load, (see: DefaultModelBank::profile).
+        for entry in manifest.profiles {
+            let file_name = entry.model.or_else(|| {
+                entry
+                    .configs
+                    .model_id()
+                    .and_then(|model_id| self.models.get(&model_id))
+                    .map(|model| model.file_name().to_string())
+            });
+            match file_name {
+                Some(file_name) => {
+                    self.profiles.insert(entry.name, (file_name, entry.configs));
+                }
+                None => {
+                    log_warn_or_eprint!("Failed to resolve the model of profile: {}", entry.name)
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Default for DefaultModelBank {
@@ -249,48 +861,116 @@ impl ModelBank for DefaultModelBank {
         model_id: ModelId,
         new_name: String,
     ) -> Result<Option<ModelId>, RibbleWhisperError> {
-        if let Some(model) = self.models.get_mut(&model_id) {
-            model.rename(new_name);
-            Ok(Some(model_id))
-        } else {
-            Ok(None)
+        let Some(model) = self.models.get_mut(&model_id) else {
+            return Ok(None);
+        };
+        model.rename(new_name);
+        #[cfg(feature = "serde")]
+        if self.is_custom_model(model_id) {
+            self.save_manifest()?;
         }
+        Ok(Some(model_id))
     }
 
+    // Custom models are moved to the id of their new file name, (see:
+    // DefaultModelBank::change_custom_model_file_name); default models keep theirs, (see:
+    // DefaultModelBank::get_model_id).
     fn change_model_file_name(
         &mut self,
         model_id: ModelId,
         new_file_name: String,
     ) -> Result<Option<ModelId>, RibbleWhisperError> {
-        if let Some(model) = self.models.get_mut(&model_id) {
+        let Some(old_file_name) = self
+            .models
+            .get(&model_id)
+            .map(|model| model.file_name().to_string())
+        else {
+            return Ok(None);
+        };
+        let new_id = if self.is_custom_model(model_id) {
+            self.change_custom_model_file_name(model_id, new_file_name)?
+                .unwrap_or(model_id)
+        } else if let Some(model) = self.models.get_mut(&model_id) {
             model.change_file_name(new_file_name);
-            Ok(Some(model_id))
+            model_id
         } else {
-            Ok(None)
+            return Ok(None);
+        };
+        if let Some(new_file_name) = self
+            .models
+            .get(&new_id)
+            .map(|model| model.file_name().to_string())
+        {
+            self.update_profile_models(&old_file_name, &new_file_name);
         }
+        #[cfg(feature = "serde")]
+        self.save_manifest()?;
+        Ok(Some(new_id))
     }
 
     fn remove_model(&mut self, model_id: ModelId) -> Result<Option<ModelId>, RibbleWhisperError> {
         let model = self
             .models
             .get(&model_id)
-            .ok_or(RibbleWhisperError::ParameterError(
-                "Invalid model id supplied to test bank".to_string(),
-            ))?;
+            .ok_or(RibbleWhisperError::ModelNotFound { id: model_id })?;
 
-        if self.model_exists_in_storage(model_id)? {
+        // Models registered by path are owned by the user and are left on disk.
+        if !self.is_user_owned(model_id) && self.model_exists_in_storage(model_id)? {
             let file_path = self.model_directory.join(model.file_name());
             fs::remove_file(&file_path)?;
         }
         self.models.remove(&model_id);
+        #[cfg(feature = "serde")]
+        if self.custom_models.remove(&model_id).is_some() {
+            self.save_manifest()?;
+        }
+        #[cfg(not(feature = "serde"))]
+        self.custom_models.remove(&model_id);
         Ok(Some(model_id))
     }
 
+    // Models registered by path are owned by the user and are left on disk.
+    fn delete_model(&mut self, model_id: ModelId) -> Result<Option<ModelId>, RibbleWhisperError> {
+        let Some(model) = self.models.get(&model_id) else {
+            return Ok(None);
+        };
+        if self.is_user_owned(model_id) || !self.model_exists_in_storage(model_id)? {
+            return Ok(None);
+        }
+        fs::remove_file(self.model_directory.join(model.file_name()))?;
+        Ok(Some(model_id))
+    }
+
+    // Only counts the files the bank manages, (i.e. not models registered by path).
+    fn disk_usage(&self) -> Result<u64, RibbleWhisperError> {
+        let mut total = 0;
+        for (model_id, model) in self.models.iter() {
+            if self.is_user_owned(*model_id) {
+                continue;
+            }
+            total += model_size_on_disk(&self.model_directory, model)?.unwrap_or(0);
+        }
+        Ok(total)
+    }
+
     fn refresh_model_bank(&mut self) -> Result<(), RibbleWhisperError> {
         self.models.clear();
+        self.custom_models.clear();
         Ok(())
     }
 
+    fn download_url(&self, model_id: ModelId) -> Option<String> {
+        match self.custom_models.get(&model_id) {
+            Some(CustomModelSource::Url(url)) => Some(url.clone()),
+            Some(CustomModelSource::Path(_)) => None,
+            None => {
+                let model = self.models.get(&model_id)?;
+                DefaultModelType::from_file_name(model.file_name())
+                    .map(|model_type| model_type.url())
+            }
+        }
+    }
+
     #[cfg(feature = "integrity")]
     fn verify_checksum(
         &mut self,
@@ -302,9 +982,7 @@ impl ModelBank for DefaultModelBank {
         let model = self
             .models
             .get_mut(&model_id)
-            .ok_or(RibbleWhisperError::ParameterError(
-                "Invalid model key supplied to test bank.".to_string(),
-            ))?;
+            .ok_or(RibbleWhisperError::ModelNotFound { id: model_id })?;
 
         if !exists {
             model.checksum_verified = false;
@@ -331,6 +1009,10 @@ pub struct Model {
     file_name: String,
     #[cfg(feature = "integrity")]
     checksum_verified: bool,
+    /// The checksum a downloaded copy of this model is expected to match.
+    #[cfg(feature = "integrity")]
+    #[cfg_attr(feature = "serde", serde(default))]
+    expected_checksum: Option<ModelChecksum>,
 }
 
 impl Model {
@@ -340,6 +1022,8 @@ impl Model {
             file_name: Default::default(),
             #[cfg(feature = "integrity")]
             checksum_verified: false,
+            #[cfg(feature = "integrity")]
+            expected_checksum: None,
         }
     }
 
@@ -383,6 +1067,14 @@ impl Model {
         self.file_name = new_file_name;
     }
 
+    /// Sets the checksum the model file is expected to match.
+    /// Requires the integrity feature flag to be set.
+    #[cfg(feature = "integrity")]
+    pub fn with_expected_checksum(mut self, checksum: Option<ModelChecksum>) -> Self {
+        self.expected_checksum = checksum;
+        self
+    }
+
     /// Gets the model's (checksum) verified status.
     /// Requires the integrity feature flag to be set.
     #[cfg(feature = "integrity")]
@@ -390,51 +1082,46 @@ impl Model {
         self.checksum_verified
     }
 
-    // NOTE:
-    // It should be the responsibility of the Model Bank to handle and mediate all changes to models.
-    // This is reflected in the interface of ModelBank.
-
-    // However, it is the case that all prior self-contained model integrity mechanisms still work,
-    // they are still exposed and can be used for the interim.
-
+    /// Gets the checksum the model file is expected to match, if it is known.
+    /// Requires the integrity feature flag to be set.
     #[cfg(feature = "integrity")]
-    fn compare_sha256(
-        &self,
-        checksum: &str,
-        model_directory: &Path,
-    ) -> Result<bool, RibbleWhisperError> {
-        // Compute the checksum on the file
-        let mut file = fs::File::open(model_directory.join(self.file_name()))?;
-        let mut hasher = Sha256::new();
-        std::io::copy(&mut file, &mut hasher)?;
-        let hash = hasher.finalize();
+    pub fn expected_checksum(&self) -> Option<&ModelChecksum> {
+        self.expected_checksum.as_ref()
+    }
 
-        // Convert to byte string and compare checksums
-        let byte_string = format!("{:x}", hash);
-        let byte_str = byte_string.as_str();
-        Ok(checksum.to_lowercase() == byte_str)
+    /// Gets the model's quantization, as parsed from its file name (e.g. ggml-base.en-q5_1.bin).
+    /// Returns None for full-precision models, (or when the quantization cannot be determined).
+    pub fn quantization(&self) -> Option<ModelQuantization> {
+        let stem = self
+            .file_name
+            .strip_suffix(".bin")
+            .unwrap_or(&self.file_name);
+        let (_, suffix) = stem.rsplit_once('-')?;
+        suffix.parse().ok()
     }
 
-    // TODO: -> move to model bank, args: id + checksum
-    #[cfg(feature = "integrity")]
-    fn compare_sha1(
-        &self,
-        checksum: &str,
-        model_directory: &Path,
-    ) -> Result<bool, RibbleWhisperError> {
-        // TODO: -> move to model bank
-        // Compute the checksum on the file
-        let mut file = fs::File::open(model_directory.join(self.file_name()))?;
-        let mut hasher = Sha1::new();
-        std::io::copy(&mut file, &mut hasher)?;
-        let hash = hasher.finalize();
+    /// Gets the file (or directory) name whisper.cpp expects the model's [EncoderCompanion] to
+    /// have, e.g. ggml-base.en-encoder.mlmodelc for ggml-base.en.bin
+    pub fn encoder_companion_file_name(&self, companion: EncoderCompanion) -> String {
+        let stem = self
+            .file_name
+            .strip_suffix(".bin")
+            .unwrap_or(&self.file_name);
+        [stem, companion.file_suffix()].concat()
+    }
 
-        // Convert to byte string and compare checksums
-        let byte_string = format!("{:x}", hash);
-        let byte_str = byte_string.as_str();
-        Ok(checksum.to_lowercase() == byte_str)
+    /// Returns false if the model is English-only (i.e. its file name contains ".en").
+    pub fn is_multilingual(&self) -> bool {
+        !self.file_name.contains(".en")
     }
 
+    // NOTE:
+    // It should be the responsibility of the Model Bank to handle and mediate all changes to models.
+    // This is reflected in the interface of ModelBank.
+
+    // However, it is the case that all prior self-contained model integrity mechanisms still work,
+    // they are still exposed and can be used for the interim.
+
     /// # Deprecated: These will eventually become utility functions which can be used in a [ModelBank] or similar to handle checksums.
     /// For verifying file integrity against a user-provided checksum.
     /// Since a Model file can come from anywhere, responsibility falls upon the user to ensure
@@ -449,10 +1136,7 @@ impl Model {
         model_directory: &Path,
         checksum: &Checksum,
     ) -> Result<bool, RibbleWhisperError> {
-        let is_equal = match checksum {
-            Checksum::Sha1(c) => self.compare_sha1(c, model_directory),
-            Checksum::Sha256(c) => self.compare_sha256(c, model_directory),
-        };
+        let is_equal = verify_file_checksum(&model_directory.join(self.file_name()), checksum);
 
         match is_equal {
             Ok(equal) => {
@@ -473,8 +1157,126 @@ impl Default for Model {
     }
 }
 
+/// Accelerated encoders that whisper.cpp loads from companion files placed next to a ggml model.
+/// CoreML requires the coreml feature flag; OpenVINO requires a whisper.cpp build with OpenVINO support.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, AsRefStr, Display, EnumIter, IntoStaticStr)]
+pub enum EncoderCompanion {
+    /// A compiled CoreML encoder, (a .mlmodelc directory).
+    CoreMl,
+    /// An OpenVINO IR encoder, (an .xml file with an accompanying .bin).
+    OpenVino,
+}
+
+impl EncoderCompanion {
+    fn file_suffix(&self) -> &'static str {
+        match self {
+            EncoderCompanion::CoreMl => "-encoder.mlmodelc",
+            EncoderCompanion::OpenVino => "-encoder-openvino.xml",
+        }
+    }
+}
+
+/// The quantization types used in ggml whisper model files.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Hash, AsRefStr, EnumIter, EnumString, Display, IntoStaticStr,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum ModelQuantization {
+    F32,
+    F16,
+    #[strum(serialize = "q4_0")]
+    Q4_0,
+    #[strum(serialize = "q4_1")]
+    Q4_1,
+    #[strum(serialize = "q5_0")]
+    Q5_0,
+    #[strum(serialize = "q5_1")]
+    Q5_1,
+    #[strum(serialize = "q8_0")]
+    Q8_0,
+    #[strum(serialize = "q2_k")]
+    Q2K,
+    #[strum(serialize = "q3_k")]
+    Q3K,
+    #[strum(serialize = "q4_k")]
+    Q4K,
+    #[strum(serialize = "q5_k")]
+    Q5K,
+    #[strum(serialize = "q6_k")]
+    Q6K,
+}
+
+impl ModelQuantization {
+    /// Maps a ggml file type (the ftype hyperparameter stored in a model's header) to its
+    /// quantization. The quantization version should already be stripped from ftype.
+    pub fn from_ggml_ftype(ftype: i32) -> Option<Self> {
+        match ftype {
+            0 => Some(Self::F32),
+            1 => Some(Self::F16),
+            2 => Some(Self::Q4_0),
+            3 | 4 => Some(Self::Q4_1),
+            7 => Some(Self::Q8_0),
+            8 => Some(Self::Q5_0),
+            9 => Some(Self::Q5_1),
+            10 => Some(Self::Q2K),
+            11 => Some(Self::Q3K),
+            12 => Some(Self::Q4K),
+            13 => Some(Self::Q5K),
+            14 => Some(Self::Q6K),
+            _ => None,
+        }
+    }
+}
+
+/// Information about a [Model] in a [ModelBank], for building model-management interfaces.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModelMetadata {
+    /// The size of the model file in bytes, or None if the model is not in storage.
+    pub size_on_disk: Option<u64>,
+    /// The model's quantization, or None for full-precision models.
+    pub quantization: Option<ModelQuantization>,
+    /// False for English-only (.en) models.
+    pub multilingual: bool,
+}
+
+impl ModelMetadata {
+    /// Inspects a model stored in model_directory.
+    /// # Returns:
+    /// * Ok(ModelMetadata) on success, Err on an I/O failure other than the file not existing.
+    pub fn from_model(model: &Model, model_directory: &Path) -> Result<Self, RibbleWhisperError> {
+        Ok(Self {
+            size_on_disk: model_size_on_disk(model_directory, model)?,
+            quantization: model.quantization(),
+            multilingual: model.is_multilingual(),
+        })
+    }
+
+    pub fn in_storage(&self) -> bool {
+        self.size_on_disk.is_some()
+    }
+}
+
+// Returns None if the model's file does not exist.
+fn model_size_on_disk(
+    model_directory: &Path,
+    model: &Model,
+) -> Result<Option<u64>, RibbleWhisperError> {
+    match fs::metadata(model_directory.join(model.file_name())) {
+        Ok(m) if m.is_file() => Ok(Some(m.len())),
+        Ok(_) => Ok(None),
+        Err(e) => match e.kind() {
+            std::io::ErrorKind::NotFound => Ok(None),
+            _ => Err(e.into()),
+        },
+    }
+}
+
 /// Encapsulates a series of base models available for download and use with Ribble-Whisper
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
 #[derive(
     Copy,
     Clone,
@@ -530,6 +1332,11 @@ impl DefaultModelType {
         }
     }
 
+    /// Gets the [DefaultModelType] with the matching (ggml) file name, if one exists.
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        Self::iter().find(|model_type| model_type.to_file_name() == file_name)
+    }
+
     /// Due to legacy implementation details, this method exists to provide a way to transition
     /// over to the new scheme.
     pub fn old_file_name(&self) -> String {
@@ -554,11 +1361,71 @@ impl DefaultModelType {
         }
     }
 
+    /// The sha256 digest of each model file, as published in huggingface's LFS metadata.
+    /// If a model is ever re-uploaded, use [DefaultModelType::get_sha256_checksum] to retrieve the
+    /// latest.
+    #[cfg(feature = "integrity")]
+    pub fn expected_sha256(&self) -> &'static str {
+        match self {
+            DefaultModelType::TinyEn => {
+                "921e4cf8686fdd993dcd081a5da5b6c365bfde1162e72b08d75ac75289920b1f"
+            }
+            DefaultModelType::Tiny => {
+                "be07e048e1e599ad46341c8d2a135645097a538221678b7acdd1b1919c6e1b21"
+            }
+            DefaultModelType::BaseEn => {
+                "a03779c86df3323075f5e796cb2ce5029f00ec8869eee3fdfb897afe36c6d002"
+            }
+            DefaultModelType::Base => {
+                "60ed5bc3dd14eea856493d334349b405782ddcaf0028d4b5df4088345fba2efe"
+            }
+            DefaultModelType::SmallEn => {
+                "c6138d6d58ecc8322097e0f987c32f1be8bb0a18532a3f88f734d1bbf9c41e5d"
+            }
+            DefaultModelType::Small => {
+                "1be3a9b2063867b937e64e2ec7483364a79917e157fa98c5d94b5c1fffea987b"
+            }
+            DefaultModelType::MediumEn => {
+                "cc37e93478338ec7700281a7ac30a10128929eb8f427dda2e865faa8f6da4356"
+            }
+            DefaultModelType::Medium => {
+                "6c14d5adee5f86394037b4e4e8b59f1673b6cee10e3cf0b11bbdbee79c156208"
+            }
+            DefaultModelType::LargeV1 => {
+                "7d99f41a10525d0206bddadd86760181fa920438b6b33237e3118ff6c83bb53d"
+            }
+            DefaultModelType::LargeV2 => {
+                "9a423fe4d40c82774b6af34115b8b935f34152246eb19e80e376071d3f999487"
+            }
+            DefaultModelType::LargeV3 => {
+                "64d182b440b98d5203c4f9bd541544d84c605196c4f7b845dfa11fb23594d1e2"
+            }
+            DefaultModelType::LargeV3Turbo => {
+                "1fc70f774d38eb169993ac391eea357ef47c88757ef72ee5943879b7e8e2bc69"
+            }
+        }
+    }
+
+    /// Gets the expected (sha256) checksum stored with this model type.
+    #[cfg(feature = "integrity")]
+    pub fn expected_checksum(&self) -> ModelChecksum {
+        ModelChecksum::Sha256(self.expected_sha256().to_string())
+    }
+
     /// Constructs a model object with a "default" file-name and user-facing name
+    /// When the integrity feature is enabled, the model also carries its expected checksum.
     pub fn to_model(&self) -> Model {
-        Model::new()
+        let model = Model::new()
             .with_name(self.to_string())
-            .with_file_name(self.to_file_name().to_string())
+            .with_file_name(self.to_file_name().to_string());
+        #[cfg(feature = "integrity")]
+        {
+            model.with_expected_checksum(Some(self.expected_checksum()))
+        }
+        #[cfg(not(feature = "integrity"))]
+        {
+            model
+        }
     }
 
     /// Canonicalizes a download url to retrieve the model from huggingface.
@@ -569,6 +1436,20 @@ impl DefaultModelType {
         [URL_PREFIX, file_name].concat()
     }
 
+    /// Gets the download urls for this model in order of preference: huggingface, followed by
+    /// its mirrors. See: [crate::downloader::request::DownloadRequest::for_model]
+    pub fn mirror_urls(&self) -> Vec<String> {
+        const MIRROR_PREFIXES: [&str; 1] =
+            ["https://hf-mirror.com/ggerganov/whisper.cpp/resolve/main/"];
+        std::iter::once(self.url())
+            .chain(
+                MIRROR_PREFIXES
+                    .iter()
+                    .map(|prefix| [prefix, self.to_file_name()].concat()),
+            )
+            .collect()
+    }
+
     /// To canonicalize the huggingface url for downloading the appropriate coreml zip
     /// This will need to be extracted into the same directory as an accompanying model
     /// NOTE: Do not strip away the top level directory when extracting:
@@ -577,8 +1458,18 @@ impl DefaultModelType {
     /// e.g. models/ggml-base.en-encoder.mlmodelc AND models/ggml-base.en.bin
     #[cfg(feature = "coreml")]
     pub fn coreml_zip_url(&self) -> String {
-        let url = self.url();
-        url.replace(".bin", "-encoder.mlmodelc.zip")
+        self.encoder_companion_url(EncoderCompanion::CoreMl)
+            .unwrap()
+    }
+
+    /// Gets the url of a zipped [EncoderCompanion] for this model, if one is hosted.
+    /// Only CoreML encoders are published on huggingface; OpenVINO encoders must be generated with
+    /// whisper.cpp's conversion script, (or downloaded from a user-provided url).
+    pub fn encoder_companion_url(&self, companion: EncoderCompanion) -> Option<String> {
+        match companion {
+            EncoderCompanion::CoreMl => Some(self.url().replace(".bin", "-encoder.mlmodelc.zip")),
+            EncoderCompanion::OpenVino => None,
+        }
     }
 
     /// Makes a blocking request to check the sha1 checksums for each of the provided default models
@@ -623,6 +1514,25 @@ impl DefaultModelType {
         )?;
         Ok(model_checksum)
     }
+
+    /// Makes a blocking request for the model's sha256 checksum from huggingface's LFS metadata.
+    /// Requires the integrity feature flag to be set.
+    /// # Arguments:
+    /// * client: An optional reference to a (blocking) Reqwest client. Supply None if client-reuse is not a concern.
+    /// # Returns:
+    /// * Ok(checksum) on success
+    /// * Err on network failure, or if the model is missing from the repository
+    #[cfg(feature = "integrity")]
+    pub fn get_sha256_checksum(
+        &self,
+        client: Option<&blocking::Client>,
+    ) -> Result<String, RibbleWhisperError> {
+        let file_name = self.to_file_name();
+        get_sha256_checksum(file_name, client)?.ok_or(RibbleWhisperError::ParameterError(format!(
+            "Failed to find sha256 for: {}",
+            file_name
+        )))
+    }
 }
 
 #[cfg(feature = "integrity")]
@@ -630,3 +1540,39 @@ pub enum Checksum<'a> {
     Sha1(&'a str),
     Sha256(&'a str),
 }
+
+#[cfg(feature = "integrity")]
+impl Checksum<'_> {
+    /// Gets the expected digest as a hex string.
+    pub fn digest(&self) -> &str {
+        match self {
+            Checksum::Sha1(c) => c,
+            Checksum::Sha256(c) => c,
+        }
+    }
+}
+
+/// An owned [Checksum], for storing expected checksums alongside a [Model].
+#[cfg(feature = "integrity")]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModelChecksum {
+    Sha1(String),
+    Sha256(String),
+}
+
+#[cfg(feature = "integrity")]
+impl ModelChecksum {
+    pub fn as_checksum(&self) -> Checksum<'_> {
+        match self {
+            ModelChecksum::Sha1(c) => Checksum::Sha1(c.as_str()),
+            ModelChecksum::Sha256(c) => Checksum::Sha256(c.as_str()),
+        }
+    }
+    pub fn digest(&self) -> &str {
+        match self {
+            ModelChecksum::Sha1(c) => c,
+            ModelChecksum::Sha256(c) => c,
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::transcriber::build_whisper_context;
+use crate::utils::errors::RibbleWhisperError;
+use crate::utils::logging::log_warn;
+use crate::whisper::model::{ModelId, ModelLocation, ModelRetriever};
+use crate::whisper::model_info::probe_model;
+
+/// The default memory budget for a [SharedModelCache]: 4 GiB
+pub const DEFAULT_MODEL_CACHE_BUDGET: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Usage information for a model held in a [SharedModelCache].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CacheUsage {
+    /// The number of outstanding references handed out by the cache that are still alive.
+    pub references: usize,
+    /// The (estimated) memory used by the cached model, in bytes.
+    pub size: u64,
+    /// The number of times the cached model has been requested.
+    pub hits: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum CacheKey {
+    Buffer(ModelId),
+    Context(ModelId),
+}
+
+impl CacheKey {
+    fn model_id(&self) -> ModelId {
+        match self {
+            CacheKey::Buffer(id) | CacheKey::Context(id) => *id,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum CachedModel {
+    Buffer(Arc<[u8]>),
+    Context(Arc<whisper_rs::WhisperContext>),
+}
+
+impl CachedModel {
+    // Excludes the reference held by the cache.
+    fn references(&self) -> usize {
+        match self {
+            CachedModel::Buffer(buf) => Arc::strong_count(buf) - 1,
+            CachedModel::Context(ctx) => Arc::strong_count(ctx) - 1,
+        }
+    }
+}
+
+struct CacheEntry {
+    model: CachedModel,
+    size: u64,
+    hits: usize,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    // A logical clock to order entries by recency.
+    clock: u64,
+}
+
+impl CacheState {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn get(&mut self, key: CacheKey) -> Option<CachedModel> {
+        let now = self.tick();
+        self.entries.get_mut(&key).map(|entry| {
+            entry.hits += 1;
+            entry.last_used = now;
+            entry.model.clone()
+        })
+    }
+
+    // If another thread finished loading the same model first, its copy is kept and returned.
+    fn insert(&mut self, key: CacheKey, model: CachedModel, size: u64) -> CachedModel {
+        if let Some(existing) = self.get(key) {
+            return existing;
+        }
+        let now = self.tick();
+        self.entries.insert(
+            key,
+            CacheEntry {
+                model: model.clone(),
+                size,
+                hits: 1,
+                last_used: now,
+            },
+        );
+        model
+    }
+
+    fn memory_usage(&self) -> u64 {
+        self.entries.values().map(|entry| entry.size).sum()
+    }
+
+    // Evicts least-recently-used models until the cache fits within the budget.
+    // Models that are still in use are never evicted, so the cache may remain over budget
+    // until their references are dropped.
+    fn evict_to_budget(&mut self, budget: u64, keep: CacheKey) {
+        while self.memory_usage() > budget {
+            let lru = self
+                .entries
+                .iter()
+                .filter(|(key, entry)| **key != keep && entry.model.references() == 0)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key);
+            match lru {
+                Some(key) => {
+                    self.entries.remove(&key);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+/// A thread-safe cache that hands out reference-counted model buffers and whisper contexts keyed by
+/// [ModelId]. When the cache exceeds its memory budget, least-recently-used models that are no
+/// longer referenced are evicted. This is intended for applications that switch between models
+/// frequently and would otherwise reload them from disk each time.
+///
+/// The cache is also a [ModelRetriever]; transcribers built with it receive shared buffers, so a
+/// model is only read from disk once while it remains cached.
+///
+/// NOTE: contexts are built with the parameters of the first request for a given model. Call
+/// [SharedModelCache::evict] to rebuild a context with different parameters.
+pub struct SharedModelCache<M: ModelRetriever> {
+    retriever: M,
+    memory_budget: u64,
+    state: Mutex<CacheState>,
+}
+
+impl<M: ModelRetriever> SharedModelCache<M> {
+    /// Creates a cache over the supplied retriever with [DEFAULT_MODEL_CACHE_BUDGET]
+    pub fn new(retriever: M) -> Self {
+        Self {
+            retriever,
+            memory_budget: DEFAULT_MODEL_CACHE_BUDGET,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Sets the memory budget in bytes.
+    pub fn with_memory_budget(mut self, memory_budget: u64) -> Self {
+        self.memory_budget = memory_budget;
+        self
+    }
+
+    pub fn memory_budget(&self) -> u64 {
+        self.memory_budget
+    }
+
+    /// Gets the (estimated) memory currently held by the cache, in bytes.
+    pub fn memory_usage(&self) -> u64 {
+        self.state.lock().memory_usage()
+    }
+
+    /// Gets the ModelIds of all models held in the cache (as either a buffer or a context).
+    pub fn cached_models(&self) -> Vec<ModelId> {
+        let mut ids = self
+            .state
+            .lock()
+            .entries
+            .keys()
+            .map(|key| key.model_id())
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Gets usage information for a cached model context, or None if it is not cached.
+    pub fn context_usage(&self, model_id: ModelId) -> Option<CacheUsage> {
+        self.usage(CacheKey::Context(model_id))
+    }
+
+    /// Gets usage information for a cached model buffer, or None if it is not cached.
+    pub fn buffer_usage(&self, model_id: ModelId) -> Option<CacheUsage> {
+        self.usage(CacheKey::Buffer(model_id))
+    }
+
+    fn usage(&self, key: CacheKey) -> Option<CacheUsage> {
+        self.state.lock().entries.get(&key).map(|entry| CacheUsage {
+            references: entry.model.references(),
+            size: entry.size,
+            hits: entry.hits,
+        })
+    }
+
+    /// Removes a model's buffer and context from the cache. Outstanding references remain valid;
+    /// the memory is released once they are dropped.
+    pub fn evict(&self, model_id: ModelId) {
+        let mut state = self.state.lock();
+        state.entries.remove(&CacheKey::Buffer(model_id));
+        state.entries.remove(&CacheKey::Context(model_id));
+    }
+
+    /// Removes all models from the cache.
+    pub fn clear(&self) {
+        self.state.lock().entries.clear();
+    }
+
+    /// Gets a shared buffer containing the model file, reading it from storage if it is not cached.
+    /// # Returns:
+    /// * Ok(`Arc<[u8]>`) on success
+    /// * Err(RibbleWhisperError) if the model cannot be retrieved, or on I/O failure.
+    pub fn get_buffer(&self, model_id: ModelId) -> Result<Arc<[u8]>, RibbleWhisperError> {
+        let key = CacheKey::Buffer(model_id);
+        if let Some(CachedModel::Buffer(buf)) = self.state.lock().get(key) {
+            return Ok(buf);
+        }
+
+        // Load outside of the lock so that other models can still be retrieved.
+        let buf: Arc<[u8]> = match self.retrieve(model_id)? {
+            ModelLocation::StaticFilePath(path) => std::fs::read(path)?.into(),
+            ModelLocation::DynamicFilePath(path) => std::fs::read(path)?.into(),
+            ModelLocation::StaticBuffer(buf) => buf.into(),
+            ModelLocation::DynamicBuffer(buf) => buf.into(),
+            ModelLocation::SharedBuffer(buf) => buf,
+        };
+        let size = buf.len() as u64;
+        match self.insert(key, CachedModel::Buffer(buf), size) {
+            CachedModel::Buffer(buf) => Ok(buf),
+            CachedModel::Context(_) => unreachable!("Buffer keys only hold buffers."),
+        }
+    }
+
+    /// Gets a shared whisper context for the model, building it if it is not cached.
+    /// # Arguments:
+    /// * model_id: the model to load
+    /// * params: the context parameters, see: [crate::whisper::configs::WhisperConfigs::as_whisper_context_params]
+    /// # Returns:
+    /// * Ok(`Arc<WhisperContext>`) on success
+    /// * Err(RibbleWhisperError) if the model cannot be retrieved or whisper fails to load it.
+    pub fn get_context(
+        &self,
+        model_id: ModelId,
+        params: whisper_rs::WhisperContextParameters,
+    ) -> Result<Arc<whisper_rs::WhisperContext>, RibbleWhisperError> {
+        let key = CacheKey::Context(model_id);
+        if let Some(CachedModel::Context(ctx)) = self.state.lock().get(key) {
+            return Ok(ctx);
+        }
+
+        let location = self.retrieve(model_id)?;
+        let size = estimate_context_size(&location);
+        let ctx = Arc::new(build_whisper_context(location, params)?);
+        match self.insert(key, CachedModel::Context(ctx), size) {
+            CachedModel::Context(ctx) => Ok(ctx),
+            CachedModel::Buffer(_) => unreachable!("Context keys only hold contexts."),
+        }
+    }
+
+    fn retrieve(&self, model_id: ModelId) -> Result<ModelLocation, RibbleWhisperError> {
+        self.retriever
+            .retrieve_model(model_id)
+            .ok_or(RibbleWhisperError::ModelNotFound { id: model_id })
+    }
+
+    fn insert(&self, key: CacheKey, model: CachedModel, size: u64) -> CachedModel {
+        let mut state = self.state.lock();
+        let model = state.insert(key, model, size);
+        state.evict_to_budget(self.memory_budget, key);
+        model
+    }
+}
+
+impl<M: ModelRetriever> ModelRetriever for SharedModelCache<M> {
+    fn retrieve_model(&self, model_id: ModelId) -> Option<ModelLocation> {
+        self.get_buffer(model_id)
+            .map(ModelLocation::SharedBuffer)
+            .inspect_err(|_e| {
+                log_warn!("Failed to cache model: {model_id}. Error: {_e}");
+            })
+            .ok()
+    }
+}
+
+// Contexts from files are sized using the header-based estimate; buffers fall back to their length.
+fn estimate_context_size(location: &ModelLocation) -> u64 {
+    let path = match location {
+        ModelLocation::StaticFilePath(path) => *path,
+        ModelLocation::DynamicFilePath(path) => path.as_path(),
+        ModelLocation::StaticBuffer(buf) => return buf.len() as u64,
+        ModelLocation::DynamicBuffer(buf) => return buf.len() as u64,
+        ModelLocation::SharedBuffer(buf) => return buf.len() as u64,
+    };
+    probe_model(path)
+        .map(|info| info.estimated_memory)
+        .or_else(|_| std::fs::metadata(path).map(|m| m.len()))
+        .unwrap_or(0)
+}
@@ -0,0 +1,247 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use strum::{AsRefStr, Display, EnumIs, EnumIter, IntoStaticStr};
+
+use crate::utils::errors::RibbleWhisperError;
+use crate::whisper::configs::{WhisperConfigs, WhisperSamplingStrategy};
+use crate::whisper::model::ModelQuantization;
+
+// "ggml" as a little-endian u32.
+const GGML_MAGIC: u32 = 0x67676d6c;
+// "GGUF" as little-endian bytes.
+const GGUF_MAGIC: &[u8; 4] = b"GGUF";
+// Quantized ftypes are stored as ftype + quantization_version * GGML_QNT_VERSION_FACTOR
+const GGML_QNT_VERSION_FACTOR: i32 = 1000;
+// Whisper.cpp treats any vocabulary at least this large as multilingual.
+const MULTILINGUAL_N_VOCAB: i32 = 51865;
+// The size in bytes of the (f16) kv-cache entries.
+const KV_CACHE_ELEMENT_SIZE: u64 = 2;
+const KV_CACHE_PADDING: u64 = 256;
+// A rough multiplier of the encoder's final activation size to approximate its compute buffers.
+const ENCODER_COMPUTE_FACTOR: u64 = 16;
+
+/// The family of a whisper model, derived from its encoder/decoder layer counts.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Hash, AsRefStr, Display, EnumIs, EnumIter, IntoStaticStr,
+)]
+pub enum ModelSize {
+    Tiny,
+    Base,
+    Small,
+    Medium,
+    Large,
+    /// Large-v3-turbo: a large encoder with a (much smaller) 4-layer decoder.
+    LargeTurbo,
+    Unknown,
+}
+
+/// The hyperparameters stored in a ggml whisper model header.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ModelHyperparameters {
+    pub n_vocab: i32,
+    pub n_audio_ctx: i32,
+    pub n_audio_state: i32,
+    pub n_audio_head: i32,
+    pub n_audio_layer: i32,
+    pub n_text_ctx: i32,
+    pub n_text_state: i32,
+    pub n_text_head: i32,
+    pub n_text_layer: i32,
+    pub n_mels: i32,
+    pub ftype: i32,
+}
+
+/// Information about a model file, obtained by [probe_model] without loading the model.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModelInfo {
+    pub model_size: ModelSize,
+    /// None if the file type is not recognized.
+    pub quantization: Option<ModelQuantization>,
+    /// False for English-only (.en) models.
+    pub multilingual: bool,
+    pub hyperparameters: ModelHyperparameters,
+    /// The size of the model file in bytes.
+    pub file_size: u64,
+    /// A rough estimate of the memory (RAM or VRAM) in bytes required to load and run the model:
+    /// the model weights, plus the decoder's kv-caches and (approximate) compute buffers.
+    /// This is only intended for warnings, e.g. "this model needs ~5 GB of VRAM".
+    /// This assumes the default configurations; use [estimate_memory] for a specific
+    /// configuration and a CPU/GPU breakdown.
+    pub estimated_memory: u64,
+}
+
+/// Reads the header of a ggml whisper model to report its type, quantization, vocabulary and an
+/// estimate of its memory requirements without loading it.
+/// # Arguments:
+/// * path: the path to the model file
+/// # Returns:
+/// * Ok(ModelInfo) on success
+/// * Err(RibbleWhisperError::ModelError) if the file is not a ggml whisper model, (GGUF files are
+///   not supported by whisper.cpp), or Err(RibbleWhisperError::IOError) on I/O failure.
+pub fn probe_model(path: &Path) -> Result<ModelInfo, RibbleWhisperError> {
+    let file = File::open(path)?;
+    let file_size = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic == GGUF_MAGIC {
+        return Err(RibbleWhisperError::ModelError(format!(
+            "{path:?} is a GGUF file. Whisper models must be in the ggml format."
+        )));
+    }
+    if u32::from_le_bytes(magic) != GGML_MAGIC {
+        return Err(RibbleWhisperError::ModelError(format!(
+            "{path:?} is not a ggml model file."
+        )));
+    }
+
+    let mut read_i32 = || -> Result<i32, RibbleWhisperError> {
+        let mut buf = [0u8; 4];
+        reader.read_exact(&mut buf)?;
+        Ok(i32::from_le_bytes(buf))
+    };
+
+    let hyperparameters = ModelHyperparameters {
+        n_vocab: read_i32()?,
+        n_audio_ctx: read_i32()?,
+        n_audio_state: read_i32()?,
+        n_audio_head: read_i32()?,
+        n_audio_layer: read_i32()?,
+        n_text_ctx: read_i32()?,
+        n_text_state: read_i32()?,
+        n_text_head: read_i32()?,
+        n_text_layer: read_i32()?,
+        n_mels: read_i32()?,
+        ftype: read_i32()?,
+    };
+
+    let mut info = ModelInfo {
+        model_size: model_size(&hyperparameters),
+        quantization: ModelQuantization::from_ggml_ftype(
+            hyperparameters.ftype % GGML_QNT_VERSION_FACTOR,
+        ),
+        multilingual: hyperparameters.n_vocab >= MULTILINGUAL_N_VOCAB,
+        hyperparameters,
+        file_size,
+        estimated_memory: 0,
+    };
+    info.estimated_memory = estimate_memory(&info, &WhisperConfigs::default()).total();
+    Ok(info)
+}
+
+fn model_size(hparams: &ModelHyperparameters) -> ModelSize {
+    match (hparams.n_audio_layer, hparams.n_text_layer) {
+        (4, _) => ModelSize::Tiny,
+        (6, _) => ModelSize::Base,
+        (12, _) => ModelSize::Small,
+        (24, _) => ModelSize::Medium,
+        (32, 4) => ModelSize::LargeTurbo,
+        (32, _) => ModelSize::Large,
+        _ => ModelSize::Unknown,
+    }
+}
+
+/// A breakdown of the memory, in bytes, needed to load and run a model with a given set of
+/// configurations, (see: [estimate_memory]).
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// The model weights, which are roughly the size of the model file.
+    pub weights: u64,
+    /// The decoder's self-attention and cross-attention caches, for every decoder.
+    pub kv_cache: u64,
+    /// The (approximate) encoder and decoder compute buffers.
+    pub compute: u64,
+    /// The extra padding cache allocated when flash attention is enabled.
+    pub flash_attention: u64,
+    /// The portion of the total expected to be allocated in system memory.
+    pub cpu: u64,
+    /// The portion of the total expected to be allocated in device memory (VRAM); 0 when the GPU
+    /// is not used.
+    pub gpu: u64,
+}
+
+impl MemoryEstimate {
+    pub fn total(&self) -> u64 {
+        self.cpu + self.gpu
+    }
+
+    /// Returns true if the estimate fits within the available system and device memory.
+    pub fn fits_within(&self, available_cpu: u64, available_gpu: u64) -> bool {
+        self.cpu <= available_cpu && self.gpu <= available_gpu
+    }
+}
+
+/// Estimates the memory required to load info's model and transcribe with configs, so that
+/// applications can warn or refuse before loading a model the machine cannot hold.
+/// This mirrors whisper.cpp's allocations, but it is only an estimate: backends add their own
+/// overhead, and the weights are assumed to be the size of the model file.
+/// # Arguments:
+/// * info: the model's header information, (see: [probe_model])
+/// * configs: the configurations the model will be run with
+/// # Returns:
+/// * The estimated memory, split between system and device memory
+pub fn estimate_memory(info: &ModelInfo, configs: &WhisperConfigs) -> MemoryEstimate {
+    let hparams = &info.hyperparameters;
+    let dim = |value: i32| value.max(0) as u64;
+    let n_text_layer = dim(hparams.n_text_layer);
+    let n_text_state = dim(hparams.n_text_state);
+    let n_audio_ctx = dim(hparams.n_audio_ctx);
+    let n_audio_state = dim(hparams.n_audio_state);
+
+    // Whisper.cpp grows the self-attention cache when running more than one decoder, with room
+    // for two extra sequences.
+    let n_decoders = match configs.sampling_strategy() {
+        WhisperSamplingStrategy::Greedy { best_of } => best_of,
+        WhisperSamplingStrategy::BeamSearch { beam_size, .. } => beam_size,
+    }
+    .max(1) as u64;
+    let kv_factor = if n_decoders > 1 { n_decoders + 2 } else { 1 };
+
+    // Self-attention and cross-attention caches, (one key and one value tensor each).
+    let kv_cells = |n_ctx: u64| 2 * n_text_layer * pad_kv_cells(n_ctx) * n_text_state;
+    let kv_self = kv_factor * kv_cells(dim(hparams.n_text_ctx)) * KV_CACHE_ELEMENT_SIZE;
+    let kv_cross = kv_cells(n_audio_ctx) * KV_CACHE_ELEMENT_SIZE;
+
+    let f32_size = size_of::<f32>() as u64;
+    let mut compute = n_audio_ctx * n_audio_state * f32_size * ENCODER_COMPUTE_FACTOR;
+    // Without flash attention, the encoder materializes its attention scores.
+    if !configs.using_flash_attention() {
+        compute += dim(hparams.n_audio_head) * n_audio_ctx * n_audio_ctx * f32_size;
+    }
+
+    let flash_attention = if configs.using_flash_attention() {
+        2 * pad_kv_cells(n_audio_ctx) * n_audio_state * KV_CACHE_ELEMENT_SIZE
+    } else {
+        0
+    };
+
+    // The mel spectrogram is always computed on the CPU: n_mels * (2 * n_audio_ctx) frames.
+    let mel = dim(hparams.n_mels) * 2 * n_audio_ctx * f32_size;
+    let device = info.file_size + kv_self + kv_cross + compute + flash_attention;
+    let (cpu, gpu) = if configs.using_gpu() {
+        (mel, device)
+    } else {
+        (mel + device, 0)
+    };
+
+    MemoryEstimate {
+        weights: info.file_size,
+        kv_cache: kv_self + kv_cross,
+        compute,
+        flash_attention,
+        cpu,
+        gpu,
+    }
+}
+
+// Whisper.cpp pads kv-caches to a multiple of 256 cells.
+fn pad_kv_cells(n_ctx: u64) -> u64 {
+    n_ctx.div_ceil(KV_CACHE_PADDING) * KV_CACHE_PADDING
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod audio_archive_tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use ribble_whisper::audio::archive::{
+        ArchiveManifest, ArchiveSampleFormat, AudioArchiver, RotationPolicy,
+    };
+
+    fn archive_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_rotates_by_duration() {
+        let dir = archive_dir("ribble_archive_duration");
+        let mut archiver = AudioArchiver::create(&dir, 100, 1)
+            .expect("Archive should be created without issue.")
+            .with_rotation(RotationPolicy::new().with_max_duration(Duration::from_secs(1)));
+        let audio: Vec<f32> = (0..250).map(|idx| (idx % 50) as f32 / 50.0).collect();
+        archiver
+            .push(&audio[..120])
+            .expect("Audio should be archived without issue.");
+        archiver
+            .push(&audio[120..])
+            .expect("Audio should be archived without issue.");
+        let manifest = archiver
+            .finish()
+            .expect("Archive should finish without issue.");
+
+        let frames: Vec<(u64, u64)> = manifest
+            .files
+            .iter()
+            .map(|file| (file.start_frame, file.frames))
+            .collect();
+        assert_eq!(frames, vec![(0, 100), (100, 100), (200, 50)]);
+        assert_eq!(manifest.duration(), Duration::from_millis(2500));
+        assert_eq!(
+            ArchiveManifest::load(&dir).expect("Manifest should load without issue."),
+            manifest
+        );
+
+        // Every file should be a valid WAV holding its share of the audio.
+        let mut read = vec![];
+        for file in &manifest.files {
+            let reader = hound::WavReader::open(dir.join(&file.file_name))
+                .expect("Archived files should be valid WAV files.");
+            assert_eq!(reader.spec().sample_rate, 100);
+            assert_eq!(reader.len() as u64, file.frames);
+            read.extend(
+                reader
+                    .into_samples::<i16>()
+                    .map(|sample| sample.unwrap() as f32 / i16::MAX as f32),
+            );
+        }
+        assert!(
+            read.iter()
+                .zip(&audio)
+                .all(|(read, written)| (read - written).abs() < 1e-3),
+            "Archived audio should match the pushed audio."
+        );
+
+        let (file, offset) = manifest
+            .locate(150)
+            .expect("The timestamp should be archived.");
+        assert_eq!(file.file_name, "session-0001.wav");
+        assert_eq!(offset, Duration::from_millis(500));
+        assert!(manifest.locate(300).is_none());
+    }
+
+    #[test]
+    fn test_rotates_by_size() {
+        let dir = archive_dir("ribble_archive_size");
+        let mut archiver = AudioArchiver::create(&dir, 16000, 2)
+            .expect("Archive should be created without issue.")
+            .with_format(ArchiveSampleFormat::Float32)
+            .with_prefix("capture")
+            // The header, and 10 stereo f32 frames.
+            .with_rotation(RotationPolicy::new().with_max_bytes(44 + 10 * 8));
+        archiver
+            .push(&[0.25; 45])
+            .expect("Audio should be archived without issue.");
+        let manifest = archiver
+            .finish()
+            .expect("Archive should finish without issue.");
+
+        // The trailing partial frame is dropped.
+        assert_eq!(manifest.frames(), 22);
+        assert_eq!(manifest.files.len(), 3);
+        for file in &manifest.files {
+            let size = std::fs::metadata(dir.join(&file.file_name))
+                .expect("Archived files should exist.")
+                .len();
+            assert!(size <= 44 + 10 * 8, "{} is too large", file.file_name);
+        }
+        let reader = hound::WavReader::open(dir.join("capture-0002.wav"))
+            .expect("Archived files should be valid WAV files.");
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.spec().sample_format, hound::SampleFormat::Float);
+        assert_eq!(reader.len(), 4);
+    }
+}
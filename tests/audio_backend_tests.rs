@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod audio_backend_tests {
+    use ribble_whisper::audio::audio_backend::{CaptureSpec, DeviceCapabilities, InputDevice};
+    use ribble_whisper::audio::microphone::{MicCapture, RibbleAudioFormat};
+
+    // Reports a fixed capture format, for testing the provided MicCapture methods.
+    struct FixedCapture {
+        channels: u8,
+        buffer_size: usize,
+    }
+
+    impl MicCapture for FixedCapture {
+        fn play(&self) {}
+        fn pause(&self) {}
+        fn sample_rate(&self) -> usize {
+            16000
+        }
+        fn format(&self) -> RibbleAudioFormat {
+            RibbleAudioFormat::F32
+        }
+        fn channels(&self) -> u8 {
+            self.channels
+        }
+        fn buffer_size(&self) -> usize {
+            self.buffer_size
+        }
+    }
+
+    #[test]
+    fn test_supports_spec() {
+        let capabilities = DeviceCapabilities::new(
+            vec![16000, 44100, 48000],
+            vec![1, 2],
+            vec![RibbleAudioFormat::F32],
+        );
+        assert!(capabilities.supports(&CaptureSpec::default()));
+        assert!(capabilities.supports(&CaptureSpec::new()));
+        assert!(capabilities.supports_whisper());
+        assert!(!capabilities.supports(&CaptureSpec::new().with_sample_rate(Some(22050))));
+        assert!(!capabilities.supports(&CaptureSpec::new().with_num_channels(Some(6))));
+        assert_eq!(capabilities.resample_from(), None);
+    }
+
+    #[test]
+    fn test_resample_from() {
+        let capabilities =
+            DeviceCapabilities::new(vec![44100, 48000], vec![2], vec![RibbleAudioFormat::I16]);
+        assert!(!capabilities.supports_whisper());
+        assert!(!capabilities.supports(&CaptureSpec::default()));
+        // Without a native rate, capture at the highest rate.
+        assert_eq!(capabilities.resample_from(), Some(48000));
+
+        let capabilities = capabilities.with_native_spec(Some(44100), Some(2));
+        assert_eq!(capabilities.native_sample_rate(), Some(44100));
+        assert_eq!(capabilities.native_channels(), Some(2));
+        assert_eq!(capabilities.resample_from(), Some(44100));
+    }
+
+    #[test]
+    fn test_input_device() {
+        let device = InputDevice::new(2, "USB Microphone".to_string());
+        assert_eq!(device.id(), 2);
+        assert_eq!(device.name(), "USB Microphone");
+        assert_eq!(device.to_string(), "2: USB Microphone");
+
+        let spec = CaptureSpec::default().with_device(Some(device.id()));
+        assert_eq!(spec.device(), Some(2));
+        assert_eq!(CaptureSpec::default().device(), None);
+    }
+
+    #[test]
+    fn test_loopback_spec() {
+        assert!(!CaptureSpec::default().is_loopback());
+        let spec = CaptureSpec::loopback()
+            .with_device(Some(1))
+            .with_sample_rate(Some(48000));
+        assert!(spec.is_loopback());
+        assert_eq!(spec.device(), Some(1));
+        assert_eq!(spec.sample_rate(), Some(48000));
+        assert_eq!(spec.channels(), Some(1));
+        assert!(!spec.with_loopback(false).is_loopback());
+    }
+
+    #[test]
+    fn test_buffer_samples() {
+        let capture = FixedCapture {
+            channels: 2,
+            buffer_size: 1024,
+        };
+        // The buffer size is in frames; the sample count spans every channel.
+        assert_eq!(capture.buffer_size(), 1024);
+        assert_eq!(capture.buffer_samples(), 2048);
+    }
+
+    #[cfg(feature = "cpal")]
+    #[test]
+    fn test_cpal_sample_formats() {
+        assert_eq!(
+            RibbleAudioFormat::from(cpal::SampleFormat::F32),
+            RibbleAudioFormat::F32
+        );
+        assert_eq!(
+            RibbleAudioFormat::from(cpal::SampleFormat::I16),
+            RibbleAudioFormat::I16
+        );
+        assert!(RibbleAudioFormat::from(cpal::SampleFormat::U8).is_invalid());
+    }
+}
@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod audio_event_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ribble_whisper::export::cea608::{CaptionEvent, RollUpChunker};
+    use ribble_whisper::transcriber::audio_events::{
+        AudioEvent, AudioEventKind, AudioEventScore, AudioEventTracker,
+    };
+    use ribble_whisper::transcriber::{SnapshotAssembler, WhisperOutput};
+
+    #[test]
+    fn test_kind_from_label() {
+        assert_eq!(AudioEventKind::from_label("Music"), AudioEventKind::Music);
+        assert_eq!(
+            AudioEventKind::from_label(" Clapping "),
+            AudioEventKind::Applause
+        );
+        assert_eq!(
+            AudioEventKind::from_label("Belly laugh"),
+            AudioEventKind::Laughter
+        );
+        let other = AudioEventKind::from_label("Door Knock");
+        assert_eq!(other, AudioEventKind::Other(Arc::from("door knock")));
+        assert_eq!(other.to_string(), "[door knock]");
+        assert_eq!(AudioEventKind::Applause.to_string(), "[applause]");
+    }
+
+    #[test]
+    fn test_tracker_joins_consecutive_windows() {
+        let mut tracker = AudioEventTracker::new();
+        let music = |confidence| AudioEventScore::new(AudioEventKind::Music, confidence);
+        let applause = AudioEventScore::new(AudioEventKind::Applause, 0.8);
+
+        assert!(tracker.update(vec![music(0.6)], 0, 30).is_empty());
+        assert!(
+            tracker
+                .update(vec![music(0.9), applause.clone()], 30, 60)
+                .is_empty()
+        );
+
+        let finished = tracker.update(vec![applause], 60, 90);
+        assert_eq!(
+            finished,
+            vec![AudioEvent::new(AudioEventKind::Music, 0, 60, 0.9)],
+            "Music should end at the last window it was heard in, with its highest confidence."
+        );
+
+        let finished = tracker.finish();
+        assert_eq!(
+            finished,
+            vec![AudioEvent::new(AudioEventKind::Applause, 30, 90, 0.8)]
+        );
+        assert!(tracker.finish().is_empty());
+    }
+
+    #[test]
+    fn test_audio_event_outputs() {
+        let output =
+            WhisperOutput::AudioEvent(AudioEvent::new(AudioEventKind::Laughter, 100, 250, 0.7));
+
+        let mut assembler = SnapshotAssembler::new();
+        assert!(
+            assembler.push(&output).is_none(),
+            "Audio events should not produce snapshots."
+        );
+
+        let mut chunker = RollUpChunker::new();
+        let events = chunker.push_output(&output, Duration::ZERO);
+        assert_eq!(
+            events[0].event,
+            CaptionEvent::Text("[laughter]".to_string())
+        );
+
+        assert_eq!(output.into_inner(), "[laughter]");
+    }
+}
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod black_box_tests {
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use ribble_whisper::transcriber::RibbleWhisperSegment;
+    use ribble_whisper::transcriber::black_box::{
+        BlackBoxEvent, BlackBoxRecorder, SessionBundle, SessionRecorder,
+    };
+    use ribble_whisper::whisper::configs::WhisperRealtimeConfigs;
+
+    fn bundle_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn segment(text: &str, start_time: i64, end_time: i64) -> RibbleWhisperSegment {
+        RibbleWhisperSegment {
+            text: Arc::from(text),
+            start_time,
+            end_time,
+            confidence: Some(0.75),
+            word_confidences: None,
+            speaker: None,
+        }
+    }
+
+    #[test]
+    fn test_record_and_load_bundle() {
+        let dir = bundle_dir("ribble_whisper_black_box_roundtrip");
+        let first: Vec<f32> = (0..400).map(|idx| idx as f32 / 400.0).collect();
+        let second: Vec<f32> = (0..250).map(|idx| -(idx as f32) / 250.0).collect();
+        let configs = WhisperRealtimeConfigs::default().with_n_threads(3);
+        {
+            let mut recorder = BlackBoxRecorder::create(&dir).unwrap();
+            recorder.start_session(&configs);
+            recorder.record_vad(false, &first[..100]);
+            recorder.record_vad(true, &first[..100]);
+            recorder.record_window(&first, false, false);
+            recorder.record_segments(&[segment("Mary has many dreams", 0, 250)]);
+            recorder.record_vad(true, &second[..100]);
+            recorder.record_window(&second, true, true);
+            recorder.record_segments(&[]);
+            recorder.finish_session("Mary has many dreams");
+            assert_eq!(recorder.windows(), 2);
+        }
+
+        let bundle = SessionBundle::load(&dir).unwrap();
+        assert_eq!(bundle.configs().map(|c| c.n_threads()), Some(3));
+        assert_eq!(bundle.vad_decisions(), [false, true, true]);
+        assert_eq!(bundle.n_windows(), 2);
+        assert_eq!(bundle.window_audio(0).unwrap(), first);
+        assert_eq!(bundle.window_audio(1).unwrap(), second);
+        assert!(bundle.window_audio(2).is_err());
+
+        let segments = bundle.window_segments(0).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].text, "Mary has many dreams");
+        assert_eq!(segments[0].end_time, 250);
+        assert_eq!(segments[0].confidence, Some(0.75));
+        assert_eq!(bundle.window_segments(1), Some(&[][..]));
+        assert!(matches!(
+            bundle.events()[3],
+            BlackBoxEvent::Window {
+                index: 0,
+                audio_offset: 0,
+                samples: 400,
+                use_context: false,
+                segment_merge: false,
+                ..
+            }
+        ));
+        assert_eq!(bundle.transcription(), Some("Mary has many dreams"));
+    }
+
+    // A session that crashed mid-write should still load up to the last complete event.
+    #[test]
+    fn test_load_truncated_bundle() {
+        let dir = bundle_dir("ribble_whisper_black_box_truncated");
+        {
+            let mut recorder = BlackBoxRecorder::create(&dir).unwrap();
+            recorder.record_vad(true, &[0.0; 16]);
+            recorder.record_window(&[0.5; 32], false, false);
+        }
+        let mut events = std::fs::OpenOptions::new()
+            .append(true)
+            .open(dir.join("events.jsonl"))
+            .unwrap();
+        events.write_all(br#"{"event":"segments","elaps"#).unwrap();
+        drop(events);
+
+        let bundle = SessionBundle::load(&dir).unwrap();
+        assert!(bundle.configs().is_none());
+        assert_eq!(bundle.events().len(), 2);
+        assert_eq!(bundle.window_audio(0).unwrap(), vec![0.5; 32]);
+        assert!(bundle.transcription().is_none());
+    }
+}
@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod buffer_pool_tests {
+    use std::sync::Arc;
+
+    use ribble_whisper::audio::buffer_pool::{ArcBufferPool, BufferPoolStats};
+
+    #[test]
+    fn test_buffers_are_recycled() {
+        let mut pool = ArcBufferPool::new(2);
+        let first = pool.copy_from_slice(&[1.0f32, 2.0]);
+        let first_ptr = Arc::as_ptr(&first);
+        let second = pool.copy_from_slice(&[3.0, 4.0]);
+        assert_ne!(Arc::as_ptr(&second), first_ptr);
+        assert_eq!(pool.len(), 2);
+
+        drop(first);
+        let recycled = pool.copy_from_slice(&[5.0, 6.0]);
+        assert_eq!(
+            Arc::as_ptr(&recycled),
+            first_ptr,
+            "A dropped buffer should be reused."
+        );
+        assert_eq!(&*recycled, &[5.0, 6.0]);
+        assert_eq!(&*second, &[3.0, 4.0], "Held buffers should not be written.");
+
+        assert_eq!(
+            pool.stats(),
+            BufferPoolStats {
+                reused: 1,
+                pooled: 2,
+                unpooled: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_pool_falls_back_to_allocating() {
+        let mut pool = ArcBufferPool::new(1);
+        let held = pool.copy_from_slice(&[1i16, 2, 3]);
+        let overflow = pool.copy_from_slice(&[4, 5, 6]);
+        assert_eq!(&*overflow, &[4, 5, 6]);
+        assert_eq!(pool.len(), 1, "The pool should not grow past its capacity.");
+
+        drop(held);
+        let different_length = pool.copy_from_slice(&[7, 8]);
+        assert_eq!(&*different_length, &[7, 8]);
+        assert_eq!(pool.stats().unpooled, 2);
+        assert_eq!(pool.stats().reused, 0);
+
+        pool.clear();
+        assert!(pool.is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+#[cfg(test)]
+mod callback_tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use ribble_whisper::utils;
+    use ribble_whisper::utils::callback::{
+        Callback, CallbackError, DebouncedCallback, RibbleWhisperCallback,
+        RibbleWhisperTryCallback, ShortCircuitAdapter, ShortCircuitCallback,
+        ShortCircuitRibbleWhisperCallback, ThrottledCallback, TryCallbackReporter,
+    };
+    use ribble_whisper::utils::progress::CompositeProgress;
+
+    fn recorder() -> (Arc<Mutex<Vec<usize>>>, impl Callback<Argument = usize>) {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let sink = Arc::clone(&calls);
+        (
+            calls,
+            RibbleWhisperCallback::new(move |arg: usize| sink.lock().unwrap().push(arg)),
+        )
+    }
+
+    #[test]
+    fn test_throttled_callback() {
+        let (calls, callback) = recorder();
+        let mut throttled = ThrottledCallback::new(callback, Duration::from_millis(100));
+        for i in 0..10 {
+            throttled.call(i);
+        }
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![0],
+            "Only the first call should run within the interval."
+        );
+
+        std::thread::sleep(Duration::from_millis(120));
+        throttled.call(10);
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![0, 10],
+            "The latest value should run once the interval elapses."
+        );
+
+        throttled.call(11);
+        drop(throttled);
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![0, 10, 11],
+            "The held-back value should be delivered on drop."
+        );
+    }
+
+    #[test]
+    fn test_debounced_callback() {
+        let (calls, callback) = recorder();
+        let mut debounced = DebouncedCallback::new(callback, Duration::from_millis(100));
+        for i in 0..5 {
+            debounced.call(i);
+        }
+        assert!(
+            calls.lock().unwrap().is_empty(),
+            "Unsettled values should not be delivered."
+        );
+
+        std::thread::sleep(Duration::from_millis(120));
+        debounced.call(5);
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![4],
+            "The settled value should be delivered by the next call."
+        );
+
+        debounced.flush();
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![4, 5],
+            "Flush should deliver the pending value."
+        );
+        drop(debounced);
+        assert_eq!(calls.lock().unwrap().len(), 2, "Nothing should be pending.");
+    }
+
+    #[test]
+    fn test_composite_progress() {
+        let reported = Arc::new(Mutex::new(vec![]));
+        let sink = Arc::clone(&reported);
+        let progress = CompositeProgress::new(RibbleWhisperCallback::new(move |fraction: f64| {
+            sink.lock().unwrap().push(fraction)
+        }))
+        .with_stage(20.0)
+        .with_stage(10.0)
+        .with_stage(70.0);
+
+        let download = progress.stage(0).expect("Download stage should exist.");
+        let load = progress.stage(1).expect("Load stage should exist.");
+        let mut transcribe = progress.stage(2).expect("Transcribe stage should exist.");
+        assert!(progress.stage(3).is_none(), "Stage should not exist.");
+
+        download.set_count(50, 100);
+        assert!(
+            (progress.fraction() - 0.1).abs() < 1e-9,
+            "Download should be at 10%."
+        );
+        download.finish();
+        // Repeated updates that do not change the overall progress should not be reported.
+        download.finish();
+        load.finish();
+        assert!(
+            (progress.fraction() - 0.3).abs() < 1e-9,
+            "Load should end at 30%."
+        );
+        transcribe.call(0.5);
+        transcribe.set_percent(100);
+
+        let reported = reported.lock().unwrap();
+        assert_eq!(reported.len(), 5, "Unexpected number of progress updates.");
+        assert!(
+            reported.windows(2).all(|pair| pair[0] < pair[1]),
+            "Progress should increase monotonically."
+        );
+        assert!(
+            (reported.last().unwrap() - 1.0).abs() < 1e-9,
+            "Progress should finish at 100%."
+        );
+    }
+
+    #[test]
+    fn test_try_callback_reporter() {
+        let (error_sender, errors) = utils::get_channel(4);
+        let running = Arc::new(AtomicBool::new(true));
+        let callback = RibbleWhisperTryCallback::new(|progress: i32| match progress {
+            50 => Err(format!("Failed at {progress}")),
+            75 => panic!("Callback panicked at {progress}"),
+            _ => Ok(()),
+        });
+        let mut reporter =
+            TryCallbackReporter::new(callback, error_sender).with_stop_flag(Arc::clone(&running));
+
+        reporter.call(25);
+        assert!(
+            errors.try_recv().is_err(),
+            "Successful calls should not report."
+        );
+        assert!(
+            running.load(Ordering::Acquire),
+            "Stop flag set without an error."
+        );
+
+        reporter.call(50);
+        let failed = errors.try_recv().expect("Error should have been reported.");
+        assert!(
+            matches!(&failed, CallbackError::Failed(msg) if msg == "Failed at 50"),
+            "Unexpected error: {failed}"
+        );
+        assert!(
+            !running.load(Ordering::Acquire),
+            "Stop flag not set on error."
+        );
+
+        reporter.call(75);
+        let panicked = errors.try_recv().expect("Panic should have been reported.");
+        assert!(panicked.is_panic(), "Expected a panic. Got: {panicked}");
+        assert!(
+            panicked.to_string().contains("Callback panicked at 75"),
+            "Panic message not preserved: {panicked}"
+        );
+    }
+
+    #[test]
+    fn test_short_circuit_policies() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let sink = Arc::clone(&calls);
+        let every_third = ShortCircuitRibbleWhisperCallback::from_callback(move |arg: usize| {
+            sink.lock().unwrap().push(arg)
+        })
+        .with_every_nth(3);
+        let mut every_third = ShortCircuitAdapter::new(every_third);
+        for i in 0..7 {
+            every_third.call(i);
+        }
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![0, 3, 6],
+            "Expected the first and every third call."
+        );
+
+        let changes = Arc::new(Mutex::new(vec![]));
+        let sink = Arc::clone(&changes);
+        let mut only_changed = ShortCircuitAdapter::new(
+            ShortCircuitRibbleWhisperCallback::from_callback(move |text: String| {
+                sink.lock().unwrap().push(text)
+            })
+            .with_only_when_changed(),
+        );
+        for text in ["a", "a", "b", "b", "a"] {
+            only_changed.call(text.to_string());
+        }
+        assert_eq!(
+            *changes.lock().unwrap(),
+            vec!["a", "b", "a"],
+            "Unchanged arguments should be skipped."
+        );
+
+        let (calls, mut callback) = recorder();
+        let mut min_interval =
+            ShortCircuitRibbleWhisperCallback::from_callback(move |arg| callback.call(arg))
+                .with_min_interval(Duration::from_millis(100));
+        for i in 0..5 {
+            if min_interval.should_run_callback() {
+                ShortCircuitCallback::call(&mut min_interval, i);
+            }
+        }
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![0],
+            "Only the first call should run within the interval."
+        );
+        std::thread::sleep(Duration::from_millis(120));
+        assert!(
+            min_interval.should_run_callback(),
+            "Callback should run once the interval has passed."
+        );
+    }
+}
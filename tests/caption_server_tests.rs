@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod caption_server_tests {
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use ribble_whisper::export::caption_server::CaptionServer;
+    use ribble_whisper::export::jsonl::SegmentRecord;
+    use ribble_whisper::transcriber::{TranscriptionSnapshot, WhisperControlPhrase, WhisperOutput};
+
+    fn read_json(
+        client: &mut tungstenite::WebSocket<
+            tungstenite::stream::MaybeTlsStream<std::net::TcpStream>,
+        >,
+    ) -> serde_json::Value {
+        let message = client.read().expect("Failed to read caption message.");
+        serde_json::from_str(message.to_text().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_broadcast_to_clients() {
+        let server = CaptionServer::bind("127.0.0.1:0").unwrap();
+        let snapshot =
+            TranscriptionSnapshot::new(Arc::from("Hello"), Arc::from([Arc::from("wor")]));
+        server
+            .broadcast(&WhisperOutput::TranscriptionSnapshot(Arc::new(snapshot)))
+            .unwrap();
+
+        let (mut client, _) =
+            tungstenite::connect(format!("ws://{}", server.local_addr())).unwrap();
+        let message = read_json(&mut client);
+        assert_eq!(
+            message["type"], "snapshot",
+            "New clients should be sent the latest snapshot."
+        );
+        assert_eq!(message["confirmed"], "Hello");
+        assert_eq!(message["working"][0], "wor");
+
+        let start = Instant::now();
+        while server.num_clients() == 0 && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(server.num_clients(), 1);
+
+        server
+            .broadcast(&WhisperOutput::ControlPhrase(
+                WhisperControlPhrase::StartSpeaking,
+            ))
+            .unwrap();
+        let message = read_json(&mut client);
+        assert_eq!(message["type"], "control");
+        assert_eq!(message["code"], "start_speaking");
+        assert_eq!(message["phrase"], "[START SPEAKING]");
+        assert!(message.get("payload").is_none());
+
+        server
+            .broadcast_segment(&SegmentRecord::new("Hello world.", 0, 1500))
+            .unwrap();
+        let message = read_json(&mut client);
+        assert_eq!(message["type"], "segment");
+        assert_eq!(message["end_ms"], 1500);
+
+        server.shutdown();
+    }
+}
@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod capture_check_tests {
+    use ribble_whisper::audio::capture_check::{CHECK_VAD_FRAME_MS, CaptureCheck};
+    use ribble_whisper::testing::vad::{NeverVoiced, ScriptedVad};
+
+    const SAMPLE_RATE: usize = 16000;
+    const FRAME_LEN: usize = SAMPLE_RATE * CHECK_VAD_FRAME_MS / 1000;
+
+    #[test]
+    fn test_levels_and_voice_probability() {
+        let audio = vec![0.5f32; FRAME_LEN * 4];
+        let mut vad = ScriptedVad::new([true, false, true, false]);
+        let check = CaptureCheck::analyze(audio, SAMPLE_RATE, 1, Some(&mut vad)).unwrap();
+        assert_eq!(check.levels().peak, 0.5);
+        assert!((check.levels().rms - 0.5).abs() < 1e-6);
+        assert_eq!(check.clipped_samples(), 0);
+        assert_eq!(check.voice_probability(), Some(0.5));
+        assert!(check.heard_voice());
+        assert!(!check.is_silent());
+        assert_eq!(check.duration().as_millis(), 4 * CHECK_VAD_FRAME_MS as u128);
+        assert_eq!(vad.calls(), 4);
+    }
+
+    #[test]
+    fn test_silence_and_clipping() {
+        let check = CaptureCheck::analyze(
+            vec![0.0f32; FRAME_LEN],
+            SAMPLE_RATE,
+            1,
+            Some(&mut NeverVoiced),
+        )
+        .unwrap();
+        assert!(check.is_silent());
+        assert!(!check.heard_voice());
+        assert_eq!(check.voice_probability(), Some(0.0));
+
+        let mut audio = vec![0.1f32; FRAME_LEN * 2];
+        audio[0] = 1.0;
+        audio[1] = -1.0;
+        let check = CaptureCheck::analyze(audio, SAMPLE_RATE, 2, None::<&mut NeverVoiced>).unwrap();
+        assert_eq!(check.clipped_samples(), 2);
+        assert_eq!(check.channels(), 2);
+        assert_eq!(check.duration().as_millis(), CHECK_VAD_FRAME_MS as u128);
+        assert_eq!(check.voice_probability(), None);
+    }
+
+    #[test]
+    fn test_stereo_is_downmixed_for_vad() {
+        // Two frames of stereo audio: one mono frame each, so the VAD decides twice.
+        let audio = vec![0.25f32; FRAME_LEN * 4];
+        let mut vad = ScriptedVad::new([true, true]);
+        let check = CaptureCheck::analyze(audio, SAMPLE_RATE, 2, Some(&mut vad)).unwrap();
+        assert_eq!(vad.calls(), 2);
+        assert_eq!(check.voice_probability(), Some(1.0));
+    }
+
+    #[test]
+    fn test_short_or_invalid_audio() {
+        let check = CaptureCheck::analyze(
+            vec![0.5f32; FRAME_LEN - 1],
+            SAMPLE_RATE,
+            1,
+            Some(&mut NeverVoiced),
+        )
+        .unwrap();
+        assert_eq!(check.voice_probability(), None);
+
+        assert!(
+            CaptureCheck::analyze(vec![0.5f32; 3], SAMPLE_RATE, 2, None::<&mut NeverVoiced>)
+                .is_err()
+        );
+        assert!(CaptureCheck::analyze(vec![0.5f32; 4], 0, 1, None::<&mut NeverVoiced>).is_err());
+    }
+}
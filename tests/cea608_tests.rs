@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod cea608_tests {
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ribble_whisper::export::cea608::{
+        CaptionEvent, RollUpChunker, RollUpSink, TimedCaptionEvent, ROW_WIDTH,
+    };
+    use ribble_whisper::transcriber::{TranscriptionSnapshot, WhisperControlPhrase, WhisperOutput};
+    use ribble_whisper::utils::sink::OutputSink;
+
+    fn snapshot(confirmed: &str) -> WhisperOutput {
+        WhisperOutput::TranscriptionSnapshot(Arc::new(TranscriptionSnapshot::new(
+            Arc::from(confirmed),
+            Arc::from([]),
+        )))
+    }
+
+    fn rows(events: &[TimedCaptionEvent]) -> Vec<String> {
+        let mut rows = vec![String::new()];
+        for event in events {
+            match &event.event {
+                CaptionEvent::Text(text) => rows.last_mut().unwrap().push_str(text),
+                CaptionEvent::CarriageReturn => rows.push(String::new()),
+                CaptionEvent::EraseDisplay => rows = vec![String::new()],
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn test_wraps_rows() {
+        let mut chunker = RollUpChunker::new();
+        let events = chunker.push_text(
+            "The quick brown fox jumps over the lazy dog, then naps — “quietly” in_the sun.",
+            Duration::ZERO,
+        );
+        let wrapped = rows(&events);
+        assert_eq!(
+            wrapped,
+            vec![
+                "The quick brown fox jumps over",
+                "the lazy dog, then naps -",
+                "\"quietly\" inthe sun."
+            ],
+            "Text should wrap at word boundaries, with unsupported characters replaced."
+        );
+        assert!(wrapped.iter().all(|row| row.chars().count() <= ROW_WIDTH));
+
+        // Text continues on the current row.
+        let events = chunker.push_text("Yes.", Duration::from_secs(10));
+        assert_eq!(events[0].event, CaptionEvent::Text(" Yes.".to_string()));
+
+        let long_word = "a".repeat(40);
+        let events = chunker.push_text(&long_word, Duration::from_secs(20));
+        assert_eq!(
+            rows(&events),
+            vec!["", &"a".repeat(32), &"a".repeat(8)],
+            "Words longer than a row should be broken."
+        );
+    }
+
+    #[test]
+    fn test_pacing() {
+        let mut chunker = RollUpChunker::new()
+            .with_chars_per_second(10.0)
+            .with_min_row_duration(Duration::from_secs(2));
+        let text = format!("{} {}", "a".repeat(30), "b".repeat(30));
+        let events = chunker.push_text(&text, Duration::ZERO);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].at, Duration::ZERO);
+        // 30 characters at 10 characters per second.
+        assert_eq!(events[1].event, CaptionEvent::CarriageReturn);
+        assert_eq!(events[1].at, Duration::from_secs(3));
+        assert!(events[2].at > events[1].at, "Text should follow the roll.");
+
+        let events = chunker.push_text(&"c".repeat(30), Duration::from_secs(4));
+        assert_eq!(events[0].event, CaptionEvent::CarriageReturn);
+        assert!(
+            events[0].at >= Duration::from_secs(6),
+            "Rows should not roll faster than the minimum row duration."
+        );
+        for pair in events.windows(2) {
+            assert!(pair[0].at <= pair[1].at);
+        }
+    }
+
+    #[test]
+    fn test_sink_sends_confirmed_text() {
+        let (sender, receiver) = mpsc::channel();
+        let sink = RollUpSink::new(RollUpChunker::new(), sender);
+
+        sink.try_send(snapshot(" Hello there.")).unwrap();
+        sink.try_send(WhisperOutput::ControlPhrase(
+            WhisperControlPhrase::StartSpeaking,
+        ))
+        .unwrap();
+        sink.try_send(snapshot(" Hello there. How are you?"))
+            .unwrap();
+        sink.clear().unwrap();
+
+        let events: Vec<CaptionEvent> = receiver.try_iter().map(|event| event.event).collect();
+        assert_eq!(
+            events,
+            vec![
+                CaptionEvent::Text("Hello there.".to_string()),
+                CaptionEvent::Text(" How are you?".to_string()),
+                CaptionEvent::EraseDisplay
+            ],
+            "Only newly confirmed text should be sent."
+        );
+
+        drop(receiver);
+        assert!(sink.try_send(snapshot(" Hello there. Bye.")).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod commit_policy_tests {
+    use std::sync::Arc;
+
+    use ribble_whisper::transcriber::RibbleWhisperSegment;
+    use ribble_whisper::whisper::configs::{
+        BufferClearPolicy, CommitPolicy, OverflowPolicy, RETAIN_MS, WhisperRealtimeConfigs,
+    };
+
+    fn segment(start_time: i64, end_time: i64, confidence: Option<f32>) -> RibbleWhisperSegment {
+        RibbleWhisperSegment {
+            text: Arc::from("segment"),
+            start_time,
+            end_time,
+            confidence,
+            word_confidences: None,
+            speaker: None,
+        }
+    }
+
+    fn window() -> Vec<RibbleWhisperSegment> {
+        vec![
+            segment(0, 200, Some(0.9)),
+            segment(200, 450, Some(0.4)),
+            segment(450, 700, Some(0.95)),
+            segment(700, 950, Some(0.9)),
+        ]
+    }
+
+    #[test]
+    fn test_on_pause() {
+        assert_eq!(
+            WhisperRealtimeConfigs::default().commit_policy(),
+            CommitPolicy::OnPause
+        );
+        assert_eq!(CommitPolicy::OnPause.n_ready(&window(), 1000), 0);
+    }
+
+    #[test]
+    fn test_segment_count() {
+        let policy = CommitPolicy::SegmentCount { n_segments: 3 };
+        assert_eq!(policy.n_ready(&window(), 1000), 1);
+        assert_eq!(policy.n_ready(&window()[..2], 1000), 0);
+        // The last segment may be cut off mid-word, so it is never confirmed.
+        let policy = CommitPolicy::SegmentCount { n_segments: 0 };
+        assert_eq!(policy.n_ready(&window(), 1000), 3);
+    }
+
+    #[test]
+    fn test_time() {
+        let policy = CommitPolicy::Time { after_ms: 2000 };
+        assert_eq!(policy.n_ready(&window(), 1000), 3);
+        assert_eq!(policy.n_ready(&window(), 700), 2);
+        assert_eq!(policy.n_ready(&window(), 300), 0);
+    }
+
+    #[test]
+    fn test_confidence() {
+        let policy = CommitPolicy::Confidence {
+            min_confidence: 0.8,
+        };
+        // Segments are confirmed in order; the uncertain second segment holds back the rest.
+        assert_eq!(policy.n_ready(&window(), 1000), 1);
+
+        let policy = CommitPolicy::Confidence {
+            min_confidence: 0.3,
+        };
+        assert_eq!(policy.n_ready(&window(), 1000), 3);
+        assert_eq!(
+            policy.n_ready(&[segment(0, 100, None), segment(100, 200, None)], 300),
+            0
+        );
+    }
+
+    #[test]
+    fn test_overflow_policy() {
+        assert_eq!(
+            WhisperRealtimeConfigs::default().overflow_policy(),
+            OverflowPolicy::ConfirmOldest
+        );
+        let policy = OverflowPolicy::Grow { max_segments: 12 };
+        let configs = WhisperRealtimeConfigs::default().with_overflow_policy(policy);
+        assert_eq!(configs.overflow_policy(), policy);
+    }
+
+    #[test]
+    fn test_buffer_clear_policy() {
+        let policy = WhisperRealtimeConfigs::default().buffer_clear_policy();
+        assert_eq!(policy.rollover_retain_ms(), Some(RETAIN_MS));
+        assert!(policy.clear_on_pause());
+
+        let policy = BufferClearPolicy::Clear {
+            rollover_retain_ms: 1500,
+            clear_on_pause: false,
+        };
+        let configs = WhisperRealtimeConfigs::default().with_buffer_clear_policy(policy);
+        assert_eq!(configs.buffer_clear_policy(), policy);
+        assert_eq!(policy.rollover_retain_ms(), Some(1500));
+        assert!(!policy.clear_on_pause());
+
+        assert_eq!(BufferClearPolicy::SlideOnly.rollover_retain_ms(), None);
+        assert!(!BufferClearPolicy::SlideOnly.clear_on_pause());
+    }
+}
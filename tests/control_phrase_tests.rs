@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod control_phrase_tests {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use ribble_whisper::transcriber::{
+        ControlCode, ControlPayload, SessionStartReason, WhisperControlPhrase,
+    };
+
+    #[test]
+    fn test_control_codes() {
+        assert_eq!(
+            WhisperControlPhrase::StartSpeaking.code(),
+            ControlCode::StartSpeaking
+        );
+        assert_eq!(ControlCode::StartSpeaking.as_str(), "start_speaking");
+        assert_eq!(ControlCode::SlowStop.to_string(), "slow_stop");
+        assert_eq!(
+            ControlCode::from_str("awaiting_wake_phrase"),
+            Ok(ControlCode::AwaitingWakePhrase)
+        );
+
+        // Numeric forms are stable.
+        assert_eq!(ControlCode::Idle.value(), 0);
+        assert_eq!(ControlCode::StartSpeaking.value(), 4);
+        assert_eq!(ControlCode::Debug.value(), 11);
+        for value in 0..=12 {
+            let code = ControlCode::from_value(value).expect("Every value up to 12 is a code.");
+            assert_eq!(code.value(), value);
+        }
+        assert_eq!(ControlCode::from_value(13), None);
+    }
+
+    #[test]
+    fn test_control_payloads() {
+        assert_eq!(WhisperControlPhrase::StartSpeaking.payload(), None);
+        assert_eq!(
+            WhisperControlPhrase::TimeoutApproaching {
+                remaining: Duration::from_secs(30)
+            }
+            .payload(),
+            Some(ControlPayload::RemainingMs(30000))
+        );
+        assert_eq!(
+            WhisperControlPhrase::SessionStarted(SessionStartReason::WakePhrase(
+                "hey ribble".to_string()
+            ))
+            .payload(),
+            Some(ControlPayload::WakePhrase("hey ribble".to_string()))
+        );
+        assert_eq!(
+            WhisperControlPhrase::SessionStarted(SessionStartReason::Manual).payload(),
+            None
+        );
+        assert_eq!(
+            WhisperControlPhrase::WorkingSetOverflow {
+                n_segments: 2,
+                confirmed: false
+            }
+            .payload(),
+            Some(ControlPayload::Overflow {
+                n_segments: 2,
+                confirmed: false
+            })
+        );
+        let debug = WhisperControlPhrase::Debug("message".to_string());
+        assert_eq!(debug.code(), ControlCode::Debug);
+        assert_eq!(
+            debug.payload(),
+            Some(ControlPayload::Message("message".to_string()))
+        );
+    }
+}
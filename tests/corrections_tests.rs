@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod corrections_tests {
+    use ribble_whisper::transcriber::corrections::Correction;
+    use ribble_whisper::transcriber::{SnapshotAssembler, WhisperOutput};
+
+    #[test]
+    fn test_between() {
+        let previous = "The cat sat on the mad.";
+        let next = "The cat sat on the mat.";
+        let correction = Correction::between(previous, next).unwrap();
+        assert_eq!(correction.range(), 21..22);
+        assert_eq!(correction.new_text(), "t");
+
+        let mut text = previous.to_string();
+        assert!(correction.apply(&mut text));
+        assert_eq!(text, next);
+
+        assert!(Correction::between(next, next).is_none());
+    }
+
+    #[test]
+    fn test_between_removal() {
+        // Deduplication removed words that the next segment repeats.
+        let previous = "It was the best of times it was";
+        let next = "It was the best of times";
+        let correction = Correction::between(previous, next).unwrap();
+        assert_eq!(correction.range(), 24..31);
+        assert_eq!(correction.new_text(), "");
+        assert_eq!(correction.reach(previous.len()), 7);
+    }
+
+    #[test]
+    fn test_between_multibyte() {
+        let previous = "Café au lait";
+        let next = "Cafés au lait";
+        let correction = Correction::between(previous, next).unwrap();
+        let mut text = previous.to_string();
+        assert!(correction.apply(&mut text));
+        assert_eq!(text, next);
+    }
+
+    #[test]
+    fn test_apply_out_of_bounds() {
+        let mut text = "Hello".to_string();
+        assert!(!Correction::new(3..10, "p").apply(&mut text));
+        assert!(!Correction::new(6..6, "!").apply(&mut text));
+        assert_eq!(text, "Hello");
+
+        let mut text = "Café".to_string();
+        // Not on a char boundary.
+        assert!(!Correction::new(4..5, "e").apply(&mut text));
+    }
+
+    #[test]
+    fn test_output() {
+        let output = WhisperOutput::Correction(Correction::new(4..7, "dog"));
+        assert!(SnapshotAssembler::new().push(&output).is_none());
+        assert_eq!(output.into_inner(), "[CORRECTION] 4..7: dog");
+    }
+}
@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod decode_diagnostics_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ribble_whisper::transcriber::decode_diagnostics::{
+        COMPRESSION_RATIO_THRESHOLD, DecodeDiagnostics, ENTROPY_THRESHOLD, compression_ratio,
+        token_entropy,
+    };
+    use ribble_whisper::transcriber::{SnapshotAssembler, WhisperOutput};
+    use ribble_whisper::whisper::configs::RetryPolicy;
+
+    fn diagnostics(n_tokens: usize, avg_logprob: f32, token_entropy: f32) -> DecodeDiagnostics {
+        DecodeDiagnostics {
+            audio_duration: Duration::from_secs(10),
+            inference_time: Duration::from_millis(250),
+            use_context: false,
+            retries: 0,
+            n_tokens,
+            avg_logprob,
+            token_entropy,
+            compression_ratio: 1.0,
+            segments: vec![],
+        }
+    }
+
+    #[test]
+    fn test_token_entropy() {
+        assert_eq!(token_entropy(&[]), 0.0);
+        assert_eq!(token_entropy(&[7; 40]), 0.0);
+
+        let distinct: Vec<i32> = (0..32).collect();
+        assert!((token_entropy(&distinct) - 32f32.ln()).abs() < 1e-4);
+        assert!(token_entropy(&distinct) > ENTROPY_THRESHOLD);
+
+        // Only the last 32 tokens are measured.
+        let mut looping: Vec<i32> = (0..100).collect();
+        looping.extend([1, 2].repeat(16));
+        assert!((token_entropy(&looping) - 2f32.ln()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_compression_ratio() {
+        assert_eq!(compression_ratio(""), 1.0);
+        let speech = "The quick brown fox jumps over the lazy dog.";
+        assert!(compression_ratio(speech) < 1.2);
+
+        let looping = "Thank you. ".repeat(20);
+        assert!(compression_ratio(&looping) > COMPRESSION_RATIO_THRESHOLD);
+    }
+
+    #[test]
+    fn test_fallback_checks() {
+        let healthy = diagnostics(40, -0.3, 3.2);
+        assert!(!healthy.fallback_exhausted());
+
+        let uncertain = diagnostics(40, -1.4, 3.2);
+        assert!(uncertain.low_logprob());
+        assert!(uncertain.fallback_exhausted());
+
+        let repetitive = diagnostics(40, -0.2, 0.7);
+        assert!(repetitive.repetitive());
+        assert!(repetitive.fallback_exhausted());
+
+        // The entropy check only applies to long outputs.
+        assert!(!diagnostics(10, -0.2, 0.7).repetitive());
+
+        let output = WhisperOutput::DecodeDiagnostics(Arc::new(uncertain));
+        assert!(SnapshotAssembler::new().push(&output).is_none());
+        assert!(output.into_inner().ends_with("fallback exhausted"));
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        let policy = RetryPolicy::new();
+        assert!(!policy.enabled());
+
+        let policy = policy.with_max_retries(2);
+        assert!(policy.enabled());
+        assert!(!policy.should_retry(&diagnostics(40, -0.3, 3.2)));
+        assert!(policy.should_retry(&diagnostics(40, -0.2, 0.7)));
+        // Low confidence alone is left to whisper.cpp's own fallback.
+        assert!(!policy.should_retry(&diagnostics(40, -1.4, 3.2)));
+
+        let looping = DecodeDiagnostics {
+            compression_ratio: COMPRESSION_RATIO_THRESHOLD + 1.0,
+            ..diagnostics(10, -0.3, 2.0)
+        };
+        assert!(policy.should_retry(&looping));
+        assert!(
+            !policy
+                .with_compression_ratio_threshold(COMPRESSION_RATIO_THRESHOLD + 2.0)
+                .should_retry(&looping)
+        );
+
+        assert_eq!(policy.temperature(1), 0.2);
+        assert_eq!(policy.with_temperature_increment(0.6).temperature(2), 1.0);
+
+        // Retries drop the prompt by default.
+        assert!(policy.use_context(true, 0));
+        assert!(!policy.use_context(true, 1));
+        assert!(policy.with_disable_context(false).use_context(true, 1));
+        assert!(!policy.use_context(false, 0));
+
+        let retried = DecodeDiagnostics {
+            retries: 2,
+            ..diagnostics(40, -0.3, 3.2)
+        };
+        assert!(retried.to_string().ends_with("retries: 2"));
+    }
+}
@@ -0,0 +1,451 @@
+#[cfg(test)]
+mod dedup_tests {
+    use std::sync::Arc;
+
+    use ribble_whisper::transcriber::dedup::{
+        blend_segments, deduplicate_strings, find_closest_match, run_stride, ConflictPolicy,
+        DedupConfigs, DedupEvent, DedupOutcome, DedupSide, DedupStage, MergeStrategy,
+        DIFF_THRESHOLD_HIGH, DIFF_THRESHOLD_LOW, N_TOKENS,
+    };
+    use ribble_whisper::transcriber::RibbleWhisperSegment;
+    use ribble_whisper::utils::callback::RibbleWhisperCallback;
+    use strsim::jaro_winkler;
+
+    const N_CASES: u64 = 500;
+
+    // A small linear congruential generator, so that failures are reproducible from their seed.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 33) as usize % bound
+        }
+    }
+
+    // Random lowercase words that are all dissimilar from each other, so that the only overlap
+    // between two texts is the one a test puts there.
+    fn distinct_words(rng: &mut Lcg, n: usize) -> Vec<String> {
+        let mut words: Vec<String> = Vec::with_capacity(n);
+        while words.len() < n {
+            let len = 4 + rng.next(5);
+            let word: String = (0..len)
+                .map(|_| (b'a' + rng.next(26) as u8) as char)
+                .collect();
+            if words
+                .iter()
+                .all(|w| jaro_winkler(w, &word) < DIFF_THRESHOLD_LOW)
+            {
+                words.push(word);
+            }
+        }
+        words
+    }
+
+    fn segment(text: &str) -> RibbleWhisperSegment {
+        RibbleWhisperSegment {
+            text: Arc::from(text),
+            start_time: 0,
+            end_time: 0,
+            confidence: None,
+            word_confidences: None,
+            speaker: None,
+        }
+    }
+
+    #[test]
+    fn test_find_closest_match() {
+        let left = ["the", "cat", "sat", "on"];
+        let right = ["sat", "on", "the", "mat"];
+        // The last left word with a match anchors the overlap.
+        assert_eq!(
+            find_closest_match(&left, &right, DIFF_THRESHOLD_HIGH),
+            Some((3, 1))
+        );
+        assert_eq!(
+            find_closest_match(&left, &["dog", "ran"], DIFF_THRESHOLD_HIGH),
+            None
+        );
+        // Repeated words match as close to the boundary as possible.
+        assert_eq!(
+            find_closest_match(&["go"], &["go", "go"], DIFF_THRESHOLD_HIGH),
+            Some((0, 1))
+        );
+
+        assert_eq!(run_stride(&left, 2, &right, 0, DIFF_THRESHOLD_HIGH), (4, 2));
+        assert_eq!(run_stride(&left, 0, &right, 0, DIFF_THRESHOLD_HIGH), (0, 0));
+    }
+
+    // Overlaps taken from realtime windows, with whisper's usual respellings at the boundary.
+    #[test]
+    fn test_dedup_corpus() {
+        let corpus = [
+            (
+                "and then we went to the",
+                "went to the store to buy",
+                Some(("and then we went to the", "store to buy")),
+            ),
+            (
+                "I think that's about it for today.",
+                "for today. Thanks everyone.",
+                Some(("I think that's about it for today.", "Thanks everyone.")),
+            ),
+            (
+                "so the results were pretty good",
+                "were pretty good, overall.",
+                Some(("so the results were pretty good", "overall.")),
+            ),
+            ("Let me share my screen", "Can everyone see this?", None),
+        ];
+        for (left, right, expected) in corpus {
+            let deduped = deduplicate_strings(left, right);
+            assert_eq!(
+                deduped.as_ref().map(|(l, r)| (l.as_str(), r.as_str())),
+                expected,
+                "Incorrect deduplication of {left:?} and {right:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_dedup_exact_overlap() {
+        for seed in 0..N_CASES {
+            let mut rng = Lcg(seed);
+            let words = distinct_words(&mut rng, 24);
+            let n_before = rng.next(8);
+            let n_overlap = 1 + rng.next(N_TOKENS);
+            let n_after = rng.next(8);
+            let (before, rest) = words.split_at(n_before);
+            let (overlap, rest) = rest.split_at(n_overlap);
+            let after = &rest[..n_after];
+
+            let left = [before, overlap].concat().join(" ");
+            let right = [overlap, after].concat().join(" ");
+            let expected = [before, overlap, after].concat().join(" ");
+
+            let (l, r) = deduplicate_strings(&left, &right)
+                .unwrap_or_else(|| panic!("Seed {seed}: no overlap in {left:?} and {right:?}"));
+            assert_eq!(
+                [l, r].join(" ").trim(),
+                expected,
+                "Seed {seed}: incorrect deduplication of {left:?} and {right:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_dedup_properties() {
+        for seed in 0..N_CASES {
+            let mut rng = Lcg(seed);
+            let words = distinct_words(&mut rng, 12);
+            // Texts drawn from the same few words, so that they overlap somewhere, if at all.
+            let text = |rng: &mut Lcg| {
+                (0..1 + rng.next(10))
+                    .map(|_| words[rng.next(words.len())].as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            let left = text(&mut rng);
+            let right = text(&mut rng);
+
+            if let Some((l, r)) = deduplicate_strings(&left, &right) {
+                assert!(
+                    left.starts_with(&l),
+                    "Seed {seed}: {l:?} is not a prefix of {left:?}"
+                );
+                assert!(
+                    right.ends_with(&r),
+                    "Seed {seed}: {r:?} is not a suffix of {right:?}"
+                );
+            }
+
+            let mut blended = segment(&left);
+            blend_segments(&mut blended, &segment(&right));
+            assert!(
+                left.starts_with(blended.text()),
+                "Seed {seed}: blending {left:?} with {right:?} gave {:?}",
+                blended.text()
+            );
+        }
+    }
+
+    #[test]
+    fn test_dedup_disjoint() {
+        for seed in 0..N_CASES {
+            let mut rng = Lcg(seed);
+            let words = distinct_words(&mut rng, 16);
+            let (left, right) = words.split_at(8);
+            assert!(
+                deduplicate_strings(&left.join(" "), &right.join(" ")).is_none(),
+                "Seed {seed}: found an overlap in distinct texts."
+            );
+
+            let left = left.join(" ");
+            let mut blended = segment(&left);
+            blend_segments(&mut blended, &segment(&right.join(" ")));
+            assert_eq!(blended.text(), left);
+        }
+    }
+
+    #[test]
+    fn test_dedup_configs() {
+        let left = "it was a long day at work.";
+        let right = "at work, then I went home";
+        let deduped = deduplicate_strings(left, right);
+        assert_eq!(
+            deduped,
+            Some((left.to_string(), "then I went home".to_string()))
+        );
+
+        // Punctuation differences no longer match with exact thresholds.
+        let exact = DedupConfigs::new()
+            .with_match_threshold(1.0)
+            .with_stride_threshold(1.0);
+        assert_eq!(
+            exact.deduplicate_strings(left, right),
+            Some((
+                "it was a long day at".to_string(),
+                "work, then I went home".to_string()
+            ))
+        );
+
+        // Comparing a single word only looks at the boundary.
+        let narrow = DedupConfigs::new().with_n_tokens(1);
+        assert!(narrow
+            .deduplicate_strings("one two three", "two three four")
+            .is_none());
+        assert_eq!(DedupConfigs::new().with_n_tokens(0).n_tokens(), 1);
+        assert_eq!(
+            DedupConfigs::new()
+                .with_stride_threshold(2.0)
+                .stride_threshold(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_blend_segments() {
+        let mut left = segment("we should probably get going before");
+        blend_segments(&mut left, &segment("probably get going before it rains"));
+        assert_eq!(left.text(), "we should probably get going before");
+
+        // Words after the overlap on the left are dropped, except for the one just after it.
+        let mut left = segment("the meeting starts at nine thirty sharp");
+        blend_segments(&mut left, &segment("starts at nine, okay"));
+        assert_eq!(left.text(), "the meeting starts at nine thirty");
+    }
+
+    #[test]
+    fn test_dedup_diagnostics() {
+        let configs = DedupConfigs::new();
+        let mut events = vec![];
+        let mut diagnostics = RibbleWhisperCallback::new(|event: DedupEvent| events.push(event));
+
+        let deduped = configs.deduplicate_strings_with_diagnostics(
+            "and then we went to the",
+            "went to the store",
+            &mut diagnostics,
+        );
+        assert!(deduped.is_some());
+        configs.deduplicate_strings_with_diagnostics(
+            "Let me share my screen",
+            "Can everyone see this?",
+            &mut diagnostics,
+        );
+        let mut left = segment("the meeting starts at nine thirty sharp");
+        configs.blend_segments_with_diagnostics(
+            &mut left,
+            &segment("starts at nine, okay"),
+            &mut diagnostics,
+        );
+
+        assert_eq!(
+            events,
+            vec![
+                DedupEvent {
+                    stage: DedupStage::Deduplicate,
+                    outcome: DedupOutcome::Applied,
+                    match_len: 1,
+                    left_trailing: 0,
+                    right_leading: 3,
+                    kept: DedupSide::Left,
+                },
+                DedupEvent {
+                    stage: DedupStage::Deduplicate,
+                    outcome: DedupOutcome::NoMatch,
+                    match_len: 0,
+                    left_trailing: 0,
+                    right_leading: 0,
+                    kept: DedupSide::Left,
+                },
+                DedupEvent {
+                    stage: DedupStage::Blend,
+                    outcome: DedupOutcome::Applied,
+                    match_len: 1,
+                    left_trailing: 2,
+                    right_leading: 3,
+                    kept: DedupSide::Left,
+                },
+            ]
+        );
+    }
+
+    fn timed_segment(text: &str, start_time: i64, end_time: i64) -> RibbleWhisperSegment {
+        RibbleWhisperSegment {
+            text: Arc::from(text),
+            start_time,
+            end_time,
+            confidence: None,
+            word_confidences: None,
+            speaker: None,
+        }
+    }
+
+    #[test]
+    fn test_dedup_segments_aligned() {
+        let configs = DedupConfigs::new().with_merge_strategy(MergeStrategy::Timestamps);
+        assert_eq!(configs.merge_strategy(), MergeStrategy::Timestamps);
+        let mut nop = RibbleWhisperCallback::new(|_: DedupEvent| {});
+
+        // The speaker repeats "yes" after the half second the windows share. Text similarity
+        // alone anchors on the repeat, and drops everything before it.
+        let left = timed_segment("I said yes", 0, 300);
+        let right = timed_segment("yes we will yes", 0, 300);
+        assert_eq!(
+            configs.deduplicate_strings(left.text(), right.text()),
+            Some(("I said yes".to_string(), String::new()))
+        );
+        let (l, r) = configs
+            .deduplicate_segments_aligned(&left, &right, 280, &mut nop)
+            .unwrap();
+        assert_eq!([l, r].join(" "), "I said yes we will yes");
+
+        // Windows that don't share any time are never deduplicated, even if the words match.
+        let left = timed_segment("we can go now", 0, 100);
+        let right = timed_segment("go now please", 0, 100);
+        assert!(
+            configs
+                .deduplicate_segments_aligned(&left, &right, 200, &mut nop)
+                .is_none()
+        );
+        let mut blended = left.clone();
+        configs.blend_segments_aligned(&mut blended, &right, 200, &mut nop);
+        assert_eq!(blended.text(), "we can go now");
+
+        // Missing timestamps fall back to text similarity.
+        let left = segment("we can go now");
+        let right = segment("go now please");
+        assert_eq!(
+            configs.deduplicate_segments_aligned(&left, &right, 200, &mut nop),
+            configs.deduplicate_strings(left.text(), right.text())
+        );
+    }
+
+    #[test]
+    fn test_dedup_conflict_policy() {
+        let confident = DedupConfigs::new().with_conflict_policy(ConflictPolicy::Confidence);
+        let mut nop = RibbleWhisperCallback::new(|_: DedupEvent| {});
+        let with_confidence = |text: &str, confidence: f32| RibbleWhisperSegment {
+            confidence: Some(confidence),
+            ..segment(text)
+        };
+
+        // The earlier window heard the end of the phrase cut off.
+        let left = with_confidence("I'll see you at noon", 0.4);
+        let right = with_confidence("noon, okay bye", 0.8);
+        assert_eq!(
+            DedupConfigs::new().deduplicate_segments(&left, &right, &mut nop),
+            Some(("I'll see you at noon".to_string(), "okay bye".to_string()))
+        );
+        assert_eq!(
+            confident.deduplicate_segments(&left, &right, &mut nop),
+            Some(("I'll see you at".to_string(), "noon, okay bye".to_string()))
+        );
+
+        // A less confident right window never replaces the left.
+        let right = with_confidence("noon, okay bye", 0.2);
+        assert_eq!(
+            confident.deduplicate_segments(&left, &right, &mut nop),
+            DedupConfigs::new().deduplicate_segments(&left, &right, &mut nop)
+        );
+
+        let mut blended = left.clone();
+        confident.blend_segments(&mut blended, &with_confidence("noon, okay bye", 0.8));
+        assert_eq!(blended.text(), "I'll see you at noon, okay");
+
+        // Unknown confidence falls back to keeping the left.
+        let mut blended = segment("I'll see you at noon");
+        confident.blend_segments(&mut blended, &with_confidence("noon, okay bye", 0.8));
+        assert_eq!(blended.text(), "I'll see you at noon");
+    }
+
+    #[test]
+    fn test_dedup_word_confidences() {
+        let confident = DedupConfigs::new()
+            .with_conflict_policy(ConflictPolicy::Confidence)
+            .with_n_tokens(2);
+        let mut nop = RibbleWhisperCallback::new(|_: DedupEvent| {});
+        let with_confidences = |text: &str, confidence: f32, words: &[f32]| RibbleWhisperSegment {
+            confidence: Some(confidence),
+            word_confidences: Some(Arc::from(words)),
+            ..segment(text)
+        };
+
+        // The left segment is more confident overall, but not in the words the windows share.
+        let left = with_confidences("I'll see you at noon", 0.9, &[0.9, 0.9, 0.9, 0.9, 0.2]);
+        let right = with_confidences("noon, okay bye", 0.5, &[0.8, 0.6, 0.9]);
+        assert_eq!(
+            confident.deduplicate_segments(&left, &right, &mut nop),
+            Some(("I'll see you at".to_string(), "noon, okay bye".to_string()))
+        );
+
+        // Without word confidences, the whole segments are compared.
+        let whole = |segment: &RibbleWhisperSegment| RibbleWhisperSegment {
+            word_confidences: None,
+            ..segment.clone()
+        };
+        assert_eq!(
+            confident.deduplicate_segments(&whole(&left), &whole(&right), &mut nop),
+            Some(("I'll see you at noon".to_string(), "okay bye".to_string()))
+        );
+
+        // Blending keeps the word confidences in step with the blended text.
+        let mut blended = left.clone();
+        confident.blend_segments(&mut blended, &right);
+        assert_eq!(blended.text(), "I'll see you at noon, okay");
+        assert_eq!(
+            blended.word_confidences.as_deref(),
+            Some(&[0.9, 0.9, 0.9, 0.9, 0.8, 0.6][..])
+        );
+
+        // Taking words without known confidences leaves the blend's unknown.
+        let mut blended = left.clone();
+        confident.blend_segments(
+            &mut blended,
+            &RibbleWhisperSegment {
+                confidence: Some(0.8),
+                ..segment("noon, okay bye")
+            },
+        );
+        assert_eq!(blended.text(), "I'll see you at noon, okay");
+        assert!(blended.word_confidences.is_none());
+    }
+
+    // Deserialized configs are clamped like the setters would clamp them, (e.g. so that a config
+    // file can't set n_tokens to 0).
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_dedup_configs_deserialize() {
+        let configs: DedupConfigs =
+            serde_json::from_str(r#"{"n_tokens": 0, "match_threshold": 1.5}"#).unwrap();
+        assert_eq!(configs.n_tokens(), 1);
+        assert_eq!(configs.match_threshold(), 1.0);
+        assert_eq!(
+            configs.stride_threshold(),
+            DedupConfigs::new().stride_threshold()
+        );
+    }
+}
@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod document_export_tests {
+    use ribble_whisper::export::confidence::{ConfidenceStyle, ConfidenceStyles};
+    use ribble_whisper::export::document::TranscriptExporter;
+    use ribble_whisper::export::jsonl::SegmentRecord;
+    use ribble_whisper::postprocess::document::{DocumentSegmenter, TranscriptDocument};
+
+    fn meeting() -> TranscriptDocument {
+        let records = [
+            SegmentRecord::new("Welcome, everyone.", 0, 1500)
+                .with_speaker("Alice")
+                .with_confidence(0.9),
+            SegmentRecord::new("Let's begin.", 1600, 2500)
+                .with_speaker("Alice")
+                .with_confidence(0.8),
+            SegmentRecord::new("Thanks. I have *two* updates.", 65_000, 68_000)
+                .with_speaker("Bob")
+                .with_confidence(0.3),
+        ];
+        DocumentSegmenter::new().segment_records(&records)
+    }
+
+    #[test]
+    fn test_segment_records() {
+        let document = meeting();
+        assert_eq!(
+            document.paragraphs().len(),
+            2,
+            "A change of speaker should start a new paragraph."
+        );
+        let alice = &document.paragraphs()[0];
+        assert_eq!(alice.speaker(), Some("Alice"));
+        assert_eq!(alice.start_timestamp(), Some(0));
+        let confidence = alice.sentences()[1].confidence().unwrap();
+        assert!((confidence - 0.8).abs() < 1e-6);
+        assert_eq!(document.paragraphs()[1].start_timestamp(), Some(6500));
+    }
+
+    #[test]
+    fn test_markdown() {
+        let markdown = TranscriptExporter::new()
+            .with_title("Standup")
+            .to_markdown(&meeting());
+        assert_eq!(
+            markdown,
+            "# Standup\n\n\
+             ## 00:00:00 — Alice\n\nWelcome, everyone. Let's begin.\n\n\
+             ## 00:01:05 — Bob\n\n*Thanks.* *I have \\*two\\* updates.*\n\n",
+            "Low confidence sentences should be italic, and Markdown should be escaped."
+        );
+
+        let markdown = TranscriptExporter::new()
+            .with_timestamps(false)
+            .with_speakers(false)
+            .with_confidence_threshold(0.0)
+            .to_markdown(&meeting());
+        assert_eq!(
+            markdown,
+            "Welcome, everyone. Let's begin.\n\nThanks. I have \\*two\\* updates.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let html = TranscriptExporter::new()
+            .with_title("Q&A")
+            .to_html(&meeting());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Q&amp;A</title>"));
+        assert!(html.contains("<section id=\"t-65\">"));
+        assert!(html.contains(
+            "<h2><a href=\"#t-65\">00:01:05</a> <span class=\"speaker\">Bob</span></h2>"
+        ));
+        assert!(html
+            .contains("<span class=\"low-confidence\" title=\"Confidence: 30%\">Thanks.</span>"));
+        assert!(html.contains("<p>Welcome, everyone. Let's begin.</p>"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn test_confidence_styles() {
+        let styles = ConfidenceStyles::new().with_band(
+            0.85,
+            ConfidenceStyle::new()
+                .with_colour(0x99, 0x33, 0x33)
+                .with_class("review"),
+        );
+        let exporter = TranscriptExporter::new().with_confidence_styles(styles);
+        let html = exporter.to_html(&meeting());
+        assert!(html.contains(
+            "<span class=\"review\" style=\"color: #993333\" title=\"Confidence: 80%\">Let's begin.</span>"
+        ));
+        assert!(html.contains("<p>Welcome, everyone. <span"));
+
+        let markdown = exporter.to_markdown(&meeting());
+        assert!(
+            !markdown.contains("*Let's begin.*"),
+            "Styles without italics should not italicize Markdown."
+        );
+        let markdown = TranscriptExporter::new()
+            .with_confidence_styles(ConfidenceStyles::uncertain_below(0.85))
+            .to_markdown(&meeting());
+        assert!(markdown.contains("Welcome, everyone. *Let's begin.*"));
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod document_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ribble_whisper::postprocess::document::DocumentSegmenter;
+    use ribble_whisper::transcriber::{RibbleWhisperSegment, TranscriptionSnapshot};
+
+    fn segment(text: &str, start_time: i64, end_time: i64) -> RibbleWhisperSegment {
+        RibbleWhisperSegment {
+            text: Arc::from(text),
+            start_time,
+            end_time,
+            confidence: None,
+            word_confidences: None,
+            speaker: None,
+        }
+    }
+
+    #[test]
+    fn test_segment_text() {
+        let document = DocumentSegmenter::new()
+            .segment_text("Hello there. Dr. Smith is in!  Is it 3.5 now? \"Yes.\" Trailing");
+        let sentences: Vec<&str> = document.sentences().map(|s| s.text()).collect();
+        assert_eq!(
+            sentences,
+            vec![
+                "Hello there.",
+                "Dr. Smith is in!",
+                "Is it 3.5 now?",
+                "\"Yes.\"",
+                "Trailing"
+            ],
+            "Sentences should split on punctuation, skipping abbreviations and decimals."
+        );
+        assert_eq!(document.paragraphs().len(), 1);
+        assert!(
+            document.sentences().all(|s| s.start_timestamp().is_none()),
+            "Untimed text should not have timestamps."
+        );
+    }
+
+    #[test]
+    fn test_max_sentences() {
+        let snapshot = TranscriptionSnapshot::new(Arc::from("One. Two. Three."), Arc::from([]));
+        let document = DocumentSegmenter::new()
+            .with_max_sentences(2)
+            .segment_snapshot(&snapshot);
+        assert_eq!(document.paragraphs().len(), 2);
+        assert_eq!(document.to_string(), "One. Two.\n\nThree.");
+    }
+
+    #[test]
+    fn test_segment_pauses() {
+        let segments = [
+            segment(" Welcome to the", 0, 100),
+            segment(" meeting. First item", 100, 300),
+            // A 2 second pause.
+            segment(" second item.", 500, 600),
+            // A 4 second pause.
+            segment(" New topic.", 1000, 1100),
+        ];
+        let document = DocumentSegmenter::new()
+            .with_sentence_pause(Duration::from_secs(1))
+            .with_paragraph_pause(Duration::from_secs(3))
+            .segment(&segments);
+        let sentences: Vec<&str> = document.sentences().map(|s| s.text()).collect();
+        assert_eq!(
+            sentences,
+            vec![
+                "Welcome to the meeting.",
+                "First item",
+                "second item.",
+                "New topic."
+            ],
+            "A sentence pause should end a sentence without punctuation."
+        );
+        assert_eq!(
+            document.to_string(),
+            "Welcome to the meeting. First item second item.\n\nNew topic.",
+            "A paragraph pause should start a new paragraph."
+        );
+
+        let document = DocumentSegmenter::new()
+            .with_sentence_pause(Duration::from_secs(5))
+            .with_paragraph_pause(Duration::from_secs(5))
+            .segment(&segments);
+        assert_eq!(document.paragraphs().len(), 1);
+        let paragraph = &document.paragraphs()[0];
+        assert_eq!(paragraph.sentences()[1].text(), "First item second item.");
+        assert_eq!(paragraph.start_timestamp(), Some(0));
+        assert_eq!(
+            paragraph.sentences()[1].start_timestamp(),
+            Some(195),
+            "Timestamps within a segment should be interpolated."
+        );
+        assert_eq!(paragraph.end_timestamp(), Some(1100));
+    }
+}
@@ -7,9 +7,13 @@ mod downloader_tests {
 
     use crate::common::prep_model_bank;
     use ribble_whisper::downloader;
+    use ribble_whisper::downloader::cache::CacheValidators;
+    use ribble_whisper::downloader::manager::{BandwidthLimiter, DownloadManager};
+    use ribble_whisper::downloader::progress::{DownloadProgress, DownloadProgressTracker};
+    use ribble_whisper::downloader::request::{DownloadRequest, RetryPolicy};
     use ribble_whisper::downloader::AsyncDownload;
     use ribble_whisper::downloader::SyncDownload;
-    use ribble_whisper::utils::callback::{RibbleAbortCallback, RibbleWhisperCallback};
+    use ribble_whisper::utils::callback::{Callback, RibbleAbortCallback, RibbleWhisperCallback};
     use ribble_whisper::utils::errors::RibbleWhisperError;
     use ribble_whisper::whisper::model::{
         DefaultModelType, ModelBank, ModelLocation, ModelRetriever,
@@ -429,4 +433,375 @@ mod downloader_tests {
             "Content name algorithm didn't fallback with invalid url. Expected: {model_name}, Actual: {content_name}",
         );
     }
+
+    #[test]
+    fn test_retry_policy_backoff() {
+        let policy = RetryPolicy::new()
+            .with_initial_backoff(std::time::Duration::from_millis(100))
+            .with_max_backoff(std::time::Duration::from_millis(350))
+            .with_backoff_multiplier(2.0);
+        let backoffs = (0..4)
+            .map(|retry| policy.backoff(retry).as_millis())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            backoffs,
+            vec![100, 200, 350, 350],
+            "Backoff not exponential/capped: {:?}",
+            backoffs
+        );
+        assert_eq!(
+            RetryPolicy::none().max_attempts(),
+            1,
+            "No-retry policy should make a single attempt."
+        );
+    }
+
+    #[test]
+    fn test_download_request_mirror_fallback() {
+        let model_type = DefaultModelType::TinyEn;
+        let request = DownloadRequest::for_model(model_type);
+        assert_eq!(
+            request.urls()[0],
+            model_type.url(),
+            "Huggingface should be the primary url."
+        );
+        assert!(
+            request.urls().len() > 1,
+            "Default models should have mirrors."
+        );
+
+        // An unparseable primary url should fall through to the (valid) mirror.
+        let downloader = DownloadRequest::new("not a url", model_type.to_file_name())
+            .with_mirror(model_type.url().as_str())
+            .with_retry_policy(RetryPolicy::none())
+            .send_sync();
+        assert!(
+            downloader.is_ok(),
+            "Failed to fall back to mirror: {}",
+            downloader.err().unwrap()
+        );
+        let downloader = downloader.unwrap();
+        assert_eq!(
+            downloader.content_name(),
+            model_type.to_file_name(),
+            "Incorrect content name from mirror."
+        );
+    }
+
+    #[test]
+    fn test_download_progress_tracker() {
+        let updates = std::cell::RefCell::new(vec![]);
+        let mut tracker = DownloadProgressTracker::new(
+            RibbleWhisperCallback::new(|progress: DownloadProgress| {
+                updates.borrow_mut().push(progress)
+            }),
+            1000,
+        );
+        tracker.call(0);
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        tracker.call(500);
+        drop(tracker);
+
+        let updates = updates.into_inner();
+        let last = updates.last().expect("No progress updates received.");
+        assert_eq!(last.bytes, 500, "Incorrect byte count: {}", last.bytes);
+        assert_eq!(last.total, Some(1000), "Incorrect total: {:?}", last.total);
+        assert!(
+            last.bytes_per_sec > 0.0,
+            "Download speed not computed: {}",
+            last.bytes_per_sec
+        );
+        assert!(last.eta.is_some(), "ETA not computed.");
+        assert_eq!(last.fraction(), Some(0.5), "Incorrect fraction.");
+
+        let mut indeterminate = DownloadProgressTracker::new(
+            RibbleWhisperCallback::new(|progress: DownloadProgress| {
+                assert!(progress.total.is_none(), "Size 1 should be indeterminate.");
+                assert!(
+                    progress.eta.is_none(),
+                    "Indeterminate downloads have no ETA."
+                );
+            }),
+            1,
+        );
+        indeterminate.call(10);
+    }
+
+    #[test]
+    fn test_download_manager_concurrency() {
+        let manager = DownloadManager::new().with_max_concurrent(2);
+        let first = manager.try_acquire();
+        let second = manager.clone().try_acquire();
+        assert!(
+            first.is_some() && second.is_some(),
+            "Failed to acquire free slots."
+        );
+        assert_eq!(
+            manager.active_downloads(),
+            2,
+            "Incorrect active download count."
+        );
+        assert!(
+            manager.try_acquire().is_none(),
+            "Acquired more than max_concurrent slots."
+        );
+
+        drop(first);
+        assert_eq!(manager.active_downloads(), 1, "Slot not released on drop.");
+        assert!(
+            manager.try_acquire().is_some(),
+            "Failed to acquire a released slot."
+        );
+    }
+
+    #[test]
+    fn test_bandwidth_limited_download() {
+        let limiter = BandwidthLimiter::new(1000);
+        assert!(
+            limiter.reserve(1000).is_zero(),
+            "Bursts within the limit should not wait."
+        );
+        assert!(
+            !limiter.reserve(500).is_zero(),
+            "Reads over the limit should wait."
+        );
+
+        let file_directory = std::env::current_dir()
+            .unwrap()
+            .join("data")
+            .join("throttle_test");
+        let data = vec![0u8; 1500];
+        let mut downloader = downloader::downloaders::SyncDownloader::new_with_parameters(
+            std::io::Cursor::new(data),
+            "throttled.bin".to_string(),
+            1500,
+        )
+        .with_bandwidth_limiter(Some(BandwidthLimiter::new(1000)));
+
+        let start = std::time::Instant::now();
+        let path = downloader
+            .download(&file_directory)
+            .expect("Throttled download failed.");
+        let elapsed = start.elapsed();
+        let _ = std::fs::remove_dir_all(&file_directory);
+
+        assert_eq!(
+            path.file_name().unwrap(),
+            "throttled.bin",
+            "Incorrect download path: {path:?}"
+        );
+        assert!(
+            elapsed >= std::time::Duration::from_millis(400),
+            "Download was not throttled: {elapsed:?}"
+        );
+    }
+
+    #[test]
+    fn test_cache_validators_roundtrip() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert!(
+            CacheValidators::from_headers(&headers).is_none(),
+            "Validators parsed from empty headers."
+        );
+        headers.insert(reqwest::header::ETAG, "\"abc123\"".parse().unwrap());
+        let validators =
+            CacheValidators::from_headers(&headers).expect("Failed to parse the ETag header.");
+        assert_eq!(
+            validators.etag.as_deref(),
+            Some("\"abc123\""),
+            "Incorrect ETag."
+        );
+        assert!(
+            validators.last_modified.is_none(),
+            "Unexpected Last-Modified."
+        );
+
+        let file_directory = std::env::current_dir()
+            .unwrap()
+            .join("data")
+            .join("cache_test");
+        std::fs::create_dir_all(&file_directory).unwrap();
+        let file_path = file_directory.join("model.bin");
+        assert_eq!(
+            CacheValidators::sidecar_path(&file_path),
+            file_directory.join("model.bin.validators"),
+            "Incorrect sidecar path."
+        );
+
+        validators
+            .save(&file_path)
+            .expect("Failed to save validators.");
+        let loaded = CacheValidators::load(&file_path);
+        CacheValidators::remove(&file_path);
+        let removed = CacheValidators::load(&file_path);
+        let _ = std::fs::remove_dir_all(&file_directory);
+
+        assert_eq!(loaded, Some(validators), "Validators did not roundtrip.");
+        assert!(removed.is_none(), "Validators not removed.");
+    }
+
+    #[test]
+    fn test_download_request_client_options() {
+        let request = DownloadRequest::new("https://example.com/model.bin", "model.bin")
+            .with_proxy("http://proxy.example.com:8080")
+            .with_user_agent("ribble-test/1.0")
+            .with_ca_bundle(std::path::PathBuf::from("corp-ca.pem"));
+        assert_eq!(
+            request.proxy(),
+            Some("http://proxy.example.com:8080"),
+            "Incorrect proxy."
+        );
+        assert_eq!(
+            request.user_agent(),
+            Some("ribble-test/1.0"),
+            "Incorrect user agent."
+        );
+        assert_eq!(request.ca_bundles().len(), 1, "Incorrect CA bundle count.");
+
+        // The CA bundle does not exist, so the client cannot be built.
+        let missing_bundle = request.with_retry_policy(RetryPolicy::none()).send_sync();
+        assert!(
+            matches!(missing_bundle, Err(RibbleWhisperError::IOError(_))),
+            "Missing CA bundle should return an IOError."
+        );
+    }
+
+    #[test]
+    fn test_download_to_writer() {
+        let data = (0..4096u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let progress = std::cell::Cell::new(0);
+        let mut downloader = downloader::downloaders::SyncDownloader::new_with_parameters(
+            std::io::Cursor::new(data.clone()),
+            "buffer.bin".to_string(),
+            data.len(),
+        )
+        .with_progress_callback(RibbleWhisperCallback::new(|bytes: usize| {
+            progress.set(bytes)
+        }));
+
+        let mut writer = vec![];
+        let written = downloader
+            .download_to_writer(&mut writer)
+            .expect("Download to writer failed.");
+        drop(downloader);
+        assert_eq!(written, data.len(), "Incorrect number of bytes written.");
+        assert_eq!(writer, data, "Incorrect bytes written.");
+        assert_eq!(progress.get(), data.len(), "Progress not reported.");
+
+        let mut downloader = downloader::downloaders::SyncDownloader::new_with_parameters(
+            std::io::Cursor::new(data.clone()),
+            "buffer.bin".to_string(),
+            data.len(),
+        );
+        let buffer = downloader
+            .download_to_shared_buffer()
+            .expect("Download to memory failed.");
+        assert_eq!(buffer.as_ref(), data.as_slice(), "Incorrect shared buffer.");
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn test_extract_archive() {
+        use ribble_whisper::downloader::archive::{
+            ArchiveFormat, ExtractArchive, ExtractionProgress, PostDownload,
+        };
+        use std::io::Write;
+
+        let directory = std::env::current_dir()
+            .unwrap()
+            .join("data")
+            .join("archive_test");
+        let destination = directory.join("extracted");
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let archive_path = directory.join("encoder.zip");
+        {
+            let mut writer = zip::ZipWriter::new(std::fs::File::create(&archive_path).unwrap());
+            let options = zip::write::SimpleFileOptions::default();
+            writer.add_directory("encoder.mlmodelc/", options).unwrap();
+            writer
+                .start_file("encoder.mlmodelc/weights.bin", options)
+                .unwrap();
+            writer.write_all(b"weights").unwrap();
+            writer.start_file("../escaped.bin", options).unwrap();
+            writer.write_all(b"unsafe").unwrap();
+            writer.finish().unwrap();
+        }
+        assert_eq!(
+            ArchiveFormat::from_path(&archive_path),
+            Some(ArchiveFormat::Zip),
+            "Failed to detect the archive format."
+        );
+
+        let last_progress = std::cell::Cell::new(None);
+        let mut extract = ExtractArchive::new()
+            .with_destination(destination.clone())
+            .with_progress_callback(RibbleWhisperCallback::new(
+                |progress: ExtractionProgress| last_progress.set(Some(progress)),
+            ));
+        let extracted = extract.process(&archive_path);
+        drop(extract);
+
+        let weights = std::fs::read(destination.join("encoder.mlmodelc").join("weights.bin"));
+        let escaped = directory.join("escaped.bin").exists();
+        let archive_removed = !archive_path.exists();
+        let _ = std::fs::remove_dir_all(&directory);
+
+        assert_eq!(
+            extracted.expect("Failed to extract archive."),
+            destination,
+            "Incorrect extraction directory."
+        );
+        assert_eq!(
+            weights.expect("Missing extracted file."),
+            b"weights",
+            "Incorrect extracted contents."
+        );
+        assert!(!escaped, "Extracted an entry outside of the destination.");
+        assert!(archive_removed, "Archive not removed after extraction.");
+
+        let progress = last_progress
+            .get()
+            .expect("No extraction progress reported.");
+        assert_eq!(progress.total_entries, Some(3), "Incorrect entry count.");
+        assert_eq!(
+            progress.bytes_extracted, 7,
+            "Incorrect extracted byte count."
+        );
+    }
+
+    #[test]
+    fn test_disk_space_preflight() {
+        let file_directory = std::env::current_dir()
+            .unwrap()
+            .join("data")
+            .join("preflight_test");
+        std::fs::create_dir_all(&file_directory).unwrap();
+        let available = downloader::available_disk_space(&file_directory)
+            .expect("Failed to query free disk space.");
+        assert!(available > 0, "No free disk space reported.");
+
+        // Claim a download far larger than any disk.
+        let mut downloader = downloader::downloaders::SyncDownloader::new_with_parameters(
+            std::io::Cursor::new(vec![0u8; 16]),
+            "huge.bin".to_string(),
+            usize::MAX / 2,
+        );
+        let result = downloader.download(&file_directory);
+        let tmp_created = file_directory.join("huge.bin.tmp").exists();
+        let _ = std::fs::remove_dir_all(&file_directory);
+
+        assert!(
+            matches!(
+                result,
+                Err(RibbleWhisperError::InsufficientDiskSpace { needed, .. })
+                    if needed > (usize::MAX / 2) as u64
+            ),
+            "Oversized download was not rejected: {result:?}"
+        );
+        assert!(
+            !tmp_created,
+            "Download started despite the preflight failure."
+        );
+    }
 }
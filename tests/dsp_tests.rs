@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod dsp_tests {
+    use ribble_whisper::audio::dsp;
+    use ribble_whisper::audio::pcm::{convert_float_to_integer_audio, IntoPcmS16};
+
+    // Lengths on either side of the vector widths, so that the remainder is covered.
+    const LENGTHS: [usize; 8] = [0, 1, 3, 4, 7, 8, 9, 1003];
+
+    fn float_audio(len: usize) -> Vec<f32> {
+        (0..len).map(|i| (i as f32 * 0.37).sin() * 1.5).collect()
+    }
+
+    #[test]
+    fn test_gain_and_normalize() {
+        for len in LENGTHS {
+            let audio = float_audio(len);
+            let mut gained = audio.clone();
+            dsp::apply_gain(&mut gained, 0.3);
+            let expected: Vec<f32> = audio.iter().map(|s| s * 0.3).collect();
+            assert_eq!(gained, expected);
+
+            let expected_peak = audio.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+            assert_eq!(dsp::peak(&audio), expected_peak, "Length: {len}");
+        }
+
+        let mut audio = vec![0.5, -2.0, 1.0, 0.25, f32::NAN, 0.1];
+        assert_eq!(dsp::peak(&audio), 2.0, "NaN samples should be ignored.");
+        assert_eq!(dsp::normalize(&mut audio, 1.0), 2.0);
+        assert_eq!(&audio[..4], &[0.25, -1.0, 0.5, 0.125]);
+
+        let mut quiet = vec![0.25f32; 9];
+        assert_eq!(
+            dsp::normalize(&mut quiet, 1.0),
+            1.0,
+            "Audio quieter than the floor should not be amplified."
+        );
+        assert_eq!(quiet, vec![0.25; 9]);
+    }
+
+    #[test]
+    fn test_pcm_conversion() {
+        for len in LENGTHS {
+            let integers: Vec<i16> = (0..len)
+                .map(|i| (i as i32 * 7919 % 65536 - 32768) as i16)
+                .collect();
+            let mut floats = vec![0.0; len];
+            dsp::integer_to_float(&integers, &mut floats);
+            let expected: Vec<f32> = integers.iter().map(|s| *s as f32 / 32768.0).collect();
+            assert_eq!(floats, expected);
+
+            let mut audio = float_audio(len);
+            if len > 8 {
+                audio[1] = f32::NEG_INFINITY;
+                audio[2] = f32::INFINITY;
+                audio[5] = f32::NAN;
+            }
+            let expected: Vec<i16> = audio.iter().map(|s| s.into_pcm_s16()).collect();
+            assert_eq!(convert_float_to_integer_audio(&audio), expected);
+        }
+    }
+}
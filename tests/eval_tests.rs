@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod eval_tests {
+    use ribble_whisper::transcriber::eval::{
+        ErrorCounts, Evaluator, character_error_rate, word_error_rate,
+    };
+
+    #[test]
+    fn test_word_error_rate() {
+        let reference = "The cat sat on the mat.";
+        assert_eq!(word_error_rate(reference, "the cat sat on the mat"), 0.0);
+
+        // One insertion, (oh), one substitution, (bat), and one deletion, (the).
+        let counts = Evaluator::new().word_errors(reference, "Oh, the bat sat on mat");
+        assert_eq!(
+            counts,
+            ErrorCounts {
+                substitutions: 1,
+                deletions: 1,
+                insertions: 1,
+                reference_len: 6,
+            }
+        );
+        assert_eq!(counts.errors(), 3);
+        assert_eq!(counts.hits(), 4);
+        assert_eq!(counts.rate(), 0.5);
+        assert_eq!(counts.accuracy(), 0.5);
+
+        // Insertions can push the rate above 1.0; accuracy is clamped.
+        let counts = Evaluator::new().word_errors("hi", "oh hi there you");
+        assert_eq!(counts.rate(), 3.0);
+        assert_eq!(counts.accuracy(), 0.0);
+
+        assert_eq!(word_error_rate("", ""), 0.0);
+        assert_eq!(word_error_rate("", "something"), 1.0);
+    }
+
+    #[test]
+    fn test_normalization() {
+        let evaluator = Evaluator::new();
+        assert_eq!(
+            evaluator.normalize("  Don't STOP -- it's well-known!  "),
+            "don't stop it's well known"
+        );
+        assert_eq!(
+            Evaluator::new()
+                .with_case_sensitive(true)
+                .with_keep_punctuation(true)
+                .normalize("Hello,   World!"),
+            "Hello, World!"
+        );
+        assert_eq!(
+            Evaluator::new()
+                .with_case_sensitive(true)
+                .word_error_rate("Hello world", "hello world"),
+            0.5
+        );
+    }
+
+    #[test]
+    fn test_character_error_rate() {
+        // "kitten" -> "sitting": 2 substitutions and an insertion.
+        let counts = Evaluator::new().character_errors("kitten", "sitting");
+        assert_eq!(counts.substitutions, 2);
+        assert_eq!(counts.insertions, 1);
+        assert_eq!(counts.reference_len, 6);
+        assert_eq!(character_error_rate("kitten", "sitting"), 0.5);
+        assert_eq!(character_error_rate("Hello, world.", "hello world"), 0.0);
+    }
+
+    #[test]
+    fn test_evaluate_and_aggregate() {
+        let evaluation =
+            Evaluator::new().evaluate("I scream for ice cream", "ice scream for ice cream");
+        assert_eq!(evaluation.words().substitutions, 1);
+        assert_eq!(evaluation.word_error_rate(), 0.2);
+        assert_eq!(evaluation.alignment().changes().count(), 1);
+        assert!(evaluation.character_error_rate() > 0.0);
+
+        let corpus: ErrorCounts = [("a b c d", "a b c d"), ("a b", "a c")]
+            .iter()
+            .map(|(reference, hypothesis)| Evaluator::new().word_errors(reference, hypothesis))
+            .sum();
+        assert_eq!(corpus.reference_len, 6);
+        assert_eq!(corpus.errors(), 1);
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod excerpt_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use hound::{SampleFormat, WavReader};
+    use ribble_whisper::audio::excerpt::AudioExcerpter;
+    use ribble_whisper::audio::{AudioChannelConfiguration, WhisperAudioSample};
+    use ribble_whisper::transcriber::RibbleWhisperSegment;
+
+    fn segment(start_time: i64, end_time: i64) -> RibbleWhisperSegment {
+        RibbleWhisperSegment {
+            text: Arc::from("segment"),
+            start_time,
+            end_time,
+            confidence: None,
+            word_confidences: None,
+            speaker: None,
+        }
+    }
+
+    #[test]
+    fn test_sample_range() {
+        let excerpter = AudioExcerpter::new(1000, AudioChannelConfiguration::Stereo);
+        // 0.5s - 1.5s at 1 kHz stereo.
+        assert_eq!(excerpter.sample_range(&segment(50, 150), 4000), 1000..3000);
+        assert_eq!(
+            excerpter.sample_range(&segment(150, 300), 4000),
+            3000..4000,
+            "The range should be clamped to the audio."
+        );
+
+        let padded = excerpter.with_padding(Duration::from_millis(100));
+        assert_eq!(padded.sample_range(&segment(50, 150), 4000), 800..3200);
+        assert_eq!(
+            padded.sample_range(&segment(0, 10), 4000),
+            0..400,
+            "Padding should not extend before the start of the audio."
+        );
+    }
+
+    #[test]
+    fn test_write_excerpts() {
+        let dir = std::env::temp_dir().join("ribble_whisper_excerpt_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        // One second of 16 kHz mono audio, where each sample is its own index, (mod i16).
+        let samples: Vec<i16> = (0..16000).map(|i| i as i16).collect();
+        let audio = WhisperAudioSample::I16(Arc::from(samples));
+        let segments = [segment(0, 25), segment(25, 100)];
+
+        let paths = AudioExcerpter::default()
+            .write_excerpts(&dir, &audio, &segments)
+            .unwrap();
+        assert_eq!(paths.len(), 2);
+        assert!(paths[1].ends_with("segment_0001.wav"));
+
+        let mut reader = WavReader::open(&paths[1]).unwrap();
+        let spec = reader.spec();
+        assert_eq!(spec.sample_rate, 16000);
+        assert_eq!(spec.channels, 1);
+        assert_eq!(spec.bits_per_sample, 16);
+        assert_eq!(spec.sample_format, SampleFormat::Int);
+        let excerpt: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(excerpt.len(), 12000);
+        assert_eq!(excerpt[0], 4000, "The excerpt should start at 0.25s.");
+
+        let float_audio = WhisperAudioSample::F32(Arc::from(vec![0.5f32; 1600]));
+        let path = dir.join("float.wav");
+        AudioExcerpter::default()
+            .write_excerpt(&path, &float_audio, &segment(0, 5))
+            .unwrap();
+        let mut reader = WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().sample_format, SampleFormat::Float);
+        let excerpt: Vec<f32> = reader.samples::<f32>().map(|s| s.unwrap()).collect();
+        assert_eq!(excerpt, vec![0.5; 800]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod ffi_tests {
+    use ribble_whisper::ffi::configs::{
+        default_realtime_session_configs, default_transcription_configs,
+    };
+    use ribble_whisper::ffi::model::ModelBankHandle;
+    use ribble_whisper::utils::errors::RibbleWhisperError;
+    use ribble_whisper::whisper::configs::{
+        Language, RealtimeBufferingStrategy, WhisperConfigs, WhisperRealtimeConfigs,
+        WhisperSamplingStrategy,
+    };
+    use ribble_whisper::whisper::model::DefaultModelType;
+
+    #[test]
+    fn test_transcription_configs() {
+        let mut configs = default_transcription_configs(42);
+        assert_eq!(configs.model_id, 42);
+        assert!(configs.beam_size.is_none());
+
+        configs.language = Some("EN".to_string());
+        configs.beam_size = Some(5);
+        let whisper_configs = WhisperConfigs::try_from(&configs).unwrap();
+        assert_eq!(*whisper_configs.model_id(), Some(42));
+        assert_eq!(*whisper_configs.language(), Some(Language::En));
+        assert!(matches!(
+            whisper_configs.sampling_strategy(),
+            WhisperSamplingStrategy::BeamSearch { beam_size: 5, .. }
+        ));
+
+        configs.language = Some("xx".to_string());
+        assert!(matches!(
+            WhisperConfigs::try_from(&configs),
+            Err(RibbleWhisperError::ParameterError(_))
+        ));
+    }
+
+    #[test]
+    fn test_realtime_session_configs() {
+        let mut configs = default_realtime_session_configs(7);
+        assert!(
+            configs.whisper.use_no_context,
+            "Realtime defaults should not keep context."
+        );
+        configs.timeout_ms = 0;
+        configs.buffer_ms = Some(1500);
+
+        let realtime_configs = WhisperRealtimeConfigs::try_from(&configs).unwrap();
+        assert_eq!(*realtime_configs.model_id(), Some(7));
+        assert_eq!(realtime_configs.realtime_timeout(), 0);
+        assert_eq!(
+            realtime_configs.vad_sample_len(),
+            configs.vad_sample_len_ms as usize
+        );
+        assert!(matches!(
+            realtime_configs.realtime_buffering_strategy(),
+            RealtimeBufferingStrategy::Buffered { buffer_ms: 1500 }
+        ));
+        assert!(realtime_configs.using_no_context());
+    }
+
+    #[test]
+    fn test_model_bank_handle() {
+        let dir = std::env::temp_dir().join("ribble_whisper_ffi_model_bank_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("Failed to create test directory.");
+        let bank = ModelBankHandle::new(dir.to_string_lossy().into_owned());
+
+        let models = bank.list_models().unwrap();
+        assert!(!models.is_empty());
+        assert!(models.iter().all(|model| model.size_on_disk.is_none()));
+        let tiny_en = bank.default_model_id(DefaultModelType::TinyEn);
+        let model = models
+            .iter()
+            .find(|model| model.id == tiny_en)
+            .expect("Default models should be listed.");
+        assert!(!model.multilingual);
+        assert!(model.download_url.is_some());
+
+        let model_path = dir.join("ggml-finetuned.bin");
+        std::fs::write(&model_path, [0u8; 16]).expect("Failed to write test model.");
+        let custom_id = bank
+            .register_model_file(
+                "Fine-tuned".to_string(),
+                model_path.to_string_lossy().into_owned(),
+            )
+            .unwrap();
+        assert!(bank.model_in_storage(custom_id).unwrap());
+        assert!(bank
+            .list_models()
+            .unwrap()
+            .iter()
+            .any(|model| model.id == custom_id && model.size_on_disk == Some(16)));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod grammar_tests {
+    use ribble_whisper::utils::errors::RibbleWhisperError;
+    use ribble_whisper::whisper::grammar::{Grammar, GrammarElement, GrammarElementKind as Kind};
+
+    fn chars(text: &str) -> Vec<GrammarElement> {
+        text.chars()
+            .map(|c| GrammarElement::new(Kind::Character, c as u32))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_alternates() {
+        let grammar = Grammar::parse("root ::= \"yes\" | \"no\"\n").unwrap();
+        assert_eq!(grammar.rules().len(), 1);
+        assert_eq!(grammar.start_rule(), 0);
+
+        let mut expected = chars("yes");
+        expected.push(GrammarElement::new(Kind::Alternate, 0));
+        expected.extend(chars("no"));
+        expected.push(GrammarElement::new(Kind::End, 0));
+        assert_eq!(grammar.rules()[0], expected);
+    }
+
+    #[test]
+    fn test_parse_rule_references() {
+        let gbnf = r#"
+            # Slide controls
+            root    ::= " "? command
+            command ::= "next slide" | "previous slide"
+        "#;
+        let grammar = Grammar::parse(gbnf).unwrap();
+        let command = grammar.rule_id("command").unwrap();
+        let root = &grammar.rules()[grammar.start_rule()];
+        assert_eq!(root.last(), Some(&GrammarElement::new(Kind::End, 0)));
+        assert!(root.contains(&GrammarElement::new(Kind::RuleReference, command as u32)));
+        assert!(grammar.rule_id("missing").is_none());
+    }
+
+    #[test]
+    fn test_parse_character_classes() {
+        let grammar = Grammar::parse("root ::= [a-c_] [^0-9]\n").unwrap();
+        assert_eq!(
+            grammar.rules()[0],
+            vec![
+                GrammarElement::new(Kind::Character, 'a' as u32),
+                GrammarElement::new(Kind::CharacterRangeUpper, 'c' as u32),
+                GrammarElement::new(Kind::CharacterAlternate, '_' as u32),
+                GrammarElement::new(Kind::NotCharacter, '0' as u32),
+                GrammarElement::new(Kind::CharacterRangeUpper, '9' as u32),
+                GrammarElement::new(Kind::End, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_escapes() {
+        let grammar = Grammar::parse(r#"root ::= "\"\x41\u00e9\n""#).unwrap();
+        let mut expected = chars("\"A\u{e9}\n");
+        expected.push(GrammarElement::new(Kind::End, 0));
+        assert_eq!(grammar.rules()[0], expected);
+    }
+
+    #[test]
+    fn test_parse_repetitions() {
+        // x* --> root_1 ::= "x" root_1 |
+        let grammar = Grammar::parse("root ::= \"x\"*\n").unwrap();
+        let star = grammar.rule_id("root_1").unwrap();
+        assert_eq!(
+            grammar.rules()[0],
+            vec![
+                GrammarElement::new(Kind::RuleReference, star as u32),
+                GrammarElement::new(Kind::End, 0),
+            ]
+        );
+        assert_eq!(
+            grammar.rules()[star],
+            vec![
+                GrammarElement::new(Kind::Character, 'x' as u32),
+                GrammarElement::new(Kind::RuleReference, star as u32),
+                GrammarElement::new(Kind::Alternate, 0),
+                GrammarElement::new(Kind::End, 0),
+            ]
+        );
+
+        // x+ --> root_1 ::= "x" root_1 | "x"
+        let grammar = Grammar::parse("root ::= \"x\"+\n").unwrap();
+        let plus = grammar.rule_id("root_1").unwrap();
+        assert_eq!(
+            grammar.rules()[plus],
+            vec![
+                GrammarElement::new(Kind::Character, 'x' as u32),
+                GrammarElement::new(Kind::RuleReference, plus as u32),
+                GrammarElement::new(Kind::Alternate, 0),
+                GrammarElement::new(Kind::Character, 'x' as u32),
+                GrammarElement::new(Kind::End, 0),
+            ]
+        );
+
+        // (a | b)? --> root_1 ::= "a" | "b", root_2 ::= root_1 |
+        let grammar = Grammar::parse("root ::= (\"a\" | \"b\")?\n").unwrap();
+        let group = grammar.rule_id("root_1").unwrap();
+        let optional = grammar.rule_id("root_2").unwrap();
+        assert_eq!(
+            grammar.rules()[optional],
+            vec![
+                GrammarElement::new(Kind::RuleReference, group as u32),
+                GrammarElement::new(Kind::Alternate, 0),
+                GrammarElement::new(Kind::End, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_start_rule() {
+        let gbnf = "command ::= \"stop\"\n";
+        assert!(matches!(
+            Grammar::parse(gbnf),
+            Err(RibbleWhisperError::GrammarParseError { .. })
+        ));
+        let grammar = Grammar::parse_with_start_rule(gbnf, "command").unwrap();
+        assert_eq!(grammar.start_rule(), grammar.rule_id("command").unwrap());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let invalid = [
+            "",
+            "root \"yes\"\n",
+            "root ::= \"yes\n",
+            "root ::= [a-z\n",
+            "root ::= (\"yes\"\n",
+            "root ::= *\n",
+            "root ::= \"\\q\"\n",
+            "root ::= command\n",
+        ];
+        for gbnf in invalid {
+            assert!(
+                matches!(
+                    Grammar::parse(gbnf),
+                    Err(RibbleWhisperError::GrammarParseError { .. })
+                ),
+                "Expected a parse error for: {gbnf:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_phrases() {
+        let grammar = Grammar::from_phrases(&["open settings", "say \"hi\""]).unwrap();
+        let expected =
+            Grammar::parse("root ::= [ ]? (\"open settings\" | \"say \\\"hi\\\"\") [.!?]?\n")
+                .unwrap();
+        assert_eq!(grammar, expected);
+
+        let empty: [&str; 0] = [];
+        assert!(Grammar::from_phrases(&empty).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod grpc_tests {
+    use std::time::{Duration, Instant};
+
+    use ribble_whisper::grpc::proto::submit_job_request::Source;
+    use ribble_whisper::grpc::proto::transcription_server::Transcription;
+    use ribble_whisper::grpc::proto::{
+        JobRequest, JobState, ListJobsRequest, StreamConfig, SubmitJobRequest, TranscriptionConfig,
+    };
+    use ribble_whisper::grpc::service::TranscriptionService;
+    use ribble_whisper::utils::errors::RibbleWhisperError;
+    use ribble_whisper::whisper::configs::{Language, WhisperConfigs, WhisperRealtimeConfigs};
+    use ribble_whisper::whisper::model::DefaultModelBank;
+    use tonic::{Code, Request, Status};
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to build the tokio runtime.")
+    }
+
+    #[test]
+    fn test_configs() {
+        let defaults = WhisperConfigs::default();
+        let configs = WhisperConfigs::try_from(None).unwrap();
+        assert_eq!(configs.n_threads(), defaults.n_threads());
+
+        let mut transcription_config = TranscriptionConfig {
+            model_id: Some(3),
+            language: Some("FR".to_string()),
+            translate: true,
+            ..Default::default()
+        };
+        let configs = WhisperConfigs::try_from(Some(&transcription_config)).unwrap();
+        assert_eq!(*configs.model_id(), Some(3));
+        assert_eq!(*configs.language(), Some(Language::Fr));
+        assert!(configs.translate());
+        assert_eq!(
+            configs.using_gpu(),
+            defaults.using_gpu(),
+            "Unset fields should keep the defaults."
+        );
+
+        transcription_config.language = Some("xx".to_string());
+        let stream_config = StreamConfig {
+            whisper: Some(transcription_config),
+            timeout_ms: Some(0),
+            ..Default::default()
+        };
+        assert!(matches!(
+            WhisperRealtimeConfigs::try_from(&stream_config),
+            Err(RibbleWhisperError::ParameterError(_))
+        ));
+    }
+
+    #[test]
+    fn test_status_codes() {
+        let status = Status::from(RibbleWhisperError::ModelNotFound { id: 1 });
+        assert_eq!(status.code(), Code::NotFound);
+        let status = Status::from(RibbleWhisperError::ParameterError("bad".to_string()));
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_jobs() {
+        let dir = std::env::temp_dir().join("ribble_whisper_grpc_job_test");
+        let bank = DefaultModelBank::new().with_model_directory(dir);
+        let service = TranscriptionService::new(bank);
+        let rt = runtime();
+
+        rt.block_on(async {
+            let missing_source = service
+                .submit_job(Request::new(SubmitJobRequest::default()))
+                .await;
+            assert_eq!(missing_source.unwrap_err().code(), Code::InvalidArgument);
+
+            let path_job = SubmitJobRequest {
+                source: Some(Source::Path("/tmp/audio.wav".to_string())),
+                ..Default::default()
+            };
+            let denied = service.submit_job(Request::new(path_job)).await;
+            assert_eq!(denied.unwrap_err().code(), Code::PermissionDenied);
+
+            // Undecodable audio fails the job without needing a model.
+            let bad_audio = SubmitJobRequest {
+                source: Some(Source::AudioFile(b"not audio".to_vec())),
+                ..Default::default()
+            };
+            let job = service
+                .submit_job(Request::new(bad_audio))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(job.state(), JobState::Queued);
+
+            let request = || Request::new(JobRequest { id: job.id.clone() });
+            let start = Instant::now();
+            let failed = loop {
+                let current = service.get_job(request()).await.unwrap().into_inner();
+                if current.state() == JobState::Failed {
+                    break current;
+                }
+                assert!(
+                    start.elapsed() < Duration::from_secs(10),
+                    "Job did not fail in time: {:?}",
+                    current.state()
+                );
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            };
+            assert!(!failed.error.is_empty());
+
+            let jobs = service
+                .list_jobs(Request::new(ListJobsRequest {}))
+                .await
+                .unwrap()
+                .into_inner()
+                .jobs;
+            assert_eq!(jobs.len(), 1);
+
+            service.delete_job(request()).await.unwrap();
+            let deleted = service.get_job(request()).await;
+            assert_eq!(deleted.unwrap_err().code(), Code::NotFound);
+        });
+    }
+}
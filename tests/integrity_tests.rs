@@ -13,9 +13,13 @@ mod model_integrity_tests {
     use std::collections::HashMap;
 
     use crate::common::prep_model_bank;
-    use ribble_whisper::whisper::integrity_utils::CHECKSUM_RE;
+    use ribble_whisper::downloader::SyncDownload;
+    use ribble_whisper::downloader::downloaders::SyncDownloader;
+    use ribble_whisper::utils::errors::RibbleWhisperError;
+    use ribble_whisper::whisper::integrity_utils::{CHECKSUM_RE, verify_file_checksum};
     use ribble_whisper::whisper::model::{
-        Checksum, DefaultModelType, ModelBank, ModelLocation, ModelRetriever,
+        Checksum, DefaultModelBank, DefaultModelType, ModelBank, ModelChecksum, ModelId,
+        ModelLocation, ModelRetriever,
     };
 
     fn delete_model(file_path: &std::path::Path) -> std::io::Result<()> {
@@ -179,4 +183,101 @@ mod model_integrity_tests {
             checksum
         )
     }
+
+    #[test]
+    fn test_verify_file_checksum() {
+        let dir = std::env::temp_dir().join("ribble_whisper_checksum_test");
+        std::fs::create_dir_all(&dir).expect("Temp directory expected to be writable.");
+        let file_path = dir.join("abc.bin");
+        std::fs::write(&file_path, b"abc").expect("Temp file expected to be writable.");
+
+        let sha1 = Checksum::Sha1("A9993E364706816ABA3E25717850C26C9CD0D89D");
+        let sha256 =
+            Checksum::Sha256("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        let wrong = Checksum::Sha1("bd577a113a864445d4c299885e0cb97d4ba92b5f");
+
+        let sha1_verified = verify_file_checksum(&file_path, &sha1);
+        assert!(sha1_verified.is_ok(), "{}", sha1_verified.unwrap_err());
+        assert!(sha1_verified.unwrap(), "Sha1 failed to verify.");
+
+        let sha256_verified = verify_file_checksum(&file_path, &sha256);
+        assert!(sha256_verified.is_ok(), "{}", sha256_verified.unwrap_err());
+        assert!(sha256_verified.unwrap(), "Sha256 failed to verify.");
+
+        let wrong_verified = verify_file_checksum(&file_path, &wrong);
+        assert!(wrong_verified.is_ok(), "{}", wrong_verified.unwrap_err());
+        assert!(!wrong_verified.unwrap(), "Mismatched checksum verified.");
+
+        let _ = std::fs::remove_file(&file_path);
+    }
+
+    #[test]
+    fn test_default_model_expected_checksum() {
+        let model_type = DefaultModelType::TinyEn;
+        let model = model_type.to_model();
+        let expected = ModelChecksum::Sha256(
+            "921e4cf8686fdd993dcd081a5da5b6c365bfde1162e72b08d75ac75289920b1f".to_string(),
+        );
+        assert_eq!(
+            model.expected_checksum(),
+            Some(&expected),
+            "Default model missing expected checksum."
+        );
+    }
+
+    #[test]
+    fn test_verify_model_offline() {
+        let dir = std::env::temp_dir().join("ribble_whisper_verify_model_test");
+        std::fs::create_dir_all(&dir).expect("Temp directory expected to be writable.");
+        let mut bank = DefaultModelBank::new().with_model_directory(dir.clone());
+        let model_id = bank.get_model_id(DefaultModelType::TinyEn);
+        let file_path = dir.join(DefaultModelType::TinyEn.to_file_name());
+        let _ = std::fs::remove_file(&file_path);
+
+        // A model in the bank without a file in storage doesn't verify.
+        let missing = bank.verify_model(model_id);
+        assert!(matches!(missing, Ok(false)), "Missing model file verified.");
+
+        // A corrupted file is checked against the stored sha256, without a network request.
+        std::fs::write(&file_path, b"abc").expect("Temp file expected to be writable.");
+        let corrupted = bank.verify_model(model_id);
+        assert!(matches!(corrupted, Ok(false)), "Corrupted model verified.");
+        let _ = std::fs::remove_file(&file_path);
+
+        // A model that isn't in the bank is an error.
+        assert!(matches!(
+            bank.verify_model(ModelId::MAX),
+            Err(RibbleWhisperError::ModelNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verified_download_to_memory() {
+        // sha256("abc")
+        let digest = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        let mut downloader = SyncDownloader::new_with_parameters(
+            std::io::Cursor::new(b"abc".to_vec()),
+            "abc.bin".to_string(),
+            3,
+        )
+        .with_expected_checksum(Some(ModelChecksum::Sha256(digest.to_uppercase())));
+        let buffer = downloader
+            .download_to_memory()
+            .expect("Verified in-memory download failed.");
+        assert_eq!(buffer, b"abc", "Incorrect downloaded bytes.");
+
+        let mut corrupted = SyncDownloader::new_with_parameters(
+            std::io::Cursor::new(b"abd".to_vec()),
+            "abc.bin".to_string(),
+            3,
+        )
+        .with_expected_checksum(Some(ModelChecksum::Sha256(digest.to_string())));
+        assert!(
+            matches!(
+                corrupted.download_to_memory(),
+                Err(RibbleWhisperError::ChecksumMismatch { .. })
+            ),
+            "Corrupted in-memory download was not rejected."
+        );
+    }
 }
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod itn_tests {
+    use ribble_whisper::postprocess::itn::InverseTextNormalizer;
+    use ribble_whisper::postprocess::TextProcessor;
+
+    fn normalize(text: &str) -> String {
+        InverseTextNormalizer::new().process(text.to_string())
+    }
+
+    #[test]
+    fn test_cardinals() {
+        assert_eq!(normalize("two hundred and fifty people"), "250 people");
+        assert_eq!(normalize("one hundred thousand"), "100,000");
+        assert_eq!(normalize("Forty-two."), "42.");
+        assert_eq!(normalize("about three point five"), "about 3.5");
+        assert_eq!(
+            normalize("I have one idea"),
+            "I have one idea",
+            "Small standalone cardinals should be left as words."
+        );
+        assert_eq!(
+            InverseTextNormalizer::new()
+                .with_min_cardinal(0)
+                .process("I have one idea".to_string()),
+            "I have 1 idea"
+        );
+    }
+
+    #[test]
+    fn test_currencies_and_percentages() {
+        assert_eq!(normalize("twenty five dollars"), "$25");
+        assert_eq!(
+            normalize("It costs five dollars and fifty cents."),
+            "It costs $5.50.",
+            "Cents should be folded into the currency amount."
+        );
+        assert_eq!(normalize("ten percent"), "10%");
+        assert_eq!(normalize("two point five percent"), "2.5%");
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(
+            normalize("january twenty first twenty twenty four"),
+            "January 21, 2024"
+        );
+        assert_eq!(normalize("On may fifth, we met."), "On May 5, we met.");
+        assert_eq!(
+            normalize("I may go"),
+            "I may go",
+            "Months should only be converted with an ordinal day."
+        );
+    }
+
+    #[test]
+    fn test_unconverted_words_are_unchanged() {
+        assert_eq!(
+            normalize("the twenty-first century"),
+            "the twenty-first century",
+            "Partially matched hyphenated words should be left alone."
+        );
+        assert_eq!(normalize("He came in second."), "He came in second.");
+    }
+}
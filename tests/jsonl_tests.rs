@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod jsonl_tests {
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use ribble_whisper::export::jsonl::{read_records, JsonlWriter, SegmentRecord};
+    use ribble_whisper::transcriber::RibbleWhisperSegment;
+
+    #[test]
+    fn test_write_and_read_records() {
+        let mut writer = JsonlWriter::new(Vec::new()).with_session_id("lecture-1");
+        let segment = RibbleWhisperSegment {
+            text: Arc::from(" Hello world."),
+            start_time: 100,
+            end_time: 250,
+            confidence: None,
+            word_confidences: None,
+            speaker: None,
+        };
+        writer.write_segment(&segment).unwrap();
+        writer
+            .write_record(
+                SegmentRecord::new("Second.", 2500, 3000)
+                    .with_confidence(0.9)
+                    .with_speaker("A"),
+            )
+            .unwrap();
+
+        let bytes = writer.into_inner();
+        assert_eq!(
+            bytes.iter().filter(|b| **b == b'\n').count(),
+            2,
+            "Each record should be written on its own line."
+        );
+
+        let records = read_records(bytes.as_slice()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].text, "Hello world.");
+        assert_eq!(
+            (records[0].start_ms, records[0].end_ms),
+            (1000, 2500),
+            "Segment timestamps should be converted to milliseconds."
+        );
+        assert_eq!(records[0].session_id.as_deref(), Some("lecture-1"));
+        assert_eq!(records[1].sequence, 1);
+        assert_eq!(records[1].speaker.as_deref(), Some("A"));
+    }
+
+    #[test]
+    fn test_truncated_line_is_skipped() {
+        let log =
+            "{\"sequence\":0,\"text\":\"ok\",\"start_ms\":0,\"end_ms\":10}\n{\"sequence\":1,\"te";
+        let records = read_records(log.as_bytes()).unwrap();
+        assert_eq!(
+            records.len(),
+            1,
+            "A truncated final line should be skipped."
+        );
+
+        let corrupt = "not json\n{\"sequence\":0,\"text\":\"ok\",\"start_ms\":0,\"end_ms\":10}\n";
+        assert!(
+            read_records(corrupt.as_bytes()).is_err(),
+            "Invalid complete lines should be reported."
+        );
+    }
+
+    #[test]
+    fn test_append_recovers_from_crash() {
+        let path = std::env::temp_dir().join("ribble_whisper_jsonl_append_test.jsonl");
+        let _ = std::fs::remove_file(&path);
+
+        let mut writer = JsonlWriter::append(&path).unwrap();
+        writer
+            .write_record(SegmentRecord::new("first", 0, 10))
+            .unwrap();
+        let mut file = writer.into_inner();
+        // Simulate a crash partway through a write.
+        file.write_all(b"{\"sequence\":1,\"text\":\"lo").unwrap();
+        drop(file);
+
+        let mut writer = JsonlWriter::append(&path).unwrap();
+        assert_eq!(
+            writer.next_sequence(),
+            1,
+            "Sequence numbers should continue from the last complete record."
+        );
+        writer
+            .write_record(SegmentRecord::new("second", 10, 20))
+            .unwrap();
+        drop(writer);
+
+        let file = std::fs::File::open(&path).unwrap();
+        let records = read_records(std::io::BufReader::new(file)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let texts: Vec<&str> = records.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, vec!["first", "second"]);
+    }
+}
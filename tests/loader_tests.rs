@@ -1,9 +1,16 @@
 mod common;
 #[cfg(test)]
 mod loader_tests {
+    use std::sync::atomic::AtomicBool;
+
     use ribble_whisper::audio::loading::{
-        audio_file_num_frames, load_audio_file, load_normalized_audio_file,
+        audio_file_num_frames, load_audio_bytes, load_audio_bytes_with_abort, load_audio_file,
+        load_audio_file_with_abort, load_normalized_audio_bytes,
+        load_normalized_audio_bytes_with_abort, load_normalized_audio_bytes_with_mime_type,
+        load_normalized_audio_bytes_with_mime_type_with_abort, load_normalized_audio_file,
+        load_normalized_audio_file_with_abort,
     };
+    use ribble_whisper::utils::errors::RibbleWhisperError;
 
     #[test]
     fn test_num_frames() {
@@ -56,4 +63,73 @@ mod loader_tests {
             expected_n_frames, n_frames_normalized
         );
     }
+
+    #[test]
+    fn test_load_bytes() {
+        let path = "tests/audio_files/128896__joshenanigans__sentence-recitation.wav";
+        let bytes = std::fs::read(path).expect("Failed to read test audio.");
+
+        let from_file = load_audio_file(path, None::<fn(usize)>).unwrap();
+        let from_bytes = load_audio_bytes(bytes.clone(), None::<fn(usize)>).unwrap();
+        assert_eq!(from_file.len(), from_bytes.len());
+
+        let normalized_from_file = load_normalized_audio_file(path, None::<fn(usize)>).unwrap();
+        let normalized_from_bytes = load_normalized_audio_bytes(bytes, None::<fn(usize)>).unwrap();
+        assert_eq!(normalized_from_file.len(), normalized_from_bytes.len());
+
+        assert!(
+            load_audio_bytes(vec![0u8; 64], None::<fn(usize)>).is_err(),
+            "Garbage bytes should fail to probe."
+        );
+    }
+
+    #[test]
+    fn test_load_abort() {
+        let path = "tests/audio_files/128896__joshenanigans__sentence-recitation.wav";
+        let bytes = std::fs::read(path).expect("Failed to read test audio.");
+
+        let running = AtomicBool::new(true);
+        let loaded = load_normalized_audio_file_with_abort(path, None::<fn(usize)>, &running)
+            .expect("Loading should finish while running.");
+        let expected = load_normalized_audio_file(path, None::<fn(usize)>).unwrap();
+        assert_eq!(loaded.len(), expected.len());
+
+        let cancelled = AtomicBool::new(false);
+        assert!(matches!(
+            load_normalized_audio_file_with_abort(path, None::<fn(usize)>, &cancelled),
+            Err(RibbleWhisperError::Cancelled)
+        ));
+        assert!(matches!(
+            load_normalized_audio_bytes_with_abort(bytes.clone(), None::<fn(usize)>, &cancelled),
+            Err(RibbleWhisperError::Cancelled)
+        ));
+        assert!(matches!(
+            load_normalized_audio_bytes_with_mime_type_with_abort(
+                bytes.clone(),
+                "audio/wav",
+                None::<fn(usize)>,
+                &cancelled
+            ),
+            Err(RibbleWhisperError::Cancelled)
+        ));
+        assert!(matches!(
+            load_audio_file_with_abort(path, None::<fn(usize)>, &cancelled),
+            Err(RibbleWhisperError::Cancelled)
+        ));
+        assert!(matches!(
+            load_audio_bytes_with_abort(bytes.clone(), None::<fn(usize)>, &cancelled),
+            Err(RibbleWhisperError::Cancelled)
+        ));
+
+        let loaded = load_audio_file_with_abort(path, None::<fn(usize)>, &running)
+            .expect("Loading should finish while running.");
+        assert_eq!(
+            loaded.len(),
+            load_audio_file(path, None::<fn(usize)>).unwrap().len()
+        );
+        let loaded =
+            load_normalized_audio_bytes_with_mime_type(bytes, "audio/wav", None::<fn(usize)>)
+                .expect("Loading with a MIME type should succeed.");
+        assert_eq!(loaded.len(), expected.len());
+    }
 }
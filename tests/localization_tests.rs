@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod localization_tests {
+    use std::sync::Arc;
+
+    use ribble_whisper::transcriber::audio_events::AudioEventKind;
+    use ribble_whisper::transcriber::localization::{EnglishLocalizer, MessageLocalizer};
+    use ribble_whisper::transcriber::{ControlCode, WhisperControlPhrase};
+
+    struct SpanishLocalizer;
+
+    impl MessageLocalizer for SpanishLocalizer {
+        fn control_phrase(&self, phrase: &WhisperControlPhrase) -> Option<String> {
+            match phrase.code() {
+                ControlCode::StartSpeaking => Some("[EMPIECE A HABLAR]".to_string()),
+                _ => None,
+            }
+        }
+
+        fn audio_event(&self, kind: &AudioEventKind) -> Option<String> {
+            match kind {
+                AudioEventKind::Applause => Some("[aplausos]".to_string()),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_english_localizer() {
+        let phrase = WhisperControlPhrase::StartSpeaking;
+        assert_eq!(phrase.localized(&EnglishLocalizer), phrase.to_string());
+        assert_eq!(
+            AudioEventKind::Laughter.localized(&EnglishLocalizer),
+            "[laughter]"
+        );
+    }
+
+    #[test]
+    fn test_localizer_fallback() {
+        assert_eq!(
+            WhisperControlPhrase::StartSpeaking.localized(&SpanishLocalizer),
+            "[EMPIECE A HABLAR]"
+        );
+        // Untranslated messages fall back to English.
+        assert_eq!(
+            WhisperControlPhrase::EndTranscription.localized(&SpanishLocalizer),
+            WhisperControlPhrase::EndTranscription.to_string()
+        );
+        assert_eq!(
+            AudioEventKind::Applause.localized(&SpanishLocalizer),
+            "[aplausos]"
+        );
+        assert_eq!(
+            AudioEventKind::Music.localized(&SpanishLocalizer),
+            "[music]"
+        );
+
+        // Shared localizers can be passed in wherever one is expected.
+        let shared = Arc::new(SpanishLocalizer);
+        assert_eq!(
+            WhisperControlPhrase::StartSpeaking.localized(&shared),
+            "[EMPIECE A HABLAR]"
+        );
+    }
+}
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod log_router_tests {
+    use std::str::FromStr;
+
+    use ribble_whisper::transcriber::log_router::{
+        WhisperLogLevel, WhisperLogRouter, WhisperLogSource,
+    };
+
+    #[test]
+    fn test_route_level_filters_and_remaps() {
+        let router = WhisperLogRouter::new();
+        assert_eq!(router.route_level(WhisperLogLevel::Debug), None);
+        assert_eq!(
+            router.route_level(WhisperLogLevel::Info),
+            Some(WhisperLogLevel::Info)
+        );
+
+        let router = WhisperLogRouter::new()
+            .with_min_level(Some(WhisperLogLevel::Debug))
+            .with_remap(WhisperLogLevel::Info, Some(WhisperLogLevel::Debug))
+            .with_remap(WhisperLogLevel::Warn, None);
+        assert_eq!(
+            router.route_level(WhisperLogLevel::Info),
+            Some(WhisperLogLevel::Debug)
+        );
+        assert_eq!(router.route_level(WhisperLogLevel::Warn), None);
+        assert_eq!(
+            router.route_level(WhisperLogLevel::Error),
+            Some(WhisperLogLevel::Error)
+        );
+
+        // Remapping happens before filtering, and later remaps replace earlier ones.
+        let router = WhisperLogRouter::new()
+            .with_min_level(Some(WhisperLogLevel::Warn))
+            .with_remap(WhisperLogLevel::Info, Some(WhisperLogLevel::Debug))
+            .with_remap(WhisperLogLevel::Info, Some(WhisperLogLevel::Error));
+        assert_eq!(
+            router.route_level(WhisperLogLevel::Info),
+            Some(WhisperLogLevel::Error)
+        );
+
+        let silenced = WhisperLogRouter::new().with_min_level(None);
+        assert_eq!(silenced.route_level(WhisperLogLevel::Error), None);
+    }
+
+    #[test]
+    fn test_levels_and_sources() {
+        assert!(WhisperLogLevel::Debug < WhisperLogLevel::Error);
+        assert_eq!(
+            WhisperLogLevel::from_str("Warn").unwrap(),
+            WhisperLogLevel::Warn
+        );
+        assert_eq!(WhisperLogSource::Whisper.target(), "whisper_cpp");
+        assert_eq!(WhisperLogSource::Ggml.target(), "ggml");
+    }
+}
@@ -1,7 +1,16 @@
 // Model unit tests: Checking model path coherence, DefaultModelType url canonicalization
 #[cfg(test)]
 mod model_tests {
-    use ribble_whisper::whisper::model::DefaultModelType;
+    use ribble_whisper::whisper::configs::{WhisperConfigs, WhisperSamplingStrategy};
+    use ribble_whisper::whisper::model::ModelId;
+    use ribble_whisper::whisper::model::{
+        CustomModelSource, DefaultModelBank, DefaultModelType, EncoderCompanion, Model, ModelBank,
+        ModelLocation, ModelQuantization, ModelRetriever,
+    };
+    use ribble_whisper::whisper::model_cache::SharedModelCache;
+    use ribble_whisper::whisper::model_info::{
+        estimate_memory, probe_model, ModelHyperparameters, ModelInfo, ModelSize,
+    };
 
     // TODO: implement ModelBank tests.
     #[test]
@@ -43,4 +52,674 @@ mod model_tests {
             actual, expected
         )
     }
+
+    #[test]
+    fn test_model_quantization() {
+        let quantized = Model::new_with_parameters(
+            "Base.en Q5_1".to_string(),
+            "ggml-base.en-q5_1.bin".to_string(),
+        );
+        assert_eq!(
+            quantized.quantization(),
+            Some(ModelQuantization::Q5_1),
+            "Failed to parse quantization from: {}",
+            quantized.file_name()
+        );
+        assert!(!quantized.is_multilingual(), "Base.en is English-only.");
+
+        let full = DefaultModelType::LargeV3Turbo.to_model();
+        assert!(
+            full.quantization().is_none(),
+            "Full precision model reported quantization: {:?}",
+            full.quantization()
+        );
+        assert!(full.is_multilingual(), "LargeV3Turbo is multilingual.");
+    }
+
+    #[test]
+    fn test_model_bank_management() {
+        let dir = std::env::temp_dir().join("ribble_whisper_model_bank_test");
+        std::fs::create_dir_all(&dir).expect("Failed to create test directory.");
+        let mut bank = DefaultModelBank::new().with_model_directory(dir.clone());
+
+        let num_models = bank.list_models().len();
+        assert_eq!(
+            num_models, 6,
+            "Unexpected number of default models: {}",
+            num_models
+        );
+
+        let model_id = bank.get_model_id(DefaultModelType::TinyEn);
+        let file_path = dir.join(DefaultModelType::TinyEn.to_file_name());
+        std::fs::write(&file_path, [0u8; 64]).expect("Failed to write test model.");
+
+        let metadata = bank
+            .model_metadata(model_id)
+            .expect("Failed to inspect model.")
+            .expect("Model missing from bank.");
+        assert_eq!(
+            metadata.size_on_disk,
+            Some(64),
+            "Incorrect size on disk: {:?}",
+            metadata.size_on_disk
+        );
+        assert!(!metadata.multilingual, "TinyEn is English-only.");
+
+        let usage = bank.disk_usage().expect("Failed to compute disk usage.");
+        assert_eq!(usage, 64, "Incorrect disk usage: {}", usage);
+
+        let deleted = bank
+            .delete_model(model_id)
+            .expect("Failed to delete model.");
+        assert_eq!(deleted, Some(model_id), "Model file was not deleted.");
+        assert!(!file_path.exists(), "Model file still exists.");
+        assert!(
+            bank.get_model(model_id).is_some(),
+            "Deleting a model should keep its entry in the bank."
+        );
+
+        let metadata = bank
+            .model_metadata(model_id)
+            .expect("Failed to inspect model.")
+            .expect("Model missing from bank.");
+        assert!(!metadata.in_storage(), "Deleted model reported in storage.");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_register_custom_model() {
+        let dir = std::env::temp_dir().join("ribble_whisper_custom_model_test");
+        std::fs::create_dir_all(&dir).expect("Failed to create test directory.");
+        let model_path = dir.join("ggml-finetuned.bin");
+        std::fs::write(&model_path, [0u8; 16]).expect("Failed to write test model.");
+
+        let mut bank = DefaultModelBank::new().with_model_directory(dir.clone());
+        let path_id = bank
+            .register_custom_model(
+                "Fine-tuned".to_string(),
+                CustomModelSource::Path(model_path.clone()),
+            )
+            .expect("Failed to register path model.");
+        let url_id = bank
+            .register_custom_model(
+                "Distil".to_string(),
+                CustomModelSource::Url(
+                    "https://example.com/models/ggml-distil-small.en.bin".to_string(),
+                ),
+            )
+            .expect("Failed to register url model.");
+
+        assert!(bank.is_custom_model(path_id), "Path model not registered.");
+        assert!(
+            bank.model_exists_in_storage(path_id).unwrap(),
+            "Path model should resolve outside the model directory."
+        );
+        match bank.retrieve_model(path_id) {
+            Some(ModelLocation::DynamicFilePath(path)) => assert_eq!(
+                path, model_path,
+                "Path error. Retrieved: {:?}, Expected: {:?}",
+                path, model_path
+            ),
+            _ => panic!("Failed to retrieve path model."),
+        }
+
+        let url_model = bank.get_model(url_id).expect("Url model not registered.");
+        assert_eq!(
+            url_model.file_name(),
+            "ggml-distil-small.en.bin",
+            "Incorrect file name for url model: {}",
+            url_model.file_name()
+        );
+
+        let bad_path = bank.register_custom_model(
+            "Missing".to_string(),
+            CustomModelSource::Path(dir.join("does-not-exist.bin")),
+        );
+        assert!(bad_path.is_err(), "Registered a model that does not exist.");
+
+        // Path models aren't the bank's to delete, or to count towards its disk usage.
+        assert_eq!(
+            bank.delete_model(path_id)
+                .expect("Failed to delete path model."),
+            None,
+            "Deleted a path model."
+        );
+        assert!(model_path.exists(), "Path model file was deleted.");
+        assert_eq!(
+            bank.disk_usage().expect("Failed to compute disk usage."),
+            0,
+            "Path model counted towards disk usage."
+        );
+
+        // Removing a path model must never delete the user's file.
+        bank.remove_model(path_id)
+            .expect("Failed to remove path model.");
+        assert!(model_path.exists(), "Path model file was deleted.");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_register_relative_custom_model() {
+        let dir = std::env::temp_dir().join("ribble_whisper_relative_model_test");
+        std::fs::create_dir_all(&dir).expect("Failed to create test directory.");
+        let file_name = "ribble_whisper_relative_model_test.bin";
+        let cwd = std::env::current_dir().expect("Failed to get current directory.");
+        std::fs::write(cwd.join(file_name), [0u8; 16]).expect("Failed to write test model.");
+
+        // The relative path is resolved against the current directory, not the model directory.
+        let mut bank = DefaultModelBank::new().with_model_directory(dir.clone());
+        let model_id = bank
+            .register_custom_model(
+                "Relative".to_string(),
+                CustomModelSource::Path(file_name.into()),
+            )
+            .expect("Failed to register relative path model.");
+        assert_eq!(
+            bank.custom_model_source(model_id),
+            Some(&CustomModelSource::Path(cwd.join(file_name)))
+        );
+        assert!(
+            bank.model_exists_in_storage(model_id).unwrap(),
+            "Relative path model should resolve to its file."
+        );
+
+        let _ = std::fs::remove_file(cwd.join(file_name));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_custom_model_manifest() {
+        let dir = std::env::temp_dir().join("ribble_whisper_manifest_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("Failed to create test directory.");
+        let manifest = dir.join("manifest.json");
+
+        let mut bank = DefaultModelBank::new()
+            .with_model_directory(dir.clone())
+            .with_manifest(manifest.clone())
+            .expect("Failed to load empty manifest.");
+        let model_id = bank
+            .register_custom_model(
+                "Distil".to_string(),
+                CustomModelSource::Url(
+                    "https://example.com/models/ggml-distil-small.en.bin".to_string(),
+                ),
+            )
+            .expect("Failed to register url model.");
+        assert!(manifest.is_file(), "Manifest was not written.");
+
+        let reloaded = DefaultModelBank::new()
+            .with_model_directory(dir.clone())
+            .with_manifest(manifest)
+            .expect("Failed to load manifest.");
+        let model = reloaded.get_model(model_id);
+        assert!(model.is_some(), "Custom model missing after reload.");
+        assert_eq!(
+            model.unwrap().name(),
+            "Distil",
+            "Incorrect name after reload."
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_rename_custom_model_manifest() {
+        let dir = std::env::temp_dir().join("ribble_whisper_rename_manifest_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("Failed to create test directory.");
+        let manifest = dir.join("manifest.json");
+        let model_file = dir.join("ggml-finetuned.bin");
+        std::fs::write(&model_file, b"ggml").expect("Failed to write test model.");
+        let moved_file = dir.join("ggml-finetuned-v2.bin");
+        std::fs::write(&moved_file, b"ggml").expect("Failed to write test model.");
+
+        let mut bank = DefaultModelBank::new()
+            .with_model_directory(dir.clone())
+            .with_manifest(manifest.clone())
+            .expect("Failed to load empty manifest.");
+        let url_id = bank
+            .register_custom_model(
+                "Distil".to_string(),
+                CustomModelSource::Url(
+                    "https://example.com/models/ggml-distil-small.en.bin".to_string(),
+                ),
+            )
+            .expect("Failed to register url model.");
+        let path_id = bank
+            .register_custom_model(
+                "Fine-tuned".to_string(),
+                CustomModelSource::Path(model_file),
+            )
+            .expect("Failed to register path model.");
+
+        assert_eq!(
+            bank.rename_model(url_id, "Distil English".to_string())
+                .expect("Failed to rename model."),
+            Some(url_id)
+        );
+        let url_id = bank
+            .change_model_file_name(url_id, "ggml-distil-en.bin".to_string())
+            .expect("Failed to change file name.")
+            .expect("Url model missing.");
+        let path_id = bank
+            .change_model_file_name(path_id, moved_file.to_string_lossy().into_owned())
+            .expect("Failed to change file name.")
+            .expect("Path model missing.");
+        assert_eq!(
+            bank.custom_model_source(path_id),
+            Some(&CustomModelSource::Path(moved_file.clone()))
+        );
+        // Path models must still point at a file.
+        assert!(
+            bank.change_model_file_name(path_id, dir.join("missing.bin").to_string_lossy().into())
+                .is_err()
+        );
+
+        let reloaded = DefaultModelBank::new()
+            .with_model_directory(dir.clone())
+            .with_manifest(manifest)
+            .expect("Failed to load manifest.");
+        let model = reloaded
+            .get_model(url_id)
+            .expect("Url model missing after reload.");
+        assert_eq!(model.name(), "Distil English", "Rename was not saved.");
+        assert_eq!(
+            model.file_name(),
+            "ggml-distil-en.bin",
+            "File name change was not saved."
+        );
+        assert_eq!(
+            reloaded.download_url(url_id).as_deref(),
+            Some("https://example.com/models/ggml-distil-small.en.bin")
+        );
+        assert_eq!(
+            reloaded.custom_model_source(path_id),
+            Some(&CustomModelSource::Path(moved_file)),
+            "Path change was not saved."
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_model_profiles() {
+        let mut bank = DefaultModelBank::new();
+        let medium = bank.get_model_id(DefaultModelType::Medium);
+        let small_en = bank.get_model_id(DefaultModelType::SmallEn);
+        let meetings = WhisperConfigs::default()
+            .with_model_id(Some(medium))
+            .with_sampling_strategy(WhisperSamplingStrategy::BeamSearch {
+                beam_size: 5,
+                patience: -1.0,
+            });
+        let dictation = WhisperConfigs::default().with_model_id(Some(small_en));
+
+        bank.set_profile("meetings".to_string(), meetings)
+            .expect("Failed to set meetings profile.");
+        bank.set_profile("dictation".to_string(), dictation)
+            .expect("Failed to set dictation profile.");
+        assert_eq!(bank.profile_names(), vec!["dictation", "meetings"]);
+
+        let (model_id, configs) = bank.profile("meetings").expect("Missing meetings profile.");
+        assert_eq!(model_id, medium);
+        assert!(matches!(
+            configs.sampling_strategy(),
+            WhisperSamplingStrategy::BeamSearch { beam_size: 5, .. }
+        ));
+        assert!(bank.profile("missing").is_none());
+
+        // Profiles must name a model in the bank.
+        assert!(
+            bank.set_profile("none".to_string(), WhisperConfigs::default())
+                .is_err()
+        );
+        assert!(
+            bank.set_profile(
+                "unknown".to_string(),
+                WhisperConfigs::default().with_model_id(Some(0))
+            )
+            .is_err()
+        );
+
+        // A profile whose model was removed no longer resolves.
+        bank.remove_model(small_en)
+            .expect("Failed to remove dictation model.");
+        assert!(bank.profile("dictation").is_none());
+
+        assert!(
+            bank.remove_profile("meetings")
+                .expect("Failed to remove profile.")
+                .is_some()
+        );
+        assert_eq!(bank.profile_names(), vec!["dictation"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_profile_manifest() {
+        let dir = std::env::temp_dir().join("ribble_whisper_profile_manifest_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("Failed to create test directory.");
+        let manifest = dir.join("manifest.json");
+
+        let mut bank = DefaultModelBank::new()
+            .with_model_directory(dir.clone())
+            .with_manifest(manifest.clone())
+            .expect("Failed to load empty manifest.");
+        let model_id = bank
+            .register_custom_model(
+                "Medium Q5".to_string(),
+                CustomModelSource::Url(
+                    "https://example.com/models/ggml-medium-q5_0.bin".to_string(),
+                ),
+            )
+            .expect("Failed to register url model.");
+        bank.set_profile(
+            "meetings".to_string(),
+            WhisperConfigs::default().with_model_id(Some(model_id)),
+        )
+        .expect("Failed to set profile.");
+
+        let reloaded = DefaultModelBank::new()
+            .with_model_directory(dir.clone())
+            .with_manifest(manifest.clone())
+            .expect("Failed to load manifest.");
+        let profile = reloaded.profile("meetings");
+        assert!(profile.is_some(), "Profile missing after reload.");
+        let (profile_id, configs) = profile.unwrap();
+        assert_eq!(profile_id, model_id);
+        assert_eq!(*configs.model_id(), Some(model_id));
+
+        // Profiles are saved by their model's file name, since ids aren't stable across builds.
+        let dictation = bank.get_model_id(DefaultModelType::SmallEn);
+        bank.set_profile(
+            "dictation".to_string(),
+            WhisperConfigs::default().with_model_id(Some(dictation)),
+        )
+        .expect("Failed to set profile.");
+        let saved: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(&manifest).expect("Failed to read manifest."),
+        )
+        .expect("Malformed manifest.");
+        let saved_models: Vec<&str> = saved["profiles"]
+            .as_array()
+            .expect("Missing profiles.")
+            .iter()
+            .filter_map(|entry| entry["model"].as_str())
+            .collect();
+        assert_eq!(
+            saved_models,
+            vec!["ggml-small.en.bin", "ggml-medium-q5_0.bin"]
+        );
+
+        // Profiles follow their model when its file name changes.
+        let model_id = bank
+            .change_model_file_name(model_id, "ggml-medium-q5.bin".to_string())
+            .expect("Failed to change file name.")
+            .expect("Model missing.");
+        let reloaded = DefaultModelBank::new()
+            .with_model_directory(dir.clone())
+            .with_manifest(manifest.clone())
+            .expect("Failed to load manifest.");
+        assert_eq!(
+            reloaded.profile("meetings").map(|(id, _)| id),
+            Some(model_id)
+        );
+        assert_eq!(
+            reloaded.profile("dictation").map(|(id, _)| id),
+            Some(reloaded.get_model_id(DefaultModelType::SmallEn))
+        );
+
+        // Manifests from before profiles were added are a bare list of models.
+        std::fs::write(
+            &manifest,
+            r#"[{"name": "Distil", "source": {"Url": "https://example.com/models/ggml-distil-small.en.bin"}}]"#,
+        )
+        .expect("Failed to write legacy manifest.");
+        let legacy = DefaultModelBank::new()
+            .with_model_directory(dir.clone())
+            .with_manifest(manifest)
+            .expect("Failed to load legacy manifest.");
+        assert!(legacy.profile_names().is_empty());
+        assert!(legacy.iter().any(|(_, model)| model.name() == "Distil"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_platform_data_dir() {
+        let bank = DefaultModelBank::new()
+            .with_platform_data_dir("ribble_whisper_test_app")
+            .expect("Failed to determine platform data directory.");
+        let expected_suffix = std::path::Path::new("ribble_whisper_test_app").join("models");
+        assert!(
+            bank.model_directory().ends_with(&expected_suffix),
+            "Model directory not namespaced: {:?}",
+            bank.model_directory()
+        );
+        assert!(
+            bank.model_directory().is_absolute(),
+            "Platform data directory should be absolute: {:?}",
+            bank.model_directory()
+        );
+    }
+
+    #[test]
+    fn test_model_download_url() {
+        let mut bank = DefaultModelBank::new();
+        let model_id = bank.get_model_id(DefaultModelType::TinyEn);
+        let expected = DefaultModelType::TinyEn.url();
+        assert_eq!(
+            bank.download_url(model_id),
+            Some(expected.clone()),
+            "Failed to resolve url for: {}",
+            expected
+        );
+
+        let custom_url = "https://example.com/models/ggml-distil-small.en.bin".to_string();
+        let custom_id = bank
+            .register_custom_model(
+                "Distil".to_string(),
+                CustomModelSource::Url(custom_url.clone()),
+            )
+            .expect("Failed to register url model.");
+        assert_eq!(
+            bank.download_url(custom_id),
+            Some(custom_url),
+            "Custom model url not resolved."
+        );
+    }
+
+    #[test]
+    fn test_probe_model() {
+        let dir = std::env::temp_dir().join("ribble_whisper_probe_test");
+        std::fs::create_dir_all(&dir).expect("Failed to create test directory.");
+        let model_path = dir.join("ggml-base.en-q5_1.bin");
+
+        // Base.en hyperparameters, quantized to q5_1 (quantization version 2).
+        let hparams: [i32; 11] = [51864, 1500, 512, 8, 6, 448, 512, 8, 6, 80, 2009];
+        let mut header = 0x67676d6cu32.to_le_bytes().to_vec();
+        header.extend(hparams.iter().flat_map(|param| param.to_le_bytes()));
+        std::fs::write(&model_path, &header).expect("Failed to write test model.");
+
+        let info = probe_model(&model_path).expect("Failed to probe model header.");
+        assert_eq!(
+            info.model_size,
+            ModelSize::Base,
+            "Incorrect model size: {}",
+            info.model_size
+        );
+        assert_eq!(
+            info.quantization,
+            Some(ModelQuantization::Q5_1),
+            "Incorrect quantization: {:?}",
+            info.quantization
+        );
+        assert!(!info.multilingual, "Base.en is English-only.");
+        assert!(
+            info.estimated_memory > info.file_size,
+            "Memory estimate should include more than the weights."
+        );
+
+        std::fs::write(&model_path, b"GGUF").expect("Failed to write test model.");
+        assert!(
+            probe_model(&model_path).is_err(),
+            "GGUF files should not be probed as whisper models."
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_encoder_companion_names() {
+        let model_type = DefaultModelType::BaseEn;
+        let model = model_type.to_model();
+        let coreml = model.encoder_companion_file_name(EncoderCompanion::CoreMl);
+        assert_eq!(
+            coreml, "ggml-base.en-encoder.mlmodelc",
+            "Incorrect CoreML encoder name: {}",
+            coreml
+        );
+        let openvino = model.encoder_companion_file_name(EncoderCompanion::OpenVino);
+        assert_eq!(
+            openvino, "ggml-base.en-encoder-openvino.xml",
+            "Incorrect OpenVINO encoder name: {}",
+            openvino
+        );
+
+        let expected_url = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en-encoder.mlmodelc.zip";
+        let url = model_type.encoder_companion_url(EncoderCompanion::CoreMl);
+        assert_eq!(
+            url.as_deref(),
+            Some(expected_url),
+            "CoreML url malformed: {:?}",
+            url
+        );
+        assert!(
+            model_type
+                .encoder_companion_url(EncoderCompanion::OpenVino)
+                .is_none(),
+            "OpenVINO encoders are not hosted."
+        );
+
+        let bank = DefaultModelBank::new()
+            .with_model_directory(std::env::temp_dir().join("ribble_whisper_missing_encoders"));
+        let exists = bank
+            .encoder_companion_exists(
+                bank.get_model_id(DefaultModelType::TinyEn),
+                EncoderCompanion::CoreMl,
+            )
+            .expect("Failed to check for encoder.");
+        assert!(!exists, "Encoder should not exist in an empty directory.");
+    }
+
+    // Serves 1 KiB buffers for every id.
+    struct BufferRetriever;
+    impl ModelRetriever for BufferRetriever {
+        fn retrieve_model(&self, model_id: ModelId) -> Option<ModelLocation> {
+            Some(ModelLocation::DynamicBuffer(vec![model_id as u8; 1024]))
+        }
+    }
+
+    #[test]
+    fn test_shared_model_cache_eviction() {
+        let cache = SharedModelCache::new(BufferRetriever).with_memory_budget(2048);
+
+        let first = cache.get_buffer(1).expect("Failed to load model 1.");
+        let again = cache.get_buffer(1).expect("Failed to load model 1.");
+        assert!(
+            std::sync::Arc::ptr_eq(&first, &again),
+            "Cached buffers should be shared."
+        );
+        let usage = cache.buffer_usage(1).expect("Model 1 not cached.");
+        assert_eq!(usage.references, 2, "Incorrect reference count.");
+        assert_eq!(usage.hits, 2, "Incorrect number of hits.");
+
+        let _second = cache.get_buffer(2).expect("Failed to load model 2.");
+        drop(first);
+        drop(again);
+
+        // Model 1 is the least recently used and no longer referenced.
+        let _third = cache.get_buffer(3).expect("Failed to load model 3.");
+        assert_eq!(
+            cache.cached_models(),
+            vec![2, 3],
+            "Least-recently-used model was not evicted."
+        );
+        assert!(
+            cache.memory_usage() <= cache.memory_budget(),
+            "Cache exceeds its budget: {}",
+            cache.memory_usage()
+        );
+
+        // Models in use are never evicted, even over budget.
+        let _fourth = cache.get_buffer(4).expect("Failed to load model 4.");
+        assert_eq!(
+            cache.cached_models(),
+            vec![2, 3, 4],
+            "A referenced model was evicted."
+        );
+    }
+
+    #[test]
+    fn test_estimate_memory() {
+        // Base.en hyperparameters at f16.
+        let info = ModelInfo {
+            model_size: ModelSize::Base,
+            quantization: Some(ModelQuantization::F16),
+            multilingual: false,
+            hyperparameters: ModelHyperparameters {
+                n_vocab: 51864,
+                n_audio_ctx: 1500,
+                n_audio_state: 512,
+                n_audio_head: 8,
+                n_audio_layer: 6,
+                n_text_ctx: 448,
+                n_text_state: 512,
+                n_text_head: 8,
+                n_text_layer: 6,
+                n_mels: 80,
+                ftype: 1,
+            },
+            file_size: 147_964_211,
+            estimated_memory: 0,
+        };
+        let configs = WhisperConfigs::default().with_flash_attention(false);
+
+        let cpu = estimate_memory(&info, &configs.with_use_gpu(false));
+        assert_eq!(cpu.weights, info.file_size);
+        assert_eq!(cpu.gpu, 0, "A CPU-only estimate should not use the GPU.");
+        assert!(cpu.total() > cpu.weights + cpu.kv_cache + cpu.compute);
+
+        let gpu = estimate_memory(&info, &configs.with_use_gpu(true));
+        assert_eq!(gpu.total(), cpu.total());
+        assert!(
+            gpu.gpu > gpu.cpu,
+            "The weights should be allocated on the GPU."
+        );
+        assert!(gpu.fits_within(gpu.cpu, gpu.gpu));
+        assert!(!gpu.fits_within(gpu.total(), gpu.gpu - 1));
+
+        let flash = estimate_memory(&info, &configs.with_flash_attention(true));
+        assert!(flash.flash_attention > 0);
+        assert!(
+            flash.compute < cpu.compute,
+            "Flash attention should not materialize the attention scores."
+        );
+
+        let beam_search = configs.with_sampling_strategy(WhisperSamplingStrategy::BeamSearch {
+            beam_size: 5,
+            patience: -1.0,
+        });
+        let beam_search = estimate_memory(&info, &beam_search);
+        assert!(
+            beam_search.kv_cache > cpu.kv_cache,
+            "Multiple decoders should need a larger kv-cache."
+        );
+    }
 }
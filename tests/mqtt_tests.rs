@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod mqtt_tests {
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::mpsc::{channel, Receiver};
+    use std::sync::Arc;
+    use std::thread::spawn;
+    use std::time::Duration;
+
+    use ribble_whisper::export::mqtt::{MqttEvent, MqttSinkBuilder, QoS, DEFAULT_TOPIC_PREFIX};
+    use ribble_whisper::transcriber::{TranscriptionSnapshot, WhisperControlPhrase, WhisperOutput};
+    use ribble_whisper::utils::errors::RibbleWhisperError;
+    use ribble_whisper::utils::sink::OutputSink;
+
+    const CONNECT: u8 = 1;
+    const PUBLISH: u8 = 3;
+    const DISCONNECT: u8 = 14;
+
+    // Reads an MQTT packet as (type, flags, body).
+    fn read_packet(stream: &mut TcpStream) -> Option<(u8, u8, Vec<u8>)> {
+        let mut header = [0u8; 1];
+        stream.read_exact(&mut header).ok()?;
+        let (mut len, mut shift) = (0usize, 0);
+        loop {
+            let mut byte = [0u8; 1];
+            stream.read_exact(&mut byte).ok()?;
+            len |= ((byte[0] & 0x7f) as usize) << shift;
+            shift += 7;
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+        }
+        let mut body = vec![0u8; len];
+        stream.read_exact(&mut body).ok()?;
+        Some((header[0] >> 4, header[0] & 0x0f, body))
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Received {
+        Connect(Vec<u8>),
+        Publish { topic: String, payload: String },
+    }
+
+    // A minimal broker that accepts one client and records its packets until it disconnects.
+    fn broker() -> (u16, Receiver<Received>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sender, receiver) = channel();
+        spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(10)))
+                .unwrap();
+            while let Some((packet_type, flags, body)) = read_packet(&mut stream) {
+                match packet_type {
+                    CONNECT => {
+                        stream.write_all(&[0x20, 0x02, 0x00, 0x00]).unwrap();
+                        let _ = sender.send(Received::Connect(body));
+                    }
+                    PUBLISH => {
+                        let topic_len = u16::from_be_bytes([body[0], body[1]]) as usize;
+                        let topic = String::from_utf8(body[2..2 + topic_len].to_vec()).unwrap();
+                        let mut payload_start = 2 + topic_len;
+                        // QoS 1 publishes carry a packet id, which is acknowledged.
+                        if (flags >> 1) & 0x03 == 1 {
+                            let id = &body[payload_start..payload_start + 2];
+                            stream.write_all(&[0x40, 0x02, id[0], id[1]]).unwrap();
+                            payload_start += 2;
+                        }
+                        let payload = String::from_utf8(body[payload_start..].to_vec()).unwrap();
+                        let _ = sender.send(Received::Publish { topic, payload });
+                    }
+                    DISCONNECT => return,
+                    _ => {}
+                }
+            }
+        });
+        (port, receiver)
+    }
+
+    fn received(receiver: &Receiver<Received>) -> Received {
+        receiver
+            .recv_timeout(Duration::from_secs(10))
+            .expect("Broker received nothing.")
+    }
+
+    fn snapshot(confirmed: &str, working: &str) -> WhisperOutput {
+        WhisperOutput::TranscriptionSnapshot(Arc::new(TranscriptionSnapshot::new(
+            Arc::from(confirmed),
+            Arc::from([Arc::from(working)]),
+        )))
+    }
+
+    #[test]
+    fn test_invalid_topics() {
+        let res = MqttSinkBuilder::new("localhost", 1883)
+            .with_text_topic("home/+/speech")
+            .build();
+        assert!(matches!(res, Err(RibbleWhisperError::ParameterError(_))));
+        let res = MqttSinkBuilder::new("localhost", 1883)
+            .with_event_topic("")
+            .build();
+        assert!(matches!(res, Err(RibbleWhisperError::ParameterError(_))));
+    }
+
+    #[test]
+    fn test_control_phrase_events() {
+        assert_eq!(
+            MqttEvent::from_control_phrase(&WhisperControlPhrase::StartSpeaking, None),
+            MqttEvent::SessionStarted { session_id: None }
+        );
+        let event = MqttEvent::from_control_phrase(
+            &WhisperControlPhrase::GettingReady,
+            Some("kitchen".to_string()),
+        );
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"control","phrase":"[GETTING_READY]","session_id":"kitchen"}"#
+        );
+    }
+
+    #[test]
+    fn test_publish_outputs() {
+        let (port, receiver) = broker();
+        let sink = MqttSinkBuilder::new("127.0.0.1", port)
+            .with_client_id("ribble-test")
+            .with_session_id("kitchen")
+            .build()
+            .unwrap();
+        assert_eq!(sink.text_topic(), format!("{DEFAULT_TOPIC_PREFIX}/text"));
+
+        let Received::Connect(connect) = received(&receiver) else {
+            panic!("Expected a CONNECT packet first.");
+        };
+        let connect = String::from_utf8_lossy(&connect);
+        assert!(connect.contains("ribble-test"));
+        assert!(
+            connect.contains(r#""event":"disconnected""#),
+            "The last will should be the disconnected event."
+        );
+
+        sink.try_send(WhisperOutput::ControlPhrase(
+            WhisperControlPhrase::StartSpeaking,
+        ))
+        .unwrap();
+        assert_eq!(
+            received(&receiver),
+            Received::Publish {
+                topic: format!("{DEFAULT_TOPIC_PREFIX}/event"),
+                payload: r#"{"event":"session_started","session_id":"kitchen"}"#.to_string(),
+            }
+        );
+
+        sink.try_send(snapshot(" Turn on", "the")).unwrap();
+        // Nothing new was confirmed; nothing should be published for this snapshot.
+        sink.try_send(snapshot(" Turn on", "the lights")).unwrap();
+        sink.try_send(snapshot(" Turn on the lights.", "")).unwrap();
+        for expected in ["Turn on", "the lights."] {
+            assert_eq!(
+                received(&receiver),
+                Received::Publish {
+                    topic: format!("{DEFAULT_TOPIC_PREFIX}/text"),
+                    payload: format!(r#"{{"text":"{expected}","session_id":"kitchen"}}"#),
+                },
+                "Only newly confirmed text should be published."
+            );
+        }
+        sink.shutdown();
+    }
+
+    #[test]
+    fn test_plain_text_payloads() {
+        let (port, receiver) = broker();
+        let sink = MqttSinkBuilder::new("127.0.0.1", port)
+            .with_topic_prefix("home/office/")
+            .with_qos(QoS::AtMostOnce)
+            .with_json_payloads(false)
+            .build()
+            .unwrap();
+        assert!(matches!(received(&receiver), Received::Connect(_)));
+
+        sink.publish_text("lights off").unwrap();
+        assert_eq!(
+            received(&receiver),
+            Received::Publish {
+                topic: "home/office/text".to_string(),
+                payload: "lights off".to_string(),
+            }
+        );
+        sink.shutdown();
+    }
+}
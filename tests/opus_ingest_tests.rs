@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod opus_ingest_tests {
+    use opus::{Application, Channels, Encoder};
+    use ribble_whisper::audio::audio_ring_buffer::AudioRingBuffer;
+    use ribble_whisper::audio::opus_ingest::{OggDemuxer, OggOpusIngest, OggPacket, OpusIngest};
+
+    // 20 ms at 16 kHz.
+    const FRAME_LEN: usize = 320;
+
+    fn page(serial: u32, continued: bool, lacing: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut page = b"OggS".to_vec();
+        page.push(0);
+        page.push(continued as u8);
+        page.extend_from_slice(&[0; 8]);
+        page.extend_from_slice(&serial.to_le_bytes());
+        page.extend_from_slice(&[0; 8]);
+        page.push(lacing.len() as u8);
+        page.extend_from_slice(lacing);
+        page.extend_from_slice(body);
+        page
+    }
+
+    // A page holding whole packets, each shorter than 255 bytes.
+    fn packets_page(serial: u32, packets: &[Vec<u8>]) -> Vec<u8> {
+        let lacing: Vec<u8> = packets.iter().map(|packet| packet.len() as u8).collect();
+        page(serial, false, &lacing, &packets.concat())
+    }
+
+    fn opus_head(pre_skip: u16) -> Vec<u8> {
+        let mut head = b"OpusHead".to_vec();
+        head.push(1);
+        head.push(1);
+        head.extend_from_slice(&pre_skip.to_le_bytes());
+        head.extend_from_slice(&48000u32.to_le_bytes());
+        head.extend_from_slice(&[0, 0, 0]);
+        head
+    }
+
+    fn encode_frames(n_frames: usize) -> Vec<Vec<u8>> {
+        let mut encoder = Encoder::new(16000, Channels::Mono, Application::Voip).unwrap();
+        let frame: Vec<f32> = (0..FRAME_LEN)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 16000.0).sin() * 0.5)
+            .collect();
+        let mut output = [0u8; 250];
+        (0..n_frames)
+            .map(|_| {
+                let len = encoder.encode_float(&frame, &mut output).unwrap();
+                output[..len].to_vec()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_demuxer_splits_packets() {
+        let mut demuxer = OggDemuxer::new();
+        // A 300-byte packet continued across two pages, then a short packet.
+        let long = vec![7u8; 300];
+        let first = page(1, false, &[255], &long[..255]);
+        let mut second_body = long[255..].to_vec();
+        second_body.extend_from_slice(b"abc");
+        let second = page(1, true, &[45, 3], &second_body);
+
+        let mut bytes = b"garbage".to_vec();
+        bytes.extend_from_slice(&first);
+        bytes.extend_from_slice(&second);
+        // Bytes can arrive split anywhere.
+        for chunk in bytes.chunks(10) {
+            demuxer.push_bytes(chunk);
+        }
+        assert_eq!(
+            demuxer.next_packet(),
+            Some(OggPacket {
+                serial: 1,
+                data: long
+            })
+        );
+        assert_eq!(demuxer.next_packet().unwrap().data, b"abc");
+        assert_eq!(demuxer.next_packet(), None);
+    }
+
+    #[test]
+    fn test_demuxer_drops_orphaned_continuation() {
+        let mut demuxer = OggDemuxer::new();
+        demuxer.push_bytes(&page(
+            1,
+            true,
+            &[10, 3],
+            &[[0u8; 10].as_slice(), b"abc"].concat(),
+        ));
+        assert_eq!(demuxer.next_packet().unwrap().data, b"abc");
+        assert_eq!(demuxer.next_packet(), None);
+    }
+
+    #[test]
+    fn test_ogg_opus_ingest() {
+        let buffer: AudioRingBuffer<f32> = AudioRingBuffer::default();
+        let mut ingest = OggOpusIngest::new(buffer.clone()).unwrap();
+        // 312 samples at 48 kHz is 104 at 16 kHz.
+        let mut stream = packets_page(5, &[opus_head(312)]);
+        stream.extend(packets_page(5, &[b"OpusTags".to_vec()]));
+        stream.extend(packets_page(5, &encode_frames(5)));
+
+        let pushed = ingest.push_bytes(&stream).unwrap();
+        assert_eq!(pushed, 5 * FRAME_LEN - 104);
+        assert_eq!(buffer.get_audio_length(), pushed);
+
+        // Packets from other streams are ignored.
+        assert_eq!(
+            ingest
+                .push_bytes(&packets_page(6, &encode_frames(1)))
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_ogg_opus_rejects_multichannel() {
+        let mut head = opus_head(0);
+        head[18] = 1;
+        let mut ingest = OggOpusIngest::new(AudioRingBuffer::default()).unwrap();
+        assert!(ingest.push_bytes(&packets_page(1, &[head])).is_err());
+    }
+
+    #[test]
+    fn test_opus_ingest_packets() {
+        let buffer: AudioRingBuffer<f32> = AudioRingBuffer::default();
+        let mut ingest = OpusIngest::new(buffer.clone()).unwrap();
+        assert_eq!(ingest.push_lost_packet().unwrap(), 0);
+        for packet in encode_frames(3) {
+            assert_eq!(ingest.push_packet(&packet).unwrap(), FRAME_LEN);
+        }
+        assert_eq!(ingest.push_lost_packet().unwrap(), FRAME_LEN);
+        assert_eq!(buffer.get_audio_length(), 4 * FRAME_LEN);
+    }
+}
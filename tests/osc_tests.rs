@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod osc_tests {
+    use std::net::UdpSocket;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ribble_whisper::export::jsonl::SegmentRecord;
+    use ribble_whisper::export::osc::{
+        encode_osc_message, OscArg, OscSink, DEFAULT_CONTROL_PATH, DEFAULT_TEXT_PATH,
+    };
+    use ribble_whisper::transcriber::{TranscriptionSnapshot, WhisperControlPhrase, WhisperOutput};
+    use ribble_whisper::utils::sink::OutputSink;
+
+    fn read_osc_string(bytes: &[u8], offset: &mut usize) -> String {
+        let end = *offset + bytes[*offset..].iter().position(|b| *b == 0).unwrap();
+        let string = String::from_utf8(bytes[*offset..end].to_vec()).unwrap();
+        *offset = (end / 4 + 1) * 4;
+        string
+    }
+
+    // Decodes the address and arguments of an OSC message with string and int arguments.
+    fn decode(bytes: &[u8]) -> (String, Vec<OscArg>) {
+        let mut offset = 0;
+        let address = read_osc_string(bytes, &mut offset);
+        let type_tags = read_osc_string(bytes, &mut offset);
+        let args = type_tags
+            .chars()
+            .skip(1)
+            .map(|tag| match tag {
+                's' => OscArg::String(read_osc_string(bytes, &mut offset)),
+                'i' => {
+                    let int = i32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap());
+                    offset += 4;
+                    OscArg::Int(int)
+                }
+                _ => panic!("Unexpected type tag: {tag}"),
+            })
+            .collect();
+        assert_eq!(offset, bytes.len(), "Message has trailing bytes.");
+        (address, args)
+    }
+
+    fn receive(socket: &UdpSocket) -> (String, Vec<OscArg>) {
+        let mut buf = [0u8; 1024];
+        let (len, _) = socket
+            .recv_from(&mut buf)
+            .expect("No OSC message received.");
+        decode(&buf[..len])
+    }
+
+    fn receiver() -> UdpSocket {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        socket
+    }
+
+    fn snapshot(confirmed: &str, working: &str) -> WhisperOutput {
+        WhisperOutput::TranscriptionSnapshot(Arc::new(TranscriptionSnapshot::new(
+            Arc::from(confirmed),
+            Arc::from([Arc::from(working)]),
+        )))
+    }
+
+    #[test]
+    fn test_encode_message() {
+        let encoded = encode_osc_message("/caption", &[OscArg::String("hi".to_string())]);
+        assert_eq!(
+            encoded,
+            b"/caption\0\0\0\0,s\0\0hi\0\0".to_vec(),
+            "Strings should be null-terminated and padded to 4 bytes."
+        );
+
+        let encoded = encode_osc_message("/a", &[OscArg::Int(1), OscArg::Float(1.0)]);
+        assert_eq!(
+            encoded,
+            vec![b'/', b'a', 0, 0, b',', b'i', b'f', 0, 0, 0, 0, 1, 0x3f, 0x80, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_send_confirmed_text() {
+        let receiver = receiver();
+        let sink = OscSink::new(receiver.local_addr().unwrap()).unwrap();
+
+        sink.try_send(snapshot(" Hello there.", "How")).unwrap();
+        assert_eq!(
+            receive(&receiver),
+            (
+                DEFAULT_TEXT_PATH.to_string(),
+                vec![OscArg::String("Hello there.".to_string())]
+            )
+        );
+
+        // Nothing new was confirmed; nothing should be sent for this snapshot.
+        sink.try_send(snapshot(" Hello there.", "How are")).unwrap();
+        sink.try_send(snapshot(" Hello there. How are you?", ""))
+            .unwrap();
+        assert_eq!(
+            receive(&receiver),
+            (
+                DEFAULT_TEXT_PATH.to_string(),
+                vec![OscArg::String("How are you?".to_string())]
+            ),
+            "Only newly confirmed text should be sent."
+        );
+
+        sink.try_send(WhisperOutput::ControlPhrase(
+            WhisperControlPhrase::StartSpeaking,
+        ))
+        .unwrap();
+        assert_eq!(
+            receive(&receiver),
+            (
+                DEFAULT_CONTROL_PATH.to_string(),
+                vec![OscArg::String(
+                    WhisperControlPhrase::StartSpeaking.to_string()
+                )]
+            )
+        );
+    }
+
+    #[test]
+    fn test_custom_paths() {
+        let receiver = receiver();
+        let sink = OscSink::new(receiver.local_addr().unwrap())
+            .unwrap()
+            .with_text_path("/stage/captions")
+            .unwrap()
+            .with_working_path("/stage/partial")
+            .unwrap();
+        assert_eq!(sink.target(), receiver.local_addr().unwrap());
+
+        sink.send_output(&snapshot("Done.", "Not yet")).unwrap();
+        assert_eq!(receive(&receiver).0, "/stage/captions");
+        assert_eq!(
+            receive(&receiver),
+            (
+                "/stage/partial".to_string(),
+                vec![OscArg::String("Not yet".to_string())]
+            )
+        );
+
+        sink.send_segment(&SegmentRecord::new("A segment.", 1000, 2500))
+            .unwrap();
+        assert_eq!(
+            receive(&receiver),
+            (
+                "/stage/captions".to_string(),
+                vec![
+                    OscArg::String("A segment.".to_string()),
+                    OscArg::Int(1000),
+                    OscArg::Int(2500)
+                ]
+            )
+        );
+
+        let sink = OscSink::new(receiver.local_addr().unwrap()).unwrap();
+        assert!(sink.with_control_path("no-slash").is_err());
+    }
+}
@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod output_serde_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ribble_whisper::transcriber::audio_events::{AudioEvent, AudioEventKind};
+    use ribble_whisper::transcriber::corrections::Correction;
+    use ribble_whisper::transcriber::speaker::{SnapshotSpeakers, SpeakerId, SpeakerTurn};
+    use ribble_whisper::transcriber::timestamps::{
+        TimestampedSegment, TimestampedSnapshot, WordTiming,
+    };
+    use ribble_whisper::transcriber::{
+        RibbleWhisperSegment, SessionStartReason, SnapshotAssembler, SnapshotDelta,
+        TranscriptionSnapshot, WhisperControlPhrase, WhisperOutput,
+    };
+
+    fn round_trip(output: &WhisperOutput) -> WhisperOutput {
+        let json = serde_json::to_string(output).expect("Failed to serialize output.");
+        serde_json::from_str(&json).expect("Failed to deserialize output.")
+    }
+
+    fn snapshot(confirmed: &str, segments: &[&str]) -> TranscriptionSnapshot {
+        let segments: Vec<Arc<str>> = segments.iter().map(|segment| Arc::from(*segment)).collect();
+        TranscriptionSnapshot::new(Arc::from(confirmed), Arc::from(segments))
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let speakers = SnapshotSpeakers::new(
+            Arc::from([SpeakerTurn {
+                offset: 0,
+                speaker: SpeakerId(1),
+            }]),
+            Arc::from([None, Some(SpeakerId(2))]),
+        );
+        let original =
+            snapshot(" Hello.", &[" How", " are"]).with_speakers(Some(Arc::new(speakers.clone())));
+        let output = round_trip(&WhisperOutput::TranscriptionSnapshot(Arc::new(original)));
+        let WhisperOutput::TranscriptionSnapshot(decoded) = output else {
+            panic!("Expected a snapshot.");
+        };
+        assert_eq!(decoded.confirmed(), " Hello.");
+        assert_eq!(
+            decoded.string_segments(),
+            &[Arc::from(" How"), Arc::from(" are")]
+        );
+        assert_eq!(decoded.speakers(), Some(&speakers));
+
+        // Snapshots without speakers leave the field out.
+        let json = serde_json::to_string(&snapshot("", &[])).unwrap();
+        assert!(!json.contains("speakers"));
+        let decoded: TranscriptionSnapshot = serde_json::from_str(&json).unwrap();
+        assert!(decoded.speakers().is_none());
+    }
+
+    #[test]
+    fn test_delta_round_trip() {
+        let previous = snapshot(" Hello.", &[" How"]);
+        let next = snapshot(" Hello. How", &[" are", " you?"]);
+        let delta = SnapshotDelta::between(&previous, &next).unwrap();
+
+        let mut assembler = SnapshotAssembler::new();
+        assembler.push(&WhisperOutput::TranscriptionSnapshot(Arc::new(previous)));
+        let output = round_trip(&WhisperOutput::SnapshotDelta(Arc::new(delta.clone())));
+        let WhisperOutput::SnapshotDelta(decoded) = &output else {
+            panic!("Expected a delta.");
+        };
+        assert_eq!(decoded.as_ref(), &delta);
+        let assembled = assembler
+            .push(&output)
+            .expect("The delta should apply to the previous snapshot.");
+        assert_eq!(assembled.confirmed(), next.confirmed());
+        assert_eq!(assembled.string_segments(), next.string_segments());
+    }
+
+    #[test]
+    fn test_control_phrase_round_trip() {
+        let phrases = [
+            WhisperControlPhrase::StartSpeaking,
+            WhisperControlPhrase::TimeoutApproaching {
+                remaining: Duration::from_secs(30),
+            },
+            WhisperControlPhrase::SessionStarted(SessionStartReason::WakePhrase(
+                "hey ribble".to_string(),
+            )),
+            WhisperControlPhrase::Debug("debug message".to_string()),
+        ];
+        for phrase in phrases {
+            let expected = phrase.to_string();
+            let WhisperOutput::ControlPhrase(decoded) =
+                round_trip(&WhisperOutput::ControlPhrase(phrase))
+            else {
+                panic!("Expected a control phrase.");
+            };
+            assert_eq!(decoded.to_string(), expected);
+        }
+        let WhisperOutput::ControlPhrase(WhisperControlPhrase::TimeoutApproaching { remaining }) =
+            round_trip(&WhisperOutput::ControlPhrase(
+                WhisperControlPhrase::TimeoutApproaching {
+                    remaining: Duration::from_millis(1500),
+                },
+            ))
+        else {
+            panic!("Expected a timeout warning.");
+        };
+        assert_eq!(remaining, Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_event_and_correction_round_trip() {
+        let event = AudioEvent::new(
+            AudioEventKind::Other(Arc::from("door knock")),
+            100,
+            250,
+            0.8,
+        );
+        let WhisperOutput::AudioEvent(decoded) =
+            round_trip(&WhisperOutput::AudioEvent(event.clone()))
+        else {
+            panic!("Expected an audio event.");
+        };
+        assert_eq!(decoded, event);
+
+        let correction = Correction::new(7..11, "who");
+        let WhisperOutput::Correction(decoded) =
+            round_trip(&WhisperOutput::Correction(correction.clone()))
+        else {
+            panic!("Expected a correction.");
+        };
+        assert_eq!(decoded, correction);
+    }
+
+    #[test]
+    fn test_segment_round_trip() {
+        let segment = RibbleWhisperSegment {
+            text: Arc::from(" Hello."),
+            start_time: 0,
+            end_time: 150,
+            confidence: Some(0.9),
+            word_confidences: None,
+            speaker: Some(SpeakerId(0)),
+        };
+        let json = serde_json::to_string(&segment).unwrap();
+        let decoded: RibbleWhisperSegment = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.text(), segment.text());
+        assert_eq!(decoded.start_timestamp(), segment.start_timestamp());
+        assert_eq!(decoded.end_timestamp(), segment.end_timestamp());
+        assert_eq!(decoded.confidence(), segment.confidence());
+        assert_eq!(decoded.speaker(), segment.speaker());
+    }
+
+    #[test]
+    fn test_timestamped_snapshot_round_trip() {
+        let word = WordTiming {
+            text: Arc::from("Hello."),
+            start_time: 510,
+            end_time: 560,
+            probability: 0.75,
+        };
+        let snapshot = TimestampedSnapshot {
+            window_start: 500,
+            segments: vec![TimestampedSegment {
+                segment: RibbleWhisperSegment {
+                    text: Arc::from(" Hello."),
+                    start_time: 510,
+                    end_time: 560,
+                    confidence: Some(0.75),
+                    word_confidences: None,
+                    speaker: None,
+                },
+                words: vec![word.clone()],
+            }],
+        };
+        let WhisperOutput::TimestampedSnapshot(decoded) =
+            round_trip(&WhisperOutput::TimestampedSnapshot(Arc::new(snapshot)))
+        else {
+            panic!("Expected a timestamped snapshot.");
+        };
+        assert_eq!(decoded.window_start, 500);
+        assert_eq!(decoded.segments[0].segment.text(), " Hello.");
+        assert_eq!(decoded.segments[0].segment.end_timestamp(), 560);
+        assert_eq!(decoded.words().collect::<Vec<_>>(), vec![&word]);
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod playback_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ribble_whisper::audio::playback::Playback;
+    use ribble_whisper::audio::{AudioChannelConfiguration, WhisperAudioSample};
+    use ribble_whisper::transcriber::RibbleWhisperSegment;
+    use ribble_whisper::utils::errors::RibbleWhisperError;
+
+    // Playback needs an output device, which CI machines might not have.
+    fn playback(
+        audio: WhisperAudioSample,
+        channels: AudioChannelConfiguration,
+    ) -> Option<Playback> {
+        match Playback::new(audio, 16000, channels) {
+            Ok(playback) => Some(playback),
+            Err(RibbleWhisperError::DeviceError(e)) => {
+                eprintln!("Skipping playback test: {e}");
+                None
+            }
+            Err(e) => panic!("Failed to open playback: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_sample_rate() {
+        let audio = WhisperAudioSample::F32(Arc::from(vec![0.0; 16]));
+        let res = Playback::new(audio, 0, AudioChannelConfiguration::Mono);
+        assert!(matches!(res, Err(RibbleWhisperError::ParameterError(_))));
+    }
+
+    #[test]
+    fn test_seek_to_segment() {
+        // Two seconds of stereo silence.
+        let audio = WhisperAudioSample::I16(Arc::from(vec![0i16; 16000 * 2 * 2]));
+        let Some(playback) = playback(audio, AudioChannelConfiguration::Stereo) else {
+            return;
+        };
+        assert!(playback.is_paused(), "Playback should start paused.");
+        assert_eq!(playback.duration(), Duration::from_secs(2));
+
+        playback.seek(Duration::from_millis(500));
+        assert!(playback.is_paused(), "Seeking should keep the pause state.");
+        assert_eq!(playback.position(), Duration::from_millis(500));
+
+        playback.seek(Duration::from_secs(10));
+        assert_eq!(
+            playback.position(),
+            playback.duration(),
+            "Seeking past the end should clamp to the end."
+        );
+
+        // Timestamps are in centiseconds: 1.25s - 1.5s.
+        let segment = RibbleWhisperSegment {
+            text: Arc::from("hello"),
+            start_time: 125,
+            end_time: 150,
+            confidence: None,
+            word_confidences: None,
+            speaker: None,
+        };
+        playback.play_segment(&segment);
+        assert!(!playback.is_paused());
+        assert!(playback.position() >= Duration::from_millis(1250));
+        playback.pause();
+    }
+}
@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod postprocess_tests {
+    use ribble_whisper::postprocess::locale::{LocaleNormalizer, PunctuationStyle};
+    use ribble_whisper::postprocess::stages::{
+        CaseNormalizer, CaseStyle, FillerWordRemover, PunctuationNormalizer,
+    };
+    use ribble_whisper::postprocess::{TextPipeline, TextProcessor};
+    use ribble_whisper::whisper::configs::Language;
+
+    #[test]
+    fn test_punctuation_normalizer() {
+        let normalizer = PunctuationNormalizer::new();
+        assert_eq!(
+            normalizer.process("  hello ,world  .  ".to_string()),
+            "hello, world.",
+            "Whitespace before punctuation should be removed and a space added after."
+        );
+        assert_eq!(
+            normalizer.process("wait,, what".to_string()),
+            "wait, what",
+            "Repeated commas should be collapsed."
+        );
+        assert_eq!(
+            normalizer.process("about 3.5 or 1,000 people".to_string()),
+            "about 3.5 or 1,000 people",
+            "Numbers should be left alone."
+        );
+    }
+
+    #[test]
+    fn test_case_normalizer() {
+        let sentence = CaseNormalizer::default();
+        assert_eq!(sentence.style(), CaseStyle::Sentence);
+        assert_eq!(
+            sentence.process("i think i'm ready. are you? yes".to_string()),
+            "I think I'm ready. Are you? Yes",
+            "Sentence starts and the pronoun I should be capitalized."
+        );
+
+        let lower = CaseNormalizer::new(CaseStyle::Lower);
+        assert_eq!(lower.process("Hello World".to_string()), "hello world");
+        let upper = CaseNormalizer::new(CaseStyle::Upper);
+        assert_eq!(upper.process("Hello World".to_string()), "HELLO WORLD");
+    }
+
+    #[test]
+    fn test_filler_word_remover() {
+        let remover = FillerWordRemover::new();
+        assert_eq!(
+            remover.process("I um think so, Uh.".to_string()),
+            "I think so.",
+            "Filler words should be removed, keeping sentence-ending punctuation."
+        );
+
+        let custom = FillerWordRemover::empty().with_filler_words(["like"]);
+        assert_eq!(
+            custom.process("it was um like fine".to_string()),
+            "it was um fine",
+            "Only the configured filler words should be removed."
+        );
+    }
+
+    #[test]
+    fn test_text_pipeline() {
+        let empty = TextPipeline::new();
+        assert!(empty.is_empty());
+        assert_eq!(
+            empty.process("unchanged ,text".to_string()),
+            "unchanged ,text",
+            "An empty pipeline should not modify the text."
+        );
+
+        let pipeline = TextPipeline::new()
+            .with_stage(FillerWordRemover::new())
+            .with_stage(PunctuationNormalizer::new())
+            .with_stage(CaseNormalizer::default())
+            .with_stage(|text: String| text.replace("world", "World"));
+        assert_eq!(pipeline.len(), 4);
+        assert_eq!(
+            pipeline.process("um, hello ,world .".to_string()),
+            "Hello, World.",
+            "Stages should run in the order they were added."
+        );
+    }
+
+    #[test]
+    fn test_locale_normalizer() {
+        let normalizer = LocaleNormalizer::new();
+        assert_eq!(
+            normalizer.process("i think so. are you ready".to_string()),
+            "I think so. Are you ready",
+            "Text of an unknown language should be treated as English."
+        );
+        assert_eq!(
+            normalizer.process_for_language(
+                "bonjour ! ça va ? «oui» à 10:30.".to_string(),
+                Some(Language::Fr)
+            ),
+            "Bonjour\u{202F}! Ça va\u{202F}? «\u{202F}Oui\u{202F}» à 10:30.",
+            "French punctuation should take a narrow no-break space."
+        );
+        assert_eq!(
+            normalizer
+                .process_for_language("你好 , 世界 . 我用 GPT 4.0".to_string(), Some(Language::Zh)),
+            "你好，世界。我用 GPT 4.0",
+            "CJK punctuation should be full-width, without surrounding spaces."
+        );
+        assert_eq!(
+            normalizer.process_for_language("istanbul güzel. iyi".to_string(), Some(Language::Tr)),
+            "İstanbul güzel. İyi"
+        );
+
+        let fixed = LocaleNormalizer::new()
+            .with_language(Some(Language::Fr))
+            .with_sentence_case(false);
+        assert_eq!(
+            fixed.process_for_language("oui ?".to_string(), Some(Language::En)),
+            "oui\u{202F}?",
+            "A fixed language should take precedence over the transcriber's."
+        );
+        assert_eq!(
+            PunctuationStyle::for_language(Language::Ja),
+            PunctuationStyle::FullWidth
+        );
+
+        let pipeline = TextPipeline::new().with_stage(LocaleNormalizer::new());
+        assert_eq!(
+            pipeline.process_for_language("ça va ?".to_string(), Some(Language::Fr)),
+            "Ça va\u{202F}?",
+            "Pipelines should pass the language on to each stage."
+        );
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod profanity_tests {
+    use ribble_whisper::postprocess::profanity::{MaskStyle, ProfanityFilter};
+    use ribble_whisper::postprocess::TextProcessor;
+
+    #[test]
+    fn test_default_filter() {
+        let filter = ProfanityFilter::new();
+        assert_eq!(
+            filter.process("Oh SHIT, that's \"damn\" good shitake.".to_string()),
+            "Oh ****, that's \"****\" good shitake.",
+            "Whole words should be masked regardless of case, keeping punctuation."
+        );
+        assert_eq!(filter.process("fuckin' hell".to_string()), "******* hell");
+    }
+
+    #[test]
+    fn test_mask_styles() {
+        let text = "well shit".to_string();
+        let styled = |style: MaskStyle| {
+            ProfanityFilter::new()
+                .with_mask_style(style)
+                .process(text.clone())
+        };
+        assert_eq!(styled(MaskStyle::KeepFirst), "well s***");
+        assert_eq!(styled(MaskStyle::Grawlix), "well #$@!");
+        assert_eq!(
+            styled(MaskStyle::Replace("[bleep]".to_string())),
+            "well [bleep]"
+        );
+        assert_eq!(styled(MaskStyle::Remove), "well");
+    }
+
+    #[test]
+    fn test_custom_words() {
+        let filter = ProfanityFilter::empty()
+            .with_words(["Heck", "darn"])
+            .without_words(["darn"]);
+        assert_eq!(
+            filter.process("heck, darn it".to_string()),
+            "****, darn it",
+            "Only the configured words should be masked."
+        );
+
+        let relaxed = ProfanityFilter::new().without_words(["damn"]);
+        assert!(!relaxed.words().contains("damn"));
+        assert_eq!(relaxed.process("damn".to_string()), "damn");
+    }
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod replacement_tests {
+    use ribble_whisper::postprocess::replacements::{ReplacementRule, ReplacementRules};
+    use ribble_whisper::postprocess::TextProcessor;
+
+    #[test]
+    fn test_exact_rule_matches_whole_words() {
+        let rule = ReplacementRule::exact("wisper", "Whisper").unwrap();
+        assert_eq!(
+            rule.apply("wisper's wispers Wisper wisper"),
+            "Whisper's wispers Wisper Whisper",
+            "Exact rules should only match whole, identically-cased words."
+        );
+    }
+
+    #[test]
+    fn test_case_insensitive_rule() {
+        let rule = ReplacementRule::case_insensitive("ribble", "Ribble").unwrap();
+        assert_eq!(
+            rule.apply("RIBBLE and ribble, not scribble"),
+            "Ribble and Ribble, not scribble",
+            "Case-insensitive rules should match regardless of case."
+        );
+    }
+
+    #[test]
+    fn test_empty_pattern_is_invalid() {
+        let err = ReplacementRule::exact("", "nothing").unwrap_err();
+        assert_eq!(err.error_code(), "invalid_replacement_rule");
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rules: ReplacementRules = "# Product names\n\
+            \n\
+            wisper => Whisper\n\
+            exact: rust lang => Rust\n\
+            nocase: ribble => Ribble\n"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            rules.len(),
+            3,
+            "Comments and blank lines should be skipped."
+        );
+        assert_eq!(rules.rules()[1].pattern(), "rust lang");
+        assert_eq!(
+            rules.process("wisper in rust lang, by RIBBLE".to_string()),
+            "Whisper in Rust, by Ribble"
+        );
+
+        let err = "wisper Whisper".parse::<ReplacementRules>().unwrap_err();
+        assert_eq!(
+            err.error_code(),
+            "invalid_replacement_rule",
+            "A rule without a separator should fail to parse."
+        );
+    }
+
+    #[test]
+    fn test_rules_from_file() {
+        let path = std::env::temp_dir().join("ribble_whisper_replacement_rules.txt");
+        std::fs::write(&path, "git hub => GitHub\n").unwrap();
+        let rules = ReplacementRules::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(rules.process("on git hub".to_string()), "on GitHub");
+    }
+
+    #[cfg(feature = "regex-rules")]
+    #[test]
+    fn test_regex_rule() {
+        let rules: ReplacementRules = r"regex: (\d+) k\b => ${1}k".parse().unwrap();
+        assert_eq!(
+            rules.process("about 50 k users".to_string()),
+            "about 50k users",
+            "Regex replacements should support capture groups."
+        );
+        assert!(
+            ReplacementRule::regex("(unclosed", "").is_err(),
+            "Invalid expressions should fail to compile."
+        );
+    }
+
+    #[cfg(not(feature = "regex-rules"))]
+    #[test]
+    fn test_regex_rule_requires_feature() {
+        assert!(
+            "regex: a+ => b".parse::<ReplacementRules>().is_err(),
+            "Regex rules should be rejected without the regex-rules feature."
+        );
+    }
+}
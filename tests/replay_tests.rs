@@ -0,0 +1,528 @@
+mod common;
+#[cfg(test)]
+mod replay_tests {
+    use std::sync::atomic::AtomicBool;
+
+    use crate::common::prep_model_bank;
+    use ribble_whisper::audio::audio_ring_buffer::AudioRingBuffer;
+    use ribble_whisper::testing::replay::{AudioReplay, FixedModelRetriever, RealtimeReplay};
+    use ribble_whisper::testing::vad::{AlwaysVoiced, NeverVoiced};
+    use ribble_whisper::transcriber::audio_events::{
+        AudioEventClassifier, AudioEventKind, AudioEventScore,
+    };
+    use ribble_whisper::transcriber::vad::Silero;
+    use ribble_whisper::transcriber::{
+        WHISPER_SAMPLE_RATE, WhisperControlPhrase, WhisperOutput, redirect_whisper_logging_to_hooks,
+    };
+    use ribble_whisper::whisper::configs::{CommitPolicy, IdlePolicy, WhisperRealtimeConfigs};
+    use ribble_whisper::whisper::model::{DefaultModelType, ModelLocation, ModelRetriever};
+
+    const AUDIO_FILE: &str = "tests/audio_files/128896__joshenanigans__sentence-recitation.wav";
+
+    // Hears music in every window, so that an audio event is sent when the session ends.
+    struct AlwaysMusic;
+
+    impl AudioEventClassifier for AlwaysMusic {
+        fn classify(&mut self, _samples: &[f32]) -> Vec<AudioEventScore> {
+            vec![AudioEventScore::new(AudioEventKind::Music, 0.9)]
+        }
+    }
+
+    fn is_control(output: &WhisperOutput) -> bool {
+        matches!(
+            output,
+            WhisperOutput::ControlPhrase(_) | WhisperOutput::DecodeDiagnostics(_)
+        )
+    }
+
+    fn control_replay(control_channel: bool) -> RealtimeReplay<Silero, FixedModelRetriever> {
+        let (model_bank, model_id) = prep_model_bank(DefaultModelType::Medium);
+        let Some(ModelLocation::DynamicFilePath(model_path)) = model_bank.retrieve_model(model_id)
+        else {
+            unreachable!("DefaultModelBank only returns DynamicFilePath.");
+        };
+
+        let configs = WhisperRealtimeConfigs::default()
+            .with_n_threads(8)
+            .with_model_id(Some(model_id))
+            .with_use_flash_attention(true)
+            .with_decode_diagnostics(true)
+            .with_timestamped_snapshots(true)
+            .with_correction_horizon(64);
+        let vad = Silero::try_new_whisper_realtime_default()
+            .expect("Silero VAD expected to build without issue");
+        let replay = AudioReplay::from_file(AUDIO_FILE)
+            .expect("Test audio should load without issue.")
+            .with_speed(2.0);
+        RealtimeReplay::new(configs, vad, FixedModelRetriever::new(model_path), replay)
+            .with_audio_event_classifier(AlwaysMusic)
+            .with_control_channel(control_channel)
+    }
+
+    #[test]
+    fn test_audio_replay() {
+        let audio: Vec<f32> = (0..WHISPER_SAMPLE_RATE as usize)
+            .map(|idx| (idx % 100) as f32 / 100.0)
+            .collect();
+        let replay = AudioReplay::new(audio.clone())
+            .with_chunk_len(173)
+            .with_speed(f64::INFINITY);
+        assert_eq!(replay.duration().as_secs(), 1);
+
+        let buffer = AudioRingBuffer::<f32>::default();
+        replay.replay_into(&buffer, &AtomicBool::new(true));
+        assert_eq!(buffer.get_audio_length(), audio.len());
+        assert_eq!(buffer.read(0), audio);
+
+        // Nothing is pushed once the replay is stopped.
+        let buffer = AudioRingBuffer::<f32>::default();
+        replay.replay_into(&buffer, &AtomicBool::new(false));
+        assert_eq!(buffer.get_audio_length(), 0);
+    }
+
+    #[test]
+    fn test_realtime_replay() {
+        let (model_bank, model_id) = prep_model_bank(DefaultModelType::Medium);
+        let Some(ModelLocation::DynamicFilePath(model_path)) = model_bank.retrieve_model(model_id)
+        else {
+            unreachable!("DefaultModelBank only returns DynamicFilePath.");
+        };
+
+        let configs = WhisperRealtimeConfigs::default()
+            .with_n_threads(8)
+            .with_model_id(Some(model_id))
+            .with_use_flash_attention(true);
+        let vad = Silero::try_new_whisper_realtime_default()
+            .expect("Silero VAD expected to build without issue");
+        let replay = AudioReplay::from_file(AUDIO_FILE)
+            .expect("Test audio should load without issue.")
+            .with_speed(2.0);
+
+        redirect_whisper_logging_to_hooks();
+        let outcome =
+            RealtimeReplay::new(configs, vad, FixedModelRetriever::new(model_path), replay)
+                .run()
+                .expect("Replay expected to run without issue.");
+
+        assert!(
+            matches!(
+                outcome.control_phrases().last(),
+                Some(WhisperControlPhrase::EndTranscription)
+            ),
+            "The transcriber did not end the transcription."
+        );
+        assert!(outcome.snapshots().count() > 0, "No snapshots were sent.");
+
+        // Nothing confirmed should be transcribed twice.
+        let words: Vec<&str> = outcome.transcription.split_whitespace().collect();
+        assert!(
+            words
+                .windows(2)
+                .all(|pair| pair[0].to_lowercase() != pair[1].to_lowercase()),
+            "Duplicated words in: {}",
+            outcome.transcription
+        );
+
+        let expected = "Mary has many dreams but can't touch Tennessee by way of flight";
+        let edit_distance = strsim::levenshtein(&outcome.transcription, expected);
+        assert!(
+            edit_distance <= 4,
+            "Failed to output reasonable match.\nEdit distance: {}, Output: {}, Expected: {}",
+            edit_distance,
+            outcome.transcription,
+            expected
+        );
+    }
+
+    // Without any detected speech, the transcriber should pause throughout and confirm nothing.
+    #[test]
+    fn test_realtime_replay_no_voice() {
+        let (model_bank, model_id) = prep_model_bank(DefaultModelType::Medium);
+        let Some(ModelLocation::DynamicFilePath(model_path)) = model_bank.retrieve_model(model_id)
+        else {
+            unreachable!("DefaultModelBank only returns DynamicFilePath.");
+        };
+
+        let configs = WhisperRealtimeConfigs::default()
+            .with_n_threads(8)
+            .with_model_id(Some(model_id))
+            .with_use_flash_attention(true);
+        let replay = AudioReplay::from_file(AUDIO_FILE)
+            .expect("Test audio should load without issue.")
+            .with_speed(4.0);
+
+        let outcome = RealtimeReplay::new(
+            configs,
+            NeverVoiced,
+            FixedModelRetriever::new(model_path),
+            replay,
+        )
+        .with_settle_time(std::time::Duration::from_millis(500))
+        .run()
+        .expect("Replay expected to run without issue.");
+
+        assert!(
+            outcome.transcription.trim().is_empty(),
+            "Unexpected transcription: {}",
+            outcome.transcription
+        );
+        assert!(
+            outcome
+                .snapshots()
+                .all(|snapshot| snapshot.confirmed().trim().is_empty()),
+            "Text was confirmed without any detected speech."
+        );
+    }
+
+    // The transcriber should warn before timing out.
+    #[test]
+    fn test_realtime_replay_timeout_warning() {
+        let (model_bank, model_id) = prep_model_bank(DefaultModelType::Medium);
+        let Some(ModelLocation::DynamicFilePath(model_path)) = model_bank.retrieve_model(model_id)
+        else {
+            unreachable!("DefaultModelBank only returns DynamicFilePath.");
+        };
+
+        let configs = WhisperRealtimeConfigs::default()
+            .with_n_threads(8)
+            .with_model_id(Some(model_id))
+            .with_use_flash_attention(true)
+            .with_realtime_timeout(2000)
+            .with_timeout_warning(1500);
+        let replay = AudioReplay::from_file(AUDIO_FILE)
+            .expect("Test audio should load without issue.")
+            .with_speed(1.0);
+
+        let outcome = RealtimeReplay::new(
+            configs,
+            AlwaysVoiced,
+            FixedModelRetriever::new(model_path),
+            replay,
+        )
+        .run()
+        .expect("Replay expected to run without issue.");
+
+        let phrases: Vec<&WhisperControlPhrase> = outcome.control_phrases().collect();
+        let warning = phrases.iter().position(|phrase| {
+            matches!(
+                phrase,
+                WhisperControlPhrase::TimeoutApproaching { remaining }
+                    if remaining.as_millis() <= 1500
+            )
+        });
+        let timeout = phrases
+            .iter()
+            .position(|phrase| matches!(phrase, WhisperControlPhrase::TranscriptionTimeout));
+        assert!(
+            matches!((warning, timeout), (Some(warning), Some(timeout)) if warning < timeout),
+            "The timeout warning should be sent before the timeout."
+        );
+    }
+
+    // Without any detected speech, the idle policy should stop the session early.
+    #[test]
+    fn test_realtime_replay_idle_stop() {
+        let (model_bank, model_id) = prep_model_bank(DefaultModelType::Medium);
+        let Some(ModelLocation::DynamicFilePath(model_path)) = model_bank.retrieve_model(model_id)
+        else {
+            unreachable!("DefaultModelBank only returns DynamicFilePath.");
+        };
+
+        let configs = WhisperRealtimeConfigs::default()
+            .with_n_threads(8)
+            .with_model_id(Some(model_id))
+            .with_use_flash_attention(true)
+            .with_idle_policy(IdlePolicy::Stop { after_ms: 1000 });
+        let replay = AudioReplay::from_file(AUDIO_FILE)
+            .expect("Test audio should load without issue.")
+            .with_speed(1.0);
+
+        let outcome = RealtimeReplay::new(
+            configs,
+            NeverVoiced,
+            FixedModelRetriever::new(model_path),
+            replay,
+        )
+        .run()
+        .expect("Replay expected to run without issue.");
+
+        let phrases: Vec<&WhisperControlPhrase> = outcome.control_phrases().collect();
+        assert!(
+            phrases
+                .iter()
+                .any(|phrase| matches!(phrase, WhisperControlPhrase::IdleTimeout)),
+            "The session should have stopped on the idle timeout."
+        );
+        assert!(
+            matches!(phrases.last(), Some(WhisperControlPhrase::EndTranscription)),
+            "The transcriber did not end the transcription."
+        );
+    }
+
+    // Time spent idle-paused shouldn't count toward the realtime timeout.
+    #[test]
+    fn test_realtime_replay_idle_pause_timeout() {
+        let (model_bank, model_id) = prep_model_bank(DefaultModelType::Medium);
+        let Some(ModelLocation::DynamicFilePath(model_path)) = model_bank.retrieve_model(model_id)
+        else {
+            unreachable!("DefaultModelBank only returns DynamicFilePath.");
+        };
+
+        let configs = WhisperRealtimeConfigs::default()
+            .with_n_threads(8)
+            .with_model_id(Some(model_id))
+            .with_use_flash_attention(true)
+            .with_realtime_timeout(3000)
+            .with_idle_policy(IdlePolicy::Pause { after_ms: 500 });
+        // Well past the timeout, were the paused time counted.
+        let replay = AudioReplay::new(vec![0.0f32; 6 * WHISPER_SAMPLE_RATE as usize]);
+
+        let outcome = RealtimeReplay::new(
+            configs,
+            NeverVoiced,
+            FixedModelRetriever::new(model_path),
+            replay,
+        )
+        .run()
+        .expect("Replay expected to run without issue.");
+
+        let phrases: Vec<&WhisperControlPhrase> = outcome.control_phrases().collect();
+        assert!(
+            phrases
+                .iter()
+                .any(|phrase| matches!(phrase, WhisperControlPhrase::IdlePaused)),
+            "The session should have paused on the idle timeout."
+        );
+        assert!(
+            !phrases
+                .iter()
+                .any(|phrase| matches!(phrase, WhisperControlPhrase::TranscriptionTimeout)),
+            "The paused time was counted toward the realtime timeout."
+        );
+        assert!(
+            matches!(phrases.last(), Some(WhisperControlPhrase::EndTranscription)),
+            "The transcriber did not end the transcription."
+        );
+    }
+
+    // Segments confirmed by the commit policy shouldn't be confirmed again, (or sent with word
+    // timings again), by the final window of a slow stop, which re-transcribes the buffered audio.
+    #[test]
+    fn test_realtime_replay_commit_policy_slow_stop() {
+        let (model_bank, model_id) = prep_model_bank(DefaultModelType::Medium);
+        let Some(ModelLocation::DynamicFilePath(model_path)) = model_bank.retrieve_model(model_id)
+        else {
+            unreachable!("DefaultModelBank only returns DynamicFilePath.");
+        };
+
+        let configs = WhisperRealtimeConfigs::default()
+            .with_n_threads(8)
+            .with_model_id(Some(model_id))
+            .with_use_flash_attention(true)
+            .with_commit_policy(CommitPolicy::Time { after_ms: 300 })
+            .with_timestamped_snapshots(true);
+        let replay = AudioReplay::from_file(AUDIO_FILE)
+            .expect("Test audio should load without issue.")
+            .with_speed(1.0);
+
+        // Without pauses, the buffer is never cleared, so the slow stop sees the committed audio.
+        let outcome = RealtimeReplay::new(
+            configs,
+            AlwaysVoiced,
+            FixedModelRetriever::new(model_path),
+            replay,
+        )
+        .with_settle_time(std::time::Duration::from_millis(1000))
+        .run()
+        .expect("Replay expected to run without issue.");
+
+        assert!(
+            outcome
+                .control_phrases()
+                .any(|phrase| matches!(phrase, WhisperControlPhrase::SlowStop)),
+            "The transcriber did not slow stop."
+        );
+        // Every word of the recording is distinct, so a repeat means a duplicated segment.
+        let mut words: Vec<String> = outcome
+            .transcription
+            .split_whitespace()
+            .map(|word| {
+                word.trim_matches(|c: char| !c.is_alphanumeric())
+                    .to_lowercase()
+            })
+            .filter(|word| !word.is_empty())
+            .collect();
+        let n_words = words.len();
+        words.sort();
+        words.dedup();
+        assert_eq!(
+            words.len(),
+            n_words,
+            "Duplicated segments in: {}",
+            outcome.transcription
+        );
+
+        let last_timestamped = outcome
+            .outputs
+            .iter()
+            .rev()
+            .find_map(|output| match output {
+                WhisperOutput::TimestampedSnapshot(snapshot) => Some(snapshot),
+                _ => None,
+            })
+            .expect("No timestamped snapshots were sent.");
+        assert!(
+            last_timestamped.words().count() < n_words,
+            "The final timestamped snapshot resent committed segments: {last_timestamped}"
+        );
+    }
+
+    // A pause in the middle of the recording clears the buffer; what's heard after it should be
+    // timed after the pause, and nothing from before it should be re-sent.
+    #[test]
+    fn test_realtime_replay_pause_resume() {
+        let (model_bank, model_id) = prep_model_bank(DefaultModelType::Medium);
+        let Some(ModelLocation::DynamicFilePath(model_path)) = model_bank.retrieve_model(model_id)
+        else {
+            unreachable!("DefaultModelBank only returns DynamicFilePath.");
+        };
+
+        let configs = WhisperRealtimeConfigs::default()
+            .with_n_threads(8)
+            .with_model_id(Some(model_id))
+            .with_use_flash_attention(true)
+            .with_timestamped_snapshots(true);
+        let vad = Silero::try_new_whisper_realtime_default()
+            .expect("Silero VAD expected to build without issue");
+        let recording = AudioReplay::from_file(AUDIO_FILE)
+            .expect("Test audio should load without issue.")
+            .audio()
+            .to_vec();
+        // Long enough to outlast the VAD timeout, so the transcriber pauses.
+        let silence = vec![0.0f32; 4 * WHISPER_SAMPLE_RATE as usize];
+        let audio = [recording.as_slice(), &silence, &recording].concat();
+        let resume_time =
+            ((recording.len() + silence.len()) * 100) as i64 / WHISPER_SAMPLE_RATE as i64;
+        let replay = AudioReplay::new(audio).with_speed(2.0);
+
+        let outcome =
+            RealtimeReplay::new(configs, vad, FixedModelRetriever::new(model_path), replay)
+                .run()
+                .expect("Replay expected to run without issue.");
+
+        assert!(
+            matches!(
+                outcome.control_phrases().last(),
+                Some(WhisperControlPhrase::EndTranscription)
+            ),
+            "The transcriber did not end the transcription."
+        );
+
+        let after_pause: Vec<_> = outcome
+            .outputs
+            .iter()
+            .filter_map(|output| match output {
+                WhisperOutput::TimestampedSnapshot(snapshot) => Some(snapshot),
+                _ => None,
+            })
+            .filter(|snapshot| snapshot.window_start >= resume_time)
+            .collect();
+        assert!(
+            !after_pause.is_empty(),
+            "No timestamped snapshots were sent after the pause."
+        );
+        for snapshot in after_pause {
+            assert!(
+                snapshot.words().all(|word| word.end_time > resume_time),
+                "A snapshot after the pause re-sent audio from before it: {snapshot}"
+            );
+        }
+
+        // Both recitations should be confirmed, once each.
+        let n_repeats = outcome
+            .transcription
+            .to_lowercase()
+            .matches("tennessee")
+            .count();
+        assert_eq!(
+            n_repeats, 2,
+            "Expected both recitations in: {}",
+            outcome.transcription
+        );
+    }
+
+    // Control phrases and diagnostics go to the control sender; transcript updates stay on the
+    // output sender.
+    #[test]
+    fn test_realtime_replay_control_sender() {
+        let outcome = control_replay(true)
+            .run()
+            .expect("Replay expected to run without issue.");
+
+        assert!(
+            outcome.control_outputs.iter().all(is_control),
+            "Transcript updates were sent on the control sender."
+        );
+        assert!(
+            !outcome.outputs.iter().any(is_control),
+            "Control outputs were sent on the output sender."
+        );
+        assert!(
+            matches!(
+                outcome.control_phrases().last(),
+                Some(WhisperControlPhrase::EndTranscription)
+            ),
+            "The control sender did not receive the end of the transcription."
+        );
+        assert!(
+            outcome
+                .control_outputs
+                .iter()
+                .any(|output| matches!(output, WhisperOutput::DecodeDiagnostics(_))),
+            "No decode diagnostics were sent on the control sender."
+        );
+        assert!(outcome.snapshots().count() > 0, "No snapshots were sent.");
+        assert!(
+            outcome
+                .outputs
+                .iter()
+                .any(|output| matches!(output, WhisperOutput::TimestampedSnapshot(_))),
+            "No timestamped snapshots were sent on the output sender."
+        );
+        assert!(
+            outcome
+                .outputs
+                .iter()
+                .any(|output| matches!(output, WhisperOutput::AudioEvent(_))),
+            "No audio events were sent on the output sender."
+        );
+    }
+
+    // Without a control sender, everything is sent on the output sender.
+    #[test]
+    fn test_realtime_replay_control_fallback() {
+        let outcome = control_replay(false)
+            .run()
+            .expect("Replay expected to run without issue.");
+
+        assert!(
+            outcome.control_outputs.is_empty(),
+            "Outputs were sent without a control sender."
+        );
+        assert!(
+            outcome
+                .outputs
+                .iter()
+                .any(|output| matches!(output, WhisperOutput::DecodeDiagnostics(_))),
+            "Decode diagnostics did not fall back to the output sender."
+        );
+        assert!(
+            matches!(
+                outcome.control_phrases().last(),
+                Some(WhisperControlPhrase::EndTranscription)
+            ),
+            "Control phrases did not fall back to the output sender."
+        );
+        assert!(outcome.snapshots().count() > 0, "No snapshots were sent.");
+    }
+}
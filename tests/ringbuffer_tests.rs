@@ -221,6 +221,79 @@ mod ringbuffer_tests {
         assert_eq!(ring_buffer.get_head_position(), head_pos);
     }
 
+    #[test]
+    fn test_snapshot_with_wraparound() {
+        let ring_buffer: AudioRingBuffer<f32> = AudioRingBuffer::default();
+        let second = transcriber::WHISPER_SAMPLE_RATE as usize;
+        // Push 12 one-second chunks, (counting up), so the buffer wraps around.
+        for idx in 0..12 {
+            ring_buffer.push_audio(&vec![idx as f32; second]);
+        }
+
+        let snapshot = ring_buffer.snapshot();
+        assert_eq!(snapshot.len(), ring_buffer.get_audio_length());
+        // The oldest two seconds were overwritten.
+        assert_eq!(snapshot.first(), Some(&2.0));
+        assert_eq!(snapshot.last(), Some(&11.0));
+        assert!(non_decreasing(&snapshot));
+        assert_eq!(snapshot, ring_buffer.read(0));
+
+        ring_buffer.clear();
+        assert!(ring_buffer.snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_extend_capacity_and_keep() {
+        let ring_buffer: AudioRingBuffer<f32> = AudioRingBuffer::default();
+        let second = transcriber::WHISPER_SAMPLE_RATE as usize;
+        for idx in 0..12 {
+            ring_buffer.push_audio(&vec![idx as f32; second]);
+        }
+        let before = ring_buffer.snapshot();
+
+        // Shrinking is ignored.
+        ring_buffer.extend_capacity_and_keep(crate::SAMPLE_DURATION / 2);
+        assert_eq!(ring_buffer.get_capacity_in_ms(), crate::SAMPLE_DURATION);
+
+        ring_buffer.extend_capacity_and_keep(crate::SAMPLE_DURATION * 2);
+        assert_eq!(ring_buffer.get_capacity_in_ms(), crate::SAMPLE_DURATION * 2);
+        assert_eq!(ring_buffer.get_capacity(), 20 * second);
+        assert_eq!(ring_buffer.snapshot(), before);
+
+        // New audio is appended after the kept audio, and nothing is overwritten until full.
+        for idx in 12..20 {
+            ring_buffer.push_audio(&vec![idx as f32; second]);
+        }
+        let snapshot = ring_buffer.snapshot();
+        assert_eq!(snapshot.len(), 18 * second);
+        assert_eq!(&snapshot[..before.len()], before.as_slice());
+        assert_eq!(snapshot.last(), Some(&19.0));
+        assert!(non_decreasing(&snapshot));
+    }
+
+    #[test]
+    fn test_samples_pushed() {
+        let ring_buffer: AudioRingBuffer<f32> = AudioRingBuffer::default();
+        let second = transcriber::WHISPER_SAMPLE_RATE as usize;
+        for _ in 0..12 {
+            ring_buffer.push_audio(&vec![0.5; second]);
+        }
+        // The buffer is full, but the stream keeps counting.
+        assert_eq!(ring_buffer.get_audio_length(), ring_buffer.get_capacity());
+        assert_eq!(ring_buffer.get_samples_pushed(), 12 * second);
+
+        // Clearing does not reset the stream.
+        ring_buffer.clear_from_back_retain_ms(500);
+        ring_buffer.clear();
+        ring_buffer.push_audio(&vec![0.5; second]);
+        assert_eq!(ring_buffer.get_audio_length(), second);
+        assert_eq!(ring_buffer.get_samples_pushed(), 13 * second);
+
+        // Input longer than the buffer still advances the stream by its full length.
+        ring_buffer.push_audio(&vec![0.5; 11 * second]);
+        assert_eq!(ring_buffer.get_samples_pushed(), 24 * second);
+    }
+
     fn non_decreasing(v: &[f32]) -> bool {
         for i in 0..v.len() - 1 {
             let j = i + 1;
@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod rtp_tests {
+    use std::net::UdpSocket;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    use ribble_whisper::audio::audio_ring_buffer::AudioRingBuffer;
+    use ribble_whisper::audio::rtp::{
+        JitterBuffer, JitterSlot, RtpPacket, RtpPayloadFormat, RtpSource, alaw_to_linear,
+        ulaw_to_linear,
+    };
+
+    const L16_16K: RtpPayloadFormat = RtpPayloadFormat::L16 {
+        sample_rate: 16000,
+        channels: 1,
+    };
+
+    fn rtp_packet(payload_type: u8, sequence: u16, ssrc: u32, payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0x80, payload_type];
+        packet.extend_from_slice(&sequence.to_be_bytes());
+        packet.extend_from_slice(&(sequence as u32 * 160).to_be_bytes());
+        packet.extend_from_slice(&ssrc.to_be_bytes());
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    fn l16_payload(samples: &[i16]) -> Vec<u8> {
+        samples.iter().flat_map(|s| s.to_be_bytes()).collect()
+    }
+
+    fn source(format: RtpPayloadFormat) -> (RtpSource, AudioRingBuffer<f32>) {
+        let buffer = AudioRingBuffer::default();
+        let source = RtpSource::bind("127.0.0.1:0", format, buffer.clone()).unwrap();
+        (source, buffer)
+    }
+
+    #[test]
+    fn test_parse_packet() {
+        // Padding, one CSRC and a one-word header extension around a 3-byte payload.
+        let mut datagram = vec![0xB1, 0x80 | 96, 0x01, 0x02];
+        datagram.extend_from_slice(&7u32.to_be_bytes());
+        datagram.extend_from_slice(&9u32.to_be_bytes());
+        datagram.extend_from_slice(&[0; 4]);
+        datagram.extend_from_slice(&[0xBE, 0xDE, 0x00, 0x01, 1, 2, 3, 4]);
+        datagram.extend_from_slice(&[10, 11, 12, 0, 2]);
+        let packet = RtpPacket::parse(&datagram).unwrap();
+        assert!(packet.marker);
+        assert_eq!(packet.payload_type, 96);
+        assert_eq!(packet.sequence, 0x0102);
+        assert_eq!(packet.timestamp, 7);
+        assert_eq!(packet.ssrc, 9);
+        assert_eq!(packet.payload, &[10, 11, 12]);
+
+        assert!(RtpPacket::parse(&datagram[..8]).is_err());
+        datagram[0] = 0x40;
+        assert!(RtpPacket::parse(&datagram).is_err());
+    }
+
+    #[test]
+    fn test_jitter_buffer_reorders() {
+        let mut jitter = JitterBuffer::new(2);
+        assert!(jitter.push(u16::MAX, vec![0]));
+        assert_eq!(jitter.pop(), Some(JitterSlot::Packet(vec![0])));
+        // Wraps around, arriving out of order.
+        assert!(jitter.push(1, vec![2]));
+        assert_eq!(jitter.pop(), None);
+        assert!(jitter.push(0, vec![1]));
+        assert!(!jitter.push(0, vec![1]));
+        assert_eq!(jitter.pop(), Some(JitterSlot::Packet(vec![1])));
+        assert_eq!(jitter.pop(), Some(JitterSlot::Packet(vec![2])));
+        assert_eq!(jitter.pop(), None);
+        // Too late.
+        assert!(!jitter.push(u16::MAX, vec![0]));
+    }
+
+    #[test]
+    fn test_jitter_buffer_gives_up_on_lost_packets() {
+        let mut jitter = JitterBuffer::new(2);
+        jitter.push(10, vec![10]);
+        assert_eq!(jitter.pop(), Some(JitterSlot::Packet(vec![10])));
+        jitter.push(12, vec![12]);
+        jitter.push(13, vec![13]);
+        assert_eq!(jitter.pop(), None);
+        jitter.push(14, vec![14]);
+        assert_eq!(jitter.pop(), Some(JitterSlot::Lost));
+        assert_eq!(jitter.pop(), Some(JitterSlot::Packet(vec![12])));
+        assert_eq!(jitter.pop(), Some(JitterSlot::Packet(vec![13])));
+        assert_eq!(jitter.pop(), Some(JitterSlot::Packet(vec![14])));
+        assert_eq!(jitter.pop(), None);
+
+        jitter.push(16, vec![16]);
+        jitter.push(18, vec![18]);
+        assert_eq!(jitter.pop(), None);
+        assert_eq!(
+            jitter.flush(),
+            [
+                JitterSlot::Lost,
+                JitterSlot::Packet(vec![16]),
+                JitterSlot::Lost,
+                JitterSlot::Packet(vec![18])
+            ]
+        );
+        assert!(jitter.is_empty());
+    }
+
+    #[test]
+    fn test_g711() {
+        assert_eq!(ulaw_to_linear(0xFF), 0);
+        assert_eq!(ulaw_to_linear(0x80), 32124);
+        assert_eq!(ulaw_to_linear(0x00), -32124);
+        assert_eq!(alaw_to_linear(0xD5), 8);
+        assert_eq!(alaw_to_linear(0x55), -8);
+        assert_eq!(alaw_to_linear(0xAA), 32256);
+    }
+
+    #[test]
+    fn test_l16_passthrough() {
+        let (mut source, buffer) = source(L16_16K);
+        let payload = l16_payload(&[16384; 160]);
+        assert_eq!(
+            source
+                .push_datagram(&rtp_packet(96, 0, 1, &payload))
+                .unwrap(),
+            160
+        );
+        assert_eq!(buffer.read(10), vec![0.5; 160]);
+    }
+
+    #[test]
+    fn test_pcmu_is_upsampled_and_gaps_concealed() {
+        let (source, buffer) = source(RtpPayloadFormat::Pcmu);
+        let mut source = source.with_jitter_depth(0);
+        // 20 ms of 8 kHz audio is 320 samples at 16 kHz; the resampler holds back the last
+        // sample to interpolate with the next packet.
+        assert_eq!(
+            source
+                .push_datagram(&rtp_packet(0, 0, 1, &[0xFF; 160]))
+                .unwrap(),
+            318
+        );
+        // Sequence 1 is lost.
+        assert_eq!(
+            source
+                .push_datagram(&rtp_packet(0, 2, 1, &[0xFF; 160]))
+                .unwrap(),
+            320 + 320
+        );
+        assert_eq!(buffer.get_audio_length(), 318 + 320 + 320);
+        // Other payload types are ignored.
+        assert_eq!(
+            source
+                .push_datagram(&rtp_packet(13, 3, 1, &[0xFF; 160]))
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_new_source_resets_stream() {
+        let (source, buffer) = source(L16_16K);
+        let mut source = source.with_payload_type(None);
+        let payload = l16_payload(&[0; 160]);
+        source
+            .push_datagram(&rtp_packet(96, 100, 1, &payload))
+            .unwrap();
+        // A new SSRC starts its own sequence rather than waiting for 101.
+        assert_eq!(
+            source
+                .push_datagram(&rtp_packet(96, 7, 2, &payload))
+                .unwrap(),
+            160
+        );
+        assert_eq!(buffer.get_audio_length(), 320);
+    }
+
+    #[test]
+    fn test_run_receives_over_udp() {
+        let (mut source, buffer) = source(L16_16K);
+        let addr = source.local_addr().unwrap();
+        let run_transcription = Arc::new(AtomicBool::new(true));
+        let run = Arc::clone(&run_transcription);
+        let receiver = std::thread::spawn(move || source.run(run));
+
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let payload = l16_payload(&[0; 160]);
+        for sequence in 0..3 {
+            sender
+                .send_to(&rtp_packet(96, sequence, 1, &payload), addr)
+                .unwrap();
+        }
+        // Give the receiver time to go quiet and flush.
+        std::thread::sleep(Duration::from_millis(300));
+        run_transcription.store(false, Ordering::Release);
+        receiver.join().unwrap().unwrap();
+        assert_eq!(buffer.get_audio_length(), 3 * 160);
+    }
+}
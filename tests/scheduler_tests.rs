@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod scheduler_tests {
+    use std::sync::mpsc::channel;
+    use std::thread;
+    use std::time::Duration;
+
+    use ribble_whisper::transcriber::scheduler::{InferencePriority, InferenceScheduler};
+
+    // Spins until n calls of the given priority are waiting on the scheduler.
+    fn wait_for_waiting(scheduler: &InferenceScheduler, priority: InferencePriority, n: usize) {
+        while scheduler.waiting(priority) < n {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_realtime_runs_first() {
+        let scheduler = InferenceScheduler::new();
+        let running = scheduler.acquire(InferencePriority::Background);
+        assert!(scheduler.is_running());
+        assert!(scheduler.try_acquire(InferencePriority::Realtime).is_none());
+
+        let (sender, receiver) = channel();
+        let mut handles = vec![];
+        // Queue two background calls, then two realtime calls.
+        for (i, priority) in [
+            InferencePriority::Background,
+            InferencePriority::Background,
+            InferencePriority::Realtime,
+            InferencePriority::Realtime,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let queued = scheduler.clone();
+            let sender = sender.clone();
+            let waiting = scheduler.waiting(priority);
+            handles.push(thread::spawn(move || {
+                let _permit = queued.acquire(priority);
+                sender.send(i).unwrap();
+            }));
+            wait_for_waiting(&scheduler, priority, waiting + 1);
+        }
+
+        drop(running);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let order: Vec<usize> = receiver.try_iter().collect();
+        assert_eq!(
+            order,
+            vec![2, 3, 0, 1],
+            "Realtime calls should run first, each priority in arrival order."
+        );
+        assert!(!scheduler.is_running());
+        assert!(scheduler
+            .try_acquire(InferencePriority::Background)
+            .is_some());
+    }
+
+    #[test]
+    fn test_yield_turn() {
+        let scheduler = InferenceScheduler::new();
+        let mut background = scheduler.acquire(InferencePriority::Background);
+        assert!(!background.yield_turn(), "Nothing is waiting to run.");
+
+        let realtime = {
+            let scheduler = scheduler.clone();
+            thread::spawn(move || {
+                let permit = scheduler.acquire(InferencePriority::Realtime);
+                permit.priority()
+            })
+        };
+        wait_for_waiting(&scheduler, InferencePriority::Realtime, 1);
+        assert!(background.yield_turn());
+        assert_eq!(realtime.join().unwrap(), InferencePriority::Realtime);
+        assert!(scheduler.is_running(), "The background call should resume.");
+        assert_eq!(scheduler.waiting(InferencePriority::Background), 0);
+    }
+}
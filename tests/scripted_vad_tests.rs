@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod scripted_vad_tests {
+    use std::sync::atomic::AtomicBool;
+
+    use ribble_whisper::testing::vad::{AlwaysVoiced, NeverVoiced, ScriptedVad};
+    use ribble_whisper::transcriber::vad::{Resettable, VAD};
+    use ribble_whisper::utils::errors::RibbleWhisperError;
+
+    #[test]
+    fn test_scripted_vad() {
+        let samples = [0.5f32; 16];
+        let mut vad = ScriptedVad::new([true, false, true]);
+        let decisions: Vec<bool> = (0..5).map(|_| vad.voice_detected(&samples)).collect();
+        assert_eq!(decisions, [true, false, true, false, false]);
+        assert!(vad.exhausted());
+        assert_eq!(vad.calls(), 5);
+
+        vad.reset_session();
+        assert_eq!(vad.calls(), 0);
+        assert_eq!(vad.extract_voiced_frames(&samples).as_ref(), &samples);
+        assert!(vad.extract_voiced_frames(&samples).is_empty());
+
+        let mut vad = ScriptedVad::new([false]).with_fallback(true);
+        assert!(!vad.voice_detected(&samples));
+        assert!(vad.voice_detected(&samples));
+        assert!(vad.voice_detected(&[]));
+    }
+
+    #[test]
+    fn test_fixed_vads() {
+        let samples = [0.5f32; 16];
+        let mut always = AlwaysVoiced;
+        assert!(always.voice_detected(&samples));
+        assert!(always.voice_detected(&[]));
+        assert_eq!(always.extract_voiced_frames(&samples).as_ref(), &samples);
+
+        let mut never = NeverVoiced;
+        assert!(!never.voice_detected(&samples));
+        assert!(never.extract_voiced_frames(&samples).is_empty());
+    }
+
+    #[test]
+    fn test_default_extraction_abort() {
+        let samples = [0.5f32; 16];
+        let mut always = AlwaysVoiced;
+        let voiced = always
+            .extract_voiced_frames_with_abort(&samples, &AtomicBool::new(true))
+            .unwrap();
+        assert_eq!(voiced.as_ref(), &samples);
+        assert!(matches!(
+            always.extract_voiced_frames_with_abort(&samples, &AtomicBool::new(false)),
+            Err(RibbleWhisperError::Cancelled)
+        ));
+    }
+}
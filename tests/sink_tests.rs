@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod sink_tests {
+    use ribble_whisper::utils::get_channel;
+    use ribble_whisper::utils::sink::OutputSink;
+
+    #[test]
+    fn test_channel_output_sink() {
+        let (sender, receiver) = get_channel::<usize>(1);
+        OutputSink::try_send(&sender, 1).expect("Failed to send to an empty channel.");
+        let full = OutputSink::try_send(&sender, 2).expect_err("Sent to a full channel.");
+        assert!(full.is_full(), "Incorrect error: {full:?}");
+        assert_eq!(
+            full.into_inner(),
+            2,
+            "Failed to recover the unsent message."
+        );
+        assert_eq!(receiver.recv().unwrap(), 1, "Incorrect message received.");
+
+        OutputSink::close(sender);
+        assert!(receiver.recv().is_err(), "Channel not closed.");
+    }
+
+    #[test]
+    fn test_disconnected_output_sink() {
+        let (sender, receiver) = std::sync::mpsc::channel::<usize>();
+        drop(receiver);
+        let disconnected =
+            OutputSink::send(&sender, 1).expect_err("Sent to a disconnected channel.");
+        assert!(
+            disconnected.is_disconnected(),
+            "Incorrect error: {disconnected:?}"
+        );
+    }
+}
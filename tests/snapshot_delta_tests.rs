@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod snapshot_delta_tests {
+    use std::sync::Arc;
+
+    use ribble_whisper::transcriber::{
+        ConfirmedText, SnapshotAssembler, SnapshotDelta, TranscriptionSnapshot,
+        WhisperControlPhrase, WhisperOutput,
+    };
+
+    fn snapshot(confirmed: &str, segments: &[&str]) -> TranscriptionSnapshot {
+        let segments: Vec<Arc<str>> = segments.iter().map(|segment| Arc::from(*segment)).collect();
+        TranscriptionSnapshot::new(Arc::from(confirmed), Arc::from(segments))
+    }
+
+    fn assert_same(a: &TranscriptionSnapshot, b: &TranscriptionSnapshot) {
+        assert_eq!(a.confirmed(), b.confirmed());
+        assert_eq!(a.string_segments(), b.string_segments());
+    }
+
+    #[test]
+    fn test_delta_between() {
+        let previous = snapshot(" Hello.", &[" How", " are"]);
+        let next = snapshot(" Hello. How", &[" are", " you?"]);
+        let delta = SnapshotDelta::between(&previous, &next)
+            .expect("Appended confirmed text should produce a delta.");
+        assert_eq!(delta.confirmed_append(), " How");
+        assert_eq!(
+            delta.replaced_segments(),
+            &[(0, Arc::from(" are")), (1, Arc::from(" you?"))]
+        );
+        assert_eq!(delta.num_segments(), 2);
+        assert_same(&delta.apply(&previous), &next);
+
+        // Segments removed from the end of the working set.
+        let shorter = snapshot(" Hello. How", &[" are"]);
+        let delta = SnapshotDelta::between(&next, &shorter).unwrap();
+        assert!(delta.confirmed_append().is_empty());
+        assert!(delta.replaced_segments().is_empty());
+        assert_same(&delta.apply(&next), &shorter);
+
+        // Revised confirmed text cannot be sent as a delta.
+        let revised = snapshot(" Hello, how", &[]);
+        assert!(SnapshotDelta::between(&next, &revised).is_none());
+    }
+
+    #[test]
+    fn test_assembler() {
+        let first = snapshot(" One", &[" two"]);
+        let second = snapshot(" One two", &[" three"]);
+        let third = snapshot(" One two three", &[]);
+
+        let mut assembler = SnapshotAssembler::new();
+        assert!(assembler.current().is_none());
+        assert!(assembler
+            .push(&WhisperOutput::ControlPhrase(
+                WhisperControlPhrase::StartSpeaking
+            ))
+            .is_none());
+
+        let assembled = assembler
+            .push(&WhisperOutput::TranscriptionSnapshot(Arc::new(
+                first.clone(),
+            )))
+            .unwrap();
+        assert_same(&assembled, &first);
+
+        for (previous, next) in [(&first, &second), (&second, &third)] {
+            let delta = SnapshotDelta::between(previous, next).unwrap();
+            let assembled = assembler
+                .push(&WhisperOutput::SnapshotDelta(Arc::new(delta)))
+                .unwrap();
+            assert_same(&assembled, next);
+        }
+        assert_same(assembler.current().unwrap(), &third);
+
+        // A delta without a prior snapshot applies to an empty snapshot.
+        let delta = SnapshotDelta::between(&TranscriptionSnapshot::default(), &first).unwrap();
+        let assembled = SnapshotAssembler::new()
+            .push(&WhisperOutput::SnapshotDelta(Arc::new(delta)))
+            .unwrap();
+        assert_same(&assembled, &first);
+    }
+
+    #[test]
+    fn test_chunked_confirmed_text() {
+        let chunks: Vec<Arc<str>> = vec![
+            Arc::from("One two"),
+            Arc::from(" three"),
+            Arc::from(" four"),
+        ];
+        let confirmed = ConfirmedText::from_chunks(Arc::from(chunks.clone()));
+        assert_eq!(confirmed.len(), 18);
+        assert_eq!(confirmed.as_str(), "One two three four");
+        assert_eq!(confirmed.to_string(), "One two three four");
+
+        let from = |offset| {
+            confirmed
+                .chunks_from(offset)
+                .map(|chunks| chunks.collect::<String>())
+        };
+        assert_eq!(from(0).as_deref(), Some("One two three four"));
+        assert_eq!(from(9).as_deref(), Some("hree four"));
+        assert_eq!(from(13).as_deref(), Some(" four"));
+        assert_eq!(from(18).as_deref(), Some(""));
+        assert!(from(19).is_none());
+
+        // A later snapshot shares the earlier chunks, with new text in the last one.
+        let previous = TranscriptionSnapshot::from_confirmed_text(confirmed.clone(), Arc::from([]));
+        let mut extended = chunks[..2].to_vec();
+        extended.push(Arc::from(" four five"));
+        let next = TranscriptionSnapshot::from_confirmed_text(
+            ConfirmedText::from_chunks(Arc::from(extended)),
+            Arc::from([]),
+        );
+        let delta = SnapshotDelta::between(&previous, &next).unwrap();
+        assert_eq!(delta.confirmed_append(), " five");
+        let applied = delta.apply(&previous);
+        assert_same(&applied, &next);
+        assert_eq!(
+            applied.confirmed_text().chunks().len(),
+            3,
+            "Small appends should not add chunks."
+        );
+    }
+}
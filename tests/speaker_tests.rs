@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod speaker_tests {
+    use std::sync::Arc;
+
+    use ribble_whisper::transcriber::speaker::{
+        SnapshotSpeakers, SpeakerId, SpeakerRegistry, SpeakerTurn,
+    };
+    use ribble_whisper::transcriber::{SnapshotDelta, TranscriptionSnapshot};
+
+    fn diarized_snapshot(
+        confirmed: &str,
+        turns: &[(usize, u32)],
+        segments: &[(&str, Option<u32>)],
+    ) -> TranscriptionSnapshot {
+        let turns: Vec<SpeakerTurn> = turns
+            .iter()
+            .map(|(offset, speaker)| SpeakerTurn {
+                offset: *offset,
+                speaker: SpeakerId(*speaker),
+            })
+            .collect();
+        let texts: Vec<Arc<str>> = segments.iter().map(|(text, _)| Arc::from(*text)).collect();
+        let speakers: Vec<Option<SpeakerId>> = segments
+            .iter()
+            .map(|(_, speaker)| speaker.map(SpeakerId))
+            .collect();
+        TranscriptionSnapshot::new(Arc::from(confirmed), Arc::from(texts)).with_speakers(Some(
+            Arc::new(SnapshotSpeakers::new(Arc::from(turns), Arc::from(speakers))),
+        ))
+    }
+
+    #[test]
+    fn test_confirmed_spans() {
+        let snapshot = diarized_snapshot(
+            "Hi there. Hello! How are you?",
+            &[(0, 0), (10, 1)],
+            &[(" Fine", Some(0))],
+        );
+        let spans: Vec<_> = snapshot.confirmed_spans().collect();
+        assert_eq!(
+            spans,
+            [
+                (Some(SpeakerId(0)), "Hi there. "),
+                (Some(SpeakerId(1)), "Hello! How are you?"),
+            ]
+        );
+        let speakers = snapshot.speakers().unwrap();
+        assert_eq!(speakers.segment_speaker(0), Some(SpeakerId(0)));
+        assert_eq!(speakers.segment_speaker(1), None);
+        assert_eq!(speakers.last_confirmed_speaker(), Some(SpeakerId(1)));
+
+        // Without diarization, the confirmed text is a single span without a speaker.
+        let plain = TranscriptionSnapshot::new(Arc::from("Hi there."), Arc::from([]));
+        assert!(plain.speakers().is_none());
+        assert_eq!(
+            plain.confirmed_spans().collect::<Vec<_>>(),
+            [(None, "Hi there.")]
+        );
+
+        // Text before the first turn has no known speaker.
+        let late = diarized_snapshot("Um. Hello!", &[(4, 2)], &[]);
+        assert_eq!(
+            late.confirmed_spans().collect::<Vec<_>>(),
+            [(None, "Um. "), (Some(SpeakerId(2)), "Hello!")]
+        );
+    }
+
+    #[test]
+    fn test_delta_carries_speakers() {
+        let previous = diarized_snapshot("Hi there.", &[(0, 0)], &[(" Hello", Some(1))]);
+        let next = diarized_snapshot(
+            "Hi there. Hello!",
+            &[(0, 0), (10, 1)],
+            &[(" How are", Some(1))],
+        );
+        let delta = SnapshotDelta::between(&previous, &next).unwrap();
+        assert_eq!(delta.speakers(), next.speakers());
+        let applied = delta.apply(&previous);
+        assert_eq!(applied.speakers(), next.speakers());
+        assert_eq!(
+            applied.confirmed_spans().collect::<Vec<_>>(),
+            next.confirmed_spans().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_speaker_registry() {
+        let mut registry = SpeakerRegistry::new();
+        for speaker in [0, 1, 2, 1] {
+            registry.observe(SpeakerId(speaker));
+        }
+        assert_eq!(registry.label(SpeakerId(0)), "Speaker 1");
+        assert_eq!(
+            registry.speakers(),
+            [SpeakerId(0), SpeakerId(1), SpeakerId(2)]
+        );
+
+        registry.rename(SpeakerId(0), "Alice");
+        registry.rename(SpeakerId(2), "Bob");
+        assert_eq!(registry.label(SpeakerId(0)), "Alice");
+        assert_eq!(registry.name(SpeakerId(1)), None);
+
+        // Speaker 3 turned out to be Alice again.
+        registry.merge(SpeakerId(2), SpeakerId(0));
+        assert_eq!(registry.resolve(SpeakerId(2)), SpeakerId(0));
+        assert_eq!(registry.label(SpeakerId(2)), "Alice");
+        assert_eq!(registry.speakers(), [SpeakerId(0), SpeakerId(1)]);
+
+        // Merged names are kept when the target has none, and chains stay resolved.
+        registry.merge(SpeakerId(0), SpeakerId(1));
+        assert_eq!(registry.label(SpeakerId(1)), "Alice");
+        assert_eq!(registry.resolve(SpeakerId(2)), SpeakerId(1));
+        assert_eq!(registry.speakers(), [SpeakerId(1)]);
+
+        // Merging into itself changes nothing.
+        registry.merge(SpeakerId(2), SpeakerId(1));
+        assert_eq!(registry.speakers(), [SpeakerId(1)]);
+    }
+}
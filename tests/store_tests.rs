@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod store_tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ribble_whisper::export::jsonl::SegmentRecord;
+    use ribble_whisper::export::store::TranscriptStore;
+    use ribble_whisper::transcriber::{TranscriptionSnapshot, WhisperOutput};
+
+    fn store_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn snapshot(confirmed: &str, working: &[&str]) -> TranscriptionSnapshot {
+        let working: Vec<Arc<str>> = working.iter().map(|s| Arc::from(*s)).collect();
+        TranscriptionSnapshot::new(Arc::from(confirmed), Arc::from(working))
+    }
+
+    #[test]
+    fn test_recover_session() {
+        let dir = store_dir("ribble_whisper_store_recover");
+        {
+            let mut store = TranscriptStore::open(&dir, "lecture-1").unwrap();
+            store
+                .save_output(&WhisperOutput::TranscriptionSnapshot(Arc::new(snapshot(
+                    "Welcome to the lecture.",
+                    &["Today we"],
+                ))))
+                .unwrap();
+            store
+                .save_record(SegmentRecord::new("Welcome to the lecture.", 0, 2000))
+                .unwrap();
+            // Dropped without clearing, as if the application crashed.
+        }
+
+        assert_eq!(
+            TranscriptStore::latest_session(&dir).unwrap().as_deref(),
+            Some("lecture-1")
+        );
+        let recovered = TranscriptStore::load(&dir, "lecture-1").unwrap();
+        assert_eq!(
+            recovered.text(),
+            "Welcome to the lecture. Today we",
+            "The last snapshot should be recovered."
+        );
+        assert_eq!(recovered.segments().len(), 1);
+        assert_eq!(
+            recovered.segments()[0].session_id.as_deref(),
+            Some("lecture-1")
+        );
+
+        let store = TranscriptStore::open(&dir, "lecture-1").unwrap();
+        store.clear().unwrap();
+        assert!(
+            TranscriptStore::load(&dir, "lecture-1").unwrap().is_empty(),
+            "Clearing should delete the session."
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_interval() {
+        let dir = store_dir("ribble_whisper_store_interval");
+        let mut store = TranscriptStore::open(&dir, "session")
+            .unwrap()
+            .with_snapshot_interval(Duration::from_secs(60));
+        assert!(store.save_snapshot(&snapshot("first", &[])).unwrap());
+        assert!(
+            !store.save_snapshot(&snapshot("second", &[])).unwrap(),
+            "Snapshots within the interval should be skipped."
+        );
+        store.write_snapshot(&snapshot("final", &[])).unwrap();
+        assert_eq!(store.recover().unwrap().text(), "final");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_session_id() {
+        let dir = store_dir("ribble_whisper_store_invalid");
+        assert!(
+            TranscriptStore::open(&dir, "../escape").is_err(),
+            "Session ids should not be able to escape the store directory."
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
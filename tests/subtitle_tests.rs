@@ -0,0 +1,292 @@
+#[cfg(test)]
+mod subtitle_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use ribble_whisper::export::confidence::{ConfidenceStyle, ConfidenceStyles};
+    use ribble_whisper::export::subtitles::align::CueAligner;
+    use ribble_whisper::export::subtitles::ass::{AssAlignment, AssColour, AssExporter, AssStyle};
+    use ribble_whisper::export::subtitles::layout::CaptionLayout;
+    use ribble_whisper::export::subtitles::srt::{parse_srt, SrtExporter};
+    use ribble_whisper::export::subtitles::ttml::{TtmlExporter, TtmlRegion};
+    use ribble_whisper::export::subtitles::vtt::{parse_vtt, VttExporter};
+    use ribble_whisper::export::subtitles::{Cue, SubtitleExporter};
+    use ribble_whisper::transcriber::RibbleWhisperSegment;
+    use ribble_whisper::utils::errors::RibbleWhisperError;
+
+    fn cues() -> Vec<Cue> {
+        vec![
+            Cue::new("Hello there.", 1000, 2500),
+            Cue::new("First line\nSecond & last", 3_723_004, 3_725_000),
+        ]
+    }
+
+    #[test]
+    fn test_srt_and_vtt() {
+        assert_eq!(
+            SrtExporter.export(&cues()),
+            "1\n00:00:01,000 --> 00:00:02,500\nHello there.\n\n\
+             2\n01:02:03,004 --> 01:02:05,000\nFirst line\nSecond & last\n\n"
+        );
+        assert_eq!(
+            VttExporter.export(&cues()),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.500\nHello there.\n\n\
+             01:02:03.004 --> 01:02:05.000\nFirst line\nSecond &amp; last\n\n"
+        );
+    }
+
+    #[test]
+    fn test_ass() {
+        let style = AssStyle::new("Broadcast")
+            .with_font("Helvetica", 60)
+            .with_primary_colour(AssColour::YELLOW)
+            .with_opaque_box(true)
+            .with_alignment(AssAlignment::TopCenter);
+        let exporter = AssExporter::new()
+            .with_resolution(1280, 720)
+            .with_style(style)
+            .with_position(640, 40);
+        let ass = exporter.export(&[Cue::new("A {brace}\nNext", 1234, 5678)]);
+
+        assert!(ass.starts_with("[Script Info]\n"));
+        assert!(ass.contains("PlayResX: 1280\nPlayResY: 720\n"));
+        assert!(
+            ass.contains("Style: Broadcast,Helvetica,60,&H0000FFFF,"),
+            "Colours should be written as &HAABBGGRR: {ass}"
+        );
+        assert!(ass.contains(",3,2,1,8,80,80,60,1\n"));
+        assert!(
+            ass.contains(
+                "Dialogue: 0,0:00:01.23,0:00:05.67,Broadcast,,0,0,0,,{\\pos(640,40)}A \\{brace\\}\\NNext\n"
+            ),
+            "{ass}"
+        );
+    }
+
+    #[test]
+    fn test_ttml() {
+        let ttml = TtmlExporter::new()
+            .with_language("fr")
+            .with_region(TtmlRegion::Top)
+            .export(&cues());
+        assert!(ttml.starts_with("<?xml"));
+        assert!(ttml.contains("xml:lang=\"fr\""));
+        assert!(ttml.contains("tts:displayAlign=\"before\""));
+        assert!(ttml.contains(
+            "<p begin=\"01:02:03.004\" end=\"01:02:05.000\"><span style=\"caption\">First line<br/>Second &amp; last</span></p>"
+        ));
+        assert!(ttml.trim_end().ends_with("</tt>"));
+    }
+
+    #[test]
+    fn test_layout_wraps_and_splits() {
+        let layout = CaptionLayout::new().with_max_line_length(20);
+        let segment = RibbleWhisperSegment {
+            text: Arc::from(
+                " The quick brown fox jumps over the lazy dog and keeps running far away.",
+            ),
+            start_time: 0,
+            end_time: 600,
+            confidence: None,
+            word_confidences: None,
+            speaker: None,
+        };
+        let cues = layout.layout_segments(&[segment]);
+
+        assert!(
+            cues.len() > 1,
+            "Text longer than two lines should be split."
+        );
+        for cue in cues.iter() {
+            assert!(cue.lines().len() <= 2);
+            assert!(cue.lines().iter().all(|line| line.chars().count() <= 20));
+        }
+        let rejoined = cues.iter().map(Cue::text).collect::<Vec<_>>().join(" ");
+        assert_eq!(
+            rejoined,
+            "The quick brown fox jumps over the lazy dog and keeps running far away."
+        );
+        assert_eq!(
+            cues[0].lines(),
+            ["The quick brown fox", "jumps over the lazy"],
+            "Lines should be balanced within a cue."
+        );
+        for pair in cues.windows(2) {
+            assert!(
+                pair[0].end_ms() <= pair[1].start_ms(),
+                "Cues should not overlap."
+            );
+        }
+    }
+
+    #[test]
+    fn test_layout_timing() {
+        let layout = CaptionLayout::new()
+            .with_max_cps(10.0)
+            .with_min_duration(Duration::from_millis(1500))
+            .with_min_gap(Duration::from_millis(100));
+        let cues = layout.layout(&[
+            // 20 characters: 2 seconds at 10 cps.
+            Cue::new("Twenty characters!!!", 0, 500),
+            Cue::new("Hi.", 5000, 5200),
+            Cue::new("Soon after.", 6500, 7000),
+        ]);
+
+        assert_eq!(cues.len(), 3, "Pauses should end cues.");
+        assert_eq!(
+            cues[0].end_ms(),
+            2000,
+            "Cues should be extended to meet the CPS limit."
+        );
+        assert_eq!(
+            cues[1].end_ms(),
+            6400,
+            "Cues should not be extended into the next cue."
+        );
+        assert_eq!(
+            cues[2].end_ms(),
+            8000,
+            "Cues should meet the minimum duration."
+        );
+        assert!(cues[2].chars_per_second() <= 10.0);
+    }
+
+    #[test]
+    fn test_parse_srt_and_vtt() {
+        assert_eq!(parse_srt(&SrtExporter.export(&cues())).unwrap(), cues());
+        assert_eq!(parse_vtt(&VttExporter.export(&cues())).unwrap(), cues());
+
+        let srt = "\u{feff}1\r\n00:00:01,000 --> 00:00:02,500\r\n<i>Hello</i> there.\r\n\r\n";
+        assert_eq!(
+            parse_srt(srt).unwrap(),
+            vec![Cue::new("Hello there.", 1000, 2500)],
+            "Tags, byte order marks and CRLF line endings should be handled."
+        );
+
+        let vtt =
+            "WEBVTT - Edited\n\nNOTE reviewed\n\nintro\n00:01.000 --> 00:02.500 align:start\n\
+                   <v Speaker>Hello &amp; welcome.\n";
+        assert_eq!(
+            parse_vtt(vtt).unwrap(),
+            vec![Cue::new("Hello & welcome.", 1000, 2500)],
+            "Identifiers, settings, notes and voice tags should be skipped."
+        );
+
+        let err = parse_srt("1\n00:00:01,000 -> 00:00:02,000\nHi\n").unwrap_err();
+        assert!(matches!(
+            err,
+            RibbleWhisperError::SubtitleParseError { line: 2, .. }
+        ));
+        assert!(matches!(
+            parse_vtt("00:01.000 --> 00:02.000\nHi\n"),
+            Err(RibbleWhisperError::SubtitleParseError { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_align_edited_cues() {
+        let segment = |text: &str, start_time: i64, end_time: i64| RibbleWhisperSegment {
+            text: Arc::from(text),
+            start_time,
+            end_time,
+            confidence: None,
+            word_confidences: None,
+            speaker: None,
+        };
+        // One segment per word, in centiseconds.
+        let reference = [
+            segment(" the", 100, 120),
+            segment(" whether", 120, 160),
+            segment(" is", 160, 180),
+            segment(" nice", 180, 220),
+            segment(" today", 300, 350),
+            segment(" um", 360, 380),
+            segment(" really", 400, 450),
+        ];
+        let edited = [
+            Cue::new("The weather is nice.", 0, 1000),
+            Cue::new("Today, really!", 1000, 2000),
+            Cue::new("♪ ♪", 5000, 6000),
+        ];
+
+        let aligned = CueAligner::new().align(&edited, &reference);
+        assert_eq!(
+            aligned[0],
+            Cue::new("The weather is nice.", 1000, 2200),
+            "Corrected words should take the timings of the words they replaced."
+        );
+        assert_eq!(
+            aligned[1],
+            Cue::new("Today, really!", 3000, 4500),
+            "Removed filler words should be skipped."
+        );
+        assert_eq!(
+            aligned[2],
+            Cue::new("♪ ♪", 5000, 6000),
+            "Cues with no aligned words should keep their timings."
+        );
+    }
+
+    #[test]
+    fn test_confidence_styles() {
+        let styles = ConfidenceStyles::new()
+            .with_band(0.8, ConfidenceStyle::new().with_class("unsure"))
+            .with_band(
+                0.5,
+                ConfidenceStyle::new()
+                    .with_italic(true)
+                    .with_colour(0xcc, 0x33, 0x33)
+                    .with_class("shaky"),
+            );
+        assert_eq!(styles.bands()[0].0, 0.5, "Bands should be sorted.");
+        assert_eq!(styles.style_for(Some(0.3)).unwrap().class(), Some("shaky"));
+        assert_eq!(styles.style_for(Some(0.6)).unwrap().class(), Some("unsure"));
+        assert!(styles.style_for(Some(0.9)).is_none());
+        assert!(styles.style_for(None).is_none());
+
+        let cues = [
+            Cue::new("Shaky & unsure", 0, 1000).with_confidence(Some(0.3)),
+            Cue::new("Confident", 1000, 2000).with_confidence(Some(0.95)),
+        ];
+        assert!(SrtExporter.export_with_styles(&cues, &styles).contains(
+            "<font color=\"#cc3333\"><i>Shaky & unsure</i></font>\n\n2\n00:00:01,000 --> 00:00:02,000\nConfident\n"
+        ));
+        let vtt = VttExporter.export_with_styles(&cues, &styles);
+        assert!(
+            vtt.starts_with("WEBVTT\n\nSTYLE\n::cue(.shaky) { color: #cc3333; }\n\n"),
+            "{vtt}"
+        );
+        assert!(vtt.contains("<c.shaky><i>Shaky &amp; unsure</i></c>\n"));
+        assert_eq!(
+            parse_vtt(&vtt).unwrap(),
+            vec![
+                Cue::new("Shaky & unsure", 0, 1000),
+                Cue::new("Confident", 1000, 2000)
+            ],
+            "Styled exports should still parse."
+        );
+        assert!(TtmlExporter::new().export_with_styles(&cues, &styles).contains(
+            "<span style=\"caption\" tts:fontStyle=\"italic\" tts:color=\"#cc3333\">Shaky &amp; unsure</span>"
+        ));
+        assert!(
+            AssExporter::new()
+                .export_with_styles(&cues, &styles)
+                .contains(",,{\\i1\\c&H3333CC&}Shaky & unsure\n")
+        );
+        assert_eq!(
+            SrtExporter.export(&cues),
+            SrtExporter.export_with_styles(&cues, &ConfidenceStyles::new())
+        );
+
+        let laid_out = CaptionLayout::new().layout(&[
+            Cue::new("One", 0, 500).with_confidence(Some(0.4)),
+            Cue::new("two", 500, 1000).with_confidence(Some(0.8)),
+        ]);
+        assert_eq!(laid_out.len(), 1);
+        let confidence = laid_out[0].confidence().unwrap();
+        assert!(
+            (confidence - 0.6).abs() < 1e-6,
+            "Laid out cues should average their words' confidence."
+        );
+    }
+}
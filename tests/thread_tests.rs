@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod thread_tests {
+    use ribble_whisper::utils::thread::spawn_with_configs;
+    use ribble_whisper::whisper::configs::{QosClass, ThreadConfigs, ThreadPriority};
+
+    #[test]
+    fn test_thread_configs() {
+        let configs = ThreadConfigs::new()
+            .with_priority(ThreadPriority::Low)
+            .with_qos_class(QosClass::Utility)
+            .with_cpu_affinity(&[3, 0, 64, 3]);
+        assert_eq!(configs.priority(), ThreadPriority::Low);
+        assert_eq!(configs.qos_class(), Some(QosClass::Utility));
+        assert_eq!(
+            configs.cpu_affinity(),
+            vec![0, 3],
+            "Affinity should be sorted, deduplicated, and ignore cores past 63."
+        );
+        assert!(ThreadConfigs::default().cpu_affinity().is_empty());
+    }
+
+    #[test]
+    fn test_spawn_with_configs() {
+        // High priority usually needs elevated permissions; the thread should still run if it
+        // cannot be applied.
+        for priority in [ThreadPriority::Low, ThreadPriority::High] {
+            let configs = ThreadConfigs::new()
+                .with_priority(priority)
+                .with_cpu_affinity(&[0]);
+            let handle = spawn_with_configs("configured", configs, || {
+                std::thread::current().name().map(str::to_string)
+            })
+            .expect("The thread should spawn.");
+            assert_eq!(handle.join().unwrap().as_deref(), Some("configured"));
+        }
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod timestamps_tests {
+    use std::sync::Arc;
+
+    use ribble_whisper::transcriber::timestamps::{
+        TimestampedSegment, TimestampedSnapshot, TokenTiming, WordTiming, words_from_tokens,
+    };
+    use ribble_whisper::transcriber::{RibbleWhisperSegment, SnapshotAssembler, WhisperOutput};
+    use ribble_whisper::whisper::configs::WhisperRealtimeConfigs;
+
+    fn token(bytes: &[u8], start_time: i64, end_time: i64, probability: f32) -> TokenTiming<'_> {
+        TokenTiming {
+            bytes,
+            start_time,
+            end_time,
+            probability,
+        }
+    }
+
+    fn word(text: &str, start_time: i64, end_time: i64, probability: f32) -> WordTiming {
+        WordTiming {
+            text: Arc::from(text),
+            start_time,
+            end_time,
+            probability,
+        }
+    }
+
+    fn segment(text: &str, start_time: i64, end_time: i64) -> RibbleWhisperSegment {
+        RibbleWhisperSegment {
+            text: Arc::from(text),
+            start_time,
+            end_time,
+            confidence: None,
+            word_confidences: None,
+            speaker: None,
+        }
+    }
+
+    #[test]
+    fn test_words_from_tokens() {
+        let words = words_from_tokens([
+            token(b" Hel", 0, 20, 0.5),
+            token(b"lo", 20, 40, 1.0),
+            token(b",", 40, 42, 1.0),
+            token(b" world", 50, 90, 0.75),
+            token(b".", 90, 95, 0.25),
+        ]);
+        assert_eq!(
+            words,
+            vec![
+                word("Hello,", 0, 42, 2.5 / 3.0),
+                word("world.", 50, 95, 0.5)
+            ]
+        );
+
+        // A first token without leading whitespace still starts a word.
+        let words = words_from_tokens([token(b"Yes", 0, 30, 1.0), token(b" no", 30, 60, 1.0)]);
+        assert_eq!(
+            words,
+            vec![word("Yes", 0, 30, 1.0), word("no", 30, 60, 1.0)]
+        );
+
+        // Whitespace-only tokens don't produce words.
+        let words = words_from_tokens([token(b" ", 0, 10, 1.0), token(b" ok", 10, 20, 1.0)]);
+        assert_eq!(words, vec![word("ok", 10, 20, 1.0)]);
+        assert!(words_from_tokens([]).is_empty());
+    }
+
+    #[test]
+    fn test_words_from_split_characters() {
+        // "é" split across two tokens.
+        let words = words_from_tokens([
+            token(b" caf", 0, 20, 1.0),
+            token(&[0xC3], 20, 25, 1.0),
+            token(&[0xA9], 25, 30, 1.0),
+        ]);
+        assert_eq!(words, vec![word("café", 0, 30, 1.0)]);
+    }
+
+    #[test]
+    fn test_timestamped_snapshot() {
+        let words = words_from_tokens([token(b" Hi", 10, 30, 1.0), token(b" there", 30, 60, 1.0)]);
+        let timestamped = TimestampedSegment {
+            segment: segment(" Hi there", 10, 60),
+            words,
+        }
+        .offset(500);
+        assert_eq!(timestamped.segment.start_timestamp(), 510);
+        assert_eq!(timestamped.segment.end_timestamp(), 560);
+        assert_eq!(
+            timestamped.words,
+            vec![word("Hi", 510, 530, 1.0), word("there", 530, 560, 1.0)]
+        );
+
+        let snapshot = TimestampedSnapshot {
+            window_start: 500,
+            segments: vec![
+                timestamped,
+                TimestampedSegment {
+                    segment: segment(" Bye.", 600, 650),
+                    words: vec![word("Bye.", 600, 650, 1.0)],
+                },
+            ],
+        };
+        assert_eq!(snapshot.words().count(), 3);
+        assert_eq!(snapshot.end_time(), 650);
+        assert_eq!(TimestampedSnapshot::default().end_time(), 0);
+
+        let output = WhisperOutput::TimestampedSnapshot(Arc::new(snapshot));
+        // Timestamped snapshots don't change the transcription snapshot.
+        assert!(SnapshotAssembler::new().push(&output).is_none());
+        assert_eq!(output.into_inner(), "Hi there Bye.");
+    }
+
+    #[test]
+    fn test_timestamped_snapshots_config() {
+        let configs = WhisperRealtimeConfigs::default();
+        assert!(!configs.timestamped_snapshots());
+        assert!(
+            configs
+                .with_timestamped_snapshots(true)
+                .timestamped_snapshots()
+        );
+    }
+}
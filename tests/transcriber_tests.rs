@@ -18,6 +18,7 @@ mod transcriber_tests {
     };
     use ribble_whisper::utils;
     use ribble_whisper::utils::callback::{Nop, RibbleWhisperCallback};
+    use ribble_whisper::utils::errors::RibbleWhisperError;
     use ribble_whisper::whisper::configs::{WhisperConfigs, WhisperRealtimeConfigs};
     use ribble_whisper::whisper::model::{DefaultModelBank, DefaultModelType};
 
@@ -107,7 +108,7 @@ mod transcriber_tests {
                 while let Ok(out) = text_receiver.recv() {
                     let message = match out {
                         WhisperOutput::TranscriptionSnapshot(message) => message.to_string(),
-                        WhisperOutput::ControlPhrase(_) => "".to_string(),
+                        _ => "".to_string(),
                     };
                     let current_len = message.len();
                     if current_len > offline_output_length - epsilon {
@@ -249,4 +250,104 @@ mod transcriber_tests {
             expected_offline_transcription
         )
     }
+
+    // Cancelling returns Err(Cancelled) whether the VAD pass or inference was running, and keeps
+    // whatever inference transcribed as the partial result.
+    #[test]
+    fn test_offline_cancellation() {
+        let (model_bank, model_id) = prep_model_bank(DefaultModelType::Medium);
+        let model_bank = Arc::new(model_bank);
+        let configs = WhisperConfigs::default()
+            .with_n_threads(8)
+            .with_model_id(Some(model_id))
+            .with_flash_attention(true);
+        let audio = WhisperAudioSample::F32(Arc::clone(&AUDIO_SAMPLE));
+
+        // Cancelled during the VAD pass, (before inference starts).
+        let vad = Silero::try_new_whisper_offline_default()
+            .expect("Silero expected to build with whisper-defaults.");
+        let transcriber = OfflineTranscriberBuilder::<Silero, DefaultModelBank>::new()
+            .with_configs(configs)
+            .with_audio(audio.clone())
+            .with_channel_configurations(AudioChannelConfiguration::Mono)
+            .with_shared_model_retriever(Arc::clone(&model_bank))
+            .with_voice_activity_detector(vad)
+            .build()
+            .expect("Offline transcriber expected to build without issues.");
+        let cancelled = transcriber.process_segments(Arc::new(AtomicBool::new(false)));
+        assert!(
+            matches!(cancelled, Err(RibbleWhisperError::Cancelled)),
+            "Expected the VAD pass to be cancelled."
+        );
+        assert!(transcriber.take_partial_segments().is_empty());
+
+        // Cancelled during inference, once the first segment is decoded.
+        let transcriber = OfflineTranscriberBuilder::<Silero, DefaultModelBank>::new()
+            .with_configs(configs)
+            .with_audio(audio)
+            .with_channel_configurations(AudioChannelConfiguration::Mono)
+            .with_shared_model_retriever(model_bank)
+            .build()
+            .expect("Offline transcriber expected to build without issues.");
+        let run_transcription = Arc::new(AtomicBool::new(true));
+        let stop = Arc::clone(&run_transcription);
+        let callbacks = WhisperCallbacks {
+            progress: None::<Nop<i32>>,
+            new_segment: Some(RibbleWhisperCallback::new(move |_: String| {
+                stop.store(false, Ordering::Release);
+            })),
+        };
+        let cancelled = transcriber.process_segments_with_callbacks(run_transcription, callbacks);
+        assert!(
+            matches!(cancelled, Err(RibbleWhisperError::Cancelled)),
+            "Expected inference to be cancelled."
+        );
+        let partial = transcriber.take_partial_segments();
+        assert!(
+            !partial.is_empty(),
+            "The segments decoded before cancelling were not kept."
+        );
+        assert!(
+            transcriber.take_partial_segments().is_empty(),
+            "The partial segments should only be taken once."
+        );
+    }
+
+    #[test]
+    fn test_builder_error_taxonomy() {
+        let missing_audio = OfflineTranscriberBuilder::<Silero, DefaultModelBank>::new()
+            .with_configs(WhisperConfigs::default())
+            .with_channel_configurations(AudioChannelConfiguration::Mono)
+            .build();
+
+        let Err(err) = missing_audio else {
+            panic!("Offline transcriber built without audio.");
+        };
+        assert!(
+            matches!(
+                err,
+                RibbleWhisperError::MissingField {
+                    builder: "OfflineTranscriberBuilder",
+                    field: "audio"
+                }
+            ),
+            "Expected a missing audio field. Got: {err}"
+        );
+        assert_eq!(err.error_code(), "missing_field", "Incorrect error code.");
+        assert!(
+            !err.is_recoverable(),
+            "Configuration errors should not be recoverable."
+        );
+
+        let not_found = RibbleWhisperError::ModelNotFound { id: 0 };
+        assert_eq!(
+            not_found.error_code(),
+            "model_not_found",
+            "Incorrect error code."
+        );
+        assert!(
+            RibbleWhisperError::Cancelled.is_recoverable(),
+            "Cancellation should be recoverable."
+        );
+    }
 }
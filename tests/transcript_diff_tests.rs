@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod transcript_diff_tests {
+    use std::sync::Arc;
+
+    use ribble_whisper::postprocess::diff::{
+        MergePreference, TimedWord, TranscriptDiffer, WordEdit,
+    };
+    use ribble_whisper::transcriber::RibbleWhisperSegment;
+
+    fn segment(text: &str, start_time: i64, end_time: i64) -> RibbleWhisperSegment {
+        RibbleWhisperSegment {
+            text: Arc::from(text),
+            start_time,
+            end_time,
+            confidence: None,
+            word_confidences: None,
+            speaker: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_text() {
+        let diff = TranscriptDiffer::new().diff_text(
+            "The quick brown fox jumps over the lazy dog.",
+            "the quick brown box jumps over the dog",
+        );
+        assert_eq!(diff.matches(), 7);
+        assert_eq!(diff.substitutions(), 1);
+        assert_eq!(diff.deletions(), 1);
+        assert_eq!(diff.insertions(), 0);
+        assert_eq!(diff.reference_len(), 9);
+        assert_eq!(diff.hypothesis_len(), 8);
+        assert_eq!(
+            diff.to_string(),
+            "The quick brown fox->box jumps over the -lazy dog."
+        );
+
+        let diff = TranscriptDiffer::new()
+            .with_case_sensitive(true)
+            .with_punctuation_sensitive(true)
+            .diff_text("Hello, world", "hello world again");
+        assert_eq!(diff.substitutions(), 1);
+        assert_eq!(diff.insertions(), 1);
+        assert_eq!(
+            diff.changes().last(),
+            Some(&WordEdit::Insertion(TimedWord::new("again", None, None)))
+        );
+
+        assert!(
+            TranscriptDiffer::new()
+                .diff_text("Same words here.", "same words, here")
+                .is_identical()
+        );
+        assert!(TranscriptDiffer::new().diff_text("", "").edits().is_empty());
+    }
+
+    #[test]
+    fn test_diff_segments_keeps_timestamps() {
+        let realtime = [
+            segment("I scream for", 0, 100),
+            segment("ice cream", 150, 250),
+        ];
+        let offline = [segment(" ice scream for the ice cream", 0, 250)];
+        let diff = TranscriptDiffer::new().diff_segments(&realtime, &offline);
+
+        let changes: Vec<&WordEdit> = diff.changes().collect();
+        assert_eq!(changes.len(), 2, "{diff}");
+        assert!(matches!(changes[0], WordEdit::Substitution { .. }));
+        assert!(matches!(changes[1], WordEdit::Insertion(_)));
+
+        // The first word of the realtime segment starts at its segment's start.
+        assert_eq!(changes[0].start_timestamp(), Some(0));
+        // Insertions only have hypothesis timestamps.
+        let inserted = changes[1].hypothesis().unwrap();
+        assert!(changes[1].reference().is_none());
+        assert_eq!(changes[1].start_timestamp(), inserted.start_timestamp());
+        assert!(inserted.start_timestamp().unwrap() > 0);
+        assert!(inserted.end_timestamp().unwrap() <= 250);
+    }
+
+    #[test]
+    fn test_merge() {
+        let diff = TranscriptDiffer::new().diff_text("a b c d", "a x c d e");
+        let merged = |preference| {
+            diff.merge(preference)
+                .iter()
+                .map(TimedWord::text)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        assert_eq!(merged(MergePreference::Reference), "a b c d");
+        assert_eq!(merged(MergePreference::Hypothesis), "a x c d e");
+
+        let realtime = [segment("hello there", 0, 100)];
+        let offline = [segment("hello their friend", 0, 150)];
+        let merged = TranscriptDiffer::new()
+            .diff_segments(&realtime, &offline)
+            .merge(MergePreference::Hypothesis);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[1].text(), "their");
+        assert_eq!(merged[2].end_timestamp(), Some(150));
+    }
+}
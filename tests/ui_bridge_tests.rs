@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod ui_bridge_tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc::sync_channel;
+    use std::sync::Arc;
+
+    use ribble_whisper::transcriber::ui_bridge::UiBridge;
+    use ribble_whisper::transcriber::{TranscriptionSnapshot, WhisperControlPhrase, WhisperOutput};
+
+    fn snapshot(confirmed: &str, working: &[&str]) -> WhisperOutput {
+        let working: Vec<Arc<str>> = working.iter().map(|segment| Arc::from(*segment)).collect();
+        WhisperOutput::TranscriptionSnapshot(Arc::new(TranscriptionSnapshot::new(
+            Arc::from(confirmed),
+            Arc::from(working),
+        )))
+    }
+
+    #[test]
+    fn test_latest_state() {
+        let (sender, receiver) = sync_channel(8);
+        let notified = Arc::new(AtomicUsize::new(0));
+        let c_notified = Arc::clone(&notified);
+        let bridge = UiBridge::spawn_with_notify(receiver, move || {
+            c_notified.fetch_add(1, Ordering::Relaxed);
+        });
+        assert!(bridge.snapshot().confirmed().is_empty());
+        assert!(matches!(
+            *bridge.control_phrase(),
+            WhisperControlPhrase::Idle
+        ));
+
+        sender
+            .send(WhisperOutput::ControlPhrase(
+                WhisperControlPhrase::StartSpeaking,
+            ))
+            .unwrap();
+        sender.send(snapshot("Hello", &["there"])).unwrap();
+        sender
+            .send(snapshot("Hello there.", &["How", "are"]))
+            .unwrap();
+        drop(sender);
+
+        // Reads never wait on the background thread; poll until it has consumed everything.
+        let mut last_generation = 0;
+        while !bridge.is_finished() {
+            let generation = bridge.generation();
+            assert!(
+                generation >= last_generation,
+                "Generations should only increase."
+            );
+            last_generation = generation;
+            std::thread::yield_now();
+        }
+
+        let snapshot = bridge.snapshot();
+        assert_eq!(snapshot.confirmed(), "Hello there.");
+        assert_eq!(snapshot.string_segments().len(), 2);
+        assert!(matches!(
+            *bridge.control_phrase(),
+            WhisperControlPhrase::StartSpeaking
+        ));
+
+        let metrics = bridge.metrics();
+        assert_eq!(metrics.snapshots, 2);
+        assert_eq!(metrics.control_phrases, 1);
+        assert_eq!(metrics.confirmed_chars, "Hello there.".len());
+        assert_eq!(metrics.working_segments, 2);
+        assert!(metrics.last_output.is_some());
+        // One notification per output, plus one when the channel disconnects.
+        assert_eq!(notified.load(Ordering::Relaxed), 4);
+        assert_eq!(bridge.generation(), 4);
+        bridge.join();
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod upload_tests {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+
+    use futures_util::stream;
+    use ribble_whisper::transcriber::upload::{UploadTranscriber, is_audio_mime_type};
+    use ribble_whisper::utils::errors::RibbleWhisperError;
+    use ribble_whisper::whisper::configs::WhisperConfigs;
+    use ribble_whisper::whisper::model::{ModelId, ModelLocation, ModelRetriever};
+    use tokio::runtime::Runtime;
+
+    struct NoModels;
+
+    impl ModelRetriever for NoModels {
+        fn retrieve_model(&self, _model_id: ModelId) -> Option<ModelLocation> {
+            None
+        }
+    }
+
+    fn chunks(n_chunks: usize) -> Vec<Result<Vec<u8>, std::io::Error>> {
+        (0..n_chunks).map(|_| Ok(vec![0u8; 100])).collect()
+    }
+
+    #[test]
+    fn test_audio_mime_types() {
+        assert!(is_audio_mime_type("audio/wav"));
+        assert!(is_audio_mime_type("Audio/Ogg; codecs=opus"));
+        assert!(is_audio_mime_type("video/mp4"));
+        assert!(is_audio_mime_type("application/octet-stream"));
+        assert!(!is_audio_mime_type("text/plain"));
+        assert!(!is_audio_mime_type("application/json"));
+    }
+
+    #[test]
+    fn test_rejects_uploads_before_transcribing() {
+        let rt = Runtime::new().unwrap();
+        let transcriber =
+            UploadTranscriber::new(WhisperConfigs::default(), NoModels).with_max_upload_bytes(250);
+        let run_transcription = Arc::new(AtomicBool::new(true));
+
+        let result = rt.block_on(transcriber.transcribe_stream(
+            "text/plain",
+            stream::iter(chunks(1)),
+            Arc::clone(&run_transcription),
+        ));
+        assert!(matches!(
+            result,
+            Err(RibbleWhisperError::AudioFormat { .. })
+        ));
+
+        let result = rt.block_on(transcriber.transcribe_stream(
+            "audio/wav",
+            stream::iter(chunks(3)),
+            Arc::clone(&run_transcription),
+        ));
+        assert!(matches!(result, Err(RibbleWhisperError::ParameterError(_))));
+
+        let failing = vec![
+            Ok(vec![0u8; 10]),
+            Err(std::io::Error::other("disconnected")),
+        ];
+        let result = rt.block_on(transcriber.transcribe_stream(
+            "audio/wav",
+            stream::iter(failing),
+            run_transcription,
+        ));
+        assert!(matches!(result, Err(RibbleWhisperError::IOError(_))));
+    }
+
+    #[test]
+    fn test_undecodable_upload() {
+        let rt = Runtime::new().unwrap();
+        let transcriber = UploadTranscriber::new(WhisperConfigs::default(), NoModels);
+        let result = rt.block_on(transcriber.transcribe(
+            "audio/wav",
+            b"not audio".to_vec(),
+            Arc::new(AtomicBool::new(true)),
+        ));
+        assert!(result.is_err());
+    }
+}
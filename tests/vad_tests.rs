@@ -1,5 +1,6 @@
 #[cfg(test)]
 mod vad_tests {
+    use std::sync::atomic::AtomicBool;
     use std::sync::{Arc, LazyLock};
 
     use hound::SampleFormat;
@@ -14,6 +15,7 @@ mod vad_tests {
         VAD, WebRtc, WebRtcBuilder, WebRtcFilterAggressiveness, WebRtcFrameLengthMillis,
         WebRtcSampleRate,
     };
+    use ribble_whisper::utils::errors::RibbleWhisperError;
 
     // This audio file contains a speaker who methodically reads out a series of random sentences.
     // The voice clip is not super clear, nor loud, and there are significant gaps between phrases,
@@ -505,4 +507,43 @@ mod vad_tests {
             "Erroneously extracted voice frames from silence."
         );
     }
+
+    #[test]
+    fn test_vad_extraction_abort() {
+        let running = AtomicBool::new(true);
+        let cancelled = AtomicBool::new(false);
+
+        let mut silero = SileroBuilder::new()
+            .with_sample_rate(SileroSampleRate::R8kHz)
+            .with_detection_probability_threshold(OFFLINE_VOICE_PROBABILITY_THRESHOLD)
+            .with_voiced_proportion_threshold(DEFAULT_VOICE_PROPORTION_THRESHOLD)
+            .build()
+            .expect("Silero VAD expected to build without issues.");
+        let mut webrtc = WebRtcBuilder::new()
+            .with_sample_rate(WebRtcSampleRate::R8kHz)
+            .with_filter_aggressiveness(WebRtcFilterAggressiveness::LowBitrate)
+            .build_webrtc()
+            .expect("Webrtc expected to build without issues");
+        let mut earshot = WebRtcBuilder::new()
+            .with_sample_rate(WebRtcSampleRate::R8kHz)
+            .with_filter_aggressiveness(WebRtcFilterAggressiveness::LowBitrate)
+            .build_earshot()
+            .expect("Earshot expected to build without issues");
+        let vads: [&mut dyn VAD<i16>; 3] = [&mut silero, &mut webrtc, &mut earshot];
+
+        for vad in vads {
+            let expected = vad.extract_voiced_frames(&AUDIO_SAMPLE);
+            vad.reset_session();
+            let voiced = vad
+                .extract_voiced_frames_with_abort(&AUDIO_SAMPLE, &running)
+                .expect("Extraction should finish while running.");
+            assert_eq!(voiced, expected);
+
+            vad.reset_session();
+            assert!(matches!(
+                vad.extract_voiced_frames_with_abort(&AUDIO_SAMPLE, &cancelled),
+                Err(RibbleWhisperError::Cancelled)
+            ));
+        }
+    }
 }
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod wake_phrase_tests {
+    use ribble_whisper::transcriber::wake_phrase::{DEFAULT_WAKE_WINDOW_MS, WakePhrase};
+    use ribble_whisper::transcriber::{SessionStartReason, WhisperControlPhrase};
+
+    #[test]
+    fn test_detect_whole_words() {
+        let wake_phrase = WakePhrase::new(&["Hey Ribble", "take a note"]).unwrap();
+        assert_eq!(wake_phrase.phrases(), ["hey ribble", "take a note"]);
+        assert_eq!(wake_phrase.window_ms(), DEFAULT_WAKE_WINDOW_MS);
+
+        assert_eq!(wake_phrase.detect(" Hey, Ribble!"), Some("hey ribble"));
+        assert_eq!(
+            wake_phrase.detect("Okay. Take a note: buy milk."),
+            Some("take a note")
+        );
+        // Partial words don't count.
+        assert_eq!(wake_phrase.detect("Hey Ribbles"), None);
+        assert_eq!(wake_phrase.detect("they ribble"), None);
+        assert_eq!(wake_phrase.detect(""), None);
+    }
+
+    #[test]
+    fn test_invalid_phrases() {
+        let empty: [&str; 0] = [];
+        assert!(WakePhrase::new(&empty).is_err());
+        assert!(WakePhrase::new(&["", " ?! "]).is_err());
+    }
+
+    #[test]
+    fn test_session_started() {
+        let started = WhisperControlPhrase::SessionStarted(SessionStartReason::WakePhrase(
+            "hey ribble".to_string(),
+        ));
+        assert_eq!(started.to_string(), "[SESSION STARTED]");
+        assert_eq!(SessionStartReason::default(), SessionStartReason::Manual);
+    }
+}
@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod window_hook_tests {
+    use std::sync::{Arc, Mutex};
+
+    use ribble_whisper::transcriber::window_hooks::{LevelMeter, WindowHook, WindowLevels};
+    use ribble_whisper::utils::callback::RibbleWhisperCallback;
+
+    #[test]
+    fn test_measure_levels() {
+        let levels = WindowLevels::measure(&[0.5, -0.5, 0.5, -0.5]);
+        assert_eq!(levels.peak, 0.5);
+        assert!((levels.rms - 0.5).abs() < 1e-6);
+        assert!((levels.peak_dbfs() + 6.0206).abs() < 1e-3);
+
+        let silence = WindowLevels::measure(&[]);
+        assert_eq!(silence.peak, 0.0);
+        assert_eq!(silence.rms, 0.0);
+        assert_eq!(silence.rms_dbfs(), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_level_meter_leaves_audio_unchanged() {
+        let measured = Arc::new(Mutex::new(vec![]));
+        let sink = Arc::clone(&measured);
+        let mut meter = LevelMeter::new(RibbleWhisperCallback::new(move |levels| {
+            sink.lock().unwrap().push(levels)
+        }));
+
+        let mut samples = vec![0.25f32, -1.0, 0.0, 0.75];
+        let expected = samples.clone();
+        meter.process(&mut samples);
+        meter.process(&mut samples[..1]);
+        assert_eq!(samples, expected);
+
+        let measured = measured.lock().unwrap();
+        assert_eq!(measured.len(), 2);
+        assert_eq!(measured[0].peak, 1.0);
+        assert_eq!(measured[1], WindowLevels::measure(&[0.25]));
+    }
+}
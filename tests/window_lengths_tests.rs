@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod window_lengths_tests {
+    use ribble_whisper::whisper::configs::{
+        MAX_AUDIO_SAMPLE_MS, RealtimeBufferingStrategy, WhisperRealtimeConfigs, WindowLengths,
+    };
+
+    #[test]
+    fn test_from_latency_target() {
+        let lengths = WindowLengths::from_latency_target(500);
+        assert_eq!(
+            lengths.buffering_strategy(),
+            RealtimeBufferingStrategy::Continuous
+        );
+        assert_eq!(lengths.vad_sample_len(), 125);
+        assert_eq!(lengths.audio_sample_len(), 4000);
+        assert!(lengths.validate().is_ok());
+
+        let lengths = WindowLengths::from_latency_target(2000);
+        assert_eq!(
+            lengths.buffering_strategy(),
+            RealtimeBufferingStrategy::Buffered { buffer_ms: 2000 }
+        );
+        assert_eq!(lengths.vad_sample_len(), 500);
+        assert_eq!(lengths.audio_sample_len(), 16000);
+        assert!(lengths.validate().is_ok());
+
+        // Every target derives a valid set of lengths.
+        for latency_ms in [0, 1, 999, 1000, 1001, 4000, 15000, 30000, 60000, usize::MAX] {
+            let lengths = WindowLengths::from_latency_target(latency_ms);
+            assert!(
+                lengths.validate().is_ok(),
+                "Latency target {latency_ms} ms derived invalid lengths: {lengths:?}"
+            );
+            assert!(lengths.audio_sample_len() <= MAX_AUDIO_SAMPLE_MS);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(
+            WhisperRealtimeConfigs::default()
+                .window_lengths()
+                .validate()
+                .is_ok()
+        );
+
+        let lengths = WindowLengths::from_latency_target(1000);
+        assert!(lengths.with_vad_sample_len(0).validate().is_err());
+        assert!(
+            lengths
+                .with_vad_sample_len(lengths.audio_sample_len() + 1)
+                .validate()
+                .is_err()
+        );
+        // Inference would never run.
+        assert!(
+            lengths
+                .with_buffering_strategy(RealtimeBufferingStrategy::Buffered { buffer_ms: 12000 })
+                .with_audio_sample_len(10000)
+                .validate()
+                .is_err()
+        );
+        assert!(
+            lengths
+                .with_audio_sample_len(MAX_AUDIO_SAMPLE_MS + 1)
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_with_window_lengths() {
+        let lengths = WindowLengths::from_latency_target(3000).with_vad_sample_len(200);
+        let configs = WhisperRealtimeConfigs::default().with_window_lengths(lengths);
+        assert_eq!(configs.window_lengths(), lengths);
+        assert_eq!(configs.vad_sample_len(), 200);
+        assert_eq!(configs.audio_sample_len_ms(), lengths.audio_sample_len());
+        assert_eq!(configs.min_sample_len(), 3 * 16000);
+    }
+}